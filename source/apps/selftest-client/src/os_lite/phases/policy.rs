@@ -30,14 +30,16 @@ pub(crate) fn run(_ctx: &mut PhaseCtx) -> core::result::Result<(), ()> {
     let policyd = route_with_retry("policyd").map_err(|_| ())?;
 
     // Policyd-gated routing proof: bundlemgrd asking for execd must be DENIED.
-    let (st, route_st) = services::bundlemgrd::bundlemgrd_v1_route_status(&bundlemgrd, "execd")?;
-    if st == 0 && route_st == nexus_abi::routing::STATUS_DENIED {
+    let (st, decision) = services::bundlemgrd::bundlemgrd_v1_route_status(&bundlemgrd, "execd")?;
+    if st == nexus_abi::bundlemgrd::STATUS_OK
+        && decision == nexus_abi::bundlemgrd::ROUTE_DECISION_DENIED
+    {
         emit_line(crate::markers::M_SELFTEST_BUNDLEMGRD_ROUTE_EXECD_DENIED_OK);
     } else {
         emit_bytes(crate::markers::M_SELFTEST_BUNDLEMGRD_ROUTE_EXECD_DENIED_ST_0X.as_bytes());
         emit_hex_u64(st as u64);
-        emit_bytes(b" route=0x");
-        emit_hex_u64(route_st as u64);
+        emit_bytes(b" decision=0x");
+        emit_hex_u64(decision as u64);
         emit_byte(b'\n');
         emit_line(crate::markers::M_SELFTEST_BUNDLEMGRD_ROUTE_EXECD_DENIED_FAIL);
     }
@@ -49,6 +49,7 @@ pub(crate) fn bundlemgrd_v1_list(client: &KernelClient) -> core::result::Result<
                                 nexus_abi::IpcError::PermissionDenied => emit_bytes(b"denied"),
                                 nexus_abi::IpcError::TimedOut => emit_bytes(b"timedout"),
                                 nexus_abi::IpcError::NoSpace => emit_bytes(b"nospace"),
+                                nexus_abi::IpcError::Truncated => emit_bytes(b"truncated"),
                                 nexus_abi::IpcError::Unsupported => emit_bytes(b"unsupported"),
                             }
                         }
@@ -111,7 +112,7 @@ pub(crate) fn bundlemgrd_v1_fetch_image_slot(
         .map_err(|_| ())?;
     let rsp = nexus_ipc::budget::recv_budgeted(&clock, client, core::time::Duration::from_secs(1))
         .map_err(|_| ())?;
-    let (st, img) = nexus_abi::bundlemgrd::decode_fetch_image_rsp(&rsp).ok_or(())?;
+    let (st, img) = nexus_abi::bundlemgrd::decode_fetch_image_rsp_checked(&rsp).ok_or(())?;
     if st != nexus_abi::bundlemgrd::STATUS_OK {
         return Err(());
     }
@@ -164,32 +165,18 @@ pub(crate) fn bundlemgrd_v1_route_status(
     client: &KernelClient,
     target: &str,
 ) -> core::result::Result<(u8, u8), ()> {
-    // Bundlemgrd v1 route-status:
-    // Request: [B, N, ver, OP_ROUTE_STATUS, name_len:u8, name...]
-    // Response: [B, N, ver, OP_ROUTE_STATUS|0x80, status:u8, route_status:u8, _, _]
-    const MAGIC0: u8 = b'B';
-    const MAGIC1: u8 = b'N';
-    const VERSION: u8 = 1;
-    const OP_ROUTE_STATUS: u8 = 2;
-
-    let name = target.as_bytes();
-    if name.is_empty() || name.len() > 48 {
-        return Err(());
-    }
-    let mut req = Vec::with_capacity(5 + name.len());
-    req.push(MAGIC0);
-    req.push(MAGIC1);
-    req.push(VERSION);
-    req.push(OP_ROUTE_STATUS);
-    req.push(name.len() as u8);
-    req.extend_from_slice(name);
+    // Bundlemgrd v1 route-status → (status, policy_decision); see
+    // `nexus_abi::bundlemgrd::decode_route_status_rsp`.
+    let mut req = [0u8; 5 + nexus_abi::routing::MAX_SERVICE_NAME_LEN];
+    let len = nexus_abi::bundlemgrd::encode_route_status(target.as_bytes(), &mut req).ok_or(())?;
+    let req = &req[..len];
     let (send_slot, recv_slot) = client.slots();
     let hdr = MsgHeader::new(0, 0, 0, 0, req.len() as u32);
     let start = nexus_abi::nsec().map_err(|_| ())?;
     let deadline = start.saturating_add(2_000_000_000); // 2s
     let mut i: usize = 0;
     loop {
-        match nexus_abi::ipc_send_v1(send_slot, &hdr, &req, nexus_abi::IPC_SYS_NONBLOCK, 0) {
+        match nexus_abi::ipc_send_v1(send_slot, &hdr, req, nexus_abi::IPC_SYS_NONBLOCK, 0) {
             Ok(_) => break,
             Err(nexus_abi::IpcError::QueueFull) => {
                 if (i & 0x7f) == 0 {
@@ -223,13 +210,9 @@ pub(crate) fn bundlemgrd_v1_route_status(
         ) {
             Ok(n) => {
                 let n = core::cmp::min(n as usize, buf.len());
-                if n != 8 || buf[0] != MAGIC0 || buf[1] != MAGIC1 || buf[2] != VERSION {
-                    continue;
-                }
-                if buf[3] != (OP_ROUTE_STATUS | 0x80) {
-                    continue;
+                if let Some(decoded) = nexus_abi::bundlemgrd::decode_route_status_rsp(&buf[..n]) {
+                    return Ok(decoded);
                 }
-                return Ok((buf[4], buf[5]));
             }
             Err(nexus_abi::IpcError::QueueEmpty) => {
                 let _ = yield_();
@@ -0,0 +1,229 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: Seeded ChaCha20 DRBG used as a loud fallback when no virtio-rng device exists
+//! OWNERS: @runtime
+//! STATUS: Experimental (feature `drbg-fallback`)
+//! TEST_COVERAGE: 3 unit tests (ChaCha20 vector, deterministic output, NotFound-only fallback)
+//!
+//! The fallback is NOT a hardware entropy source: its output is only as strong as
+//! the seed handed in. It engages solely when the device read fails with
+//! [`RngError::NotFound`], refuses seeds shorter than [`MIN_FALLBACK_SEED_BYTES`],
+//! and announces itself on every use. Callers must pass fresh seed material per
+//! call (the same seed yields the same bytes) or keep a [`ChaChaDrbg`] instead.
+
+#[cfg(all(feature = "os-lite", not(feature = "std")))]
+use alloc::vec;
+#[cfg(all(feature = "os-lite", not(feature = "std")))]
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::vec::Vec;
+
+use crate::{RngError, MAX_ENTROPY_BYTES};
+
+/// Shortest seed the fallback accepts.
+pub const MIN_FALLBACK_SEED_BYTES: usize = 16;
+
+/// Marker emitted (best-effort) whenever fallback entropy is handed out.
+pub const FALLBACK_MARKER: &str = "rng-virtio: WARNING no virtio-rng device, using seeded DRBG";
+
+const BLOCK_LEN: usize = 64;
+const KEY_LEN: usize = 32;
+
+/// ChaCha20 keystream block for `key`, 64-bit block `counter` and 64-bit `nonce`.
+fn chacha20_block(key: &[u8; KEY_LEN], counter: u64, nonce: u64) -> [u8; BLOCK_LEN] {
+    let mut input = [0u32; 16];
+    input[..4].copy_from_slice(&[0x6170_7865, 0x3320_646e, 0x7962_2d32, 0x6b20_6574]);
+    for (word, bytes) in input[4..12].iter_mut().zip(key.chunks_exact(4)) {
+        *word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    }
+    input[12] = counter as u32;
+    input[13] = (counter >> 32) as u32;
+    input[14] = nonce as u32;
+    input[15] = (nonce >> 32) as u32;
+
+    let mut state = input;
+    for _ in 0..10 {
+        for [a, b, c, d] in [
+            [0, 4, 8, 12],
+            [1, 5, 9, 13],
+            [2, 6, 10, 14],
+            [3, 7, 11, 15],
+            [0, 5, 10, 15],
+            [1, 6, 11, 12],
+            [2, 7, 8, 13],
+            [3, 4, 9, 14],
+        ] {
+            state[a] = state[a].wrapping_add(state[b]);
+            state[d] = (state[d] ^ state[a]).rotate_left(16);
+            state[c] = state[c].wrapping_add(state[d]);
+            state[b] = (state[b] ^ state[c]).rotate_left(12);
+            state[a] = state[a].wrapping_add(state[b]);
+            state[d] = (state[d] ^ state[a]).rotate_left(8);
+            state[c] = state[c].wrapping_add(state[d]);
+            state[b] = (state[b] ^ state[c]).rotate_left(7);
+        }
+    }
+
+    let mut out = [0u8; BLOCK_LEN];
+    for ((chunk, word), init) in out.chunks_exact_mut(4).zip(state).zip(input) {
+        chunk.copy_from_slice(&word.wrapping_add(init).to_le_bytes());
+    }
+    out
+}
+
+fn first_key(block: &[u8; BLOCK_LEN]) -> [u8; KEY_LEN] {
+    let mut key = [0u8; KEY_LEN];
+    key.copy_from_slice(&block[..KEY_LEN]);
+    key
+}
+
+/// Deterministic ChaCha20-based DRBG (no external dependencies).
+///
+/// The key is ratcheted after every [`ChaChaDrbg::fill`], so earlier output cannot be
+/// recomputed from a later state.
+pub struct ChaChaDrbg {
+    key: [u8; KEY_LEN],
+    counter: u64,
+}
+
+impl ChaChaDrbg {
+    /// Seeds a DRBG from `seed` of any length (the length itself is mixed in).
+    pub fn from_seed(seed: &[u8]) -> Self {
+        let mut key = [0u8; KEY_LEN];
+        for (index, chunk) in seed.chunks(KEY_LEN).enumerate() {
+            for (k, s) in key.iter_mut().zip(chunk) {
+                *k ^= s;
+            }
+            key = first_key(&chacha20_block(&key, index as u64, seed.len() as u64));
+        }
+        if seed.is_empty() {
+            key = first_key(&chacha20_block(&key, 0, 0));
+        }
+        Self { key, counter: 0 }
+    }
+
+    /// Fills `out` with DRBG output, then ratchets the key.
+    pub fn fill(&mut self, out: &mut [u8]) {
+        for chunk in out.chunks_mut(BLOCK_LEN) {
+            let block = chacha20_block(&self.key, self.counter, 0);
+            chunk.copy_from_slice(&block[..chunk.len()]);
+            self.counter = self.counter.wrapping_add(1);
+        }
+        self.key = first_key(&chacha20_block(&self.key, self.counter, 0));
+        self.counter = self.counter.wrapping_add(1);
+    }
+}
+
+/// Returns `primary` unless it failed with [`RngError::NotFound`], in which case `n`
+/// bytes are drawn from a [`ChaChaDrbg`] seeded with `seed` and [`FALLBACK_MARKER`] is
+/// logged.
+///
+/// Every other error passes through unchanged, as does `NotFound` when `seed` is
+/// shorter than [`MIN_FALLBACK_SEED_BYTES`]. `n` is bounded like a device read.
+pub fn entropy_or_fallback(
+    primary: Result<Vec<u8>, RngError>,
+    seed: &[u8],
+    n: usize,
+) -> Result<Vec<u8>, RngError> {
+    match primary {
+        Err(RngError::NotFound) if seed.len() >= MIN_FALLBACK_SEED_BYTES => {}
+        other => return other,
+    }
+    if n == 0 {
+        return Err(RngError::ZeroLength);
+    }
+    if n > MAX_ENTROPY_BYTES {
+        return Err(RngError::Oversized);
+    }
+    announce_fallback();
+    let mut out = vec![0u8; n];
+    ChaChaDrbg::from_seed(seed).fill(&mut out);
+    Ok(out)
+}
+
+/// Reads entropy from the virtio-rng device, degrading to the seeded DRBG only when
+/// no device is present (see [`entropy_or_fallback`]).
+///
+/// SECURITY: Returned bytes must not be logged by callers.
+#[cfg(all(feature = "os-lite", not(feature = "std")))]
+pub fn read_entropy_with_fallback(
+    mmio_cap_slot: u32,
+    mmio_base_va: usize,
+    max_slots: usize,
+    seed: &[u8],
+    n: usize,
+) -> Result<Vec<u8>, RngError> {
+    let primary = crate::read_entropy_via_virtio_mmio(mmio_cap_slot, mmio_base_va, max_slots, n);
+    entropy_or_fallback(primary, seed, n)
+}
+
+fn announce_fallback() {
+    #[cfg(all(feature = "os-lite", not(feature = "std")))]
+    let _ = nexus_abi::debug_println(FALLBACK_MARKER);
+    #[cfg(feature = "std")]
+    std::eprintln!("{FALLBACK_MARKER}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEED: &[u8] = b"fixed-test-seed-0123456789";
+
+    #[test]
+    fn chacha20_matches_reference_keystream() {
+        // Zero key, zero nonce, block 0 (RFC 8439, appendix A.1, test vector #1).
+        let block = chacha20_block(&[0; KEY_LEN], 0, 0);
+        assert_eq!(
+            block[..16],
+            [
+                0x76, 0xb8, 0xe0, 0xad, 0xa0, 0xf1, 0x3d, 0x90, 0x40, 0x5d, 0x6a, 0xe5, 0x53, 0x86,
+                0xbd, 0x28
+            ]
+        );
+        assert_eq!(
+            block[48..],
+            [
+                0x6a, 0x43, 0xb8, 0xf4, 0x15, 0x18, 0xa1, 0x1c, 0xc3, 0x87, 0xb6, 0x69, 0xb2, 0xee,
+                0x65, 0x86
+            ]
+        );
+    }
+
+    #[test]
+    fn drbg_output_is_deterministic_per_seed() {
+        let (mut a, mut b) = (ChaChaDrbg::from_seed(SEED), ChaChaDrbg::from_seed(SEED));
+        let (mut out_a, mut out_b) = ([0u8; 100], [0u8; 100]);
+        a.fill(&mut out_a);
+        b.fill(&mut out_b);
+        assert_eq!(out_a, out_b);
+
+        // The ratchet moves the stream on; other seeds (even a trailing zero) diverge.
+        let first = out_a;
+        a.fill(&mut out_a);
+        assert_ne!(out_a, first);
+        let mut other = [0u8; 100];
+        ChaChaDrbg::from_seed(&[SEED, &[0]].concat()).fill(&mut other);
+        assert_ne!(other, first);
+
+        assert_eq!(entropy_or_fallback(Err(RngError::NotFound), SEED, 100).unwrap(), first);
+    }
+
+    #[test]
+    fn test_reject_fallback_unless_device_not_found() {
+        for err in [RngError::Timeout, RngError::MapFailed, RngError::InvalidDevice] {
+            assert_eq!(entropy_or_fallback(Err(err), SEED, 32), Err(err));
+        }
+        assert_eq!(entropy_or_fallback(Ok(vec![7; 4]), SEED, 4), Ok(vec![7; 4]));
+        let short = &SEED[..MIN_FALLBACK_SEED_BYTES - 1];
+        assert_eq!(
+            entropy_or_fallback(Err(RngError::NotFound), short, 32),
+            Err(RngError::NotFound)
+        );
+        assert_eq!(
+            entropy_or_fallback(Err(RngError::NotFound), SEED, MAX_ENTROPY_BYTES + 1),
+            Err(RngError::Oversized)
+        );
+    }
+}
@@ -0,0 +1,312 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: OS-lite virtio-mmio + virtqueue entropy path (best-effort bring-up)
+//! OWNERS: @runtime
+//! STATUS: Functional
+//! TEST_COVERAGE: QEMU rngd selftests (no host tests: raw MMIO)
+
+use alloc::vec::Vec;
+
+use nexus_abi::{cap_query, mmio_map, vmo_create, vmo_map_page, AbiError, CapQuery};
+
+use crate::{RngError, MAX_ENTROPY_BYTES, VIRTIO_DEVICE_ID_RNG, VIRTIO_MAGIC};
+
+/// VirtIO MMIO version constant for modern virtio-mmio (v2).
+const VIRTIO_MMIO_VERSION_MODERN: u32 = 2;
+
+// VirtIO MMIO register offsets (bytes) for virtio-mmio devices.
+mod mmio {
+    pub const REG_MAGIC: usize = 0x000;
+    // Present in the virtio-mmio register map; unused by the current minimal driver.
+    #[allow(dead_code)]
+    pub const REG_VERSION: usize = 0x004;
+    pub const REG_DEVICE_ID: usize = 0x008;
+    // Present in the virtio-mmio register map; unused by the current minimal driver.
+    #[allow(dead_code)]
+    pub const REG_DEVICE_FEATURES: usize = 0x010;
+    // Present in the virtio-mmio register map; unused by the current minimal driver.
+    #[allow(dead_code)]
+    pub const REG_DEVICE_FEATURES_SEL: usize = 0x014;
+    pub const REG_DRIVER_FEATURES: usize = 0x020;
+    pub const REG_DRIVER_FEATURES_SEL: usize = 0x024;
+    // Legacy-only registers (present in the virtio-mmio map, unused in modern mode).
+    #[allow(dead_code)]
+    pub const REG_GUEST_PAGE_SIZE: usize = 0x028; // legacy only
+    pub const REG_QUEUE_SEL: usize = 0x030;
+    pub const REG_QUEUE_NUM_MAX: usize = 0x034;
+    pub const REG_QUEUE_NUM: usize = 0x038;
+    #[allow(dead_code)]
+    pub const REG_QUEUE_ALIGN: usize = 0x03c; // legacy only
+    #[allow(dead_code)]
+    pub const REG_QUEUE_PFN: usize = 0x040; // legacy only
+    pub const REG_QUEUE_NOTIFY: usize = 0x050;
+    pub const REG_STATUS: usize = 0x070;
+
+    pub const REG_QUEUE_DESC_LOW: usize = 0x080;
+    pub const REG_QUEUE_DESC_HIGH: usize = 0x084;
+    pub const REG_QUEUE_DRIVER_LOW: usize = 0x090;
+    pub const REG_QUEUE_DRIVER_HIGH: usize = 0x094;
+    pub const REG_QUEUE_DEVICE_LOW: usize = 0x0a0;
+    pub const REG_QUEUE_DEVICE_HIGH: usize = 0x0a4;
+    pub const REG_QUEUE_READY: usize = 0x044;
+
+    // Status bits
+    pub const STATUS_ACKNOWLEDGE: u32 = 1;
+    pub const STATUS_DRIVER: u32 = 2;
+    pub const STATUS_DRIVER_OK: u32 = 4;
+    pub const STATUS_FEATURES_OK: u32 = 8;
+    pub const STATUS_FAILED: u32 = 128;
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct VqDesc {
+    addr: u64,
+    len: u32,
+    flags: u16,
+    next: u16,
+}
+
+#[repr(C)]
+struct VqAvail<const N: usize> {
+    flags: u16,
+    idx: u16,
+    ring: [u16; N],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct VqUsedElem {
+    id: u32,
+    len: u32,
+}
+
+#[repr(C)]
+struct VqUsed<const N: usize> {
+    flags: u16,
+    idx: u16,
+    ring: [VqUsedElem; N],
+}
+
+const VIRTQ_DESC_F_WRITE: u16 = 2;
+
+fn align4(x: usize) -> usize {
+    (x + 3) & !3usize
+}
+
+/// Reads `n` bytes of entropy from the virtio-rng device exposed in the virtio-mmio window.
+///
+/// This is the OS path used by `rngd` for real entropy in QEMU.
+///
+/// - Maps virtio-mmio window via `mmio_map`
+/// - Locates device_id == 4 (rng)
+/// - Sets up a single legacy virtqueue with one buffer descriptor
+/// - Polls used.idx with a bounded deadline
+///
+/// SECURITY: Returned bytes must not be logged by callers.
+pub fn read_entropy_via_virtio_mmio(
+    mmio_cap_slot: u32,
+    mmio_base_va: usize,
+    max_slots: usize,
+    n: usize,
+) -> Result<Vec<u8>, RngError> {
+    if n == 0 {
+        return Err(RngError::ZeroLength);
+    }
+    if n > MAX_ENTROPY_BYTES {
+        return Err(RngError::Oversized);
+    }
+
+    const SLOT_STRIDE: usize = 0x1000;
+
+    // Map slot 0 first.
+    mmio_map(mmio_cap_slot, mmio_base_va, 0)
+        .or_else(|e| if e == AbiError::InvalidArgument { Ok(()) } else { Err(e) })
+        .map_err(|_| RngError::MapFailed)?;
+
+    // Find virtio-rng slot.
+    let mut found: Option<usize> = None;
+    for slot in 0..max_slots {
+        let off = slot * SLOT_STRIDE;
+        let va = mmio_base_va + off;
+        if slot != 0 {
+            mmio_map(mmio_cap_slot, va, off)
+                .or_else(|e| if e == AbiError::InvalidArgument { Ok(()) } else { Err(e) })
+                .map_err(|_| RngError::MapFailed)?;
+        }
+        let magic = unsafe { core::ptr::read_volatile((va + mmio::REG_MAGIC) as *const u32) };
+        if magic != VIRTIO_MAGIC {
+            continue;
+        }
+        let device_id =
+            unsafe { core::ptr::read_volatile((va + mmio::REG_DEVICE_ID) as *const u32) };
+        if device_id == VIRTIO_DEVICE_ID_RNG {
+            found = Some(slot);
+            break;
+        }
+    }
+    let Some(slot) = found else {
+        return Err(RngError::NotFound);
+    };
+    let dev_va = mmio_base_va + slot * SLOT_STRIDE;
+
+    let version = unsafe { core::ptr::read_volatile((dev_va + mmio::REG_VERSION) as *const u32) };
+    // Enforce modern virtio-mmio (QEMU: `-global virtio-mmio.force-legacy=off`).
+    if version != VIRTIO_MMIO_VERSION_MODERN {
+        return Err(RngError::NotReady);
+    }
+
+    // Minimal feature negotiation (accept none).
+    unsafe {
+        core::ptr::write_volatile((dev_va + mmio::REG_STATUS) as *mut u32, 0);
+        core::ptr::write_volatile(
+            (dev_va + mmio::REG_STATUS) as *mut u32,
+            mmio::STATUS_ACKNOWLEDGE | mmio::STATUS_DRIVER,
+        );
+        // Driver features = 0 (accept none).
+        core::ptr::write_volatile((dev_va + mmio::REG_DRIVER_FEATURES_SEL) as *mut u32, 0);
+        core::ptr::write_volatile((dev_va + mmio::REG_DRIVER_FEATURES) as *mut u32, 0);
+        core::ptr::write_volatile((dev_va + mmio::REG_DRIVER_FEATURES_SEL) as *mut u32, 1);
+        core::ptr::write_volatile((dev_va + mmio::REG_DRIVER_FEATURES) as *mut u32, 0);
+        let st = core::ptr::read_volatile((dev_va + mmio::REG_STATUS) as *const u32);
+        core::ptr::write_volatile(
+            (dev_va + mmio::REG_STATUS) as *mut u32,
+            st | mmio::STATUS_FEATURES_OK,
+        );
+        let st2 = core::ptr::read_volatile((dev_va + mmio::REG_STATUS) as *const u32);
+        if (st2 & mmio::STATUS_FEATURES_OK) == 0 {
+            core::ptr::write_volatile(
+                (dev_va + mmio::REG_STATUS) as *mut u32,
+                st2 | mmio::STATUS_FAILED,
+            );
+            return Err(RngError::NotReady);
+        }
+    }
+
+    // Allocate queue memory (1 page) + buffer memory (1 page) once and reuse.
+    const Q_VA: usize = 0x2004_0000;
+    const BUF_VA: usize = 0x2006_0000;
+    const N: usize = 1;
+    static mut QUEUE_INIT: bool = false;
+    static mut Q_VMO: u32 = 0;
+    static mut BUF_VMO: u32 = 0;
+    static mut DESC_PA: u64 = 0;
+    static mut BUF_PA: u64 = 0;
+    let (_q_vmo, _buf_vmo, desc_pa, buf_pa) = unsafe {
+        if !QUEUE_INIT {
+            let q_vmo = vmo_create(4096).map_err(|_| RngError::MapFailed)?;
+            let buf_vmo = vmo_create(4096).map_err(|_| RngError::MapFailed)?;
+            let flags = nexus_abi::page_flags::VALID
+                | nexus_abi::page_flags::USER
+                | nexus_abi::page_flags::READ
+                | nexus_abi::page_flags::WRITE;
+            vmo_map_page(q_vmo, Q_VA, 0, flags).map_err(|_| RngError::MapFailed)?;
+            vmo_map_page(buf_vmo, BUF_VA, 0, flags).map_err(|_| RngError::MapFailed)?;
+            let mut q_info = CapQuery { kind_tag: 0, reserved: 0, base: 0, len: 0 };
+            cap_query(q_vmo, &mut q_info).map_err(|_| RngError::MapFailed)?;
+            let mut b_info = CapQuery { kind_tag: 0, reserved: 0, base: 0, len: 0 };
+            cap_query(buf_vmo, &mut b_info).map_err(|_| RngError::MapFailed)?;
+            Q_VMO = q_vmo;
+            BUF_VMO = buf_vmo;
+            DESC_PA = q_info.base;
+            BUF_PA = b_info.base;
+            QUEUE_INIT = true;
+        }
+        (Q_VMO, BUF_VMO, DESC_PA, BUF_PA)
+    };
+
+    // Zero queue page.
+    unsafe { core::ptr::write_bytes(Q_VA as *mut u8, 0, 4096) };
+
+    // Layout: desc then avail then used (legacy align=4) in same page.
+    let desc_va = Q_VA;
+    let avail_va = desc_va + core::mem::size_of::<VqDesc>() * N;
+    let used_va =
+        desc_va + align4(core::mem::size_of::<VqDesc>() * N + core::mem::size_of::<VqAvail<N>>());
+    let avail_pa = desc_pa + (avail_va - desc_va) as u64;
+    let used_pa = desc_pa + (used_va - desc_va) as u64;
+
+    // Program queue 0.
+    unsafe {
+        core::ptr::write_volatile((dev_va + mmio::REG_QUEUE_SEL) as *mut u32, 0);
+        let max = core::ptr::read_volatile((dev_va + mmio::REG_QUEUE_NUM_MAX) as *const u32);
+        if max == 0 || max < (N as u32) {
+            return Err(RngError::NotReady);
+        }
+        core::ptr::write_volatile((dev_va + mmio::REG_QUEUE_NUM) as *mut u32, N as u32);
+        core::ptr::write_volatile((dev_va + mmio::REG_QUEUE_DESC_LOW) as *mut u32, desc_pa as u32);
+        core::ptr::write_volatile(
+            (dev_va + mmio::REG_QUEUE_DESC_HIGH) as *mut u32,
+            (desc_pa >> 32) as u32,
+        );
+        core::ptr::write_volatile(
+            (dev_va + mmio::REG_QUEUE_DRIVER_LOW) as *mut u32,
+            avail_pa as u32,
+        );
+        core::ptr::write_volatile(
+            (dev_va + mmio::REG_QUEUE_DRIVER_HIGH) as *mut u32,
+            (avail_pa >> 32) as u32,
+        );
+        core::ptr::write_volatile(
+            (dev_va + mmio::REG_QUEUE_DEVICE_LOW) as *mut u32,
+            used_pa as u32,
+        );
+        core::ptr::write_volatile(
+            (dev_va + mmio::REG_QUEUE_DEVICE_HIGH) as *mut u32,
+            (used_pa >> 32) as u32,
+        );
+        core::ptr::write_volatile((dev_va + mmio::REG_QUEUE_READY) as *mut u32, 1);
+    }
+
+    // Prepare one writable descriptor pointing at the buffer page.
+    unsafe {
+        let d = desc_va as *mut VqDesc;
+        core::ptr::write_volatile(&mut (*d).addr, buf_pa);
+        core::ptr::write_volatile(&mut (*d).len, MAX_ENTROPY_BYTES as u32);
+        core::ptr::write_volatile(&mut (*d).flags, VIRTQ_DESC_F_WRITE);
+        core::ptr::write_volatile(&mut (*d).next, 0);
+
+        let avail = avail_va as *mut VqAvail<N>;
+        core::ptr::write_volatile(&mut (*avail).flags, 0);
+        core::ptr::write_volatile(&mut (*avail).ring[0], 0);
+        core::ptr::write_volatile(&mut (*avail).idx, 1);
+        core::sync::atomic::fence(core::sync::atomic::Ordering::SeqCst);
+    }
+
+    // Mark driver OK before notify.
+    unsafe {
+        let st = core::ptr::read_volatile((dev_va + mmio::REG_STATUS) as *const u32);
+        core::ptr::write_volatile(
+            (dev_va + mmio::REG_STATUS) as *mut u32,
+            st | mmio::STATUS_DRIVER_OK,
+        );
+    }
+
+    unsafe {
+        core::ptr::write_volatile((dev_va + mmio::REG_QUEUE_NOTIFY) as *mut u32, 0);
+    }
+
+    // Poll for completion (bounded).
+    let start = nexus_abi::nsec().map_err(|_| RngError::Timeout)?;
+    let deadline = start.saturating_add(80_000_000); // 80ms
+    let mut spins: u32 = 0;
+    const MAX_SPINS: u32 = 200_000;
+    loop {
+        let now = nexus_abi::nsec().map_err(|_| RngError::Timeout)?;
+        if now >= deadline || spins >= MAX_SPINS {
+            return Err(RngError::Timeout);
+        }
+        spins = spins.wrapping_add(1);
+        let used = unsafe { &*(used_va as *const VqUsed<N>) };
+        let used_idx = unsafe { core::ptr::read_volatile(&used.idx) };
+        if used_idx == 0 {
+            continue;
+        }
+        // Read bytes from buffer; do NOT log.
+        let slice = unsafe { core::slice::from_raw_parts(BUF_VA as *const u8, MAX_ENTROPY_BYTES) };
+        let mut out = Vec::with_capacity(n);
+        out.extend_from_slice(&slice[..n]);
+        return Ok(out);
+    }
+}
@@ -15,9 +15,8 @@
 use core::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
 
 use crate::bootstrap::policyd::policyd_cap_allowed;
-use crate::os_payload::log_topics;
 use crate::os_payload::{
-    self, InitError, Result, __data_end, __data_start, __rodata_end, __rodata_start,
+    self, __data_end, __data_start, __rodata_end, __rodata_start, log_topics, InitError, Result,
     INIT_HEALTH_MAGIC0, INIT_HEALTH_MAGIC1, INIT_HEALTH_OP_OK, INIT_HEALTH_VERSION,
     MAX_LOG_STR_LEN, PROBE_ENABLED,
 };
@@ -954,6 +953,7 @@ pub(crate) fn ipc_error_label(err: IpcError) -> &'static str {
         IpcError::PermissionDenied => "permission-denied",
         IpcError::TimedOut => "timed-out",
         IpcError::NoSpace => "no-space",
+        IpcError::Truncated => "truncated",
         IpcError::Unsupported => "unsupported",
     }
 }
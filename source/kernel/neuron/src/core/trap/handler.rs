@@ -351,16 +351,15 @@ const ENOSPC: usize = 28;
 const ENOSYS: usize = 38;
 const ESRCH: usize = 3;
 const ECHILD: usize = 10;
+const EMSGSIZE: usize = 90;
 const ETIMEDOUT: usize = 110;
 
 #[allow(dead_code)]
 fn encode_error(err: SysError) -> usize {
     match err {
         SysError::InvalidSyscall => errno(ENOSYS),
-        SysError::Capability(cap) => match cap {
-            crate::cap::CapError::NoSpace => errno(ENOSPC),
-            _ => errno(EPERM),
-        },
+        SysError::Capability(crate::cap::CapError::NoSpace) => errno(ENOSPC),
+        SysError::Capability(_) => errno(EPERM),
         SysError::Ipc(ipc_err) => ipc_errno(&ipc_err),
         SysError::Spawn(spawn) => spawn_errno(&spawn),
         SysError::Transfer(_) => errno(EPERM),
@@ -370,6 +369,7 @@ fn encode_error(err: SysError) -> usize {
         SysError::Reschedule => errno(EAGAIN),
         SysError::InvalidTarget => errno(ESRCH),
         SysError::RunQueueFull => errno(ENOSPC),
+        SysError::Truncated => errno(EMSGSIZE),
     }
 }
 
@@ -417,7 +417,7 @@ fn wait_errno(err: &task::WaitError) -> usize {
         NoChildren => errno(ECHILD),
         NoSuchPid => errno(ESRCH),
         InvalidTarget => errno(EINVAL),
-        WouldBlock => errno(EINVAL),
+        WouldBlock => errno(EAGAIN), // only via WAIT_NOHANG; a blocking wait parks
     }
 }
 
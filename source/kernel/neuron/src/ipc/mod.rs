@@ -6,15 +6,14 @@
 //! STATUS: Functional
 //! API_STABILITY: Stable
 //! TEST_COVERAGE: QEMU selftests + boot markers
-//! PUBLIC API: Router (send/recv), Message, EndpointId
+//! PUBLIC API: Router (send/recv/peek), Message, EndpointId
 //! DEPENDS_ON: ipc::header::MessageHeader
 //! INVARIANTS: Header.len bounds payload; queue depth respected; no cross-layer deps
 //! ADR: docs/adr/0001-runtime-roles-and-boundaries.md
 
 extern crate alloc;
 
-use alloc::collections::BTreeMap;
-use alloc::collections::VecDeque;
+use alloc::collections::{BTreeMap, VecDeque};
 use alloc::vec::Vec;
 use core::marker::PhantomData;
 
@@ -22,6 +21,8 @@ use core::marker::PhantomData;
 use core::sync::atomic::{AtomicBool, Ordering};
 
 pub mod header;
+mod peek;
+mod reassign;
 #[cfg(feature = "ipc_trace_ring")]
 pub mod trace;
 
@@ -507,16 +508,8 @@ impl Router {
         if self.endpoints.len() >= MAX_ENDPOINTS {
             return Err(IpcError::NoSpace);
         }
-        if let Some(owner_pid) = owner {
-            let mut count: usize = 0;
-            for ep in &self.endpoints {
-                if ep.alive && ep.owner == Some(owner_pid) {
-                    count += 1;
-                }
-            }
-            if count >= MAX_ENDPOINTS_PER_OWNER {
-                return Err(IpcError::NoSpace);
-            }
+        if owner.is_some_and(|owner_pid| self.owned_count(owner_pid) >= MAX_ENDPOINTS_PER_OWNER) {
+            return Err(IpcError::NoSpace);
         }
         let id = self.endpoints.len() as EndpointId;
         self.endpoints.push(Endpoint::with_depth(depth, owner));
@@ -0,0 +1,61 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: Read-only inspection of an endpoint's next queued message (backs `SYSCALL_IPC_PEEK_V1`)
+//! OWNERS: @kernel-ipc-team
+//! STATUS: Functional
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: 2 host unit tests
+//! INVARIANTS: Peek never dequeues, requeues, or touches byte accounting or waiter queues
+
+use super::{EndpointId, IpcError, Message, Router};
+
+impl Router {
+    /// Returns the next message queued on `id` without removing it.
+    ///
+    /// Repeated peeks return the same message, and the next `recv` on `id` dequeues it.
+    pub fn peek(&self, id: EndpointId) -> Result<&Message, IpcError> {
+        let ep = self.endpoints.get(id as usize).ok_or(IpcError::NoSuchEndpoint)?;
+        if !ep.alive {
+            return Err(IpcError::NoSuchEndpoint);
+        }
+        ep.queue.front().ok_or(IpcError::QueueEmpty)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::header::MessageHeader;
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn peek_is_idempotent_and_recv_returns_the_peeked_message() {
+        let mut router = Router::new(1);
+        router
+            .send(0, Message::new(MessageHeader::new(1, 0, 0x0301, 0, 3), vec![7, 8, 9], None))
+            .unwrap();
+        router
+            .send(0, Message::new(MessageHeader::new(1, 0, 0x0302, 0, 1), vec![1], None))
+            .unwrap();
+        let queued = router.queued_bytes_total;
+        for _ in 0..3 {
+            let msg = router.peek(0).unwrap();
+            assert_eq!((msg.header.ty, msg.payload.len()), (0x0301, 3));
+        }
+        assert_eq!(router.queued_bytes_total, queued);
+        assert_eq!(router.recv(0).unwrap().header.ty, 0x0301);
+        assert_eq!(router.peek(0).unwrap().header.ty, 0x0302);
+    }
+
+    #[test]
+    fn test_reject_peek_on_empty_or_closed_endpoint() {
+        let mut router = Router::new(1);
+        assert_eq!(router.peek(0).unwrap_err(), IpcError::QueueEmpty);
+        assert_eq!(router.peek(9).unwrap_err(), IpcError::NoSuchEndpoint);
+        let ep = router.create_endpoint(2, Some(7)).unwrap();
+        router.send(ep, Message::new(MessageHeader::new(1, 0, 1, 0, 0), vec![], None)).unwrap();
+        let _ = router.close_endpoints_for_owner(7);
+        assert_eq!(router.peek(ep).unwrap_err(), IpcError::NoSuchEndpoint);
+    }
+}
@@ -0,0 +1,88 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: Endpoint owner reassignment (backs `SYSCALL_IPC_ENDPOINT_REASSIGN`)
+//! OWNERS: @kernel-ipc-team
+//! STATUS: Functional
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: 2 host unit tests
+//! INVARIANTS: Only the close-on-exit owner changes; queued messages, waiters and every
+//! capability naming the endpoint stay valid. Per-owner byte accounting follows the owner.
+
+use super::{EndpointId, IpcError, Router, WaiterId, MAX_ENDPOINTS_PER_OWNER};
+
+impl Router {
+    /// Live endpoints currently owned by `owner`.
+    pub(super) fn owned_count(&self, owner: WaiterId) -> usize {
+        self.endpoints.iter().filter(|ep| ep.alive && ep.owner == Some(owner)).count()
+    }
+
+    /// Makes `new_owner` the close-on-exit owner of endpoint `id`.
+    ///
+    /// Fails with `NoSpace` when `new_owner` already holds `MAX_ENDPOINTS_PER_OWNER` endpoints.
+    /// Reassigning to the current owner is a no-op.
+    pub fn reassign_owner(&mut self, id: EndpointId, new_owner: WaiterId) -> Result<(), IpcError> {
+        let ep = self.endpoints.get(id as usize).ok_or(IpcError::NoSuchEndpoint)?;
+        if !ep.alive {
+            return Err(IpcError::NoSuchEndpoint);
+        }
+        if ep.owner == Some(new_owner) {
+            return Ok(());
+        }
+        if self.owned_count(new_owner) >= MAX_ENDPOINTS_PER_OWNER {
+            return Err(IpcError::NoSpace);
+        }
+        if let Some(ep) = self.endpoints.get_mut(id as usize) {
+            ep.owner = Some(new_owner);
+        }
+        // Move the queued bytes to the new owner's budget (small N).
+        self.recompute_accounting();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::header::MessageHeader;
+    use super::super::Message;
+    use super::*;
+    use alloc::vec;
+
+    fn msg(ty: u16) -> Message {
+        Message::new(MessageHeader::new(1, 0, ty, 0, 2), vec![1, 2], None)
+    }
+
+    #[test]
+    fn reassign_keeps_queued_messages_and_moves_close_on_exit() {
+        let mut router = Router::new(0);
+        let id = router.create_endpoint(4, Some(10)).unwrap();
+        router.send(id, msg(0x0301)).unwrap();
+        router.register_recv_waiter(id, 7).unwrap();
+
+        router.reassign_owner(id, 20).unwrap();
+        assert_eq!(router.owner_queued_bytes.get(&20), Some(&2));
+        assert_eq!(router.owner_queued_bytes.get(&10), None);
+
+        // The old owner exiting no longer closes the endpoint; the new one does.
+        assert!(router.close_endpoints_for_owner(10).is_empty());
+        assert_eq!(router.recv(id).unwrap().header.ty, 0x0301);
+        router.send(id, msg(0x0302)).unwrap();
+        assert_eq!(router.close_endpoints_for_owner(20), [7]);
+        assert!(!router.endpoint_alive(id));
+    }
+
+    #[test]
+    fn test_reject_reassign_of_dead_endpoint_or_to_full_owner() {
+        let mut router = Router::new(0);
+        let id = router.create_endpoint(1, Some(10)).unwrap();
+        for _ in 0..MAX_ENDPOINTS_PER_OWNER {
+            router.create_endpoint(1, Some(20)).unwrap();
+        }
+        assert_eq!(router.reassign_owner(id, 20), Err(IpcError::NoSpace));
+        assert_eq!(router.reassign_owner(id, 10), Ok(()));
+        assert_eq!(router.reassign_owner(id + 1000, 10), Err(IpcError::NoSuchEndpoint));
+
+        router.close_endpoints_for_owner(10);
+        assert_eq!(router.reassign_owner(id, 30), Err(IpcError::NoSuchEndpoint));
+    }
+}
@@ -2,8 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 //! CONTEXT: Capability-management syscalls split out of the former
-//! single-file api.rs: endpoint create/close (factory-gated v2/for),
-//! sys_cap_close/clone/query and sys_cap_transfer(_to) incl. the MANAGE /
+//! single-file api.rs: endpoint create/close/reassign (factory-gated v2/for),
+//! sys_cap_close/clone/query/rights and sys_cap_transfer(_to) incl. the MANAGE /
 //! EndpointFactory transfer whitelists (RFC-0005 Phase 2 hardening).
 //! OWNERS: @kernel-team
 //! STATUS: Functional
@@ -141,6 +141,41 @@ pub(super) fn sys_ipc_endpoint_create_for(ctx: &mut Context<'_>, args: &Args) ->
     let slot = ctx.tasks.current_caps_mut().allocate(ep_cap)?;
     Ok(slot)
 }
+
+/// Moves an endpoint's close-on-exit ownership without touching its queue or capabilities.
+///
+/// Same authority as `sys_ipc_endpoint_create_for`: an EndpointFactory with MANAGE, and a new
+/// owner that is the caller or one of its direct children. The endpoint capability must carry
+/// MANAGE as well, so plain send/recv holders cannot re-home a service inbox.
+pub(super) fn sys_ipc_endpoint_reassign(ctx: &mut Context<'_>, args: &Args) -> SysResult<usize> {
+    let factory_slot = args.get(0);
+    let endpoint_slot = args.get(1);
+    let new_owner = task::Pid::from_raw(args.get(2) as u32);
+
+    let current = ctx.tasks.current_pid();
+    let cap_table =
+        ctx.tasks.caps_of(current).ok_or(Error::Capability(CapError::PermissionDenied))?;
+    let factory = cap_table.get(factory_slot)?;
+    if factory.kind != CapabilityKind::EndpointFactory || !factory.rights.contains(Rights::MANAGE) {
+        return Err(Error::Capability(CapError::PermissionDenied));
+    }
+    let endpoint = cap_table.get(endpoint_slot)?;
+    let CapabilityKind::Endpoint(id) = endpoint.kind else {
+        return Err(Error::Capability(CapError::InvalidSlot));
+    };
+    if !endpoint.rights.contains(Rights::MANAGE) {
+        return Err(Error::Capability(CapError::PermissionDenied));
+    }
+    let owner_ok =
+        new_owner == current || ctx.tasks.task(new_owner).and_then(|t| t.parent()) == Some(current);
+    if !owner_ok {
+        return Err(Error::Capability(CapError::PermissionDenied));
+    }
+
+    ctx.router.reassign_owner(id, new_owner.as_raw())?;
+    Ok(0)
+}
+
 pub(super) fn sys_cap_close(ctx: &mut Context<'_>, args: &Args) -> SysResult<usize> {
     let slot = args.get(0);
     // Local drop only: remove the capability slot from the caller.
@@ -231,6 +266,16 @@ pub(super) fn sys_cap_query(ctx: &mut Context<'_>, args: &Args) -> SysResult<usi
     Ok(0)
 }
 
+/// Returns the caller's rights mask for `slot` (`InvalidSlot` if the slot is empty).
+///
+/// Rights are intersected when a capability is transferred, so the stored mask is the
+/// effective grant; reporting it leaks nothing the holder could not already exercise.
+pub(super) fn sys_cap_rights(ctx: &mut Context<'_>, args: &Args) -> SysResult<usize> {
+    let slot = SlotIndex::decode(args.get(0));
+    let cap = ctx.tasks.current_caps_mut().get(slot.0)?;
+    Ok(cap.rights.bits() as usize)
+}
+
 pub(super) fn sys_cap_transfer(ctx: &mut Context<'_>, args: &Args) -> SysResult<usize> {
     let typed = CapTransferArgsTyped::decode(args)?;
     let rights = typed.check()?;
@@ -341,3 +386,41 @@ pub(super) fn sys_cap_transfer_to(ctx: &mut Context<'_>, args: &Args) -> SysResu
     )?;
     Ok(typed.child_slot.0)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::syscall::SYSCALL_CAP_RIGHTS;
+
+    struct ZeroTimer;
+
+    impl crate::hal::Timer for ZeroTimer {
+        fn now(&self) -> u64 {
+            0
+        }
+        fn set_wakeup(&self, _deadline: u64) {}
+    }
+
+    #[test]
+    fn cap_rights_reports_effective_mask_and_rejects_empty_slot() {
+        let mut scheduler = Scheduler::new();
+        let mut tasks = task::TaskTable::new();
+        tasks
+            .bootstrap_mut()
+            .caps_mut()
+            .set(5, Capability { kind: CapabilityKind::Endpoint(0), rights: Rights::SEND })
+            .unwrap();
+        let mut router = ipc::Router::new(1);
+        let mut as_manager = AddressSpaceManager::new();
+        let timer = ZeroTimer;
+        let mut ctx =
+            Context::new(&mut scheduler, &mut tasks, &mut router, &mut as_manager, &timer);
+        let mut table = SyscallTable::new();
+        install_handlers(&mut table);
+
+        let bits = table.dispatch(SYSCALL_CAP_RIGHTS, &mut ctx, &Args::new([5, 0, 0, 0, 0, 0]));
+        assert_eq!(bits, Ok(Rights::SEND.bits() as usize));
+        let err = table.dispatch(SYSCALL_CAP_RIGHTS, &mut ctx, &Args::new([6, 0, 0, 0, 0, 0]));
+        assert_eq!(err, Err(Error::Capability(CapError::InvalidSlot)));
+    }
+}
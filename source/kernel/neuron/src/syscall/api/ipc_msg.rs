@@ -444,6 +444,10 @@ pub(super) fn sys_ipc_recv_v1(ctx: &mut Context<'_>, args: &Args) -> SysResult<u
         );
     }
 
+    if !truncate && !payload_fits(&msg, typed.payload_out_max) {
+        return Err(requeue_oversized(ctx, endpoint, msg, typed.header_out_ptr));
+    }
+
     // If the message carries a moved capability, allocate it into the receiver now and write the
     // allocated slot into the returned header's `src` field.
     if let Some(mut cap) = msg.moved_cap.take() {
@@ -534,10 +538,6 @@ pub(super) fn sys_ipc_recv_v1(ctx: &mut Context<'_>, args: &Args) -> SysResult<u
         return Ok(0);
     }
 
-    if total > typed.payload_out_max && !truncate {
-        return Err(AddressSpaceError::InvalidArgs.into());
-    }
-
     let n = core::cmp::min(total, typed.payload_out_max);
     unsafe {
         core::ptr::copy_nonoverlapping(msg.payload.as_ptr(), typed.payload_out_ptr as *mut u8, n);
@@ -665,6 +665,9 @@ pub(super) fn sys_ipc_recv_v2(ctx: &mut Context<'_>, args: &Args) -> SysResult<u
         }
     };
 
+    if !truncate && !payload_fits(&msg, payload_out_max) {
+        return Err(requeue_oversized(ctx, endpoint, msg, header_out_ptr));
+    }
     // CAP_MOVE allocation (same semantics as v1).
     if let Some(mut cap) = msg.moved_cap.take() {
         if msg.capmove_expected_ep != 0 {
@@ -707,9 +710,6 @@ pub(super) fn sys_ipc_recv_v2(ctx: &mut Context<'_>, args: &Args) -> SysResult<u
         ctx.last_message = Some(msg);
         return Ok(0);
     }
-    if total > payload_out_max && !truncate {
-        return Err(AddressSpaceError::InvalidArgs.into());
-    }
     let n = core::cmp::min(total, payload_out_max);
     unsafe {
         core::ptr::copy_nonoverlapping(msg.payload.as_ptr(), payload_out_ptr as *mut u8, n);
@@ -0,0 +1,53 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: sys_ipc_peek_v1 — header + payload length of the next queued message, not dequeued
+//! OWNERS: @kernel-team
+//! STATUS: Functional
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: Router-level host tests (ipc::peek) + QEMU marker gates
+//! ADR: docs/adr/0016-kernel-libs-architecture.md
+
+use super::*;
+
+#[derive(Copy, Clone)]
+pub(super) struct IpcPeekV1ArgsTyped {
+    slot: SlotIndex,
+    header_out_ptr: usize,
+}
+
+impl IpcPeekV1ArgsTyped {
+    #[inline]
+    fn decode(args: &Args) -> Result<Self, Error> {
+        Ok(Self { slot: SlotIndex::decode(args.get(0)), header_out_ptr: args.get(1) })
+    }
+
+    #[inline]
+    fn check(&self) -> Result<(), Error> {
+        ensure_user_slice(self.header_out_ptr, 16)
+    }
+}
+
+/// Writes the next message's header to `header_out_ptr` and returns its payload length.
+///
+/// Never blocks (`-EAGAIN` when the queue is empty) and never mutates the queue, so the
+/// following `SYSCALL_IPC_RECV_V1` returns the same message. A pending CAP_MOVE slot is only
+/// allocated by that receive, so the peeked header reports `src = 0` for such messages.
+pub(super) fn sys_ipc_peek_v1(ctx: &mut Context<'_>, args: &Args) -> SysResult<usize> {
+    let typed = IpcPeekV1ArgsTyped::decode(args)?;
+    typed.check()?;
+
+    let endpoint =
+        ctx.tasks.current_caps_mut().derive_endpoint_ref(typed.slot.0, Rights::RECV)?.endpoint();
+    let msg = ctx.router.peek(endpoint)?;
+    let mut header = msg.header;
+    if msg.moved_cap.is_some() {
+        header.src = 0;
+    }
+    let hdr = header.to_le_bytes();
+    // SAFETY: `check` validated `header_out_ptr..+16` as a user range.
+    unsafe {
+        core::ptr::copy_nonoverlapping(hdr.as_ptr(), typed.header_out_ptr as *mut u8, hdr.len());
+    }
+    Ok(msg.payload.len())
+}
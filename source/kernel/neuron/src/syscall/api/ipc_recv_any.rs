@@ -0,0 +1,215 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: sys_ipc_recv_any — blocking receive from the first ready of up to 8 endpoints
+//! OWNERS: @kernel-team
+//! STATUS: Functional
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: Descriptor layout host test (nexus-abi) + QEMU marker gates
+//! ADR: docs/adr/0016-kernel-libs-architecture.md
+//!
+//! A one-shot alternative to a waitset for the common "request + control endpoint" server:
+//! the slots travel in the descriptor, so no waitset object or capability is needed. The
+//! blocking path reuses the waitset recipe (register as recv-waiter on every endpoint,
+//! re-scan, block; clear stale registrations on re-entry).
+
+use super::*;
+
+// Descriptor layout is versioned to keep the ABI extensible (mirrors IPC recv v2).
+const IPC_RECV_ANY_MAGIC: u32 = 0x4E58_5241; // 'N''X''R''A'
+const IPC_RECV_ANY_VERSION: u32 = 1;
+/// Upper bound on `slot_count` (the descriptor carries the slots inline).
+const IPC_RECV_ANY_MAX_SLOTS: usize = 8;
+/// Descriptor size: 16-byte prefix, 8 inline `u32` slots, five `u64` fields.
+const IPC_RECV_ANY_DESC_LEN: usize = 88;
+const MAX_FRAME_BYTES: usize = 8 * 1024;
+
+struct IpcRecvAnyArgsTyped {
+    endpoints: [ipc::EndpointId; IPC_RECV_ANY_MAX_SLOTS],
+    count: usize,
+    /// Index scanned first: one past the caller's previously ready index (round-robin).
+    start: usize,
+    header_out_ptr: usize,
+    payload_out_ptr: usize,
+    payload_out_max: usize,
+    which_out_ptr: usize,
+    deadline_ns: u64,
+}
+
+impl IpcRecvAnyArgsTyped {
+    /// Copies in and validates the descriptor, deriving every endpoint with `Rights::RECV`.
+    fn decode(ctx: &mut Context<'_>, desc_ptr: usize) -> Result<Self, Error> {
+        ensure_user_slice(desc_ptr, IPC_RECV_ANY_DESC_LEN)?;
+        let mut raw = [0u8; IPC_RECV_ANY_DESC_LEN];
+        // SAFETY: `ensure_user_slice` validated `desc_ptr..+IPC_RECV_ANY_DESC_LEN`.
+        unsafe {
+            core::ptr::copy_nonoverlapping(desc_ptr as *const u8, raw.as_mut_ptr(), raw.len());
+        }
+        if read_u32_le(&raw, 0)? != IPC_RECV_ANY_MAGIC
+            || read_u32_le(&raw, 4)? != IPC_RECV_ANY_VERSION
+        {
+            return Err(AddressSpaceError::InvalidArgs.into());
+        }
+        let count = read_u32_le(&raw, 8)? as usize;
+        if count == 0 || count > IPC_RECV_ANY_MAX_SLOTS {
+            return Err(AddressSpaceError::InvalidArgs.into());
+        }
+        let prev = read_u32_le(&raw, 12)? as usize;
+        let start = if prev < count { (prev + 1) % count } else { 0 };
+
+        let mut endpoints = [0; IPC_RECV_ANY_MAX_SLOTS];
+        for (index, endpoint) in endpoints[..count].iter_mut().enumerate() {
+            let slot = read_u32_le(&raw, 16 + index * 4)? as usize;
+            *endpoint =
+                ctx.tasks.current_caps_mut().derive_endpoint_ref(slot, Rights::RECV)?.endpoint();
+        }
+
+        let typed = Self {
+            endpoints,
+            count,
+            start,
+            header_out_ptr: read_u64_le(&raw, 48)? as usize,
+            payload_out_ptr: read_u64_le(&raw, 56)? as usize,
+            payload_out_max: read_u64_le(&raw, 64)? as usize,
+            which_out_ptr: read_u64_le(&raw, 72)? as usize,
+            deadline_ns: read_u64_le(&raw, 80)?,
+        };
+        typed.check()?;
+        Ok(typed)
+    }
+
+    fn check(&self) -> Result<(), Error> {
+        ensure_user_slice(self.header_out_ptr, 16)?;
+        if self.payload_out_max > MAX_FRAME_BYTES {
+            return Err(AddressSpaceError::InvalidArgs.into());
+        }
+        if self.payload_out_max != 0 {
+            ensure_user_slice(self.payload_out_ptr, self.payload_out_max)?;
+        }
+        ensure_user_slice(self.which_out_ptr, 8)
+    }
+
+    fn endpoints(&self) -> &[ipc::EndpointId] {
+        &self.endpoints[..self.count]
+    }
+
+    /// First endpoint index with a pending message, scanning round-robin from `start`.
+    fn first_ready(&self, router: &ipc::Router) -> Option<usize> {
+        (0..self.count)
+            .map(|offset| (self.start + offset) % self.count)
+            .find(|&index| router.pending(self.endpoints[index]))
+    }
+}
+
+/// Blocks until any descriptor slot has a message, receives it, and writes the slot's index to
+/// `which_out_ptr`. Returns the copied payload length.
+///
+/// Fairness: the scan starts one past the descriptor's `prev_index`, so a caller that feeds
+/// each returned index back in is served round-robin across ready slots.
+pub(super) fn sys_ipc_recv_any(ctx: &mut Context<'_>, args: &Args) -> SysResult<usize> {
+    let typed = IpcRecvAnyArgsTyped::decode(ctx, args.get(0))?;
+    let deadline_ns = typed.deadline_ns;
+    let cur = ctx.tasks.current_pid();
+
+    // Clear registrations left from a prior blocking round (a sender woke us via one endpoint;
+    // the others still list us). Idempotent on first entry.
+    for &ep in typed.endpoints() {
+        let _ = ctx.router.remove_recv_waiter(ep, cur.as_raw());
+    }
+
+    let index = match typed.first_ready(ctx.router) {
+        Some(index) => index,
+        None => {
+            if deadline_ns != 0 && ctx.timer.now() >= deadline_ns {
+                return Err(Error::Ipc(ipc::IpcError::TimedOut));
+            }
+            if deadline_ns != 0 {
+                crate::trap::arm_wakeup(ctx.timer, deadline_ns);
+            }
+            for &ep in typed.endpoints() {
+                // A dead endpoint must not leave us blocked on the others forever.
+                if let Err(e) = ctx.router.register_recv_waiter(ep, cur.as_raw()) {
+                    for &ep in typed.endpoints() {
+                        let _ = ctx.router.remove_recv_waiter(ep, cur.as_raw());
+                    }
+                    return Err(e.into());
+                }
+            }
+            // Missed-wakeup guard: a sender may have enqueued before we registered.
+            if let Some(index) = typed.first_ready(ctx.router) {
+                for &ep in typed.endpoints() {
+                    let _ = ctx.router.remove_recv_waiter(ep, cur.as_raw());
+                }
+                index
+            } else {
+                // The deadline sweep deregisters `endpoint` only; re-entry clears the rest.
+                let endpoint = typed.endpoints[typed.start];
+                ctx.tasks
+                    .block_current(BlockReason::IpcRecv { endpoint, deadline_ns }, ctx.scheduler);
+                wake_expired_blocked(ctx);
+                if let Some(next) = ctx.scheduler.schedule_next() {
+                    ctx.tasks.set_current(next);
+                    return Err(Error::Reschedule);
+                }
+                for &ep in typed.endpoints() {
+                    let _ = ctx.router.remove_recv_waiter(ep, cur.as_raw());
+                }
+                observe_wake_outcome(ctx.tasks.wake(cur, ctx.scheduler));
+                return Err(Error::Reschedule);
+            }
+        }
+    };
+
+    let endpoint = typed.endpoints[index];
+    let mut msg = ctx.router.recv(endpoint)?;
+    if let Ok(Some(waiter)) = ctx.router.pop_send_waiter(endpoint) {
+        observe_wake_outcome(ctx.tasks.wake(task::Pid::from_raw(waiter), ctx.scheduler));
+    }
+
+    if !payload_fits(&msg, typed.payload_out_max) {
+        return Err(requeue_oversized(ctx, endpoint, msg, typed.header_out_ptr));
+    }
+
+    // CAP_MOVE allocation (same semantics as v1/v2).
+    if let Some(mut cap) = msg.moved_cap.take() {
+        if msg.capmove_expected_ep != 0 {
+            if let CapabilityKind::Endpoint(id) = cap.kind {
+                if id != msg.capmove_expected_ep {
+                    cap.kind = CapabilityKind::Endpoint(msg.capmove_expected_ep);
+                }
+            }
+        }
+        match ctx.tasks.current_caps_mut().allocate(cap) {
+            Ok(slot) => msg.header.src = slot as u32,
+            Err(_) => {
+                msg.moved_cap = Some(cap);
+                let _ = ctx.router.requeue_front(endpoint, msg);
+                return Err(Error::Ipc(ipc::IpcError::NoSpace));
+            }
+        }
+    }
+
+    let hdr = msg.header.to_le_bytes();
+    let which = (index as u64).to_le_bytes();
+    // SAFETY: `check` validated both user ranges.
+    unsafe {
+        core::ptr::copy_nonoverlapping(hdr.as_ptr(), typed.header_out_ptr as *mut u8, hdr.len());
+        core::ptr::copy_nonoverlapping(which.as_ptr(), typed.which_out_ptr as *mut u8, 8);
+    }
+
+    let total = msg.payload.len();
+    if total == 0 || typed.payload_out_max == 0 {
+        ctx.last_message = Some(msg);
+        return Ok(0);
+    }
+    // SAFETY: `total <= payload_out_max`, validated by `check`.
+    unsafe {
+        core::ptr::copy_nonoverlapping(
+            msg.payload.as_ptr(),
+            typed.payload_out_ptr as *mut u8,
+            total,
+        );
+    }
+    ctx.last_message = Some(msg);
+    Ok(total)
+}
@@ -0,0 +1,212 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: sys_ipc_recv_vmo — blocking receive whose payload lands directly in a VMO window
+//! OWNERS: @kernel-team
+//! STATUS: Functional
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: Descriptor layout host test (nexus-abi), oversized-payload host test, QEMU
+//! marker gates
+//! ADR: docs/adr/0016-kernel-libs-architecture.md
+//!
+//! Large transfers (bundle images, log batches) otherwise bounce through a user stack buffer
+//! and a `vmo_write`. Here the kernel copies the queued payload straight to
+//! `vmo.base + offset`; the header still goes to a user pointer. The VMO capability needs
+//! `Rights::MAP` (the right `vmo_write` checks), and `offset..offset + max` must lie inside it.
+//! `max` is a hard bound: unlike a v1 receive, `max == 0` is not "no limit" but a window that
+//! only takes an empty payload.
+
+use super::*;
+
+// Descriptor layout is versioned to keep the ABI extensible (mirrors IPC recv v2).
+const IPC_RECV_VMO_MAGIC: u32 = 0x4E58_5256; // 'N''X''R''V'
+const IPC_RECV_VMO_VERSION: u32 = 1;
+/// Descriptor size: four `u32` fields, four `u64` fields.
+const IPC_RECV_VMO_DESC_LEN: usize = 48;
+
+struct IpcRecvVmoArgsTyped {
+    endpoint: ipc::EndpointId,
+    /// Kernel address of the window start (`vmo.base + offset`).
+    window: usize,
+    max: usize,
+    header_out_ptr: usize,
+    deadline_ns: u64,
+}
+
+impl IpcRecvVmoArgsTyped {
+    /// Copies in and validates the descriptor: `Rights::RECV` on the endpoint, `Rights::MAP`
+    /// on the VMO, and the window inside the VMO.
+    fn decode(ctx: &mut Context<'_>, desc_ptr: usize) -> Result<Self, Error> {
+        ensure_user_slice(desc_ptr, IPC_RECV_VMO_DESC_LEN)?;
+        let mut raw = [0u8; IPC_RECV_VMO_DESC_LEN];
+        // SAFETY: `ensure_user_slice` validated `desc_ptr..+IPC_RECV_VMO_DESC_LEN`.
+        unsafe {
+            core::ptr::copy_nonoverlapping(desc_ptr as *const u8, raw.as_mut_ptr(), raw.len());
+        }
+        if read_u32_le(&raw, 0)? != IPC_RECV_VMO_MAGIC
+            || read_u32_le(&raw, 4)? != IPC_RECV_VMO_VERSION
+        {
+            return Err(AddressSpaceError::InvalidArgs.into());
+        }
+        let slot = read_u32_le(&raw, 8)? as usize;
+        let vmo_slot = read_u32_le(&raw, 12)? as usize;
+        let header_out_ptr = read_u64_le(&raw, 16)? as usize;
+        let offset = read_u64_le(&raw, 24)? as usize;
+        let max = read_u64_le(&raw, 32)? as usize;
+        let deadline_ns = read_u64_le(&raw, 40)?;
+
+        ensure_user_slice(header_out_ptr, 16)?;
+        let caps = ctx.tasks.current_caps_mut();
+        let endpoint = caps.derive_endpoint_ref(slot, Rights::RECV)?.endpoint();
+        let (base, vmo_len) = match caps.derive(vmo_slot, Rights::MAP)?.kind {
+            CapabilityKind::Vmo { base, len } => (base, len),
+            _ => return Err(Error::Capability(CapError::PermissionDenied)),
+        };
+        let end = offset.checked_add(max).ok_or(Error::Capability(CapError::PermissionDenied))?;
+        if end > vmo_len {
+            return Err(Error::Capability(CapError::PermissionDenied));
+        }
+        Ok(Self { endpoint, window: base + offset, max, header_out_ptr, deadline_ns })
+    }
+}
+
+/// Blocks until the endpoint has a message, then writes its header to `header_out_ptr` and its
+/// payload to the VMO window. Returns the payload length.
+///
+/// A payload larger than `max` is rejected like a non-truncating v1 receive (`-EMSGSIZE`,
+/// header written, message requeued). `payload_fits` is not used: its `max == 0` means "no
+/// limit", which here would copy past the window.
+pub(super) fn sys_ipc_recv_vmo(ctx: &mut Context<'_>, args: &Args) -> SysResult<usize> {
+    let typed = IpcRecvVmoArgsTyped::decode(ctx, args.get(0))?;
+    let (endpoint, deadline_ns) = (typed.endpoint, typed.deadline_ns);
+    let cur = ctx.tasks.current_pid();
+    // Clear a registration left from a prior blocking round; idempotent on first entry.
+    let _ = ctx.router.remove_recv_waiter(endpoint, cur.as_raw());
+
+    if !ctx.router.pending(endpoint) {
+        if deadline_ns != 0 && ctx.timer.now() >= deadline_ns {
+            return Err(Error::Ipc(ipc::IpcError::TimedOut));
+        }
+        if deadline_ns != 0 {
+            crate::trap::arm_wakeup(ctx.timer, deadline_ns);
+        }
+        ctx.router.register_recv_waiter(endpoint, cur.as_raw())?;
+        // Missed-wakeup guard: a sender may have enqueued before we registered.
+        if ctx.router.pending(endpoint) {
+            let _ = ctx.router.remove_recv_waiter(endpoint, cur.as_raw());
+        } else {
+            ctx.tasks.block_current(BlockReason::IpcRecv { endpoint, deadline_ns }, ctx.scheduler);
+            wake_expired_blocked(ctx);
+            if let Some(next) = ctx.scheduler.schedule_next() {
+                ctx.tasks.set_current(next);
+                return Err(Error::Reschedule);
+            }
+            let _ = ctx.router.remove_recv_waiter(endpoint, cur.as_raw());
+            observe_wake_outcome(ctx.tasks.wake(cur, ctx.scheduler));
+            return Err(Error::Reschedule);
+        }
+    }
+
+    let mut msg = ctx.router.recv(endpoint)?;
+    if let Ok(Some(waiter)) = ctx.router.pop_send_waiter(endpoint) {
+        observe_wake_outcome(ctx.tasks.wake(task::Pid::from_raw(waiter), ctx.scheduler));
+    }
+
+    if msg.payload.len() > typed.max {
+        return Err(requeue_oversized(ctx, endpoint, msg, typed.header_out_ptr));
+    }
+
+    // CAP_MOVE allocation (same semantics as v1/v2).
+    if let Some(mut cap) = msg.moved_cap.take() {
+        if msg.capmove_expected_ep != 0 {
+            if let CapabilityKind::Endpoint(id) = cap.kind {
+                if id != msg.capmove_expected_ep {
+                    cap.kind = CapabilityKind::Endpoint(msg.capmove_expected_ep);
+                }
+            }
+        }
+        match ctx.tasks.current_caps_mut().allocate(cap) {
+            Ok(slot) => msg.header.src = slot as u32,
+            Err(_) => {
+                msg.moved_cap = Some(cap);
+                let _ = ctx.router.requeue_front(endpoint, msg);
+                return Err(Error::Ipc(ipc::IpcError::NoSpace));
+            }
+        }
+    }
+
+    let hdr = msg.header.to_le_bytes();
+    // SAFETY: `decode` validated the header range.
+    unsafe {
+        core::ptr::copy_nonoverlapping(hdr.as_ptr(), typed.header_out_ptr as *mut u8, hdr.len());
+    }
+
+    let total = msg.payload.len();
+    if total != 0 {
+        // SAFETY: `total <= max` and `decode` bounded `offset + max` by the VMO length.
+        unsafe {
+            core::ptr::copy_nonoverlapping(msg.payload.as_ptr(), typed.window as *mut u8, total);
+            riscv::asm::fence_i();
+        }
+    }
+    ctx.last_message = Some(msg);
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct ZeroTimer;
+
+    impl crate::hal::Timer for ZeroTimer {
+        fn now(&self) -> u64 {
+            0
+        }
+        fn set_wakeup(&self, _deadline: u64) {}
+    }
+
+    #[test]
+    fn test_reject_payload_with_zero_max_at_the_end_of_the_vmo() {
+        let mut scheduler = Scheduler::new();
+        let mut tasks = task::TaskTable::new();
+        let mut router = ipc::Router::new(0);
+        let endpoint = router.create_endpoint(2, None).unwrap();
+        // The VMO is the first half of `backing`; the second half must stay untouched.
+        let mut backing = [0u8; 32];
+        let vmo_len = 16;
+        {
+            let caps = tasks.bootstrap_mut().caps_mut();
+            caps.set(
+                0,
+                Capability { kind: CapabilityKind::Endpoint(endpoint), rights: Rights::RECV },
+            )
+            .unwrap();
+            let vmo = CapabilityKind::Vmo { base: backing.as_mut_ptr() as usize, len: vmo_len };
+            caps.set(1, Capability { kind: vmo, rights: Rights::MAP }).unwrap();
+        }
+        let hdr = crate::ipc::header::MessageHeader::new(0, endpoint, 0, 0, 8);
+        let msg = crate::ipc::Message::new(hdr, alloc::vec![0xAA; 8], None);
+        router.send(endpoint, msg).unwrap();
+        let mut as_manager = AddressSpaceManager::new();
+        let timer = ZeroTimer;
+        let mut ctx =
+            Context::new(&mut scheduler, &mut tasks, &mut router, &mut as_manager, &timer);
+
+        let mut out_hdr = [0u8; 16];
+        let mut desc = [0u8; IPC_RECV_VMO_DESC_LEN];
+        desc[0..4].copy_from_slice(&IPC_RECV_VMO_MAGIC.to_le_bytes());
+        desc[4..8].copy_from_slice(&IPC_RECV_VMO_VERSION.to_le_bytes());
+        desc[8..12].copy_from_slice(&0u32.to_le_bytes());
+        desc[12..16].copy_from_slice(&1u32.to_le_bytes());
+        desc[16..24].copy_from_slice(&(out_hdr.as_mut_ptr() as u64).to_le_bytes());
+        desc[24..32].copy_from_slice(&(vmo_len as u64).to_le_bytes()); // offset: VMO end
+        desc[32..40].copy_from_slice(&0u64.to_le_bytes()); // max
+        let args = Args::new([desc.as_ptr() as usize, 0, 0, 0, 0, 0]);
+
+        assert!(matches!(sys_ipc_recv_vmo(&mut ctx, &args), Err(Error::Truncated)));
+        assert_eq!(u32::from_le_bytes([out_hdr[12], out_hdr[13], out_hdr[14], out_hdr[15]]), 8);
+        assert!(ctx.router.pending(endpoint), "oversized message is requeued");
+        assert_eq!(backing, [0u8; 32]);
+    }
+}
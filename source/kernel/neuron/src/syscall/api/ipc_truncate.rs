@@ -0,0 +1,118 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: Oversized-payload rejection shared by the IPC receive syscalls
+//! OWNERS: @kernel-team
+//! STATUS: Functional
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: 1 host test (requeued message drained by a retry) + errno mapping host test
+//! (nexus-abi) + QEMU marker gates
+//! ADR: docs/adr/0016-kernel-libs-architecture.md
+//!
+//! Without `IPC_SYS_TRUNCATE`, a payload larger than the caller's buffer is not clipped:
+//! the receive fails with [`Error::Truncated`] (`EMSGSIZE`), the header is still written so
+//! `len` reports the required buffer size, and the message goes back to the queue head.
+//!
+//! Behavior change: this used to be `EINVAL` with the message dropped. A receive loop that
+//! skips the error and receives again with the same buffer now gets the same message back
+//! forever, and everything queued behind it waits. Callers retry with a buffer of `len`
+//! bytes or with `IPC_SYS_TRUNCATE` (RFC-0005).
+
+use super::*;
+
+/// `true` when the payload fits `payload_out_max` (a zero buffer is a header-only receive).
+pub(super) fn payload_fits(msg: &ipc::Message, payload_out_max: usize) -> bool {
+    payload_out_max == 0 || msg.payload.len() <= payload_out_max
+}
+
+/// Writes the header of an oversized `msg`, puts it back at the head of `endpoint` and returns
+/// [`Error::Truncated`]. Must run before any CAP_MOVE allocation; `src` reads 0 when a
+/// capability is still attached.
+pub(super) fn requeue_oversized(
+    ctx: &mut Context<'_>,
+    endpoint: ipc::EndpointId,
+    msg: ipc::Message,
+    header_out_ptr: usize,
+) -> Error {
+    let mut header = msg.header;
+    header.len = msg.payload.len() as u32;
+    if msg.moved_cap.is_some() {
+        header.src = 0;
+    }
+    let hdr = header.to_le_bytes();
+    // SAFETY: every caller validated `header_out_ptr..+16` with `ensure_user_slice`.
+    unsafe {
+        core::ptr::copy_nonoverlapping(hdr.as_ptr(), header_out_ptr as *mut u8, hdr.len());
+    }
+    match ctx.router.requeue_front(endpoint, msg) {
+        Ok(()) => Error::Truncated,
+        Err(e) => e.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct ZeroTimer;
+
+    impl crate::hal::Timer for ZeroTimer {
+        fn now(&self) -> u64 {
+            0
+        }
+        fn set_wakeup(&self, _deadline: u64) {}
+    }
+
+    fn recv_args(out_hdr: &mut [u8; 16], buf: &mut [u8], sys_flags: usize) -> Args {
+        let (hdr, ptr) = (out_hdr.as_mut_ptr() as usize, buf.as_mut_ptr() as usize);
+        Args::new([0, hdr, ptr, buf.len(), sys_flags | IPC_SYS_NONBLOCK, 0])
+    }
+
+    #[test]
+    fn requeued_oversized_message_is_drained_by_a_retry() {
+        let mut scheduler = Scheduler::new();
+        let mut tasks = task::TaskTable::new();
+        let mut router = ipc::Router::new(0);
+        let endpoint = router.create_endpoint(2, None).unwrap();
+        tasks
+            .bootstrap_mut()
+            .caps_mut()
+            .set(0, Capability { kind: CapabilityKind::Endpoint(endpoint), rights: Rights::RECV })
+            .unwrap();
+        for fill in [0xA1u8, 0xB2] {
+            let hdr = crate::ipc::header::MessageHeader::new(0, endpoint, 0, 0, 8);
+            router
+                .send(endpoint, crate::ipc::Message::new(hdr, alloc::vec![fill; 8], None))
+                .unwrap();
+        }
+        let mut as_manager = AddressSpaceManager::new();
+        let timer = ZeroTimer;
+        let mut ctx =
+            Context::new(&mut scheduler, &mut tasks, &mut router, &mut as_manager, &timer);
+        let mut out_hdr = [0u8; 16];
+        let header_len = |h: &[u8; 16]| u32::from_le_bytes([h[12], h[13], h[14], h[15]]);
+
+        // Too small a buffer: the same message comes back on every plain retry.
+        let mut small = [0u8; 4];
+        for _ in 0..2 {
+            let args = recv_args(&mut out_hdr, &mut small, 0);
+            assert!(matches!(sys_ipc_recv_v1(&mut ctx, &args), Err(Error::Truncated)));
+            assert_eq!(header_len(&out_hdr), 8);
+            assert_eq!(small, [0; 4]);
+        }
+
+        // A buffer of the reported length drains it, and the next message moves up.
+        let mut big = [0u8; 8];
+        let args = recv_args(&mut out_hdr, &mut big, 0);
+        assert_eq!(sys_ipc_recv_v1(&mut ctx, &args).unwrap(), 8);
+        assert_eq!(big, [0xA1; 8]);
+
+        // So does IPC_SYS_TRUNCATE, clipping the payload to the buffer.
+        let args = recv_args(&mut out_hdr, &mut small, 0);
+        assert!(matches!(sys_ipc_recv_v1(&mut ctx, &args), Err(Error::Truncated)));
+        let args = recv_args(&mut out_hdr, &mut small, IPC_SYS_TRUNCATE);
+        assert_eq!(sys_ipc_recv_v1(&mut ctx, &args).unwrap(), 4);
+        assert_eq!((small, header_len(&out_hdr)), ([0xB2; 4], 8));
+        assert!(!ctx.router.pending(endpoint), "both messages drained");
+    }
+}
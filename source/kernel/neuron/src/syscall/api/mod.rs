@@ -40,6 +40,10 @@ use crate::task::BlockReason;
 mod caps;
 mod exec;
 mod ipc_msg;
+mod ipc_peek;
+mod ipc_recv_any;
+mod ipc_recv_vmo;
+mod ipc_truncate;
 mod sched_task;
 mod sync_objects;
 mod task_image;
@@ -52,6 +56,10 @@ use caps::*;
 use exec::*;
 pub(crate) use exec::{exec_phase_a, exec_v2_phase_a, run_copy_plan, CopyPlan};
 use ipc_msg::*;
+use ipc_peek::*;
+use ipc_recv_any::*;
+use ipc_recv_vmo::*;
+use ipc_truncate::*;
 use sched_task::*;
 use sync_objects::*;
 pub(crate) use task_image::exit_current_and_release;
@@ -219,6 +227,7 @@ pub fn install_handlers(table: &mut SyscallTable) {
     table.register(SYSCALL_MAP, sys_map);
     table.register(SYSCALL_MMIO_MAP, sys_mmio_map);
     table.register(SYSCALL_CAP_QUERY, sys_cap_query);
+    table.register(crate::syscall::SYSCALL_CAP_RIGHTS, sys_cap_rights);
     table.register(SYSCALL_DEVICE_CAP_CREATE, sys_device_cap_create);
     table.register(SYSCALL_VMO_CREATE, sys_vmo_create);
     table.register(SYSCALL_VMO_WRITE, sys_vmo_write);
@@ -241,6 +250,7 @@ pub fn install_handlers(table: &mut SyscallTable) {
     table.register(crate::syscall::SYSCALL_IPC_ENDPOINT_CLOSE, sys_ipc_endpoint_close);
     table.register(crate::syscall::SYSCALL_IPC_ENDPOINT_CREATE_V2, sys_ipc_endpoint_create_v2);
     table.register(crate::syscall::SYSCALL_IPC_ENDPOINT_CREATE_FOR, sys_ipc_endpoint_create_for);
+    table.register(crate::syscall::SYSCALL_IPC_ENDPOINT_REASSIGN, sys_ipc_endpoint_reassign);
     table.register(crate::syscall::SYSCALL_GETPID, sys_getpid);
     table.register(SYSCALL_TASK_QOS, sys_task_qos);
     table.register(SYSCALL_SCHED, sys_sched);
@@ -258,6 +268,9 @@ pub fn install_handlers(table: &mut SyscallTable) {
     table.register(crate::syscall::SYSCALL_FENCE_SIGNAL, sys_fence_signal);
     table.register(crate::syscall::SYSCALL_FENCE_WAIT, sys_fence_wait);
     table.register(crate::syscall::SYSCALL_IPC_RECV_V2, sys_ipc_recv_v2);
+    table.register(crate::syscall::SYSCALL_IPC_PEEK_V1, sys_ipc_peek_v1);
+    table.register(crate::syscall::SYSCALL_IPC_RECV_ANY, sys_ipc_recv_any);
+    table.register(crate::syscall::SYSCALL_IPC_RECV_VMO, sys_ipc_recv_vmo);
     table.register(SYSCALL_SPAWN_LAST_ERROR, sys_spawn_last_error);
     table.register(SYSCALL_DEBUG_PUTC, sys_debug_putc);
     table.register(SYSCALL_DEBUG_WRITE, sys_debug_write);
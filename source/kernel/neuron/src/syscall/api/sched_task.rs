@@ -426,9 +426,14 @@ pub(super) fn sys_exit(ctx: &mut Context<'_>, args: &Args) -> SysResult<usize> {
     Err(Error::TaskExit)
 }
 
+/// `SYSCALL_WAIT` (12): reap a zombie child (`a0 > 0`) or any child (`a0 <= 0`).
+///
+/// With `WAIT_NOHANG` in `a1` a child that is still running fails with `-EAGAIN` instead
+/// of blocking the caller.
 pub(super) fn sys_wait(ctx: &mut Context<'_>, args: &Args) -> SysResult<usize> {
     let raw_pid = args.get(0) as i32;
     let target = if raw_pid <= 0 { None } else { Some(task::Pid::from_raw(raw_pid as u32)) };
+    let nohang = args.get(1) & crate::syscall::WAIT_NOHANG != 0;
     loop {
         match ctx.tasks.reap_child(target, ctx.address_spaces) {
             Ok((pid, status)) => {
@@ -437,6 +442,7 @@ pub(super) fn sys_wait(ctx: &mut Context<'_>, args: &Args) -> SysResult<usize> {
                 }
                 return Ok(pid.as_index());
             }
+            Err(err @ task::WaitError::WouldBlock) if nohang => return Err(Error::from(err)),
             Err(task::WaitError::WouldBlock) => {
                 let cur = ctx.tasks.current_pid();
                 ctx.tasks.block_current(BlockReason::WaitChild { target }, ctx.scheduler);
@@ -56,6 +56,8 @@ pub const SYSCALL_AS_CREATE: usize = 9;
 pub const SYSCALL_AS_MAP: usize = 10;
 pub const SYSCALL_EXIT: usize = 11;
 pub const SYSCALL_WAIT: usize = 12;
+/// `SYSCALL_WAIT` flag (`a1`): fail with `-EAGAIN` instead of blocking on a live child.
+pub const WAIT_NOHANG: usize = 1 << 0;
 pub const SYSCALL_EXEC: usize = 13;
 /// IPC v1 (payload copy-in): see RFC-0005.
 pub const SYSCALL_IPC_SEND_V1: usize = 14;
@@ -100,6 +102,22 @@ pub const SYSCALL_CAP_CLONE: usize = 24;
 pub const SYSCALL_GETPID: usize = 25;
 /// Receives an IPC message and additionally returns sender service identity metadata (v2).
 pub const SYSCALL_IPC_RECV_V2: usize = 26;
+/// Non-blocking, read-only peek at the next queued message: writes its header and returns the
+/// payload length without dequeuing it (`-EAGAIN` when empty; `Rights::RECV`; RFC-0005).
+/// Args: (slot, header_out_ptr).
+pub const SYSCALL_IPC_PEEK_V1: usize = 52;
+/// Blocking receive from the first ready of up to 8 endpoints, round-robin across ready slots;
+/// writes the ready slot's index to the descriptor's `which_out_ptr` (`Rights::RECV` on every
+/// slot; RFC-0005). Args: (desc_ptr).
+pub const SYSCALL_IPC_RECV_ANY: usize = 53;
+/// Hands an endpoint's close-on-exit ownership to `new_owner_pid` (the caller or one of its
+/// direct children). Needs `Rights::MANAGE` on both the endpoint-factory and the endpoint
+/// capability; queued messages and existing capabilities are untouched. Args:
+/// (factory_slot, endpoint_slot, new_owner_pid).
+pub const SYSCALL_IPC_ENDPOINT_REASSIGN: usize = 54;
+/// Blocking receive that writes the payload straight into a VMO window instead of a user buffer
+/// (`Rights::RECV` on the endpoint, `Rights::MAP` on the VMO; RFC-0005). Args: (desc_ptr).
+pub const SYSCALL_IPC_RECV_VMO: usize = 55;
 /// Maps a device MMIO capability window into the caller's address space (USER|RW, never EXEC).
 ///
 /// This is the kernel primitive required for userspace virtio drivers on QEMU `virt`.
@@ -109,6 +127,11 @@ pub const SYSCALL_MMIO_MAP: usize = 27;
 /// This is a small introspection primitive needed by userspace drivers to obtain physical
 /// addresses for DMA-capable resources (e.g., VMOs) without exposing ambient physical memory.
 pub const SYSCALL_CAP_QUERY: usize = 28;
+/// Returns the rights mask held by a capability slot in the caller's table.
+///
+/// The stored mask is already the effective (intersected-at-transfer) rights; no extra
+/// right is required to introspect a slot the caller holds. Args: (cap_slot).
+pub const SYSCALL_CAP_RIGHTS: usize = 51;
 /// Creates a DeviceMmio capability in the caller's cap table (privileged; init-only).
 pub const SYSCALL_DEVICE_CAP_CREATE: usize = 30;
 /// Resume a suspended task (enqueue into scheduler). Privileged.
@@ -189,6 +212,9 @@ pub enum Error {
     InvalidTarget,
     /// Task resume: scheduler run queue is full.
     RunQueueFull,
+    /// IPC receive: the payload exceeds the caller's buffer and `IPC_SYS_TRUNCATE` was not set.
+    /// The header (with the full `len`) was written and the message stays queued.
+    Truncated,
 }
 
 impl From<cap::CapError> for Error {
@@ -0,0 +1,163 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: Bounded walker over length-prefixed sub-frames in a batch body
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: 3 host unit tests (well-formed, truncated last sub-frame, over-count)
+//!
+//! A batch request packs sub-frames back to back, each behind a little-endian length
+//! prefix: `len | bytes | len | bytes ...`. [`SubFrameIter`] yields the sub-frame slices
+//! in order and never indexes past the body: a short prefix or a length that runs past
+//! the end stops the walk, as does reaching the caller's `max_count` with bytes left.
+//! After `None`, [`SubFrameIter::stop`] tells a clean end apart from those two cases so
+//! a decoder can reject the whole batch instead of acting on a prefix of it.
+
+/// Width of each sub-frame's length prefix (little-endian).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LenPrefix {
+    /// One byte (sub-frames up to 255 bytes).
+    U8,
+    /// Two bytes.
+    U16,
+    /// Four bytes.
+    U32,
+}
+
+impl LenPrefix {
+    const fn width(self) -> usize {
+        match self {
+            Self::U8 => 1,
+            Self::U16 => 2,
+            Self::U32 => 4,
+        }
+    }
+}
+
+/// Why a [`SubFrameIter`] stopped before the end of the body.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BatchStop {
+    /// The last length prefix or sub-frame runs past the body.
+    Truncated,
+    /// `max_count` sub-frames were yielded and bytes remain.
+    OverCount,
+}
+
+/// Iterator over the length-prefixed sub-frames of a batch body.
+#[derive(Clone, Debug)]
+pub struct SubFrameIter<'a> {
+    rest: &'a [u8],
+    prefix: LenPrefix,
+    left: usize,
+    stop: Option<BatchStop>,
+}
+
+impl<'a> SubFrameIter<'a> {
+    /// Walks `body`, yielding at most `max_count` sub-frames.
+    pub const fn new(body: &'a [u8], prefix: LenPrefix, max_count: usize) -> Self {
+        Self { rest: body, prefix, left: max_count, stop: None }
+    }
+
+    /// Why the walk stopped early; `None` while running and after a clean end.
+    pub const fn stop(&self) -> Option<BatchStop> {
+        self.stop
+    }
+
+    /// Whether the whole body was consumed without a stop.
+    pub const fn is_complete(&self) -> bool {
+        self.rest.is_empty() && self.stop.is_none()
+    }
+
+    fn halt(&mut self, stop: BatchStop) -> Option<&'a [u8]> {
+        self.stop = Some(stop);
+        self.rest = &[];
+        None
+    }
+}
+
+impl<'a> Iterator for SubFrameIter<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<&'a [u8]> {
+        if self.rest.is_empty() {
+            return None;
+        }
+        if self.left == 0 {
+            return self.halt(BatchStop::OverCount);
+        }
+        let Some((len, body)) = self.rest.split_at_checked(self.prefix.width()) else {
+            return self.halt(BatchStop::Truncated);
+        };
+        let len = match *len {
+            [b0] => usize::from(b0),
+            [b0, b1] => usize::from(u16::from_le_bytes([b0, b1])),
+            [b0, b1, b2, b3] => u32::from_le_bytes([b0, b1, b2, b3]) as usize,
+            _ => return self.halt(BatchStop::Truncated),
+        };
+        let Some((frame, rest)) = body.split_at_checked(len) else {
+            return self.halt(BatchStop::Truncated);
+        };
+        self.rest = rest;
+        self.left -= 1;
+        Some(frame)
+    }
+}
+
+impl core::iter::FusedIterator for SubFrameIter<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn well_formed_batches_yield_every_sub_frame() {
+        let body = [2, b'a', b'b', 0, 3, 1, 2, 3];
+        let mut iter = SubFrameIter::new(&body, LenPrefix::U8, 8);
+        assert_eq!(iter.by_ref().collect::<Vec<_>>(), [&b"ab"[..], &[], &[1, 2, 3]]);
+        assert!(iter.is_complete());
+        assert_eq!(iter.next(), None);
+
+        let body = [1, 0, 0xAA, 2, 0, 0xBB, 0xCC];
+        let iter = SubFrameIter::new(&body, LenPrefix::U16, 2);
+        assert_eq!(iter.collect::<Vec<_>>(), [&[0xAA][..], &[0xBB, 0xCC]]);
+
+        let body = [1, 0, 0, 0, 7];
+        assert_eq!(SubFrameIter::new(&body, LenPrefix::U32, 1).next(), Some(&[7][..]));
+        let mut empty = SubFrameIter::new(&[], LenPrefix::U32, 0);
+        assert_eq!((empty.next(), empty.is_complete()), (None, true));
+    }
+
+    #[test]
+    fn test_reject_truncated_last_sub_frame() {
+        // Payload shorter than its prefix claims.
+        let body = [1, b'x', 5, b'y'];
+        let mut iter = SubFrameIter::new(&body, LenPrefix::U8, 8);
+        assert_eq!(iter.next(), Some(&b"x"[..]));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.stop(), Some(BatchStop::Truncated));
+        assert!(!iter.is_complete());
+        assert_eq!(iter.next(), None);
+
+        // Half a length prefix, and a length near u32::MAX.
+        let mut iter = SubFrameIter::new(&[0, 0, 9], LenPrefix::U16, 8);
+        assert_eq!((iter.next(), iter.next()), (Some(&[][..]), None));
+        assert_eq!(iter.stop(), Some(BatchStop::Truncated));
+        let mut iter = SubFrameIter::new(&[0xFF, 0xFF, 0xFF, 0xFF, 1], LenPrefix::U32, 8);
+        assert_eq!((iter.next(), iter.stop()), (None, Some(BatchStop::Truncated)));
+    }
+
+    #[test]
+    fn test_reject_over_count_input() {
+        let body = [0u8; 64];
+        let mut iter = SubFrameIter::new(&body, LenPrefix::U8, 4);
+        assert_eq!(iter.by_ref().count(), 4);
+        assert_eq!(iter.stop(), Some(BatchStop::OverCount));
+        assert!(!iter.is_complete());
+
+        // Exactly `max_count` sub-frames is a clean end.
+        let mut iter = SubFrameIter::new(&body[..4], LenPrefix::U8, 4);
+        assert_eq!(iter.by_ref().count(), 4);
+        assert!(iter.is_complete());
+    }
+}
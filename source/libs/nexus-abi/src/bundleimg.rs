@@ -0,0 +1,152 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: `NXBI` bundle image decoders plus a streaming builder (feature `alloc`)
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: 2 host unit tests (multi-entry round trip, field/count overflow)
+//!
+//! The format and its decoders live in `nexus_wire::bundleimg` and are re-exported here
+//! unchanged. [`Builder`] is the encoding side, so userspace (bundlemgrd) no longer
+//! hand-assembles image bytes: entries are encoded as they are added and [`Builder::finish`]
+//! prepends the header with the final count. `NXBI` v1 is the only version and files are
+//! its only entry kind, so there is no directory or symlink entry to add.
+
+pub use nexus_wire::bundleimg::*;
+
+#[cfg(any(feature = "alloc", test))]
+pub use builder::{BuildError, Builder};
+
+#[cfg(any(feature = "alloc", test))]
+mod builder {
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use nexus_wire::bundleimg::{KIND_FILE, MAGIC, VERSION};
+    use nexus_wire::codec::Writer;
+
+    /// Why an entry could not be added to a [`Builder`].
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum BuildError {
+        /// A field exceeds its length prefix (bundle/version 255, path 65535, data `u32::MAX`).
+        FieldTooLong,
+        /// The image already holds `u16::MAX` entries.
+        TooManyEntries,
+    }
+
+    /// Streaming `NXBI` v1 image builder.
+    #[derive(Clone, Debug, Default)]
+    pub struct Builder {
+        count: u16,
+        entries: Vec<u8>,
+    }
+
+    impl Builder {
+        /// Creates an empty image.
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Number of entries added so far.
+        pub fn len(&self) -> usize {
+            usize::from(self.count)
+        }
+
+        /// Whether no entry has been added.
+        pub fn is_empty(&self) -> bool {
+            self.count == 0
+        }
+
+        /// Appends a file entry; on error the image is left unchanged.
+        pub fn add_file(
+            &mut self,
+            bundle: &[u8],
+            version: &[u8],
+            path: &[u8],
+            data: &[u8],
+        ) -> Result<(), BuildError> {
+            let count = self.count.checked_add(1).ok_or(BuildError::TooManyEntries)?;
+            let len = 1 + bundle.len() + 1 + version.len() + 2 + path.len() + 2 + 4 + data.len();
+            let mut entry = vec![0u8; len];
+            let mut w = Writer::new(&mut entry);
+            w.put_len8_bytes(bundle, 0, u8::MAX as usize)
+                .and_then(|()| w.put_len8_bytes(version, 0, u8::MAX as usize))
+                .and_then(|()| w.put_len16_bytes(path, 0, u16::MAX as usize))
+                .and_then(|()| w.put_u16le(KIND_FILE))
+                .and_then(|()| w.put_len32_bytes(data, 0, u32::MAX as usize))
+                .ok_or(BuildError::FieldTooLong)?;
+            self.entries.extend_from_slice(&entry);
+            self.count = count;
+            Ok(())
+        }
+
+        /// Emits the image: header with the entry count, then the entries in order.
+        pub fn finish(self) -> Vec<u8> {
+            let mut out = Vec::with_capacity(7 + self.entries.len());
+            out.extend_from_slice(&MAGIC);
+            out.push(VERSION);
+            out.extend_from_slice(&self.count.to_le_bytes());
+            out.extend_from_slice(&self.entries);
+            out
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `(bundle, version, path, data)`.
+    type File<'a> = (&'a [u8], &'a [u8], &'a [u8], &'a [u8]);
+
+    #[test]
+    fn multi_entry_image_round_trips() {
+        let files: [File<'_>; 3] = [
+            (b"system", b"1.0.0", b"build.prop", b"ro.nexus.build=dev\n"),
+            (b"system", b"1.0.0", b"etc/empty", b""),
+            (b"launcher", b"2.1", b"manifest.nxb", &[0, 1, 2, 0xff]),
+        ];
+        let mut builder = Builder::new();
+        for (bundle, version, path, data) in files {
+            builder.add_file(bundle, version, path, data).unwrap();
+        }
+        assert_eq!(builder.len(), 3);
+        let img = builder.finish();
+
+        let (count, mut off) = decode_header(&img).unwrap();
+        assert_eq!(count, 3);
+        for (bundle, version, path, data) in files {
+            let entry = decode_next(&img, &mut off).unwrap();
+            assert_eq!((entry.bundle, entry.version, entry.path), (bundle, version, path));
+            assert_eq!((entry.kind, entry.data), (KIND_FILE, data));
+        }
+        assert_eq!(off, img.len());
+
+        // Byte-identical to the hand-assembled bring-up image.
+        let mut golden = Builder::new();
+        golden.add_file(b"system", b"1.0.0", b"build.prop", b"ro.nexus.build=dev\n").unwrap();
+        let golden = golden.finish();
+        assert_eq!(&golden[..7], &[b'N', b'X', b'B', b'I', 1, 1, 0]);
+        assert_eq!(golden.len(), 7 + 7 + 6 + 12 + 2 + 4 + 19);
+        assert_eq!(decode_header(&Builder::new().finish()), Some((0, 7)));
+    }
+
+    #[test]
+    fn test_reject_field_or_count_overflow() {
+        let mut builder = Builder::new();
+        let long = [b'x'; 256];
+        assert_eq!(builder.add_file(&long, b"1", b"p", b""), Err(BuildError::FieldTooLong));
+        assert_eq!(builder.add_file(b"b", &long, b"p", b""), Err(BuildError::FieldTooLong));
+        let long_path = alloc::vec![b'p'; u16::MAX as usize + 1];
+        assert_eq!(builder.add_file(b"b", b"1", &long_path, b""), Err(BuildError::FieldTooLong));
+        assert!(builder.is_empty());
+        assert_eq!(builder.clone().finish(), [b'N', b'X', b'B', b'I', 1, 0, 0]);
+
+        for _ in 0..u16::MAX {
+            builder.add_file(b"", b"", b"", b"").unwrap();
+        }
+        assert_eq!(builder.add_file(b"", b"", b"", b""), Err(BuildError::TooManyEntries));
+        assert_eq!(decode_header(&builder.finish()), Some((u16::MAX, 7)));
+    }
+}
@@ -0,0 +1,73 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: Constant-time byte comparison for security-sensitive frame fields
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: 3 host unit tests (equal, unequal, different lengths)
+//!
+//! `==` on slices returns at the first differing byte, so how long a comparison takes tells
+//! a caller how much of its guess was right. [`ct_eq`] looks at every byte whatever it
+//! finds: for inputs of equal length, the time taken does not depend on their contents.
+//!
+//! Use it wherever a peer-supplied value is checked against a secret: auth and capability
+//! tokens (policyd), MACs, key material. Nonces used only to pair replies with requests
+//! (`nonce`, payload nonces) are not secret and keep using `==`, as do opcodes, keys and
+//! other public fields. `ct_eq` only keeps the comparison itself from leaking; the caller
+//! must not branch on secret data before or after it.
+//!
+//! Lengths are not hidden: the scan runs over `expected`, so its duration follows
+//! `expected.len()` and not the length the peer sent, and a length mismatch costs the same
+//! as a content mismatch. Tokens should have a fixed length so that nothing is learned
+//! from it either. The compiler is kept from short-circuiting the loop with
+//! `core::hint::black_box`, which is best effort and not a guarantee across targets.
+
+use core::hint::black_box;
+
+/// Whether `received` equals `expected`, comparing every byte of `expected`.
+///
+/// Put the secret or reference value in `expected` and the peer-supplied bytes in
+/// `received`.
+pub fn ct_eq(expected: &[u8], received: &[u8]) -> bool {
+    let mut diff = u8::from(expected.len() != received.len());
+    for (i, &byte) in expected.iter().enumerate() {
+        let other = received.get(i).copied().unwrap_or(0);
+        diff = black_box(diff | (byte ^ other));
+    }
+    diff == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn equal_inputs_compare_equal() {
+        assert!(ct_eq(b"", b""));
+        assert!(ct_eq(b"policyd-token-0001", b"policyd-token-0001"));
+        let token = [0xA5u8; 32];
+        assert!(ct_eq(&token, &token.clone()));
+    }
+
+    #[test]
+    fn test_reject_unequal_inputs_of_the_same_length() {
+        let token = [0x5Au8; 32];
+        for i in 0..token.len() {
+            let mut guess = token;
+            guess[i] ^= 0x01;
+            assert!(!ct_eq(&token, &guess), "flip at {i}");
+        }
+        assert!(!ct_eq(&[0u8; 4], &[0xFF; 4]));
+    }
+
+    #[test]
+    fn test_reject_inputs_of_different_lengths() {
+        assert!(!ct_eq(b"token", b"token\0"));
+        assert!(!ct_eq(b"token\0", b"token"));
+        // Missing bytes are not treated as matching zeros.
+        assert!(!ct_eq(&[0u8; 4], &[0u8; 3]));
+        assert!(!ct_eq(b"", b"x"));
+        assert!(!ct_eq(b"x", b""));
+    }
+}
@@ -0,0 +1,139 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: Canonical error-reply frame (`OP_ERR`) for generic dispatchers
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: 3 host unit tests (code-only, with detail, malformed frames)
+//!
+//! Services report failures in their own shape (a status byte, a short reply, a
+//! malformed frame), so a generic client cannot tell an error from an unknown reply.
+//! A service may instead answer with an error frame in its own `MAGIC0 MAGIC1 VERSION OP`
+//! layout:
+//!
+//! `MAGIC0 MAGIC1 VERSION OP_ERR | code:u16 | detail_len:u8 | detail`
+//!
+//! `code` is little-endian and service-defined; `detail` is at most [`MAX_ERR_DETAIL_LEN`]
+//! bytes of human-readable context (ASCII by convention, never secrets). [`OP_ERR`] sits
+//! next to [`OP_HELLO`](crate::version::OP_HELLO) in the reserved top of the opcode
+//! space, so no data frame collides with it. The frame is opt-in: services that keep
+//! their own status replies are unaffected.
+
+/// Opcode a service reserves for the error frame.
+pub const OP_ERR: u8 = 0xFE;
+/// Bytes before the detail: magic, version, op, code and detail length.
+pub const ERR_HEADER_LEN: usize = 7;
+/// Longest detail an error frame carries.
+pub const MAX_ERR_DETAIL_LEN: usize = 64;
+/// Longest encoded error frame.
+pub const MAX_ERR_FRAME_LEN: usize = ERR_HEADER_LEN + MAX_ERR_DETAIL_LEN;
+
+/// A decoded error frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ErrFrame<'a> {
+    /// Protocol version byte of the frame.
+    pub version: u8,
+    /// Service-defined error code.
+    pub code: u16,
+    /// Optional context; empty for a code-only error.
+    pub detail: &'a [u8],
+}
+
+/// Writes an error frame for `magic`/`version` into `out` and returns its length.
+///
+/// Returns `None` when `detail` is longer than [`MAX_ERR_DETAIL_LEN`] or `out` is too small.
+pub fn encode_err(
+    magic: [u8; 2],
+    version: u8,
+    code: u16,
+    detail: &[u8],
+    out: &mut [u8],
+) -> Option<usize> {
+    if detail.len() > MAX_ERR_DETAIL_LEN {
+        return None;
+    }
+    let n = ERR_HEADER_LEN + detail.len();
+    let frame = out.get_mut(..n)?;
+    frame[..4].copy_from_slice(&[magic[0], magic[1], version, OP_ERR]);
+    frame[4..6].copy_from_slice(&code.to_le_bytes());
+    frame[6] = detail.len() as u8;
+    frame[ERR_HEADER_LEN..].copy_from_slice(detail);
+    Some(n)
+}
+
+/// Decodes an error frame for `magic`.
+///
+/// Returns `None` unless the frame carries `magic` and [`OP_ERR`], its detail length is at
+/// most [`MAX_ERR_DETAIL_LEN`], and its total length matches that detail length exactly.
+pub fn decode_err(frame: &[u8], magic: [u8; 2]) -> Option<ErrFrame<'_>> {
+    let (header, detail) = frame.split_at_checked(ERR_HEADER_LEN)?;
+    let [m0, m1, version, op, c0, c1, detail_len] = *header else {
+        return None;
+    };
+    if [m0, m1] != magic || op != OP_ERR {
+        return None;
+    }
+    let detail_len = usize::from(detail_len);
+    if detail_len > MAX_ERR_DETAIL_LEN || detail.len() != detail_len {
+        return None;
+    }
+    Some(ErrFrame { version, code: u16::from_le_bytes([c0, c1]), detail })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAGIC: [u8; 2] = *b"MT";
+
+    #[test]
+    fn code_only_error_round_trips() {
+        let mut buf = [0u8; MAX_ERR_FRAME_LEN];
+        let n = encode_err(MAGIC, 1, 0x0102, b"", &mut buf).unwrap();
+        assert_eq!(&buf[..n], &[b'M', b'T', 1, OP_ERR, 0x02, 0x01, 0]);
+        assert_eq!(
+            decode_err(&buf[..n], MAGIC),
+            Some(ErrFrame { version: 1, code: 0x0102, detail: b"" })
+        );
+    }
+
+    #[test]
+    fn error_with_detail_round_trips() {
+        let mut buf = [0u8; MAX_ERR_FRAME_LEN];
+        let n = encode_err(MAGIC, 2, 7, b"quota exceeded", &mut buf).unwrap();
+        assert_eq!(n, ERR_HEADER_LEN + 14);
+        let err = decode_err(&buf[..n], MAGIC).unwrap();
+        assert_eq!((err.version, err.code, err.detail), (2, 7, &b"quota exceeded"[..]));
+
+        let longest = [b'x'; MAX_ERR_DETAIL_LEN];
+        let n = encode_err(MAGIC, 1, 9, &longest, &mut buf).unwrap();
+        assert_eq!(n, MAX_ERR_FRAME_LEN);
+        assert_eq!(decode_err(&buf[..n], MAGIC).map(|e| e.detail.len()), Some(MAX_ERR_DETAIL_LEN));
+    }
+
+    #[test]
+    fn test_reject_malformed_err_frames() {
+        let mut buf = [0u8; MAX_ERR_FRAME_LEN + 1];
+        assert_eq!(encode_err(MAGIC, 1, 1, &[0u8; MAX_ERR_DETAIL_LEN + 1], &mut buf), None);
+        assert_eq!(encode_err(MAGIC, 1, 1, b"detail", &mut buf[..8]), None);
+
+        let n = encode_err(MAGIC, 1, 3, b"io", &mut buf).unwrap();
+        let frame = &buf[..n];
+        assert_eq!(decode_err(&frame[..n - 1], MAGIC), None);
+        assert_eq!(decode_err(&frame[..4], MAGIC), None);
+        assert_eq!(decode_err(&buf[..n + 1], MAGIC), None);
+        assert_eq!(decode_err(frame, *b"XX"), None);
+
+        let mut not_err = [0u8; 9];
+        not_err.copy_from_slice(frame);
+        not_err[3] = 0x01;
+        assert_eq!(decode_err(&not_err, MAGIC), None);
+
+        // A detail length over the bound is rejected even when the bytes are present.
+        let mut oversized = [0u8; ERR_HEADER_LEN + MAX_ERR_DETAIL_LEN + 1];
+        oversized[..4].copy_from_slice(&[b'M', b'T', 1, OP_ERR]);
+        oversized[6] = (MAX_ERR_DETAIL_LEN + 1) as u8;
+        assert_eq!(decode_err(&oversized, MAGIC), None);
+    }
+}
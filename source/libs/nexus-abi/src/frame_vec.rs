@@ -0,0 +1,148 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: `Vec`-returning builders for variable-length service frames (feature `alloc`)
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: 3 host unit tests (vec form equals slice form, rejects match)
+//!
+//! Fixed-size frames already come back as arrays (`encode_route_rsp`, the policyd
+//! responses, ...). The variable ones encode into a caller buffer; the helpers here
+//! size that buffer from the inputs, call the slice encoder and trim the result.
+//! Bytes and rejections are exactly the slice encoder's (`None` on invalid input).
+//! There is no route *list* frame in the routing protocol, so routing has only
+//! [`encode_route_get_vec`].
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::{imed, policyd, routing, settingsd};
+
+/// Runs `encode` over a zeroed buffer of `capacity` bytes and keeps what it wrote.
+fn encode_into_vec(
+    capacity: usize,
+    encode: impl FnOnce(&mut [u8]) -> Option<usize>,
+) -> Option<Vec<u8>> {
+    let mut out = vec![0u8; capacity];
+    let len = encode(&mut out)?;
+    out.truncate(len);
+    Some(out)
+}
+
+/// [`routing::encode_route_get`] into a `Vec`.
+pub fn encode_route_get_vec(name: &[u8]) -> Option<Vec<u8>> {
+    encode_into_vec(5 + name.len(), |out| routing::encode_route_get(name, out))
+}
+
+/// [`policyd::encode_route_v2`] into a `Vec`.
+pub fn encode_policyd_route_v2_vec(nonce: u32, requester: &[u8], target: &[u8]) -> Option<Vec<u8>> {
+    encode_into_vec(10 + requester.len() + target.len(), |out| {
+        policyd::encode_route_v2(nonce, requester, target, out)
+    })
+}
+
+/// [`policyd::encode_exec_v2`] into a `Vec`.
+pub fn encode_policyd_exec_v2_vec(nonce: u32, requester: &[u8], image_id: u8) -> Option<Vec<u8>> {
+    encode_into_vec(10 + requester.len(), |out| {
+        policyd::encode_exec_v2(nonce, requester, image_id, out)
+    })
+}
+
+/// [`policyd::encode_exec_batch_v3`] into a `Vec`.
+pub fn encode_policyd_exec_batch_v3_vec(
+    nonce: u32,
+    requester_id: u64,
+    image_ids: &[u8],
+) -> Option<Vec<u8>> {
+    encode_into_vec(17 + image_ids.len(), |out| {
+        policyd::encode_exec_batch_v3(nonce, requester_id, image_ids, out)
+    })
+}
+
+/// [`settingsd::encode_get_req`] into a `Vec`.
+pub fn encode_settings_get_vec(key: &str) -> Option<Vec<u8>> {
+    encode_into_vec(5 + key.len(), |out| settingsd::encode_get_req(key, out))
+}
+
+/// [`settingsd::encode_set_req`] into a `Vec`.
+pub fn encode_settings_set_vec(key: &str, value: &str) -> Option<Vec<u8>> {
+    encode_into_vec(7 + key.len() + value.len(), |out| settingsd::encode_set_req(key, value, out))
+}
+
+/// [`settingsd::encode_watch_req`] into a `Vec`.
+pub fn encode_settings_watch_vec(prefix: &str) -> Option<Vec<u8>> {
+    encode_into_vec(5 + prefix.len(), |out| settingsd::encode_watch_req(prefix, out))
+}
+
+/// [`imed::encode_candidate_list`] into a `Vec` (the list payload, not a whole frame).
+pub fn encode_candidate_list_vec(candidates: &[&str]) -> Option<Vec<u8>> {
+    let capacity = candidates.iter().map(|c| 1 + c.len()).sum::<usize>();
+    encode_into_vec(capacity, |out| imed::encode_candidate_list(candidates, out))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slice_form(encode: impl FnOnce(&mut [u8]) -> Option<usize>) -> Option<Vec<u8>> {
+        let mut buf = [0u8; 1024];
+        let len = encode(&mut buf)?;
+        Some(buf[..len].to_vec())
+    }
+
+    #[test]
+    fn vec_form_equals_slice_form() {
+        assert_eq!(
+            encode_route_get_vec(b"vfsd"),
+            slice_form(|out| routing::encode_route_get(b"vfsd", out))
+        );
+        assert_eq!(
+            encode_policyd_route_v2_vec(9, b"execd", b"vfsd"),
+            slice_form(|out| policyd::encode_route_v2(9, b"execd", b"vfsd", out))
+        );
+        assert_eq!(
+            encode_policyd_exec_v2_vec(9, b"execd", 3),
+            slice_form(|out| policyd::encode_exec_v2(9, b"execd", 3, out))
+        );
+        assert_eq!(
+            encode_policyd_exec_batch_v3_vec(9, 42, &[1, 2, 3]),
+            slice_form(|out| policyd::encode_exec_batch_v3(9, 42, &[1, 2, 3], out))
+        );
+        assert_eq!(
+            encode_settings_get_vec(settingsd::KEY_UI_LOCALE),
+            slice_form(|out| settingsd::encode_get_req(settingsd::KEY_UI_LOCALE, out))
+        );
+        assert_eq!(
+            encode_settings_set_vec("ui.shell.mode", "desktop"),
+            slice_form(|out| settingsd::encode_set_req("ui.shell.mode", "desktop", out))
+        );
+        assert_eq!(
+            encode_settings_watch_vec("ui."),
+            slice_form(|out| settingsd::encode_watch_req("ui.", out))
+        );
+        assert_eq!(
+            encode_candidate_list_vec(&["ni", "hao"]),
+            slice_form(|out| imed::encode_candidate_list(&["ni", "hao"], out))
+        );
+    }
+
+    #[test]
+    fn vec_form_has_no_slack() {
+        let frame = encode_route_get_vec(b"samgrd").unwrap();
+        assert_eq!(frame.len(), 5 + b"samgrd".len());
+        assert_eq!(routing::decode_route_get(&frame), Some(&b"samgrd"[..]));
+        assert_eq!(encode_settings_set_vec("k", "").unwrap().len(), 8);
+    }
+
+    #[test]
+    fn test_reject_same_inputs_as_slice_form() {
+        let long = [b'n'; routing::MAX_SERVICE_NAME_LEN + 1];
+        assert_eq!(encode_route_get_vec(&long), None);
+        assert_eq!(encode_route_get_vec(b""), None);
+        assert_eq!(encode_policyd_route_v2_vec(1, b"", b"vfsd"), None);
+        assert_eq!(encode_policyd_exec_batch_v3_vec(1, 2, &[]), None);
+        assert_eq!(encode_settings_get_vec(""), None);
+        assert_eq!(encode_candidate_list_vec(&[""]), None);
+    }
+}
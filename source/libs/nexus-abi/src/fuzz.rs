@@ -0,0 +1,158 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: Single fuzz entry point over the public protocol decoders (feature `fuzz`)
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: 2 host unit tests (random/near-valid corpus, valid frames recognized)
+//!
+//! [`fuzz_all_decoders`] hands one input to every decoder of the routing, policyd,
+//! bundlemgrd, bundleimg and policy protocols. A cargo-fuzz / libFuzzer target is one line:
+//!
+//! ```ignore
+//! fuzz_target!(|data: &[u8]| { let _ = nexus_abi::fuzz::fuzz_all_decoders(data); });
+//! ```
+//!
+//! No decoder may panic on any input; a panic is the finding, and the fuzzer reports it
+//! with the input. The returned names say which decoders accepted the input, so a
+//! corpus can be checked for coverage. A decoder added to one of these protocols is
+//! added to [`DECODERS`] too.
+
+use alloc::vec::Vec;
+
+use crate::{bundleimg, bundlemgrd, policy, policyd, routing};
+
+/// `(name, decoder)`: the decoder answers whether it accepted the input.
+type Decoder = (&'static str, fn(&[u8]) -> bool);
+
+/// Every decoder [`fuzz_all_decoders`] runs, in report order.
+pub const DECODERS: &[Decoder] = &[
+    ("routing::decode_route_get", |f| routing::decode_route_get(f).is_some()),
+    ("routing::decode_route_rsp", |f| routing::decode_route_rsp(f).is_some()),
+    ("routing::decode_route_rsp_v2", |f| routing::decode_route_rsp_v2(f).is_some()),
+    ("policyd::decode_route_v2", |f| policyd::decode_route_v2(f).is_some()),
+    ("policyd::decode_exec_v2", |f| policyd::decode_exec_v2(f).is_some()),
+    ("policyd::decode_route_v3_id", |f| policyd::decode_route_v3_id(f).is_some()),
+    ("policyd::decode_exec_v3_id", |f| policyd::decode_exec_v3_id(f).is_some()),
+    ("policyd::decode_exec_batch_v3", |f| policyd::decode_exec_batch_v3(f).is_some()),
+    ("policyd::decode_exec_batch_rsp_v3", |f| policyd::decode_exec_batch_rsp_v3(f).is_some()),
+    ("policyd::decode_abi_profile_get_v2", |f| policyd::decode_abi_profile_get_v2(f).is_some()),
+    ("policyd::decode_abi_profile_rsp_v2", |f| policyd::decode_abi_profile_rsp_v2(f).is_some()),
+    ("policyd::decode_rsp_v2_or_v3_reason", |f| policyd::decode_rsp_v2_or_v3_reason(f).is_some()),
+    ("policyd::decode_rsp_v2_or_v3", |f| policyd::decode_rsp_v2_or_v3(f).is_some()),
+    ("policyd::decode_rsp_v2", |f| policyd::decode_rsp_v2(f).is_some()),
+    ("bundlemgrd::decode_request_op", |f| bundlemgrd::decode_request_op(f).is_some()),
+    ("bundlemgrd::decode_list_apps_header", |f| bundlemgrd::decode_list_apps_header(f).is_some()),
+    ("bundlemgrd::decode_fetch_image_rsp_checked", |f| {
+        bundlemgrd::decode_fetch_image_rsp_checked(f).is_some()
+    }),
+    ("bundlemgrd::decode_list_rsp", |f| bundlemgrd::decode_list_rsp(f).is_some()),
+    ("bundlemgrd::decode_fetch_image_rsp", |f| bundlemgrd::decode_fetch_image_rsp(f).is_some()),
+    ("bundlemgrd::decode_set_active_slot_rsp", |f| {
+        bundlemgrd::decode_set_active_slot_rsp(f).is_some()
+    }),
+    ("bundlemgrd::decode_get_payload", |f| bundlemgrd::decode_get_payload(f).is_some()),
+    ("bundlemgrd::decode_payload_header", |f| bundlemgrd::decode_payload_header(f).is_some()),
+    ("bundleimg::decode_header", |f| bundleimg::decode_header(f).is_some()),
+    ("bundleimg::decode_next", decode_whole_image),
+    ("policy::decode_exec_check", |f| policy::decode_exec_check(f).is_some()),
+    ("policy::decode_exec_check_rsp", |f| policy::decode_exec_check_rsp(f).is_some()),
+];
+
+/// Runs every decoder in [`DECODERS`] on `data`; returns the names of those that accepted it.
+pub fn fuzz_all_decoders(data: &[u8]) -> Vec<&'static str> {
+    DECODERS.iter().filter(|(_, decode)| decode(data)).map(|(name, _)| *name).collect()
+}
+
+/// Walks an `NXBI` image the way a consumer does: the header, then `count` entries.
+fn decode_whole_image(image: &[u8]) -> bool {
+    let Some((count, mut off)) = bundleimg::decode_header(image) else {
+        return false;
+    };
+    (0..count).all(|_| bundleimg::decode_next(image, &mut off).is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    /// Valid frames, each with one decoder that must accept it.
+    fn valid_frames() -> Vec<(&'static str, Vec<u8>)> {
+        fn sized(encode: impl FnOnce(&mut [u8]) -> Option<usize>) -> Vec<u8> {
+            let mut buf = [0u8; 64];
+            let n = encode(&mut buf).unwrap();
+            buf[..n].to_vec()
+        }
+        let route_get = sized(|buf| routing::encode_route_get(b"vfsd", buf));
+        let route_v2 = sized(|buf| policyd::encode_route_v2(7, b"netd", b"vfsd", buf));
+        let exec_batch = sized(|buf| policyd::encode_exec_batch_v3(7, 42, &[1, 2], buf));
+        let exec_check = sized(|buf| policy::encode_exec_check(9, b"selftest", 2, buf));
+        let get_payload = sized(|buf| bundlemgrd::encode_get_payload(b"launcher", buf));
+        let fetch = sized(|buf| bundlemgrd::encode_fetch_image_rsp_checked(0, b"img", buf));
+        let mut list = [0u8; 4];
+        bundlemgrd::encode_list(&mut list);
+        let mut image = bundleimg::Builder::new();
+        image.add_file(b"system", b"1.0.0", b"build.prop", b"ro.x=1\n").unwrap();
+        let reason = policyd::ReasonCode::Quota;
+        vec![
+            ("routing::decode_route_get", route_get),
+            ("routing::decode_route_rsp", routing::encode_route_rsp(0, 3, 4).to_vec()),
+            ("routing::decode_route_rsp_v2", routing::encode_route_rsp_v2(0, 3, 4, 9).to_vec()),
+            ("policyd::decode_route_v2", route_v2),
+            ("policyd::decode_exec_batch_v3", exec_batch),
+            (
+                "policyd::decode_rsp_v2_or_v3_reason",
+                policyd::encode_rsp_v3_reason(policyd::OP_ROUTE, 7, 1, reason).to_vec(),
+            ),
+            ("policyd::decode_rsp_v2", policyd::encode_rsp_v2(policyd::OP_ROUTE, 7, 0).to_vec()),
+            ("bundlemgrd::decode_request_op", list.to_vec()),
+            ("bundlemgrd::decode_get_payload", get_payload),
+            ("bundlemgrd::decode_fetch_image_rsp_checked", fetch),
+            ("bundlemgrd::decode_payload_header", bundlemgrd::encode_payload_header(1, 5).to_vec()),
+            ("bundleimg::decode_next", image.finish()),
+            ("policy::decode_exec_check", exec_check),
+            ("policy::decode_exec_check_rsp", policy::encode_exec_check_rsp(9, 0).to_vec()),
+        ]
+    }
+
+    #[test]
+    fn valid_frames_are_recognized() {
+        for (decoder, frame) in valid_frames() {
+            assert!(fuzz_all_decoders(&frame).contains(&decoder), "{decoder} rejected its frame");
+        }
+        assert!(fuzz_all_decoders(&[]).is_empty());
+    }
+
+    #[test]
+    fn random_and_near_valid_frames_never_panic() {
+        // xorshift64: a fixed, dependency-free corpus.
+        let mut state = 0x9E37_79B9_7F4A_7C15u64;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        for _ in 0..2_000 {
+            let len = (next() % 96) as usize;
+            let frame: Vec<u8> = (0..len).map(|_| next() as u8).collect();
+            let _ = fuzz_all_decoders(&frame);
+        }
+        for (_, frame) in valid_frames() {
+            for cut in 0..frame.len() {
+                let _ = fuzz_all_decoders(&frame[..cut]);
+            }
+            for _ in 0..64 {
+                let mut mutated = frame.clone();
+                let at = (next() as usize) % mutated.len();
+                mutated[at] = next() as u8;
+                let _ = fuzz_all_decoders(&mutated);
+                mutated.push(next() as u8);
+                let _ = fuzz_all_decoders(&mutated);
+            }
+        }
+    }
+}
@@ -10,7 +10,7 @@
 
 //! CONTEXT: Shared ABI definitions exposed to userland crates
 //! OWNERS: @runtime
-//! PUBLIC API: MsgHeader, IpcError; OS-only syscalls: yield_, spawn, exit, wait, cap_transfer, as_*, vmo_*, debug_*
+//! PUBLIC API: MsgHeader, IpcError, ReplyToken, ReplyChannel, opcode::{Service, classify}, nonce::{encode_with_nonce, decode_nonce}, ct::ct_eq, version::{negotiate, encode_hello, decode_hello}, errframe::{encode_err, decode_err}, batch::SubFrameIter, supervise::{WaitOutcome, wait_deadline}, deadline_none, deadline_in_with, provenance_name_from, frame_vec, route::{try_route, RouteError}, loopback::{loopback_pair, LoopbackEndpoint} and bundleimg::Builder (feature `alloc`); fuzz::fuzz_all_decoders (feature `fuzz`); DebugLineBuffer, buffered_putc, buffered_flush; OS-only syscalls: yield_, spawn, spawn_supervised, exit, wait, try_wait, cap_transfer, cap_rights, ipc_endpoint_reassign, ipc_recv_into_vmo, deadline_in, provenance_name, as_*, vmo_*, debug_*
 //! DEPENDS_ON: no_std (OS), riscv ecall asm (OS), bitflags
 //! INVARIANTS: Header is 16 bytes LE; userspace wrappers map to stable kernel syscall IDs
 //! ADR: docs/adr/0016-kernel-libs-architecture.md
@@ -33,6 +33,9 @@ pub enum IpcError {
     TimedOut,
     /// Not enough resources to complete the IPC operation (e.g. receiver cap table full).
     NoSpace,
+    /// Received payload exceeds the buffer and truncation was not requested; the header
+    /// reports the required length and the message stays queued.
+    Truncated,
     /// IPC is not supported for this configuration.
     Unsupported,
 }
@@ -45,7 +48,7 @@ pub struct MsgHeader {
     pub src: u32,
     /// Destination endpoint identifier.
     pub dst: u32,
-    /// Message opcode.
+    /// Message opcode (service ranges: [`opcode`]).
     pub ty: u16,
     /// Transport flags.
     pub flags: u16,
@@ -64,6 +67,11 @@ pub mod ipc_hdr {
     /// - On receive, `MsgHeader.src` is overwritten with the **newly allocated capability slot**
     ///   in the receiver.
     pub const CAP_MOVE: u16 = 1 << 0;
+
+    /// `MsgHeader.ty` bit (not a `flags` bit): a 4-byte correlation nonce leads the payload.
+    ///
+    /// See [`crate::nonce`]; service opcode ranges ([`crate::opcode`]) never use this bit.
+    pub const HDR_NONCE: u16 = 1 << 15;
 }
 
 // ADR-0051: service wire protocols live in the declarative SSOT crate
@@ -71,9 +79,12 @@ pub mod ipc_hdr {
 // paths compiling unchanged (transitional shim — consumers migrate to
 // `nexus_wire::<svc>` in a follow-up task).
 pub use nexus_wire::{
-    bundleimg, bundlemgrd, execd, imed, policy, policyd, routing, sessiond, settingsd, updated,
+    bundlemgrd, execd, imed, policy, policyd, routing, sessiond, settingsd, updated,
 };
 
+/// `NXBI` bundle images: the `nexus_wire` decoders plus [`bundleimg::Builder`] (feature `alloc`).
+pub mod bundleimg;
+
 /// Computes a stable service identifier from the UTF-8 service name bytes.
 ///
 /// This is the userspace mirror of the kernel's `BootstrapInfo.service_id` derivation.
@@ -125,9 +136,60 @@ pub mod slot_probe;
 /// Deterministic userspace ABI syscall filter profile helpers.
 pub mod abi_filter;
 
+/// One-shot ownership of CAP_MOVE reply capabilities.
+pub mod reply;
+pub use reply::{KernelReply, MovedReply, ReplyChannel, ReplyToken, ReplyTransport};
+
+/// Per-service `MsgHeader.ty` ranges and the misrouting classifier.
+pub mod opcode;
+
+/// Constant-time comparison for tokens and other secrets in frames.
+pub mod ct;
+pub use ct::ct_eq;
+
+/// Transport-level request/reply correlation nonce.
+pub mod nonce;
+pub use nonce::{decode_nonce, encode_with_nonce};
+
+/// Optional `OP_HELLO` frame version negotiation.
+pub mod version;
+
+/// Canonical `OP_ERR` error-reply frame.
+pub mod errframe;
+
+/// Bounded iteration over length-prefixed sub-frames of a batch request.
+pub mod batch;
+
+/// Deadline-bounded spawn + wait for supervisors.
+pub mod supervise;
+#[cfg(nexus_env = "os")]
+pub use supervise::spawn_supervised;
+pub use supervise::WaitOutcome;
+
+#[cfg(any(feature = "alloc", test))]
+extern crate alloc;
+
+/// `Vec`-returning builders for variable-length service frames.
+#[cfg(any(feature = "alloc", test))]
+pub mod frame_vec;
+
+/// Typed `ROUTE_GET` round trip over caller-supplied transport closures.
+#[cfg(any(feature = "alloc", test))]
+pub mod route;
+#[cfg(any(feature = "alloc", test))]
+pub use route::{try_route, RouteError};
+
+/// In-process frame transport for host tests of byte protocols.
+#[cfg(any(feature = "alloc", test))]
+pub mod loopback;
+
+/// Fuzz entry point running every protocol decoder on one input.
+#[cfg(any(feature = "fuzz", test))]
+pub mod fuzz;
+
 #[cfg(test)]
 mod tests {
-    use super::{IpcRecvV2Desc, MsgHeader};
+    use super::{IpcRecvAnyDesc, IpcRecvV2Desc, IpcRecvVmoDesc, MsgHeader};
     use core::mem::{align_of, size_of};
 
     #[test]
@@ -180,4 +242,84 @@ mod tests {
         assert_eq!(offset_of!(IpcRecvV2Desc, _pad1), 52);
         assert_eq!(offset_of!(IpcRecvV2Desc, deadline_ns), 56);
     }
+
+    #[test]
+    fn recv_any_desc_layout_round_trips() {
+        use core::mem::offset_of;
+
+        assert_eq!(size_of::<IpcRecvAnyDesc>(), IpcRecvAnyDesc::LEN);
+        assert_eq!(align_of::<IpcRecvAnyDesc>(), 8);
+        assert_eq!(offset_of!(IpcRecvAnyDesc, prev_index), 12);
+        assert_eq!(offset_of!(IpcRecvAnyDesc, slots), 16);
+        assert_eq!(offset_of!(IpcRecvAnyDesc, header_out_ptr), 48);
+        assert_eq!(offset_of!(IpcRecvAnyDesc, which_out_ptr), 72);
+        assert_eq!(offset_of!(IpcRecvAnyDesc, deadline_ns), 80);
+
+        let mut desc = IpcRecvAnyDesc::new(&[3, 9], 1, 0x0102_0304_0506_0708).unwrap();
+        desc.which_out_ptr = 0xdead_beef;
+        let bytes = desc.to_le_bytes();
+        // Offsets the kernel reads (syscall/api/ipc_recv_any.rs).
+        assert_eq!(&bytes[..4], b"ARXN");
+        assert_eq!(&bytes[8..24], &[2, 0, 0, 0, 1, 0, 0, 0, 3, 0, 0, 0, 9, 0, 0, 0]);
+        assert_eq!(&bytes[72..76], &[0xef, 0xbe, 0xad, 0xde]);
+        assert_eq!(&bytes[80..], &[8, 7, 6, 5, 4, 3, 2, 1]);
+        assert_eq!(IpcRecvAnyDesc::from_le_bytes(bytes), desc);
+
+        assert!(IpcRecvAnyDesc::new(&[], 0, 0).is_none());
+        assert!(IpcRecvAnyDesc::new(&[0; super::IPC_RECV_ANY_MAX_SLOTS + 1], 0, 0).is_none());
+        let (mut header, mut which) = (MsgHeader::new(0, 0, 0, 0, 0), 0);
+        assert_eq!(
+            super::ipc_recv_any(&[1, 2], &mut header, &mut [0; 4], &mut which, 0),
+            Err(super::IpcError::Unsupported)
+        );
+    }
+
+    #[test]
+    fn recv_vmo_desc_layout_round_trips() {
+        use core::mem::offset_of;
+
+        assert_eq!(size_of::<IpcRecvVmoDesc>(), IpcRecvVmoDesc::LEN);
+        assert_eq!(align_of::<IpcRecvVmoDesc>(), 8);
+        assert_eq!(offset_of!(IpcRecvVmoDesc, vmo_slot), 12);
+        assert_eq!(offset_of!(IpcRecvVmoDesc, header_out_ptr), 16);
+        assert_eq!(offset_of!(IpcRecvVmoDesc, vmo_offset), 24);
+        assert_eq!(offset_of!(IpcRecvVmoDesc, max), 32);
+        assert_eq!(offset_of!(IpcRecvVmoDesc, deadline_ns), 40);
+
+        let mut desc = IpcRecvVmoDesc::new(3, 7, 0x1000, 0x2_0000, 0x0102_0304_0506_0708);
+        desc.header_out_ptr = 0xdead_beef;
+        let bytes = desc.to_le_bytes();
+        // Offsets the kernel reads (syscall/api/ipc_recv_vmo.rs).
+        assert_eq!(&bytes[..4], b"VRXN");
+        assert_eq!(&bytes[8..16], &[3, 0, 0, 0, 7, 0, 0, 0]);
+        assert_eq!(&bytes[16..20], &[0xef, 0xbe, 0xad, 0xde]);
+        assert_eq!(&bytes[24..27], &[0, 0x10, 0]);
+        assert_eq!(&bytes[32..35], &[0, 0, 2]);
+        assert_eq!(&bytes[40..], &[8, 7, 6, 5, 4, 3, 2, 1]);
+        assert_eq!(IpcRecvVmoDesc::from_le_bytes(bytes), desc);
+
+        let mut header = MsgHeader::new(0, 0, 0, 0, 0);
+        assert_eq!(
+            super::ipc_recv_into_vmo(1, 2, 0, 64, &mut header, 0),
+            Err(super::IpcError::Unsupported)
+        );
+    }
+
+    #[test]
+    fn test_reject_ipc_peek_on_host() {
+        let mut header = MsgHeader::new(0, 0, 0, 0, 0);
+        assert_eq!(super::ipc_peek_v1(0, &mut header), Err(super::IpcError::Unsupported));
+        assert_eq!(header, MsgHeader::new(0, 0, 0, 0, 0));
+    }
+
+    #[test]
+    fn route_rsp_v2_carries_kernel_service_id() {
+        use super::routing::{decode_route_rsp_v2, encode_route_rsp_v2, STATUS_OK};
+
+        let id = super::service_id_from_name(b"samgrd");
+        let frame = encode_route_rsp_v2(STATUS_OK, 5, 6, id);
+        assert_eq!(decode_route_rsp_v2(&frame), Some((STATUS_OK, 5, 6, id)));
+        // FNV-1a of "samgrd": pinned so the userspace mirror cannot drift from the kernel.
+        assert_eq!(id, 0x75f6_4afe_c44c_d4d7);
+    }
 }
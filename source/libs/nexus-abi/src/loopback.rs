@@ -0,0 +1,138 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: In-process frame transport for host tests of byte protocols (feature `alloc`)
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: 3 host unit tests (echo round trip, `try_route` over the pair, closed/full queues)
+//!
+//! `nexus_ipc::LoopbackClient` needs std and the `Client`/`Server` traits; a protocol
+//! author who only wants to push an encoded request through a handler and decode what comes
+//! back has no lighter option. [`loopback_pair`] returns two [`LoopbackEndpoint`]s joined by
+//! in-memory queues: what one `send`s the other `recv`s, whole frames in order.
+//! [`LoopbackEndpoint::serve_one`] runs a handler over the next request and sends its reply,
+//! so a test drives both sides from one thread without QEMU.
+//!
+//! Single-threaded and non-blocking: `recv` on an empty queue is `QueueEmpty`, never a wait.
+//! Each direction holds at most [`LOOPBACK_QUEUE_DEPTH`] frames (`QueueFull` beyond), and
+//! sending to a dropped peer is `NoSuchEndpoint`. Frames are opaque; no header, nonce or
+//! capability travels with them.
+
+use alloc::collections::VecDeque;
+use alloc::rc::{Rc, Weak};
+use alloc::vec::Vec;
+use core::cell::RefCell;
+
+use crate::{IpcError, Result};
+
+/// Frames one direction of a [`loopback_pair`] holds before `send` fails.
+pub const LOOPBACK_QUEUE_DEPTH: usize = 64;
+
+type Queue = RefCell<VecDeque<Vec<u8>>>;
+
+/// One end of an in-process frame transport; see [`loopback_pair`].
+pub struct LoopbackEndpoint {
+    inbox: Rc<Queue>,
+    peer_inbox: Weak<Queue>,
+}
+
+/// Two connected endpoints: frames sent on one are received on the other.
+pub fn loopback_pair() -> (LoopbackEndpoint, LoopbackEndpoint) {
+    let a = Rc::new(RefCell::new(VecDeque::new()));
+    let b = Rc::new(RefCell::new(VecDeque::new()));
+    let a_end = LoopbackEndpoint { inbox: a.clone(), peer_inbox: Rc::downgrade(&b) };
+    let b_end = LoopbackEndpoint { inbox: b, peer_inbox: Rc::downgrade(&a) };
+    (a_end, b_end)
+}
+
+impl LoopbackEndpoint {
+    /// Queues `frame` for the peer.
+    pub fn send(&self, frame: Vec<u8>) -> Result<()> {
+        let peer = self.peer_inbox.upgrade().ok_or(IpcError::NoSuchEndpoint)?;
+        let mut queue = peer.borrow_mut();
+        if queue.len() >= LOOPBACK_QUEUE_DEPTH {
+            return Err(IpcError::QueueFull);
+        }
+        queue.push_back(frame);
+        Ok(())
+    }
+
+    /// Takes the oldest frame the peer sent; `QueueEmpty` if there is none.
+    pub fn recv(&self) -> Result<Vec<u8>> {
+        self.inbox.borrow_mut().pop_front().ok_or(IpcError::QueueEmpty)
+    }
+
+    /// Frames waiting to be received on this end.
+    pub fn pending(&self) -> usize {
+        self.inbox.borrow().len()
+    }
+
+    /// Receives one request, passes it to `handler` and sends the handler's reply back.
+    pub fn serve_one(&self, handler: impl FnOnce(&[u8]) -> Vec<u8>) -> Result<()> {
+        let request = self.recv()?;
+        self.send(handler(&request))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::route::{try_route, RouteError};
+    use crate::routing::{decode_route_get, encode_route_rsp, STATUS_NOT_FOUND, STATUS_OK};
+
+    #[test]
+    fn request_round_trips_through_an_echo_handler() {
+        let (client, server) = loopback_pair();
+        let request = crate::frame_vec::encode_route_get_vec(b"statefsd").unwrap_or_default();
+        assert!(!request.is_empty());
+        assert_eq!(client.send(request.clone()), Ok(()));
+        assert_eq!(client.send(b"second".to_vec()), Ok(()));
+        assert_eq!(server.pending(), 2);
+
+        assert_eq!(server.serve_one(<[u8]>::to_vec), Ok(()));
+        let echoed = client.recv().unwrap_or_default();
+        assert_eq!(echoed, request);
+        assert_eq!(decode_route_get(&echoed), Some(&b"statefsd"[..]));
+        assert_eq!(server.recv().as_deref(), Ok(&b"second"[..]));
+        assert_eq!(client.recv(), Err(IpcError::QueueEmpty));
+    }
+
+    #[test]
+    fn try_route_runs_against_a_handler_over_the_pair() {
+        let (client, router) = loopback_pair();
+        let handler = |frame: &[u8]| {
+            let status = match decode_route_get(frame) {
+                Some(b"logd") => STATUS_OK,
+                _ => STATUS_NOT_FOUND,
+            };
+            encode_route_rsp(status, 5, 6).to_vec()
+        };
+        let route = |service: &[u8]| {
+            try_route(
+                service,
+                |frame| {
+                    client.send(frame.to_vec())?;
+                    router.serve_one(handler)
+                },
+                || client.recv(),
+            )
+        };
+        assert_eq!(route(b"logd"), Ok((5, 6)));
+        assert_eq!(route(b"netd"), Err(RouteError::NotFound));
+    }
+
+    #[test]
+    fn test_reject_send_to_a_full_queue_or_dropped_peer() {
+        let (client, server) = loopback_pair();
+        for _ in 0..LOOPBACK_QUEUE_DEPTH {
+            assert_eq!(client.send(Vec::new()), Ok(()));
+        }
+        assert_eq!(client.send(Vec::new()), Err(IpcError::QueueFull));
+        assert_eq!(server.pending(), LOOPBACK_QUEUE_DEPTH);
+
+        drop(server);
+        assert_eq!(client.send(Vec::new()), Err(IpcError::NoSuchEndpoint));
+        assert_eq!(client.recv(), Err(IpcError::QueueEmpty));
+    }
+}
@@ -0,0 +1,124 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: Transport-level request/reply correlation nonce (`ipc_hdr::HDR_NONCE`)
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: 3 host unit tests (round trip, CAP_MOVE, malformed frames)
+//!
+//! A frame whose `MsgHeader.ty` has [`HDR_NONCE`] set carries a caller-chosen
+//! `u32` nonce (little-endian) as the first [`NONCE_LEN`] payload bytes, directly
+//! after the 16-byte header; `MsgHeader.len` counts it. A reply echoes the nonce
+//! the same way, so a generic dispatcher can pair replies with requests without
+//! decoding the service payload. The base header layout is unchanged and the
+//! kernel never interprets `ty`, so the convention needs no kernel support.
+//!
+//! `CAP_MOVE` is orthogonal: it lives in `flags` and only rewrites `src`, so a
+//! correlated request can move its reply capability as usual, and the server
+//! echoes the nonce on that capability. Service payload formats that embed their
+//! own nonce (policyd, metricsd) keep working; this prefix comes before them.
+//!
+//! [`HDR_NONCE`]: crate::ipc_hdr::HDR_NONCE
+
+use crate::ipc_hdr::HDR_NONCE;
+use crate::MsgHeader;
+
+/// Bytes the nonce occupies at the start of the payload.
+pub const NONCE_LEN: usize = 4;
+
+/// A received frame split into its correlation nonce and service payload.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Correlated<'a> {
+    /// `ty` with [`HDR_NONCE`] cleared.
+    pub ty: u16,
+    /// The correlation nonce, when the frame carries one.
+    pub nonce: Option<u32>,
+    /// The service payload (after the nonce, if any).
+    pub payload: &'a [u8],
+}
+
+/// Writes `nonce` followed by `payload` into `out` and returns the header to send
+/// with the first `n` bytes of `out`.
+///
+/// The returned header is `header` with [`HDR_NONCE`] set in `ty` and `len = n`.
+/// Returns `None` when `header.ty` already has the nonce bit or `out` is too small.
+pub fn encode_with_nonce(
+    header: &MsgHeader,
+    nonce: u32,
+    payload: &[u8],
+    out: &mut [u8],
+) -> Option<(MsgHeader, usize)> {
+    if header.ty & HDR_NONCE != 0 {
+        return None;
+    }
+    let n = NONCE_LEN.checked_add(payload.len())?;
+    let len = u32::try_from(n).ok()?;
+    let frame = out.get_mut(..n)?;
+    frame[..NONCE_LEN].copy_from_slice(&nonce.to_le_bytes());
+    frame[NONCE_LEN..].copy_from_slice(payload);
+    let mut sent = *header;
+    sent.ty |= HDR_NONCE;
+    sent.len = len;
+    Some((sent, n))
+}
+
+/// Splits a received `payload` according to `header.ty`.
+///
+/// Frames without [`HDR_NONCE`] pass through with `nonce: None`. Returns `None`
+/// for a frame that claims a nonce but is shorter than [`NONCE_LEN`].
+pub fn decode_nonce<'a>(header: &MsgHeader, payload: &'a [u8]) -> Option<Correlated<'a>> {
+    let ty = header.ty & !HDR_NONCE;
+    if header.ty & HDR_NONCE == 0 {
+        return Some(Correlated { ty, nonce: None, payload });
+    }
+    let (nonce, payload) = payload.split_first_chunk::<NONCE_LEN>()?;
+    Some(Correlated { ty, nonce: Some(u32::from_le_bytes(*nonce)), payload })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ipc_hdr::CAP_MOVE;
+    use crate::opcode::{classify, Service};
+
+    #[test]
+    fn nonce_round_trips_without_touching_the_payload() {
+        let ty = Service::Statefs.ty(2);
+        let header = MsgHeader::new(0, 0, ty, 0, 0);
+        let mut buf = [0u8; 16];
+        let (sent, n) = encode_with_nonce(&header, 0xA1B2_C3D4, b"key", &mut buf).unwrap();
+        assert_eq!((sent.ty, sent.len, n), (ty | HDR_NONCE, 7, 7));
+        assert_eq!(&buf[..n], &[0xD4, 0xC3, 0xB2, 0xA1, b'k', b'e', b'y']);
+        // Range classification ignores the nonce bit.
+        assert_eq!(classify(sent.ty), Some(Service::Statefs));
+
+        let got = decode_nonce(&MsgHeader::from_le_bytes(sent.to_le_bytes()), &buf[..n]).unwrap();
+        assert_eq!(got, Correlated { ty, nonce: Some(0xA1B2_C3D4), payload: b"key" });
+
+        // Legacy frames pass through untouched.
+        let plain = decode_nonce(&header, b"key").unwrap();
+        assert_eq!(plain, Correlated { ty, nonce: None, payload: b"key" });
+    }
+
+    #[test]
+    fn cap_move_flags_and_src_are_preserved() {
+        let header = MsgHeader::new(9, 3, Service::Policyd.ty(1), CAP_MOVE, 0);
+        let mut buf = [0u8; 8];
+        let (sent, n) = encode_with_nonce(&header, 7, b"", &mut buf).unwrap();
+        assert_eq!((sent.src, sent.dst, sent.flags, n), (9, 3, CAP_MOVE, NONCE_LEN));
+        // The kernel rewrites `src` to the receiver's new slot; the nonce is unaffected.
+        let received = MsgHeader { src: 42, ..sent };
+        assert_eq!(decode_nonce(&received, &buf[..n]).unwrap().nonce, Some(7));
+    }
+
+    #[test]
+    fn test_reject_malformed_nonce_frames() {
+        let marked = MsgHeader::new(0, 0, Service::Logd.ty(1) | HDR_NONCE, 0, 3);
+        assert_eq!(decode_nonce(&marked, &[1, 2, 3]), None);
+        let mut buf = [0u8; 8];
+        assert_eq!(encode_with_nonce(&marked, 1, b"", &mut buf), None);
+        let header = MsgHeader::new(0, 0, Service::Logd.ty(1), 0, 0);
+        assert_eq!(encode_with_nonce(&header, 1, &[0; 5], &mut buf), None);
+    }
+}
@@ -0,0 +1,330 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: One-shot reply token for CAP_MOVE request/reply services
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: 5 host unit tests (fake transport)
+//!
+//! A client that wants an answer moves a reply capability along with its
+//! request ([`crate::ipc_hdr::CAP_MOVE`]); the server receives it as a fresh
+//! slot in `MsgHeader.src`. That slot must be used for exactly one reply and
+//! then closed, or the server's cap table leaks one slot per request.
+//! [`ReplyToken`] owns the slot: [`ReplyToken::send`] consumes the token, and a
+//! token dropped unanswered closes the slot and logs a warning.
+//!
+//! The client side is [`ReplyChannel`]: it holds the reply endpoint's send/recv
+//! pair and mints one [`MovedReply`] clone of the send slot per request. The
+//! clone leaves the client only when the kernel accepts the CAP_MOVE send
+//! ([`MovedReply::commit`]); a clone whose send failed is closed on drop instead
+//! of leaking one slot per failed request.
+
+use crate::MsgHeader;
+
+/// Sends on and releases reply capabilities on behalf of a [`ReplyToken`].
+///
+/// [`KernelReply`] is the real transport; host tests substitute a fake.
+pub trait ReplyTransport {
+    /// Error surfaced when the reply could not be delivered.
+    type Error;
+
+    /// Sends `payload` on the reply capability in `slot`.
+    fn send(&self, slot: u32, payload: &[u8]) -> core::result::Result<(), Self::Error>;
+
+    /// Releases the reply capability in `slot`.
+    fn close(&self, slot: u32);
+
+    /// Clones the capability in `slot` into a fresh slot (to move with a request).
+    fn duplicate(&self, slot: u32) -> core::result::Result<u32, Self::Error>;
+
+    /// Reports that the token for `slot` was dropped without a reply.
+    fn warn_unanswered(&self, slot: u32) {
+        let _ = slot;
+    }
+}
+
+/// Owns a received reply capability until it is answered or dropped.
+#[must_use = "an unanswered reply token closes its capability on drop"]
+pub struct ReplyToken<T: ReplyTransport = KernelReply> {
+    slot: u32,
+    transport: T,
+    armed: bool,
+}
+
+impl ReplyToken<KernelReply> {
+    /// Takes ownership of the reply capability in `slot`.
+    pub fn new(slot: u32) -> Self {
+        Self::with_transport(slot, KernelReply)
+    }
+
+    /// Takes the moved reply capability out of a received header.
+    ///
+    /// Returns `None` when the sender did not set [`crate::ipc_hdr::CAP_MOVE`]
+    /// (no reply capability travelled with the request).
+    pub fn from_header(header: &MsgHeader) -> Option<Self> {
+        if header.flags & crate::ipc_hdr::CAP_MOVE == 0 {
+            return None;
+        }
+        Some(Self::new(header.src))
+    }
+}
+
+impl<T: ReplyTransport> ReplyToken<T> {
+    /// Takes ownership of `slot`, sending and closing through `transport`.
+    pub fn with_transport(slot: u32, transport: T) -> Self {
+        Self { slot, transport, armed: true }
+    }
+
+    /// Returns the reply capability slot (for diagnostics; do not close it).
+    pub fn slot(&self) -> u32 {
+        self.slot
+    }
+
+    /// Sends the one reply and closes the capability.
+    ///
+    /// The capability is closed even if the send fails: the reply slot is
+    /// single-use and a failed send does not make it reusable.
+    pub fn send(mut self, payload: &[u8]) -> core::result::Result<(), T::Error> {
+        self.armed = false;
+        let res = self.transport.send(self.slot, payload);
+        self.transport.close(self.slot);
+        res
+    }
+
+    /// Closes the capability without replying (a deliberate, silent discard).
+    pub fn discard(mut self) {
+        self.armed = false;
+        self.transport.close(self.slot);
+    }
+}
+
+impl<T: ReplyTransport> Drop for ReplyToken<T> {
+    fn drop(&mut self) {
+        if !self.armed {
+            return;
+        }
+        self.armed = false;
+        self.transport.warn_unanswered(self.slot);
+        self.transport.close(self.slot);
+    }
+}
+
+/// Client half of a CAP_MOVE round trip: the reply endpoint's send/recv slots.
+///
+/// The channel only borrows the pair; it never closes either slot.
+#[derive(Clone, Copy, Debug)]
+pub struct ReplyChannel<T: ReplyTransport = KernelReply> {
+    send_slot: u32,
+    recv_slot: u32,
+    transport: T,
+}
+
+impl ReplyChannel<KernelReply> {
+    /// Wraps the reply endpoint pair `send_slot` / `recv_slot`.
+    pub fn new(send_slot: u32, recv_slot: u32) -> Self {
+        Self::with_transport(send_slot, recv_slot, KernelReply)
+    }
+}
+
+impl<T: ReplyTransport + Clone> ReplyChannel<T> {
+    /// Wraps the pair, cloning and closing through `transport`.
+    pub fn with_transport(send_slot: u32, recv_slot: u32, transport: T) -> Self {
+        Self { send_slot, recv_slot, transport }
+    }
+
+    /// Slot the replies arrive on.
+    pub fn recv_slot(&self) -> u32 {
+        self.recv_slot
+    }
+
+    /// Mints the reply capability to move with one request.
+    pub fn reply_send(&self) -> core::result::Result<MovedReply<T>, T::Error> {
+        let slot = self.transport.duplicate(self.send_slot)?;
+        Ok(MovedReply { slot, transport: self.transport.clone(), armed: true })
+    }
+}
+
+/// A clone of the reply send slot, owned until a CAP_MOVE send hands it over.
+#[must_use = "an uncommitted reply capability is closed on drop"]
+pub struct MovedReply<T: ReplyTransport = KernelReply> {
+    slot: u32,
+    transport: T,
+    armed: bool,
+}
+
+impl<T: ReplyTransport> MovedReply<T> {
+    /// Slot to put in `MsgHeader.src` alongside [`crate::ipc_hdr::CAP_MOVE`].
+    pub fn slot(&self) -> u32 {
+        self.slot
+    }
+
+    /// Records that the kernel accepted the send: the capability now belongs
+    /// to the receiver and must not be closed here.
+    pub fn commit(mut self) {
+        self.armed = false;
+    }
+}
+
+impl<T: ReplyTransport> Drop for MovedReply<T> {
+    fn drop(&mut self) {
+        if self.armed {
+            self.armed = false;
+            self.transport.close(self.slot);
+        }
+    }
+}
+
+/// Kernel IPC reply transport (non-blocking send, then `cap_close`).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct KernelReply;
+
+#[cfg(nexus_env = "os")]
+impl ReplyTransport for KernelReply {
+    type Error = crate::AbiError;
+
+    fn send(&self, slot: u32, payload: &[u8]) -> crate::SysResult<()> {
+        let header = MsgHeader::new(0, 0, 0, 0, payload.len() as u32);
+        crate::ipc_send_v1_nb(slot, &header, payload).map(|_| ()).map_err(|err| match err {
+            crate::IpcError::QueueFull => crate::AbiError::WouldBlock,
+            crate::IpcError::TimedOut => crate::AbiError::TimedOut,
+            crate::IpcError::PermissionDenied => crate::AbiError::CapabilityDenied,
+            crate::IpcError::Unsupported => crate::AbiError::Unsupported,
+            _ => crate::AbiError::IpcFailure,
+        })
+    }
+
+    fn close(&self, slot: u32) {
+        let _ = crate::cap_close(slot);
+    }
+
+    fn duplicate(&self, slot: u32) -> crate::SysResult<u32> {
+        crate::cap_clone(slot)
+    }
+
+    fn warn_unanswered(&self, _slot: u32) {
+        let _ = crate::debug_println("warn: reply token dropped without a reply");
+    }
+}
+
+/// Host stub: there is no kernel to reply through.
+#[cfg(not(nexus_env = "os"))]
+impl ReplyTransport for KernelReply {
+    type Error = crate::IpcError;
+
+    fn send(&self, _slot: u32, _payload: &[u8]) -> crate::Result<()> {
+        Err(crate::IpcError::Unsupported)
+    }
+
+    fn close(&self, _slot: u32) {}
+
+    fn duplicate(&self, _slot: u32) -> crate::Result<u32> {
+        Err(crate::IpcError::Unsupported)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::cell::RefCell;
+
+    #[derive(Default)]
+    struct FakeTransport {
+        sent: RefCell<Vec<(u32, Vec<u8>)>>,
+        closed: RefCell<Vec<u32>>,
+        warned: RefCell<u32>,
+        next_clone: RefCell<u32>,
+        fail_send: bool,
+    }
+
+    impl ReplyTransport for &FakeTransport {
+        type Error = ();
+
+        fn send(&self, slot: u32, payload: &[u8]) -> core::result::Result<(), ()> {
+            if self.fail_send {
+                return Err(());
+            }
+            self.sent.borrow_mut().push((slot, payload.to_vec()));
+            Ok(())
+        }
+
+        fn close(&self, slot: u32) {
+            self.closed.borrow_mut().push(slot);
+        }
+
+        fn warn_unanswered(&self, _slot: u32) {
+            *self.warned.borrow_mut() += 1;
+        }
+
+        fn duplicate(&self, slot: u32) -> core::result::Result<u32, ()> {
+            let mut next = self.next_clone.borrow_mut();
+            *next += 1;
+            Ok(slot * 100 + *next)
+        }
+    }
+
+    #[test]
+    fn send_replies_once_and_closes_once() {
+        let fake = FakeTransport::default();
+        let token = ReplyToken::with_transport(7, &fake);
+        assert_eq!(token.send(b"ok"), Ok(()));
+        assert_eq!(*fake.sent.borrow(), vec![(7, b"ok".to_vec())]);
+        assert_eq!(*fake.closed.borrow(), vec![7]);
+        assert_eq!(*fake.warned.borrow(), 0);
+    }
+
+    #[test]
+    fn test_reject_unanswered_token_closes_on_drop() {
+        let fake = FakeTransport::default();
+        drop(ReplyToken::with_transport(9, &fake));
+        assert!(fake.sent.borrow().is_empty());
+        assert_eq!(*fake.closed.borrow(), vec![9]);
+        assert_eq!(*fake.warned.borrow(), 1);
+
+        // A deliberate discard closes without the warning.
+        ReplyToken::with_transport(10, &fake).discard();
+        assert_eq!(*fake.closed.borrow(), vec![9, 10]);
+        assert_eq!(*fake.warned.borrow(), 1);
+    }
+
+    #[test]
+    fn test_reject_failed_send_still_closes_once() {
+        let fake = FakeTransport { fail_send: true, ..FakeTransport::default() };
+        let token = ReplyToken::with_transport(3, &fake);
+        assert_eq!(token.send(b"x"), Err(()));
+        assert_eq!(*fake.closed.borrow(), vec![3]);
+        assert_eq!(*fake.warned.borrow(), 0);
+        // Header without CAP_MOVE carries no reply capability.
+        assert!(ReplyToken::from_header(&MsgHeader::new(5, 0, 0, 0, 0)).is_none());
+        let moved = MsgHeader::new(5, 0, 0, crate::ipc_hdr::CAP_MOVE, 0);
+        let token = ReplyToken::from_header(&moved).map(|t| t.slot());
+        assert_eq!(token, Some(5));
+    }
+
+    #[test]
+    fn reply_send_mints_one_clone_per_request() {
+        let fake = FakeTransport::default();
+        let channel = ReplyChannel::with_transport(4, 5, &fake);
+        assert_eq!(channel.recv_slot(), 5);
+        let first = channel.reply_send().unwrap();
+        let second = channel.reply_send().unwrap();
+        assert_eq!((first.slot(), second.slot()), (401, 402));
+        // Committed clones travelled with their requests; nothing is closed here.
+        first.commit();
+        second.commit();
+        assert!(fake.closed.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_reject_uncommitted_reply_clone_closes_on_drop() {
+        let fake = FakeTransport::default();
+        let channel = ReplyChannel::with_transport(4, 5, &fake);
+        let sent = channel.reply_send().unwrap();
+        drop(channel.reply_send().unwrap());
+        sent.commit();
+        // Only the clone whose send never went through is released; the pair stays open.
+        assert_eq!(*fake.closed.borrow(), vec![402]);
+        assert_eq!(*fake.warned.borrow(), 0);
+        assert!(ReplyChannel::new(4, 5).reply_send().is_err());
+    }
+}
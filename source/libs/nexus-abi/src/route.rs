@@ -0,0 +1,116 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: Typed ROUTE_GET round trip (`try_route`, feature `alloc`)
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: 3 host unit tests (success slots, each status mapped, transport/bad frames)
+//!
+//! Bootstrap code used to encode `ROUTE_GET` itself and then compare the status byte of
+//! `decode_route_rsp` against `STATUS_NOT_FOUND`/`STATUS_DENIED`/`STATUS_MALFORMED`.
+//! [`try_route`] does the round trip over caller-supplied transport closures and returns
+//! the `(send_slot, recv_slot)` pair or a [`RouteError`]. The closures keep it free of
+//! syscalls, so the OS passes `ipc_send_v1`/`ipc_recv_v1` wrappers and host tests a fake.
+//! Retries, deadlines and nonce correlation stay with the caller's transport.
+
+use alloc::vec::Vec;
+
+use crate::routing::{
+    decode_route_rsp, STATUS_DENIED, STATUS_MALFORMED, STATUS_NOT_FOUND, STATUS_OK,
+};
+
+/// Why [`try_route`] returned no capability slots.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RouteError<E> {
+    /// The router does not know the service or does not route it for the caller.
+    NotFound,
+    /// The router understood the request and policy denied it.
+    Denied,
+    /// The router rejected the request frame as malformed.
+    Malformed,
+    /// The service name is empty or longer than `MAX_SERVICE_NAME_LEN`; nothing was sent.
+    InvalidName,
+    /// The reply is not a `ROUTE_RSP` frame or carries an unknown status.
+    BadResponse,
+    /// The transport failed to send the request or receive the reply.
+    Transport(E),
+}
+
+/// Sends `ROUTE_GET` for `service` and maps the `ROUTE_RSP` to the routed slot pair.
+///
+/// The slots are plain `u32` (the OS-only `Cap` alias) so host builds can use the helper.
+/// `send_frame` is called once with the encoded request and `recv_frame` once for the reply;
+/// their errors come back as [`RouteError::Transport`].
+pub fn try_route<E>(
+    service: &[u8],
+    send_frame: impl FnOnce(&[u8]) -> Result<(), E>,
+    recv_frame: impl FnOnce() -> Result<Vec<u8>, E>,
+) -> Result<(u32, u32), RouteError<E>> {
+    let request = crate::frame_vec::encode_route_get_vec(service).ok_or(RouteError::InvalidName)?;
+    send_frame(&request).map_err(RouteError::Transport)?;
+    let reply = recv_frame().map_err(RouteError::Transport)?;
+    let (status, send_slot, recv_slot) = decode_route_rsp(&reply).ok_or(RouteError::BadResponse)?;
+    match status {
+        STATUS_OK => Ok((send_slot, recv_slot)),
+        STATUS_NOT_FOUND => Err(RouteError::NotFound),
+        STATUS_DENIED => Err(RouteError::Denied),
+        STATUS_MALFORMED => Err(RouteError::Malformed),
+        _ => Err(RouteError::BadResponse),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routing::{decode_route_get, encode_route_rsp};
+    use crate::IpcError;
+
+    /// Routes through a fake router answering `status` with slots 5/6.
+    fn route_with_status(service: &[u8], status: u8) -> Result<(u32, u32), RouteError<IpcError>> {
+        let mut asked = None;
+        let result = try_route(
+            service,
+            |frame| {
+                asked = decode_route_get(frame).map(<[u8]>::to_vec);
+                Ok(())
+            },
+            || Ok(encode_route_rsp(status, 5, 6).to_vec()),
+        );
+        assert_eq!(asked.as_deref(), Some(service));
+        result
+    }
+
+    #[test]
+    fn ok_status_returns_both_slots() {
+        assert_eq!(route_with_status(b"vfsd", STATUS_OK), Ok((5, 6)));
+    }
+
+    #[test]
+    fn test_reject_each_router_status_as_typed_error() {
+        assert_eq!(route_with_status(b"vfsd", STATUS_NOT_FOUND), Err(RouteError::NotFound));
+        assert_eq!(route_with_status(b"vfsd", STATUS_DENIED), Err(RouteError::Denied));
+        assert_eq!(route_with_status(b"vfsd", STATUS_MALFORMED), Err(RouteError::Malformed));
+        assert_eq!(route_with_status(b"vfsd", 0x7f), Err(RouteError::BadResponse));
+    }
+
+    #[test]
+    fn test_reject_transport_failures_and_bad_frames() {
+        let unreachable = || -> Result<Vec<u8>, IpcError> { panic!("no reply expected") };
+        assert_eq!(try_route(b"", |_| Ok(()), unreachable), Err(RouteError::InvalidName));
+        assert_eq!(
+            try_route(b"vfsd", |_| Err(IpcError::QueueFull), unreachable),
+            Err(RouteError::Transport(IpcError::QueueFull))
+        );
+        assert_eq!(
+            try_route(b"vfsd", |_| Ok(()), || Err(IpcError::TimedOut)),
+            Err(RouteError::Transport(IpcError::TimedOut))
+        );
+        let mut truncated = encode_route_rsp(STATUS_OK, 5, 6).to_vec();
+        truncated.pop();
+        assert_eq!(
+            try_route::<IpcError>(b"vfsd", |_| Ok(()), || Ok(truncated)),
+            Err(RouteError::BadResponse)
+        );
+    }
+}
@@ -0,0 +1,132 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: Time-bounded spawn + wait for supervised launches (`spawn_supervised`)
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: 2 host unit tests (exit before deadline, deadline elapses first)
+//!
+//! A supervisor that calls `spawn` and then the blocking `wait` is wedged for good by a
+//! child that hangs before it exits. `spawn_supervised` instead polls the child with the
+//! non-blocking `try_wait` and yields between polls, until the child exits or the
+//! absolute monotonic `deadline_ns` (the `nsec` clock) passes.
+//!
+//! A timeout does **not** kill the child: the kernel exposes no kill for a parent and
+//! that stays kernel policy. The helper returns [`WaitOutcome::TimedOut`] together with the
+//! PID, and the child stays a live (later zombie) child of the caller. The caller decides
+//! whether to keep waiting, reap it later with `wait`, or escalate.
+
+/// How a deadline-bounded wait ended.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WaitOutcome {
+    /// The child exited and was reaped with this status.
+    Exited(i32),
+    /// The deadline passed first; the child is still running and was not reaped.
+    TimedOut,
+}
+
+/// Polls `try_reap` until it reports an exit status or `now` reaches `deadline_ns`.
+///
+/// `idle` runs between polls (a yield on the OS). The child is polled once more after the
+/// deadline is seen, so an exit that races the deadline is still reported as `Exited`.
+/// Errors from any of the closures end the wait unchanged.
+pub fn wait_deadline<E>(
+    deadline_ns: u64,
+    mut now: impl FnMut() -> Result<u64, E>,
+    mut try_reap: impl FnMut() -> Result<Option<i32>, E>,
+    mut idle: impl FnMut() -> Result<(), E>,
+) -> Result<WaitOutcome, E> {
+    loop {
+        let expired = now()? >= deadline_ns;
+        if let Some(status) = try_reap()? {
+            return Ok(WaitOutcome::Exited(status));
+        }
+        if expired {
+            return Ok(WaitOutcome::TimedOut);
+        }
+        idle()?;
+    }
+}
+
+/// Spawns a task like [`crate::spawn`] and waits for it until `deadline_ns` (absolute `nsec`).
+///
+/// Returns the child's PID with the outcome. On [`WaitOutcome::TimedOut`] the child keeps
+/// running and is not killed; see the module docs.
+#[cfg(nexus_env = "os")]
+pub fn spawn_supervised(
+    entry_pc: u64,
+    stack_sp: u64,
+    asid: u64,
+    bootstrap_ep: u32,
+    global_pointer: u64,
+    deadline_ns: u64,
+) -> crate::SysResult<(crate::Pid, WaitOutcome)> {
+    #[cfg(all(target_arch = "riscv64", target_os = "none"))]
+    {
+        let pid = crate::spawn(entry_pc, stack_sp, asid, bootstrap_ep, global_pointer)?;
+        let outcome = wait_deadline(
+            deadline_ns,
+            crate::nsec,
+            || crate::try_wait(pid as i32).map(|reaped| reaped.map(|(_, status)| status)),
+            crate::yield_,
+        )?;
+        Ok((pid, outcome))
+    }
+    #[cfg(not(all(target_arch = "riscv64", target_os = "none")))]
+    {
+        let _ = (entry_pc, stack_sp, asid, bootstrap_ep, global_pointer, deadline_ns);
+        Err(crate::AbiError::Unsupported)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::cell::Cell;
+
+    #[test]
+    fn exit_before_deadline_reports_the_status() {
+        let clock = Cell::new(0u64);
+        let polls = Cell::new(0u32);
+        let outcome = wait_deadline::<()>(
+            1_000,
+            || Ok(clock.get()),
+            || {
+                polls.set(polls.get() + 1);
+                Ok((polls.get() == 3).then_some(42))
+            },
+            || {
+                clock.set(clock.get() + 100);
+                Ok(())
+            },
+        );
+        assert_eq!(outcome, Ok(WaitOutcome::Exited(42)));
+        assert_eq!(polls.get(), 3);
+
+        // An exit seen on the final poll wins over the expired deadline.
+        let late = wait_deadline::<()>(10, || Ok(10), || Ok(Some(-1)), || Err(()));
+        assert_eq!(late, Ok(WaitOutcome::Exited(-1)));
+    }
+
+    #[test]
+    fn test_reject_hung_child_past_deadline() {
+        let clock = Cell::new(0u64);
+        let yields = Cell::new(0u32);
+        let outcome = wait_deadline::<()>(
+            500,
+            || Ok(clock.get()),
+            || Ok(None),
+            || {
+                yields.set(yields.get() + 1);
+                clock.set(clock.get() + 200);
+                Ok(())
+            },
+        );
+        assert_eq!(outcome, Ok(WaitOutcome::TimedOut));
+        assert_eq!(yields.get(), 3);
+
+        // A failing poll ends the wait with that error.
+        assert_eq!(wait_deadline(500, || Ok(0), || Err("ESRCH"), || Ok(())), Err("ESRCH"));
+    }
+}
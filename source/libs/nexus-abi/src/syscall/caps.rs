@@ -1,7 +1,7 @@
 // Copyright 2024 Open Nexus OS Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-//! CONTEXT: Capability + endpoint + IRQ syscalls — cap transfer/clone/close, endpoint create/close, irq bind/complete
+//! CONTEXT: Capability + endpoint + IRQ syscalls — cap transfer/clone/close/rights, endpoint create/close/reassign, irq bind/complete
 //! (Mechanical split out of the former lib.rs monolith — ADR-0051 hygiene
 //! pass; behavior and syscall IDs unchanged.)
 
@@ -44,6 +44,12 @@ pub fn irq_complete(irq: u32) -> SysResult<()> {
 }
 
 /// Transfers a capability from the current task to `dst_task` with intersected `rights`.
+///
+/// To hand out a sub-capability, compute `rights` as
+/// `cap_rights(cap)?.derive(requested)` (see [`Rights::derive`]) rather than passing the
+/// requested mask through: the transfer then states exactly what the child receives, and
+/// a request for rights the parent lacks narrows instead of being silently widened by a
+/// later edit.
 #[cfg(nexus_env = "os")]
 pub fn cap_transfer(dst_task: Pid, cap: Cap, rights: Rights) -> SysResult<Cap> {
     #[cfg(all(target_arch = "riscv64", target_os = "none"))]
@@ -170,6 +176,60 @@ pub fn ipc_endpoint_create_for(
     }
 }
 
+/// Register arguments of `SYSCALL_IPC_ENDPOINT_REASSIGN = 54`, in `a0..a2` order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EndpointReassignArgs {
+    /// `EndpointFactory` capability slot (needs `Rights::MANAGE`).
+    pub factory_cap: u32,
+    /// Endpoint capability slot (needs `Rights::MANAGE`).
+    pub endpoint_cap: u32,
+    /// PID of the new close-on-exit owner.
+    pub new_owner_pid: u32,
+}
+
+impl EndpointReassignArgs {
+    /// Argument registers exactly as the kernel reads them.
+    pub const fn to_regs(self) -> [usize; 3] {
+        [self.factory_cap as usize, self.endpoint_cap as usize, self.new_owner_pid as usize]
+    }
+
+    /// Inverse of [`Self::to_regs`]; `None` when a register does not fit in `u32`.
+    pub fn from_regs(regs: [usize; 3]) -> Option<Self> {
+        let [factory_cap, endpoint_cap, new_owner_pid] = regs.map(|reg| u32::try_from(reg).ok());
+        Some(Self {
+            factory_cap: factory_cap?,
+            endpoint_cap: endpoint_cap?,
+            new_owner_pid: new_owner_pid?,
+        })
+    }
+}
+
+/// Hands an existing endpoint to `new_owner_pid`, e.g. when a service restarts into a new PID.
+///
+/// Only the close-on-exit owner changes: queued messages, blocked waiters and every send/recv
+/// capability already naming the endpoint stay valid. Both `factory_cap` and `endpoint_cap` need
+/// `Rights::MANAGE`; the new owner must be the caller or one of its direct children.
+#[cfg(nexus_env = "os")]
+pub fn ipc_endpoint_reassign(
+    factory_cap: Cap,
+    endpoint_cap: Cap,
+    new_owner_pid: u32,
+) -> SysResult<()> {
+    let args = EndpointReassignArgs { factory_cap, endpoint_cap, new_owner_pid };
+    #[cfg(all(target_arch = "riscv64", target_os = "none"))]
+    {
+        const SYSCALL_IPC_ENDPOINT_REASSIGN: usize = 54;
+        let [a0, a1, a2] = args.to_regs();
+        let raw = unsafe { ecall3(SYSCALL_IPC_ENDPOINT_REASSIGN, a0, a1, a2) };
+        decode_syscall(raw).map(|_| ())
+    }
+    #[cfg(not(all(target_arch = "riscv64", target_os = "none")))]
+    {
+        let _ = args;
+        Err(AbiError::Unsupported)
+    }
+}
+
 /// Closes an IPC endpoint referenced by `cap` (slot id) if the capability includes `Rights::MANAGE`.
 ///
 /// This is a *global close* (revocation-by-close): once closed, subsequent IPC operations on the
@@ -205,6 +265,29 @@ pub fn cap_close(cap: Cap) -> SysResult<()> {
     }
 }
 
+/// Returns the rights mask of the capability in `cap` as held by the caller.
+///
+/// The mask is the effective grant: rights are intersected when a capability is
+/// transferred, so this is exactly what the holder may exercise (e.g. check for
+/// [`Rights::SEND`] up front instead of discovering its absence on the first send).
+/// Kernel rights bits unknown to this ABI build are dropped.
+///
+/// Fails with [`AbiError::CapabilityDenied`] if the slot is empty or invalid.
+#[cfg(nexus_env = "os")]
+pub fn cap_rights(cap: Cap) -> SysResult<Rights> {
+    #[cfg(all(target_arch = "riscv64", target_os = "none"))]
+    {
+        const SYSCALL_CAP_RIGHTS: usize = 51;
+        let raw = unsafe { ecall1(SYSCALL_CAP_RIGHTS, cap as usize) };
+        decode_syscall(raw).map(|bits| Rights::from_bits_truncate(bits as u32))
+    }
+    #[cfg(not(all(target_arch = "riscv64", target_os = "none")))]
+    {
+        let _ = cap;
+        Err(AbiError::Unsupported)
+    }
+}
+
 /// Clones a capability slot locally.
 ///
 /// Returns the newly allocated slot in the caller. This is a local duplicate only (no transfer).
@@ -222,3 +305,30 @@ pub fn cap_clone(cap: Cap) -> SysResult<Cap> {
         Err(AbiError::Unsupported)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::EndpointReassignArgs;
+
+    #[test]
+    fn reassign_args_round_trip_through_registers() {
+        let args = EndpointReassignArgs { factory_cap: 3, endpoint_cap: 17, new_owner_pid: 42 };
+        assert_eq!(args.to_regs(), [3, 17, 42]);
+        assert_eq!(EndpointReassignArgs::from_regs(args.to_regs()), Some(args));
+
+        let max = EndpointReassignArgs {
+            factory_cap: u32::MAX,
+            endpoint_cap: 0,
+            new_owner_pid: u32::MAX,
+        };
+        assert_eq!(EndpointReassignArgs::from_regs(max.to_regs()), Some(max));
+    }
+
+    #[test]
+    fn test_reject_reassign_registers_wider_than_u32() {
+        let wide = u32::MAX as usize + 1;
+        assert_eq!(EndpointReassignArgs::from_regs([wide, 1, 1]), None);
+        assert_eq!(EndpointReassignArgs::from_regs([1, wide, 1]), None);
+        assert_eq!(EndpointReassignArgs::from_regs([1, 1, wide]), None);
+    }
+}
@@ -287,6 +287,19 @@ pub fn debug_write(bytes: &[u8]) -> SysResult<()> {
     }
 }
 
+/// Most bytes the kernel emits per `SYSCALL_DEBUG_WRITE`; it silently drops the rest.
+pub const DEBUG_WRITE_MAX: usize = 1024;
+
+/// [`debug_write`] in chunks of at most [`DEBUG_WRITE_MAX`] bytes, so a long slice is not cut
+/// short by the kernel's per-call cap. Each chunk is atomic on its own; returns the bytes written.
+#[cfg(nexus_env = "os")]
+pub fn debug_write_bounded(bytes: &[u8]) -> SysResult<usize> {
+    for chunk in bytes.chunks(DEBUG_WRITE_MAX) {
+        debug_write(chunk)?;
+    }
+    Ok(bytes.len())
+}
+
 /// Writes a line (with trailing '\n') to the kernel UART for debugging. The content and the
 /// newline go out in a single atomic [`debug_write`] (via a bounded stack buffer) so a line
 /// is never split across two console writes; very long lines fall back to content + newline.
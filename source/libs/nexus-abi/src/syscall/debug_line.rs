@@ -0,0 +1,122 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: Boot-time line buffering for byte-wise debug output (`buffered_putc`/`buffered_flush`)
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: 2 host unit tests (per-line flush, over-capacity line)
+//!
+//! Early-boot emitters that build their output one byte at a time (`debug_putc`) pay one
+//! syscall per byte. A [`DebugLineBuffer`] collects the bytes instead and hands a whole line to
+//! [`debug_write_bounded`](super::debug_write_bounded) on `\n`, or a full buffer's worth when a
+//! line is longer than [`DEBUG_LINE_BUFFER_LEN`]. Fixed size, no heap: keep one in the
+//! emitter's state and call [`buffered_flush`] before anything else writes to the console, or
+//! the buffered bytes come out after it. Off the OS (`nexus_env != "os"`) the writes are no-ops.
+
+/// Bytes a [`DebugLineBuffer`] holds before it flushes without a newline.
+pub const DEBUG_LINE_BUFFER_LEN: usize = 256;
+
+/// Fixed-size byte buffer that flushes per line.
+pub struct DebugLineBuffer {
+    buf: [u8; DEBUG_LINE_BUFFER_LEN],
+    len: usize,
+}
+
+impl DebugLineBuffer {
+    /// An empty buffer.
+    pub const fn new() -> Self {
+        Self { buf: [0; DEBUG_LINE_BUFFER_LEN], len: 0 }
+    }
+
+    /// Bytes buffered and not yet flushed.
+    pub const fn pending(&self) -> usize {
+        self.len
+    }
+
+    /// Buffers `byte`; hands the buffer to `write` after a `\n` or once it is full.
+    pub fn push_with(&mut self, byte: u8, write: impl FnOnce(&[u8])) {
+        self.buf[self.len] = byte;
+        self.len += 1;
+        if byte == b'\n' || self.len == DEBUG_LINE_BUFFER_LEN {
+            self.flush_with(write);
+        }
+    }
+
+    /// Hands the buffered bytes to `write` (not called when empty) and empties the buffer.
+    pub fn flush_with(&mut self, write: impl FnOnce(&[u8])) {
+        if self.len != 0 {
+            write(&self.buf[..self.len]);
+            self.len = 0;
+        }
+    }
+}
+
+impl Default for DebugLineBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Buffers one console byte in `line`, writing the line out on `\n` or when the buffer fills.
+pub fn buffered_putc(line: &mut DebugLineBuffer, byte: u8) {
+    line.push_with(byte, console_write);
+}
+
+/// Writes out whatever `line` holds (a partial line included).
+pub fn buffered_flush(line: &mut DebugLineBuffer) {
+    line.flush_with(console_write);
+}
+
+#[cfg(nexus_env = "os")]
+fn console_write(bytes: &[u8]) {
+    // Best effort like `debug_putc` callers: a console write failure has nowhere to go.
+    let _ = super::debug_write_bounded(bytes);
+}
+
+#[cfg(not(nexus_env = "os"))]
+fn console_write(_bytes: &[u8]) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feed(line: &mut DebugLineBuffer, bytes: &[u8], writes: &mut Vec<Vec<u8>>) {
+        for &byte in bytes {
+            line.push_with(byte, |out| writes.push(out.to_vec()));
+        }
+    }
+
+    #[test]
+    fn multi_line_output_flushes_per_line() {
+        let mut line = DebugLineBuffer::new();
+        let mut writes = Vec::new();
+        feed(&mut line, b"init: up\nselftest: ok\nready", &mut writes);
+        assert_eq!(writes, [b"init: up\n".to_vec(), b"selftest: ok\n".to_vec()]);
+        assert_eq!(line.pending(), 5);
+
+        line.flush_with(|out| writes.push(out.to_vec()));
+        assert_eq!(writes.last().map(Vec::as_slice), Some(&b"ready"[..]));
+        // Nothing buffered: no empty write.
+        line.flush_with(|out| writes.push(out.to_vec()));
+        assert_eq!(writes.len(), 3);
+
+        // The OS-less entry points are no-ops but still drain the buffer.
+        buffered_putc(&mut line, b'x');
+        buffered_flush(&mut line);
+        assert_eq!(line.pending(), 0);
+    }
+
+    #[test]
+    fn over_capacity_line_flushes_when_the_buffer_fills() {
+        let mut line = DebugLineBuffer::new();
+        let mut writes = Vec::new();
+        let long = [b'a'; DEBUG_LINE_BUFFER_LEN + 10];
+        feed(&mut line, &long, &mut writes);
+        feed(&mut line, b"\n", &mut writes);
+        assert_eq!(writes.len(), 2);
+        assert_eq!(writes[0], [b'a'; DEBUG_LINE_BUFFER_LEN]);
+        assert_eq!(writes[1], [&[b'a'; 10][..], b"\n"].concat());
+        assert_eq!(line.pending(), 0);
+    }
+}
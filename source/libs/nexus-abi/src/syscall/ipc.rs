@@ -1,7 +1,7 @@
 // Copyright 2024 Open Nexus OS Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-//! CONTEXT: IPC v1/v2 syscalls — send/recv wrappers + IpcRecvV2Desc (kernel/userspace syscall ABI)
+//! CONTEXT: IPC v1/v2 syscalls — send/recv wrappers + IpcRecvV2Desc/IpcRecvAnyDesc/IpcRecvVmoDesc (syscall ABI)
 //! (Mechanical split out of the former lib.rs monolith — ADR-0051 hygiene
 //! pass; behavior and syscall IDs unchanged.)
 
@@ -13,6 +13,10 @@ use super::*;
 #[cfg(nexus_env = "os")]
 pub const IPC_SYS_NONBLOCK: u32 = 1 << 0;
 /// Permit payload truncation on receive.
+///
+/// With this flag an oversized payload is clipped to the buffer silently (the message is
+/// consumed). Without it the receive fails with [`IpcError::Truncated`], `header_out.len`
+/// reports the required length and the message stays queued for a retry.
 #[cfg(nexus_env = "os")]
 pub const IPC_SYS_TRUNCATE: u32 = 1 << 1;
 
@@ -32,14 +36,16 @@ fn decode_ipc_send(value: usize) -> Result<usize> {
     }
 }
 
-#[cfg(all(nexus_env = "os", target_arch = "riscv64", target_os = "none"))]
-fn decode_ipc_recv(value: usize) -> Result<usize> {
+#[cfg(any(test, all(nexus_env = "os", target_arch = "riscv64", target_os = "none")))]
+fn decode_ipc_recv(value: usize) -> crate::Result<usize> {
+    use crate::IpcError;
     if (value as isize) < 0 {
         match -(value as isize) as usize {
             1 => Err(IpcError::PermissionDenied), // EPERM
             3 => Err(IpcError::NoSuchEndpoint),   // ESRCH
             11 => Err(IpcError::QueueEmpty),      // EAGAIN
             28 => Err(IpcError::NoSpace),         // ENOSPC
+            90 => Err(IpcError::Truncated),       // EMSGSIZE
             110 => Err(IpcError::TimedOut),       // ETIMEDOUT
             _ => Err(IpcError::Unsupported),
         }
@@ -105,6 +111,9 @@ pub fn ipc_send_v1_nb(slot: Cap, header: &MsgHeader, payload: &[u8]) -> Result<u
 /// `deadline_ns` expires.
 ///
 /// `deadline_ns=0` means “no deadline”.
+///
+/// A payload larger than `payload_out` without TRUNCATE yields [`IpcError::Truncated`]:
+/// `header_out` is filled in (`len` = required bytes) and the message is left queued.
 #[cfg(nexus_env = "os")]
 pub fn ipc_recv_v1(
     slot: Cap,
@@ -141,6 +150,35 @@ pub fn ipc_recv_v1(
     }
 }
 
+/// Peeks at the next message queued on `slot` without dequeuing it (`SYSCALL_IPC_PEEK_V1 = 52`).
+///
+/// Writes the message header into `header_out` and returns its payload length, or
+/// [`IpcError::QueueEmpty`] when nothing is queued. Never blocks and never modifies the queue:
+/// repeated peeks return the same message, and a subsequent [`ipc_recv_v1`] receives it. For a
+/// CAP_MOVE message `header_out.src` is 0, as the moved slot is only allocated by the receive.
+#[cfg(nexus_env = "os")]
+pub fn ipc_peek_v1(slot: Cap, header_out: &mut MsgHeader) -> Result<usize> {
+    #[cfg(all(target_arch = "riscv64", target_os = "none"))]
+    {
+        const SYSCALL_IPC_PEEK_V1: usize = 52;
+        let header_out_ptr = header_out as *mut MsgHeader as usize;
+        let raw = unsafe { ecall2(SYSCALL_IPC_PEEK_V1, slot as usize, header_out_ptr) };
+        decode_ipc_recv(raw)
+    }
+    #[cfg(not(all(target_arch = "riscv64", target_os = "none")))]
+    {
+        let _ = (slot, header_out);
+        Err(IpcError::Unsupported)
+    }
+}
+
+/// Host stub: there is no kernel queue to peek at.
+#[cfg(not(nexus_env = "os"))]
+pub fn ipc_peek_v1(slot: u32, header_out: &mut crate::MsgHeader) -> crate::Result<usize> {
+    let _ = (slot, header_out);
+    Err(crate::IpcError::Unsupported)
+}
+
 /// IPC recv v2 descriptor (extensible ABI for recv-side metadata).
 ///
 /// This struct is part of the **kernel/userspace syscall ABI** and is therefore treated as
@@ -231,3 +269,320 @@ pub fn ipc_recv_v1_nb(
     }
     ipc_recv_v1(slot, header_out, payload_out, flags, 0)
 }
+
+/// Maximum number of slots one [`ipc_recv_any`] call may wait on.
+pub const IPC_RECV_ANY_MAX_SLOTS: usize = 8;
+/// `IpcRecvAnyDesc` magic (`'N''X''R''A'`).
+pub const IPC_RECV_ANY_DESC_MAGIC: u32 = u32::from_be_bytes(*b"NXRA");
+/// `IpcRecvAnyDesc` version.
+pub const IPC_RECV_ANY_DESC_VERSION: u32 = 1;
+
+/// IPC recv-any descriptor (`SYSCALL_IPC_RECV_ANY = 53`).
+///
+/// Part of the **kernel/userspace syscall ABI** (88 bytes, little-endian, layout-stable). The
+/// slots travel inline so the kernel copies in a single fixed-size block.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IpcRecvAnyDesc {
+    /// Descriptor magic ('N''X''R''A').
+    pub magic: u32,
+    /// Descriptor version.
+    pub version: u32,
+    /// Number of valid entries in `slots` (1..=[`IPC_RECV_ANY_MAX_SLOTS`]).
+    pub slot_count: u32,
+    /// Index returned by the previous call; the kernel scans from the slot after it.
+    pub prev_index: u32,
+    /// Receive endpoint capability slots.
+    pub slots: [u32; IPC_RECV_ANY_MAX_SLOTS],
+    /// User pointer to `MsgHeader` to be written by the kernel.
+    pub header_out_ptr: u64,
+    /// User pointer to payload buffer to be written by the kernel.
+    pub payload_out_ptr: u64,
+    /// Maximum payload bytes the kernel may write.
+    pub payload_out_max: u64,
+    /// User pointer to `u64` where the kernel writes the ready slot's index.
+    pub which_out_ptr: u64,
+    /// Deadline in nanoseconds (`0` means no deadline).
+    pub deadline_ns: u64,
+}
+
+impl IpcRecvAnyDesc {
+    /// Encoded size in bytes.
+    pub const LEN: usize = 88;
+
+    /// Builds a descriptor for `slots`; `None` when `slots` is empty or exceeds
+    /// [`IPC_RECV_ANY_MAX_SLOTS`] (the wrapper reports that as `Unsupported`). Output pointers
+    /// start at zero.
+    pub fn new(slots: &[u32], prev_index: u32, deadline_ns: u64) -> Option<Self> {
+        if slots.is_empty() || slots.len() > IPC_RECV_ANY_MAX_SLOTS {
+            return None;
+        }
+        let mut inline = [0u32; IPC_RECV_ANY_MAX_SLOTS];
+        inline[..slots.len()].copy_from_slice(slots);
+        Some(Self {
+            magic: IPC_RECV_ANY_DESC_MAGIC,
+            version: IPC_RECV_ANY_DESC_VERSION,
+            slot_count: slots.len() as u32,
+            prev_index,
+            slots: inline,
+            header_out_ptr: 0,
+            payload_out_ptr: 0,
+            payload_out_max: 0,
+            which_out_ptr: 0,
+            deadline_ns,
+        })
+    }
+
+    /// Encodes the descriptor exactly as the kernel reads it.
+    pub fn to_le_bytes(&self) -> [u8; Self::LEN] {
+        let mut out = [0u8; Self::LEN];
+        let words = [self.magic, self.version, self.slot_count, self.prev_index];
+        for (chunk, word) in out[..48].chunks_exact_mut(4).zip(words.iter().chain(&self.slots)) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        let tail = [
+            self.header_out_ptr,
+            self.payload_out_ptr,
+            self.payload_out_max,
+            self.which_out_ptr,
+            self.deadline_ns,
+        ];
+        for (chunk, value) in out[48..].chunks_exact_mut(8).zip(tail) {
+            chunk.copy_from_slice(&value.to_le_bytes());
+        }
+        out
+    }
+
+    /// Decodes a descriptor produced by [`IpcRecvAnyDesc::to_le_bytes`].
+    pub fn from_le_bytes(bytes: [u8; Self::LEN]) -> Self {
+        let word =
+            |i: usize| u32::from_le_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+        let wide = |i: usize| {
+            let mut b = [0u8; 8];
+            b.copy_from_slice(&bytes[i..i + 8]);
+            u64::from_le_bytes(b)
+        };
+        let mut slots = [0u32; IPC_RECV_ANY_MAX_SLOTS];
+        for (index, slot) in slots.iter_mut().enumerate() {
+            *slot = word(16 + index * 4);
+        }
+        Self {
+            magic: word(0),
+            version: word(4),
+            slot_count: word(8),
+            prev_index: word(12),
+            slots,
+            header_out_ptr: wide(48),
+            payload_out_ptr: wide(56),
+            payload_out_max: wide(64),
+            which_out_ptr: wide(72),
+            deadline_ns: wide(80),
+        }
+    }
+}
+
+/// Blocks until any endpoint in `slots` has a message, receives it, and stores the index of
+/// the ready slot in `which_out` (`SYSCALL_IPC_RECV_ANY = 53`).
+///
+/// Lets one loop serve e.g. a request and a control endpoint without busy-polling
+/// non-blocking receives. At most [`IPC_RECV_ANY_MAX_SLOTS`] slots; each needs
+/// `Rights::RECV`. `deadline_ns = 0` means no deadline, otherwise [`IpcError::TimedOut`].
+///
+/// Fairness: `which_out` is also read. The kernel scans ready slots round-robin starting
+/// after `*which_out` (an out-of-range value starts at slot 0), so reusing the same variable
+/// across calls keeps a busy endpoint from starving the others. A payload larger than
+/// `payload_out` is rejected like a non-truncating [`ipc_recv_v1`] ([`IpcError::Truncated`];
+/// `which_out` is left unchanged so the retry scans that slot again).
+#[cfg(nexus_env = "os")]
+pub fn ipc_recv_any(
+    slots: &[Cap],
+    header_out: &mut MsgHeader,
+    payload_out: &mut [u8],
+    which_out: &mut usize,
+    deadline_ns: u64,
+) -> Result<usize> {
+    let prev_index = u32::try_from(*which_out).unwrap_or(u32::MAX);
+    let Some(mut desc) = IpcRecvAnyDesc::new(slots, prev_index, deadline_ns) else {
+        return Err(IpcError::Unsupported);
+    };
+    #[cfg(all(target_arch = "riscv64", target_os = "none"))]
+    {
+        const SYSCALL_IPC_RECV_ANY: usize = 53;
+        let mut which: u64 = 0;
+        desc.header_out_ptr = header_out as *mut MsgHeader as u64;
+        desc.payload_out_ptr = payload_out.as_mut_ptr() as u64;
+        desc.payload_out_max = payload_out.len() as u64;
+        desc.which_out_ptr = &mut which as *mut u64 as u64;
+        let raw = unsafe { ecall1(SYSCALL_IPC_RECV_ANY, &desc as *const _ as usize) };
+        let n = decode_ipc_recv(raw)?;
+        *which_out = which as usize;
+        Ok(n)
+    }
+    #[cfg(not(all(target_arch = "riscv64", target_os = "none")))]
+    {
+        let _ = (&mut desc, header_out, payload_out);
+        Err(IpcError::Unsupported)
+    }
+}
+
+/// Host stub: there are no kernel endpoints to wait on.
+#[cfg(not(nexus_env = "os"))]
+pub fn ipc_recv_any(
+    slots: &[u32],
+    header_out: &mut crate::MsgHeader,
+    payload_out: &mut [u8],
+    which_out: &mut usize,
+    deadline_ns: u64,
+) -> crate::Result<usize> {
+    let _ = (slots, header_out, payload_out, which_out, deadline_ns);
+    Err(crate::IpcError::Unsupported)
+}
+
+/// `IpcRecvVmoDesc` magic (`'N''X''R''V'`).
+pub const IPC_RECV_VMO_DESC_MAGIC: u32 = u32::from_be_bytes(*b"NXRV");
+/// `IpcRecvVmoDesc` version.
+pub const IPC_RECV_VMO_DESC_VERSION: u32 = 1;
+
+/// IPC recv-into-VMO descriptor (`SYSCALL_IPC_RECV_VMO = 55`).
+///
+/// Part of the **kernel/userspace syscall ABI** (48 bytes, little-endian, layout-stable). The
+/// payload window is `vmo_offset..vmo_offset + max` inside the VMO named by `vmo_slot`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IpcRecvVmoDesc {
+    /// Descriptor magic ('N''X''R''V').
+    pub magic: u32,
+    /// Descriptor version.
+    pub version: u32,
+    /// Receive endpoint capability slot.
+    pub slot: u32,
+    /// VMO capability slot the payload is written into.
+    pub vmo_slot: u32,
+    /// User pointer to `MsgHeader` to be written by the kernel.
+    pub header_out_ptr: u64,
+    /// Byte offset of the payload window inside the VMO.
+    pub vmo_offset: u64,
+    /// Maximum payload bytes the kernel may write into the window.
+    pub max: u64,
+    /// Deadline in nanoseconds (`0` means no deadline).
+    pub deadline_ns: u64,
+}
+
+impl IpcRecvVmoDesc {
+    /// Encoded size in bytes.
+    pub const LEN: usize = 48;
+
+    /// Builds a descriptor; the header pointer starts at zero.
+    pub fn new(slot: u32, vmo_slot: u32, vmo_offset: u64, max: u64, deadline_ns: u64) -> Self {
+        Self {
+            magic: IPC_RECV_VMO_DESC_MAGIC,
+            version: IPC_RECV_VMO_DESC_VERSION,
+            slot,
+            vmo_slot,
+            header_out_ptr: 0,
+            vmo_offset,
+            max,
+            deadline_ns,
+        }
+    }
+
+    /// Encodes the descriptor exactly as the kernel reads it.
+    pub fn to_le_bytes(&self) -> [u8; Self::LEN] {
+        let mut out = [0u8; Self::LEN];
+        let words = [self.magic, self.version, self.slot, self.vmo_slot];
+        for (chunk, word) in out[..16].chunks_exact_mut(4).zip(words) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        let tail = [self.header_out_ptr, self.vmo_offset, self.max, self.deadline_ns];
+        for (chunk, value) in out[16..].chunks_exact_mut(8).zip(tail) {
+            chunk.copy_from_slice(&value.to_le_bytes());
+        }
+        out
+    }
+
+    /// Decodes a descriptor produced by [`IpcRecvVmoDesc::to_le_bytes`].
+    pub fn from_le_bytes(bytes: [u8; Self::LEN]) -> Self {
+        let word =
+            |i: usize| u32::from_le_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+        let wide = |i: usize| {
+            let mut b = [0u8; 8];
+            b.copy_from_slice(&bytes[i..i + 8]);
+            u64::from_le_bytes(b)
+        };
+        Self {
+            magic: word(0),
+            version: word(4),
+            slot: word(8),
+            vmo_slot: word(12),
+            header_out_ptr: wide(16),
+            vmo_offset: wide(24),
+            max: wide(32),
+            deadline_ns: wide(40),
+        }
+    }
+}
+
+/// Receives a message on `slot` with the kernel writing the payload straight into the VMO
+/// `vmo` at `offset` (`SYSCALL_IPC_RECV_VMO = 55`), skipping the `payload_out` buffer of
+/// [`ipc_recv_v1`]. Returns the payload length; the bytes are read back through a mapping of
+/// the VMO or [`vmo_read`].
+///
+/// Rights: `Rights::RECV` on `slot` and `Rights::MAP` on `vmo` — the right [`vmo_write`]
+/// checks, i.e. the holder may write the VMO. `offset..offset + max` must lie inside the VMO.
+/// A payload larger than `max` fails with [`IpcError::Truncated`] and stays queued, as with a
+/// non-truncating [`ipc_recv_v1`]; `max = 0` admits only an empty payload, not "no limit".
+/// `deadline_ns = 0` means no deadline.
+#[cfg(nexus_env = "os")]
+pub fn ipc_recv_into_vmo(
+    slot: Cap,
+    vmo: Handle,
+    offset: usize,
+    max: usize,
+    header_out: &mut MsgHeader,
+    deadline_ns: u64,
+) -> Result<usize> {
+    let mut desc = IpcRecvVmoDesc::new(slot, vmo, offset as u64, max as u64, deadline_ns);
+    #[cfg(all(target_arch = "riscv64", target_os = "none"))]
+    {
+        const SYSCALL_IPC_RECV_VMO: usize = 55;
+        desc.header_out_ptr = header_out as *mut MsgHeader as u64;
+        let raw = unsafe { ecall1(SYSCALL_IPC_RECV_VMO, &desc as *const _ as usize) };
+        decode_ipc_recv(raw)
+    }
+    #[cfg(not(all(target_arch = "riscv64", target_os = "none")))]
+    {
+        let _ = (&mut desc, header_out);
+        Err(IpcError::Unsupported)
+    }
+}
+
+/// Host stub: there are no kernel endpoints or VMOs.
+#[cfg(not(nexus_env = "os"))]
+pub fn ipc_recv_into_vmo(
+    slot: u32,
+    vmo: u32,
+    offset: usize,
+    max: usize,
+    header_out: &mut crate::MsgHeader,
+    deadline_ns: u64,
+) -> crate::Result<usize> {
+    let _ = (slot, vmo, offset, max, header_out, deadline_ns);
+    Err(crate::IpcError::Unsupported)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::decode_ipc_recv;
+    use crate::IpcError;
+
+    #[test]
+    fn test_reject_oversized_receive_maps_to_truncated() {
+        let errno = |e: isize| (-e) as usize;
+        assert_eq!(decode_ipc_recv(errno(90)), Err(IpcError::Truncated));
+        assert_eq!(decode_ipc_recv(errno(11)), Err(IpcError::QueueEmpty));
+        assert_eq!(decode_ipc_recv(errno(110)), Err(IpcError::TimedOut));
+        // EINVAL and unknown errnos stay generic.
+        assert_eq!(decode_ipc_recv(errno(22)), Err(IpcError::Unsupported));
+        assert_eq!(decode_ipc_recv(48), Ok(48));
+    }
+}
@@ -8,6 +8,7 @@
 
 pub mod caps;
 pub mod debug;
+pub mod debug_line;
 pub mod ipc;
 pub mod memory;
 pub mod task;
@@ -17,6 +18,7 @@ pub mod types;
 #[cfg(nexus_env = "os")]
 pub use caps::*;
 pub use debug::*;
+pub use debug_line::*;
 pub use ipc::*;
 #[cfg(nexus_env = "os")]
 pub use memory::*;
@@ -75,14 +77,14 @@ pub(crate) unsafe fn ecall1(n: usize, a0: usize) -> usize {
 #[cfg(all(nexus_env = "os", target_arch = "riscv64", target_os = "none"))]
 #[allow(unused_assignments)]
 #[inline(always)]
-pub(crate) unsafe fn ecall1_pair(n: usize, a0: usize) -> (usize, usize) {
+pub(crate) unsafe fn ecall2_pair(n: usize, a0: usize, a1: usize) -> (usize, usize) {
     let mut r0 = a0;
+    let mut r1 = a1;
     let mut r7 = n;
-    let mut r1: usize;
     core::arch::asm!(
         "ecall",
         inout("a0") r0,
-        lateout("a1") r1,
+        inout("a1") r1,
         inout("a7") r7,
         clobber_abi("C"),
         options(nostack)
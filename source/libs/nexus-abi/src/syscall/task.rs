@@ -426,6 +426,81 @@ pub fn exec_v2(
     }
 }
 
+/// Upper bound of a provenance name (the kernel's `exec_v2` limit).
+pub const PROVENANCE_NAME_MAX: usize = 64;
+/// Child VA of the read-only `BootstrapInfo` page `exec_v2` maps (stack top + 12 pages).
+pub const BOOTSTRAP_INFO_VA: usize = 0x2000_C000;
+/// Child VA of the read-only page holding the service name (just below the info page).
+pub const BOOTSTRAP_META_VA: usize = BOOTSTRAP_INFO_VA - 4096;
+/// Bytes of `BootstrapInfo` (v2) read by [`provenance_name_from`].
+pub const BOOTSTRAP_INFO_LEN: usize = 32;
+
+/// Copies the current task's service name, as passed to `exec_v2`, into `buf`.
+///
+/// Reads the kernel's read-only metadata mapping (RFC-0004), so the name is immutable for
+/// the task's lifetime and cannot be spoofed by the task itself. Only tasks started with
+/// `exec_v2` have the mapping; the init-lite service path always does. `InvalidArgument`
+/// when the info page is not v2 or `buf` is shorter than the name (at most
+/// [`PROVENANCE_NAME_MAX`] bytes).
+#[cfg(nexus_env = "os")]
+pub fn provenance_name(buf: &mut [u8]) -> SysResult<usize> {
+    #[cfg(all(target_arch = "riscv64", target_os = "none"))]
+    {
+        let mut info = [0u8; BOOTSTRAP_INFO_LEN];
+        let mut meta = [0u8; PROVENANCE_NAME_MAX];
+        // SAFETY: `exec_v2` maps both pages readable for the task's lifetime; the reads stay
+        // inside them and the kernel never writes them after spawn.
+        unsafe {
+            core::ptr::copy_nonoverlapping(
+                BOOTSTRAP_INFO_VA as *const u8,
+                info.as_mut_ptr(),
+                info.len(),
+            );
+            core::ptr::copy_nonoverlapping(
+                BOOTSTRAP_META_VA as *const u8,
+                meta.as_mut_ptr(),
+                meta.len(),
+            );
+        }
+        provenance_name_from(&info, &meta, buf).ok_or(AbiError::InvalidArgument)
+    }
+    #[cfg(not(all(target_arch = "riscv64", target_os = "none")))]
+    {
+        let _ = buf;
+        Err(AbiError::Unsupported)
+    }
+}
+
+/// Host stub: there is no bootstrap mapping.
+#[cfg(not(nexus_env = "os"))]
+pub fn provenance_name(buf: &mut [u8]) -> crate::Result<usize> {
+    let _ = buf;
+    Err(crate::IpcError::Unsupported)
+}
+
+/// Decodes a `BootstrapInfo` page and copies the name it describes from `meta` into `buf`.
+///
+/// `None` unless the info is v2 or later, points at [`BOOTSTRAP_META_VA`], and the name
+/// fits both [`PROVENANCE_NAME_MAX`] and `buf`. Returns the name length.
+pub fn provenance_name_from(
+    info: &[u8; BOOTSTRAP_INFO_LEN],
+    meta: &[u8],
+    buf: &mut [u8],
+) -> Option<usize> {
+    let word = |at: usize| u32::from_le_bytes([info[at], info[at + 1], info[at + 2], info[at + 3]]);
+    let mut ptr = [0u8; 8];
+    ptr.copy_from_slice(&info[8..16]);
+    let len = word(16) as usize;
+    if word(0) < 2 || u64::from_le_bytes(ptr) != BOOTSTRAP_META_VA as u64 {
+        return None;
+    }
+    if len > PROVENANCE_NAME_MAX || len > buf.len() {
+        return None;
+    }
+    buf[..len].copy_from_slice(meta.get(..len)?);
+    Some(len)
+}
+
 /// Terminates the current task with the provided exit `status`.
 #[cfg(nexus_env = "os")]
 pub fn exit(status: i32) -> ! {
@@ -453,7 +528,7 @@ pub fn wait(pid: i32) -> SysResult<(Pid, i32)> {
     #[cfg(all(target_arch = "riscv64", target_os = "none"))]
     {
         const SYSCALL_WAIT: usize = 12;
-        let (raw_pid, raw_status) = unsafe { ecall1_pair(SYSCALL_WAIT, pid as usize) };
+        let (raw_pid, raw_status) = unsafe { ecall2_pair(SYSCALL_WAIT, pid as usize, 0) };
         let pid = decode_syscall(raw_pid)?;
         Ok((pid as Pid, raw_status as i32))
     }
@@ -463,3 +538,59 @@ pub fn wait(pid: i32) -> SysResult<(Pid, i32)> {
         Err(AbiError::Unsupported)
     }
 }
+
+/// Non-blocking [`wait`]: `Ok(None)` while the targeted child (or every child) is still running.
+#[cfg(nexus_env = "os")]
+pub fn try_wait(pid: i32) -> SysResult<Option<(Pid, i32)>> {
+    #[cfg(all(target_arch = "riscv64", target_os = "none"))]
+    {
+        const SYSCALL_WAIT: usize = 12;
+        const WAIT_NOHANG: usize = 1 << 0;
+        let (raw_pid, raw_status) = unsafe { ecall2_pair(SYSCALL_WAIT, pid as usize, WAIT_NOHANG) };
+        match decode_syscall(raw_pid) {
+            Ok(pid) => Ok(Some((pid as Pid, raw_status as i32))),
+            Err(AbiError::WouldBlock) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+    #[cfg(not(all(target_arch = "riscv64", target_os = "none")))]
+    {
+        let _ = pid;
+        Err(AbiError::Unsupported)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(version: u32, name_ptr: u64, name_len: u32) -> [u8; BOOTSTRAP_INFO_LEN] {
+        let mut info = [0u8; BOOTSTRAP_INFO_LEN];
+        info[0..4].copy_from_slice(&version.to_le_bytes());
+        info[8..16].copy_from_slice(&name_ptr.to_le_bytes());
+        info[16..20].copy_from_slice(&name_len.to_le_bytes());
+        info
+    }
+
+    #[test]
+    fn provenance_name_reads_the_described_bytes() {
+        let mut meta = [0u8; PROVENANCE_NAME_MAX];
+        meta[..8].copy_from_slice(b"metricsd");
+        let mut buf = [0u8; PROVENANCE_NAME_MAX];
+        let len = provenance_name_from(&info(2, BOOTSTRAP_META_VA as u64, 8), &meta, &mut buf);
+        assert_eq!(len, Some(8));
+        assert_eq!(&buf[..8], b"metricsd");
+        assert_eq!(provenance_name(&mut buf), Err(crate::IpcError::Unsupported));
+    }
+
+    #[test]
+    fn test_reject_malformed_info_or_short_buffer() {
+        let meta = [b'n'; PROVENANCE_NAME_MAX];
+        let meta_va = BOOTSTRAP_META_VA as u64;
+        let mut buf = [0u8; PROVENANCE_NAME_MAX];
+        assert_eq!(provenance_name_from(&info(1, meta_va, 8), &meta, &mut buf), None);
+        assert_eq!(provenance_name_from(&info(2, meta_va + 8, 8), &meta, &mut buf), None);
+        assert_eq!(provenance_name_from(&info(2, meta_va, 65), &meta, &mut buf), None);
+        assert_eq!(provenance_name_from(&info(2, meta_va, 8), &meta, &mut buf[..4]), None);
+    }
+}
@@ -22,6 +22,44 @@ pub fn nsec() -> SysResult<u64> {
     }
 }
 
+/// Nanoseconds per millisecond, for [`deadline_in_with`].
+const NS_PER_MS: u64 = 1_000_000;
+
+/// The "no deadline" value the IPC/wait wrappers take (`deadline_ns = 0`).
+pub const fn deadline_none() -> u64 {
+    0
+}
+
+/// Absolute deadline `ms` milliseconds after the current [`nsec`] time.
+///
+/// Pass the result straight to a wrapper's `deadline_ns`. See [`deadline_in_with`] for the
+/// arithmetic; the error is `nsec`'s (`Unsupported` off the RISC-V target).
+#[cfg(nexus_env = "os")]
+pub fn deadline_in(ms: u64) -> SysResult<u64> {
+    deadline_in_with(nsec, ms)
+}
+
+/// Host stub: there is no kernel clock.
+#[cfg(not(nexus_env = "os"))]
+pub fn deadline_in(ms: u64) -> crate::Result<u64> {
+    let _ = ms;
+    Err(crate::IpcError::Unsupported)
+}
+
+/// [`deadline_in`] reading the time from `now_ns` instead of the kernel (tests inject a clock).
+///
+/// The sum saturates: a delay past the end of the `u64` nanosecond range yields `u64::MAX`,
+/// a deadline that never arrives in practice but is still a deadline rather than
+/// [`deadline_none`]. The result is never 0, so `deadline_in(0)` at boot (time 0) means
+/// "already due", not "wait forever".
+pub fn deadline_in_with<E>(
+    now_ns: impl FnOnce() -> core::result::Result<u64, E>,
+    ms: u64,
+) -> core::result::Result<u64, E> {
+    let now = now_ns()?;
+    Ok(now.saturating_add(ms.saturating_mul(NS_PER_MS)).max(1))
+}
+
 /// Creates a kernel timer capability bound to `notify_ep_cap`.
 ///
 /// `notify_ep_cap` must reference an endpoint capability in the caller's cap table.
@@ -187,3 +225,29 @@ pub fn fence_wait(fence_cap: Cap, target: u64, deadline_ns: u64) -> SysResult<()
         Err(AbiError::Unsupported)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{deadline_in_with, deadline_none};
+
+    fn clock(now: u64) -> impl FnOnce() -> Result<u64, ()> {
+        move || Ok(now)
+    }
+
+    #[test]
+    fn deadline_is_now_plus_milliseconds() {
+        assert_eq!(deadline_none(), 0);
+        assert_eq!(deadline_in_with(clock(1_000), 500), Ok(500_001_000));
+        assert_eq!(deadline_in_with(clock(7), 0), Ok(7));
+        // Never collapses to "no deadline" at time 0.
+        assert_eq!(deadline_in_with(clock(0), 0), Ok(1));
+    }
+
+    #[test]
+    fn saturates_near_u64_max_and_propagates_clock_errors() {
+        assert_eq!(deadline_in_with(clock(u64::MAX - 10), 1), Ok(u64::MAX));
+        assert_eq!(deadline_in_with(clock(5), u64::MAX), Ok(u64::MAX));
+        assert_eq!(deadline_in_with(|| Err::<u64, _>("no clock"), 500), Err("no clock"));
+        assert_eq!(super::deadline_in(500), Err(crate::IpcError::Unsupported));
+    }
+}
@@ -7,7 +7,8 @@
 
 // ——— Task and capability primitives (OS build) ———
 
-#[cfg(nexus_env = "os")]
+// Rights, SysResult and AbiError are plain data: also compiled for host tests.
+#[cfg(any(nexus_env = "os", test))]
 bitflags::bitflags! {
     /// Rights mask accepted by capability-transfer syscalls.
     #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -23,6 +24,48 @@ bitflags::bitflags! {
     }
 }
 
+#[cfg(any(nexus_env = "os", test))]
+impl Rights {
+    /// Rights a holder needs to send on an endpoint.
+    pub const fn for_send() -> Self {
+        Self::SEND
+    }
+
+    /// Rights a holder needs to receive from an endpoint.
+    pub const fn for_recv() -> Self {
+        Self::RECV
+    }
+
+    /// Rights a holder needs to map a VMO.
+    pub const fn for_map() -> Self {
+        Self::MAP
+    }
+
+    /// Checks that this mask grants every bit in `required`.
+    ///
+    /// Fails with [`AbiError::CapabilityDenied`] — the error the kernel reports for the
+    /// same condition — when any required bit is missing. An empty `required` always
+    /// passes.
+    pub const fn validate_for(self, required: Rights) -> SysResult<()> {
+        if self.contains(required) {
+            Ok(())
+        } else {
+            Err(AbiError::CapabilityDenied)
+        }
+    }
+
+    /// Rights for a capability derived from one holding `self`: `self & requested`.
+    ///
+    /// Derivation only ever narrows. Bits in `requested` the parent lacks are dropped rather
+    /// than granted, mirroring the intersection the kernel applies on transfer, so a
+    /// sub-capability can never be wider than the capability it came from.
+    pub const fn derive(self, requested: Rights) -> Rights {
+        let derived = self.intersection(requested);
+        debug_assert!(self.contains(derived));
+        derived
+    }
+}
+
 /// Kernel task identifier returned from [`spawn`].
 #[cfg(nexus_env = "os")]
 pub type Pid = u32;
@@ -40,11 +83,11 @@ pub type Handle = u32;
 pub type AsHandle = u64;
 
 /// Result returned by privileged syscalls that expose kernel operations.
-#[cfg(nexus_env = "os")]
+#[cfg(any(nexus_env = "os", test))]
 pub type SysResult<T> = core::result::Result<T, AbiError>;
 
 /// Errors surfaced when invoking privileged syscalls from userland.
-#[cfg(nexus_env = "os")]
+#[cfg(any(nexus_env = "os", test))]
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum AbiError {
     /// Syscall number is not implemented by the kernel build.
@@ -165,3 +208,59 @@ impl AbiError {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{AbiError, Rights};
+
+    #[test]
+    fn validate_for_accepts_supersets() {
+        assert_eq!(Rights::SEND.validate_for(Rights::for_send()), Ok(()));
+        assert_eq!(Rights::RECV.validate_for(Rights::for_recv()), Ok(()));
+        assert_eq!(Rights::MAP.validate_for(Rights::for_map()), Ok(()));
+        assert_eq!((Rights::SEND | Rights::RECV).validate_for(Rights::for_recv()), Ok(()));
+        assert_eq!(Rights::all().validate_for(Rights::all()), Ok(()));
+        // Nothing required: any mask, including the empty one, passes.
+        assert_eq!(Rights::empty().validate_for(Rights::empty()), Ok(()));
+        assert_eq!(Rights::MANAGE.validate_for(Rights::empty()), Ok(()));
+    }
+
+    #[test]
+    fn test_reject_missing_required_rights() {
+        let denied = Err(AbiError::CapabilityDenied);
+        assert_eq!(Rights::empty().validate_for(Rights::for_send()), denied);
+        assert_eq!(Rights::RECV.validate_for(Rights::for_send()), denied);
+        assert_eq!(Rights::SEND.validate_for(Rights::for_recv()), denied);
+        assert_eq!((Rights::SEND | Rights::RECV).validate_for(Rights::for_map()), denied);
+        // Partially held: every required bit must be present.
+        assert_eq!(Rights::SEND.validate_for(Rights::SEND | Rights::RECV), denied);
+        assert_eq!((Rights::all() - Rights::MANAGE).validate_for(Rights::all()), denied);
+        assert_eq!(Rights::empty().validate_for(Rights::all()), denied);
+    }
+
+    #[test]
+    fn derive_never_widens() {
+        let masks = (0..=Rights::all().bits()).map(Rights::from_bits_truncate);
+        for parent in masks.clone() {
+            for requested in masks.clone() {
+                let derived = parent.derive(requested);
+                assert!(parent.contains(derived), "{parent:?} -> {derived:?}");
+                assert_eq!(derived, parent & requested);
+            }
+        }
+        assert_eq!((Rights::SEND | Rights::RECV).derive(Rights::SEND), Rights::SEND);
+        assert_eq!(Rights::all().derive(Rights::all()), Rights::all());
+    }
+
+    #[test]
+    fn test_reject_deriving_rights_the_parent_lacks() {
+        // Asking for more than the parent holds yields only the overlap, never the extra bits.
+        assert_eq!(Rights::SEND.derive(Rights::SEND | Rights::MANAGE), Rights::SEND);
+        assert_eq!(Rights::RECV.derive(Rights::SEND), Rights::empty());
+        assert_eq!(Rights::empty().derive(Rights::all()), Rights::empty());
+        assert_eq!(
+            (Rights::all() - Rights::MANAGE).derive(Rights::all()),
+            Rights::all() - Rights::MANAGE
+        );
+    }
+}
@@ -0,0 +1,139 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: Optional frame version negotiation (one-shot `OP_HELLO`)
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: 3 host unit tests (overlap, disjoint, malformed hello)
+//!
+//! Service protocols frame requests as `MAGIC0 MAGIC1 VERSION OP ...` and reject any
+//! `VERSION` they do not speak. A client that wants to roll forward may first send a hello
+//! in the same layout:
+//!
+//! `MAGIC0 MAGIC1 0 OP_HELLO | supported:u32` (8 bytes, little-endian bitmap)
+//!
+//! Bit `v` of `supported` means "speaks version `v`" (versions `1..=31`; bit 0 is
+//! reserved). The version byte is [`HELLO_VERSION`] (0), which no data frame uses, so a
+//! server without hello support rejects it like any other version mismatch and the client
+//! falls back to its default version. A server that understands the hello answers with a
+//! hello whose bitmap has only the chosen version set, or is empty when nothing overlaps.
+//! Both sides then frame data with that version. The hello is additive: nothing requires
+//! it, and frames of an agreed version are unchanged.
+
+/// Version byte of a hello frame; data frames always use a version `>= 1`.
+pub const HELLO_VERSION: u8 = 0;
+/// Opcode a service reserves for the hello frame.
+pub const OP_HELLO: u8 = 0xFF;
+/// Encoded hello frame length.
+pub const HELLO_LEN: usize = 8;
+/// Highest version a hello bitmap can carry.
+pub const MAX_HELLO_VERSION: u8 = 31;
+
+/// Highest version in `local` that is at most `remote_max` (`None` when there is none).
+///
+/// Version 0 is never selected.
+pub fn negotiate(local: &[u8], remote_max: u8) -> Option<u8> {
+    local.iter().copied().filter(|&v| v != HELLO_VERSION && v <= remote_max).max()
+}
+
+/// Highest version in `local` whose bit is set in the peer's hello bitmap.
+pub fn negotiate_bitmap(local: &[u8], remote: u32) -> Option<u8> {
+    local.iter().copied().filter(|&v| bit(v).is_some_and(|b| remote & b != 0)).max()
+}
+
+/// Bitmap with a bit per version in `versions`; `None` for 0 or a version above 31.
+pub fn supported_bitmap(versions: &[u8]) -> Option<u32> {
+    versions.iter().try_fold(0u32, |acc, &v| Some(acc | bit(v)?))
+}
+
+/// Encodes a hello frame for a protocol with the given `magic` bytes.
+pub fn encode_hello(magic: [u8; 2], supported: u32) -> Option<[u8; HELLO_LEN]> {
+    if supported & 1 != 0 {
+        return None;
+    }
+    let mut out = [0u8; HELLO_LEN];
+    out[..4].copy_from_slice(&[magic[0], magic[1], HELLO_VERSION, OP_HELLO]);
+    out[4..].copy_from_slice(&supported.to_le_bytes());
+    Some(out)
+}
+
+/// Decodes a hello frame for `magic` into its version bitmap.
+///
+/// Returns `None` unless the frame is exactly [`HELLO_LEN`] bytes with `magic`,
+/// [`HELLO_VERSION`], [`OP_HELLO`] and the reserved bit 0 clear.
+pub fn decode_hello(frame: &[u8], magic: [u8; 2]) -> Option<u32> {
+    let [m0, m1, version, op, b0, b1, b2, b3] = *frame else {
+        return None;
+    };
+    if [m0, m1] != magic || version != HELLO_VERSION || op != OP_HELLO {
+        return None;
+    }
+    let supported = u32::from_le_bytes([b0, b1, b2, b3]);
+    (supported & 1 == 0).then_some(supported)
+}
+
+fn bit(version: u8) -> Option<u32> {
+    (version != HELLO_VERSION && version <= MAX_HELLO_VERSION).then(|| 1u32 << version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAGIC: [u8; 2] = *b"MT";
+
+    #[test]
+    fn overlapping_ranges_select_the_highest_common_version() {
+        assert_eq!(negotiate(&[1, 2, 3], 2), Some(2));
+        assert_eq!(negotiate(&[3, 1], 9), Some(3));
+
+        let client = supported_bitmap(&[1, 2, 4]).unwrap();
+        let hello = encode_hello(MAGIC, client).unwrap();
+        assert_eq!(hello, [b'M', b'T', 0, OP_HELLO, 0b1_0110, 0, 0, 0]);
+        let remote = decode_hello(&hello, MAGIC).unwrap();
+        let chosen = negotiate_bitmap(&[2, 3, 4, 5], remote);
+        assert_eq!(chosen, Some(4));
+
+        // The server answers with only the chosen bit set.
+        let reply = encode_hello(MAGIC, supported_bitmap(&[4]).unwrap()).unwrap();
+        let agreed = decode_hello(&reply, MAGIC).unwrap();
+        assert_eq!(negotiate_bitmap(&[1, 2, 4], agreed), Some(4));
+    }
+
+    #[test]
+    fn disjoint_ranges_return_none() {
+        assert_eq!(negotiate(&[3, 4], 2), None);
+        assert_eq!(negotiate(&[0], 5), None);
+        assert_eq!(negotiate(&[], 5), None);
+        let remote = supported_bitmap(&[1, 2]).unwrap();
+        assert_eq!(negotiate_bitmap(&[3, 4], remote), None);
+        // An empty reply bitmap means "no common version".
+        let reply = encode_hello(MAGIC, 0).unwrap();
+        assert_eq!(negotiate_bitmap(&[1], decode_hello(&reply, MAGIC).unwrap()), None);
+    }
+
+    #[test]
+    fn test_reject_malformed_hello() {
+        let hello = encode_hello(MAGIC, 0b10).unwrap();
+        assert_eq!(decode_hello(&hello[..7], MAGIC), None);
+        let mut long = [0u8; 9];
+        long[..8].copy_from_slice(&hello);
+        assert_eq!(decode_hello(&long, MAGIC), None);
+        assert_eq!(decode_hello(&hello, *b"LG"), None);
+
+        let mut data_frame = hello;
+        data_frame[2] = 1;
+        assert_eq!(decode_hello(&data_frame, MAGIC), None);
+        let mut other_op = hello;
+        other_op[3] = 1;
+        assert_eq!(decode_hello(&other_op, MAGIC), None);
+        let mut reserved = hello;
+        reserved[4] |= 1;
+        assert_eq!(decode_hello(&reserved, MAGIC), None);
+
+        assert_eq!(encode_hello(MAGIC, 1), None);
+        assert_eq!(supported_bitmap(&[0]), None);
+        assert_eq!(supported_bitmap(&[32]), None);
+    }
+}
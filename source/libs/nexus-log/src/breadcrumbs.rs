@@ -0,0 +1,237 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: Breadcrumb trail — the last few `(target, level)` pairs that logged, oldest first
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: 3 unit tests (ordering, wrap-around, intern table overflow)
+//!
+//! Every record that reaches a sink leaves a crumb in a fixed ring of [`BREADCRUMB_DEPTH`]
+//! entries; [`breadcrumbs`] hands a crash handler the "who spoke last" view without keeping
+//! any message text. Targets are borrowed per call, so they are interned (truncated to
+//! [`MAX_TARGET_LEN`] bytes) into a write-once table of [`BREADCRUMB_TARGETS`] names;
+//! once that is full, new targets are reported as [`BREADCRUMB_OTHER_TARGET`].
+//!
+//! Lock-free: a writer reserves a sequence number and stores one packed `u64`; a reader
+//! keeps only entries stamped with the sequence it expects, so a crumb that is being
+//! overwritten concurrently is skipped rather than torn.
+
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicU64, AtomicU8, AtomicUsize, Ordering};
+
+use crate::config::{level_from_u8, MAX_TARGET_LEN};
+use crate::Level;
+
+/// Crumbs kept in the ring.
+pub const BREADCRUMB_DEPTH: usize = 16;
+/// Distinct target names the intern table can hold.
+pub const BREADCRUMB_TARGETS: usize = 32;
+/// Reported target for crumbs whose name did not fit in the intern table.
+pub const BREADCRUMB_OTHER_TARGET: &str = "<other>";
+
+const NAME_EMPTY: u8 = 0;
+const NAME_WRITING: u8 = 1;
+const NAME_READY: u8 = 2;
+/// Name index recorded for targets that could not be interned.
+const OTHER_INDEX: u8 = u8::MAX;
+
+/// One write-once intern slot.
+struct Name {
+    state: AtomicU8,
+    len: AtomicU8,
+    bytes: UnsafeCell<[u8; MAX_TARGET_LEN]>,
+}
+
+// SAFETY: `bytes` is written only by the writer that moved `state` from EMPTY to WRITING,
+// and read only after observing READY (Acquire); it is never written again after that.
+unsafe impl Sync for Name {}
+
+impl Name {
+    const fn new() -> Self {
+        Self {
+            state: AtomicU8::new(NAME_EMPTY),
+            len: AtomicU8::new(0),
+            bytes: UnsafeCell::new([0; MAX_TARGET_LEN]),
+        }
+    }
+
+    fn get(&'static self) -> Option<&'static str> {
+        if self.state.load(Ordering::Acquire) != NAME_READY {
+            return None;
+        }
+        let len = usize::from(self.len.load(Ordering::Relaxed));
+        // SAFETY: READY slots are immutable for the rest of the program (see `impl Sync`).
+        let bytes: &'static [u8; MAX_TARGET_LEN] = unsafe { &*self.bytes.get() };
+        core::str::from_utf8(bytes.get(..len)?).ok()
+    }
+
+    fn claim(&'static self, name: &str) -> bool {
+        if self
+            .state
+            .compare_exchange(NAME_EMPTY, NAME_WRITING, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            return false;
+        }
+        // SAFETY: the EMPTY -> WRITING transition makes this the only writer, and no reader
+        // touches `bytes` before READY is published below.
+        // `name.len() <= MAX_TARGET_LEN` (see `Trail::intern`).
+        unsafe {
+            core::ptr::copy_nonoverlapping(name.as_ptr(), self.bytes.get().cast(), name.len())
+        };
+        self.len.store(name.len() as u8, Ordering::Relaxed);
+        self.state.store(NAME_READY, Ordering::Release);
+        true
+    }
+}
+
+/// Intern table plus crumb ring; the process-wide instance is `TRAIL`.
+pub(crate) struct Trail {
+    names: [Name; BREADCRUMB_TARGETS],
+    /// Sequence number of the next crumb.
+    next: AtomicUsize,
+    /// `(seq + 1) << 16 | name_index << 8 | level`; `0` means never written.
+    ring: [AtomicU64; BREADCRUMB_DEPTH],
+}
+
+static TRAIL: Trail = Trail::new();
+
+impl Trail {
+    pub(crate) const fn new() -> Self {
+        Self {
+            names: [const { Name::new() }; BREADCRUMB_TARGETS],
+            next: AtomicUsize::new(0),
+            ring: [const { AtomicU64::new(0) }; BREADCRUMB_DEPTH],
+        }
+    }
+
+    /// Index of `target` in the intern table, adding it if there is room.
+    fn intern(&'static self, target: &str) -> u8 {
+        let mut len = target.len().min(MAX_TARGET_LEN);
+        while !target.is_char_boundary(len) {
+            len -= 1;
+        }
+        let name = &target[..len];
+        for (index, slot) in self.names.iter().enumerate() {
+            // A slot claimed concurrently for the same name may leave a duplicate entry later
+            // in the table; both resolve to the same text.
+            if slot.get() == Some(name) || slot.claim(name) {
+                return index as u8;
+            }
+        }
+        OTHER_INDEX
+    }
+
+    pub(crate) fn record(&'static self, target: &str, level: Level) {
+        let index = self.intern(target);
+        let seq = self.next.fetch_add(1, Ordering::Relaxed);
+        let packed = stamp(seq) << 16 | u64::from(index) << 8 | level as u64;
+        self.ring[seq % BREADCRUMB_DEPTH].store(packed, Ordering::Release);
+    }
+
+    pub(crate) fn read(&'static self, out: &mut [(&'static str, Level)]) -> usize {
+        let next = self.next.load(Ordering::Acquire);
+        let first = next - next.min(BREADCRUMB_DEPTH).min(out.len());
+        let mut written = 0;
+        for seq in first..next {
+            let packed = self.ring[seq % BREADCRUMB_DEPTH].load(Ordering::Acquire);
+            if packed >> 16 != stamp(seq) {
+                continue;
+            }
+            let Some(level) = level_from_u8(packed as u8) else { continue };
+            let index = usize::from((packed >> 8) as u8);
+            let target = self.names.get(index).and_then(Name::get);
+            out[written] = (target.unwrap_or(BREADCRUMB_OTHER_TARGET), level);
+            written += 1;
+        }
+        written
+    }
+}
+
+/// Ring stamp for `seq`: offset by one so an unwritten slot never matches, cut to 48 bits.
+fn stamp(seq: usize) -> u64 {
+    (seq as u64).wrapping_add(1) & (u64::MAX >> 16)
+}
+
+pub(crate) fn record(target: &str, level: Level) {
+    TRAIL.record(target, level);
+}
+
+/// Copies the most recent crumbs (at most `out.len()` and [`BREADCRUMB_DEPTH`]) into `out`,
+/// oldest first, and returns how many were written.
+///
+/// Best-effort under concurrency: a crumb being overwritten while it is read is left out.
+pub fn breadcrumbs(out: &mut [(&'static str, Level)]) -> usize {
+    TRAIL.read(out)
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+
+    const EMPTY: (&str, Level) = ("", Level::Trace);
+
+    #[test]
+    fn crumbs_read_back_oldest_first() {
+        static TRAIL: Trail = Trail::new();
+        let mut out = [EMPTY; BREADCRUMB_DEPTH];
+        assert_eq!(TRAIL.read(&mut out), 0);
+        TRAIL.record("netstackd", Level::Info);
+        TRAIL.record("dsoftbusd", Level::Warn);
+        TRAIL.record("netstackd", Level::Error);
+        assert_eq!(TRAIL.read(&mut out), 3);
+        assert_eq!(
+            out[..3],
+            [("netstackd", Level::Info), ("dsoftbusd", Level::Warn), ("netstackd", Level::Error)]
+        );
+
+        // A short buffer gets the most recent crumbs.
+        let mut last = [EMPTY; 2];
+        assert_eq!(TRAIL.read(&mut last), 2);
+        assert_eq!(last, [("dsoftbusd", Level::Warn), ("netstackd", Level::Error)]);
+    }
+
+    #[test]
+    fn ring_wraps_around_keeping_the_newest() {
+        static TRAIL: Trail = Trail::new();
+        const TARGETS: [&str; 3] = ["a", "b", "c"];
+        let total = BREADCRUMB_DEPTH * 2 + 5;
+        for i in 0..total {
+            TRAIL.record(TARGETS[i % 3], Level::Debug);
+        }
+        let mut out = [EMPTY; BREADCRUMB_DEPTH + 4];
+        assert_eq!(TRAIL.read(&mut out), BREADCRUMB_DEPTH);
+        for (offset, crumb) in out[..BREADCRUMB_DEPTH].iter().enumerate() {
+            let seq = total - BREADCRUMB_DEPTH + offset;
+            assert_eq!(*crumb, (TARGETS[seq % 3], Level::Debug));
+        }
+    }
+
+    #[test]
+    fn test_reject_intern_overflow_and_overlong_targets() {
+        static TRAIL: Trail = Trail::new();
+        const NAMES: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefgh";
+        for i in 0..=BREADCRUMB_TARGETS {
+            TRAIL.record(&NAMES[i..i + 1], Level::Info);
+        }
+        let mut out = [EMPTY; 2];
+        assert_eq!(TRAIL.read(&mut out), 2);
+        assert_eq!(out[0], (&NAMES[BREADCRUMB_TARGETS - 1..BREADCRUMB_TARGETS], Level::Info));
+        // The table is full: later names are reported as the shared overflow target, while
+        // already interned names keep resolving.
+        assert_eq!(out[1], (BREADCRUMB_OTHER_TARGET, Level::Info));
+        TRAIL.record("A", Level::Error);
+        assert_eq!(TRAIL.read(&mut out[..1]), 1);
+        assert_eq!(out[0], ("A", Level::Error));
+
+        // A long multi-byte target is cut on a char boundary at MAX_TARGET_LEN bytes.
+        static FRESH: Trail = Trail::new();
+        let long = "\u{e9}".repeat(MAX_TARGET_LEN);
+        FRESH.record(&long, Level::Warn);
+        assert_eq!(FRESH.read(&mut out), 1);
+        assert_eq!(out[0], (&long[..MAX_TARGET_LEN], Level::Warn));
+    }
+}
@@ -0,0 +1,406 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: Console-to-logd forwarding bridge for the kernel sink (`kernel-logd-bridge`)
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: 4 unit tests (`--features kernel-logd-bridge`)
+//!
+//! `sink-kernel` writes to the UART only, so early kernel-adjacent records never reach the
+//! central collector. With the `kernel-logd-bridge` feature every console record is also
+//! captured (cut at [`LOGD_BRIDGE_LINE_LEN`] bytes) and handed to the bridge from the sink's
+//! write path. Until [`set_logd_bridge`] installs a [`LogdEndpoint`] the records wait in a
+//! fixed backlog of [`LOGD_BRIDGE_BACKLOG`] lines, oldest evicted first. The first record
+//! written after an endpoint is installed replays the backlog in order, then goes out itself.
+//!
+//! Like `sink-logd` this is best effort: the endpoint is only offered records, never waited
+//! on. A record it declines stays queued behind the backlog and is offered again with the next
+//! record. The bridge never blocks: a record that finds the bridge busy (a trap logging while
+//! its own hart forwards) skips logd and is counted in [`logd_bridge_dropped`], like every
+//! evicted line. The UART copy is never affected. Without the feature, [`tee`] is a
+//! pass-through and nothing here is public.
+//!
+//! Under backpressure the topic priority table (`set_topic_priority`, see `throttle`) decides
+//! which records still join a nearly full backlog; every lost line is also counted per topic.
+//!
+//! INVARIANTS:
+//! - Bounded: no allocation; the backlog and the per-record capture are fixed arrays
+//! - Order: records reach the endpoint in the order they hit the console, minus dropped lines
+
+use crate::{LineMeta, LineSink};
+
+#[cfg(feature = "kernel-logd-bridge")]
+mod throttle;
+
+/// Runs `f` against `sink`; console records are also forwarded to logd through the bridge.
+#[cfg(not(feature = "kernel-logd-bridge"))]
+pub(crate) fn tee<R>(
+    meta: &LineMeta<'_>,
+    console: bool,
+    sink: &mut dyn LineSink,
+    f: impl FnOnce(&mut dyn LineSink) -> R,
+) -> R {
+    let _ = (meta, console);
+    f(sink)
+}
+
+#[cfg(feature = "kernel-logd-bridge")]
+pub(crate) use installed::tee;
+#[cfg(feature = "kernel-logd-bridge")]
+pub use installed::{
+    clear_logd_bridge, logd_bridge_dropped, set_logd_bridge, LogdEndpoint, LOGD_BRIDGE_BACKLOG,
+    LOGD_BRIDGE_LINE_LEN,
+};
+#[cfg(feature = "kernel-logd-bridge")]
+pub use throttle::{
+    clear_topic_priorities, set_topic_priority, topic_dropped, LOGD_PRESSURE_HEADROOM,
+    MAX_TOPIC_PRIORITIES,
+};
+
+#[cfg(feature = "kernel-logd-bridge")]
+mod installed {
+    use core::cell::UnsafeCell;
+    use core::fmt;
+    use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+    use super::{throttle, LineMeta, LineSink};
+    use crate::{Level, Topic};
+
+    /// Records held while no endpoint takes them.
+    pub const LOGD_BRIDGE_BACKLOG: usize = 16;
+    /// Bytes of a record that are forwarded (longer records are cut).
+    pub const LOGD_BRIDGE_LINE_LEN: usize = 160;
+
+    /// Non-blocking hand-off to logd (see [`set_logd_bridge`]).
+    pub trait LogdEndpoint: Sync {
+        /// Offers one rendered `[LEVEL target] …\n` record; `false` leaves it queued.
+        ///
+        /// Must not block. Records it logs itself skip the bridge.
+        fn try_forward(&self, level: Level, record: &[u8]) -> bool;
+    }
+
+    #[derive(Clone, Copy)]
+    struct Line {
+        level: Level,
+        topic: Topic,
+        len: usize,
+        bytes: [u8; LOGD_BRIDGE_LINE_LEN],
+    }
+
+    impl Line {
+        const EMPTY: Self = Self {
+            level: Level::Info,
+            topic: Topic::empty(),
+            len: 0,
+            bytes: [0; LOGD_BRIDGE_LINE_LEN],
+        };
+    }
+
+    /// Ring of records waiting for the endpoint, oldest first.
+    struct Backlog {
+        lines: [Line; LOGD_BRIDGE_BACKLOG],
+        head: usize,
+        len: usize,
+    }
+
+    impl Backlog {
+        const fn new() -> Self {
+            Self { lines: [Line::EMPTY; LOGD_BRIDGE_BACKLOG], head: 0, len: 0 }
+        }
+
+        /// Queues `record` unless its topic is throttled; returns 1 when `record` was
+        /// throttled or the oldest line was evicted to make room.
+        fn push(&mut self, level: Level, topic: Topic, record: &[u8]) -> u32 {
+            if !throttle::admit(topic, self.len, LOGD_BRIDGE_BACKLOG) {
+                throttle::note_dropped(topic, 1);
+                return 1;
+            }
+            let evicted = self.len == LOGD_BRIDGE_BACKLOG;
+            if evicted {
+                throttle::note_dropped(self.lines[self.head].topic, 1);
+                self.pop_front();
+            }
+            let line = &mut self.lines[(self.head + self.len) % LOGD_BRIDGE_BACKLOG];
+            line.level = level;
+            line.topic = topic;
+            line.len = record.len().min(LOGD_BRIDGE_LINE_LEN);
+            line.bytes[..line.len].copy_from_slice(&record[..line.len]);
+            self.len += 1;
+            u32::from(evicted)
+        }
+
+        fn pop_front(&mut self) {
+            self.head = (self.head + 1) % LOGD_BRIDGE_BACKLOG;
+            self.len -= 1;
+        }
+
+        /// Replays the backlog into `endpoint`, then offers `record`; whatever is declined
+        /// (or everything, without an endpoint) stays queued. Returns the lines dropped.
+        fn submit(
+            &mut self,
+            endpoint: Option<&dyn LogdEndpoint>,
+            level: Level,
+            topic: Topic,
+            record: &[u8],
+        ) -> u32 {
+            if let Some(endpoint) = endpoint {
+                while self.len > 0 {
+                    let line = &self.lines[self.head];
+                    if !endpoint.try_forward(line.level, &line.bytes[..line.len]) {
+                        break;
+                    }
+                    self.pop_front();
+                }
+                if self.len == 0 && endpoint.try_forward(level, record) {
+                    return 0;
+                }
+            }
+            self.push(level, topic, record)
+        }
+    }
+
+    struct State {
+        endpoint: Option<&'static dyn LogdEndpoint>,
+        backlog: Backlog,
+    }
+
+    struct Slot(UnsafeCell<State>);
+
+    // SAFETY: the cell is only read or written while `LOCK` is held.
+    unsafe impl Sync for Slot {}
+
+    static LOCK: AtomicBool = AtomicBool::new(false);
+    static SLOT: Slot = Slot(UnsafeCell::new(State { endpoint: None, backlog: Backlog::new() }));
+    static DROPPED: AtomicU32 = AtomicU32::new(0);
+
+    /// Runs `f` on the bridge state, or returns `None` at once if it is busy.
+    fn try_with_state<R>(f: impl FnOnce(&mut State) -> R) -> Option<R> {
+        if LOCK.compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed).is_err() {
+            return None;
+        }
+        // SAFETY: `LOCK` is held, so this is the only live reference to the state.
+        let out = f(unsafe { &mut *SLOT.0.get() });
+        LOCK.store(false, Ordering::Release);
+        Some(out)
+    }
+
+    fn with_state<R>(mut f: impl FnMut(&mut State) -> R) -> R {
+        loop {
+            if let Some(out) = try_with_state(&mut f) {
+                return out;
+            }
+            core::hint::spin_loop();
+        }
+    }
+
+    /// Installs `endpoint`; the next console record replays the backlog into it.
+    pub fn set_logd_bridge(endpoint: &'static dyn LogdEndpoint) {
+        with_state(|state| state.endpoint = Some(endpoint));
+    }
+
+    /// Removes the endpoint; records are held in the backlog again.
+    pub fn clear_logd_bridge() {
+        with_state(|state| state.endpoint = None);
+    }
+
+    /// Records that never reached logd (throttled, evicted from the backlog or bridge busy).
+    pub fn logd_bridge_dropped() -> u32 {
+        DROPPED.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn tee<R>(
+        meta: &LineMeta<'_>,
+        console: bool,
+        sink: &mut dyn LineSink,
+        f: impl FnOnce(&mut dyn LineSink) -> R,
+    ) -> R {
+        if !console {
+            return f(sink);
+        }
+        let mut capture = Capture { inner: sink, bytes: [0; LOGD_BRIDGE_LINE_LEN], len: 0 };
+        let out = f(&mut capture);
+        let record = &capture.bytes[..capture.len];
+        let (level, topic) = (meta.level, meta.topic);
+        let submitted =
+            try_with_state(|state| state.backlog.submit(state.endpoint, level, topic, record));
+        let dropped = submitted.unwrap_or_else(|| {
+            throttle::note_dropped(topic, 1);
+            1
+        });
+        if dropped > 0 {
+            DROPPED.fetch_add(dropped, Ordering::Relaxed);
+        }
+        out
+    }
+
+    struct Capture<'s> {
+        inner: &'s mut dyn LineSink,
+        bytes: [u8; LOGD_BRIDGE_LINE_LEN],
+        len: usize,
+    }
+
+    impl LineSink for Capture<'_> {
+        fn write_byte(&mut self, byte: u8) {
+            self.inner.write_byte(byte);
+            if let Some(slot) = self.bytes.get_mut(self.len) {
+                *slot = byte;
+                self.len += 1;
+            }
+        }
+
+        fn write_bytes(&mut self, bytes: &[u8]) {
+            self.inner.write_bytes(bytes);
+            let take = bytes.len().min(LOGD_BRIDGE_LINE_LEN - self.len);
+            self.bytes[self.len..self.len + take].copy_from_slice(&bytes[..take]);
+            self.len += take;
+        }
+
+        fn set_error_code(&mut self, code: u32) {
+            self.inner.set_error_code(code);
+        }
+    }
+
+    impl fmt::Write for Capture<'_> {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            LineSink::write_bytes(self, s.as_bytes());
+            Ok(())
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        extern crate std;
+
+        use std::sync::Mutex;
+        use std::vec::Vec;
+
+        use super::*;
+        use crate::bridge::throttle::tests::{NOISY, SCHED, SECURITY};
+        use crate::bridge::throttle::{
+            clear_topic_priorities, set_topic_priority, topic_dropped, LOGD_PRESSURE_HEADROOM,
+        };
+        use crate::{error, info, TOPIC_GENERAL};
+
+        /// Mocked logd endpoint that declines everything until it comes online.
+        struct MockLogd {
+            online: AtomicBool,
+            got: Mutex<Vec<Vec<u8>>>,
+        }
+
+        impl MockLogd {
+            const fn new() -> Self {
+                Self { online: AtomicBool::new(false), got: Mutex::new(Vec::new()) }
+            }
+
+            fn take(&self) -> Vec<Vec<u8>> {
+                core::mem::take(&mut *self.got.lock().unwrap_or_else(|e| e.into_inner()))
+            }
+        }
+
+        impl LogdEndpoint for MockLogd {
+            fn try_forward(&self, _level: Level, record: &[u8]) -> bool {
+                if !self.online.load(Ordering::Relaxed) {
+                    return false;
+                }
+                self.got.lock().unwrap_or_else(|e| e.into_inner()).push(record.to_vec());
+                true
+            }
+        }
+
+        #[test]
+        fn buffered_lines_replay_when_the_endpoint_comes_online() {
+            let logd = MockLogd::new();
+            let mut backlog = Backlog::new();
+            assert_eq!(backlog.submit(None, Level::Info, TOPIC_GENERAL, b"a"), 0);
+            assert_eq!(backlog.submit(None, Level::Warn, TOPIC_GENERAL, b"b"), 0);
+            // Installed but not serving yet: nothing is lost, order is kept.
+            assert_eq!(backlog.submit(Some(&logd), Level::Info, TOPIC_GENERAL, b"c"), 0);
+            assert_eq!(backlog.len, 3);
+
+            logd.online.store(true, Ordering::Relaxed);
+            assert_eq!(backlog.submit(Some(&logd), Level::Info, TOPIC_GENERAL, b"d"), 0);
+            assert_eq!(logd.take(), [b"a", b"b", b"c", b"d"]);
+            assert_eq!(backlog.len, 0);
+        }
+
+        #[test]
+        fn test_reject_overflow_evicts_the_oldest_lines() {
+            let logd = MockLogd::new();
+            let mut backlog = Backlog::new();
+            let mut evicted = 0;
+            for i in 0..LOGD_BRIDGE_BACKLOG as u8 + 3 {
+                evicted += backlog.submit(None, Level::Info, TOPIC_GENERAL, &[i]);
+            }
+            assert_eq!(evicted, 3);
+            let long = [b'x'; LOGD_BRIDGE_LINE_LEN + 40];
+            assert_eq!(backlog.submit(None, Level::Info, TOPIC_GENERAL, &long), 1);
+
+            logd.online.store(true, Ordering::Relaxed);
+            assert_eq!(backlog.submit(Some(&logd), Level::Info, TOPIC_GENERAL, b"new"), 0);
+            let got = logd.take();
+            assert_eq!(got.len(), LOGD_BRIDGE_BACKLOG + 1);
+            assert_eq!(got[0], [4]);
+            assert_eq!(got[LOGD_BRIDGE_BACKLOG - 1], long[..LOGD_BRIDGE_LINE_LEN]);
+            assert_eq!(got[LOGD_BRIDGE_BACKLOG], b"new");
+        }
+
+        #[test]
+        fn low_priority_topics_drop_before_high_ones_near_full() {
+            let _serial = crate::dedup::tests::SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+            clear_topic_priorities();
+            assert!(set_topic_priority(SCHED, 2));
+            assert!(set_topic_priority(SECURITY, 4));
+            // logd is installed but backpressuring: every record lands in the backlog.
+            let logd = MockLogd::new();
+            let mut backlog = Backlog::new();
+            let near_full = LOGD_BRIDGE_BACKLOG - LOGD_PRESSURE_HEADROOM;
+            for i in 0..near_full as u8 {
+                assert_eq!(backlog.submit(Some(&logd), Level::Info, NOISY, &[i]), 0);
+            }
+            assert_eq!(backlog.submit(Some(&logd), Level::Info, NOISY, b"noisy"), 1);
+            assert_eq!(backlog.submit(Some(&logd), Level::Info, SCHED, b"sched1"), 0);
+            assert_eq!(backlog.submit(Some(&logd), Level::Info, SCHED, b"sched2"), 0);
+            assert_eq!(backlog.submit(Some(&logd), Level::Info, SCHED, b"sched3"), 1);
+            assert_eq!(backlog.submit(Some(&logd), Level::Warn, SECURITY, b"sec1"), 0);
+            assert_eq!(backlog.submit(Some(&logd), Level::Warn, SECURITY, b"sec2"), 0);
+            // Full: the top priority still gets in, at the oldest line's expense.
+            assert_eq!(backlog.submit(Some(&logd), Level::Warn, SECURITY, b"sec3"), 1);
+
+            assert_eq!(topic_dropped(NOISY), 2);
+            assert_eq!(topic_dropped(SCHED), 1);
+            assert_eq!(topic_dropped(SECURITY), 0);
+
+            logd.online.store(true, Ordering::Relaxed);
+            assert_eq!(backlog.submit(Some(&logd), Level::Info, TOPIC_GENERAL, b"ok"), 0);
+            let got = logd.take();
+            assert_eq!(got.len(), LOGD_BRIDGE_BACKLOG + 1);
+            assert_eq!(got[0], [1]);
+            assert_eq!(
+                &got[near_full - 1..],
+                [&b"sched1"[..], b"sched2", b"sec1", b"sec2", b"sec3", b"ok"]
+            );
+            clear_topic_priorities();
+        }
+
+        static LOGD: MockLogd = MockLogd::new();
+
+        #[test]
+        fn console_records_reach_an_endpoint_installed_mid_stream() {
+            let _serial = crate::dedup::tests::SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+            info("bridge", |line| line.text("early"));
+            LOGD.online.store(true, Ordering::Relaxed);
+            set_logd_bridge(&LOGD);
+            error("bridge", |line| line.text("online"));
+            clear_logd_bridge();
+            info("bridge", |line| line.text("after clear"));
+
+            let ours: Vec<_> = LOGD
+                .take()
+                .into_iter()
+                .filter(|r| r.windows(8).any(|w| w == b" bridge]"))
+                .collect();
+            assert_eq!(ours, [&b"[INFO bridge] early\n"[..], b"[ERROR bridge] online\n"]);
+        }
+    }
+}
@@ -0,0 +1,167 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: Topic priorities for the logd bridge under backpressure (`set_topic_priority`)
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: 3 unit tests (`--features kernel-logd-bridge`: reserve per priority, per-topic
+//! drop counters, low topics dropped before high ones in the bridge backlog)
+//!
+//! While logd declines records the bridge backlog fills, and without priorities every topic
+//! competes for it alike. [`set_topic_priority`] marks topics worth keeping (scheduler,
+//! security, ...): once the table has an entry, the last [`LOGD_PRESSURE_HEADROOM`] backlog
+//! slots are a reserve, and a record of priority `p` may only take a slot while more than
+//! `LOGD_PRESSURE_HEADROOM - p` are free. Unlisted topics have priority 0 and are dropped first;
+//! priority `LOGD_PRESSURE_HEADROOM` and above is never throttled and, like every record with an
+//! empty table, evicts the oldest line when the backlog is full.
+//!
+//! Every line the bridge loses is counted against its topic ([`topic_dropped`]), whether it
+//! was throttled, evicted or found the bridge busy; topics without a table entry share one
+//! counter. The table holds [`MAX_TOPIC_PRIORITIES`] topics, matched by exact bits.
+//!
+//! INVARIANTS:
+//! - Bounded and lock-free: fixed arrays of atomics, no allocation, no waiting
+//! - The per-topic counters add up to `logd_bridge_dropped`
+
+use core::sync::atomic::{AtomicU32, AtomicU8, Ordering};
+
+use crate::Topic;
+
+/// Topics the priority table can hold.
+pub const MAX_TOPIC_PRIORITIES: usize = 8;
+/// Backlog slots reserved for prioritized topics once any priority is set.
+pub const LOGD_PRESSURE_HEADROOM: usize = 4;
+
+struct Entry {
+    /// Topic bits; 0 while the slot is free.
+    topic: AtomicU32,
+    priority: AtomicU8,
+    dropped: AtomicU32,
+}
+
+impl Entry {
+    const fn new() -> Self {
+        Self { topic: AtomicU32::new(0), priority: AtomicU8::new(0), dropped: AtomicU32::new(0) }
+    }
+}
+
+static ENTRIES: [Entry; MAX_TOPIC_PRIORITIES] = [const { Entry::new() }; MAX_TOPIC_PRIORITIES];
+/// Drops of topics without a table entry.
+static OTHER_DROPPED: AtomicU32 = AtomicU32::new(0);
+
+fn entry(topic: Topic) -> Option<&'static Entry> {
+    let bits = topic.bits();
+    ENTRIES.iter().find(|entry| bits != 0 && entry.topic.load(Ordering::Acquire) == bits)
+}
+
+/// Sets the bridge priority of `topic` (0 = unlisted; higher is kept longer).
+///
+/// Returns `false` for an empty topic or when the table is full of other topics.
+pub fn set_topic_priority(topic: Topic, priority: u8) -> bool {
+    if topic.is_empty() {
+        return false;
+    }
+    if let Some(entry) = entry(topic) {
+        entry.priority.store(priority, Ordering::Relaxed);
+        return true;
+    }
+    for entry in &ENTRIES {
+        let claimed = entry
+            .topic
+            .compare_exchange(0, topic.bits(), Ordering::AcqRel, Ordering::Acquire)
+            .map_or_else(|current| current == topic.bits(), |_| true);
+        if claimed {
+            entry.priority.store(priority, Ordering::Relaxed);
+            return true;
+        }
+    }
+    false
+}
+
+/// Empties the priority table and zeroes every drop counter.
+pub fn clear_topic_priorities() {
+    for entry in &ENTRIES {
+        entry.topic.store(0, Ordering::Release);
+        entry.priority.store(0, Ordering::Relaxed);
+        entry.dropped.store(0, Ordering::Relaxed);
+    }
+    OTHER_DROPPED.store(0, Ordering::Relaxed);
+}
+
+/// Lines of `topic` the bridge dropped (shared by all topics without a table entry).
+pub fn topic_dropped(topic: Topic) -> u32 {
+    entry(topic).map_or(&OTHER_DROPPED, |entry| &entry.dropped).load(Ordering::Relaxed)
+}
+
+/// Whether a `topic` record may join a backlog holding `len` of `capacity` lines.
+pub(crate) fn admit(topic: Topic, len: usize, capacity: usize) -> bool {
+    let active = ENTRIES.iter().any(|entry| entry.topic.load(Ordering::Relaxed) != 0);
+    if !active {
+        return true;
+    }
+    let priority = entry(topic).map_or(0, |entry| entry.priority.load(Ordering::Relaxed));
+    let reserve = LOGD_PRESSURE_HEADROOM.saturating_sub(usize::from(priority));
+    reserve == 0 || capacity.saturating_sub(len) > reserve
+}
+
+/// Counts `count` lost lines of `topic`.
+pub(crate) fn note_dropped(topic: Topic, count: u32) {
+    if count > 0 {
+        let counter = entry(topic).map_or(&OTHER_DROPPED, |entry| &entry.dropped);
+        counter.fetch_add(count, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub(crate) const SCHED: Topic = Topic::bit(5);
+    pub(crate) const SECURITY: Topic = Topic::bit(6);
+    pub(crate) const NOISY: Topic = Topic::bit(7);
+
+    #[test]
+    fn each_priority_keeps_its_share_of_the_reserve() {
+        let _serial = crate::dedup::tests::SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+        clear_topic_priorities();
+        // No priorities: nothing is throttled.
+        assert!(admit(NOISY, 16, 16));
+
+        assert!(set_topic_priority(SCHED, 2));
+        assert!(set_topic_priority(SECURITY, u8::MAX));
+        // 12 of 16 lines used: the 4 free slots are the reserve.
+        assert!(admit(NOISY, 11, 16) && !admit(NOISY, 12, 16));
+        assert!(admit(SCHED, 13, 16) && !admit(SCHED, 14, 16));
+        assert!(admit(SECURITY, 15, 16) && admit(SECURITY, 16, 16));
+
+        // Updating a listed topic does not take another slot.
+        assert!(set_topic_priority(SCHED, 4));
+        assert!(admit(SCHED, 16, 16));
+        clear_topic_priorities();
+    }
+
+    #[test]
+    fn test_reject_priority_table_overflow_and_count_drops_per_topic() {
+        let _serial = crate::dedup::tests::SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+        clear_topic_priorities();
+        assert!(!set_topic_priority(Topic::empty(), 1));
+        for bit in 0..MAX_TOPIC_PRIORITIES as u8 {
+            assert!(set_topic_priority(Topic::bit(bit), 1));
+        }
+        assert!(!set_topic_priority(Topic::bit(20), 1));
+
+        note_dropped(Topic::bit(1), 2);
+        note_dropped(Topic::bit(1), 1);
+        note_dropped(Topic::bit(3), 1);
+        note_dropped(Topic::bit(20), 4);
+        note_dropped(Topic::bit(21), 0);
+        assert_eq!(topic_dropped(Topic::bit(1)), 3);
+        assert_eq!(topic_dropped(Topic::bit(3)), 1);
+        assert_eq!(topic_dropped(Topic::bit(2)), 0);
+        // Unlisted topics share the "other" counter.
+        assert_eq!(topic_dropped(Topic::bit(21)), 4);
+        clear_topic_priorities();
+        assert_eq!(topic_dropped(Topic::bit(1)), 0);
+    }
+}
@@ -0,0 +1,123 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: Stable numeric error codes on a record (`code=0x...`) for machine triage
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: 2 unit tests (human line + recorded field, dedup path keeps the code)
+//!
+//! [`LineBuilder::error_code`] writes `code=0x` plus eight hex digits into the human line
+//! and hands the code to the sink, which ships it to logd as the record's `fields` blob
+//! ([`encode_field`], the RFC-0011 `key=value` convention). Collectors can then bucket
+//! records by code however the message is worded. A record carries at most one code; a
+//! second call replaces it in the field (both stay in the human text).
+
+use crate::{emit_hex, error, LineBuilder};
+
+/// Key of the structured error-code field.
+pub const CODE_FIELD_KEY: &str = "code";
+/// Encoded length of the error-code field (`code=0x` plus eight hex digits).
+pub const CODE_FIELD_LEN: usize = CODE_FIELD_KEY.len() + 3 + 8;
+
+/// Logs an `Error` record that starts with `code=0x...`, followed by whatever `f` writes.
+pub fn error_coded(target: &str, code: u32, f: impl FnOnce(&mut LineBuilder)) {
+    error(target, |line| {
+        line.error_code(code);
+        line.text(" ");
+        f(line);
+    });
+}
+
+impl LineBuilder<'_, '_> {
+    /// Records `code` as the record's structured error code and writes `code=0x...`.
+    pub fn error_code(&mut self, code: u32) {
+        let mut field = [0u8; CODE_FIELD_LEN];
+        self.sink.write_bytes(encode_field(code, &mut field));
+        self.sink.set_error_code(code);
+    }
+}
+
+/// Encodes the logd field for `code` (`code=0x0000002a`) into `out`.
+pub(crate) fn encode_field(code: u32, out: &mut [u8; CODE_FIELD_LEN]) -> &[u8] {
+    let key_len = CODE_FIELD_KEY.len();
+    out[..key_len].copy_from_slice(CODE_FIELD_KEY.as_bytes());
+    out[key_len..key_len + 3].copy_from_slice(b"=0x");
+    // `emit_hex` renders all 16 nibbles of a u64; a u32 is the last eight.
+    let mut nibble = 0;
+    emit_hex(u64::from(code), |ch| {
+        if nibble >= 8 {
+            out[key_len + 3 + nibble - 8] = ch;
+        }
+        nibble += 1;
+    });
+    &out[..]
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use core::fmt;
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::dedup::LineBuf;
+    use crate::{write_record, Level, LineMeta, LineSink, TOPIC_GENERAL};
+
+    /// Stand-in sink: human bytes plus the structured code it was handed.
+    #[derive(Default)]
+    struct Capture {
+        bytes: Vec<u8>,
+        code: Option<u32>,
+    }
+
+    impl LineSink for Capture {
+        fn write_byte(&mut self, byte: u8) {
+            self.bytes.push(byte);
+        }
+
+        fn write_bytes(&mut self, bytes: &[u8]) {
+            self.bytes.extend_from_slice(bytes);
+        }
+
+        fn set_error_code(&mut self, code: u32) {
+            self.code = Some(code);
+        }
+    }
+
+    impl fmt::Write for Capture {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            self.write_bytes(s.as_bytes());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn code_appears_in_human_line_and_structured_field() {
+        let meta = LineMeta { level: Level::Error, target: "blk", topic: TOPIC_GENERAL };
+        let mut capture = Capture::default();
+        write_record(&mut capture, &meta, |line| {
+            line.error_code(0xdead_002a);
+            line.text(" write failed");
+        });
+        assert_eq!(capture.bytes, b"[ERROR blk] code=0xdead002a write failed\n");
+        assert_eq!(capture.code, Some(0xdead_002a));
+
+        let mut field = [0u8; CODE_FIELD_LEN];
+        assert_eq!(encode_field(0x2a, &mut field), b"code=0x0000002a");
+    }
+
+    #[test]
+    fn dedup_buffer_keeps_the_code_for_logd() {
+        let meta = LineMeta { level: Level::Warn, target: "net", topic: TOPIC_GENERAL };
+        let mut line = LineBuf::new();
+        write_record(&mut line, &meta, |l| l.error_code(7));
+        assert_eq!(line.error_code(), Some(7));
+        assert_eq!(line.finish(), b"[WARN net] code=0x00000007\n");
+
+        let mut plain = LineBuf::new();
+        write_record(&mut plain, &meta, |l| l.text("code=0x00000007"));
+        assert_eq!(plain.error_code(), None);
+    }
+}
@@ -0,0 +1,314 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: Runtime log filtering — console/logd floors, topic mask, per-target
+//! overrides, and the `load_config` text format (RFC-0003 runtime config)
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: 4 unit tests
+//!
+//! Config text is fed from the `/state/log/config` statefs value at service start:
+//!
+//! ```text
+//! # comment
+//! max_level=INFO
+//! topic_mask=0x0000000f
+//! target.netstackd=DEBUG
+//! ```
+//!
+//! Parsing is allocation-free and all-or-nothing: the whole input is validated
+//! before anything is applied, so a rejected config leaves the current filters
+//! untouched.
+
+use core::sync::atomic::{AtomicU32, AtomicU8, Ordering};
+
+use crate::{Level, Topic};
+
+/// Upper bound on config input size in bytes.
+pub const MAX_CONFIG_LEN: usize = 1024;
+/// Upper bound on per-target level overrides.
+pub const MAX_TARGET_OVERRIDES: usize = 8;
+/// Upper bound on an override's target name length in bytes.
+pub const MAX_TARGET_LEN: usize = 32;
+
+// Console (UART) verbosity floor. Quiet by default: Error/Warn/Info reach the UART, Debug/Trace
+// do not. Decoupled from the logd journal floor below so the console can stay small/curated
+// while the full structured stream still lands in logd.
+static MAX_LEVEL: AtomicU8 = AtomicU8::new(Level::Info as u8);
+// logd journal floor — default Trace: the structured journal keeps EVERY record (the full
+// stream), so a curated console never means lost detail. The detail is recalled at runtime via
+// logd `OP_QUERY` (filter by level/service/time), not by a rebuild.
+static LOGD_LEVEL: AtomicU8 = AtomicU8::new(Level::Trace as u8);
+static TOPIC_MASK: AtomicU32 = AtomicU32::new(u32::MAX);
+
+pub fn set_max_level(level: Level) {
+    MAX_LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+/// Set the logd journal floor (how much of the stream logd retains, independent of the console).
+#[allow(dead_code)]
+pub fn set_logd_level(level: Level) {
+    LOGD_LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+pub fn set_topic_mask(mask: Topic) {
+    TOPIC_MASK.store(mask.bits(), Ordering::Relaxed);
+}
+
+/// Whether `level` reaches the console (UART) for `target`.
+///
+/// A per-target override (see [`set_target_level`]) replaces the global floor.
+pub(crate) fn level_enabled(level: Level, target: &str) -> bool {
+    let floor = target_level(target).map_or(MAX_LEVEL.load(Ordering::Relaxed), |l| l as u8);
+    level as u8 <= floor
+}
+
+/// Whether `level` is retained by the logd journal (independent of the console floor).
+pub(crate) fn logd_enabled(level: Level) -> bool {
+    level as u8 <= LOGD_LEVEL.load(Ordering::Relaxed)
+}
+
+pub(crate) fn topic_enabled(topic: Topic) -> bool {
+    let mask = TOPIC_MASK.load(Ordering::Relaxed);
+    let bits = topic.bits();
+    if bits == 0 {
+        return true;
+    }
+    (mask & bits) == bits
+}
+
+// Per-target console floors, keyed by FNV-1a hash of the target name. Entries are
+// published by storing `TARGET_COUNT` last, so readers never see a half-written slot.
+static TARGET_HASHES: [AtomicU32; MAX_TARGET_OVERRIDES] =
+    [const { AtomicU32::new(0) }; MAX_TARGET_OVERRIDES];
+static TARGET_LEVELS: [AtomicU8; MAX_TARGET_OVERRIDES] =
+    [const { AtomicU8::new(0) }; MAX_TARGET_OVERRIDES];
+static TARGET_COUNT: AtomicU8 = AtomicU8::new(0);
+
+fn target_hash(target: &[u8]) -> u32 {
+    let mut hash: u32 = 0x811c_9dc5;
+    for &byte in target {
+        hash ^= u32::from(byte);
+        hash = hash.wrapping_mul(0x0100_0193);
+    }
+    hash
+}
+
+/// Console floor override for `target`, if one is installed.
+fn target_level(target: &str) -> Option<Level> {
+    let count = usize::from(TARGET_COUNT.load(Ordering::Acquire));
+    if count == 0 {
+        return None;
+    }
+    let hash = target_hash(target.as_bytes());
+    (0..count.min(MAX_TARGET_OVERRIDES))
+        .find(|&i| TARGET_HASHES[i].load(Ordering::Relaxed) == hash)
+        .and_then(|i| level_from_u8(TARGET_LEVELS[i].load(Ordering::Relaxed)))
+}
+
+/// Install per-target console floors, replacing any previous overrides.
+///
+/// Returns `false` (and changes nothing) if `overrides` exceeds
+/// [`MAX_TARGET_OVERRIDES`].
+pub fn set_target_levels(overrides: &[(&str, Level)]) -> bool {
+    if overrides.len() > MAX_TARGET_OVERRIDES {
+        return false;
+    }
+    TARGET_COUNT.store(0, Ordering::Release);
+    for (i, (target, level)) in overrides.iter().enumerate() {
+        TARGET_HASHES[i].store(target_hash(target.as_bytes()), Ordering::Relaxed);
+        TARGET_LEVELS[i].store(*level as u8, Ordering::Relaxed);
+    }
+    TARGET_COUNT.store(overrides.len() as u8, Ordering::Release);
+    true
+}
+
+/// Reason a config was rejected; `line` is 1-based.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConfigError {
+    /// Input exceeds [`MAX_CONFIG_LEN`] or is not UTF-8.
+    Malformed,
+    /// A line is not `key=value`.
+    MissingValue { line: usize },
+    /// The key is not one of `max_level`, `topic_mask`, `target.<name>`.
+    UnknownKey { line: usize },
+    /// The level is not one of `ERROR|WARN|INFO|DEBUG|TRACE`.
+    BadLevel { line: usize },
+    /// `topic_mask` is not `0x` followed by 1..=8 hex digits.
+    BadMask { line: usize },
+    /// Target name empty or longer than [`MAX_TARGET_LEN`].
+    BadTarget { line: usize },
+    /// More than [`MAX_TARGET_OVERRIDES`] distinct targets.
+    TooManyTargets { line: usize },
+}
+
+struct Parsed<'a> {
+    max_level: Option<Level>,
+    topic_mask: Option<u32>,
+    targets: [(&'a str, Level); MAX_TARGET_OVERRIDES],
+    target_count: usize,
+}
+
+/// Parse `bytes` and apply it through the existing setters.
+///
+/// Keys absent from the input keep their current value, except per-target
+/// overrides, which are replaced as a set.
+pub fn load_config(bytes: &[u8]) -> Result<(), ConfigError> {
+    let parsed = parse_config(bytes)?;
+    if let Some(level) = parsed.max_level {
+        set_max_level(level);
+    }
+    if let Some(mask) = parsed.topic_mask {
+        set_topic_mask(Topic::from_bits(mask));
+    }
+    set_target_levels(&parsed.targets[..parsed.target_count]);
+    Ok(())
+}
+
+fn parse_config(bytes: &[u8]) -> Result<Parsed<'_>, ConfigError> {
+    if bytes.len() > MAX_CONFIG_LEN {
+        return Err(ConfigError::Malformed);
+    }
+    let text = core::str::from_utf8(bytes).map_err(|_| ConfigError::Malformed)?;
+    let mut parsed = Parsed {
+        max_level: None,
+        topic_mask: None,
+        targets: [("", Level::Error); MAX_TARGET_OVERRIDES],
+        target_count: 0,
+    };
+    for (idx, raw) in text.lines().enumerate() {
+        let line = idx + 1;
+        let entry = raw.trim();
+        if entry.is_empty() || entry.starts_with('#') {
+            continue;
+        }
+        let (key, value) = entry.split_once('=').ok_or(ConfigError::MissingValue { line })?;
+        let (key, value) = (key.trim(), value.trim());
+        if key == "max_level" {
+            parsed.max_level = Some(parse_level(value).ok_or(ConfigError::BadLevel { line })?);
+        } else if key == "topic_mask" {
+            parsed.topic_mask = Some(parse_mask(value).ok_or(ConfigError::BadMask { line })?);
+        } else if let Some(target) = key.strip_prefix("target.") {
+            if target.is_empty() || target.len() > MAX_TARGET_LEN {
+                return Err(ConfigError::BadTarget { line });
+            }
+            let level = parse_level(value).ok_or(ConfigError::BadLevel { line })?;
+            let used = &mut parsed.targets[..parsed.target_count];
+            if let Some(slot) = used.iter_mut().find(|(name, _)| *name == target) {
+                slot.1 = level;
+            } else if parsed.target_count == MAX_TARGET_OVERRIDES {
+                return Err(ConfigError::TooManyTargets { line });
+            } else {
+                parsed.targets[parsed.target_count] = (target, level);
+                parsed.target_count += 1;
+            }
+        } else {
+            return Err(ConfigError::UnknownKey { line });
+        }
+    }
+    Ok(parsed)
+}
+
+const LEVELS: [Level; 5] = [Level::Error, Level::Warn, Level::Info, Level::Debug, Level::Trace];
+
+pub(crate) fn level_from_u8(raw: u8) -> Option<Level> {
+    LEVELS.into_iter().find(|level| *level as u8 == raw)
+}
+
+fn parse_level(value: &str) -> Option<Level> {
+    LEVELS.into_iter().find(|level| level.label() == value)
+}
+
+fn parse_mask(value: &str) -> Option<u32> {
+    let digits = value.strip_prefix("0x")?;
+    if digits.is_empty() || digits.len() > 8 {
+        return None;
+    }
+    u32::from_str_radix(digits, 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_valid_config() {
+        let cfg =
+            b"# boot defaults\n max_level = WARN\ntopic_mask=0x0000000F\n\ntarget.vfsd=TRACE\n";
+        let parsed = parse_config(cfg).expect("valid config");
+        assert_eq!(parsed.max_level, Some(Level::Warn));
+        assert_eq!(parsed.topic_mask, Some(0xF));
+        assert_eq!(&parsed.targets[..parsed.target_count], &[("vfsd", Level::Trace)]);
+    }
+
+    #[test]
+    fn test_reject_unknown_key_and_bad_values() {
+        assert_eq!(
+            parse_config(b"max_level=INFO\ncolor=on").err(),
+            Some(ConfigError::UnknownKey { line: 2 })
+        );
+        assert_eq!(parse_config(b"max_level=info").err(), Some(ConfigError::BadLevel { line: 1 }));
+        assert_eq!(parse_config(b"target.x=LOUD").err(), Some(ConfigError::BadLevel { line: 1 }));
+        assert_eq!(parse_config(b"topic_mask=15").err(), Some(ConfigError::BadMask { line: 1 }));
+        assert_eq!(
+            parse_config(b"topic_mask=0x123456789").err(),
+            Some(ConfigError::BadMask { line: 1 })
+        );
+        assert_eq!(parse_config(b"target.=INFO").err(), Some(ConfigError::BadTarget { line: 1 }));
+        assert_eq!(parse_config(b"max_level").err(), Some(ConfigError::MissingValue { line: 1 }));
+        assert_eq!(parse_config(&[b'#'; MAX_CONFIG_LEN + 1]).err(), Some(ConfigError::Malformed));
+    }
+
+    #[test]
+    fn test_reject_too_many_targets() {
+        let mut cfg = [0u8; 256];
+        let mut len = 0;
+        for i in 0..=MAX_TARGET_OVERRIDES {
+            let line = [
+                b't',
+                b'a',
+                b'r',
+                b'g',
+                b'e',
+                b't',
+                b'.',
+                b'a' + i as u8,
+                b'=',
+                b'I',
+                b'N',
+                b'F',
+                b'O',
+                b'\n',
+            ];
+            cfg[len..len + line.len()].copy_from_slice(&line);
+            len += line.len();
+        }
+        assert_eq!(
+            parse_config(&cfg[..len]).err(),
+            Some(ConfigError::TooManyTargets { line: MAX_TARGET_OVERRIDES + 1 })
+        );
+        // Repeating a target updates it in place instead of taking a slot.
+        let parsed = parse_config(b"target.a=INFO\ntarget.a=DEBUG").expect("repeat");
+        assert_eq!(&parsed.targets[..parsed.target_count], &[("a", Level::Debug)]);
+    }
+
+    #[test]
+    fn per_target_override_replaces_global_floor() {
+        // Floors are process-global; serialize with the tests that log through them.
+        let _serial = crate::dedup::tests::SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+        load_config(b"max_level=INFO\ntarget.netstackd=DEBUG\ntarget.quiet=ERROR").expect("load");
+        assert!(level_enabled(Level::Debug, "netstackd"));
+        assert!(!level_enabled(Level::Trace, "netstackd"));
+        assert!(!level_enabled(Level::Debug, "other"));
+        assert!(level_enabled(Level::Info, "other"));
+        assert!(!level_enabled(Level::Warn, "quiet"));
+
+        // A rejected config leaves the installed overrides untouched.
+        assert!(load_config(b"target.netstackd=TRACE\nbogus=1").is_err());
+        assert!(!level_enabled(Level::Trace, "netstackd"));
+
+        load_config(b"").expect("empty config clears overrides");
+        assert!(!level_enabled(Level::Debug, "netstackd"));
+    }
+}
@@ -0,0 +1,175 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: Application-supplied log sink (`sink-custom`) teed off the console stream
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: 2 unit tests (`--features sink-custom`)
+//!
+//! With the `sink-custom` feature, [`set_custom_sink`] installs a `'static` [`CustomSink`]
+//! that receives every record the console receives, byte for byte, in addition to the
+//! compiled-in sink. Host tests and tools use it to capture log output for assertions.
+//! Without the feature, [`tee`] is a pass-through and nothing here is public.
+
+use crate::{sink::Sink, LineSink};
+
+/// Runs `f` against `sink`; with a custom sink installed, console bytes are copied to it.
+#[cfg(not(feature = "sink-custom"))]
+pub(crate) fn tee<R>(
+    sink: &mut Sink<'_>,
+    console: bool,
+    f: impl FnOnce(&mut dyn LineSink) -> R,
+) -> R {
+    let _ = console;
+    f(sink)
+}
+
+#[cfg(feature = "sink-custom")]
+pub(crate) use installed::tee;
+#[cfg(feature = "sink-custom")]
+pub use installed::{clear_custom_sink, set_custom_sink, CustomSink};
+
+#[cfg(feature = "sink-custom")]
+mod installed {
+    use core::cell::UnsafeCell;
+    use core::fmt;
+    use core::sync::atomic::{AtomicBool, Ordering};
+
+    use super::{LineSink, Sink};
+
+    /// Destination for a copy of the console stream (see [`set_custom_sink`]).
+    pub trait CustomSink: Sync {
+        /// Receives one byte of a record.
+        fn write_byte(&self, byte: u8);
+
+        /// Receives a run of record bytes.
+        fn write_bytes(&self, bytes: &[u8]) {
+            for &byte in bytes {
+                self.write_byte(byte);
+            }
+        }
+    }
+
+    // Spin-guarded slot: installs are rare and readers only copy the reference out.
+    struct Slot(UnsafeCell<Option<&'static dyn CustomSink>>);
+
+    // SAFETY: the cell is only read or written while `LOCK` is held.
+    unsafe impl Sync for Slot {}
+
+    static LOCK: AtomicBool = AtomicBool::new(false);
+    static SLOT: Slot = Slot(UnsafeCell::new(None));
+
+    fn with_slot<R>(f: impl FnOnce(&mut Option<&'static dyn CustomSink>) -> R) -> R {
+        while LOCK.compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed).is_err()
+        {
+            core::hint::spin_loop();
+        }
+        // SAFETY: `LOCK` is held, so this is the only live reference to the slot.
+        let out = f(unsafe { &mut *SLOT.0.get() });
+        LOCK.store(false, Ordering::Release);
+        out
+    }
+
+    /// Installs `sink` to receive a copy of every console record, replacing any previous one.
+    pub fn set_custom_sink(sink: &'static dyn CustomSink) {
+        with_slot(|slot| *slot = Some(sink));
+    }
+
+    /// Removes the installed custom sink, if any.
+    pub fn clear_custom_sink() {
+        with_slot(|slot| *slot = None);
+    }
+
+    pub(crate) fn tee<R>(
+        sink: &mut Sink<'_>,
+        console: bool,
+        f: impl FnOnce(&mut dyn LineSink) -> R,
+    ) -> R {
+        let custom = if console { with_slot(|slot| *slot) } else { None };
+        match custom {
+            Some(custom) => f(&mut Tee { inner: sink, custom }),
+            None => f(sink),
+        }
+    }
+
+    struct Tee<'s, 'm> {
+        inner: &'s mut Sink<'m>,
+        custom: &'static dyn CustomSink,
+    }
+
+    impl LineSink for Tee<'_, '_> {
+        fn write_byte(&mut self, byte: u8) {
+            self.inner.write_byte(byte);
+            self.custom.write_byte(byte);
+        }
+
+        fn write_bytes(&mut self, bytes: &[u8]) {
+            self.inner.write_bytes(bytes);
+            self.custom.write_bytes(bytes);
+        }
+
+        fn set_error_code(&mut self, code: u32) {
+            self.inner.set_error_code(code);
+        }
+    }
+
+    impl fmt::Write for Tee<'_, '_> {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            LineSink::write_bytes(self, s.as_bytes());
+            Ok(())
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        extern crate std;
+
+        use std::sync::Mutex;
+        use std::vec::Vec;
+
+        use super::*;
+        use crate::{error, trace, Level};
+
+        struct Capture(Mutex<Vec<u8>>);
+
+        impl CustomSink for Capture {
+            fn write_byte(&self, byte: u8) {
+                self.0.lock().unwrap_or_else(|e| e.into_inner()).push(byte);
+            }
+        }
+
+        impl Capture {
+            fn take(&self) -> Vec<u8> {
+                core::mem::take(&mut *self.0.lock().unwrap_or_else(|e| e.into_inner()))
+            }
+        }
+
+        static CAPTURE: Capture = Capture(Mutex::new(Vec::new()));
+
+        #[test]
+        fn captures_exact_record_bytes() {
+            let _serial = crate::dedup::tests::SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+            set_custom_sink(&CAPTURE);
+            error("capture", |line| {
+                line.text("disk ");
+                line.dec(3);
+                line.text(" offline");
+            });
+            clear_custom_sink();
+            error("capture", |line| line.text("after clear"));
+            assert_eq!(CAPTURE.take(), b"[ERROR capture] disk 3 offline\n");
+        }
+
+        #[test]
+        fn test_reject_records_below_console_floor() {
+            let _serial = crate::dedup::tests::SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+            assert!(!crate::config::level_enabled(Level::Trace, "capture"));
+            set_custom_sink(&CAPTURE);
+            trace("capture", |line| line.text("too chatty"));
+            error("capture", |line| line.text("kept"));
+            clear_custom_sink();
+            assert_eq!(CAPTURE.take(), b"[ERROR capture] kept\n");
+        }
+    }
+}
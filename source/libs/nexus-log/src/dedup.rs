@@ -0,0 +1,248 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: Opt-in console dedup — consecutive identical lines collapse into a repeat count
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: 3 unit tests
+//!
+//! With [`set_dedup`] on, a console record is first rendered into a fixed
+//! [`DEDUP_LINE_LEN`]-byte buffer and compared with the previous console line. An
+//! identical line is suppressed and counted; the count is flushed as
+//! `[last message repeated N times]` when a different line arrives, or once it reaches
+//! [`DEDUP_MAX_REPEATS`] so a tight loop still shows signs of life.
+//!
+//! ERROR lines bypass dedup. Only the console copy is suppressed: the logd journal still
+//! receives every record. While dedup is on, records are cut at [`DEDUP_LINE_LEN`] bytes.
+
+use core::fmt;
+use core::sync::atomic::{AtomicBool, AtomicU32, AtomicU8, AtomicUsize, Ordering};
+
+use crate::bridge;
+use crate::custom::tee;
+use crate::sink::Sink;
+use crate::{write_record, Level, LineBuilder, LineMeta, LineSink};
+
+/// Bytes of a rendered record kept for comparison (longer records are cut).
+pub const DEDUP_LINE_LEN: usize = 320;
+/// Suppressed repeats after which the count is flushed even without a new line.
+pub const DEDUP_MAX_REPEATS: u32 = 1000;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+// Bounded-try guard over the previous-line state: a concurrent emitter that cannot take it
+// skips dedup for its line instead of comparing against a half-written buffer.
+static BUSY: AtomicBool = AtomicBool::new(false);
+static PREV: [AtomicU8; DEDUP_LINE_LEN] = [const { AtomicU8::new(0) }; DEDUP_LINE_LEN];
+static PREV_LEN: AtomicUsize = AtomicUsize::new(0);
+static REPEATS: AtomicU32 = AtomicU32::new(0);
+
+/// Enable or disable console dedup. Either way the remembered line is forgotten.
+pub fn set_dedup(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+    PREV_LEN.store(0, Ordering::Relaxed);
+    REPEATS.store(0, Ordering::Relaxed);
+}
+
+/// Whether a console record at `level` goes through dedup.
+pub(crate) fn applies(level: Level) -> bool {
+    level != Level::Error && ENABLED.load(Ordering::Relaxed)
+}
+
+/// Renders the record, then emits it unless it repeats the previous console line.
+///
+/// The returned sink holds the record for the logd journal whether or not it was shown.
+pub(crate) fn log_deduped<'m>(meta: &LineMeta<'m>, f: impl FnOnce(&mut LineBuilder)) -> Sink<'m> {
+    let mut line = LineBuf::new();
+    write_record(&mut line, meta, f);
+    let code = line.error_code();
+    let line = line.finish();
+    let verdict = admit(line);
+
+    #[cfg(all(not(feature = "sink-userspace"), feature = "sink-kernel"))]
+    let _record_guard = crate::sink::record_lock::acquire(true);
+    if let Some(repeats) = verdict.repeats {
+        let mut notice = Sink::new(meta.level, meta.target, meta.topic, true);
+        tee(&mut notice, true, |sink| {
+            bridge::tee(meta, true, sink, |sink| {
+                let mut builder = LineBuilder { sink };
+                builder.text("[last message repeated ");
+                builder.dec(u64::from(repeats));
+                builder.text(" times]\n");
+            })
+        });
+    }
+    let mut sink = Sink::new(meta.level, meta.target, meta.topic, verdict.emit);
+    // Byte-wise: the held copy lives on the stack, outside the image bounds the slice
+    // guard accepts.
+    tee(&mut sink, verdict.emit, |sink| {
+        bridge::tee(meta, verdict.emit, sink, |sink| {
+            line.iter().for_each(|&byte| sink.write_byte(byte));
+            if let Some(code) = code {
+                sink.set_error_code(code);
+            }
+        })
+    });
+    sink
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Verdict {
+    /// Show this line on the console.
+    emit: bool,
+    /// Flush a `[last message repeated N times]` notice first.
+    repeats: Option<u32>,
+}
+
+fn admit(line: &[u8]) -> Verdict {
+    if BUSY.compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed).is_err() {
+        return Verdict { emit: true, repeats: None };
+    }
+    let repeats = REPEATS.load(Ordering::Relaxed);
+    let same = PREV_LEN.load(Ordering::Relaxed) == line.len()
+        && PREV.iter().zip(line).all(|(prev, &byte)| prev.load(Ordering::Relaxed) == byte);
+    let verdict = if !same {
+        for (prev, &byte) in PREV.iter().zip(line) {
+            prev.store(byte, Ordering::Relaxed);
+        }
+        PREV_LEN.store(line.len(), Ordering::Relaxed);
+        REPEATS.store(0, Ordering::Relaxed);
+        Verdict { emit: true, repeats: (repeats != 0).then_some(repeats) }
+    } else if repeats + 1 >= DEDUP_MAX_REPEATS {
+        REPEATS.store(0, Ordering::Relaxed);
+        Verdict { emit: false, repeats: Some(repeats + 1) }
+    } else {
+        REPEATS.store(repeats + 1, Ordering::Relaxed);
+        Verdict { emit: false, repeats: None }
+    };
+    BUSY.store(false, Ordering::Release);
+    verdict
+}
+
+/// Fixed-size record buffer; bytes past [`DEDUP_LINE_LEN`] are dropped.
+pub(crate) struct LineBuf {
+    buf: [u8; DEDUP_LINE_LEN],
+    len: usize,
+    cut: bool,
+    code: Option<u32>,
+}
+
+impl LineBuf {
+    pub(crate) fn new() -> Self {
+        Self { buf: [0u8; DEDUP_LINE_LEN], len: 0, cut: false, code: None }
+    }
+
+    /// Structured error code the record set, handed on to the real sink.
+    pub(crate) fn error_code(&self) -> Option<u32> {
+        self.code
+    }
+
+    /// The rendered record, newline-terminated even when it was cut.
+    pub(crate) fn finish(&mut self) -> &[u8] {
+        if self.cut {
+            self.buf[DEDUP_LINE_LEN - 1] = b'\n';
+        }
+        &self.buf[..self.len]
+    }
+}
+
+impl LineSink for LineBuf {
+    fn write_byte(&mut self, byte: u8) {
+        match self.buf.get_mut(self.len) {
+            Some(slot) => {
+                *slot = byte;
+                self.len += 1;
+            }
+            None => self.cut = true,
+        }
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.write_byte(byte);
+        }
+    }
+
+    fn set_error_code(&mut self, code: u32) {
+        self.code = Some(code);
+    }
+}
+
+impl fmt::Write for LineBuf {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.write_bytes(s.as_bytes());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    extern crate std;
+
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::TOPIC_GENERAL;
+
+    // The dedup state is process-global; serialize the tests that drive it (or log through it).
+    pub(crate) static SERIAL: Mutex<()> = Mutex::new(());
+
+    fn render(level: Level, text: &str) -> LineBuf {
+        let meta = LineMeta { level, target: "dedup", topic: TOPIC_GENERAL };
+        let mut line = LineBuf::new();
+        write_record(&mut line, &meta, |l| l.text(text));
+        line
+    }
+
+    #[test]
+    fn coalesces_identical_lines_until_the_bound() {
+        let _serial = SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+        set_dedup(true);
+        let mut line = render(Level::Info, "poll idle");
+        let line = line.finish();
+        assert_eq!(line, b"[INFO dedup] poll idle\n");
+        assert_eq!(admit(line), Verdict { emit: true, repeats: None });
+        for _ in 1..DEDUP_MAX_REPEATS {
+            assert_eq!(admit(line), Verdict { emit: false, repeats: None });
+        }
+        // The bound flushes the count while the line keeps being suppressed.
+        assert_eq!(admit(line), Verdict { emit: false, repeats: Some(DEDUP_MAX_REPEATS) });
+        assert_eq!(admit(line), Verdict { emit: false, repeats: None });
+
+        // Over-long records are cut but stay newline-terminated.
+        let mut long = render(Level::Info, core::str::from_utf8(&[b'x'; 400]).unwrap());
+        let long = long.finish();
+        assert_eq!(long.len(), DEDUP_LINE_LEN);
+        assert_eq!(long.last(), Some(&b'\n'));
+        set_dedup(false);
+    }
+
+    #[test]
+    fn flushes_repeat_count_when_a_different_line_arrives() {
+        let _serial = SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+        set_dedup(true);
+        let mut same = render(Level::Warn, "retry");
+        let same = same.finish();
+        let mut other = render(Level::Warn, "retry ok");
+        let other = other.finish();
+        assert!(admit(same).emit);
+        assert!(!admit(same).emit);
+        assert!(!admit(same).emit);
+        assert_eq!(admit(other), Verdict { emit: true, repeats: Some(2) });
+        // No pending repeats: the next change flushes nothing.
+        assert_eq!(admit(same), Verdict { emit: true, repeats: None });
+        set_dedup(false);
+    }
+
+    #[test]
+    fn test_reject_dedup_of_error_lines() {
+        let _serial = SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+        assert!(!applies(Level::Warn), "dedup is opt-in");
+        set_dedup(true);
+        assert!(applies(Level::Warn));
+        assert!(applies(Level::Trace));
+        assert!(!applies(Level::Error));
+        set_dedup(false);
+        assert!(!applies(Level::Info));
+    }
+}
@@ -0,0 +1,135 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: Bounded hex dump of binary payloads on `LineBuilder`
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: 3 unit tests (short buffer, empty buffer, truncation)
+//!
+//! Bytes are rendered as space-separated two-digit lowercase hex (`de ad be ef`) and
+//! written in small chunks through the sink's `write_bytes`, so the userspace slice
+//! guards see every write. A dump never emits more than `MAX_SLICE_LEN` bytes: the
+//! input bound is clamped accordingly and anything past it is replaced by ` ...`.
+
+use crate::{emit_hex, LineBuilder, MAX_SLICE_LEN};
+
+/// Input bytes rendered per `write_bytes` call.
+const CHUNK_BYTES: usize = 16;
+/// Rendered width of one byte: two hex digits plus a separator.
+const BYTE_WIDTH: usize = 3;
+/// Appended when the input is longer than the bound.
+const ELLIPSIS: &[u8] = b" ...";
+
+impl LineBuilder<'_, '_> {
+    /// Default input bound of [`LineBuilder::hexdump`].
+    pub const HEXDUMP_MAX_BYTES: usize = 64;
+
+    /// Emits at most [`Self::HEXDUMP_MAX_BYTES`] of `bytes` as space-separated hex.
+    pub fn hexdump(&mut self, bytes: &[u8]) {
+        self.hexdump_max(bytes, Self::HEXDUMP_MAX_BYTES);
+    }
+
+    /// Emits at most `max_bytes` of `bytes` as space-separated hex, followed by ` ...`
+    /// when the input was cut. `max_bytes` is clamped so the dump fits `MAX_SLICE_LEN`.
+    pub fn hexdump_max(&mut self, bytes: &[u8], max_bytes: usize) {
+        let limit = max_bytes.min((MAX_SLICE_LEN - ELLIPSIS.len()) / BYTE_WIDTH);
+        let shown = &bytes[..bytes.len().min(limit)];
+        let mut buf = [0u8; CHUNK_BYTES * BYTE_WIDTH];
+        for (index, chunk) in shown.chunks(CHUNK_BYTES).enumerate() {
+            let mut len = 0;
+            for (offset, &byte) in chunk.iter().enumerate() {
+                if index > 0 || offset > 0 {
+                    buf[len] = b' ';
+                    len += 1;
+                }
+                // `emit_hex` renders all 16 nibbles of a u64; a byte is the last two.
+                let mut nibble = 0;
+                emit_hex(u64::from(byte), |ch| {
+                    if nibble >= 14 {
+                        buf[len] = ch;
+                        len += 1;
+                    }
+                    nibble += 1;
+                });
+            }
+            self.sink.write_bytes(&buf[..len]);
+        }
+        if shown.len() < bytes.len() {
+            self.sink.write_bytes(ELLIPSIS);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use core::fmt;
+    use std::vec::Vec;
+
+    use crate::{LineBuilder, LineSink, MAX_SLICE_LEN};
+
+    #[derive(Default)]
+    struct Capture {
+        bytes: Vec<u8>,
+        largest_write: usize,
+    }
+
+    impl LineSink for Capture {
+        fn write_byte(&mut self, byte: u8) {
+            self.write_bytes(&[byte]);
+        }
+
+        fn write_bytes(&mut self, bytes: &[u8]) {
+            self.largest_write = self.largest_write.max(bytes.len());
+            self.bytes.extend_from_slice(bytes);
+        }
+    }
+
+    impl fmt::Write for Capture {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            self.write_bytes(s.as_bytes());
+            Ok(())
+        }
+    }
+
+    fn dump(bytes: &[u8], max_bytes: usize) -> Capture {
+        let mut capture = Capture::default();
+        LineBuilder { sink: &mut capture }.hexdump_max(bytes, max_bytes);
+        capture
+    }
+
+    #[test]
+    fn formats_short_buffer() {
+        let mut capture = Capture::default();
+        LineBuilder { sink: &mut capture }.hexdump(&[0xde, 0xad, 0x00, 0x0f, 0xf0]);
+        assert_eq!(capture.bytes, b"de ad 00 0f f0");
+
+        // Chunk boundaries do not show in the output.
+        let long: Vec<u8> = (0..20).collect();
+        let expected: Vec<std::string::String> = (0..20).map(|b| std::format!("{b:02x}")).collect();
+        assert_eq!(dump(&long, 20).bytes, expected.join(" ").as_bytes());
+    }
+
+    #[test]
+    fn empty_buffer_emits_nothing() {
+        assert!(dump(&[], 8).bytes.is_empty());
+        assert_eq!(dump(&[1, 2], 0).bytes, b" ...");
+    }
+
+    #[test]
+    fn test_reject_over_long_buffer_is_truncated() {
+        assert_eq!(dump(&[1, 2, 3, 4, 5, 6], 4).bytes, b"01 02 03 04 ...");
+        assert_eq!(dump(&[1, 2, 3, 4], 4).bytes, b"01 02 03 04");
+
+        // The default bound applies to `hexdump`, and no bound escapes MAX_SLICE_LEN.
+        let mut capture = Capture::default();
+        LineBuilder { sink: &mut capture }.hexdump(&[0xaa; 100]);
+        assert_eq!(capture.bytes.len(), LineBuilder::HEXDUMP_MAX_BYTES * 3 - 1 + 4);
+        let huge = dump(&[0xaa; MAX_SLICE_LEN], usize::MAX);
+        assert!(huge.bytes.len() <= MAX_SLICE_LEN);
+        assert!(huge.bytes.ends_with(b" ..."));
+        assert!(huge.largest_write <= 48);
+    }
+}
@@ -0,0 +1,159 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: Per-level entry points and the compile-time level ceiling (`max-level-*` features)
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: 1 unit test for any ceiling + 1 under `--features max-level-info`
+//!
+//! The `max-level-error` / `-warn` / `-info` / `-debug` features fix a [`STATIC_MAX_LEVEL`].
+//! Entry points above it test a `const` and return before touching the `LineBuilder`
+//! closure, so the optimizer drops the call together with its string data: a
+//! size-constrained image pays nothing for `trace!`-style calls it never emits. When
+//! several features are enabled (feature unification), the lowest ceiling wins.
+//!
+//! [`set_max_level`](crate::set_max_level) and the other runtime floors still apply below
+//! the ceiling; raising them past it has no effect. [`crate::log`] and [`crate::raw`]
+//! check the ceiling too, so no path emits a compiled-out level.
+
+use crate::{log, Level, LineBuilder, LineMeta, StrRef, Topic, TOPIC_GENERAL};
+
+/// Most verbose level compiled into this build (`Trace` unless a `max-level-*` feature is set).
+pub const STATIC_MAX_LEVEL: Level = if cfg!(feature = "max-level-error") {
+    Level::Error
+} else if cfg!(feature = "max-level-warn") {
+    Level::Warn
+} else if cfg!(feature = "max-level-info") {
+    Level::Info
+} else if cfg!(feature = "max-level-debug") {
+    Level::Debug
+} else {
+    Level::Trace
+};
+
+/// Whether records at `level` exist in this build at all.
+#[inline(always)]
+pub(crate) const fn compiled_in(level: Level) -> bool {
+    level as u8 <= STATIC_MAX_LEVEL as u8
+}
+
+// Error sits at or below every ceiling, so it needs no gate.
+pub fn error(target: &str, f: impl FnOnce(&mut LineBuilder)) {
+    log(LineMeta { level: Level::Error, target, topic: TOPIC_GENERAL }, f);
+}
+
+pub fn error_topic(target: &str, topic: Topic, f: impl FnOnce(&mut LineBuilder)) {
+    log(LineMeta { level: Level::Error, target, topic }, f);
+}
+
+#[inline]
+pub fn warn(target: &str, f: impl FnOnce(&mut LineBuilder)) {
+    if const { compiled_in(Level::Warn) } {
+        log(LineMeta { level: Level::Warn, target, topic: TOPIC_GENERAL }, f);
+    }
+}
+
+#[inline]
+pub fn warn_topic(target: &str, topic: Topic, f: impl FnOnce(&mut LineBuilder)) {
+    if const { compiled_in(Level::Warn) } {
+        log(LineMeta { level: Level::Warn, target, topic }, f);
+    }
+}
+
+#[inline]
+pub fn info(target: &str, f: impl FnOnce(&mut LineBuilder)) {
+    if const { compiled_in(Level::Info) } {
+        log(LineMeta { level: Level::Info, target, topic: TOPIC_GENERAL }, f);
+    }
+}
+
+#[inline]
+pub fn info_topic(target: &str, topic: Topic, f: impl FnOnce(&mut LineBuilder)) {
+    if const { compiled_in(Level::Info) } {
+        log(LineMeta { level: Level::Info, target, topic }, f);
+    }
+}
+
+#[inline]
+pub fn debug(target: &str, f: impl FnOnce(&mut LineBuilder)) {
+    if const { compiled_in(Level::Debug) } {
+        log(LineMeta { level: Level::Debug, target, topic: TOPIC_GENERAL }, f);
+    }
+}
+
+#[inline]
+pub fn debug_topic(target: &str, topic: Topic, f: impl FnOnce(&mut LineBuilder)) {
+    if const { compiled_in(Level::Debug) } {
+        log(LineMeta { level: Level::Debug, target, topic }, f);
+    }
+}
+
+#[inline]
+pub fn trace(target: &str, f: impl FnOnce(&mut LineBuilder)) {
+    if const { compiled_in(Level::Trace) } {
+        log(LineMeta { level: Level::Trace, target, topic: TOPIC_GENERAL }, f);
+    }
+}
+
+#[inline]
+pub fn trace_topic(target: &str, topic: Topic, f: impl FnOnce(&mut LineBuilder)) {
+    if const { compiled_in(Level::Trace) } {
+        log(LineMeta { level: Level::Trace, target, topic }, f);
+    }
+}
+
+pub fn info_static(target: &str, message: &str) {
+    info(target, |line| line.text_ref(StrRef::from(message)));
+}
+
+pub fn warn_static(target: &str, message: &str) {
+    warn(target, |line| line.text_ref(StrRef::from(message)));
+}
+
+pub fn debug_static(target: &str, message: &str) {
+    debug(target, |line| line.text_ref(StrRef::from(message)));
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+
+    use super::*;
+    use crate::set_max_level;
+
+    /// Which of `debug`/`info`/`warn` ran their closure under the runtime floor `floor`.
+    fn ran(floor: Level) -> [bool; 3] {
+        let hits = [Cell::new(false), Cell::new(false), Cell::new(false)];
+        set_max_level(floor);
+        debug("ceiling", |_| hits[0].set(true));
+        info("ceiling", |_| hits[1].set(true));
+        warn("ceiling", |_| hits[2].set(true));
+        set_max_level(Level::Info);
+        hits.map(Cell::into_inner)
+    }
+
+    #[test]
+    fn ceiling_caps_the_runtime_floor() {
+        let _serial = crate::dedup::tests::SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+        for floor in [Level::Trace, Level::Info, Level::Warn] {
+            let expected = [Level::Debug, Level::Info, Level::Warn]
+                .map(|l| l <= floor && l <= STATIC_MAX_LEVEL);
+            assert_eq!(ran(floor), expected, "floor {floor:?}");
+        }
+        assert!(compiled_in(Level::Error));
+    }
+
+    #[cfg(all(
+        feature = "max-level-info",
+        not(any(feature = "max-level-error", feature = "max-level-warn"))
+    ))]
+    #[test]
+    fn test_reject_debug_above_info_ceiling() {
+        let _serial = crate::dedup::tests::SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+        assert_eq!(STATIC_MAX_LEVEL, Level::Info);
+        assert!(!compiled_in(Level::Debug) && !compiled_in(Level::Trace));
+        // A runtime floor past the ceiling cannot bring Debug back.
+        assert_eq!(ran(Level::Trace), [false, true, true]);
+    }
+}
@@ -5,13 +5,12 @@
 //! OWNERS: @runtime
 //! STATUS: Experimental
 //! API_STABILITY: Unstable
-//! TEST_COVERAGE: No tests
+//! TEST_COVERAGE: Unit tests for runtime config, dedup, hex dumps, breadcrumbs, custom sink, raw,
+//! priority sink, error codes, kernel-sink logd bridge and its topic throttle, uptime, level ceiling, scope
 //! ADR: docs/rfcs/RFC-0003-unified-logging.md
 //!
-//! This is the first step toward RFC-0003 (unified logging). For now it only
-//! offers raw line emission with compile-time level gating and a per-domain
-//! prefix. Future work (tracked in the RFC) will layer richer routing,
-//! formatting, and runtime configuration on top.
+//! RFC-0003 (unified logging): one `[LEVEL target] …` record path with compile-time and runtime
+//! filtering, fanned out to the console, the logd journal and the optional sinks below.
 
 #![no_std]
 
@@ -19,14 +18,52 @@
 extern crate alloc;
 
 use core::fmt;
-use core::ops::{BitOr, BitOrAssign};
-use core::sync::atomic::{AtomicU32, AtomicU8, Ordering};
 
-#[cfg(all(feature = "sink-userspace", target_arch = "riscv64", target_os = "none"))]
-use core::sync::atomic::AtomicBool;
+mod breadcrumbs;
+mod bridge;
+mod code;
+mod config;
+mod custom;
+mod dedup;
+mod hexdump;
+mod levels;
+mod priority;
+mod raw;
+mod scope;
+#[cfg(feature = "sink-kernel")]
+mod sink_kernel;
+mod topic;
+mod uptime;
+
+pub use breadcrumbs::{breadcrumbs, BREADCRUMB_DEPTH, BREADCRUMB_OTHER_TARGET, BREADCRUMB_TARGETS};
+#[cfg(feature = "kernel-logd-bridge")]
+pub use bridge::{
+    clear_logd_bridge, clear_topic_priorities, logd_bridge_dropped, set_logd_bridge,
+    set_topic_priority, topic_dropped, LogdEndpoint, LOGD_BRIDGE_BACKLOG, LOGD_BRIDGE_LINE_LEN,
+    LOGD_PRESSURE_HEADROOM, MAX_TOPIC_PRIORITIES,
+};
+pub use code::{error_coded, CODE_FIELD_KEY, CODE_FIELD_LEN};
+use config::{level_enabled, logd_enabled, topic_enabled};
+pub use config::{
+    load_config, set_logd_level, set_max_level, set_target_levels, set_topic_mask, ConfigError,
+    MAX_CONFIG_LEN, MAX_TARGET_LEN, MAX_TARGET_OVERRIDES,
+};
+#[cfg(feature = "sink-custom")]
+pub use custom::{clear_custom_sink, set_custom_sink, CustomSink};
+pub use dedup::{set_dedup, DEDUP_LINE_LEN, DEDUP_MAX_REPEATS};
+pub use levels::{
+    debug, debug_static, debug_topic, error, error_topic, info, info_static, info_topic, trace,
+    trace_topic, warn, warn_static, warn_topic, STATIC_MAX_LEVEL,
+};
+#[cfg(feature = "dual-sink")]
+pub use priority::{clear_priority_sink, set_priority_sink, PrioritySink};
+pub use raw::raw;
+pub use scope::{scope, scope_with_clock, ScopeGuard, SCOPE_LEVEL};
+pub use topic::{Topic, TOPIC_GENERAL};
+pub use uptime::{set_uptime_clock, UptimeClock};
 
 #[cfg(all(feature = "sink-userspace", target_arch = "riscv64", target_os = "none"))]
-use core::sync::atomic::AtomicUsize;
+use core::sync::atomic::{AtomicBool, AtomicUsize};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 #[repr(u8)]
@@ -50,103 +87,8 @@ impl Level {
     }
 }
 
-// Console (UART) verbosity floor. Quiet by default: Error/Warn/Info reach the UART, Debug/Trace
-// do not. Decoupled from the logd journal floor below so the console can stay small/curated
-// while the full structured stream still lands in logd.
-static MAX_LEVEL: AtomicU8 = AtomicU8::new(Level::Info as u8);
-// logd journal floor — default Trace: the structured journal keeps EVERY record (the full
-// stream), so a curated console never means lost detail. The detail is recalled at runtime via
-// logd `OP_QUERY` (filter by level/service/time), not by a rebuild.
-static LOGD_LEVEL: AtomicU8 = AtomicU8::new(Level::Trace as u8);
-static TOPIC_MASK: AtomicU32 = AtomicU32::new(u32::MAX);
 const MAX_SLICE_LEN: usize = 0x4000;
 
-pub fn set_max_level(level: Level) {
-    MAX_LEVEL.store(level as u8, Ordering::Relaxed);
-}
-
-/// Set the logd journal floor (how much of the stream logd retains, independent of the console).
-#[allow(dead_code)]
-pub fn set_logd_level(level: Level) {
-    LOGD_LEVEL.store(level as u8, Ordering::Relaxed);
-}
-
-pub fn set_topic_mask(mask: Topic) {
-    TOPIC_MASK.store(mask.bits(), Ordering::Relaxed);
-}
-
-/// Whether `level` reaches the console (UART).
-fn level_enabled(level: Level) -> bool {
-    level as u8 <= MAX_LEVEL.load(Ordering::Relaxed)
-}
-
-/// Whether `level` is retained by the logd journal (independent of the console floor).
-#[allow(dead_code)]
-fn logd_enabled(level: Level) -> bool {
-    level as u8 <= LOGD_LEVEL.load(Ordering::Relaxed)
-}
-
-fn topic_enabled(topic: Topic) -> bool {
-    let mask = TOPIC_MASK.load(Ordering::Relaxed);
-    let bits = topic.bits();
-    if bits == 0 {
-        return true;
-    }
-    (mask & bits) == bits
-}
-
-pub fn error(target: &str, f: impl FnOnce(&mut LineBuilder)) {
-    log(LineMeta { level: Level::Error, target, topic: TOPIC_GENERAL }, f);
-}
-
-pub fn error_topic(target: &str, topic: Topic, f: impl FnOnce(&mut LineBuilder)) {
-    log(LineMeta { level: Level::Error, target, topic }, f);
-}
-
-pub fn warn(target: &str, f: impl FnOnce(&mut LineBuilder)) {
-    log(LineMeta { level: Level::Warn, target, topic: TOPIC_GENERAL }, f);
-}
-
-pub fn warn_topic(target: &str, topic: Topic, f: impl FnOnce(&mut LineBuilder)) {
-    log(LineMeta { level: Level::Warn, target, topic }, f);
-}
-
-pub fn info(target: &str, f: impl FnOnce(&mut LineBuilder)) {
-    log(LineMeta { level: Level::Info, target, topic: TOPIC_GENERAL }, f);
-}
-
-pub fn info_topic(target: &str, topic: Topic, f: impl FnOnce(&mut LineBuilder)) {
-    log(LineMeta { level: Level::Info, target, topic }, f);
-}
-
-pub fn debug(target: &str, f: impl FnOnce(&mut LineBuilder)) {
-    log(LineMeta { level: Level::Debug, target, topic: TOPIC_GENERAL }, f);
-}
-
-pub fn debug_topic(target: &str, topic: Topic, f: impl FnOnce(&mut LineBuilder)) {
-    log(LineMeta { level: Level::Debug, target, topic }, f);
-}
-
-pub fn trace(target: &str, f: impl FnOnce(&mut LineBuilder)) {
-    log(LineMeta { level: Level::Trace, target, topic: TOPIC_GENERAL }, f);
-}
-
-pub fn trace_topic(target: &str, topic: Topic, f: impl FnOnce(&mut LineBuilder)) {
-    log(LineMeta { level: Level::Trace, target, topic }, f);
-}
-
-pub fn info_static(target: &str, message: &str) {
-    info(target, |line| line.text_ref(StrRef::from(message)));
-}
-
-pub fn warn_static(target: &str, message: &str) {
-    warn(target, |line| line.text_ref(StrRef::from(message)));
-}
-
-pub fn debug_static(target: &str, message: &str) {
-    debug(target, |line| line.text_ref(StrRef::from(message)));
-}
-
 /// Configure deterministic logd sink slots for the current process.
 ///
 /// This is an opt-in override used by services that receive fixed slots from init-lite.
@@ -164,21 +106,21 @@ pub fn configure_sink_logd_slots(logd_send: u32, reply_send: u32, reply_recv: u3
 }
 
 pub fn log(meta: LineMeta<'_>, f: impl FnOnce(&mut LineBuilder)) {
-    if !topic_enabled(meta.topic) {
+    if !levels::compiled_in(meta.level) || !topic_enabled(meta.topic) {
         return;
     }
     // The console (UART) is a CURATED view (floor = MAX_LEVEL); the logd journal keeps the FULL
     // stream (floor = LOGD_LEVEL). The record is built once and routed to whichever sinks accept
     // this level: the console writes only when `console`, but the bytes are always captured for
     // logd. A record below both floors is skipped entirely.
-    let console = level_enabled(meta.level);
-    #[cfg(all(feature = "sink-logd", target_arch = "riscv64", target_os = "none"))]
-    let logd = logd_enabled(meta.level);
-    #[cfg(not(all(feature = "sink-logd", target_arch = "riscv64", target_os = "none")))]
-    let logd = false;
-    if !console && !logd {
+    let console = level_enabled(meta.level, meta.target);
+    let logd = cfg!(all(feature = "sink-logd", target_arch = "riscv64", target_os = "none"))
+        && logd_enabled(meta.level);
+    // ERROR/WARN go to an installed priority sink even below both floors (`dual-sink`).
+    if !console && !logd && !priority::wants(meta.level) {
         return;
     }
+    breadcrumbs::record(meta.target, meta.level);
 
     #[cfg(all(feature = "sink-userspace", target_arch = "riscv64", target_os = "none"))]
     {
@@ -188,33 +130,45 @@ pub fn log(meta: LineMeta<'_>, f: impl FnOnce(&mut LineBuilder)) {
         }
     }
 
-    let mut sink = sink::Sink::new(meta.level, meta.target, meta.topic, console);
-    {
+    let sink = if console && dedup::applies(meta.level) {
+        dedup::log_deduped(&meta, f)
+    } else {
+        let mut sink = sink::Sink::new(meta.level, meta.target, meta.topic, console);
         // Hold the cross-hart record lock for the whole `[LEVEL target] …\n`
         // span so concurrent harts never interleave bytes at the shared UART
         // (kernel sink only; userspace logs don't touch the raw MMIO).
         #[cfg(all(not(feature = "sink-userspace"), feature = "sink-kernel"))]
         let _record_guard = sink::record_lock::acquire(console);
+        custom::tee(&mut sink, console, |sink| {
+            bridge::tee(&meta, console, sink, |sink| write_record(sink, &meta, f))
+        });
+        sink
+    };
 
+    #[cfg(all(feature = "sink-logd", target_arch = "riscv64", target_os = "none"))]
+    if logd {
+        let mut field = [0u8; CODE_FIELD_LEN];
+        let fields = sink.error_code().map_or(&[][..], |c| code::encode_field(c, &mut field));
+        sink_logd::try_append(meta.level, meta.target, sink.capture_bytes(), false, fields);
+    }
+    #[cfg(not(all(feature = "sink-logd", target_arch = "riscv64", target_os = "none")))]
+    let _ = sink;
+}
+
+/// Writes one `[LEVEL target] …\n` record.
+///
+/// ERROR/WARN records are mirrored to the priority sink as they are rendered.
+fn write_record(sink: &mut dyn LineSink, meta: &LineMeta<'_>, f: impl FnOnce(&mut LineBuilder)) {
+    priority::mirror(meta.level, sink, |sink| {
         sink.write_byte(b'[');
-        sink.write_str(meta.level.label());
+        sink.write_bytes(meta.level.label().as_bytes());
         sink.write_byte(b' ');
-        sink.write_str(meta.target);
+        sink.write_bytes(meta.target.as_bytes());
         sink.write_byte(b']');
         sink.write_byte(b' ');
-
-        {
-            let mut builder = LineBuilder { sink: &mut sink };
-            f(&mut builder);
-        }
-
+        f(&mut LineBuilder { sink });
         sink.write_byte(b'\n');
-    }
-
-    #[cfg(all(feature = "sink-logd", target_arch = "riscv64", target_os = "none"))]
-    if logd {
-        sink_logd::try_append(meta.level, meta.target, sink.capture_bytes());
-    }
+    });
 }
 
 pub struct LineMeta<'a> {
@@ -323,54 +277,18 @@ fn str_ref_permitted(ptr: usize, len: usize) -> bool {
 }
 
 pub struct LineBuilder<'a, 'meta> {
-    sink: &'a mut sink::Sink<'meta>,
-}
-
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub struct Topic(u32);
-
-impl Topic {
-    pub const fn empty() -> Self {
-        Topic(0)
-    }
-
-    pub const fn bit(bit: u8) -> Self {
-        Topic(1u32 << (bit as u32))
-    }
-
-    pub const fn from_bits(bits: u32) -> Self {
-        Topic(bits)
-    }
-
-    pub const fn all() -> Self {
-        Topic(u32::MAX)
-    }
-
-    pub const fn bits(self) -> u32 {
-        self.0
-    }
-
-    pub const fn is_empty(self) -> bool {
-        self.0 == 0
-    }
+    sink: &'a mut (dyn LineSink + 'meta),
 }
 
-impl BitOr for Topic {
-    type Output = Topic;
-
-    fn bitor(self, rhs: Topic) -> Topic {
-        Topic(self.0 | rhs.0)
-    }
+/// Byte destination behind a [`LineBuilder`]: the active sink or the dedup line buffer.
+trait LineSink: fmt::Write {
+    fn write_byte(&mut self, byte: u8);
+    fn write_bytes(&mut self, bytes: &[u8]);
+    /// Records the structured error code of the record (see `code`); sinks without a
+    /// structured channel drop it.
+    fn set_error_code(&mut self, _code: u32) {}
 }
 
-impl BitOrAssign for Topic {
-    fn bitor_assign(&mut self, rhs: Topic) {
-        self.0 |= rhs.0;
-    }
-}
-
-pub const TOPIC_GENERAL: Topic = Topic::bit(0);
-
 impl LineBuilder<'_, '_> {
     pub fn text(&mut self, text: &str) {
         self.text_ref(StrRef::new(text));
@@ -409,11 +327,11 @@ impl LineBuilder<'_, '_> {
             }
         }
 
-        self.sink.write_str("<bad-str ptr=");
+        self.sink.write_bytes(b"<bad-str ptr=");
         self.hex(text.ptr() as u64);
-        self.sink.write_str(" len=");
+        self.sink.write_bytes(b" len=");
         self.dec(text.len() as u64);
-        self.sink.write_str(">");
+        self.sink.write_bytes(b">");
     }
 
     pub fn kv_literal(&mut self, key: &str, value: &str) {
@@ -486,9 +404,18 @@ impl LineBuilder<'_, '_> {
     }
 }
 
-impl sink::Sink<'_> {
-    fn write_str(&mut self, s: &str) {
-        self.write_bytes(s.as_bytes());
+impl LineSink for sink::Sink<'_> {
+    fn write_byte(&mut self, byte: u8) {
+        sink::Sink::write_byte(self, byte);
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        sink::Sink::write_bytes(self, bytes);
+    }
+
+    #[cfg(all(feature = "sink-logd", target_arch = "riscv64", target_os = "none"))]
+    fn set_error_code(&mut self, code: u32) {
+        sink::Sink::set_error_code(self, code);
     }
 }
 
@@ -528,34 +455,22 @@ fn log_guard_violation(
     target: &str,
 ) {
     userspace_putc(b'!');
-    for &b in b"guard-str reason=" {
-        userspace_putc(b);
-    }
+    emit_literal(b"guard-str reason=");
     fault.emit_label();
     userspace_putc(b' ');
-    for &b in b"ptr=0x" {
-        userspace_putc(b);
-    }
+    emit_literal(b"ptr=0x");
     emit_hex(ptr as u64, |b| userspace_putc(b));
     userspace_putc(b' ');
-    for &b in b"len=0x" {
-        userspace_putc(b);
-    }
+    emit_literal(b"len=0x");
     emit_hex(len as u64, |b| userspace_putc(b));
     userspace_putc(b' ');
-    for &b in b"ra=0x" {
-        userspace_putc(b);
-    }
+    emit_literal(b"ra=0x");
     emit_hex(ra as u64, |b| userspace_putc(b));
     userspace_putc(b' ');
-    for &b in b"level=" {
-        userspace_putc(b);
-    }
+    emit_literal(b"level=");
     emit_level_label(level);
     userspace_putc(b' ');
-    for &b in b"target=" {
-        userspace_putc(b);
-    }
+    emit_literal(b"target=");
     for &b in target.as_bytes().iter().take(16) {
         userspace_putc(b);
     }
@@ -605,7 +520,6 @@ static USERS_STR_GOOD: AtomicUsize = AtomicUsize::new(0);
 static USERS_TEXT_FAST: AtomicUsize = AtomicUsize::new(0);
 #[cfg(all(feature = "sink-userspace", target_arch = "riscv64", target_os = "none"))]
 static USERS_TEXT_FALLBACK: AtomicUsize = AtomicUsize::new(0);
-
 #[cfg(all(feature = "sink-userspace", target_arch = "riscv64", target_os = "none"))]
 static USERS_STR_PROBE: AtomicUsize = AtomicUsize::new(0);
 
@@ -629,14 +543,10 @@ fn debug_str_probe(ptr: usize, len: usize) {
         return;
     }
     userspace_putc(b'#');
-    for &b in b"probe-str ptr=0x" {
-        userspace_putc(b);
-    }
+    emit_literal(b"probe-str ptr=0x");
     emit_hex(ptr as u64, |b| userspace_putc(b));
     userspace_putc(b' ');
-    for &b in b"len=0x" {
-        userspace_putc(b);
-    }
+    emit_literal(b"len=0x");
     emit_hex(len as u64, |b| userspace_putc(b));
     userspace_putc(b'\n');
 }
@@ -647,14 +557,10 @@ fn debug_bad_str(ptr: usize, len: usize) {
         return;
     }
     userspace_putc(b'!');
-    for &b in b"bad-str ptr=0x" {
-        userspace_putc(b);
-    }
+    emit_literal(b"bad-str ptr=0x");
     emit_hex(ptr as u64, |b| userspace_putc(b));
     userspace_putc(b' ');
-    for &b in b"len=0x" {
-        userspace_putc(b);
-    }
+    emit_literal(b"len=0x");
     emit_hex(len as u64, |b| userspace_putc(b));
     userspace_putc(b'\n');
 }
@@ -676,14 +582,10 @@ fn trace_good_str(ptr: usize, len: usize) {
         return;
     }
     userspace_putc(b'~');
-    for &b in b"good-str ptr=0x" {
-        userspace_putc(b);
-    }
+    emit_literal(b"good-str ptr=0x");
     emit_hex(ptr as u64, |b| userspace_putc(b));
     userspace_putc(b' ');
-    for &b in b"len=0x" {
-        userspace_putc(b);
-    }
+    emit_literal(b"len=0x");
     emit_hex(len as u64, |b| userspace_putc(b));
     userspace_putc(b'\n');
 }
@@ -694,14 +596,10 @@ fn trace_text_fast(ptr: usize, len: usize) {
         return;
     }
     userspace_putc(b'^');
-    for &b in b"text-fast ptr=0x" {
-        userspace_putc(b);
-    }
+    emit_literal(b"text-fast ptr=0x");
     emit_hex(ptr as u64, |b| userspace_putc(b));
     userspace_putc(b' ');
-    for &b in b"len=0x" {
-        userspace_putc(b);
-    }
+    emit_literal(b"len=0x");
     emit_hex(len as u64, |b| userspace_putc(b));
     userspace_putc(b'\n');
 }
@@ -712,14 +610,10 @@ fn trace_text_fallback(ptr: usize, len: usize) {
         return;
     }
     userspace_putc(b'^');
-    for &b in b"text-fallback ptr=0x" {
-        userspace_putc(b);
-    }
+    emit_literal(b"text-fallback ptr=0x");
     emit_hex(ptr as u64, |b| userspace_putc(b));
     userspace_putc(b' ');
-    for &b in b"len=0x" {
-        userspace_putc(b);
-    }
+    emit_literal(b"len=0x");
     emit_hex(len as u64, |b| userspace_putc(b));
     userspace_putc(b'\n');
 }
@@ -758,8 +652,7 @@ fn guard_logs_enabled() -> bool {
     option_env!("INIT_LITE_GUARD_LOG") == Some("1")
 }
 
-// Host/non-RISC-V stub to keep sink-userspace compiling when the feature is
-// enabled but we are not building the os-lite target.
+// Host/non-RISC-V stub: keeps sink-userspace compiling off the os-lite target.
 #[cfg(all(feature = "sink-userspace", not(all(target_arch = "riscv64", target_os = "none"))))]
 fn guard_logs_enabled() -> bool {
     false
@@ -771,31 +664,21 @@ fn trace_large_write(ptr: usize, len: usize, ra: usize, level: Level, target: &s
         return;
     }
     userspace_putc(b'^');
-    for &b in b"log-large level=" {
-        userspace_putc(b);
-    }
+    emit_literal(b"log-large level=");
     userspace_putc(level_tag(level));
     userspace_putc(b' ');
-    for &b in b"target=" {
-        userspace_putc(b);
-    }
+    emit_literal(b"target=");
     for &b in target.as_bytes().iter().take(16) {
         userspace_putc(b);
     }
     userspace_putc(b' ');
-    for &b in b"log-large ptr=0x" {
-        userspace_putc(b);
-    }
+    emit_literal(b"log-large ptr=0x");
     emit_hex(ptr as u64, |b| userspace_putc(b));
     userspace_putc(b' ');
-    for &b in b"len=0x" {
-        userspace_putc(b);
-    }
+    emit_literal(b"len=0x");
     emit_hex(len as u64, |b| userspace_putc(b));
     userspace_putc(b' ');
-    for &b in b"ra=0x" {
-        userspace_putc(b);
-    }
+    emit_literal(b"ra=0x");
     emit_hex(ra as u64, |b| userspace_putc(b));
     userspace_putc(b'\n');
 }
@@ -810,19 +693,13 @@ fn trace_dec_slice(ptr: usize, len: usize, ra: usize) {
         return;
     }
     userspace_putc(b'^');
-    for &b in b"dec-slice ptr=0x" {
-        userspace_putc(b);
-    }
+    emit_literal(b"dec-slice ptr=0x");
     emit_hex(ptr as u64, |b| userspace_putc(b));
     userspace_putc(b' ');
-    for &b in b"len=0x" {
-        userspace_putc(b);
-    }
+    emit_literal(b"len=0x");
     emit_hex(len as u64, |b| userspace_putc(b));
     userspace_putc(b' ');
-    for &b in b"ra=0x" {
-        userspace_putc(b);
-    }
+    emit_literal(b"ra=0x");
     emit_hex(ra as u64, |b| userspace_putc(b));
     userspace_putc(b'\n');
 }
@@ -976,6 +853,8 @@ mod sink_userspace {
         cap_len: usize,
         #[cfg(all(feature = "sink-logd", target_arch = "riscv64", target_os = "none"))]
         cap_buf: [u8; 320],
+        #[cfg(all(feature = "sink-logd", target_arch = "riscv64", target_os = "none"))]
+        code: Option<u32>,
     }
 
     impl<'meta> Sink<'meta> {
@@ -990,6 +869,8 @@ mod sink_userspace {
                 cap_len: 0,
                 #[cfg(all(feature = "sink-logd", target_arch = "riscv64", target_os = "none"))]
                 cap_buf: [0u8; 320],
+                #[cfg(all(feature = "sink-logd", target_arch = "riscv64", target_os = "none"))]
+                code: None,
             }
         }
 
@@ -1055,6 +936,17 @@ mod sink_userspace {
         pub fn capture_bytes(&self) -> &[u8] {
             &self.cap_buf[..self.cap_len]
         }
+
+        #[cfg(all(feature = "sink-logd", target_arch = "riscv64", target_os = "none"))]
+        pub fn set_error_code(&mut self, code: u32) {
+            self.code = Some(code);
+        }
+
+        /// Structured error code for the logd `fields` blob, if the record set one.
+        #[cfg(all(feature = "sink-logd", target_arch = "riscv64", target_os = "none"))]
+        pub fn error_code(&self) -> Option<u32> {
+            self.code
+        }
     }
 
     impl fmt::Write for Sink<'_> {
@@ -1072,7 +964,7 @@ mod sink_logd {
     use nexus_abi::{
         cap_clone, cap_close, ipc_recv_v1, MsgHeader, IPC_SYS_NONBLOCK, IPC_SYS_TRUNCATE,
     };
-    use nexus_ipc::KernelClient;
+    use nexus_ipc::{KernelClient, Wait};
 
     use crate::Level;
 
@@ -1083,25 +975,26 @@ mod sink_logd {
 
     const MAX_SCOPE: usize = 64;
     const MAX_MSG: usize = 256;
+    const MAX_FIELDS: usize = crate::CODE_FIELD_LEN;
 
     // Cached slots (0 means unknown).
     static LOGD_SEND_SLOT: AtomicU32 = AtomicU32::new(0);
     static REPLY_SEND_SLOT: AtomicU32 = AtomicU32::new(0);
     static REPLY_RECV_SLOT: AtomicU32 = AtomicU32::new(0);
 
-    pub fn try_append(level: Level, target: &str, line: &[u8]) {
+    /// `raw`: the line has no `[LEVEL target] ` prefix to strip (see `crate::raw`).
+    /// `fields`: structured `key=value` blob (RFC-0011), cut at `MAX_FIELDS` bytes.
+    pub fn try_append(level: Level, target: &str, line: &[u8], raw: bool, fields: &[u8]) {
         // Best-effort only: logging must not block or panic.
-        let (logd_send, reply_send, reply_recv) = match ensure_slots(target) {
-            Some(v) => v,
-            None => return,
-        };
+        let Some((logd_send, reply_send, reply_recv)) = ensure_slots(target) else { return };
 
-        let mut frame = [0u8; 4 + 1 + 1 + 2 + 2 + MAX_SCOPE + MAX_MSG];
+        let mut frame = [0u8; 4 + 1 + 1 + 2 + 2 + MAX_SCOPE + MAX_MSG + MAX_FIELDS];
         let scope = target.as_bytes();
         let scope_len = core::cmp::min(scope.len(), MAX_SCOPE);
 
-        let msg = strip_prefix_and_nl(line);
+        let msg = if raw { strip_nl(line) } else { strip_prefix_and_nl(line) };
         let msg_len = core::cmp::min(msg.len(), MAX_MSG);
+        let fields = &fields[..core::cmp::min(fields.len(), MAX_FIELDS)];
 
         // Header
         let mut n = 0usize;
@@ -1116,24 +1009,24 @@ mod sink_logd {
         n += 1;
         frame[n..n + 2].copy_from_slice(&(msg_len as u16).to_le_bytes());
         n += 2;
-        frame[n..n + 2].copy_from_slice(&0u16.to_le_bytes()); // fields_len
+        frame[n..n + 2].copy_from_slice(&(fields.len() as u16).to_le_bytes());
         n += 2;
         frame[n..n + scope_len].copy_from_slice(&scope[..scope_len]);
         n += scope_len;
         frame[n..n + msg_len].copy_from_slice(&msg[..msg_len]);
         n += msg_len;
+        frame[n..n + fields.len()].copy_from_slice(fields);
+        n += fields.len();
 
-        let moved = match cap_clone(reply_send) {
-            Ok(slot) => slot,
-            Err(_) => return,
+        let Ok(moved) = nexus_abi::ReplyChannel::new(reply_send, reply_recv).reply_send() else {
+            return;
         };
 
         // Use explicit slots to avoid route queries/allocations per line.
-        let client = match KernelClient::new_with_slots(logd_send, reply_recv) {
-            Ok(c) => c,
-            Err(_) => return,
-        };
-        let _ = client.send_with_cap_move_wait(&frame[..n], moved, nexus_ipc::Wait::NonBlocking);
+        let Ok(client) = KernelClient::new_with_slots(logd_send, reply_recv) else { return };
+        if client.send_with_cap_move_wait(&frame[..n], moved.slot(), Wait::NonBlocking).is_ok() {
+            moved.commit();
+        }
 
         // Drain a few replies to avoid filling the reply inbox under high log volume.
         drain_reply(reply_recv);
@@ -1181,38 +1074,22 @@ mod sink_logd {
     fn drain_reply(recv_slot: u32) {
         let mut hdr = MsgHeader::new(0, 0, 0, 0, 0);
         let mut buf = [0u8; 64];
+        let flags = IPC_SYS_NONBLOCK | IPC_SYS_TRUNCATE;
         for _ in 0..4 {
-            match ipc_recv_v1(recv_slot, &mut hdr, &mut buf, IPC_SYS_NONBLOCK | IPC_SYS_TRUNCATE, 0)
-            {
-                Ok(_n) => {}
-                Err(nexus_abi::IpcError::QueueEmpty) => break,
-                Err(_) => break,
+            if ipc_recv_v1(recv_slot, &mut hdr, &mut buf, flags, 0).is_err() {
+                break;
             }
         }
     }
 
-    fn strip_prefix_and_nl(line: &[u8]) -> &[u8] {
-        let mut s = line;
-        if let Some(last) = s.last() {
-            if *last == b'\n' {
-                s = &s[..s.len().saturating_sub(1)];
-            }
-        }
-        if let Some(pos) = find_bracket_space(s) {
-            &s[pos..]
-        } else {
-            s
-        }
+    fn strip_nl(line: &[u8]) -> &[u8] {
+        line.strip_suffix(b"\n").unwrap_or(line)
     }
 
-    fn find_bracket_space(s: &[u8]) -> Option<usize> {
-        // Find the first occurrence of "] " and return the index after it.
-        for i in 0..s.len().saturating_sub(1) {
-            if s[i] == b']' && s[i + 1] == b' ' {
-                return Some(i + 2);
-            }
-        }
-        None
+    fn strip_prefix_and_nl(line: &[u8]) -> &[u8] {
+        let s = strip_nl(line);
+        // Everything after the first "] " (the end of the `[LEVEL target]` prefix).
+        s.windows(2).position(|w| w == b"] ").map_or(s, |i| &s[i + 2..])
     }
 
     fn level_to_logd(level: Level) -> u8 {
@@ -1288,120 +1165,6 @@ fn userspace_putc(byte: u8) {
 #[cfg(not(all(feature = "sink-userspace", target_arch = "riscv64", target_os = "none")))]
 fn userspace_putc(_byte: u8) {}
 
-#[cfg(feature = "sink-kernel")]
-mod sink_kernel {
-    use core::fmt;
-
-    use super::{Level, Topic};
-
-    pub struct Sink<'meta> {
-        #[allow(unused)]
-        level: Level,
-        #[allow(unused)]
-        target: &'meta str,
-        #[allow(unused)]
-        topic: Topic,
-        console: bool,
-    }
-
-    impl<'meta> Sink<'meta> {
-        #[allow(dead_code)]
-        pub fn new(level: Level, target: &'meta str, topic: Topic, console: bool) -> Self {
-            Self { level, target, topic, console }
-        }
-
-        pub fn write_byte(&mut self, byte: u8) {
-            if !self.console {
-                return;
-            }
-            unsafe {
-                const UART_BASE: usize = 0x1000_0000;
-                const UART_TX: usize = 0x0;
-                const UART_LSR: usize = 0x5;
-                const LSR_TX_IDLE: u8 = 1 << 5;
-
-                while core::ptr::read_volatile((UART_BASE + UART_LSR) as *const u8) & LSR_TX_IDLE
-                    == 0
-                {}
-                core::ptr::write_volatile((UART_BASE + UART_TX) as *mut u8, byte);
-            }
-        }
-
-        pub fn write_bytes(&mut self, bytes: &[u8]) {
-            for &b in bytes {
-                self.write_byte(b);
-            }
-        }
-    }
-
-    impl fmt::Write for Sink<'_> {
-        fn write_str(&mut self, s: &str) -> fmt::Result {
-            self.write_bytes(s.as_bytes());
-            Ok(())
-        }
-    }
-
-    /// Cross-hart record atomicity for the shared 16550 UART.
-    ///
-    /// The kernel sink writes each record byte-by-byte to one MMIO TX register.
-    /// Under SMP (MTTCG, real parallelism) two harts emitting concurrently
-    /// interleave their bytes, corrupting whole marker lines — which broke the
-    /// `KSELFTEST: bkl budget ok` grep gate and the nexus-evidence trace parser.
-    /// A record acquires this lock for its full `[LEVEL target] …\n` span so
-    /// records never interleave.
-    ///
-    /// It is a **bounded-try** lock, never a blocking one: kernel logging is
-    /// reentrant (a trap/IRQ handler can log while the interrupted context on
-    /// the SAME hart holds the lock), and a blocking spinlock would deadlock
-    /// there. So `acquire` spins up to a generous bound (long enough to outlast
-    /// any in-flight record on another hart) and, if it can't get in, proceeds
-    /// WITHOUT the lock rather than hang. Common cross-hart contention is
-    /// serialized (atomic records); the rare reentrant/over-contended case falls
-    /// back to today's unlocked behavior — strictly never worse, never a hang.
-    pub(super) mod record_lock {
-        use core::sync::atomic::{AtomicBool, Ordering};
-
-        static LOCK: AtomicBool = AtomicBool::new(false);
-
-        pub struct Guard {
-            held: bool,
-        }
-
-        /// Bounded-try acquire. `active` is the `console` flag — no lock needed
-        /// when this record isn't going to the UART.
-        pub fn acquire(active: bool) -> Guard {
-            if !active {
-                return Guard { held: false };
-            }
-            // A record is tens of bytes, each a TX-idle spin of a few hundred
-            // cycles (~tens of thousands total); this bound comfortably outlasts
-            // one record on another hart while still guaranteeing forward
-            // progress (no deadlock) and never pathologically slowing logging.
-            const SPIN_BOUND: u32 = 2_000_000;
-            let mut spins: u32 = 0;
-            while LOCK
-                .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
-                .is_err()
-            {
-                core::hint::spin_loop();
-                spins += 1;
-                if spins >= SPIN_BOUND {
-                    return Guard { held: false };
-                }
-            }
-            Guard { held: true }
-        }
-
-        impl Drop for Guard {
-            fn drop(&mut self) {
-                if self.held {
-                    LOCK.store(false, Ordering::Release);
-                }
-            }
-        }
-    }
-}
-
 #[cfg(feature = "sink-userspace")]
 use sink_userspace as sink;
 
@@ -0,0 +1,211 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: High-priority mirror of ERROR/WARN records (`dual-sink`)
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: 2 unit tests (`--features sink-custom,dual-sink`)
+//!
+//! With the `dual-sink` feature, [`set_priority_sink`] installs a `'static` [`PrioritySink`]
+//! (e.g. a reserved crash ring) that receives a copy of every `Error` and `Warn` record as it
+//! is rendered, before the console floor, dedup or the logd queue get a say. A warning that
+//! dedup suppresses on the console, or one whose logd append is dropped, is still mirrored.
+//! Topic gating still applies. The copy is written byte by byte through the sink's methods:
+//! nothing is allocated or buffered here, and a sink that drops bytes loses only its copy.
+//! Without the feature, [`mirror`] is a pass-through and nothing here is public.
+
+use crate::{Level, LineSink};
+
+/// Runs `f` against `sink`; `Error`/`Warn` bytes also go to the installed priority sink.
+#[cfg(not(feature = "dual-sink"))]
+pub(crate) fn mirror<R>(
+    level: Level,
+    sink: &mut dyn LineSink,
+    f: impl FnOnce(&mut dyn LineSink) -> R,
+) -> R {
+    let _ = level;
+    f(sink)
+}
+
+/// Whether a record at `level` is mirrored (a priority sink is installed for it).
+#[cfg(not(feature = "dual-sink"))]
+pub(crate) fn wants(level: Level) -> bool {
+    let _ = level;
+    false
+}
+
+#[cfg(feature = "dual-sink")]
+pub use installed::{clear_priority_sink, set_priority_sink, PrioritySink};
+#[cfg(feature = "dual-sink")]
+pub(crate) use installed::{mirror, wants};
+
+#[cfg(feature = "dual-sink")]
+mod installed {
+    use core::cell::UnsafeCell;
+    use core::fmt;
+    use core::sync::atomic::{AtomicBool, Ordering};
+
+    use super::{Level, LineSink};
+
+    /// Destination for the ERROR/WARN copy (see [`set_priority_sink`]).
+    ///
+    /// Implementations must not allocate or block; they may drop bytes when full.
+    pub trait PrioritySink: Sync {
+        /// Receives one byte of a record.
+        fn write_byte(&self, byte: u8);
+
+        /// Receives a run of record bytes.
+        fn write_bytes(&self, bytes: &[u8]) {
+            for &byte in bytes {
+                self.write_byte(byte);
+            }
+        }
+    }
+
+    // Spin-guarded slot: installs are rare and readers only copy the reference out.
+    struct Slot(UnsafeCell<Option<&'static dyn PrioritySink>>);
+
+    // SAFETY: the cell is only read or written while `LOCK` is held.
+    unsafe impl Sync for Slot {}
+
+    static LOCK: AtomicBool = AtomicBool::new(false);
+    static SLOT: Slot = Slot(UnsafeCell::new(None));
+
+    fn with_slot<R>(f: impl FnOnce(&mut Option<&'static dyn PrioritySink>) -> R) -> R {
+        while LOCK.compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed).is_err()
+        {
+            core::hint::spin_loop();
+        }
+        // SAFETY: `LOCK` is held, so this is the only live reference to the slot.
+        let out = f(unsafe { &mut *SLOT.0.get() });
+        LOCK.store(false, Ordering::Release);
+        out
+    }
+
+    /// Installs `sink` to receive every ERROR/WARN record, replacing any previous one.
+    pub fn set_priority_sink(sink: &'static dyn PrioritySink) {
+        with_slot(|slot| *slot = Some(sink));
+    }
+
+    /// Removes the installed priority sink, if any.
+    pub fn clear_priority_sink() {
+        with_slot(|slot| *slot = None);
+    }
+
+    fn installed_for(level: Level) -> Option<&'static dyn PrioritySink> {
+        if level > Level::Warn {
+            return None;
+        }
+        with_slot(|slot| *slot)
+    }
+
+    pub(crate) fn wants(level: Level) -> bool {
+        installed_for(level).is_some()
+    }
+
+    pub(crate) fn mirror<R>(
+        level: Level,
+        sink: &mut dyn LineSink,
+        f: impl FnOnce(&mut dyn LineSink) -> R,
+    ) -> R {
+        match installed_for(level) {
+            Some(priority) => f(&mut Mirror { inner: sink, priority }),
+            None => f(sink),
+        }
+    }
+
+    struct Mirror<'s> {
+        inner: &'s mut dyn LineSink,
+        priority: &'static dyn PrioritySink,
+    }
+
+    impl LineSink for Mirror<'_> {
+        fn write_byte(&mut self, byte: u8) {
+            self.inner.write_byte(byte);
+            self.priority.write_byte(byte);
+        }
+
+        fn write_bytes(&mut self, bytes: &[u8]) {
+            self.inner.write_bytes(bytes);
+            self.priority.write_bytes(bytes);
+        }
+
+        fn set_error_code(&mut self, code: u32) {
+            self.inner.set_error_code(code);
+        }
+    }
+
+    impl fmt::Write for Mirror<'_> {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            LineSink::write_bytes(self, s.as_bytes());
+            Ok(())
+        }
+    }
+
+    #[cfg(all(test, feature = "sink-custom"))]
+    mod tests {
+        extern crate std;
+
+        use std::sync::Mutex;
+        use std::vec::Vec;
+
+        use super::*;
+        use crate::{clear_custom_sink, error, info, set_custom_sink, set_dedup, warn, CustomSink};
+
+        struct Capture(Mutex<Vec<u8>>);
+
+        impl Capture {
+            fn push(&self, byte: u8) {
+                self.0.lock().unwrap_or_else(|e| e.into_inner()).push(byte);
+            }
+
+            fn take(&self) -> Vec<u8> {
+                core::mem::take(&mut *self.0.lock().unwrap_or_else(|e| e.into_inner()))
+            }
+        }
+
+        impl CustomSink for Capture {
+            fn write_byte(&self, byte: u8) {
+                self.push(byte);
+            }
+        }
+
+        impl PrioritySink for Capture {
+            fn write_byte(&self, byte: u8) {
+                self.push(byte);
+            }
+        }
+
+        static PRIMARY: Capture = Capture(Mutex::new(Vec::new()));
+        static PRIORITY: Capture = Capture(Mutex::new(Vec::new()));
+
+        #[test]
+        fn error_reaches_both_sinks_info_only_the_primary() {
+            let _serial = crate::dedup::tests::SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+            set_custom_sink(&PRIMARY);
+            set_priority_sink(&PRIORITY);
+            error("disk", |line| line.text("offline"));
+            info("disk", |line| line.text("rescan"));
+            clear_priority_sink();
+            clear_custom_sink();
+            assert_eq!(PRIMARY.take(), b"[ERROR disk] offline\n[INFO disk] rescan\n");
+            assert_eq!(PRIORITY.take(), b"[ERROR disk] offline\n");
+        }
+
+        #[test]
+        fn test_reject_dedup_suppressing_the_priority_copy() {
+            let _serial = crate::dedup::tests::SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+            set_dedup(true);
+            set_custom_sink(&PRIMARY);
+            set_priority_sink(&PRIORITY);
+            warn("net", |line| line.text("link flap"));
+            warn("net", |line| line.text("link flap"));
+            clear_priority_sink();
+            clear_custom_sink();
+            set_dedup(false);
+            assert_eq!(PRIMARY.take(), b"[WARN net] link flap\n");
+            assert_eq!(PRIORITY.take(), b"[WARN net] link flap\n[WARN net] link flap\n");
+        }
+    }
+}
@@ -0,0 +1,94 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: Raw passthrough of externally formatted lines (no `[LEVEL target]` prefix)
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: 2 unit tests (`--features sink-custom`)
+//!
+//! [`raw`] forwards bytes that already carry their own formatting — a child process's
+//! stderr, say — through the same sinks as a regular record. The line is level and topic
+//! gated like [`crate::log`] (topic `GENERAL`), and the bytes still go through the sink's
+//! `write_bytes`, so the userspace slice guards apply. Only a trailing newline is added.
+//! The logd journal receives the bytes as the message unchanged, since there is no prefix
+//! to strip. Raw lines bypass console dedup.
+
+use crate::config::{level_enabled, logd_enabled, topic_enabled};
+use crate::{breadcrumbs, custom, sink, Level, TOPIC_GENERAL};
+
+/// Emits `bytes` followed by `\n`, gated on `level` for `target`, without the bracket prefix.
+pub fn raw(target: &str, level: Level, bytes: &[u8]) {
+    if !crate::levels::compiled_in(level) || !topic_enabled(TOPIC_GENERAL) {
+        return;
+    }
+    let console = level_enabled(level, target);
+    let logd = cfg!(all(feature = "sink-logd", target_arch = "riscv64", target_os = "none"))
+        && logd_enabled(level);
+    if !console && !logd {
+        return;
+    }
+    breadcrumbs::record(target, level);
+
+    let mut sink = sink::Sink::new(level, target, TOPIC_GENERAL, console);
+    #[cfg(all(not(feature = "sink-userspace"), feature = "sink-kernel"))]
+    let _record_guard = sink::record_lock::acquire(console);
+    custom::tee(&mut sink, console, |sink| {
+        sink.write_bytes(bytes);
+        sink.write_byte(b'\n');
+    });
+
+    #[cfg(all(feature = "sink-logd", target_arch = "riscv64", target_os = "none"))]
+    if logd {
+        crate::sink_logd::try_append(level, target, sink.capture_bytes(), true, &[]);
+    }
+}
+
+#[cfg(all(test, feature = "sink-custom"))]
+mod tests {
+    extern crate std;
+
+    use std::sync::Mutex;
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::{clear_custom_sink, set_custom_sink, CustomSink};
+
+    struct Capture(Mutex<Vec<u8>>);
+
+    impl CustomSink for Capture {
+        fn write_byte(&self, byte: u8) {
+            self.0.lock().unwrap_or_else(|e| e.into_inner()).push(byte);
+        }
+    }
+
+    impl Capture {
+        fn take(&self) -> Vec<u8> {
+            core::mem::take(&mut *self.0.lock().unwrap_or_else(|e| e.into_inner()))
+        }
+    }
+
+    static CAPTURE: Capture = Capture(Mutex::new(Vec::new()));
+
+    #[test]
+    fn raw_line_has_no_bracket_prefix() {
+        let _serial = crate::dedup::tests::SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+        set_custom_sink(&CAPTURE);
+        // Error: the one level every `max-level-*` ceiling keeps.
+        raw("child", Level::Error, b"make: *** [all] Error 2");
+        raw("child", Level::Error, b"");
+        clear_custom_sink();
+        assert_eq!(CAPTURE.take(), b"make: *** [all] Error 2\n\n");
+    }
+
+    #[test]
+    fn test_reject_raw_line_below_level_floor() {
+        let _serial = crate::dedup::tests::SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+        assert!(!level_enabled(Level::Trace, "child"));
+        set_custom_sink(&CAPTURE);
+        raw("child", Level::Trace, b"too chatty");
+        raw("child", Level::Error, b"kept");
+        clear_custom_sink();
+        assert_eq!(CAPTURE.take(), b"kept\n");
+    }
+}
@@ -0,0 +1,158 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: Scoped timing guard logging `enter X` / `exit X (Nms)` (`scope`)
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: 2 unit tests (`--features sink-custom`: both lines with a mock clock, gated out)
+//!
+//! [`scope`] times a code path without metricsd, the log-only counterpart of
+//! `nexus_metrics::SpanGuard`: it logs `enter <name>` at [`SCOPE_LEVEL`] and returns a
+//! [`ScopeGuard`] that logs `exit <name> (<ms>ms)` when dropped. The time comes from the
+//! uptime clock (`nexus_abi::nsec` on a userspace OS build, or whatever [`set_uptime_clock`]
+//! installed); [`scope_with_clock`] takes the clock directly. No allocation, no formatting
+//! buffer beyond the record itself.
+//!
+//! Whether the scope logs is decided once, on entry: a scope whose level is compiled out or
+//! below every runtime floor reads no clock and logs neither line, even if the floor changes
+//! before it ends. A clock failure renders the duration as `?ms`.
+//!
+//! [`set_uptime_clock`]: crate::set_uptime_clock
+
+use crate::config::{level_enabled, logd_enabled, topic_enabled};
+use crate::levels::compiled_in;
+use crate::{log, uptime, Level, LineMeta, UptimeClock, TOPIC_GENERAL};
+
+/// Level of the enter/exit lines.
+pub const SCOPE_LEVEL: Level = Level::Debug;
+
+const NS_PER_MS: u64 = 1_000_000;
+
+/// Logs the exit line of a [`scope`] when dropped.
+#[must_use = "the scope ends (and logs its exit) when the guard is dropped"]
+pub struct ScopeGuard<'a> {
+    meta: Option<LineMeta<'a>>,
+    name: &'a str,
+    clock: UptimeClock,
+    start_ns: Option<u64>,
+}
+
+/// Logs `enter <name>` under `target` and times the scope until the guard drops.
+pub fn scope<'a>(target: &'a str, name: &'a str) -> ScopeGuard<'a> {
+    scope_with_clock(target, name, uptime::clock())
+}
+
+/// [`scope`] over an explicit nanosecond clock.
+pub fn scope_with_clock<'a>(target: &'a str, name: &'a str, clock: UptimeClock) -> ScopeGuard<'a> {
+    let meta = || LineMeta { level: SCOPE_LEVEL, target, topic: TOPIC_GENERAL };
+    if !enabled(&meta()) {
+        return ScopeGuard { meta: None, name, clock, start_ns: None };
+    }
+    let start_ns = clock();
+    log(meta(), |line| {
+        line.text("enter ");
+        line.text(name);
+    });
+    ScopeGuard { meta: Some(meta()), name, clock, start_ns }
+}
+
+/// Whether a record of `meta` reaches any sink (the checks `log` makes before rendering).
+fn enabled(meta: &LineMeta<'_>) -> bool {
+    let logd = cfg!(all(feature = "sink-logd", target_arch = "riscv64", target_os = "none"))
+        && logd_enabled(meta.level);
+    compiled_in(meta.level)
+        && topic_enabled(meta.topic)
+        && (level_enabled(meta.level, meta.target) || logd)
+}
+
+impl Drop for ScopeGuard<'_> {
+    fn drop(&mut self) {
+        let Some(meta) = self.meta.take() else {
+            return;
+        };
+        let elapsed_ms = match (self.start_ns, (self.clock)()) {
+            (Some(start), Some(end)) => Some(end.saturating_sub(start) / NS_PER_MS),
+            _ => None,
+        };
+        let name = self.name;
+        log(meta, |line| {
+            line.text("exit ");
+            line.text(name);
+            line.text(" (");
+            match elapsed_ms {
+                Some(ms) => line.dec(ms),
+                None => line.text("?"),
+            }
+            line.text("ms)");
+        });
+    }
+}
+
+#[cfg(all(test, feature = "sink-custom"))]
+mod tests {
+    extern crate std;
+
+    use core::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Mutex;
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::{clear_custom_sink, set_custom_sink, set_target_levels, CustomSink};
+
+    struct Capture(Mutex<Vec<u8>>);
+
+    impl CustomSink for Capture {
+        fn write_byte(&self, byte: u8) {
+            self.0.lock().unwrap_or_else(|e| e.into_inner()).push(byte);
+        }
+    }
+
+    static CAPTURE: Capture = Capture(Mutex::new(Vec::new()));
+    static NOW_NS: AtomicU64 = AtomicU64::new(0);
+    static READS: AtomicU64 = AtomicU64::new(0);
+
+    fn mock_clock() -> Option<u64> {
+        READS.fetch_add(1, Ordering::Relaxed);
+        Some(NOW_NS.load(Ordering::Relaxed))
+    }
+
+    fn captured(f: impl FnOnce()) -> Vec<u8> {
+        set_custom_sink(&CAPTURE);
+        f();
+        clear_custom_sink();
+        core::mem::take(&mut *CAPTURE.0.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    #[test]
+    fn logs_enter_and_exit_with_the_elapsed_milliseconds() {
+        let _serial = crate::dedup::tests::SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+        assert!(set_target_levels(&[("scope", Level::Debug)]));
+        // A `max-level-*` ceiling below SCOPE_LEVEL compiles the scope out: no lines at all.
+        let expect = |lines: &'static [u8]| if compiled_in(SCOPE_LEVEL) { lines } else { b"" };
+        NOW_NS.store(5_000_000_000, Ordering::Relaxed);
+        let out = captured(|| {
+            let _guard = scope_with_clock("scope", "replay", mock_clock);
+            NOW_NS.store(5_042_900_000, Ordering::Relaxed);
+        });
+        let boot = captured(|| {
+            let _guard = scope_with_clock("scope", "boot", || None);
+        });
+        set_target_levels(&[]);
+        assert_eq!(out, expect(b"[DEBUG scope] enter replay\n[DEBUG scope] exit replay (42ms)\n"));
+        assert_eq!(boot, expect(b"[DEBUG scope] enter boot\n[DEBUG scope] exit boot (?ms)\n"));
+    }
+
+    #[test]
+    fn test_reject_scope_below_the_console_floor() {
+        let _serial = crate::dedup::tests::SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+        // Off the OS there is no logd journal, so the Info console floor gates Debug out.
+        assert!(!level_enabled(SCOPE_LEVEL, "scope"));
+        let before = READS.load(Ordering::Relaxed);
+        let out = captured(|| {
+            let _guard = scope_with_clock("scope", "quiet", mock_clock);
+        });
+        assert!(out.is_empty());
+        assert_eq!(READS.load(Ordering::Relaxed), before);
+    }
+}
@@ -0,0 +1,55 @@
+// Copyright 2025 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: Topic bitmask tagging records for the runtime topic filter (`set_topic_mask`)
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: Covered through the config tests (topic mask)
+
+use core::ops::{BitOr, BitOrAssign};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Topic(u32);
+
+impl Topic {
+    pub const fn empty() -> Self {
+        Topic(0)
+    }
+
+    pub const fn bit(bit: u8) -> Self {
+        Topic(1u32 << (bit as u32))
+    }
+
+    pub const fn from_bits(bits: u32) -> Self {
+        Topic(bits)
+    }
+
+    pub const fn all() -> Self {
+        Topic(u32::MAX)
+    }
+
+    pub const fn bits(self) -> u32 {
+        self.0
+    }
+
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl BitOr for Topic {
+    type Output = Topic;
+
+    fn bitor(self, rhs: Topic) -> Topic {
+        Topic(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for Topic {
+    fn bitor_assign(&mut self, rhs: Topic) {
+        self.0 |= rhs.0;
+    }
+}
+
+pub const TOPIC_GENERAL: Topic = Topic::bit(0);
@@ -0,0 +1,163 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: Uptime field formatting (`LineBuilder::uptime`) over an injectable clock
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: 2 unit tests (known nanosecond value, clock-error fallback)
+//!
+//! [`LineBuilder::uptime`] stamps a field with the time since boot as `SSSS.mmm` (seconds
+//! zero-padded to four digits, more once uptime passes 9999 s; milliseconds truncated).
+//! Only integer division is used and the digits are rendered into a stack buffer. The clock
+//! is `nexus_abi::nsec` on a userspace OS build (`sink-userspace`); [`set_uptime_clock`]
+//! replaces it, which host tests and builds without a kernel clock rely on. When the clock
+//! fails, or none is available, the field reads `?.???`.
+
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use crate::LineBuilder;
+
+/// Nanoseconds since boot, or `None` when the clock cannot be read.
+pub type UptimeClock = fn() -> Option<u64>;
+
+/// Emitted in place of the uptime when the clock fails.
+const UNKNOWN: &[u8] = b"?.???";
+const NS_PER_MS: u64 = 1_000_000;
+const MS_PER_S: u64 = 1_000;
+/// 20 second digits (`u64::MAX` ns is ~1.8e10 s) plus `.mmm`.
+const MAX_LEN: usize = 24;
+
+// Spin-guarded slot, as for the custom sink: installs are rare and readers copy the fn out.
+struct Slot(UnsafeCell<Option<UptimeClock>>);
+
+// SAFETY: the cell is only read or written while `LOCK` is held.
+unsafe impl Sync for Slot {}
+
+static LOCK: AtomicBool = AtomicBool::new(false);
+static SLOT: Slot = Slot(UnsafeCell::new(None));
+
+fn with_slot<R>(f: impl FnOnce(&mut Option<UptimeClock>) -> R) -> R {
+    while LOCK.compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed).is_err() {
+        core::hint::spin_loop();
+    }
+    // SAFETY: `LOCK` is held, so this is the only live reference to the slot.
+    let out = f(unsafe { &mut *SLOT.0.get() });
+    LOCK.store(false, Ordering::Release);
+    out
+}
+
+/// Replaces the clock behind [`LineBuilder::uptime`]; `None` restores the default.
+pub fn set_uptime_clock(clock: Option<UptimeClock>) {
+    with_slot(|slot| *slot = clock);
+}
+
+fn default_clock() -> Option<u64> {
+    #[cfg(all(feature = "sink-userspace", target_arch = "riscv64", target_os = "none"))]
+    {
+        nexus_abi::nsec().ok()
+    }
+    #[cfg(not(all(feature = "sink-userspace", target_arch = "riscv64", target_os = "none")))]
+    {
+        None
+    }
+}
+
+/// Renders `ns` as `SSSS.mmm` into the tail of `buf`, returning the rendered bytes.
+fn format_uptime(ns: u64, buf: &mut [u8; MAX_LEN]) -> &[u8] {
+    let ms = ns / NS_PER_MS;
+    let (mut secs, mut frac) = (ms / MS_PER_S, ms % MS_PER_S);
+    let mut idx = MAX_LEN;
+    for _ in 0..3 {
+        idx -= 1;
+        buf[idx] = b'0' + (frac % 10) as u8;
+        frac /= 10;
+    }
+    idx -= 1;
+    buf[idx] = b'.';
+    let int_end = idx;
+    while secs != 0 || int_end - idx < 4 {
+        idx -= 1;
+        buf[idx] = b'0' + (secs % 10) as u8;
+        secs /= 10;
+    }
+    &buf[idx..]
+}
+
+/// The clock in force: the installed one, else the default.
+pub(crate) fn clock() -> UptimeClock {
+    with_slot(|slot| *slot).unwrap_or(default_clock)
+}
+
+impl LineBuilder<'_, '_> {
+    /// Writes the uptime as `SSSS.mmm` seconds, or `?.???` when the clock fails.
+    pub fn uptime(&mut self) {
+        match clock()() {
+            Some(ns) => {
+                let mut buf = [0u8; MAX_LEN];
+                self.sink.write_bytes(format_uptime(ns, &mut buf));
+            }
+            None => self.sink.write_bytes(UNKNOWN),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use core::fmt;
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::LineSink;
+
+    #[derive(Default)]
+    struct Capture {
+        bytes: Vec<u8>,
+    }
+
+    impl LineSink for Capture {
+        fn write_byte(&mut self, byte: u8) {
+            self.bytes.push(byte);
+        }
+
+        fn write_bytes(&mut self, bytes: &[u8]) {
+            self.bytes.extend_from_slice(bytes);
+        }
+    }
+
+    impl fmt::Write for Capture {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            self.write_bytes(s.as_bytes());
+            Ok(())
+        }
+    }
+
+    fn uptime_with(clock: Option<UptimeClock>) -> Vec<u8> {
+        set_uptime_clock(clock);
+        let mut capture = Capture::default();
+        LineBuilder { sink: &mut capture }.uptime();
+        capture.bytes
+    }
+
+    #[test]
+    fn formats_seconds_and_truncated_milliseconds() {
+        let mut buf = [0u8; MAX_LEN];
+        assert_eq!(format_uptime(12_345_678_901, &mut buf), b"0012.345");
+        assert_eq!(format_uptime(0, &mut buf), b"0000.000");
+        assert_eq!(format_uptime(999_999, &mut buf), b"0000.000");
+        assert_eq!(format_uptime(98_765_432_000_000, &mut buf), b"98765.432");
+        assert_eq!(format_uptime(u64::MAX, &mut buf), b"18446744073.709");
+    }
+
+    // The only test touching the global clock slot, so parallel tests cannot race on it.
+    #[test]
+    fn injected_clock_and_clock_errors() {
+        assert_eq!(uptime_with(Some(|| Some(12_345_678_901))), b"0012.345");
+        assert_eq!(uptime_with(Some(|| None)), b"?.???");
+        // Off the OS target the default clock has no time source either.
+        assert_eq!(uptime_with(None), b"?.???");
+    }
+}
@@ -11,7 +11,7 @@
 use crate::codec::Reader;
 
 /// Image magic `NXBI` ("NeXuS Bundle Image").
-const MAGIC: [u8; 4] = *b"NXBI";
+pub const MAGIC: [u8; 4] = *b"NXBI";
 /// Image format version.
 pub const VERSION: u8 = 1;
 
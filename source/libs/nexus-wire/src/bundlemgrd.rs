@@ -31,6 +31,23 @@ pub const STATUS_MALFORMED: u8 = 1;
 /// Operation is not supported by this build.
 pub const STATUS_UNSUPPORTED: u8 = 2;
 
+/// ROUTE_STATUS decision: policyd let bundlemgrd route to the target.
+pub const ROUTE_DECISION_ALLOWED: u8 = 0;
+/// ROUTE_STATUS decision: policyd denied the route.
+pub const ROUTE_DECISION_DENIED: u8 = 1;
+/// ROUTE_STATUS decision: no policy verdict (unknown target, malformed lookup, or an
+/// unrecognised routing status).
+pub const ROUTE_DECISION_UNKNOWN: u8 = 2;
+
+/// FETCH_IMAGE response sub-version carrying a trailing CRC32 over the image.
+///
+/// Requests stay at [`VERSION`]; only the response header names this version, so
+/// [`decode_fetch_image_rsp`] (v1, no CRC) rejects a checked frame outright.
+pub const FETCH_IMAGE_CHECKED_VERSION: u8 = 2;
+/// Bytes a checked FETCH_IMAGE response adds around the image
+/// (header, status, `len:u32le`, `crc32:u32le`).
+pub const FETCH_IMAGE_CHECKED_OVERHEAD: usize = 13;
+
 /// Byte offset where LIST_APPS response entries begin (after status + count).
 pub const LIST_APPS_BODY_OFFSET: usize = 7;
 
@@ -76,6 +93,84 @@ pub fn decode_list_apps_header(frame: &[u8]) -> Option<(u8, u16)> {
     Some((status, count))
 }
 
+/// Decodes a ROUTE_STATUS response → `(status, policy_decision)`.
+///
+/// Response frame:
+/// `[B, N, ver, OP_ROUTE_STATUS|0x80, status:u8, route_status:u8, _reserved:u8, _reserved:u8]`
+/// where `route_status` is the routing-protocol status bundlemgrd got back for the target;
+/// it is folded into one of the `ROUTE_DECISION_*` values.
+pub fn decode_route_status_rsp(frame: &[u8]) -> Option<(u8, u8)> {
+    let mut r = crate::codec::Reader::new(frame);
+    crate::codec::check_hdr(&mut r, MAGIC0, MAGIC1, VERSION, OP_ROUTE_STATUS | 0x80)?;
+    let status = r.take_u8()?;
+    let route_status = r.take_u8()?;
+    r.skip(2)?;
+    r.finish_exact()?;
+    Some((status, route_decision(route_status)))
+}
+
+/// Maps a routing-protocol status onto a `ROUTE_DECISION_*` value.
+pub fn route_decision(route_status: u8) -> u8 {
+    match route_status {
+        crate::routing::STATUS_OK => ROUTE_DECISION_ALLOWED,
+        crate::routing::STATUS_DENIED => ROUTE_DECISION_DENIED,
+        _ => ROUTE_DECISION_UNKNOWN,
+    }
+}
+
+/// Encodes a checked FETCH_IMAGE response; returns the frame length.
+///
+/// Frame: `[B, N, FETCH_IMAGE_CHECKED_VERSION, OP_FETCH_IMAGE|0x80, status:u8,
+/// len:u32le, image..., crc32:u32le]`, the CRC32 (IEEE) covering `image` only.
+/// `None` if `out` is shorter than `image.len() + FETCH_IMAGE_CHECKED_OVERHEAD`.
+pub fn encode_fetch_image_rsp_checked(status: u8, image: &[u8], out: &mut [u8]) -> Option<usize> {
+    let mut w = crate::codec::Writer::new(out);
+    crate::codec::put_hdr(
+        &mut w,
+        MAGIC0,
+        MAGIC1,
+        FETCH_IMAGE_CHECKED_VERSION,
+        OP_FETCH_IMAGE | 0x80,
+    )?;
+    w.put_u8(status)?;
+    w.put_len32_bytes(image, 0, u32::MAX as usize)?;
+    w.put_u32le(crc32(image))?;
+    Some(w.pos())
+}
+
+/// Decodes a checked FETCH_IMAGE response → `(status, image_bytes)`.
+///
+/// `None` on a malformed or truncated frame, a v1 (unchecked) frame, or a CRC
+/// mismatch — a corrupted image never reaches the caller.
+pub fn decode_fetch_image_rsp_checked(frame: &[u8]) -> Option<(u8, &[u8])> {
+    let mut r = crate::codec::Reader::new(frame);
+    crate::codec::check_hdr(
+        &mut r,
+        MAGIC0,
+        MAGIC1,
+        FETCH_IMAGE_CHECKED_VERSION,
+        OP_FETCH_IMAGE | 0x80,
+    )?;
+    let status = r.take_u8()?;
+    let image = r.take_len32_bytes(0, u32::MAX as usize)?;
+    let crc = r.take_u32le()?;
+    r.finish_exact()?;
+    (crc == crc32(image)).then_some((status, image))
+}
+
+/// CRC-32 (IEEE 802.3, reflected, poly `0xEDB88320`).
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc: u32 = 0xFFFF_FFFF;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
 crate::frames! {
     protocol(magic0 = MAGIC0, magic1 = MAGIC1, version = VERSION);
 
@@ -97,6 +192,17 @@ crate::frames! {
         slot: u8,
         _r: pad(2),
     }
+    /// ROUTE_STATUS request: `[B, N, ver, OP_ROUTE_STATUS, name_len:u8, target...]`.
+    request encode_route_status / decode_route_status (op = OP_ROUTE_STATUS) {
+        target: bytes8(min = 1, max = crate::routing::MAX_SERVICE_NAME_LEN),
+    }
+    /// ROUTE_STATUS response carrying the raw routing status (two reserved trailing bytes);
+    /// read it back with [`decode_route_status_rsp`].
+    reply fixed encode encode_route_status_rsp (op = OP_ROUTE_STATUS) {
+        status: u8,
+        route_status: u8,
+        _r: pad(2),
+    }
     /// GET_PAYLOAD request: `[B, N, ver, OP_GET_PAYLOAD, id_len:u8, id...]`.
     request encode_get_payload / decode_get_payload (op = OP_GET_PAYLOAD) {
         app_id: bytes8(min = 1, max = 48),
@@ -223,6 +329,54 @@ mod tests {
         assert_eq!(decode_set_active_slot_rsp(&rsp), Some((STATUS_OK, 1)));
     }
 
+    #[test]
+    fn route_status_round_trip_per_decision() {
+        let mut buf = [0u8; 64];
+        let n = encode_route_status(b"execd", &mut buf).unwrap();
+        assert_eq!(&buf[..n], &[b'B', b'N', 1, OP_ROUTE_STATUS, 5, b'e', b'x', b'e', b'c', b'd']);
+        assert_eq!(decode_route_status(&buf[..n]), Some(&b"execd"[..]));
+
+        for (route_status, decision) in [
+            (crate::routing::STATUS_OK, ROUTE_DECISION_ALLOWED),
+            (crate::routing::STATUS_DENIED, ROUTE_DECISION_DENIED),
+            (crate::routing::STATUS_NOT_FOUND, ROUTE_DECISION_UNKNOWN),
+            (crate::routing::STATUS_MALFORMED, ROUTE_DECISION_UNKNOWN),
+            (0xFF, ROUTE_DECISION_UNKNOWN),
+        ] {
+            let rsp = encode_route_status_rsp(STATUS_OK, route_status);
+            assert_eq!(rsp, [b'B', b'N', 1, OP_ROUTE_STATUS | 0x80, STATUS_OK, route_status, 0, 0]);
+            assert_eq!(decode_route_status_rsp(&rsp), Some((STATUS_OK, decision)));
+        }
+        let rsp = encode_route_status_rsp(STATUS_MALFORMED, 0);
+        assert_eq!(decode_route_status_rsp(&rsp), Some((STATUS_MALFORMED, ROUTE_DECISION_ALLOWED)));
+    }
+
+    #[test]
+    fn test_reject_route_status_malformed_frames() {
+        let mut buf = [0u8; 64];
+        // Target names are bounded like routing names.
+        let long = [b'a'; crate::routing::MAX_SERVICE_NAME_LEN + 1];
+        assert!(encode_route_status(&long, &mut buf).is_none());
+        assert!(encode_route_status(b"", &mut buf).is_none());
+        assert!(
+            encode_route_status(&long[..crate::routing::MAX_SERVICE_NAME_LEN], &mut buf).is_some()
+        );
+
+        let rsp = encode_route_status_rsp(STATUS_OK, crate::routing::STATUS_DENIED);
+        for len in 0..rsp.len() {
+            assert_eq!(decode_route_status_rsp(&rsp[..len]), None);
+        }
+        let mut long_rsp = [0u8; 9];
+        long_rsp[..8].copy_from_slice(&rsp);
+        assert_eq!(decode_route_status_rsp(&long_rsp), None);
+        let mut wrong_op = rsp;
+        wrong_op[3] = OP_LIST | 0x80;
+        assert_eq!(decode_route_status_rsp(&wrong_op), None);
+        let mut wrong_magic = rsp;
+        wrong_magic[0] = b'X';
+        assert_eq!(decode_route_status_rsp(&wrong_magic), None);
+    }
+
     #[test]
     fn fetch_image_rsp_roundtrip() {
         let rsp = [b'B', b'N', 1, OP_FETCH_IMAGE | 0x80, STATUS_OK, 2, 0, 0, 0, b'h', b'i'];
@@ -230,4 +384,65 @@ mod tests {
         // Length mismatch rejected.
         assert_eq!(decode_fetch_image_rsp(&rsp[..rsp.len() - 1]), None);
     }
+
+    #[test]
+    fn fetch_image_rsp_checked_golden() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        let mut buf = [0u8; 32];
+        let n = encode_fetch_image_rsp_checked(STATUS_OK, b"hi", &mut buf).unwrap();
+        assert_eq!(n, 2 + FETCH_IMAGE_CHECKED_OVERHEAD);
+        let crc = crc32(b"hi").to_le_bytes();
+        assert_eq!(
+            &buf[..n],
+            &[
+                b'B',
+                b'N',
+                2,
+                OP_FETCH_IMAGE | 0x80,
+                STATUS_OK,
+                2,
+                0,
+                0,
+                0,
+                b'h',
+                b'i',
+                crc[0],
+                crc[1],
+                crc[2],
+                crc[3]
+            ]
+        );
+        assert_eq!(decode_fetch_image_rsp_checked(&buf[..n]), Some((STATUS_OK, &b"hi"[..])));
+        // The v1 decoder does not mistake a checked frame for an unchecked one.
+        assert_eq!(decode_fetch_image_rsp(&buf[..n]), None);
+        assert_eq!(encode_fetch_image_rsp_checked(STATUS_OK, b"hi", &mut buf[..n - 1]), None);
+    }
+
+    #[test]
+    fn test_reject_fetch_image_rsp_corrupted_payload() {
+        let mut buf = [0u8; 32];
+        let n = encode_fetch_image_rsp_checked(STATUS_OK, b"image", &mut buf).unwrap();
+        for i in 9..9 + 5 {
+            let mut bad = buf;
+            bad[i] ^= 0x01;
+            assert_eq!(decode_fetch_image_rsp_checked(&bad[..n]), None);
+        }
+        let mut bad = buf;
+        bad[n - 1] ^= 0x80;
+        assert_eq!(decode_fetch_image_rsp_checked(&bad[..n]), None);
+    }
+
+    #[test]
+    fn test_reject_fetch_image_rsp_truncated() {
+        let mut buf = [0u8; 32];
+        let n = encode_fetch_image_rsp_checked(STATUS_OK, b"image", &mut buf).unwrap();
+        for len in 0..n {
+            assert_eq!(decode_fetch_image_rsp_checked(&buf[..len]), None);
+        }
+        // Trailing bytes are rejected too (exact length).
+        assert_eq!(decode_fetch_image_rsp_checked(&buf[..n + 1]), None);
+        // An unchecked v1 frame carries no CRC and is rejected.
+        let v1 = [b'B', b'N', 1, OP_FETCH_IMAGE | 0x80, STATUS_OK, 0, 0, 0, 0];
+        assert_eq!(decode_fetch_image_rsp_checked(&v1), None);
+    }
 }
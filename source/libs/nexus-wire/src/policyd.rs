@@ -4,7 +4,8 @@
 //! Policyd service frames (v1/v2/v3) shared between init-lite, policyd, and
 //! clients. v1 is the legacy bring-up wire (no correlation), v2 adds
 //! nonce-correlated requests/responses (RFC-0019), v3 switches
-//! requester/target to stable u64 service ids.
+//! requester/target to stable u64 service ids; its response may carry a
+//! [`ReasonCode`] in the byte v2 reserves.
 
 /// First magic byte (`'P'`) — private: only touched via the codecs.
 const MAGIC0: u8 = b'P';
@@ -28,6 +29,8 @@ pub const OP_EXEC: u8 = 3;
 pub const OP_CHECK_CAP: u8 = 4;
 /// ABI syscall profile fetch opcode (nonce-correlated, v2).
 pub const OP_ABI_PROFILE_GET: u8 = 6;
+/// Batched exec authorization opcode (v3, one status per image id).
+pub const OP_EXEC_BATCH: u8 = 7;
 
 /// Status: allowed.
 pub const STATUS_ALLOW: u8 = 0;
@@ -42,9 +45,41 @@ pub const STATUS_UNSUPPORTED: u8 = 3;
 /// the wire bound (`nexus-abi`'s `abi_filter` decoder re-sources this value).
 pub const MAX_PROFILE_BYTES: usize = 512;
 
+/// Maximum image ids checked by one EXEC_BATCH request (and statuses returned).
+pub const MAX_EXEC_BATCH: usize = 8;
+
 /// Nonce used to correlate requests and responses (v2).
 pub type Nonce = u32;
 
+/// Why a request was denied, carried in the v3 response byte that v2 reserves.
+///
+/// `Unknown` (0) is what a responder that predates reason codes sends, so every
+/// existing v3 frame decodes as `Unknown`. Meaningful only with [`STATUS_DENY`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ReasonCode {
+    /// No reason given (legacy responders, or non-deny statuses).
+    Unknown = 0,
+    /// No route is configured between requester and target.
+    NoRoute = 1,
+    /// A privacy rule forbids the request.
+    PrivacyRule = 2,
+    /// The requester exhausted a quota.
+    Quota = 3,
+}
+
+impl ReasonCode {
+    /// Maps a wire byte to a reason; codes this build does not know are `Unknown`.
+    pub const fn from_u8(raw: u8) -> Self {
+        match raw {
+            1 => Self::NoRoute,
+            2 => Self::PrivacyRule,
+            3 => Self::Quota,
+            _ => Self::Unknown,
+        }
+    }
+}
+
 crate::frames! {
     protocol(magic0 = MAGIC0, magic1 = MAGIC1, version = VERSION_V2);
 
@@ -76,6 +111,19 @@ crate::frames! {
         requester_id: u64le,
         image_id: u8,
     }
+    /// v3 EXEC_BATCH request:
+    /// `[P,O,ver=3,OP_EXEC_BATCH, nonce:u32le, requester_id:u64le, count:u8, image_id...]`.
+    request encode_exec_batch_v3 / decode_exec_batch_v3 (op = OP_EXEC_BATCH, version = VERSION_V3) {
+        nonce: u32le,
+        requester_id: u64le,
+        image_ids: bytes8(min = 1, max = MAX_EXEC_BATCH),
+    }
+    /// v3 EXEC_BATCH response, one status per requested image id in order:
+    /// `[P,O,ver=3,OP_EXEC_BATCH|0x80, nonce:u32le, count:u8, status...]`.
+    reply encode_exec_batch_rsp_v3 / decode_exec_batch_rsp_v3 (op = OP_EXEC_BATCH, version = VERSION_V3) {
+        nonce: u32le,
+        statuses: bytes8(min = 1, max = MAX_EXEC_BATCH),
+    }
     /// v2 ABI profile fetch request:
     /// `[P,O,ver=2,OP_ABI_PROFILE_GET, nonce:u32le, subject_id:u64le]`.
     #[must_use = "encoded/decoded profile requests must be checked before use"]
@@ -106,8 +154,18 @@ crate::frames! {
     }
 }
 
-/// Decodes a v2/v3 response and returns (ver, op, nonce, status).
-pub fn decode_rsp_v2_or_v3(frame: &[u8]) -> Option<(u8, u8, Nonce, u8)> {
+/// v3 response carrying a [`ReasonCode`]:
+/// `[P,O,ver=3,op|0x80, nonce:u32le, status:u8, reason:u8]`.
+pub fn encode_rsp_v3_reason(op: u8, nonce: Nonce, status: u8, reason: ReasonCode) -> [u8; 10] {
+    let mut frame = encode_rsp_v3(op, nonce, status);
+    frame[9] = reason as u8;
+    frame
+}
+
+/// Decodes a v2/v3 response and returns (ver, op, nonce, status, reason).
+///
+/// v2 frames always report [`ReasonCode::Unknown`]: their last byte stays reserved.
+pub fn decode_rsp_v2_or_v3_reason(frame: &[u8]) -> Option<(u8, u8, Nonce, u8, ReasonCode)> {
     let mut r = crate::codec::Reader::new(frame);
     r.expect_u8(MAGIC0)?;
     r.expect_u8(MAGIC1)?;
@@ -121,9 +179,16 @@ pub fn decode_rsp_v2_or_v3(frame: &[u8]) -> Option<(u8, u8, Nonce, u8)> {
     }
     let nonce = r.take_u32le()?;
     let status = r.take_u8()?;
-    r.skip(1)?;
+    let reason = r.take_u8()?;
     r.finish_exact()?;
-    Some((ver, op_byte & !0x80, nonce, status))
+    let reason = if ver == VERSION_V3 { ReasonCode::from_u8(reason) } else { ReasonCode::Unknown };
+    Some((ver, op_byte & !0x80, nonce, status, reason))
+}
+
+/// Decodes a v2/v3 response and returns (ver, op, nonce, status), ignoring any reason.
+pub fn decode_rsp_v2_or_v3(frame: &[u8]) -> Option<(u8, u8, Nonce, u8)> {
+    let (ver, op, nonce, status, _reason) = decode_rsp_v2_or_v3_reason(frame)?;
+    Some((ver, op, nonce, status))
 }
 
 /// Decodes a v2 response and returns (op, nonce, status).
@@ -174,6 +239,49 @@ mod tests {
         assert_eq!(img, 9);
     }
 
+    #[test]
+    fn exec_batch_v3_roundtrip() {
+        let mut buf = [0u8; 32];
+        let n =
+            encode_exec_batch_v3(0x01020304, 0x1122_3344_5566_7788, &[2, 5, 9], &mut buf).unwrap();
+        const GOLDEN: [u8; 20] = [
+            b'P', b'O', 3, 7, // magic + ver + OP_EXEC_BATCH
+            0x04, 0x03, 0x02, 0x01, // nonce LE
+            0x88, 0x77, 0x66, 0x55, 0x44, 0x33, 0x22, 0x11, // requester_id LE
+            3, 2, 5, 9, // count + image ids
+        ];
+        assert_eq!(&buf[..n], &GOLDEN);
+        let (nonce, req, images) = decode_exec_batch_v3(&buf[..n]).unwrap();
+        assert_eq!((nonce, req, images), (0x01020304, 0x1122_3344_5566_7788, &[2u8, 5, 9][..]));
+
+        let statuses = [STATUS_ALLOW, STATUS_DENY, STATUS_ALLOW];
+        let m = encode_exec_batch_rsp_v3(0x01020304, &statuses, &mut buf).unwrap();
+        assert_eq!(&buf[..4], &[b'P', b'O', 3, 7 | 0x80]);
+        let (rsp_nonce, got) = decode_exec_batch_rsp_v3(&buf[..m]).unwrap();
+        assert_eq!(rsp_nonce, 0x01020304);
+        assert_eq!(got, &statuses);
+        // Batch replies are not single-status replies.
+        assert_eq!(decode_rsp_v2_or_v3(&buf[..m]), None);
+    }
+
+    #[test]
+    fn test_reject_exec_batch_over_limit() {
+        let mut buf = [0u8; 64];
+        let images = [1u8; MAX_EXEC_BATCH + 1];
+        assert_eq!(encode_exec_batch_v3(1, 2, &images, &mut buf), None);
+        assert_eq!(encode_exec_batch_v3(1, 2, &[], &mut buf), None);
+        assert_eq!(encode_exec_batch_rsp_v3(1, &images, &mut buf), None);
+
+        // A full batch encodes; bumping its count past the bound fails closed.
+        let n = encode_exec_batch_v3(1, 2, &images[..MAX_EXEC_BATCH], &mut buf).unwrap();
+        crate::codec::testing::assert_reject_matrix(&buf[..n], 4, &|f| {
+            decode_exec_batch_v3(f).is_some()
+        });
+        buf[16] = (MAX_EXEC_BATCH + 1) as u8;
+        buf[n] = 1;
+        assert_eq!(decode_exec_batch_v3(&buf[..n + 1]), None);
+    }
+
     #[test]
     fn rsp_v3_golden() {
         let frame = encode_rsp_v3(OP_ROUTE, 0xAABBCCDD, STATUS_DENY);
@@ -197,6 +305,48 @@ mod tests {
         assert_eq!(status, STATUS_DENY);
     }
 
+    #[test]
+    fn rsp_v3_reason_roundtrip() {
+        for reason in
+            [ReasonCode::Unknown, ReasonCode::NoRoute, ReasonCode::PrivacyRule, ReasonCode::Quota]
+        {
+            let frame = encode_rsp_v3_reason(OP_EXEC, 0x0102_0304, STATUS_DENY, reason);
+            assert_eq!(frame[9], reason as u8);
+            assert_eq!(
+                decode_rsp_v2_or_v3_reason(&frame),
+                Some((VERSION_V3, OP_EXEC, 0x0102_0304, STATUS_DENY, reason))
+            );
+            // Reason-unaware callers still decode the frame.
+            assert_eq!(
+                decode_rsp_v2_or_v3(&frame),
+                Some((VERSION_V3, OP_EXEC, 0x0102_0304, STATUS_DENY))
+            );
+        }
+        // A code from a newer responder degrades to Unknown.
+        let mut frame = encode_rsp_v3_reason(OP_ROUTE, 1, STATUS_DENY, ReasonCode::Quota);
+        frame[9] = 0xEE;
+        assert_eq!(decode_rsp_v2_or_v3_reason(&frame).unwrap().4, ReasonCode::Unknown);
+    }
+
+    #[test]
+    fn rsp_without_reason_decodes_as_unknown() {
+        // Pre-reason v3 frames carry a zero byte; it must read as Unknown.
+        let legacy = encode_rsp_v3(OP_ROUTE, 0xAABBCCDD, STATUS_DENY);
+        assert_eq!(
+            legacy,
+            encode_rsp_v3_reason(OP_ROUTE, 0xAABBCCDD, STATUS_DENY, ReasonCode::Unknown)
+        );
+        assert_eq!(
+            decode_rsp_v2_or_v3_reason(&legacy),
+            Some((VERSION_V3, OP_ROUTE, 0xAABBCCDD, STATUS_DENY, ReasonCode::Unknown))
+        );
+        // v2 keeps its byte reserved, whatever its value.
+        let mut v2 = encode_rsp_v2(OP_ROUTE, 7, STATUS_DENY);
+        v2[9] = ReasonCode::Quota as u8;
+        assert_eq!(decode_rsp_v2_or_v3_reason(&v2).unwrap().4, ReasonCode::Unknown);
+        assert!(decode_rsp_v2_or_v3_reason(&v2[..9]).is_none());
+    }
+
     #[test]
     fn route_v2_roundtrip() {
         let mut buf = [0u8; 128];
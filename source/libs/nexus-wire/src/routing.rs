@@ -14,6 +14,8 @@ pub const VERSION: u8 = 1;
 pub const OP_ROUTE_GET: u8 = 0x40;
 /// Route response opcode.
 pub const OP_ROUTE_RSP: u8 = 0x41;
+/// Extended route response opcode (adds the target's `service_id`).
+pub const OP_ROUTE_RSP_V2: u8 = 0x42;
 
 /// Status code returned in ROUTE_RSP.
 pub const STATUS_OK: u8 = 0;
@@ -41,6 +43,15 @@ crate::frames! {
         send_slot: u32le,
         recv_slot: u32le,
     }
+    /// ROUTE_RSP_V2 response: `[R, T, ver, OP_ROUTE_RSP_V2, status, send_slot:u32le,
+    /// recv_slot:u32le, service_id:u64le]`. `service_id` is the id the kernel assigned the
+    /// target (`BootstrapInfo.service_id`); it is `0` unless `status` is [`STATUS_OK`].
+    request fixed encode_route_rsp_v2 / decode_route_rsp_v2 (op = OP_ROUTE_RSP_V2) {
+        status: u8,
+        send_slot: u32le,
+        recv_slot: u32le,
+        service_id: u64le,
+    }
 }
 
 #[cfg(test)]
@@ -89,6 +100,19 @@ mod tests {
         assert_eq!(recv, 34);
     }
 
+    #[test]
+    fn route_rsp_v2_golden() {
+        let frame = encode_route_rsp_v2(STATUS_OK, 7, 8, 0x0102_0304_0506_0708);
+        const GOLDEN: [u8; 21] = [
+            b'R', b'T', 1, 0x42, 0, // status OK
+            7, 0, 0, 0, // send_slot LE
+            8, 0, 0, 0, // recv_slot LE
+            0x08, 0x07, 0x06, 0x05, 0x04, 0x03, 0x02, 0x01, // service_id LE
+        ];
+        assert_eq!(frame, GOLDEN);
+        assert_eq!(decode_route_rsp_v2(&frame), Some((STATUS_OK, 7, 8, 0x0102_0304_0506_0708)));
+    }
+
     #[test]
     fn reject_truncation_and_mutation_matrix() {
         let mut buf = [0u8; 32];
@@ -98,5 +122,10 @@ mod tests {
         });
         let rsp = encode_route_rsp(STATUS_OK, 1, 2);
         crate::codec::testing::assert_reject_matrix(&rsp, 4, &|f| decode_route_rsp(f).is_some());
+        let rsp = encode_route_rsp_v2(STATUS_OK, 1, 2, 3);
+        crate::codec::testing::assert_reject_matrix(&rsp, 4, &|f| decode_route_rsp_v2(f).is_some());
+        // v1 and v2 responses are not interchangeable.
+        assert!(decode_route_rsp(&rsp).is_none());
+        assert!(decode_route_rsp_v2(&encode_route_rsp(STATUS_OK, 1, 2)).is_none());
     }
 }
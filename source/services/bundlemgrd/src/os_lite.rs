@@ -76,6 +76,7 @@ const VERSION: u8 = nexus_abi::bundlemgrd::VERSION;
 const OP_LIST: u8 = nexus_abi::bundlemgrd::OP_LIST;
 const OP_ROUTE_STATUS: u8 = nexus_abi::bundlemgrd::OP_ROUTE_STATUS;
 const OP_FETCH_IMAGE: u8 = nexus_abi::bundlemgrd::OP_FETCH_IMAGE;
+const FETCH_IMAGE_CHECKED_OVERHEAD: usize = nexus_abi::bundlemgrd::FETCH_IMAGE_CHECKED_OVERHEAD;
 const OP_SET_ACTIVE_SLOT: u8 = nexus_abi::bundlemgrd::OP_SET_ACTIVE_SLOT;
 const OP_LIST_APPS: u8 = nexus_abi::bundlemgrd::OP_LIST_APPS;
 const OP_LOG_PROBE: u8 = 0x7f;
@@ -386,7 +387,8 @@ fn handle_frame(frame: &[u8]) -> [u8; 8] {
     //   [B, N, ver, OP_ROUTE_STATUS|0x80, status:u8, route_status:u8, _reserved:u8, _reserved:u8]
     //
     // FETCH_IMAGE request: [B, N, ver, OP_FETCH_IMAGE]
-    // FETCH_IMAGE response: [B, N, ver, OP_FETCH_IMAGE|0x80, status:u8, len:u32le, bytes...]
+    // FETCH_IMAGE response (handle_frame_vec, FETCH_IMAGE_CHECKED_VERSION):
+    //   [B, N, 2, OP_FETCH_IMAGE|0x80, status:u8, len:u32le, bytes..., crc32:u32le]
     //
     // SET_ACTIVE_SLOT request: [B, N, ver, OP_SET_ACTIVE_SLOT, slot:u8]
     // SET_ACTIVE_SLOT response:
@@ -412,21 +414,17 @@ fn handle_frame(frame: &[u8]) -> [u8; 8] {
             rsp(op, STATUS_OK, 1)
         }
         OP_ROUTE_STATUS => {
-            if frame.len() < 5 {
+            let Some(name) = nexus_abi::bundlemgrd::decode_route_status(frame) else {
                 return rsp2(op, STATUS_MALFORMED, 0);
-            }
-            let n = frame[4] as usize;
-            if n == 0 || n > nexus_abi::routing::MAX_SERVICE_NAME_LEN || frame.len() != 5 + n {
-                return rsp2(op, STATUS_MALFORMED, 0);
-            }
-            let name = core::str::from_utf8(&frame[5..]).unwrap_or("");
+            };
+            let name = core::str::from_utf8(name).unwrap_or("");
             let code = route_status(name).unwrap_or(nexus_abi::routing::STATUS_MALFORMED);
             if code == nexus_abi::routing::STATUS_OK {
                 metrics_counter_inc_best_effort("bundlemgrd.route_status.ok");
             } else {
                 metrics_counter_inc_best_effort("bundlemgrd.route_status.fail");
             }
-            rsp2(op, STATUS_OK, code)
+            nexus_abi::bundlemgrd::encode_route_status_rsp(STATUS_OK, code)
         }
         OP_FETCH_IMAGE => {
             if frame.len() != 4 {
@@ -478,30 +476,19 @@ fn handle_frame_vec(frame: &[u8]) -> alloc::vec::Vec<u8> {
             build_prop.push(slot);
             build_prop.push(b'\n');
             // Encode image inline (small and deterministic).
-            let mut img = Vec::new();
-            img.extend_from_slice(b"NXBI");
-            img.push(1); // VERSION
-            img.extend_from_slice(&1u16.to_le_bytes()); // entry_count
-                                                        // entry:
-            img.push(6); // "system"
-            img.extend_from_slice(b"system");
-            img.push(version.len() as u8);
-            img.extend_from_slice(version);
-            let path = b"build.prop";
-            img.extend_from_slice(&(path.len() as u16).to_le_bytes());
-            img.extend_from_slice(path);
-            img.extend_from_slice(&0u16.to_le_bytes()); // KIND_FILE
-            img.extend_from_slice(&(build_prop.len() as u32).to_le_bytes());
-            img.extend_from_slice(&build_prop);
-
-            let mut out = Vec::with_capacity(9 + img.len());
-            out.push(MAGIC0);
-            out.push(MAGIC1);
-            out.push(VERSION);
-            out.push(OP_FETCH_IMAGE | 0x80);
-            out.push(STATUS_OK);
-            out.extend_from_slice(&(img.len() as u32).to_le_bytes());
-            out.extend_from_slice(&img);
+            let mut image = nexus_abi::bundleimg::Builder::new();
+            if image.add_file(b"system", version, b"build.prop", &build_prop).is_err() {
+                return rsp(OP_FETCH_IMAGE, STATUS_MALFORMED, 0).to_vec();
+            }
+            let img = image.finish();
+
+            let mut out = alloc::vec![0u8; img.len() + FETCH_IMAGE_CHECKED_OVERHEAD];
+            let Some(n) =
+                nexus_abi::bundlemgrd::encode_fetch_image_rsp_checked(STATUS_OK, &img, &mut out)
+            else {
+                return rsp(OP_FETCH_IMAGE, STATUS_MALFORMED, 0).to_vec();
+            };
+            out.truncate(n);
             metrics_counter_inc_best_effort("bundlemgrd.fetch.ok");
             metrics_hist_observe_best_effort("bundlemgrd.fetch.image_bytes", img.len() as u64);
             return out;
@@ -793,15 +780,10 @@ mod tests {
         // Fetch image and verify build.prop reflects slot b.
         let img = handle_frame_vec(&build_req(OP_FETCH_IMAGE, &[]));
         assert!(img.len() > 16);
-        assert_eq!(img[0], MAGIC0);
-        assert_eq!(img[1], MAGIC1);
-        assert_eq!(img[2], VERSION);
-        assert_eq!(img[3], OP_FETCH_IMAGE | 0x80);
-        assert_eq!(img[4], STATUS_OK);
-        let n = u32::from_le_bytes(img[5..9].try_into().unwrap()) as usize;
-        assert_eq!(img.len(), 9 + n);
-
-        let payload = &img[9..];
+        assert_eq!(img[2], nexus_abi::bundlemgrd::FETCH_IMAGE_CHECKED_VERSION);
+        let (status, payload) =
+            nexus_abi::bundlemgrd::decode_fetch_image_rsp_checked(&img).expect("checked frame");
+        assert_eq!(status, STATUS_OK);
         assert!(
             payload.windows(b"ro.nexus.slot=b\n".len()).any(|w| w == b"ro.nexus.slot=b\n"),
             "expected build.prop to include ro.nexus.slot=b"
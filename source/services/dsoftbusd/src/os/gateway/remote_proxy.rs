@@ -661,19 +661,19 @@ fn encode_statefs_request_with_nonce(
     nonce: u64,
 ) -> core::result::Result<Vec<u8>, ()> {
     let mut frame = match req {
-        sfp::Request::Put { key, value } => sfp::encode_put_request(key, value).map_err(|_| ())?,
-        sfp::Request::Get { key } => {
-            sfp::encode_key_only_request(sfp::OP_GET, key).map_err(|_| ())?
+        sfp::Request::Put { key, value, durable } => {
+            let flags = if *durable { sfp::PUT_FLAG_DURABLE } else { 0 };
+            sfp::encode_put_request_with_flags(key, value, flags)
         }
-        sfp::Request::Delete { key } => {
-            sfp::encode_key_only_request(sfp::OP_DEL, key).map_err(|_| ())?
-        }
-        sfp::Request::List { prefix, limit } => {
-            sfp::encode_list_request(prefix, *limit).map_err(|_| ())?
-        }
-        sfp::Request::Sync => sfp::encode_sync_request(),
-        sfp::Request::Reopen => sfp::encode_reopen_request(),
-    };
+        sfp::Request::Get { key } => sfp::encode_key_only_request(sfp::OP_GET, key),
+        sfp::Request::Delete { key } => sfp::encode_key_only_request(sfp::OP_DEL, key),
+        sfp::Request::List { prefix, limit } => sfp::encode_list_request(prefix, *limit),
+        sfp::Request::Sync => Ok(sfp::encode_sync_request()),
+        // Reopen / Stats / MGet / Verify / Barrier / Validate / Changes / PutVersioned are not
+        // forwarded by the gateway.
+        _ => return Err(()),
+    }
+    .map_err(|_| ())?;
     if frame.len() < 4 || frame[0] != sfp::MAGIC0 || frame[1] != sfp::MAGIC1 {
         return Err(());
     }
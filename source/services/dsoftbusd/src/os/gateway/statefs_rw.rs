@@ -137,7 +137,10 @@ pub(crate) fn validate_response_shape(
 
 #[must_use]
 pub(crate) fn is_mutating_request(request: &sfp::Request<'_>) -> bool {
-    matches!(request, sfp::Request::Put { .. } | sfp::Request::Delete { .. })
+    matches!(
+        request,
+        sfp::Request::Put { .. } | sfp::Request::PutVersioned { .. } | sfp::Request::Delete { .. }
+    )
 }
 
 #[must_use]
@@ -149,6 +152,13 @@ pub(crate) fn request_op(request: &sfp::Request<'_>) -> u8 {
         sfp::Request::List { .. } => sfp::OP_LIST,
         sfp::Request::Sync => sfp::OP_SYNC,
         sfp::Request::Reopen => sfp::OP_REOPEN,
+        sfp::Request::Stats => sfp::OP_STATS,
+        sfp::Request::GetMany { .. } => sfp::OP_MGET,
+        sfp::Request::Verify => sfp::OP_VERIFY,
+        sfp::Request::Barrier => sfp::OP_BARRIER,
+        sfp::Request::Validate { .. } => sfp::OP_VALIDATE,
+        sfp::Request::Changes { .. } => sfp::OP_CHANGES,
+        sfp::Request::PutVersioned { .. } => sfp::OP_PUT_VERSIONED,
     }
 }
 
@@ -229,7 +239,7 @@ fn validate_request_bounds_and_acl(
     request: &sfp::Request<'_>,
 ) -> core::result::Result<(), RejectReason> {
     match request {
-        sfp::Request::Put { key, value } => {
+        sfp::Request::Put { key, value, .. } => {
             validate_key(key, true)?;
             if value.is_empty() || value.len() > RS_MAX_VALUE_LEN {
                 return Err(RejectReason::Oversized);
@@ -245,7 +255,15 @@ fn validate_request_bounds_and_acl(
             }
         }
         sfp::Request::Sync => {}
-        sfp::Request::Reopen => return Err(RejectReason::BadRequest),
+        sfp::Request::Reopen
+        | sfp::Request::Stats
+        | sfp::Request::GetMany { .. }
+        | sfp::Request::Verify
+        | sfp::Request::Barrier
+        | sfp::Request::Validate { .. }
+        | sfp::Request::Changes { .. } => return Err(RejectReason::BadRequest),
+        // Remote writes stay last-writer-wins `Put`s; generations are not exposed to peers.
+        sfp::Request::PutVersioned { .. } => return Err(RejectReason::BadRequest),
     }
     Ok(())
 }
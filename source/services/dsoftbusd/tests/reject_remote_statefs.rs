@@ -5,7 +5,7 @@
 //! OWNERS: @runtime
 //! STATUS: Functional
 //! API_STABILITY: Stable
-//! TEST_COVERAGE: 6 tests
+//! TEST_COVERAGE: 7 tests
 //!
 //! TEST_SCOPE:
 //!   - Write-outside-ACL rejection
@@ -13,6 +13,7 @@
 //!   - Oversized value rejection
 //!   - Unauthenticated request rejection
 //!   - Malformed empty frame rejection
+//!   - Versioned put rejection (not forwarded to peers)
 //!   - Protocol symbol link verification for host seam
 //!
 //! TEST_SCENARIOS:
@@ -21,6 +22,7 @@
 //!   - test_reject_oversize_statefs_write(): Values exceeding RS_MAX_VALUE_LEN are rejected
 //!   - test_reject_unauthenticated_statefs_request(): Unauthenticated requests are rejected
 //!   - test_reject_malformed_empty_frame(): Empty frames are rejected as BadRequest
+//!   - test_reject_remote_versioned_put(): OP_PUT_VERSIONED is rejected as BadRequest, even inside the ACL
 //!   - test_statefs_protocol_symbols_are_linked_for_host_seam(): All statefs protocol symbols are linkable
 //!
 //! DEPENDENCIES:
@@ -69,6 +71,13 @@ fn test_reject_malformed_empty_frame() {
     assert!(matches!(parse_request(&[], true), Err(RejectReason::BadRequest)));
 }
 
+#[test]
+fn test_reject_remote_versioned_put() {
+    let req =
+        sfp::encode_put_versioned_request("/state/shared/selftest/cas", b"v", 0).expect("encode");
+    assert!(matches!(parse_request(&req, true), Err(RejectReason::BadRequest)));
+}
+
 #[test]
 fn test_statefs_protocol_symbols_are_linked_for_host_seam() {
     let _ = statefs_rw::RS_MAX_FRAME_LEN;
@@ -98,7 +107,8 @@ fn test_statefs_protocol_symbols_are_linked_for_host_seam() {
     let _ = parsed.op();
     let _ = parsed.nonce();
 
-    let put_req = sfp::Request::Put { key: "/state/shared/selftest/link", value: b"v" };
+    let put_req =
+        sfp::Request::Put { key: "/state/shared/selftest/link", value: b"v", durable: false };
     assert!(statefs_rw::is_mutating_request(&put_req));
     assert_eq!(statefs_rw::request_op(&put_req), sfp::OP_PUT);
 }
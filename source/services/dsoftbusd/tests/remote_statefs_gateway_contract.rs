@@ -43,7 +43,7 @@ type ValidateResponseShapeFn = fn(u8, Option<u64>, &[u8]) -> core::result::Resul
 fn emulate_statefsd(frame: &[u8], kv: &mut BTreeMap<String, Vec<u8>>) -> Vec<u8> {
     let (request, nonce) = sfp::decode_request_with_nonce(frame).expect("decode");
     match request {
-        sfp::Request::Put { key, value } => {
+        sfp::Request::Put { key, value, .. } => {
             kv.insert(String::from(key), value.to_vec());
             sfp::encode_status_response_with_nonce(sfp::OP_PUT, sfp::STATUS_OK, nonce)
         }
@@ -77,6 +77,27 @@ fn emulate_statefsd(frame: &[u8], kv: &mut BTreeMap<String, Vec<u8>>) -> Vec<u8>
         sfp::Request::Reopen => {
             sfp::encode_status_response_with_nonce(sfp::OP_REOPEN, sfp::STATUS_UNSUPPORTED, nonce)
         }
+        sfp::Request::Stats => {
+            sfp::encode_status_response_with_nonce(sfp::OP_STATS, sfp::STATUS_UNSUPPORTED, nonce)
+        }
+        sfp::Request::GetMany { .. } => {
+            sfp::encode_status_response_with_nonce(sfp::OP_MGET, sfp::STATUS_UNSUPPORTED, nonce)
+        }
+        sfp::Request::Verify => {
+            sfp::encode_status_response_with_nonce(sfp::OP_VERIFY, sfp::STATUS_UNSUPPORTED, nonce)
+        }
+        sfp::Request::Barrier => {
+            sfp::encode_status_response_with_nonce(sfp::OP_BARRIER, sfp::STATUS_UNSUPPORTED, nonce)
+        }
+        sfp::Request::Validate { .. } => {
+            sfp::encode_status_response_with_nonce(sfp::OP_VALIDATE, sfp::STATUS_UNSUPPORTED, nonce)
+        }
+        sfp::Request::Changes { .. } => {
+            sfp::encode_status_response_with_nonce(sfp::OP_CHANGES, sfp::STATUS_UNSUPPORTED, nonce)
+        }
+        sfp::Request::PutVersioned { .. } => {
+            sfp::encode_put_versioned_response_with_nonce(sfp::STATUS_UNSUPPORTED, 0, nonce)
+        }
     }
 }
 
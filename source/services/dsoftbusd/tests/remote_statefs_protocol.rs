@@ -139,7 +139,11 @@ fn test_remote_statefs_symbols_are_linked_for_host_seam() {
     assert_eq!(parsed.op(), sfp::OP_GET);
     assert_eq!(parsed.nonce(), None);
 
-    let put_req = sfp::Request::Put { key: "/state/shared/selftest/symbol-link", value: b"v" };
+    let put_req = sfp::Request::Put {
+        key: "/state/shared/selftest/symbol-link",
+        value: b"v",
+        durable: false,
+    };
     assert!(statefs_rw::is_mutating_request(&put_req));
     assert_eq!(
         statefs_rw::reject_label_for_request(sfp::OP_PUT, RejectReason::Oversized),
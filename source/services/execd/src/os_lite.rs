@@ -35,7 +35,6 @@ use crash::{
 };
 use demo_exit0::{DEMO_EXIT0_ELF, DEMO_MINIDUMP_ELF};
 use exec_payloads::HELLO_ELF;
-use nexus_log;
 
 /// Result alias surfaced by the lite execd backend.
 pub type LiteResult<T> = Result<T, ServerError>;
@@ -462,6 +461,7 @@ fn ipc_error_label(err: nexus_abi::IpcError) -> &'static str {
         nexus_abi::IpcError::QueueEmpty => "QueueEmpty",
         nexus_abi::IpcError::NoSpace => "NoSpace",
         nexus_abi::IpcError::TimedOut => "TimedOut",
+        nexus_abi::IpcError::Truncated => "Truncated",
         nexus_abi::IpcError::Unsupported => "Unsupported",
     }
 }
@@ -1050,7 +1050,7 @@ fn status_from_statefs_error(err: StatefsError) -> u8 {
         StatefsError::AccessDenied => STATUS_DENY,
         StatefsError::ValueTooLarge | StatefsError::KeyTooLong => STATUS_TOO_LARGE,
         StatefsError::InvalidKey | StatefsError::Corrupted => STATUS_MALFORMED,
-        StatefsError::IoError | StatefsError::ReplayLimitExceeded => STATUS_UNSUPPORTED,
+        _ => STATUS_UNSUPPORTED, // io / replay-limit / superblock: backend-side failures
     }
 }
 
@@ -3,10 +3,17 @@
 
 //! CONTEXT: Location daemon domain library – service API and CLI handlers
 //! OWNERS: @runtime
-//! STATUS: Placeholder
+//! STATUS: Experimental
 //! API_STABILITY: Unstable
-//! TEST_COVERAGE: 1 unit test (fix_message)
+//! TEST_COVERAGE: 1 unit test (fix_message) + privacy (privacy.rs) + lifecycle (service.rs)
 //! ADR: docs/adr/0017-service-architecture.md
+
+pub mod privacy;
+pub mod service;
+
+pub use privacy::{Decision, Fix, LocationError, LocationState, PrivacyPolicy};
+pub use service::{Daemon, DenyAll, Samgr, READY_MARKER, STUB_MARKER};
+
 pub fn help() -> &'static str {
     "locationd fuses sensors for positioning. Usage: locationd [--help]"
 }
@@ -19,6 +26,10 @@ pub fn execute(args: &[&str]) -> String {
     }
 }
 
+/// CLI entry: prints the usage or the fix message and exits.
+///
+/// Does not call [`service::serve`] yet: samgrd registration is still stubbed (no samgrd
+/// client), so a serving daemon could never report ready.
 pub fn run() {
     let owned: Vec<String> = std::env::args().skip(1).collect();
     let refs: Vec<&str> = owned.iter().map(|s| s.as_str()).collect();
@@ -3,7 +3,7 @@
 
 //! CONTEXT: Location daemon entrypoint – wires service logic to CLI/OS entry
 //! OWNERS: @runtime
-//! STATUS: Placeholder
+//! STATUS: Experimental
 //! API_STABILITY: Unstable
 //! TEST_COVERAGE: 1 unit test (lib) + 1 integration test (tests/cli.rs)
 //! ADR: docs/adr/0017-service-architecture.md
@@ -0,0 +1,431 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: Location authority state — last fix, subscriptions, and the privacy gate
+//! OWNERS: @runtime
+//! STATUS: Functional (host-first; OS wiring pending)
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: 12 unit tests
+//!
+//! Every read of location data goes through a [`PrivacyPolicy`] (real impl: policyd
+//! client keyed by the kernel-provided `sender_service_id`). The policy answers
+//! allow / coarse-only / deny per requester; nothing leaves [`LocationState`]
+//! without that answer. Coarse requesters receive fixes snapped to a fixed grid so
+//! the precise position is never observable to them.
+//!
+//! Backend fixes enter through [`LocationState::ingest_fix`], which rejects coordinates,
+//! bearings or speeds no real receiver produces before they can reach a subscriber.
+//!
+//! The last [`HISTORY_CAPACITY`] fixes are kept for short-term backtracking. History is
+//! stored precise and read through the same gate as the last fix, so a coarse requester
+//! only ever sees coarsened history entries.
+
+use core::fmt;
+use std::collections::VecDeque;
+
+/// Upper bound on concurrent subscribers (bounded state; excess is rejected).
+pub const MAX_SUBSCRIBERS: usize = 16;
+
+/// Default coarse grid step: 0.01° (~1.1 km of latitude) in 1e-7 degree units.
+pub const DEFAULT_COARSE_STEP_E7: u32 = 100_000;
+
+/// Exclusive upper bound of [`Fix::bearing_cdeg`] (360.00°).
+pub const BEARING_LIMIT_CDEG: u16 = 36_000;
+
+/// Default speed cap: roughly the speed of sound (343 m/s) in millimeters per second.
+pub const DEFAULT_MAX_SPEED_MMPS: u32 = 343_000;
+
+/// Fixes kept for [`LocationState::recent_fixes`] (oldest evicted first).
+pub const HISTORY_CAPACITY: usize = 32;
+
+/// A position fix. Coordinates are fixed-point 1e-7 degrees (deterministic, no floats).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fix {
+    /// Latitude in 1e-7 degrees (`-90_0000000..=90_0000000`).
+    pub lat_e7: i32,
+    /// Longitude in 1e-7 degrees (`-180_0000000..=180_0000000`).
+    pub lon_e7: i32,
+    /// Horizontal accuracy radius in meters.
+    pub accuracy_m: u32,
+    /// Heading over ground in centidegrees clockwise from true north (`0..36000`).
+    pub bearing_cdeg: u16,
+    /// Speed over ground in millimeters per second.
+    pub speed_mmps: u32,
+    /// Monotonic timestamp of the fix in nanoseconds.
+    pub timestamp_ns: u64,
+}
+
+impl fmt::Display for Fix {
+    /// Textual encoding: `lat_e7=.. lon_e7=.. accuracy_m=.. bearing_cdeg=.. speed_mmps=.. ts_ns=..`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "lat_e7={} lon_e7={} accuracy_m={} bearing_cdeg={} speed_mmps={} ts_ns={}",
+            self.lat_e7,
+            self.lon_e7,
+            self.accuracy_m,
+            self.bearing_cdeg,
+            self.speed_mmps,
+            self.timestamp_ns
+        )
+    }
+}
+
+/// Outcome of a privacy check for one requester.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    /// Precise fixes may be returned.
+    Allow,
+    /// Only grid-coarsened fixes may be returned.
+    CoarseOnly,
+    /// No location data may be returned.
+    Deny,
+}
+
+/// Privacy authority consulted before any fix is handed out (real impl: policyd).
+pub trait PrivacyPolicy {
+    /// Decides what `requester_id` (kernel `sender_service_id`) may observe.
+    fn check(&self, requester_id: u64) -> Decision;
+}
+
+/// Location authority errors (deterministic, fail closed).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LocationError {
+    /// The privacy policy denied the requester.
+    Denied,
+    /// No fix has been recorded yet.
+    NoFix,
+    /// The subscriber table is full.
+    TooManySubscribers,
+    /// An ingested fix is out of range (coordinates, bearing, or speed above the cap).
+    InvalidFix,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Subscriber {
+    requester_id: u64,
+    coarse: bool,
+}
+
+/// Last-known fix plus subscribers, gated by an injected [`PrivacyPolicy`].
+pub struct LocationState<P: PrivacyPolicy> {
+    policy: P,
+    coarse_step_e7: u32,
+    max_speed_mmps: u32,
+    last: Option<Fix>,
+    history: VecDeque<Fix>,
+    subscribers: Vec<Subscriber>,
+}
+
+impl<P: PrivacyPolicy> LocationState<P> {
+    /// Creates an empty state using the default coarse grid.
+    pub fn new(policy: P) -> Self {
+        Self::with_coarse_step(policy, DEFAULT_COARSE_STEP_E7)
+    }
+
+    /// Creates an empty state with a custom coarse grid step (1e-7 degrees, min 1).
+    pub fn with_coarse_step(policy: P, coarse_step_e7: u32) -> Self {
+        Self {
+            policy,
+            coarse_step_e7: coarse_step_e7.max(1),
+            max_speed_mmps: DEFAULT_MAX_SPEED_MMPS,
+            last: None,
+            history: VecDeque::with_capacity(HISTORY_CAPACITY),
+            subscribers: Vec::new(),
+        }
+    }
+
+    /// Replaces the speed cap [`Self::ingest_fix`] enforces (mm/s, inclusive).
+    pub fn with_speed_cap(mut self, max_speed_mmps: u32) -> Self {
+        self.max_speed_mmps = max_speed_mmps;
+        self
+    }
+
+    /// Validates a fix from the positioning backend, then records it like [`Self::update`].
+    ///
+    /// Rejects with [`LocationError::InvalidFix`] (state unchanged) when the coordinates
+    /// are out of range, `bearing_cdeg >= 36000`, or `speed_mmps` exceeds the speed cap.
+    pub fn ingest_fix(&mut self, fix: Fix) -> Result<Vec<(u64, Fix)>, LocationError> {
+        let valid = (-90_0000000..=90_0000000).contains(&fix.lat_e7)
+            && (-180_0000000..=180_0000000).contains(&fix.lon_e7)
+            && fix.bearing_cdeg < BEARING_LIMIT_CDEG
+            && fix.speed_mmps <= self.max_speed_mmps;
+        if !valid {
+            return Err(LocationError::InvalidFix);
+        }
+        Ok(self.update(fix))
+    }
+
+    /// Records a new fix from the positioning backend.
+    ///
+    /// Returns the per-subscriber deliveries, each already coarsened as required.
+    pub fn update(&mut self, fix: Fix) -> Vec<(u64, Fix)> {
+        self.last = Some(fix);
+        if self.history.len() >= HISTORY_CAPACITY {
+            self.history.pop_front();
+        }
+        self.history.push_back(fix);
+        self.subscribers
+            .iter()
+            .map(|sub| {
+                let out = if sub.coarse { coarsen(fix, self.coarse_step_e7) } else { fix };
+                (sub.requester_id, out)
+            })
+            .collect()
+    }
+
+    /// Returns the last fix as visible to `requester_id`.
+    pub fn get_last(&self, requester_id: u64) -> Result<Fix, LocationError> {
+        let decision = self.policy.check(requester_id);
+        if decision == Decision::Deny {
+            return Err(LocationError::Denied);
+        }
+        let fix = self.last.ok_or(LocationError::NoFix)?;
+        Ok(match decision {
+            Decision::CoarseOnly => coarsen(fix, self.coarse_step_e7),
+            _ => fix,
+        })
+    }
+
+    /// Returns up to `max` recent fixes as visible to `requester_id`, newest first.
+    ///
+    /// At most [`HISTORY_CAPACITY`] fixes are returned; coarse requesters get every entry
+    /// coarsened. An empty history is an empty list, not [`LocationError::NoFix`].
+    pub fn recent_fixes(&self, requester_id: u64, max: usize) -> Result<Vec<Fix>, LocationError> {
+        let coarse = match self.policy.check(requester_id) {
+            Decision::Deny => return Err(LocationError::Denied),
+            Decision::CoarseOnly => true,
+            Decision::Allow => false,
+        };
+        Ok(self
+            .history
+            .iter()
+            .rev()
+            .take(max)
+            .map(|&fix| if coarse { coarsen(fix, self.coarse_step_e7) } else { fix })
+            .collect())
+    }
+
+    /// Subscribes `requester_id` to future fixes at the granularity the policy allows.
+    ///
+    /// Re-subscribing refreshes the recorded decision instead of adding a duplicate.
+    pub fn subscribe(&mut self, requester_id: u64) -> Result<Decision, LocationError> {
+        let decision = self.policy.check(requester_id);
+        let coarse = match decision {
+            Decision::Deny => {
+                self.unsubscribe(requester_id);
+                return Err(LocationError::Denied);
+            }
+            Decision::CoarseOnly => true,
+            Decision::Allow => false,
+        };
+        if let Some(sub) = self.subscribers.iter_mut().find(|s| s.requester_id == requester_id) {
+            sub.coarse = coarse;
+            return Ok(decision);
+        }
+        if self.subscribers.len() >= MAX_SUBSCRIBERS {
+            return Err(LocationError::TooManySubscribers);
+        }
+        self.subscribers.push(Subscriber { requester_id, coarse });
+        Ok(decision)
+    }
+
+    /// Removes `requester_id` from the subscriber set (no-op if absent).
+    pub fn unsubscribe(&mut self, requester_id: u64) {
+        self.subscribers.retain(|s| s.requester_id != requester_id);
+    }
+}
+
+/// Snaps a fix to the nearest point of a `step_e7` grid.
+///
+/// Accuracy is widened to at least the grid cell size so a coarse fix never
+/// claims more precision than it carries. Bearing and speed are kept as-is.
+pub fn coarsen(fix: Fix, step_e7: u32) -> Fix {
+    let step = i64::from(step_e7.max(1));
+    let snap = |v: i32| -> i32 {
+        let snapped = (i64::from(v) + step / 2).div_euclid(step) * step;
+        snapped.clamp(i64::from(i32::MIN), i64::from(i32::MAX)) as i32
+    };
+    // 1e-7 degrees of latitude ≈ 0.0111 m.
+    let cell_m = u32::try_from(step * 111 / 10_000).unwrap_or(u32::MAX);
+    Fix {
+        lat_e7: snap(fix.lat_e7),
+        lon_e7: snap(fix.lon_e7),
+        accuracy_m: fix.accuracy_m.max(cell_m),
+        ..fix
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fixed(Decision);
+
+    impl PrivacyPolicy for Fixed {
+        fn check(&self, _requester_id: u64) -> Decision {
+            self.0
+        }
+    }
+
+    /// Allows requester 1, coarse for 2, denies everyone else.
+    struct PerRequester;
+
+    impl PrivacyPolicy for PerRequester {
+        fn check(&self, requester_id: u64) -> Decision {
+            match requester_id {
+                1 => Decision::Allow,
+                2 => Decision::CoarseOnly,
+                _ => Decision::Deny,
+            }
+        }
+    }
+
+    const FIX: Fix = Fix {
+        lat_e7: 52_5200123,
+        lon_e7: 13_4049876,
+        accuracy_m: 5,
+        bearing_cdeg: 9_050,
+        speed_mmps: 13_900,
+        timestamp_ns: 1_000,
+    };
+
+    #[test]
+    fn allow_returns_precise_fix() {
+        let mut state = LocationState::new(Fixed(Decision::Allow));
+        assert_eq!(state.get_last(7), Err(LocationError::NoFix));
+        state.update(FIX);
+        assert_eq!(state.get_last(7), Ok(FIX));
+    }
+
+    #[test]
+    fn test_reject_denied_get_last_and_subscribe() {
+        let mut state = LocationState::new(Fixed(Decision::Deny));
+        state.update(FIX);
+        assert_eq!(state.get_last(7), Err(LocationError::Denied));
+        assert_eq!(state.subscribe(7), Err(LocationError::Denied));
+        assert!(state.update(FIX).is_empty());
+    }
+
+    #[test]
+    fn coarse_only_returns_rounded_fix() {
+        let mut state = LocationState::with_coarse_step(Fixed(Decision::CoarseOnly), 100_000);
+        state.update(FIX);
+        let fix = state.get_last(7).expect("coarse fix");
+        assert_eq!(fix.lat_e7, 52_5200000);
+        assert_eq!(fix.lon_e7, 13_4000000);
+        assert_eq!(fix.accuracy_m, 1_110);
+        assert_eq!(fix.timestamp_ns, FIX.timestamp_ns);
+    }
+
+    #[test]
+    fn coarsen_rounds_negative_coordinates_to_nearest_cell() {
+        let fix = Fix { lat_e7: -33_8688600, lon_e7: -151_2093400, ..FIX };
+        let out = coarsen(fix, 100_000);
+        assert_eq!(out.lat_e7, -33_8700000);
+        assert_eq!(out.lon_e7, -151_2100000);
+    }
+
+    #[test]
+    fn subscribers_receive_fixes_at_their_granularity() {
+        let mut state = LocationState::new(PerRequester);
+        assert_eq!(state.subscribe(1), Ok(Decision::Allow));
+        assert_eq!(state.subscribe(2), Ok(Decision::CoarseOnly));
+        assert_eq!(state.subscribe(3), Err(LocationError::Denied));
+        let out = state.update(FIX);
+        assert_eq!(out, vec![(1, FIX), (2, coarsen(FIX, DEFAULT_COARSE_STEP_E7))]);
+    }
+
+    #[test]
+    fn test_reject_subscriber_overflow() {
+        let mut state = LocationState::new(Fixed(Decision::Allow));
+        for id in 0..MAX_SUBSCRIBERS as u64 {
+            assert!(state.subscribe(id).is_ok());
+        }
+        // Re-subscribing an existing requester does not consume a slot.
+        assert!(state.subscribe(0).is_ok());
+        assert_eq!(state.subscribe(u64::MAX), Err(LocationError::TooManySubscribers));
+    }
+
+    #[test]
+    fn ingest_fix_records_bearing_and_speed() {
+        let mut state = LocationState::new(Fixed(Decision::Allow));
+        let edge = Fix { bearing_cdeg: 35_999, speed_mmps: DEFAULT_MAX_SPEED_MMPS, ..FIX };
+        assert_eq!(state.ingest_fix(edge), Ok(Vec::new()));
+        assert_eq!(state.ingest_fix(FIX), Ok(Vec::new()));
+        let last = state.get_last(7).unwrap();
+        assert_eq!((last.bearing_cdeg, last.speed_mmps), (9_050, 13_900));
+        assert_eq!(
+            last.to_string(),
+            "lat_e7=525200123 lon_e7=134049876 accuracy_m=5 bearing_cdeg=9050 \
+             speed_mmps=13900 ts_ns=1000"
+        );
+    }
+
+    #[test]
+    fn test_reject_out_of_range_bearing() {
+        let mut state = LocationState::new(Fixed(Decision::Allow));
+        state.ingest_fix(FIX).unwrap();
+        let bad = Fix { bearing_cdeg: BEARING_LIMIT_CDEG, timestamp_ns: 2_000, ..FIX };
+        assert_eq!(state.ingest_fix(bad), Err(LocationError::InvalidFix));
+        let bad = Fix { lat_e7: 90_0000001, timestamp_ns: 2_000, ..FIX };
+        assert_eq!(state.ingest_fix(bad), Err(LocationError::InvalidFix));
+        assert_eq!(state.get_last(7), Ok(FIX));
+    }
+
+    #[test]
+    fn test_reject_over_speed_fix() {
+        let mut state = LocationState::new(Fixed(Decision::Allow)).with_speed_cap(50_000);
+        assert_eq!(state.subscribe(7), Ok(Decision::Allow));
+        let fast = Fix { speed_mmps: 50_001, ..FIX };
+        assert_eq!(state.ingest_fix(fast), Err(LocationError::InvalidFix));
+        assert_eq!(state.get_last(7), Err(LocationError::NoFix));
+        assert_eq!(state.ingest_fix(Fix { speed_mmps: 50_000, ..FIX }).unwrap().len(), 1);
+    }
+
+    fn fix_at(timestamp_ns: u64) -> Fix {
+        Fix { lat_e7: FIX.lat_e7 + timestamp_ns as i32, timestamp_ns, ..FIX }
+    }
+
+    #[test]
+    fn recent_fixes_are_newest_first_and_capped() {
+        let mut state = LocationState::new(Fixed(Decision::Allow));
+        assert_eq!(state.recent_fixes(7, 4), Ok(Vec::new()));
+        for ts in 1..=5 {
+            state.update(fix_at(ts));
+        }
+        assert_eq!(state.recent_fixes(7, 3), Ok(vec![fix_at(5), fix_at(4), fix_at(3)]));
+        assert_eq!(state.recent_fixes(7, 100).unwrap().len(), 5);
+        assert_eq!(state.recent_fixes(7, 0), Ok(Vec::new()));
+        // Rejected fixes never enter the history.
+        assert!(state.ingest_fix(Fix { bearing_cdeg: BEARING_LIMIT_CDEG, ..FIX }).is_err());
+        assert_eq!(state.recent_fixes(7, 1), Ok(vec![fix_at(5)]));
+    }
+
+    #[test]
+    fn recent_fixes_evicts_oldest_on_wrap_around() {
+        let mut state = LocationState::new(Fixed(Decision::Allow));
+        let total = HISTORY_CAPACITY as u64 + 3;
+        for ts in 1..=total {
+            state.update(fix_at(ts));
+        }
+        let history = state.recent_fixes(7, usize::MAX).unwrap();
+        assert_eq!(history.len(), HISTORY_CAPACITY);
+        assert_eq!(history.first(), Some(&fix_at(total)));
+        assert_eq!(history.last(), Some(&fix_at(4)));
+    }
+
+    #[test]
+    fn coarse_only_history_is_coarsened() {
+        let mut state = LocationState::new(PerRequester);
+        state.update(fix_at(1));
+        state.update(fix_at(2));
+        let coarse = vec![
+            coarsen(fix_at(2), DEFAULT_COARSE_STEP_E7),
+            coarsen(fix_at(1), DEFAULT_COARSE_STEP_E7),
+        ];
+        assert_eq!(state.recent_fixes(2, 8), Ok(coarse));
+        assert_eq!(state.recent_fixes(1, 8), Ok(vec![fix_at(2), fix_at(1)]));
+        assert_eq!(state.recent_fixes(3, 8), Err(LocationError::Denied));
+    }
+}
@@ -0,0 +1,197 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: Location daemon lifecycle — samgr registration, readiness marker, heartbeat loop
+//! OWNERS: @runtime
+//! STATUS: Placeholder (samgrd registration stubbed: no samgrd client; `run()` does not serve)
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: 3 unit tests (marker after registration, stub marker for the in-process
+//! registry, no marker on failure)
+//!
+//! Readiness contract: `locationd: ready` is printed exactly once, and only after the
+//! system samgrd accepted the registration. A registration that samgrd cannot see (the
+//! in-process [`samgr::Registry`], all `serve` has until a samgrd client exists) prints
+//! [`STUB_MARKER`] instead. The daemon then heartbeats samgr every [`HEARTBEAT_INTERVAL`].
+//! [`Daemon::tick`] is one serve-loop iteration, so host tests drive the loop without
+//! sleeping.
+//!
+//! Registration with the system samgrd over IPC is not implemented: [`serve`] only fills the
+//! in-process registry, and `locationd::run` does not call it until a samgrd client exists.
+
+use std::time::Duration;
+
+use samgr::{Endpoint, Registry, ServiceHandle};
+
+use crate::privacy::{Decision, LocationState, PrivacyPolicy};
+
+/// Name the daemon registers with samgr.
+pub const SERVICE_NAME: &str = "locationd";
+/// Readiness marker (emitted once samgrd accepted the registration).
+pub const READY_MARKER: &str = "locationd: ready";
+/// Marker for a registration samgrd never sees; not a readiness signal.
+pub const STUB_MARKER: &str = "locationd: samgr stub (not registered with samgrd)";
+/// Pause between samgr heartbeats.
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_millis(500);
+
+/// The samgr operations the daemon uses (real impl: [`samgr::Registry`]).
+pub trait Samgr {
+    /// Registration handle presented on every heartbeat.
+    type Handle;
+
+    /// Whether a registration reaches the system samgrd (and so may be reported as ready).
+    const REACHES_SAMGRD: bool;
+
+    /// Registers `name`.
+    fn register(&self, name: &str) -> samgr::Result<Self::Handle>;
+
+    /// Reports that the registered instance is alive.
+    fn heartbeat(&self, handle: &Self::Handle) -> samgr::Result<()>;
+}
+
+impl Samgr for Registry {
+    type Handle = ServiceHandle;
+
+    // In-process map; the OS backend is still `Unsupported`.
+    const REACHES_SAMGRD: bool = false;
+
+    fn register(&self, name: &str) -> samgr::Result<ServiceHandle> {
+        Registry::register(self, name, Endpoint::new(name))
+    }
+
+    fn heartbeat(&self, handle: &ServiceHandle) -> samgr::Result<()> {
+        Registry::heartbeat(self, handle)
+    }
+}
+
+/// Privacy policy used until the policyd client is wired: every requester is denied.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DenyAll;
+
+impl PrivacyPolicy for DenyAll {
+    fn check(&self, _requester_id: u64) -> Decision {
+        Decision::Deny
+    }
+}
+
+/// A registered daemon instance.
+pub struct Daemon<P: PrivacyPolicy, S: Samgr> {
+    state: LocationState<P>,
+    samgr: S,
+    handle: S::Handle,
+}
+
+impl<P: PrivacyPolicy, S: Samgr> Daemon<P, S> {
+    /// Registers with samgr, then hands [`READY_MARKER`] to `emit`, or [`STUB_MARKER`] when
+    /// the registration does not reach samgrd.
+    ///
+    /// A failed registration returns the error and emits nothing.
+    pub fn start(
+        state: LocationState<P>,
+        samgr: S,
+        emit: &mut impl FnMut(&str),
+    ) -> samgr::Result<Self> {
+        let handle = samgr.register(SERVICE_NAME)?;
+        emit(if S::REACHES_SAMGRD { READY_MARKER } else { STUB_MARKER });
+        Ok(Self { state, samgr, handle })
+    }
+
+    /// One serve-loop iteration: heartbeat samgr.
+    pub fn tick(&mut self) -> samgr::Result<()> {
+        self.samgr.heartbeat(&self.handle)
+    }
+
+    /// Location state served by this instance.
+    pub fn state(&mut self) -> &mut LocationState<P> {
+        &mut self.state
+    }
+}
+
+/// Registers with the in-process samgr registry and heartbeats until it drops the
+/// registration. No samgrd client exists yet, so this reports [`STUB_MARKER`], not ready.
+pub fn serve() -> samgr::Result<()> {
+    let mut daemon = Daemon::start(LocationState::new(DenyAll), Registry::new(), &mut |line| {
+        println!("{line}")
+    })?;
+    loop {
+        std::thread::sleep(HEARTBEAT_INTERVAL);
+        daemon.tick()?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::{Cell, RefCell};
+
+    /// samgr stand-in recording calls.
+    struct MockSamgr {
+        accept: bool,
+        registered: RefCell<Vec<String>>,
+        heartbeats: Cell<u32>,
+    }
+
+    impl MockSamgr {
+        fn new(accept: bool) -> Self {
+            Self { accept, registered: RefCell::new(Vec::new()), heartbeats: Cell::new(0) }
+        }
+    }
+
+    impl Samgr for &MockSamgr {
+        type Handle = u32;
+
+        const REACHES_SAMGRD: bool = true;
+
+        fn register(&self, name: &str) -> samgr::Result<u32> {
+            if !self.accept {
+                return Err(samgr::Error::Duplicate);
+            }
+            self.registered.borrow_mut().push(name.to_string());
+            Ok(7)
+        }
+
+        fn heartbeat(&self, handle: &u32) -> samgr::Result<()> {
+            assert_eq!(*handle, 7);
+            self.heartbeats.set(self.heartbeats.get() + 1);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn ready_marker_follows_successful_registration() {
+        let samgr = MockSamgr::new(true);
+        let mut lines = Vec::new();
+        let mut daemon =
+            Daemon::start(LocationState::new(DenyAll), &samgr, &mut |l| lines.push(l.to_string()))
+                .unwrap();
+        assert_eq!(lines, [READY_MARKER]);
+        assert_eq!(*samgr.registered.borrow(), [SERVICE_NAME]);
+
+        daemon.tick().unwrap();
+        daemon.tick().unwrap();
+        assert_eq!(samgr.heartbeats.get(), 2);
+        // The interim policy fails closed.
+        assert_eq!(daemon.state().get_last(1), Err(crate::LocationError::Denied));
+    }
+
+    #[test]
+    fn in_process_registry_reports_stub_not_ready() {
+        let mut lines = Vec::new();
+        let mut daemon = Daemon::start(LocationState::new(DenyAll), Registry::new(), &mut |l| {
+            lines.push(l.to_string())
+        })
+        .unwrap();
+        assert_eq!(lines, [STUB_MARKER]);
+        daemon.tick().unwrap();
+    }
+
+    #[test]
+    fn test_reject_failed_registration_emits_no_marker() {
+        let samgr = MockSamgr::new(false);
+        let mut lines = Vec::new();
+        let started =
+            Daemon::start(LocationState::new(DenyAll), &samgr, &mut |l| lines.push(l.to_string()));
+        assert_eq!(started.err(), Some(samgr::Error::Duplicate));
+        assert!(lines.is_empty());
+        assert_eq!(samgr.heartbeats.get(), 0);
+    }
+}
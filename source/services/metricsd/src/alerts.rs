@@ -0,0 +1,238 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+//
+//! CONTEXT: metricsd threshold alerts — edge-triggered rules over gauges and counters
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: Host unit tests in this module
+//! ADR: docs/rfcs/RFC-0024-observability-v2-metrics-tracing-contract-v1.md
+//!
+//! A rule compares every update of the matching series against a threshold. The rule
+//! remembers whether its condition held after the previous update, so only the edges
+//! are reported: one `fired: true` alert when the condition starts to hold and one
+//! `fired: false` alert when it stops. Updates that stay on the same side are silent.
+//! A supervisor drains the queued edges with [`Registry::take_alerts`].
+//!
+//! INVARIANTS:
+//! - Bounded: at most [`MAX_ALERT_RULES`] rules and [`MAX_PENDING_ALERTS`] queued edges;
+//!   a full queue drops its oldest edge
+//! - Rules match a series by name and labels for any sender; the alert names the sender
+//! - Rules and queued edges are volatile (not part of the registry snapshot)
+
+use alloc::vec::Vec;
+
+use crate::{LimitKind, MetricKind, Registry, RejectReason};
+
+/// Alert rules the registry holds.
+pub const MAX_ALERT_RULES: usize = 16;
+/// Undrained alert edges kept before the oldest is dropped.
+pub const MAX_PENDING_ALERTS: usize = 32;
+
+/// Comparison a rule applies as `value <op> threshold`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CmpOp {
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+impl CmpOp {
+    fn holds(self, value: i64, threshold: i64) -> bool {
+        match self {
+            Self::Gt => value > threshold,
+            Self::Ge => value >= threshold,
+            Self::Lt => value < threshold,
+            Self::Le => value <= threshold,
+        }
+    }
+}
+
+/// One threshold edge, queued for [`Registry::take_alerts`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Alert {
+    pub sender_service_id: u64,
+    pub name: Vec<u8>,
+    pub labels: Vec<u8>,
+    /// Series value that crossed the threshold (counters saturate at `i64::MAX`).
+    pub value: i64,
+    /// `true` when the condition started to hold, `false` when it stopped (reset).
+    pub fired: bool,
+}
+
+#[derive(Clone, Debug)]
+struct AlertRule {
+    name: Vec<u8>,
+    labels: Vec<u8>,
+    op: CmpOp,
+    threshold: i64,
+    /// Whether the condition held after the last evaluated update.
+    active: bool,
+}
+
+/// Configured rules plus the queue of edges not yet taken.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Alerts {
+    rules: Vec<AlertRule>,
+    pending: Vec<Alert>,
+}
+
+impl Registry {
+    /// Installs (or replaces) the alert rule for the series `name`/`labels`.
+    ///
+    /// A replaced rule starts inactive again; the next update of the series decides.
+    pub fn set_alert(
+        &mut self,
+        name: &[u8],
+        labels: &[u8],
+        op: CmpOp,
+        threshold: i64,
+    ) -> Result<(), RejectReason> {
+        if name.is_empty() {
+            return Err(RejectReason::InvalidArgs);
+        }
+        if name.len() > self.limits.max_metric_name_len || labels.len() > self.limits.max_labels_len
+        {
+            return Err(RejectReason::OverLimit(LimitKind::FieldLen));
+        }
+        let rule = AlertRule {
+            name: name.to_vec(),
+            labels: labels.to_vec(),
+            op,
+            threshold,
+            active: false,
+        };
+        let rules = &mut self.alerts.rules;
+        if let Some(pos) =
+            rules.iter().position(|r| r.name.as_slice() == name && r.labels.as_slice() == labels)
+        {
+            rules[pos] = rule;
+        } else if rules.len() >= MAX_ALERT_RULES {
+            return Err(RejectReason::OverLimit(LimitKind::AlertRules));
+        } else {
+            rules.push(rule);
+        }
+        Ok(())
+    }
+
+    /// Drains the queued alert edges, oldest first.
+    pub fn take_alerts(&mut self) -> Vec<Alert> {
+        core::mem::take(&mut self.alerts.pending)
+    }
+
+    /// Evaluates the rules matching series `idx` after an update.
+    pub(crate) fn check_alerts(&mut self, idx: usize) {
+        let Some(series) = self.series.get(idx) else { return };
+        let value = match series.kind {
+            MetricKind::Counter => i64::try_from(series.counter_value).unwrap_or(i64::MAX),
+            MetricKind::Gauge => series.gauge_value,
+            MetricKind::Histogram | MetricKind::WindowedCounter => return,
+        };
+        let alerts = &mut self.alerts;
+        for rule in alerts.rules.iter_mut() {
+            if rule.name != series.name || rule.labels != series.labels {
+                continue;
+            }
+            let active = rule.op.holds(value, rule.threshold);
+            if active == rule.active {
+                continue;
+            }
+            rule.active = active;
+            if alerts.pending.len() >= MAX_PENDING_ALERTS {
+                alerts.pending.remove(0);
+            }
+            alerts.pending.push(Alert {
+                sender_service_id: series.sender_service_id,
+                name: series.name.clone(),
+                labels: series.labels.clone(),
+                value,
+                fired: active,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edges(reg: &mut Registry) -> Vec<(i64, bool)> {
+        reg.take_alerts().into_iter().map(|alert| (alert.value, alert.fired)).collect()
+    }
+
+    #[test]
+    fn gauge_crossing_fires_once_and_resets() {
+        let mut reg = Registry::new();
+        reg.set_alert(b"mem.free", b"", CmpOp::Lt, 100).unwrap();
+        reg.gauge_set(7, b"mem.free", b"", 500).unwrap();
+        assert!(reg.take_alerts().is_empty());
+
+        // Crossing down fires once; staying below does not re-fire.
+        reg.gauge_set(7, b"mem.free", b"", 90).unwrap();
+        reg.gauge_set(7, b"mem.free", b"", 40).unwrap();
+        reg.gauge_set(7, b"mem.free", b"", 99).unwrap();
+        let fired = reg.take_alerts();
+        assert_eq!(fired.len(), 1);
+        assert_eq!(
+            fired[0],
+            Alert {
+                sender_service_id: 7,
+                name: b"mem.free".to_vec(),
+                labels: Vec::new(),
+                value: 90,
+                fired: true,
+            }
+        );
+
+        // Recovering resets the rule, which can then fire again.
+        reg.gauge_set(7, b"mem.free", b"", 100).unwrap();
+        reg.gauge_set(7, b"mem.free", b"", 300).unwrap();
+        reg.gauge_set(7, b"mem.free", b"", 10).unwrap();
+        assert_eq!(edges(&mut reg), [(100, false), (10, true)]);
+    }
+
+    #[test]
+    fn counter_crossing_up_is_edge_triggered() {
+        let mut reg = Registry::new();
+        reg.set_alert(b"ipc.errors", b"svc=vfsd", CmpOp::Ge, 3).unwrap();
+        for _ in 0..5 {
+            reg.counter_inc(7, b"ipc.errors", b"svc=vfsd", 1).unwrap();
+        }
+        // Other labels and replayed adds are not evaluated.
+        reg.counter_inc(7, b"ipc.errors", b"svc=logd", 10).unwrap();
+        reg.counter_add_checked(7, b"ipc.errors", b"svc=vfsd", 1, Some(1)).unwrap();
+        reg.counter_add_checked(7, b"ipc.errors", b"svc=vfsd", 1, Some(1)).unwrap();
+        assert_eq!(edges(&mut reg), [(3, true)]);
+        assert!(reg.take_alerts().is_empty());
+    }
+
+    #[test]
+    fn test_reject_unbounded_alert_rules() {
+        let mut reg = Registry::new();
+        assert_eq!(reg.set_alert(b"", b"", CmpOp::Gt, 0), Err(RejectReason::InvalidArgs));
+        assert_eq!(
+            reg.set_alert(&[b'm'; 65], b"", CmpOp::Gt, 0),
+            Err(RejectReason::OverLimit(LimitKind::FieldLen))
+        );
+        for i in 0..MAX_ALERT_RULES as u8 {
+            reg.set_alert(&[b'a', i], b"", CmpOp::Gt, 0).unwrap();
+        }
+        assert_eq!(
+            reg.set_alert(b"one.more", b"", CmpOp::Gt, 0),
+            Err(RejectReason::OverLimit(LimitKind::AlertRules))
+        );
+        // Replacing an existing rule stays within the bound.
+        reg.set_alert(&[b'a', 0], b"", CmpOp::Lt, 0).unwrap();
+
+        // The edge queue keeps only the newest MAX_PENDING_ALERTS entries.
+        for value in 0..(MAX_PENDING_ALERTS as i64 + 2) {
+            reg.gauge_set(7, &[b'a', 1], b"", if value % 2 == 0 { 1 } else { 0 }).unwrap();
+        }
+        let queued = reg.take_alerts();
+        assert_eq!(queued.len(), MAX_PENDING_ALERTS);
+        // The first fire/reset pair was dropped; the newest edge is the final reset.
+        assert!(queued[0].fired);
+        assert!(!queued[MAX_PENDING_ALERTS - 1].fired);
+    }
+}
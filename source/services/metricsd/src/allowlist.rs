@@ -0,0 +1,134 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+//
+//! CONTEXT: metricsd sender allow-list — only registered services may ingest
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: Host unit tests in this module
+//! ADR: docs/rfcs/RFC-0024-observability-v2-metrics-tracing-contract-v1.md
+//!
+//! Any task that can route to metricsd can push metrics. [`Registry::allow_sender`] narrows
+//! that to a fixed set of `sender_service_id`s: once the set is non-empty, metric updates and
+//! span starts from anyone else are rejected with [`RejectReason::InvalidArgs`]. The empty set
+//! allows every sender, so a registry nobody configures behaves as before.
+//!
+//! INVARIANTS:
+//! - The id checked is the kernel-derived `sender_service_id` the daemon passes in, never a
+//!   payload field
+//! - Bounded: at most [`MAX_ALLOWED_SENDERS`] ids; entries are never removed
+//! - Only ingest is gated; queries and `span_end` of an already admitted span are not
+//! - Configuration, not state: the set is left out of registry snapshots
+
+use alloc::vec::Vec;
+
+use crate::{LimitKind, Registry, RejectReason, SELF_SENDER_ID};
+
+/// Sender ids the allow-list holds.
+pub const MAX_ALLOWED_SENDERS: usize = 64;
+
+/// Kernel sender ids admitted to ingest; empty admits everyone.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct AllowedSenders {
+    ids: Vec<u64>,
+}
+
+impl Registry {
+    /// Adds `sender_service_id` to the allow-list; from then on only listed senders ingest.
+    ///
+    /// Adding a listed id again is a no-op. A full list rejects with
+    /// `OverLimit(LimitKind::AllowedSenders)`.
+    pub fn allow_sender(&mut self, sender_service_id: u64) -> Result<(), RejectReason> {
+        let ids = &mut self.allowed_senders.ids;
+        if ids.contains(&sender_service_id) {
+            return Ok(());
+        }
+        if ids.len() >= MAX_ALLOWED_SENDERS {
+            return Err(RejectReason::OverLimit(LimitKind::AllowedSenders));
+        }
+        ids.push(sender_service_id);
+        Ok(())
+    }
+
+    /// Whether `sender_service_id` may ingest (always, while the list is empty).
+    pub fn is_allowed(&self, sender_service_id: u64) -> bool {
+        let ids = &self.allowed_senders.ids;
+        ids.is_empty() || ids.contains(&sender_service_id)
+    }
+
+    /// Rejects ingest from a sender outside a non-empty allow-list, and from the reserved
+    /// self-metrics id whatever the list holds.
+    pub(crate) fn check_sender(&self, sender_service_id: u64) -> Result<(), RejectReason> {
+        if sender_service_id != SELF_SENDER_ID && self.is_allowed(sender_service_id) {
+            Ok(())
+        } else {
+            Err(RejectReason::InvalidArgs)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SpanStartArgs;
+
+    fn span_from(sender_service_id: u64) -> SpanStartArgs<'static> {
+        SpanStartArgs {
+            sender_service_id,
+            span_id: (sender_service_id << 32) | 1,
+            trace_id: 9,
+            parent_span_id: 0,
+            start_ns: 0,
+            name: b"op",
+            attrs: b"",
+        }
+    }
+
+    #[test]
+    fn empty_list_allows_every_sender() {
+        let mut reg = Registry::new();
+        assert!(reg.is_allowed(3) && reg.is_allowed(u64::MAX));
+        assert_eq!(reg.counter_inc(3, b"net.rx", b"", 1), Ok(1));
+        assert_eq!(reg.gauge_set(4, b"mem.free", b"", 7), Ok(7));
+    }
+
+    #[test]
+    fn test_reject_ingest_from_unlisted_senders() {
+        let mut reg = Registry::new();
+        reg.allow_sender(3).unwrap();
+        assert!(!reg.is_allowed(4));
+        assert_eq!(reg.counter_inc(4, b"net.rx", b"", 1), Err(RejectReason::InvalidArgs));
+        assert_eq!(
+            reg.counter_add_checked(4, b"net.rx", b"", 1, Some(5)).map(|add| add.after),
+            Err(RejectReason::InvalidArgs)
+        );
+        assert_eq!(reg.gauge_set(4, b"mem.free", b"", 7), Err(RejectReason::InvalidArgs));
+        assert_eq!(reg.hist_observe(4, b"lat", b"", 7), Err(RejectReason::InvalidArgs));
+        assert_eq!(
+            reg.windowed_inc(4, nexus_metrics::WINDOW_10S, b"net.active", b"", 1, 0),
+            Err(RejectReason::InvalidArgs)
+        );
+        assert_eq!(reg.span_start(span_from(4)), Err(RejectReason::InvalidArgs));
+        // The listed sender is unaffected, and nothing was created for the rejected one.
+        assert_eq!(reg.counter_inc(3, b"net.rx", b"", 2), Ok(2));
+        assert_eq!(reg.span_start(span_from(3)), Ok(()));
+        assert_eq!(reg.windowed_value(4, b"net.active", b"", 0), None);
+
+        for id in 100..100 + MAX_ALLOWED_SENDERS as u64 - 1 {
+            reg.allow_sender(id).unwrap();
+        }
+        assert_eq!(reg.allow_sender(3), Ok(()));
+        assert_eq!(reg.allow_sender(7), Err(RejectReason::OverLimit(LimitKind::AllowedSenders)));
+    }
+
+    #[test]
+    fn newly_allowed_sender_is_accepted() {
+        let mut reg = Registry::new();
+        reg.allow_sender(3).unwrap();
+        assert_eq!(reg.counter_inc(4, b"net.rx", b"", 1), Err(RejectReason::InvalidArgs));
+        reg.allow_sender(4).unwrap();
+        assert!(reg.is_allowed(4));
+        assert_eq!(reg.counter_inc(4, b"net.rx", b"", 1), Ok(1));
+        assert_eq!(reg.span_start(span_from(4)), Ok(()));
+    }
+}
@@ -0,0 +1,219 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+//
+//! CONTEXT: metricsd downsampled gauge history (sparkline-style min/max/last per time bucket)
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: Host unit tests in this module
+//! ADR: docs/rfcs/RFC-0024-observability-v2-metrics-tracing-contract-v1.md
+//!
+//! With [`GaugeTimeSeries`] enabled (`Registry::set_gauge_time_series`), every timed gauge
+//! set (`Registry::gauge_set_at`, the daemon's `OP_GAUGE_SET` path) also lands in a per-series
+//! history of at most [`GAUGE_SERIES_BUCKETS`] buckets keyed by bucket epoch
+//! (`now_ns / bucket_ns`). A bucket keeps the min, max and last value of its samples, so a
+//! long-running gauge costs a fixed amount of memory however often it is set.
+//!
+//! INVARIANTS:
+//! - Off by default; when off, gauge sets record no history and allocate nothing for it
+//! - At most `GAUGE_SERIES_BUCKETS` buckets per gauge series, oldest dropped first
+//! - A bucket ages out once its epoch is `GAUGE_SERIES_BUCKETS` or more behind `now_ns`'s;
+//!   `now_ns` is always supplied by the caller, so the same calls give the same history
+//! - A sample older than the newest bucket only lands in its own bucket if that still exists
+//! - Histories are volatile: they are left out of registry snapshots
+
+use alloc::vec::Vec;
+
+use crate::{MetricKind, Registry, RejectReason};
+
+/// Buckets kept per gauge series.
+pub const GAUGE_SERIES_BUCKETS: usize = 16;
+
+/// Bucket width of the gauge history; see [`Registry::set_gauge_time_series`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GaugeTimeSeries {
+    /// Nanoseconds per bucket (0 is treated as 1).
+    pub bucket_ns: u64,
+}
+
+impl GaugeTimeSeries {
+    fn bucket_ns(self) -> u64 {
+        self.bucket_ns.max(1)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Bucket {
+    epoch: u64,
+    min: i64,
+    max: i64,
+    last: i64,
+}
+
+/// Downsampled history of one gauge series, oldest bucket first.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct GaugeHistory {
+    buckets: Vec<Bucket>,
+}
+
+impl GaugeHistory {
+    fn record(&mut self, epoch: u64, value: i64) {
+        if let Some(bucket) = self.buckets.iter_mut().find(|bucket| bucket.epoch == epoch) {
+            bucket.min = bucket.min.min(value);
+            bucket.max = bucket.max.max(value);
+            bucket.last = value;
+            return;
+        }
+        if self.buckets.last().is_some_and(|newest| newest.epoch > epoch) {
+            return;
+        }
+        self.age_out(epoch);
+        if self.buckets.len() >= GAUGE_SERIES_BUCKETS {
+            self.buckets.remove(0);
+        }
+        self.buckets.push(Bucket { epoch, min: value, max: value, last: value });
+    }
+
+    fn age_out(&mut self, epoch: u64) {
+        self.buckets.retain(|bucket| epoch - bucket.epoch < GAUGE_SERIES_BUCKETS as u64);
+    }
+
+    fn live(&self, epoch: u64) -> impl Iterator<Item = &Bucket> {
+        self.buckets.iter().filter(move |bucket| {
+            bucket.epoch <= epoch && epoch - bucket.epoch < GAUGE_SERIES_BUCKETS as u64
+        })
+    }
+}
+
+impl Registry {
+    /// Turns the gauge history on (`Some`) or off (`None`).
+    ///
+    /// Every existing history is cleared, since bucket epochs of another width do not compare.
+    pub fn set_gauge_time_series(&mut self, config: Option<GaugeTimeSeries>) {
+        self.gauge_time_series = config;
+        for series in &mut self.series {
+            series.history = GaugeHistory::default();
+        }
+    }
+
+    /// [`Registry::gauge_set`] that also records `value` at `now_ns` in the gauge history.
+    pub fn gauge_set_at(
+        &mut self,
+        sender_service_id: u64,
+        name: &[u8],
+        labels: &[u8],
+        value: i64,
+        now_ns: u64,
+    ) -> Result<i64, RejectReason> {
+        let value = self.gauge_set(sender_service_id, name, labels, value)?;
+        if self.gauge_time_series.is_some() {
+            let idx = self.ensure_series(sender_service_id, MetricKind::Gauge, name, labels)?;
+            self.record_gauge_history(idx, value, now_ns);
+        }
+        Ok(value)
+    }
+
+    /// Records `value` at `now_ns` in the history of series `idx`, if the history is on.
+    pub(crate) fn record_gauge_history(&mut self, idx: usize, value: i64, now_ns: u64) {
+        if let Some(config) = self.gauge_time_series {
+            self.series[idx].history.record(now_ns / config.bucket_ns(), value);
+        }
+    }
+
+    /// The gauge's history at `now_ns` as `(bucket_start_ns, min, max, last)`, oldest first.
+    ///
+    /// Empty for an unknown series (they are scoped to the sender) or when the history is off.
+    pub fn gauge_series(
+        &self,
+        sender_service_id: u64,
+        name: &[u8],
+        labels: &[u8],
+        now_ns: u64,
+    ) -> Vec<(u64, i64, i64, i64)> {
+        let Some(config) = self.gauge_time_series else {
+            return Vec::new();
+        };
+        let Some(series) = self.series.iter().find(|entry| {
+            entry.sender_service_id == sender_service_id
+                && entry.kind == MetricKind::Gauge
+                && entry.name.as_slice() == name
+                && entry.labels.as_slice() == labels
+        }) else {
+            return Vec::new();
+        };
+        let bucket_ns = config.bucket_ns();
+        series
+            .history
+            .live(now_ns / bucket_ns)
+            .map(|bucket| {
+                (bucket.epoch.saturating_mul(bucket_ns), bucket.min, bucket.max, bucket.last)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEC: u64 = 1_000_000_000;
+
+    fn registry() -> Registry {
+        let mut reg = Registry::new();
+        reg.set_gauge_time_series(Some(GaugeTimeSeries { bucket_ns: SEC }));
+        reg
+    }
+
+    #[test]
+    fn samples_within_a_bucket_collapse_to_min_max_last() {
+        let mut reg = registry();
+        for (value, at) in [(5, 0), (-3, SEC / 4), (9, SEC / 2), (2, SEC - 1), (7, 3 * SEC)] {
+            reg.gauge_set_at(4, b"queue.depth", b"", value, at).unwrap();
+        }
+        assert_eq!(
+            reg.gauge_series(4, b"queue.depth", b"", 3 * SEC),
+            [(0, -3, 9, 2), (3 * SEC, 7, 7, 7)]
+        );
+        // The plain gauge still holds the last value; untimed sets record no history.
+        reg.gauge_set(4, b"queue.depth", b"", 100).unwrap();
+        assert_eq!(reg.gauge_series(4, b"queue.depth", b"", 3 * SEC).len(), 2);
+        // Scoped to the sender.
+        assert!(reg.gauge_series(5, b"queue.depth", b"", 3 * SEC).is_empty());
+    }
+
+    #[test]
+    fn old_buckets_age_out() {
+        let mut reg = registry();
+        let last = GAUGE_SERIES_BUCKETS as u64 + 4;
+        for second in 0..=last {
+            reg.gauge_set_at(4, b"mem.free", b"", second as i64, second * SEC).unwrap();
+        }
+        let series = reg.gauge_series(4, b"mem.free", b"", last * SEC);
+        assert_eq!(series.len(), GAUGE_SERIES_BUCKETS);
+        assert_eq!(series[0], (5 * SEC, 5, 5, 5));
+        assert_eq!(series[GAUGE_SERIES_BUCKETS - 1], (last * SEC, 20, 20, 20));
+
+        // Reads age buckets out too, without any new sample.
+        assert_eq!(reg.gauge_series(4, b"mem.free", b"", (last + 10) * SEC).len(), 6);
+        assert!(reg.gauge_series(4, b"mem.free", b"", (last + 100) * SEC).is_empty());
+
+        // A sample for a bucket that is gone is dropped; one after a long gap starts afresh.
+        reg.gauge_set_at(4, b"mem.free", b"", -1, SEC).unwrap();
+        reg.gauge_set_at(4, b"mem.free", b"", 42, 100 * SEC).unwrap();
+        assert_eq!(reg.gauge_series(4, b"mem.free", b"", 100 * SEC), [(100 * SEC, 42, 42, 42)]);
+    }
+
+    #[test]
+    fn history_is_off_by_default_and_not_persisted() {
+        let mut reg = Registry::new();
+        reg.gauge_set_at(4, b"mem.free", b"", 1, 0).unwrap();
+        assert!(reg.gauge_series(4, b"mem.free", b"", 0).is_empty());
+
+        let mut reg = registry();
+        reg.gauge_set_at(4, b"mem.free", b"", 1, 0).unwrap();
+        let mut restored = registry();
+        assert!(restored.restore(&reg.snapshot()).is_ok());
+        assert!(restored.gauge_series(4, b"mem.free", b"", 0).is_empty());
+        assert_eq!(restored.gauge_set(4, b"mem.free", b"", 2), Ok(2));
+    }
+}
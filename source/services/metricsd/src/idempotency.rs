@@ -0,0 +1,166 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+//
+//! CONTEXT: metricsd counter idempotency — per-sender recent-nonce window for retried adds
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: Host unit tests in this module
+//! ADR: docs/rfcs/RFC-0024-observability-v2-metrics-tracing-contract-v1.md
+//!
+//! A client that retries a counter add after `TimedOut` cannot know whether the first
+//! attempt was applied. Keyed by the kernel sender identity and the request nonce, the
+//! registry remembers the outcome of the last [`RECENT_NONCES_PER_SENDER`] adds per sender
+//! and answers a repeat with the recorded outcome instead of adding again.
+//!
+//! INVARIANTS:
+//! - Bounded: at most [`MAX_NONCE_SENDERS`] senders x [`RECENT_NONCES_PER_SENDER`] nonces;
+//!   the oldest entry (and, when full, the oldest sender) is evicted first
+//! - Only successful adds are recorded; a rejected add may be retried
+//! - The window is volatile (not part of the registry snapshot)
+
+use alloc::vec::Vec;
+
+use crate::{MetricKind, Registry, RejectReason, RATE_MAX_SUBJECTS};
+
+/// Recent counter-add nonces remembered per sender.
+pub const RECENT_NONCES_PER_SENDER: usize = 16;
+/// Senders with a tracked nonce window.
+pub const MAX_NONCE_SENDERS: usize = RATE_MAX_SUBJECTS;
+
+/// Outcome of [`Registry::counter_add_checked`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CounterAdd {
+    /// Counter value before the add.
+    pub before: u64,
+    /// Counter value after the add.
+    pub after: u64,
+    /// `true` when the nonce was already applied and nothing was added this time.
+    pub replayed: bool,
+}
+
+#[derive(Clone, Debug)]
+struct SenderNonces {
+    sender_service_id: u64,
+    /// `(nonce, outcome)`, oldest first.
+    recent: Vec<(u32, CounterAdd)>,
+}
+
+/// Bounded per-sender window of applied counter-add nonces.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct RecentNonces {
+    senders: Vec<SenderNonces>,
+}
+
+impl RecentNonces {
+    fn lookup(&self, sender_service_id: u64, nonce: u32) -> Option<CounterAdd> {
+        let sender = self.senders.iter().find(|s| s.sender_service_id == sender_service_id)?;
+        sender.recent.iter().find(|(seen, _)| *seen == nonce).map(|(_, applied)| *applied)
+    }
+
+    fn record(&mut self, sender_service_id: u64, nonce: u32, applied: CounterAdd) {
+        let pos = match self.senders.iter().position(|s| s.sender_service_id == sender_service_id) {
+            Some(pos) => pos,
+            None => {
+                if self.senders.len() >= MAX_NONCE_SENDERS {
+                    self.senders.remove(0);
+                }
+                self.senders.push(SenderNonces { sender_service_id, recent: Vec::new() });
+                self.senders.len() - 1
+            }
+        };
+        let recent = &mut self.senders[pos].recent;
+        if recent.len() >= RECENT_NONCES_PER_SENDER {
+            recent.remove(0);
+        }
+        recent.push((nonce, applied));
+    }
+}
+
+impl Registry {
+    /// Adds `delta` to a counter and returns the values before and after.
+    ///
+    /// With `Some(nonce)`, a nonce this sender already applied within its recent window is a
+    /// no-op: the recorded outcome comes back with `replayed: true`. `None` always adds.
+    pub fn counter_add_checked(
+        &mut self,
+        sender_service_id: u64,
+        name: &[u8],
+        labels: &[u8],
+        delta: u64,
+        nonce: Option<u32>,
+    ) -> Result<CounterAdd, RejectReason> {
+        self.check_sender(sender_service_id)?;
+        if let Some(applied) = nonce.and_then(|n| self.recent_nonces.lookup(sender_service_id, n)) {
+            return Ok(CounterAdd { replayed: true, ..applied });
+        }
+        let idx = self.ensure_series(sender_service_id, MetricKind::Counter, name, labels)?;
+        let series = &mut self.series[idx];
+        let before = series.counter_value;
+        series.counter_value = before.saturating_add(delta);
+        let applied = CounterAdd { before, after: series.counter_value, replayed: false };
+        self.check_alerts(idx);
+        if let Some(nonce) = nonce {
+            self.recent_nonces.record(sender_service_id, nonce, applied);
+        }
+        Ok(applied)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeated_nonce_applies_once() {
+        let mut reg = Registry::new();
+        let first = reg.counter_add_checked(7, b"boot.events", b"", 5, Some(41)).unwrap();
+        assert_eq!(first, CounterAdd { before: 0, after: 5, replayed: false });
+        let retry = reg.counter_add_checked(7, b"boot.events", b"", 5, Some(41)).unwrap();
+        assert_eq!(retry, CounterAdd { before: 0, after: 5, replayed: true });
+        assert_eq!(reg.counter_inc(7, b"boot.events", b"", 0), Ok(5));
+
+        // The nonce is scoped to the sender.
+        let other = reg.counter_add_checked(8, b"boot.events", b"", 1, Some(41)).unwrap();
+        assert!(!other.replayed);
+    }
+
+    #[test]
+    fn distinct_nonces_accumulate() {
+        let mut reg = Registry::new();
+        for nonce in 1..=4 {
+            reg.counter_add_checked(7, b"boot.events", b"", 2, Some(nonce)).unwrap();
+        }
+        let last = reg.counter_add_checked(7, b"boot.events", b"", 2, None).unwrap();
+        assert_eq!(last, CounterAdd { before: 8, after: 10, replayed: false });
+        // Without a nonce nothing is deduplicated.
+        assert_eq!(reg.counter_add_checked(7, b"boot.events", b"", 2, None).unwrap().after, 12);
+    }
+
+    #[test]
+    fn test_reject_window_is_bounded() {
+        let mut reg = Registry::new();
+        for nonce in 0..=RECENT_NONCES_PER_SENDER as u32 {
+            reg.counter_add_checked(7, b"m", b"", 1, Some(nonce)).unwrap();
+        }
+        // Nonce 0 fell out of the window and is applied again; later ones are remembered.
+        assert!(!reg.counter_add_checked(7, b"m", b"", 1, Some(0)).unwrap().replayed);
+        assert!(reg.counter_add_checked(7, b"m", b"", 1, Some(2)).unwrap().replayed);
+
+        // A rejected add is not recorded, so its retry is evaluated afresh.
+        assert_eq!(
+            reg.counter_add_checked(7, b"", b"", 1, Some(99)),
+            Err(RejectReason::InvalidArgs)
+        );
+        assert!(!reg.counter_add_checked(7, b"m", b"", 1, Some(99)).unwrap().replayed);
+
+        let mut window = RecentNonces::default();
+        let applied = CounterAdd { before: 0, after: 1, replayed: false };
+        for sender in 0..=MAX_NONCE_SENDERS as u64 {
+            window.record(sender, 1, applied);
+        }
+        assert_eq!(window.senders.len(), MAX_NONCE_SENDERS);
+        assert_eq!(window.lookup(0, 1), None);
+        assert_eq!(window.lookup(MAX_NONCE_SENDERS as u64, 1), Some(applied));
+    }
+}
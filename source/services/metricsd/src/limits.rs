@@ -0,0 +1,461 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+//
+//! CONTEXT: metricsd runtime limits — defaults, the TOML-subset config parser, rate limiter
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: Host unit tests in this module
+//! ADR: docs/rfcs/RFC-0024-observability-v2-metrics-tracing-contract-v1.md
+//!
+//! `Registry::apply_limits` swaps the registry's limits at runtime, e.g. after a new config
+//! was read from [`LIMITS_STATE_KEY`], without a restart that would drop the live state.
+//!
+//! INVARIANTS:
+//! - Configured bounds may only tighten the wire caps in `nexus_metrics`
+//! - Unknown keys, malformed lines and out-of-range values reject the whole config
+//! - A reload never leaves live series or spans outside the limits in force
+
+use alloc::vec::Vec;
+
+use nexus_metrics::{MAX_ATTRS_LEN, MAX_LABELS_LEN, MAX_METRIC_NAME_LEN, MAX_SPAN_NAME_LEN};
+
+use crate::{
+    LimitKind, Registry, RejectReason, MAX_ENDED_SPANS, MAX_LIVE_SPANS, MAX_SERIES_PER_METRIC,
+    MAX_SERIES_TOTAL, RATE_MAX_BYTES_PER_WINDOW, RATE_MAX_EVENTS_PER_WINDOW, RATE_MAX_SUBJECTS,
+    RATE_WINDOW_NS, ROLLUP_TRACKED_METRICS, SELF_SENDER_ID, SPAN_MAX_AGE_NS,
+};
+
+/// Runtime config for metrics/tracing bounds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RuntimeLimits {
+    pub max_series_total: usize,
+    pub max_series_per_metric: usize,
+    pub max_live_spans: usize,
+    pub max_ended_spans: usize,
+    pub span_max_age_ns: u64,
+    /// `span_start` admits 1 in this many spans, chosen by span ID (1 keeps every span).
+    pub span_sample_rate_denominator: u32,
+    pub rate_window_ns: u64,
+    pub rate_max_events_per_window: u32,
+    pub rate_max_bytes_per_window: u64,
+    pub rate_max_subjects: usize,
+    pub max_metric_name_len: usize,
+    pub max_labels_len: usize,
+    pub max_span_name_len: usize,
+    pub max_attrs_len: usize,
+    pub retention_enabled: bool,
+    pub retention_max_segments: u32,
+    pub retention_max_records_per_segment: u32,
+    pub retention_rollup_every: u32,
+    pub retention_best_effort_retries: u32,
+    pub retention_critical_retries: u32,
+    pub retention_ttl_windows: u32,
+    pub retention_gc_batch: u32,
+    pub retention_rollup_top_k: u32,
+    pub retention_rollup_hash_bits: u32,
+}
+
+impl Default for RuntimeLimits {
+    fn default() -> Self {
+        Self {
+            max_series_total: MAX_SERIES_TOTAL,
+            max_series_per_metric: MAX_SERIES_PER_METRIC,
+            max_live_spans: MAX_LIVE_SPANS,
+            max_ended_spans: MAX_ENDED_SPANS,
+            span_max_age_ns: SPAN_MAX_AGE_NS,
+            span_sample_rate_denominator: 1,
+            rate_window_ns: RATE_WINDOW_NS,
+            rate_max_events_per_window: RATE_MAX_EVENTS_PER_WINDOW,
+            rate_max_bytes_per_window: RATE_MAX_BYTES_PER_WINDOW,
+            rate_max_subjects: RATE_MAX_SUBJECTS,
+            max_metric_name_len: MAX_METRIC_NAME_LEN,
+            max_labels_len: MAX_LABELS_LEN,
+            max_span_name_len: MAX_SPAN_NAME_LEN,
+            max_attrs_len: MAX_ATTRS_LEN,
+            retention_enabled: true,
+            retention_max_segments: 8,
+            retention_max_records_per_segment: 64,
+            retention_rollup_every: 16,
+            retention_best_effort_retries: 1,
+            retention_critical_retries: 2,
+            retention_ttl_windows: 12,
+            retention_gc_batch: 2,
+            retention_rollup_top_k: 4,
+            retention_rollup_hash_bits: 32,
+        }
+    }
+}
+
+/// statefs key a hot-reloaded limits config (same TOML subset as `metrics.toml`) is read from.
+pub const LIMITS_STATE_KEY: &str = "/state/metrics/limits";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConfigError {
+    InvalidValue,
+    UnknownKey,
+}
+
+impl RuntimeLimits {
+    /// Parses observability runtime limits from `recipes/observability/metrics.toml`.
+    pub fn parse_toml(input: &str) -> Result<Self, ConfigError> {
+        let mut cfg = Self::default();
+        let mut section = "";
+        for raw in input.lines() {
+            let line = raw.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            if line.starts_with('[') && line.ends_with(']') {
+                section = &line[1..line.len().saturating_sub(1)];
+                continue;
+            }
+            let Some((k, v)) = line.split_once('=') else {
+                return Err(ConfigError::InvalidValue);
+            };
+            let key = k.trim();
+            let value_u64 = v.trim().parse::<u64>().map_err(|_| ConfigError::InvalidValue)?;
+            match (section, key) {
+                ("metrics", "max_series_total") => cfg.max_series_total = value_u64 as usize,
+                ("metrics", "max_series_per_metric") => {
+                    cfg.max_series_per_metric = value_u64 as usize
+                }
+                ("metrics", "max_live_spans") => cfg.max_live_spans = value_u64 as usize,
+                ("metrics", "max_ended_spans") => cfg.max_ended_spans = value_u64 as usize,
+                ("metrics", "span_max_age_ns") => cfg.span_max_age_ns = value_u64,
+                ("metrics", "span_sample_rate_denominator") => {
+                    cfg.span_sample_rate_denominator =
+                        u32::try_from(value_u64).map_err(|_| ConfigError::InvalidValue)?
+                }
+                ("ingest", "rate_window_ns") => cfg.rate_window_ns = value_u64,
+                ("ingest", "max_events_per_window") => {
+                    cfg.rate_max_events_per_window = value_u64 as u32
+                }
+                ("ingest", "max_bytes_per_window") => cfg.rate_max_bytes_per_window = value_u64,
+                ("ingest", "max_subjects") => cfg.rate_max_subjects = value_u64 as usize,
+                ("wire", "max_metric_name_len") => cfg.max_metric_name_len = value_u64 as usize,
+                ("wire", "max_labels_len") => cfg.max_labels_len = value_u64 as usize,
+                ("wire", "max_span_name_len") => cfg.max_span_name_len = value_u64 as usize,
+                ("wire", "max_attrs_len") => cfg.max_attrs_len = value_u64 as usize,
+                ("retention", "enabled") => cfg.retention_enabled = value_u64 != 0,
+                ("retention", "max_segments") => cfg.retention_max_segments = value_u64 as u32,
+                ("retention", "max_records_per_segment") => {
+                    cfg.retention_max_records_per_segment = value_u64 as u32
+                }
+                ("retention", "rollup_every") => cfg.retention_rollup_every = value_u64 as u32,
+                ("retention", "best_effort_retries") => {
+                    cfg.retention_best_effort_retries = value_u64 as u32
+                }
+                ("retention", "critical_retries") => {
+                    cfg.retention_critical_retries = value_u64 as u32
+                }
+                ("retention", "ttl_windows") => cfg.retention_ttl_windows = value_u64 as u32,
+                ("retention", "gc_batch") => cfg.retention_gc_batch = value_u64 as u32,
+                ("retention", "rollup_top_k") => cfg.retention_rollup_top_k = value_u64 as u32,
+                ("retention", "rollup_hash_bits") => {
+                    cfg.retention_rollup_hash_bits = value_u64 as u32
+                }
+                _ => return Err(ConfigError::UnknownKey),
+            }
+        }
+        cfg.validate()?;
+        Ok(cfg)
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), ConfigError> {
+        if self.max_series_total == 0
+            || self.max_series_per_metric == 0
+            || self.max_live_spans == 0
+            || self.max_ended_spans == 0
+            || self.span_sample_rate_denominator == 0
+            || self.rate_window_ns == 0
+            || self.rate_max_events_per_window == 0
+            || self.rate_max_bytes_per_window == 0
+            || self.rate_max_subjects == 0
+            || self.max_metric_name_len == 0
+            || self.max_labels_len == 0
+            || self.max_span_name_len == 0
+            || self.max_attrs_len == 0
+            || self.retention_max_segments == 0
+            || self.retention_max_records_per_segment == 0
+            || self.retention_rollup_every == 0
+            || self.retention_best_effort_retries == 0
+            || self.retention_critical_retries == 0
+            || self.retention_ttl_windows == 0
+            || self.retention_gc_batch == 0
+            || self.retention_rollup_top_k == 0
+            || self.retention_rollup_hash_bits == 0
+        {
+            return Err(ConfigError::InvalidValue);
+        }
+        // Wire/runtime limits may only tighten, never exceed client wire contract.
+        if self.max_metric_name_len > MAX_METRIC_NAME_LEN
+            || self.max_labels_len > MAX_LABELS_LEN
+            || self.max_span_name_len > MAX_SPAN_NAME_LEN
+            || self.max_attrs_len > MAX_ATTRS_LEN
+        {
+            return Err(ConfigError::InvalidValue);
+        }
+        if self.retention_rollup_top_k as usize > ROLLUP_TRACKED_METRICS
+            || self.retention_rollup_hash_bits > 64
+        {
+            return Err(ConfigError::InvalidValue);
+        }
+        Ok(())
+    }
+}
+
+impl Registry {
+    /// Replaces the registry limits at runtime, keeping every series and span.
+    ///
+    /// Loosening always succeeds. A tighter config is accepted only if the live state already
+    /// fits it (series in total and per metric, live spans, and the name/labels/attrs lengths
+    /// they hold); otherwise the first bound that does not fit comes back as `OverLimit` and
+    /// the old limits stay. An invalid config is `InvalidArgs`. Ended spans beyond a smaller
+    /// `max_ended_spans` age out as new spans end. The ingest rate limits belong to
+    /// `RateLimiter` and are not applied here.
+    pub fn apply_limits(&mut self, new: RuntimeLimits) -> Result<(), RejectReason> {
+        new.validate().map_err(|_| RejectReason::InvalidArgs)?;
+        let fields_fit = self.series.iter().all(|entry| {
+            entry.name.len() <= new.max_metric_name_len && entry.labels.len() <= new.max_labels_len
+        }) && self.live_spans.iter().all(|span| span.fields_fit(&new));
+        if !fields_fit {
+            return Err(RejectReason::OverLimit(LimitKind::FieldLen));
+        }
+        if self.client_series_count() > new.max_series_total {
+            return Err(RejectReason::OverLimit(LimitKind::SeriesTotal));
+        }
+        let client_series =
+            || self.series.iter().filter(|entry| entry.sender_service_id != SELF_SENDER_ID);
+        let widest_metric = client_series()
+            .map(|entry| {
+                client_series()
+                    .filter(|other| other.kind == entry.kind && other.name == entry.name)
+                    .count()
+            })
+            .max()
+            .unwrap_or(0);
+        if widest_metric > new.max_series_per_metric {
+            return Err(RejectReason::OverLimit(LimitKind::SeriesPerMetric));
+        }
+        if self.live_spans.len() > new.max_live_spans {
+            return Err(RejectReason::OverLimit(LimitKind::LiveSpans));
+        }
+        self.limits = new;
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct RateWindow {
+    sender_service_id: u64,
+    window_start_ns: u64,
+    used: u32,
+    bytes: u64,
+}
+
+/// Deterministic per-sender event and byte limiter.
+pub struct RateLimiter {
+    windows: Vec<RateWindow>,
+    limits: RuntimeLimits,
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        Self::new_with_limits(RuntimeLimits::default())
+    }
+
+    pub fn new_with_limits(limits: RuntimeLimits) -> Self {
+        Self { windows: Vec::new(), limits }
+    }
+
+    /// Charges one event of `frame_len` bytes to the sender's window.
+    ///
+    /// Returns `true` (nothing charged) once either the event count or the byte budget of
+    /// the current window would be exceeded.
+    pub fn is_limited(&mut self, sender_service_id: u64, now_ns: u64, frame_len: usize) -> bool {
+        let frame_len = frame_len as u64;
+        if let Some(pos) =
+            self.windows.iter().position(|window| window.sender_service_id == sender_service_id)
+        {
+            let window = &mut self.windows[pos];
+            if now_ns.saturating_sub(window.window_start_ns) >= self.limits.rate_window_ns {
+                window.window_start_ns = now_ns;
+                window.used = 0;
+                window.bytes = 0;
+            }
+            if window.used >= self.limits.rate_max_events_per_window
+                || window.bytes.saturating_add(frame_len) > self.limits.rate_max_bytes_per_window
+            {
+                return true;
+            }
+            window.used = window.used.saturating_add(1);
+            window.bytes = window.bytes.saturating_add(frame_len);
+            return false;
+        }
+        if self.windows.len() >= self.limits.rate_max_subjects
+            || frame_len > self.limits.rate_max_bytes_per_window
+        {
+            return true;
+        }
+        self.windows.push(RateWindow {
+            sender_service_id,
+            window_start_ns: now_ns,
+            used: 1,
+            bytes: frame_len,
+        });
+        false
+    }
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SpanStartArgs;
+
+    /// Four series (three of `rx`) and two live spans from sender 0x51.
+    fn populated() -> Registry {
+        let mut reg = Registry::new();
+        for labels in [&b"if=eth0\n"[..], b"if=eth1\n", b"if=wlan0\n"] {
+            reg.counter_inc(0x51, b"rx", labels, 1).unwrap();
+        }
+        reg.gauge_set(0x51, b"mem.free", b"", 7).unwrap();
+        for span_id in [(0x51 << 32) | 1, (0x51 << 32) | 2] {
+            let args = SpanStartArgs {
+                sender_service_id: 0x51,
+                span_id,
+                trace_id: 9,
+                parent_span_id: 0,
+                start_ns: 0,
+                name: b"boot",
+                attrs: b"",
+            };
+            reg.span_start(args).unwrap();
+        }
+        reg
+    }
+
+    #[test]
+    fn apply_limits_loosens_and_tightens_down_to_current_usage() {
+        let mut reg = populated();
+        let loose = RuntimeLimits { max_series_total: 128, ..RuntimeLimits::default() };
+        assert_eq!(reg.apply_limits(loose), Ok(()));
+
+        let exact = RuntimeLimits {
+            max_series_total: 4,
+            max_series_per_metric: 3,
+            max_live_spans: 2,
+            max_metric_name_len: 8,
+            max_labels_len: 9,
+            ..RuntimeLimits::default()
+        };
+        assert_eq!(reg.apply_limits(exact), Ok(()));
+        // The tighter limits are in force for new series and spans; existing ones still update.
+        assert_eq!(
+            reg.counter_inc(0x51, b"rx", b"if=lo\n", 1),
+            Err(RejectReason::OverLimit(LimitKind::SeriesTotal))
+        );
+        assert_eq!(reg.counter_inc(0x51, b"rx", b"if=eth0\n", 1), Ok(2));
+    }
+
+    #[test]
+    fn test_reject_apply_limits_below_current_usage() {
+        let mut reg = populated();
+        let fits = RuntimeLimits::default();
+        let cases = [
+            (RuntimeLimits { max_series_total: 3, ..fits }, LimitKind::SeriesTotal),
+            (RuntimeLimits { max_series_per_metric: 2, ..fits }, LimitKind::SeriesPerMetric),
+            (RuntimeLimits { max_live_spans: 1, ..fits }, LimitKind::LiveSpans),
+            (RuntimeLimits { max_labels_len: 8, ..fits }, LimitKind::FieldLen),
+            (RuntimeLimits { max_span_name_len: 3, ..fits }, LimitKind::FieldLen),
+        ];
+        for (limits, kind) in cases {
+            assert_eq!(reg.apply_limits(limits), Err(RejectReason::OverLimit(kind)));
+        }
+        let invalid = RuntimeLimits { max_live_spans: 0, ..fits };
+        assert_eq!(reg.apply_limits(invalid), Err(RejectReason::InvalidArgs));
+
+        // Every rejected reload left the old limits in place.
+        assert_eq!(reg.counter_inc(0x51, b"rx", b"if=lo\n", 1), Ok(1));
+    }
+
+    #[test]
+    fn test_parse_runtime_limits_valid() {
+        let toml = "\
+[metrics]
+max_series_total = 8
+max_series_per_metric = 4
+max_live_spans = 5
+span_max_age_ns = 7000
+span_sample_rate_denominator = 8
+
+[ingest]
+rate_window_ns = 2000
+max_events_per_window = 3
+max_bytes_per_window = 4096
+max_subjects = 2
+
+[wire]
+max_metric_name_len = 32
+max_labels_len = 64
+max_span_name_len = 32
+max_attrs_len = 64
+
+[retention]
+enabled = 1
+max_segments = 2
+max_records_per_segment = 2
+rollup_every = 2
+best_effort_retries = 1
+critical_retries = 3
+ttl_windows = 2
+gc_batch = 1
+";
+        let limits = RuntimeLimits::parse_toml(toml).expect("valid limits parse");
+        assert_eq!(limits.max_series_total, 8);
+        assert_eq!(limits.span_max_age_ns, 7000);
+        assert_eq!(limits.span_sample_rate_denominator, 8);
+        assert_eq!(limits.rate_max_events_per_window, 3);
+        assert_eq!(limits.rate_max_bytes_per_window, 4096);
+        assert_eq!(limits.max_attrs_len, 64);
+        assert_eq!(limits.retention_max_segments, 2);
+        assert_eq!(limits.retention_critical_retries, 3);
+        assert_eq!(limits.retention_ttl_windows, 2);
+    }
+
+    #[test]
+    fn test_parse_runtime_limits_rejects_invalid_values() {
+        let toml = "\
+[wire]
+max_metric_name_len = 999
+";
+        assert_eq!(RuntimeLimits::parse_toml(toml), Err(ConfigError::InvalidValue));
+        let toml = "\
+[metrics]
+span_sample_rate_denominator = 0
+";
+        assert_eq!(RuntimeLimits::parse_toml(toml), Err(ConfigError::InvalidValue));
+    }
+
+    #[test]
+    fn test_parse_runtime_limits_rejects_untracked_top_k() {
+        let toml = "\
+[retention]
+rollup_top_k = 33
+";
+        assert_eq!(RuntimeLimits::parse_toml(toml), Err(ConfigError::InvalidValue));
+        let toml = "\
+[retention]
+rollup_top_k = 8
+rollup_hash_bits = 16
+";
+        let limits = RuntimeLimits::parse_toml(toml).expect("valid rollup limits");
+        assert_eq!((limits.retention_rollup_top_k, limits.retention_rollup_hash_bits), (8, 16));
+    }
+}
@@ -17,7 +17,6 @@ extern crate alloc;
 
 use alloc::boxed::Box;
 use alloc::format;
-use alloc::string::String;
 use alloc::vec::Vec;
 use core::time::Duration;
 
@@ -25,14 +24,22 @@ use nexus_abi::{debug_putc, nsec, yield_};
 use nexus_ipc::budget::{self, NonceMismatchBudget, RouteRetryOutcome};
 use nexus_ipc::{Client as _, KernelClient, KernelServer, Server as _, Wait};
 use nexus_metrics::{
-    decode_request, encode_status_response, DecodeError, Request, OP_COUNTER_INC, OP_GAUGE_SET,
-    OP_HIST_OBSERVE, OP_PING, OP_SPAN_END, OP_SPAN_START, STATUS_INVALID_ARGS, STATUS_NOT_FOUND,
-    STATUS_OK, STATUS_OVER_LIMIT, STATUS_RATE_LIMITED,
+    decode_request, encode_gauge_bound_response, encode_hist_quantile_response,
+    encode_status_response, encode_status_response_ex, DecodeError, Request, OP_BATCH,
+    OP_COUNTER_INC, OP_GAUGE_SET, OP_HIST_OBSERVE, OP_PING, OP_SPAN_END, OP_SPAN_START,
+    OP_SPAN_START_EX, OP_WINDOWED_INC, STATUS_INVALID_ARGS, STATUS_NOT_FOUND, STATUS_OK,
+    STATUS_OVER_LIMIT, STATUS_RATE_LIMITED,
 };
 
+use crate::records::{
+    metric_counter_record, metric_gauge_record, metric_hist_record, span_end_record,
+};
+use crate::snapshot_log::{
+    log_counter_snapshot, log_gauge_snapshot, log_hist_snapshot, log_span_end,
+};
 use crate::{
-    RateLimiter, Registry, RejectReason, RetentionEngine, RetentionEventKind, RuntimeLimits,
-    SpanStartArgs,
+    EndedSpan, LimitKind, RateLimiter, Registry, RejectReason, RetentionEngine, RetentionEventKind,
+    RuntimeLimits, SpanStartArgs, REGISTRY_STATE_KEY,
 };
 
 use statefs::client::StatefsClient;
@@ -67,6 +74,7 @@ struct RetentionSink {
     wal_verified_emitted: bool,
     rollup_10s_proof_emitted: bool,
     rollup_60s_proof_emitted: bool,
+    updates_since_persist: u32,
 }
 
 impl RetentionSink {
@@ -102,19 +110,47 @@ impl RetentionSink {
             wal_verified_emitted: false,
             rollup_10s_proof_emitted: false,
             rollup_60s_proof_emitted: false,
+            updates_since_persist: 0,
+        }
+    }
+
+    /// Seeds `registry` from the persisted snapshot; absent or rejected means start empty.
+    fn restore_registry(&self, registry: &mut Registry) {
+        let Some(proof) = self.proof_client.as_ref() else {
+            return;
+        };
+        let Ok(blob) = proof.get(REGISTRY_STATE_KEY) else {
+            return;
+        };
+        if registry.restore(&blob).is_err() {
+            emit_line("metricsd: registry snapshot rejected");
+        }
+    }
+
+    /// Persists a registry snapshot every `retention_rollup_every` accepted requests.
+    fn persist_registry(&mut self, registry: &Registry) {
+        let Some(client) = self.client.as_ref() else {
+            return;
+        };
+        self.updates_since_persist = self.updates_since_persist.saturating_add(1);
+        if self.updates_since_persist < self.limits.retention_rollup_every.max(1) {
+            return;
         }
+        self.updates_since_persist = 0;
+        let retries = self.limits.retention_best_effort_retries;
+        let _ = put_with_retries(client, REGISTRY_STATE_KEY, &registry.snapshot(), retries);
     }
 
-    fn record_metric(&mut self, record: &str) {
-        self.record(RetentionEventKind::Metric, record.as_bytes());
+    fn record_metric(&mut self, name: &[u8], record: &str) {
+        self.record(RetentionEventKind::Metric, name, record.as_bytes());
     }
 
     fn record_span(&mut self, record: &str) {
-        self.record(RetentionEventKind::Span, record.as_bytes());
+        self.record(RetentionEventKind::Span, &[], record.as_bytes());
     }
 
-    fn record(&mut self, kind: RetentionEventKind, record: &[u8]) {
-        let Some(update) = self.engine.append(kind, record) else {
+    fn record(&mut self, kind: RetentionEventKind, name: &[u8], record: &[u8]) {
+        let Some(update) = self.engine.append(kind, name, record) else {
             return;
         };
         let Some(client) = self.client.as_ref() else {
@@ -250,6 +286,7 @@ pub fn service_main_loop(notifier: ReadyNotifier) -> MetricsResult<()> {
     let mut registry = Registry::new_with_limits(limits);
     let mut limiter = RateLimiter::new_with_limits(limits);
     let mut retention = RetentionSink::new(limits);
+    retention.restore_registry(&mut registry);
     let mut reject_invalid_args_emitted = false;
     let mut reject_over_limit_emitted = false;
     let mut reject_rate_limited_emitted = false;
@@ -266,7 +303,8 @@ pub fn service_main_loop(notifier: ReadyNotifier) -> MetricsResult<()> {
                         fallback_now
                     }
                 };
-                let (rsp, reject_status) = handle_frame(
+                registry.emit_self_metrics(now);
+                let (rsp, reject) = handle_frame(
                     &mut registry,
                     &mut limiter,
                     &mut retention,
@@ -274,8 +312,21 @@ pub fn service_main_loop(notifier: ReadyNotifier) -> MetricsResult<()> {
                     now,
                     frame.as_slice(),
                 );
-                if let Some(status) = reject_status {
-                    match status {
+                if limits.span_max_age_ns != 0 {
+                    for ended in registry.expire_stale_spans(now, limits.span_max_age_ns) {
+                        record_ended_span(&mut retention, &ended);
+                    }
+                }
+                if reject.is_none() {
+                    retention.persist_registry(&registry);
+                }
+                if let Some(reject) = reject {
+                    registry.note_reject(
+                        sender_service_id,
+                        frame.get(3).copied().unwrap_or(0),
+                        reject,
+                    );
+                    match reject.status() {
                         STATUS_INVALID_ARGS if !reject_invalid_args_emitted => {
                             emit_line("metricsd: reject invalid_args");
                             reject_invalid_args_emitted = true;
@@ -312,56 +363,81 @@ fn handle_frame(
     sender_service_id: u64,
     now_ns: u64,
     frame: &[u8],
-) -> (Vec<u8>, Option<u8>) {
+) -> (Vec<u8>, Option<RejectReason>) {
     let op = frame.get(3).copied().unwrap_or(0);
     let decoded = match decode_request(frame) {
         Ok(req) => req,
-        Err(DecodeError::Malformed) => {
-            return (encode_status_response(op, 0, STATUS_INVALID_ARGS), Some(STATUS_INVALID_ARGS))
+        Err(DecodeError::Malformed | DecodeError::Unsupported) => {
+            return reject_rsp(op, 0, RejectReason::InvalidArgs)
         }
         Err(DecodeError::OverLimit) => {
-            return (encode_status_response(op, 0, STATUS_OVER_LIMIT), Some(STATUS_OVER_LIMIT))
-        }
-        Err(DecodeError::Unsupported) => {
-            return (encode_status_response(op, 0, STATUS_INVALID_ARGS), Some(STATUS_INVALID_ARGS))
+            return reject_rsp(op, 0, RejectReason::OverLimit(LimitKind::FieldLen))
         }
     };
 
-    // Budget all mutating operations except ping.
-    if !matches!(decoded, Request::Ping { .. }) && limiter.is_limited(sender_service_id, now_ns) {
-        let (op, nonce) = req_op_nonce(decoded);
-        return (encode_status_response(op, nonce, STATUS_RATE_LIMITED), Some(STATUS_RATE_LIMITED));
+    // Budget every operation (queries included) except ping.
+    if !matches!(decoded, Request::Ping { .. })
+        && limiter.is_limited(sender_service_id, now_ns, frame.len())
+    {
+        let (op, nonce) = decoded.op_nonce();
+        return reject_rsp(op, nonce, RejectReason::RateLimited);
     }
+    apply_request(registry, retention, sender_service_id, now_ns, decoded)
+}
 
+fn apply_request(
+    registry: &mut Registry,
+    retention: &mut RetentionSink,
+    sender_service_id: u64,
+    now_ns: u64,
+    decoded: Request<'_>,
+) -> (Vec<u8>, Option<RejectReason>) {
     match decoded {
         Request::CounterInc { nonce, name, labels, delta } => {
-            let result = registry.counter_inc(sender_service_id, name, labels, delta);
+            // Nonce 0 is the "no nonce" value; any other nonce makes a retried add idempotent.
+            let dedup = (nonce != 0).then_some(nonce);
+            let result =
+                registry.counter_add_checked(sender_service_id, name, labels, delta, dedup);
             match result {
-                Ok(value) => {
-                    log_counter_snapshot(name, value);
-                    retention.record_metric(metric_counter_record(name, value).as_str());
+                Ok(add) => {
+                    if !add.replayed {
+                        log_counter_snapshot(name, add.after);
+                        retention
+                            .record_metric(name, metric_counter_record(name, add.after).as_str());
+                    }
                     (encode_status_response(OP_COUNTER_INC, nonce, STATUS_OK), None)
                 }
                 Err(reject) => reject_rsp(OP_COUNTER_INC, nonce, reject),
             }
         }
         Request::GaugeSet { nonce, name, labels, value } => {
-            let result = registry.gauge_set(sender_service_id, name, labels, value);
+            let result = registry.gauge_set_at(sender_service_id, name, labels, value, now_ns);
             match result {
                 Ok(current) => {
                     log_gauge_snapshot(name, current);
-                    retention.record_metric(metric_gauge_record(name, current).as_str());
+                    retention.record_metric(name, metric_gauge_record(name, current).as_str());
                     (encode_status_response(OP_GAUGE_SET, nonce, STATUS_OK), None)
                 }
                 Err(reject) => reject_rsp(OP_GAUGE_SET, nonce, reject),
             }
         }
+        Request::GaugeBound { nonce, bound, name, labels, value } => {
+            let result = registry.gauge_bound(sender_service_id, bound, name, labels, value);
+            match result {
+                Ok(current) => {
+                    log_gauge_snapshot(name, current);
+                    retention.record_metric(name, metric_gauge_record(name, current).as_str());
+                    (encode_gauge_bound_response(bound, nonce, STATUS_OK, current), None)
+                }
+                Err(reject) => reject_rsp(bound.op(), nonce, reject),
+            }
+        }
         Request::HistObserve { nonce, name, labels, value } => {
             let result = registry.hist_observe(sender_service_id, name, labels, value);
             match result {
                 Ok((count, sum)) => {
                     log_hist_snapshot(name, count, sum);
-                    retention.record_metric(metric_hist_record(name, count, sum).as_str());
+                    retention.record_metric(name, metric_hist_record(name, count, sum).as_str());
                     (encode_status_response(OP_HIST_OBSERVE, nonce, STATUS_OK), None)
                 }
                 Err(reject) => reject_rsp(OP_HIST_OBSERVE, nonce, reject),
@@ -382,57 +458,75 @@ fn handle_frame(
                 Err(reject) => reject_rsp(OP_SPAN_START, nonce, reject),
             }
         }
+        Request::SpanStartEx {
+            nonce,
+            span_id,
+            trace_id,
+            parent_span_id,
+            start_ns,
+            name,
+            attrs,
+            links,
+        } => {
+            let links: Vec<u64> = links.iter().map(|link| link.0).collect();
+            let args = SpanStartArgs {
+                sender_service_id,
+                span_id: span_id.0,
+                trace_id: trace_id.0,
+                parent_span_id: parent_span_id.0,
+                start_ns,
+                name,
+                attrs,
+            };
+            match registry.span_start_linked(args, &links) {
+                Ok(()) => (encode_status_response(OP_SPAN_START_EX, nonce, STATUS_OK), None),
+                Err(reject) => reject_rsp(OP_SPAN_START_EX, nonce, reject),
+            }
+        }
         Request::SpanEnd { nonce, span_id, end_ns, status, attrs } => {
             let result = registry.span_end(sender_service_id, span_id.0, end_ns, status, attrs);
             match result {
                 Ok(ended) => {
-                    log_span_end(
-                        &ended.name,
-                        ended.parent_span_id,
-                        ended.duration_ns,
-                        ended.status,
-                        &ended.start_attrs,
-                        &ended.end_attrs,
-                    );
-                    retention.record_span(
-                        span_end_record(
-                            &ended.name,
-                            ended.parent_span_id,
-                            ended.duration_ns,
-                            ended.status,
-                            &ended.start_attrs,
-                            &ended.end_attrs,
-                        )
-                        .as_str(),
-                    );
+                    record_ended_span(retention, &ended);
                     (encode_status_response(OP_SPAN_END, nonce, STATUS_OK), None)
                 }
                 Err(reject) => reject_rsp(OP_SPAN_END, nonce, reject),
             }
         }
         Request::Ping { nonce } => (encode_status_response(OP_PING, nonce, STATUS_OK), None),
+        Request::SpansScrape { nonce } => (registry.spans_scrape_response(nonce), None),
+        Request::Rejections { nonce } => (registry.rejections_response(nonce), None),
+        Request::HistQuantile { nonce, name, labels, percentile } => {
+            match registry.hist_percentile(sender_service_id, name, labels, percentile) {
+                Some(value) => (encode_hist_quantile_response(nonce, STATUS_OK, value), None),
+                None => (encode_hist_quantile_response(nonce, STATUS_NOT_FOUND, 0), None),
+            }
+        }
+        Request::WindowedInc { nonce, window_id, name, labels, delta } => {
+            match registry.windowed_inc(sender_service_id, window_id, name, labels, delta, now_ns) {
+                Ok(_) => (encode_status_response(OP_WINDOWED_INC, nonce, STATUS_OK), None),
+                Err(reject) => reject_rsp(OP_WINDOWED_INC, nonce, reject),
+            }
+        }
+        Request::Batch { nonce, entries } => {
+            // Every entry is applied like its single-op frame (nonce 0: no dedup); the reply
+            // carries the first reject, and later entries still apply.
+            let mut first_reject = None;
+            for entry in entries.iter() {
+                let (_, reject) =
+                    apply_request(registry, retention, sender_service_id, now_ns, entry.request(0));
+                first_reject = first_reject.or(reject);
+            }
+            match first_reject {
+                None => (encode_status_response(OP_BATCH, nonce, STATUS_OK), None),
+                Some(reject) => reject_rsp(OP_BATCH, nonce, reject),
+            }
+        }
     }
 }
 
-fn req_op_nonce(req: Request<'_>) -> (u8, u32) {
-    match req {
-        Request::CounterInc { nonce, .. } => (OP_COUNTER_INC, nonce),
-        Request::GaugeSet { nonce, .. } => (OP_GAUGE_SET, nonce),
-        Request::HistObserve { nonce, .. } => (OP_HIST_OBSERVE, nonce),
-        Request::SpanStart { nonce, .. } => (OP_SPAN_START, nonce),
-        Request::SpanEnd { nonce, .. } => (OP_SPAN_END, nonce),
-        Request::Ping { nonce } => (OP_PING, nonce),
-    }
-}
-
-fn reject_rsp(op: u8, nonce: u32, reject: RejectReason) -> (Vec<u8>, Option<u8>) {
-    let status = match reject {
-        RejectReason::InvalidArgs => STATUS_INVALID_ARGS,
-        RejectReason::OverLimit => STATUS_OVER_LIMIT,
-        RejectReason::RateLimited => STATUS_RATE_LIMITED,
-        RejectReason::NotFound => STATUS_NOT_FOUND,
-    };
-    (encode_status_response(op, nonce, status), Some(status))
+fn reject_rsp(op: u8, nonce: u32, reject: RejectReason) -> (Vec<u8>, Option<RejectReason>) {
+    (encode_status_response_ex(op, nonce, reject.status(), reject.subcode()), Some(reject))
 }
 
 fn route_metricsd_blocking() -> Option<KernelServer> {
@@ -461,128 +555,25 @@ fn route_blocking(name: &[u8]) -> Option<(u32, u32)> {
     }
 }
 
-fn log_counter_snapshot(name: &[u8], value: u64) {
-    // RFC-0068: per-counter telemetry folds out of the collapsed boot overview
-    // (`NEXUS_LOG_EXPAND=metricsd` to recall it); proof boots (fold off) still print it.
-    if nexus_abi::service_trace() {
-        return;
-    }
-    nexus_log::info("metricsd", |line| {
-        line.text("metrics snapshot counter name=");
-        line.text(as_utf8_or_placeholder(name));
-        line.text(" value=");
-        line.dec(value);
-    });
-}
-
-fn log_gauge_snapshot(name: &[u8], value: i64) {
-    if nexus_abi::service_trace() {
-        return;
-    }
-    nexus_log::info("metricsd", |line| {
-        line.text("metrics snapshot gauge name=");
-        line.text(as_utf8_or_placeholder(name));
-        line.text(" value=");
-        if value < 0 {
-            line.text("-");
-            line.dec(value.unsigned_abs());
-        } else {
-            line.dec(value as u64);
-        }
-    });
-}
-
-fn log_hist_snapshot(name: &[u8], count: u64, sum: u64) {
-    if nexus_abi::service_trace() {
-        return;
-    }
-    nexus_log::info("metricsd", |line| {
-        line.text("metrics snapshot histogram name=");
-        line.text(as_utf8_or_placeholder(name));
-        line.text(" count=");
-        line.dec(count);
-        line.text(" sum=");
-        line.dec(sum);
-    });
-}
-
-fn log_span_end(
-    name: &[u8],
-    parent_span_id: u64,
-    duration_ns: u64,
-    status: u8,
-    start_attrs: &[u8],
-    end_attrs: &[u8],
-) {
-    let start_attrs_text = escaped_attrs_or_placeholder(start_attrs);
-    let end_attrs_text = escaped_attrs_or_placeholder(end_attrs);
-    nexus_log::info("metricsd", |line| {
-        line.text("tracing span end name=");
-        line.text(as_utf8_or_placeholder(name));
-        line.text(" parent_span_id=");
-        line.dec(parent_span_id);
-        line.text(" duration_ns=");
-        line.dec(duration_ns);
-        line.text(" status=");
-        line.dec(status as u64);
-        line.text(" start_attrs=");
-        line.text(start_attrs_text.as_str());
-        line.text(" end_attrs=");
-        line.text(end_attrs_text.as_str());
-    });
-}
-
-fn metric_counter_record(name: &[u8], value: u64) -> String {
-    format!("metric counter name={} value={}", as_utf8_or_placeholder(name), value)
-}
-
-fn metric_gauge_record(name: &[u8], value: i64) -> String {
-    format!("metric gauge name={} value={}", as_utf8_or_placeholder(name), value)
-}
-
-fn metric_hist_record(name: &[u8], count: u64, sum: u64) -> String {
-    format!("metric histogram name={} count={} sum={}", as_utf8_or_placeholder(name), count, sum)
-}
-
-fn span_end_record(
-    name: &[u8],
-    parent_span_id: u64,
-    duration_ns: u64,
-    status: u8,
-    start_attrs: &[u8],
-    end_attrs: &[u8],
-) -> String {
-    format!(
-        "span end name={} parent_span_id={} duration_ns={} status={} start_attrs={} end_attrs={}",
-        as_utf8_or_placeholder(name),
-        parent_span_id,
-        duration_ns,
-        status,
-        escaped_attrs_or_placeholder(start_attrs),
-        escaped_attrs_or_placeholder(end_attrs),
-    )
-}
-
-fn as_utf8_or_placeholder(bytes: &[u8]) -> &str {
-    match core::str::from_utf8(bytes) {
-        Ok(text) => text,
-        Err(_) => "<bin>",
-    }
-}
-
-fn escaped_attrs_or_placeholder(bytes: &[u8]) -> String {
-    let Ok(text) = core::str::from_utf8(bytes) else {
-        return String::from("<bin>");
-    };
-    let mut out = String::new();
-    for ch in text.chars() {
-        match ch {
-            '\n' => out.push_str("\\n"),
-            '\r' => out.push_str("\\r"),
-            _ => out.push(ch),
-        }
-    }
-    out
+/// Logs an ended (or expired) span and hands it to retention.
+fn record_ended_span(retention: &mut RetentionSink, ended: &EndedSpan) {
+    log_span_end(
+        &ended.name,
+        ended.parent_span_id,
+        ended.duration_ns,
+        ended.status,
+        &ended.start_attrs,
+        &ended.end_attrs,
+    );
+    let record = span_end_record(
+        &ended.name,
+        ended.parent_span_id,
+        ended.duration_ns,
+        ended.status,
+        &ended.start_attrs,
+        &ended.end_attrs,
+    );
+    retention.record_span(record.as_str());
 }
 
 fn emit_line(message: &str) {
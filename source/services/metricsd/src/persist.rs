@@ -0,0 +1,281 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+//
+//! CONTEXT: metricsd Registry snapshot/restore for persistence across restart
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: Host unit tests in this module
+//! ADR: docs/rfcs/RFC-0024-observability-v2-metrics-tracing-contract-v1.md
+//!
+//! INVARIANTS:
+//! - Snapshots are bounded by [`REGISTRY_SNAPSHOT_MAX_LEN`] and CRC32C-protected
+//! - Restore re-applies the current limits; a series that no longer fits rejects
+//!   the whole blob and leaves the registry untouched
+//! - Each series keeps its owning `sender_service_id`
+//! - Windowed counters are clock-relative and never written (restore drops them)
+//!
+//! Layout (little-endian):
+//!
+//! ```text
+//! Magic "NXMR" (4) | Version (u8) | Reserved (u8) | SeriesCount (u16)
+//! per series: SenderId (u64) | Kind (u8) | NameLen (u8) | LabelsLen (u16) | Name | Labels
+//!             Counter: value (u64) | Gauge: value (i64) | Histogram: buckets (5 x u64), count, sum
+//! CRC32C over everything before it (u32)
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::{HistogramState, MetricKind, Registry, RejectReason, SELF_SENDER_ID};
+
+/// StateFS key the registry snapshot is stored under.
+pub const REGISTRY_STATE_KEY: &str = "/state/metrics/registry";
+/// Largest snapshot accepted by [`Registry::restore`] (the StateFS value cap).
+pub const REGISTRY_SNAPSHOT_MAX_LEN: usize = 64 * 1024;
+
+const SNAPSHOT_MAGIC: [u8; 4] = *b"NXMR";
+const SNAPSHOT_VERSION: u8 = 1;
+const HEADER_LEN: usize = 8;
+const CRC_LEN: usize = 4;
+
+const KIND_COUNTER: u8 = 1;
+const KIND_GAUGE: u8 = 2;
+const KIND_HISTOGRAM: u8 = 3;
+
+impl Registry {
+    /// Serializes every series (values, histogram buckets, owning sender) for storage at
+    /// [`REGISTRY_STATE_KEY`]. Live spans, windowed counters and the self-metrics series
+    /// are not persisted.
+    pub fn snapshot(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(HEADER_LEN + self.series.len() * 64 + CRC_LEN);
+        out.extend_from_slice(&SNAPSHOT_MAGIC);
+        out.push(SNAPSHOT_VERSION);
+        out.push(0);
+        let persisted =
+            || self.series.iter().filter(|entry| entry.sender_service_id != SELF_SENDER_ID);
+        let count = persisted().filter(|entry| kind_byte(entry.kind).is_some()).count();
+        out.extend_from_slice(&(count as u16).to_le_bytes());
+        for entry in persisted() {
+            let Some(kind) = kind_byte(entry.kind) else { continue };
+            out.extend_from_slice(&entry.sender_service_id.to_le_bytes());
+            out.push(kind);
+            // Field lengths are bounded by the wire caps (48 / 192 bytes).
+            out.push(entry.name.len() as u8);
+            out.extend_from_slice(&(entry.labels.len() as u16).to_le_bytes());
+            out.extend_from_slice(&entry.name);
+            out.extend_from_slice(&entry.labels);
+            match entry.kind {
+                MetricKind::Counter => out.extend_from_slice(&entry.counter_value.to_le_bytes()),
+                MetricKind::Gauge => out.extend_from_slice(&entry.gauge_value.to_le_bytes()),
+                MetricKind::Histogram => {
+                    let hist = &entry.histogram;
+                    for bucket in hist.buckets {
+                        out.extend_from_slice(&bucket.to_le_bytes());
+                    }
+                    out.extend_from_slice(&hist.count.to_le_bytes());
+                    out.extend_from_slice(&hist.sum.to_le_bytes());
+                }
+                MetricKind::WindowedCounter => {}
+            }
+        }
+        let crc = crc32c(&out);
+        out.extend_from_slice(&crc.to_le_bytes());
+        out
+    }
+
+    /// Replaces the series with those in `blob`, re-validated against the current limits.
+    ///
+    /// Malformed, truncated or CRC-failing blobs reject with [`RejectReason::InvalidArgs`];
+    /// a series that exceeds today's field or cardinality limits rejects with the matching
+    /// [`RejectReason::OverLimit`]. On any reject the registry is left unchanged.
+    pub fn restore(&mut self, blob: &[u8]) -> Result<(), RejectReason> {
+        if blob.len() < HEADER_LEN + CRC_LEN || blob.len() > REGISTRY_SNAPSHOT_MAX_LEN {
+            return Err(RejectReason::InvalidArgs);
+        }
+        let (body, crc) = blob.split_at(blob.len() - CRC_LEN);
+        if crc32c(body).to_le_bytes() != crc {
+            return Err(RejectReason::InvalidArgs);
+        }
+        if body[0..4] != SNAPSHOT_MAGIC || body[4] != SNAPSHOT_VERSION || body[5] != 0 {
+            return Err(RejectReason::InvalidArgs);
+        }
+        let count = u16::from_le_bytes([body[6], body[7]]) as usize;
+
+        let mut restored = Registry::new_with_limits(self.limits);
+        let mut reader = Reader { buf: &body[HEADER_LEN..] };
+        for _ in 0..count {
+            let sender_service_id = reader.u64()?;
+            if sender_service_id == SELF_SENDER_ID {
+                return Err(RejectReason::InvalidArgs);
+            }
+            let kind = match reader.u8()? {
+                KIND_COUNTER => MetricKind::Counter,
+                KIND_GAUGE => MetricKind::Gauge,
+                KIND_HISTOGRAM => MetricKind::Histogram,
+                _ => return Err(RejectReason::InvalidArgs),
+            };
+            let name_len = reader.u8()? as usize;
+            let labels_len = reader.u16()? as usize;
+            let name = reader.take(name_len)?;
+            let labels = reader.take(labels_len)?;
+            let before = restored.series.len();
+            let idx = restored.ensure_series(sender_service_id, kind, name, labels)?;
+            if restored.series.len() == before {
+                // The same series twice: not something `snapshot` produces.
+                return Err(RejectReason::InvalidArgs);
+            }
+            let series = &mut restored.series[idx];
+            match kind {
+                MetricKind::Counter => series.counter_value = reader.u64()?,
+                MetricKind::Gauge => series.gauge_value = reader.u64()? as i64,
+                MetricKind::Histogram => {
+                    let mut hist = HistogramState::new();
+                    for bucket in hist.buckets.iter_mut() {
+                        *bucket = reader.u64()?;
+                    }
+                    hist.count = reader.u64()?;
+                    hist.sum = reader.u64()?;
+                    series.histogram = hist;
+                }
+                MetricKind::WindowedCounter => return Err(RejectReason::InvalidArgs),
+            }
+        }
+        if !reader.buf.is_empty() {
+            return Err(RejectReason::InvalidArgs);
+        }
+        self.series = restored.series;
+        Ok(())
+    }
+}
+
+pub(crate) struct Reader<'a> {
+    pub(crate) buf: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn take(&mut self, len: usize) -> Result<&'a [u8], RejectReason> {
+        if self.buf.len() < len {
+            return Err(RejectReason::InvalidArgs);
+        }
+        let (head, rest) = self.buf.split_at(len);
+        self.buf = rest;
+        Ok(head)
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8, RejectReason> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub(crate) fn u16(&mut self) -> Result<u16, RejectReason> {
+        let raw = self.take(2)?;
+        Ok(u16::from_le_bytes([raw[0], raw[1]]))
+    }
+
+    pub(crate) fn u64(&mut self) -> Result<u64, RejectReason> {
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(self.take(8)?);
+        Ok(u64::from_le_bytes(bytes))
+    }
+}
+
+/// Snapshot kind byte; `None` for windowed counters, which are not persisted.
+fn kind_byte(kind: MetricKind) -> Option<u8> {
+    match kind {
+        MetricKind::Counter => Some(KIND_COUNTER),
+        MetricKind::Gauge => Some(KIND_GAUGE),
+        MetricKind::Histogram => Some(KIND_HISTOGRAM),
+        MetricKind::WindowedCounter => None,
+    }
+}
+
+pub(crate) fn crc32c(data: &[u8]) -> u32 {
+    let mut crc: u32 = 0xFFFF_FFFF;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0x82F6_3B78 & mask);
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LimitKind, RuntimeLimits};
+
+    fn populated() -> Registry {
+        let mut reg = Registry::new();
+        assert_eq!(reg.counter_inc(7, b"boot.events", b"id=1", 5), Ok(5));
+        assert_eq!(reg.counter_inc(9, b"boot.events", b"id=1", 2), Ok(2));
+        assert_eq!(reg.gauge_set(7, b"sched.depth", b"", -3), Ok(-3));
+        assert!(reg.hist_observe(7, b"timed.latency", b"op=get", 2_000_000).is_ok());
+        assert!(reg.hist_observe(7, b"timed.latency", b"op=get", 500_000_000).is_ok());
+        reg
+    }
+
+    #[test]
+    fn snapshot_restore_roundtrip_keeps_values_and_owners() {
+        let reg = populated();
+        let blob = reg.snapshot();
+        assert!(blob.len() <= REGISTRY_SNAPSHOT_MAX_LEN);
+
+        let mut restored = Registry::new();
+        assert_eq!(restored.restore(&blob), Ok(()));
+        assert_eq!(restored.snapshot(), blob);
+        // Counters continue monotonically, per owning sender.
+        assert_eq!(restored.counter_inc(7, b"boot.events", b"id=1", 1), Ok(6));
+        assert_eq!(restored.counter_inc(9, b"boot.events", b"id=1", 1), Ok(3));
+        assert_eq!(restored.gauge_set(7, b"sched.depth", b"", 4), Ok(4));
+        assert_eq!(restored.hist_observe(7, b"timed.latency", b"op=get", 1), Ok((3, 502_000_001)));
+        let hist = &restored.series[3].histogram;
+        assert_eq!(hist.buckets, [1, 1, 0, 0, 1]);
+
+        // An empty registry round-trips too.
+        let mut empty = Registry::new();
+        assert_eq!(empty.restore(&Registry::new().snapshot()), Ok(()));
+        assert!(empty.series.is_empty());
+    }
+
+    #[test]
+    fn test_reject_restore_when_limits_shrank() {
+        let blob = populated().snapshot();
+        let shrunk = RuntimeLimits { max_series_total: 3, ..RuntimeLimits::default() };
+        let mut reg = Registry::new_with_limits(shrunk);
+        assert!(reg.counter_inc(1, b"kept", b"", 1).is_ok());
+        assert_eq!(reg.restore(&blob), Err(RejectReason::OverLimit(LimitKind::SeriesTotal)));
+        assert_eq!(reg.series.len(), 1, "a rejected restore leaves the registry unchanged");
+
+        let per_metric = RuntimeLimits { max_series_per_metric: 1, ..RuntimeLimits::default() };
+        assert_eq!(
+            Registry::new_with_limits(per_metric).restore(&blob),
+            Err(RejectReason::OverLimit(LimitKind::SeriesPerMetric))
+        );
+        let short_labels = RuntimeLimits { max_labels_len: 3, ..RuntimeLimits::default() };
+        assert_eq!(
+            Registry::new_with_limits(short_labels).restore(&blob),
+            Err(RejectReason::OverLimit(LimitKind::FieldLen))
+        );
+    }
+
+    #[test]
+    fn test_reject_corrupted_or_truncated_snapshot() {
+        let blob = populated().snapshot();
+        let mut reg = Registry::new();
+        for len in [0, HEADER_LEN, blob.len() - 1] {
+            assert_eq!(reg.restore(&blob[..len]), Err(RejectReason::InvalidArgs), "len {len}");
+        }
+        let mut flipped = blob.clone();
+        flipped[HEADER_LEN + 3] ^= 0x01;
+        assert_eq!(reg.restore(&flipped), Err(RejectReason::InvalidArgs));
+
+        // A well-formed CRC over a bad count still rejects.
+        let mut recount = blob[..blob.len() - CRC_LEN].to_vec();
+        recount[6] = recount[6].wrapping_add(1);
+        let crc = crc32c(&recount);
+        recount.extend_from_slice(&crc.to_le_bytes());
+        assert_eq!(reg.restore(&recount), Err(RejectReason::InvalidArgs));
+        assert!(reg.series.is_empty());
+    }
+}
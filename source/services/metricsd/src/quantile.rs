@@ -0,0 +1,94 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+//
+//! CONTEXT: metricsd single-percentile histogram query (OP_HIST_QUANTILE)
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: Host unit tests in this module
+//! ADR: docs/rfcs/RFC-0024-observability-v2-metrics-tracing-contract-v1.md
+//!
+//! Answers "p-th percentile of one series" from the stored bucket counts without
+//! exporting the registry. The buckets are coarse, so the answer is the upper bound
+//! of the bucket that contains the percentile rank (`u64::MAX` for the overflow
+//! bucket), never an interpolation between observations.
+
+use nexus_metrics::is_valid_percentile;
+
+use crate::{HistogramState, MetricKind, Registry, HIST_BUCKETS_NS};
+
+impl HistogramState {
+    /// Upper bound of the bucket holding rank `ceil(count * percentile / 100)`.
+    fn percentile(&self, percentile: u8) -> Option<u64> {
+        if !is_valid_percentile(percentile) || self.count == 0 {
+            return None;
+        }
+        let rank = (u128::from(self.count) * u128::from(percentile)).div_ceil(100);
+        let mut seen = 0u128;
+        for (idx, count) in self.buckets.iter().enumerate() {
+            seen += u128::from(*count);
+            if seen >= rank {
+                return Some(HIST_BUCKETS_NS.get(idx).copied().unwrap_or(u64::MAX));
+            }
+        }
+        None
+    }
+}
+
+impl Registry {
+    /// The `percentile` (`1..=100`) of a histogram series owned by `sender_service_id`.
+    ///
+    /// Returns `None` for an out-of-range percentile, an unknown series or one with no
+    /// observations.
+    pub fn hist_percentile(
+        &self,
+        sender_service_id: u64,
+        name: &[u8],
+        labels: &[u8],
+        percentile: u8,
+    ) -> Option<u64> {
+        self.series
+            .iter()
+            .find(|entry| {
+                entry.sender_service_id == sender_service_id
+                    && entry.kind == MetricKind::Histogram
+                    && entry.name.as_slice() == name
+                    && entry.labels.as_slice() == labels
+            })?
+            .histogram
+            .percentile(percentile)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentiles_land_in_the_expected_bucket() {
+        let mut reg = Registry::new();
+        // 50 x <=1ms, 40 x <=5ms, 9 x <=20ms, 1 x overflow.
+        for (value, times) in [(500_000, 50), (3_000_000, 40), (15_000_000, 9), (1 << 40, 1)] {
+            for _ in 0..times {
+                reg.hist_observe(7, b"ipc.latency", b"", value).unwrap();
+            }
+        }
+        assert_eq!(reg.hist_percentile(7, b"ipc.latency", b"", 50), Some(1_000_000));
+        assert_eq!(reg.hist_percentile(7, b"ipc.latency", b"", 90), Some(5_000_000));
+        assert_eq!(reg.hist_percentile(7, b"ipc.latency", b"", 99), Some(20_000_000));
+        assert_eq!(reg.hist_percentile(7, b"ipc.latency", b"", 100), Some(u64::MAX));
+        assert_eq!(reg.hist_percentile(7, b"ipc.latency", b"", 1), Some(1_000_000));
+    }
+
+    #[test]
+    fn test_reject_invalid_percentile_queries() {
+        let mut reg = Registry::new();
+        reg.hist_observe(7, b"ipc.latency", b"", 10).unwrap();
+        assert_eq!(reg.hist_percentile(7, b"ipc.latency", b"", 0), None);
+        assert_eq!(reg.hist_percentile(7, b"ipc.latency", b"", 101), None);
+        // Series are scoped to the sender and kind.
+        assert_eq!(reg.hist_percentile(8, b"ipc.latency", b"", 50), None);
+        reg.counter_inc(7, b"ipc.count", b"", 1).unwrap();
+        assert_eq!(reg.hist_percentile(7, b"ipc.count", b"", 50), None);
+    }
+}
@@ -0,0 +1,101 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+//
+//! CONTEXT: metricsd retention/export record text (deterministic, bounded)
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: Host unit tests in this module
+//! ADR: docs/rfcs/RFC-0024-observability-v2-metrics-tracing-contract-v1.md
+
+use alloc::format;
+use alloc::string::String;
+
+use nexus_metrics::{MetricScale, ScaledValue};
+
+/// Values of `*.milli` metrics render with a decimal point (`value=41.500`).
+pub fn metric_counter_record(name: &[u8], value: u64) -> String {
+    let value = ScaledValue::unsigned(value, MetricScale::of(name));
+    format!("metric counter name={} value={}", as_utf8_or_placeholder(name), value)
+}
+
+pub fn metric_gauge_record(name: &[u8], value: i64) -> String {
+    let value = ScaledValue::signed(value, MetricScale::of(name));
+    format!("metric gauge name={} value={}", as_utf8_or_placeholder(name), value)
+}
+
+pub fn metric_hist_record(name: &[u8], count: u64, sum: u64) -> String {
+    format!("metric histogram name={} count={} sum={}", as_utf8_or_placeholder(name), count, sum)
+}
+
+pub fn span_end_record(
+    name: &[u8],
+    parent_span_id: u64,
+    duration_ns: u64,
+    status: u8,
+    start_attrs: &[u8],
+    end_attrs: &[u8],
+) -> String {
+    format!(
+        "span end name={} parent_span_id={} duration_ns={} status={} start_attrs={} end_attrs={}",
+        as_utf8_or_placeholder(name),
+        parent_span_id,
+        duration_ns,
+        status,
+        escaped_attrs_or_placeholder(start_attrs),
+        escaped_attrs_or_placeholder(end_attrs),
+    )
+}
+
+pub fn as_utf8_or_placeholder(bytes: &[u8]) -> &str {
+    match core::str::from_utf8(bytes) {
+        Ok(text) => text,
+        Err(_) => "<bin>",
+    }
+}
+
+pub fn escaped_attrs_or_placeholder(bytes: &[u8]) -> String {
+    let Ok(text) = core::str::from_utf8(bytes) else {
+        return String::from("<bin>");
+    };
+    let mut out = String::new();
+    for ch in text.chars() {
+        match ch {
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn milli_metric_records_render_the_decimal() {
+        assert_eq!(
+            metric_gauge_record(b"thermal.temp", -3),
+            "metric gauge name=thermal.temp value=-3"
+        );
+        assert_eq!(
+            metric_gauge_record(b"thermal.temp.milli", -1_250),
+            "metric gauge name=thermal.temp.milli value=-1.250"
+        );
+        assert_eq!(
+            metric_counter_record(b"cpu.busy.milli", 2_005),
+            "metric counter name=cpu.busy.milli value=2.005"
+        );
+    }
+
+    #[test]
+    fn span_end_record_escapes_attr_newlines() {
+        let record = span_end_record(b"exec.path", 3, 80, 0, b"a=1\n", b"\xff");
+        assert_eq!(
+            record,
+            "span end name=exec.path parent_span_id=3 duration_ns=80 status=0 \
+             start_attrs=a=1\\n end_attrs=<bin>"
+        );
+    }
+}
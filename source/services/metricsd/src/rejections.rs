@@ -0,0 +1,165 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+//
+//! CONTEXT: metricsd reject audit log — per-sender tallies of rejected frames
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: Host unit tests in this module
+//! ADR: docs/rfcs/RFC-0024-observability-v2-metrics-tracing-contract-v1.md
+//!
+//! The daemon reports every rejected frame (`invalid_args`, `over_limit`, `rate_limited`)
+//! with [`Registry::note_reject`]. Rejects are not logged one by one: they fold into one
+//! [`RejectRecord`] per (`sender_service_id`, reason), whose `count` grows and whose `op`
+//! follows the latest rejected frame. A flooding sender therefore costs one slot, and the
+//! log looks the same however the flood interleaves. A supervisor drains it with
+//! [`Registry::take_rejections`]; `OP_REJECTIONS` reads it without draining, so a sender
+//! cannot erase its own record.
+//!
+//! INVARIANTS:
+//! - Bounded: at most [`MAX_REJECT_RECORDS`] records; once full, rejects for a new
+//!   (sender, reason) pair are dropped and existing records keep counting
+//! - Identity is the kernel `sender_service_id`, never a payload field
+//! - Volatile (not part of the registry snapshot)
+
+use alloc::vec::Vec;
+
+use nexus_metrics::{encode_rejections_response, RejectionEntry, MAX_REJECTION_ENTRIES};
+
+use crate::{Registry, RejectReason};
+
+/// Distinct (sender, reason) records the log holds; matches one `OP_REJECTIONS` reply.
+pub const MAX_REJECT_RECORDS: usize = MAX_REJECTION_ENTRIES;
+
+/// Rejects of one sender for one reason since the log was last drained.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RejectRecord {
+    pub sender_service_id: u64,
+    /// Opcode of the most recent rejected frame.
+    pub op: u8,
+    pub reason: RejectReason,
+    /// Rejects folded into this record (saturating).
+    pub count: u32,
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct RejectionLog {
+    records: Vec<RejectRecord>,
+}
+
+impl Registry {
+    /// Records one rejected frame from `sender_service_id`.
+    pub fn note_reject(&mut self, sender_service_id: u64, op: u8, reason: RejectReason) {
+        self.reject_totals.note(reason);
+        let records = &mut self.rejections.records;
+        if let Some(record) = records
+            .iter_mut()
+            .find(|r| r.sender_service_id == sender_service_id && r.reason == reason)
+        {
+            record.op = op;
+            record.count = record.count.saturating_add(1);
+        } else if records.len() < MAX_REJECT_RECORDS {
+            records.push(RejectRecord { sender_service_id, op, reason, count: 1 });
+        }
+    }
+
+    /// Drains the reject records, in order of each pair's first reject.
+    pub fn take_rejections(&mut self) -> Vec<RejectRecord> {
+        core::mem::take(&mut self.rejections.records)
+    }
+
+    /// `OP_REJECTIONS` reply with the current records; the log is left as is.
+    pub fn rejections_response(&self, nonce: u32) -> Vec<u8> {
+        let entries: Vec<RejectionEntry> = self
+            .rejections
+            .records
+            .iter()
+            .map(|record| RejectionEntry {
+                sender_service_id: record.sender_service_id,
+                op: record.op,
+                status: record.reason.status(),
+                subcode: record.reason.subcode(),
+                count: record.count,
+            })
+            .collect();
+        encode_rejections_response(nonce, &entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LimitKind;
+    use nexus_metrics::{
+        decode_rejections_response, OP_COUNTER_INC, OP_GAUGE_SET, REASON_SENDER_BUDGET, STATUS_OK,
+        STATUS_RATE_LIMITED,
+    };
+
+    #[test]
+    fn repeated_rejects_from_one_sender_aggregate() {
+        let mut registry = Registry::new();
+        for _ in 0..1000 {
+            registry.note_reject(0x51, OP_COUNTER_INC, RejectReason::RateLimited);
+        }
+        registry.note_reject(0x51, OP_GAUGE_SET, RejectReason::RateLimited);
+        registry.note_reject(0x51, OP_GAUGE_SET, RejectReason::InvalidArgs);
+        registry.note_reject(0x52, OP_GAUGE_SET, RejectReason::RateLimited);
+
+        let rsp = registry.rejections_response(4);
+        let (status, entries) = decode_rejections_response(&rsp, 4).unwrap();
+        assert_eq!(status, STATUS_OK);
+        assert_eq!(
+            entries[0],
+            RejectionEntry {
+                sender_service_id: 0x51,
+                op: OP_GAUGE_SET,
+                status: STATUS_RATE_LIMITED,
+                subcode: REASON_SENDER_BUDGET,
+                count: 1001,
+            }
+        );
+
+        // The scrape left the log alone; draining empties it.
+        let records = registry.take_rejections();
+        assert_eq!(
+            records,
+            [
+                RejectRecord {
+                    sender_service_id: 0x51,
+                    op: OP_GAUGE_SET,
+                    reason: RejectReason::RateLimited,
+                    count: 1001,
+                },
+                RejectRecord {
+                    sender_service_id: 0x51,
+                    op: OP_GAUGE_SET,
+                    reason: RejectReason::InvalidArgs,
+                    count: 1,
+                },
+                RejectRecord {
+                    sender_service_id: 0x52,
+                    op: OP_GAUGE_SET,
+                    reason: RejectReason::RateLimited,
+                    count: 1,
+                },
+            ]
+        );
+        assert!(registry.take_rejections().is_empty());
+    }
+
+    #[test]
+    fn test_reject_new_pairs_once_the_log_is_full() {
+        let mut registry = Registry::new();
+        let over_limit = RejectReason::OverLimit(LimitKind::SeriesTotal);
+        for sender in 0..MAX_REJECT_RECORDS as u64 + 8 {
+            registry.note_reject(sender, OP_COUNTER_INC, over_limit);
+        }
+        // Known pairs keep counting after the log fills up.
+        registry.note_reject(0, OP_COUNTER_INC, over_limit);
+
+        let records = registry.take_rejections();
+        assert_eq!(records.len(), MAX_REJECT_RECORDS);
+        assert_eq!(records[0].count, 2);
+        assert!(records.iter().all(|r| r.sender_service_id < MAX_REJECT_RECORDS as u64));
+    }
+}
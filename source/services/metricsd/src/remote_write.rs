@@ -0,0 +1,299 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+//
+//! CONTEXT: metricsd remote-write export frame (all series, timestamped, for external collectors)
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: Host unit tests in this module
+//! ADR: docs/rfcs/RFC-0024-observability-v2-metrics-tracing-contract-v1.md
+//!
+//! [`Registry::export_remote_write`] carries the same data as a Prometheus remote-write
+//! request (series identity, value, sample timestamp) without a protobuf dependency. A
+//! host-side bridge decodes it with [`decode_remote_write`] and re-encodes for the collector.
+//! Series do not record update times, so every sample is stamped with the caller's `now_ns`,
+//! which also evaluates windowed counters.
+//!
+//! INVARIANTS:
+//! - Deterministic: series are sorted by (name, labels, kind, sender)
+//! - Bounded by [`REMOTE_WRITE_MAX_LEN`]; series that do not fit are counted, not cut
+//! - Decoders skip unknown tags, so new series kinds stay readable by old bridges
+//!
+//! Layout (little-endian):
+//!
+//! ```text
+//! Magic "NXRW" (4) | Version (u8) | Flags (u8) | SeriesCount (u16) | Omitted (u16) | Reserved (u16)
+//! per series: Tag (u8) | Len (u16) | SenderId (u64) | TimestampNs (u64)
+//!             | NameLen (u8) | LabelsLen (u16) | Name | Labels | Sample
+//!             Counter (1): value (u64) | Gauge (2): value (i64)
+//!             Histogram (3): buckets (5 x u64), count, sum | Windowed (4): window_ns, value
+//! CRC32C over everything before it (u32)
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::persist::{crc32c, Reader};
+use crate::{MetricKind, Registry, RejectReason};
+
+/// Largest frame [`Registry::export_remote_write`] produces.
+pub const REMOTE_WRITE_MAX_LEN: usize = 16 * 1024;
+/// Header flag: some series did not fit and were left out (see `omitted`).
+pub const REMOTE_WRITE_FLAG_TRUNCATED: u8 = 1;
+
+const MAGIC: [u8; 4] = *b"NXRW";
+const VERSION: u8 = 1;
+const HEADER_LEN: usize = 12;
+const CRC_LEN: usize = 4;
+
+const TAG_COUNTER: u8 = 1;
+const TAG_GAUGE: u8 = 2;
+const TAG_HISTOGRAM: u8 = 3;
+const TAG_WINDOWED: u8 = 4;
+
+/// Sample of one exported series.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RemoteSample {
+    Counter(u64),
+    Gauge(i64),
+    /// Per-bucket counts for the 1/5/20/100 ms bounds plus overflow (not cumulative).
+    Histogram {
+        buckets: [u64; 5],
+        count: u64,
+        sum: u64,
+    },
+    /// Value over `window_ns` at the sample timestamp.
+    Windowed {
+        window_ns: u64,
+        value: u64,
+    },
+}
+
+/// One series of a decoded remote-write frame.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RemoteSeries {
+    pub sender_service_id: u64,
+    pub timestamp_ns: u64,
+    pub name: Vec<u8>,
+    pub labels: Vec<u8>,
+    pub sample: RemoteSample,
+}
+
+/// Decoded remote-write frame.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RemoteWriteFrame {
+    pub series: Vec<RemoteSeries>,
+    /// `true` when the exporter ran out of room ([`REMOTE_WRITE_FLAG_TRUNCATED`]).
+    pub truncated: bool,
+    /// Series left out of a truncated frame.
+    pub omitted: u16,
+}
+
+impl Registry {
+    /// Every series as a remote-write frame stamped `now_ns`, at most
+    /// [`REMOTE_WRITE_MAX_LEN`] bytes.
+    pub fn export_remote_write(&self, now_ns: u64) -> Vec<u8> {
+        self.export_remote_write_bounded(now_ns, REMOTE_WRITE_MAX_LEN)
+    }
+
+    fn export_remote_write_bounded(&self, now_ns: u64, max_len: usize) -> Vec<u8> {
+        let mut order: Vec<usize> = (0..self.series.len()).collect();
+        order.sort_by(|&a, &b| {
+            let (a, b) = (&self.series[a], &self.series[b]);
+            (&a.name, &a.labels, tag(a.kind), a.sender_service_id).cmp(&(
+                &b.name,
+                &b.labels,
+                tag(b.kind),
+                b.sender_service_id,
+            ))
+        });
+
+        let mut out = Vec::with_capacity(HEADER_LEN + self.series.len() * 64 + CRC_LEN);
+        out.extend_from_slice(&MAGIC);
+        out.extend_from_slice(&[VERSION, 0, 0, 0, 0, 0, 0, 0]);
+        let mut written = 0u16;
+        for (done, &idx) in order.iter().enumerate() {
+            let entry = &self.series[idx];
+            let mut value = Vec::with_capacity(64);
+            value.extend_from_slice(&entry.sender_service_id.to_le_bytes());
+            value.extend_from_slice(&now_ns.to_le_bytes());
+            // Field lengths are bounded by the wire caps (48 / 192 bytes).
+            value.push(entry.name.len() as u8);
+            value.extend_from_slice(&(entry.labels.len() as u16).to_le_bytes());
+            value.extend_from_slice(&entry.name);
+            value.extend_from_slice(&entry.labels);
+            match entry.kind {
+                MetricKind::Counter => value.extend_from_slice(&entry.counter_value.to_le_bytes()),
+                MetricKind::Gauge => value.extend_from_slice(&entry.gauge_value.to_le_bytes()),
+                MetricKind::Histogram => {
+                    let hist = &entry.histogram;
+                    for word in hist.buckets.iter().chain([&hist.count, &hist.sum]) {
+                        value.extend_from_slice(&word.to_le_bytes());
+                    }
+                }
+                MetricKind::WindowedCounter => {
+                    let windowed = &entry.windowed;
+                    value.extend_from_slice(&windowed.window_ns().to_le_bytes());
+                    value.extend_from_slice(&windowed.value(now_ns).to_le_bytes());
+                }
+            }
+            if out.len() + 3 + value.len() + CRC_LEN > max_len {
+                let omitted = (order.len() - done).min(u16::MAX as usize) as u16;
+                out[5] = REMOTE_WRITE_FLAG_TRUNCATED;
+                out[8..10].copy_from_slice(&omitted.to_le_bytes());
+                break;
+            }
+            out.push(tag(entry.kind));
+            out.extend_from_slice(&(value.len() as u16).to_le_bytes());
+            out.extend_from_slice(&value);
+            written += 1;
+        }
+        out[6..8].copy_from_slice(&written.to_le_bytes());
+        let crc = crc32c(&out);
+        out.extend_from_slice(&crc.to_le_bytes());
+        out
+    }
+}
+
+/// Parses a frame from [`Registry::export_remote_write`].
+///
+/// Bad magic, version, CRC or lengths reject with [`RejectReason::InvalidArgs`]; series
+/// with an unknown tag are skipped.
+pub fn decode_remote_write(frame: &[u8]) -> Result<RemoteWriteFrame, RejectReason> {
+    if frame.len() < HEADER_LEN + CRC_LEN || frame.len() > REMOTE_WRITE_MAX_LEN {
+        return Err(RejectReason::InvalidArgs);
+    }
+    let (body, crc) = frame.split_at(frame.len() - CRC_LEN);
+    if crc32c(body).to_le_bytes() != crc || body[0..4] != MAGIC || body[4] != VERSION {
+        return Err(RejectReason::InvalidArgs);
+    }
+    let flags = body[5];
+    if flags & !REMOTE_WRITE_FLAG_TRUNCATED != 0 {
+        return Err(RejectReason::InvalidArgs);
+    }
+    let count = u16::from_le_bytes([body[6], body[7]]);
+    let omitted = u16::from_le_bytes([body[8], body[9]]);
+
+    let mut series = Vec::new();
+    let mut reader = Reader { buf: &body[HEADER_LEN..] };
+    for _ in 0..count {
+        let tag = reader.u8()?;
+        let len = reader.u16()? as usize;
+        let mut value = Reader { buf: reader.take(len)? };
+        if !matches!(tag, TAG_COUNTER | TAG_GAUGE | TAG_HISTOGRAM | TAG_WINDOWED) {
+            continue;
+        }
+        let sender_service_id = value.u64()?;
+        let timestamp_ns = value.u64()?;
+        let name_len = value.u8()? as usize;
+        let labels_len = value.u16()? as usize;
+        let name = value.take(name_len)?.to_vec();
+        let labels = value.take(labels_len)?.to_vec();
+        let sample = match tag {
+            TAG_COUNTER => RemoteSample::Counter(value.u64()?),
+            TAG_GAUGE => RemoteSample::Gauge(value.u64()? as i64),
+            TAG_HISTOGRAM => {
+                let mut buckets = [0u64; 5];
+                for bucket in buckets.iter_mut() {
+                    *bucket = value.u64()?;
+                }
+                RemoteSample::Histogram { buckets, count: value.u64()?, sum: value.u64()? }
+            }
+            _ => RemoteSample::Windowed { window_ns: value.u64()?, value: value.u64()? },
+        };
+        if !value.buf.is_empty() {
+            return Err(RejectReason::InvalidArgs);
+        }
+        series.push(RemoteSeries { sender_service_id, timestamp_ns, name, labels, sample });
+    }
+    if !reader.buf.is_empty() {
+        return Err(RejectReason::InvalidArgs);
+    }
+    Ok(RemoteWriteFrame { series, truncated: flags & REMOTE_WRITE_FLAG_TRUNCATED != 0, omitted })
+}
+
+fn tag(kind: MetricKind) -> u8 {
+    match kind {
+        MetricKind::Counter => TAG_COUNTER,
+        MetricKind::Gauge => TAG_GAUGE,
+        MetricKind::Histogram => TAG_HISTOGRAM,
+        MetricKind::WindowedCounter => TAG_WINDOWED,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nexus_metrics::WINDOW_10S;
+
+    const SEC: u64 = 1_000_000_000;
+
+    fn small_registry() -> Registry {
+        let mut reg = Registry::new();
+        reg.gauge_set(2, b"sched.depth", b"cpu=0\n", -3).unwrap();
+        reg.counter_inc(1, b"boot.events", b"", 7).unwrap();
+        reg.hist_observe(1, b"timed.latency", b"", 2_000_000).unwrap();
+        reg.hist_observe(1, b"timed.latency", b"", 500_000_000).unwrap();
+        reg.windowed_inc(3, WINDOW_10S, b"net.active", b"", 4, SEC).unwrap();
+        reg
+    }
+
+    #[test]
+    fn export_round_trips_every_series_sorted() {
+        let reg = small_registry();
+        let frame = decode_remote_write(&reg.export_remote_write(5 * SEC)).unwrap();
+        assert!(!frame.truncated);
+        assert_eq!(frame.omitted, 0);
+
+        let series = |sender, name: &[u8], labels: &[u8], sample| RemoteSeries {
+            sender_service_id: sender,
+            timestamp_ns: 5 * SEC,
+            name: name.to_vec(),
+            labels: labels.to_vec(),
+            sample,
+        };
+        let expected = [
+            series(1, b"boot.events", b"", RemoteSample::Counter(7)),
+            series(3, b"net.active", b"", RemoteSample::Windowed { window_ns: 10 * SEC, value: 4 }),
+            series(2, b"sched.depth", b"cpu=0\n", RemoteSample::Gauge(-3)),
+            series(
+                1,
+                b"timed.latency",
+                b"",
+                RemoteSample::Histogram { buckets: [0, 1, 0, 0, 1], count: 2, sum: 502_000_000 },
+            ),
+        ];
+        assert_eq!(frame.series, expected);
+    }
+
+    #[test]
+    fn export_is_independent_of_insertion_order() {
+        let mut reg = Registry::new();
+        reg.windowed_inc(3, WINDOW_10S, b"net.active", b"", 4, SEC).unwrap();
+        reg.hist_observe(1, b"timed.latency", b"", 2_000_000).unwrap();
+        reg.hist_observe(1, b"timed.latency", b"", 500_000_000).unwrap();
+        reg.counter_inc(1, b"boot.events", b"", 7).unwrap();
+        reg.gauge_set(2, b"sched.depth", b"cpu=0\n", -3).unwrap();
+        assert_eq!(reg.export_remote_write(5 * SEC), small_registry().export_remote_write(5 * SEC));
+    }
+
+    #[test]
+    fn test_reject_oversized_export_is_truncated_and_flagged() {
+        let reg = small_registry();
+        let full = reg.export_remote_write(SEC);
+        // Room for the header and the first series only.
+        let first_len = 3 + 8 + 8 + 3 + b"boot.events".len() + 8;
+        let frame = reg.export_remote_write_bounded(SEC, HEADER_LEN + first_len + CRC_LEN);
+        let frame = decode_remote_write(&frame).unwrap();
+        assert!(frame.truncated);
+        assert_eq!(frame.omitted, 3);
+        assert_eq!(frame.series, decode_remote_write(&full).unwrap().series[..1]);
+    }
+
+    #[test]
+    fn test_reject_corrupt_frame() {
+        let mut frame = small_registry().export_remote_write(SEC);
+        assert_eq!(decode_remote_write(&frame[..frame.len() - 1]), Err(RejectReason::InvalidArgs));
+        frame[HEADER_LEN + 4] ^= 1;
+        assert_eq!(decode_remote_write(&frame), Err(RejectReason::InvalidArgs));
+    }
+}
@@ -0,0 +1,452 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+//
+//! CONTEXT: metricsd retention planner — ring WAL segments and 10s/60s rollups
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: Host unit tests in this module
+//! ADR: docs/rfcs/RFC-0024-observability-v2-metrics-tracing-contract-v1.md
+//!
+//! INVARIANTS:
+//! - Pure planning: no IPC here, the OS-lite sink persists what `append` returns
+//! - Rollup top-K tallies are bounded (`ROLLUP_TRACKED_METRICS`) and drop
+//!   untracked names once full, so output is a function of input order only
+
+use alloc::collections::VecDeque;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::RuntimeLimits;
+
+/// Upper bound on distinct metric names tallied per pending rollup window.
+pub const ROLLUP_TRACKED_METRICS: usize = 32;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RetentionEventKind {
+    Metric,
+    Span,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RetentionUpdate {
+    pub wal_slot: u32,
+    pub wal_bytes: Vec<u8>,
+    pub rollup_10s: Option<RollupFrame>,
+    pub rollup_60s: Option<RollupFrame>,
+    pub gc_rollup_10s: Vec<u64>,
+    pub gc_rollup_60s: Vec<u64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RollupFrame {
+    pub window_id: u64,
+    pub bytes: Vec<u8>,
+}
+
+/// Per-metric event count keyed by the truncated name hash.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MetricTally {
+    pub name_hash: u64,
+    pub count: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct RollupWindow {
+    id: u64,
+    metrics: u64,
+    spans: u64,
+    top: Vec<MetricTally>,
+}
+
+/// Deterministic ring WAL planner used by OS-lite persistence writer.
+pub struct RetentionEngine {
+    limits: RuntimeLimits,
+    active_segment: u32,
+    active_records: u32,
+    segments: Vec<Vec<u8>>,
+    metrics_total: u64,
+    spans_total: u64,
+    pending_10s_metrics: u64,
+    pending_10s_spans: u64,
+    pending_10s_tally: Vec<MetricTally>,
+    pending_60s_metrics: u64,
+    pending_60s_spans: u64,
+    pending_60s_tally: Vec<MetricTally>,
+    pending_60s_count: u32,
+    rollup_10s_windows: VecDeque<u64>,
+    rollup_60s_windows: VecDeque<u64>,
+}
+
+impl RetentionEngine {
+    pub fn new(limits: RuntimeLimits) -> Self {
+        let mut segments = Vec::new();
+        for _ in 0..limits.retention_max_segments {
+            segments.push(Vec::new());
+        }
+        Self {
+            limits,
+            active_segment: 0,
+            active_records: 0,
+            segments,
+            metrics_total: 0,
+            spans_total: 0,
+            pending_10s_metrics: 0,
+            pending_10s_spans: 0,
+            pending_10s_tally: Vec::new(),
+            pending_60s_metrics: 0,
+            pending_60s_spans: 0,
+            pending_60s_tally: Vec::new(),
+            pending_60s_count: 0,
+            rollup_10s_windows: VecDeque::new(),
+            rollup_60s_windows: VecDeque::new(),
+        }
+    }
+
+    /// Appends one record. `metric_name` feeds the rollup top-K tally for
+    /// [`RetentionEventKind::Metric`] events and is ignored for spans.
+    pub fn append(
+        &mut self,
+        kind: RetentionEventKind,
+        metric_name: &[u8],
+        record: &[u8],
+    ) -> Option<RetentionUpdate> {
+        if !self.limits.retention_enabled {
+            return None;
+        }
+        let slot = self.active_segment % self.limits.retention_max_segments;
+        let slot_idx = slot as usize;
+        if self.active_records == 0 {
+            self.segments[slot_idx].clear();
+        }
+        self.segments[slot_idx].extend_from_slice(record);
+        self.segments[slot_idx].push(b'\n');
+        self.active_records = self.active_records.saturating_add(1);
+        match kind {
+            RetentionEventKind::Metric => {
+                self.metrics_total = self.metrics_total.saturating_add(1);
+                self.pending_10s_metrics = self.pending_10s_metrics.saturating_add(1);
+                let hash = metric_name_hash(metric_name, self.limits.retention_rollup_hash_bits);
+                tally_add(&mut self.pending_10s_tally, MetricTally { name_hash: hash, count: 1 });
+            }
+            RetentionEventKind::Span => {
+                self.spans_total = self.spans_total.saturating_add(1);
+                self.pending_10s_spans = self.pending_10s_spans.saturating_add(1);
+            }
+        }
+
+        let total_events = self.metrics_total.saturating_add(self.spans_total);
+        let mut rollup_10s = None;
+        let mut rollup_60s = None;
+        let mut gc_rollup_10s = Vec::new();
+        let mut gc_rollup_60s = Vec::new();
+
+        if total_events.checked_rem(self.limits.retention_rollup_every as u64) == Some(0) {
+            let window_10s_id = total_events / self.limits.retention_rollup_every as u64;
+            let tally_10s = core::mem::take(&mut self.pending_10s_tally);
+            for entry in tally_10s.iter().copied() {
+                tally_add(&mut self.pending_60s_tally, entry);
+            }
+            let window_10s = RollupWindow {
+                id: window_10s_id,
+                metrics: self.pending_10s_metrics,
+                spans: self.pending_10s_spans,
+                top: top_k(tally_10s, self.limits.retention_rollup_top_k),
+            };
+            self.pending_10s_metrics = 0;
+            self.pending_10s_spans = 0;
+            self.rollup_10s_windows.push_back(window_10s.id);
+            rollup_10s = Some(RollupFrame {
+                window_id: window_10s.id,
+                bytes: encode_rollup_window("10s", &window_10s, self.hash_digits()),
+            });
+            gc_rollup_10s = trim_rollup_windows(
+                &mut self.rollup_10s_windows,
+                self.limits.retention_ttl_windows,
+                self.limits.retention_gc_batch,
+            );
+
+            self.pending_60s_count = self.pending_60s_count.saturating_add(1);
+            self.pending_60s_metrics = self.pending_60s_metrics.saturating_add(window_10s.metrics);
+            self.pending_60s_spans = self.pending_60s_spans.saturating_add(window_10s.spans);
+            if self.pending_60s_count >= 6 {
+                let window_60s_id = window_10s_id / 6;
+                let window_60s = RollupWindow {
+                    id: window_60s_id,
+                    metrics: self.pending_60s_metrics,
+                    spans: self.pending_60s_spans,
+                    top: top_k(
+                        core::mem::take(&mut self.pending_60s_tally),
+                        self.limits.retention_rollup_top_k,
+                    ),
+                };
+                self.pending_60s_count = 0;
+                self.pending_60s_metrics = 0;
+                self.pending_60s_spans = 0;
+                self.rollup_60s_windows.push_back(window_60s.id);
+                rollup_60s = Some(RollupFrame {
+                    window_id: window_60s.id,
+                    bytes: encode_rollup_window("60s", &window_60s, self.hash_digits()),
+                });
+                gc_rollup_60s = trim_rollup_windows(
+                    &mut self.rollup_60s_windows,
+                    self.limits.retention_ttl_windows,
+                    self.limits.retention_gc_batch,
+                );
+            }
+        }
+
+        let update = RetentionUpdate {
+            wal_slot: slot,
+            wal_bytes: self.segments[slot_idx].clone(),
+            rollup_10s,
+            rollup_60s,
+            gc_rollup_10s,
+            gc_rollup_60s,
+        };
+        if self.active_records >= self.limits.retention_max_records_per_segment {
+            self.active_records = 0;
+            self.active_segment = self.active_segment.saturating_add(1);
+        }
+        Some(update)
+    }
+
+    fn hash_digits(&self) -> usize {
+        (self.limits.retention_rollup_hash_bits as usize).div_ceil(4)
+    }
+}
+
+/// FNV-1a 64 of the metric name, truncated to the low `bits` bits.
+pub fn metric_name_hash(name: &[u8], bits: u32) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for b in name.iter().copied() {
+        hash ^= u64::from(b);
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    if bits >= 64 {
+        hash
+    } else {
+        hash & ((1u64 << bits) - 1)
+    }
+}
+
+/// Adds `entry` into `tally`. Names beyond `ROLLUP_TRACKED_METRICS` are dropped.
+fn tally_add(tally: &mut Vec<MetricTally>, entry: MetricTally) {
+    if let Some(slot) = tally.iter_mut().find(|t| t.name_hash == entry.name_hash) {
+        slot.count = slot.count.saturating_add(entry.count);
+    } else if tally.len() < ROLLUP_TRACKED_METRICS {
+        tally.push(entry);
+    }
+}
+
+/// Orders by count descending, then name hash ascending, and keeps `k` entries.
+fn top_k(mut tally: Vec<MetricTally>, k: u32) -> Vec<MetricTally> {
+    tally.sort_unstable_by(|a, b| b.count.cmp(&a.count).then(a.name_hash.cmp(&b.name_hash)));
+    tally.truncate(k as usize);
+    tally
+}
+
+fn trim_rollup_windows(windows: &mut VecDeque<u64>, ttl: u32, gc_batch: u32) -> Vec<u64> {
+    let mut gc_ids = Vec::new();
+    while windows.len() > ttl as usize && gc_ids.len() < gc_batch as usize {
+        if let Some(old) = windows.pop_front() {
+            gc_ids.push(old);
+        } else {
+            break;
+        }
+    }
+    gc_ids
+}
+
+fn encode_rollup_window(kind: &str, window: &RollupWindow, hash_digits: usize) -> Vec<u8> {
+    let mut out = String::new();
+    out.push_str("kind=");
+    out.push_str(kind);
+    out.push('\n');
+    out.push_str("window_id=");
+    out.push_str(utoa(window.id).as_str());
+    out.push('\n');
+    out.push_str("metrics_total=");
+    out.push_str(utoa(window.metrics).as_str());
+    out.push('\n');
+    out.push_str("spans_total=");
+    out.push_str(utoa(window.spans).as_str());
+    out.push('\n');
+    for (rank, entry) in window.top.iter().enumerate() {
+        out.push_str("top_");
+        out.push_str(utoa(rank as u64).as_str());
+        out.push_str("=0x");
+        push_hex(&mut out, entry.name_hash, hash_digits);
+        out.push(':');
+        out.push_str(utoa(entry.count).as_str());
+        out.push('\n');
+    }
+    out.into_bytes()
+}
+
+fn push_hex(out: &mut String, value: u64, digits: usize) {
+    const HEX: &[u8; 16] = b"0123456789abcdef";
+    for i in (0..digits.min(16)).rev() {
+        out.push(HEX[((value >> (i * 4)) & 0xf) as usize] as char);
+    }
+}
+
+pub(crate) fn utoa(mut value: u64) -> String {
+    let mut out = [0u8; 20];
+    let mut idx = out.len();
+    if value == 0 {
+        idx = idx.saturating_sub(1);
+        out[idx] = b'0';
+    } else {
+        while value != 0 {
+            idx = idx.saturating_sub(1);
+            out[idx] = b'0' + (value % 10) as u8;
+            value /= 10;
+        }
+    }
+    let mut s = String::new();
+    for b in out[idx..].iter().copied() {
+        s.push(b as char);
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retention_engine_rotates_ring_segments() {
+        let limits = RuntimeLimits {
+            retention_max_segments: 2,
+            retention_max_records_per_segment: 2,
+            retention_rollup_every: 10,
+            ..RuntimeLimits::default()
+        };
+        let mut retention = RetentionEngine::new(limits);
+        let a = retention.append(RetentionEventKind::Metric, b"m", b"r1").unwrap();
+        let b = retention.append(RetentionEventKind::Metric, b"m", b"r2").unwrap();
+        let c = retention.append(RetentionEventKind::Metric, b"m", b"r3").unwrap();
+        assert_eq!(a.wal_slot, 0);
+        assert_eq!(b.wal_slot, 0);
+        assert_eq!(c.wal_slot, 1);
+    }
+
+    #[test]
+    fn test_retention_engine_emits_rollup_deterministically() {
+        let limits = RuntimeLimits { retention_rollup_every: 2, ..RuntimeLimits::default() };
+        let mut retention = RetentionEngine::new(limits);
+        let first = retention.append(RetentionEventKind::Metric, b"m", b"m1").unwrap();
+        assert!(first.rollup_10s.is_none());
+        let second = retention.append(RetentionEventKind::Span, b"", b"s1").unwrap();
+        let rollup = second.rollup_10s.map(|r| r.bytes).unwrap_or_default();
+        assert!(rollup.starts_with(b"kind=10s\nwindow_id=1\nmetrics_total=1\nspans_total=1\n"));
+        assert!(second.rollup_60s.is_none());
+    }
+
+    #[test]
+    fn test_retention_engine_emits_60s_rollup_after_six_10s_windows() {
+        let limits = RuntimeLimits { retention_rollup_every: 1, ..RuntimeLimits::default() };
+        let mut retention = RetentionEngine::new(limits);
+        let mut saw_60 = None;
+        for i in 0..6 {
+            let kind =
+                if i % 2 == 0 { RetentionEventKind::Metric } else { RetentionEventKind::Span };
+            let update = retention.append(kind, b"m", b"x").unwrap();
+            if update.rollup_60s.is_some() {
+                saw_60 = update.rollup_60s.map(|r| r.bytes);
+            }
+        }
+        let rollup_60 = saw_60.unwrap_or_default();
+        assert!(rollup_60.starts_with(b"kind=60s\nwindow_id=1\nmetrics_total=3\nspans_total=3\n"));
+    }
+
+    #[test]
+    fn test_retention_engine_ttl_gc_is_bounded_deterministic() {
+        let limits = RuntimeLimits {
+            retention_rollup_every: 1,
+            retention_ttl_windows: 2,
+            retention_gc_batch: 1,
+            ..RuntimeLimits::default()
+        };
+        let mut retention = RetentionEngine::new(limits);
+        let _ = retention.append(RetentionEventKind::Metric, b"a", b"a").unwrap();
+        let _ = retention.append(RetentionEventKind::Metric, b"b", b"b").unwrap();
+        let c = retention.append(RetentionEventKind::Metric, b"c", b"c").unwrap();
+        // ttl=2 and gc_batch=1 => exactly one stale rollup key per update.
+        assert_eq!(c.gc_rollup_10s.len(), 1);
+        assert_eq!(c.gc_rollup_10s[0], 1);
+    }
+
+    fn expected_top_line(rank: u64, name: &[u8], bits: u32, count: u64) -> String {
+        let mut line = String::from("top_");
+        line.push_str(utoa(rank).as_str());
+        line.push_str("=0x");
+        push_hex(&mut line, metric_name_hash(name, bits), (bits as usize).div_ceil(4));
+        line.push(':');
+        line.push_str(utoa(count).as_str());
+        line.push('\n');
+        line
+    }
+
+    #[test]
+    fn test_rollup_top_k_orders_by_count_then_hash() {
+        let limits = RuntimeLimits {
+            retention_rollup_every: 8,
+            retention_rollup_top_k: 3,
+            retention_rollup_hash_bits: 16,
+            ..RuntimeLimits::default()
+        };
+        let mut retention = RetentionEngine::new(limits);
+        // a:3, b:2, c:2, d:1 → top-3 is a, then b/c ordered by hash.
+        let names: [&[u8]; 7] = [b"a", b"b", b"a", b"c", b"d", b"b", b"c"];
+        for name in names {
+            assert!(retention
+                .append(RetentionEventKind::Metric, name, b"r")
+                .unwrap()
+                .rollup_10s
+                .is_none());
+        }
+        let update = retention.append(RetentionEventKind::Metric, b"a", b"r").unwrap();
+        let rollup = String::from_utf8(update.rollup_10s.map(|r| r.bytes).unwrap_or_default())
+            .unwrap_or_default();
+        let (hb, hc) = (metric_name_hash(b"b", 16), metric_name_hash(b"c", 16));
+        let (second, third): (&[u8], &[u8]) = if hb < hc { (b"b", b"c") } else { (b"c", b"b") };
+        let mut expected = String::from("kind=10s\nwindow_id=1\nmetrics_total=8\nspans_total=0\n");
+        expected.push_str(expected_top_line(0, b"a", 16, 3).as_str());
+        expected.push_str(expected_top_line(1, second, 16, 2).as_str());
+        expected.push_str(expected_top_line(2, third, 16, 2).as_str());
+        assert_eq!(rollup, expected);
+    }
+
+    #[test]
+    fn test_rollup_tally_drops_names_beyond_tracked_set() {
+        let limits = RuntimeLimits {
+            retention_rollup_every: ROLLUP_TRACKED_METRICS as u32 + 2,
+            retention_rollup_top_k: ROLLUP_TRACKED_METRICS as u32,
+            ..RuntimeLimits::default()
+        };
+        let mut retention = RetentionEngine::new(limits);
+        let mut last = None;
+        for i in 0..ROLLUP_TRACKED_METRICS + 2 {
+            let name = utoa(i as u64);
+            last = retention.append(RetentionEventKind::Metric, name.as_bytes(), b"r");
+        }
+        let rollup = last.and_then(|u| u.rollup_10s).map(|r| r.bytes).unwrap_or_default();
+        let text = String::from_utf8(rollup).unwrap_or_default();
+        assert_eq!(text.lines().filter(|l| l.starts_with("top_")).count(), ROLLUP_TRACKED_METRICS);
+        // The two overflow names were dropped; the totals still count them.
+        assert!(text.contains("metrics_total=34\n"));
+        let dropped = metric_name_hash(b"33", limits.retention_rollup_hash_bits);
+        let mut needle = String::from("=0x");
+        push_hex(&mut needle, dropped, 8);
+        assert!(!text.contains(needle.as_str()));
+    }
+
+    #[test]
+    fn test_metric_name_hash_truncates_to_width() {
+        assert_eq!(metric_name_hash(b"", 64), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(metric_name_hash(b"", 16), 0x2325);
+        assert!(metric_name_hash(b"sched.wakeups", 12) < (1 << 12));
+    }
+}
@@ -0,0 +1,184 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+//
+//! CONTEXT: metricsd self-metrics — registry health reported as ordinary series
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: Host unit tests in this module
+//! ADR: docs/rfcs/RFC-0024-observability-v2-metrics-tracing-contract-v1.md
+//!
+//! [`Registry::emit_self_metrics`] writes metricsd's own state into the registry, so the same
+//! scrape and remote-write export that carry client metrics carry it too:
+//!
+//! - `metricsd.series_total` (gauge): client series held
+//! - `metricsd.live_spans` (gauge): spans started and not yet ended
+//! - `metricsd.rejections_total{reason=...}` (counter): rejected frames since start, one series
+//!   per reason (`invalid_args`, `over_limit`, `rate_limited`, `not_found`)
+//!
+//! The series belong to [`SELF_SENDER_ID`]. They sit outside the client cardinality caps
+//! (`max_series_total`, `max_series_per_metric`) and are not counted in `series_total`; no
+//! client can write them, since ingest from that id is rejected. The reject totals are kept
+//! apart from the drainable audit log, so draining it does not reset them.
+//!
+//! INVARIANTS:
+//! - Bounded: exactly six self series, created on the first emit
+//! - Derived state: left out of registry snapshots and rebuilt by the next emit
+//! - Gauges record history at `now_ns` like `gauge_set_at` when gauge history is enabled
+
+use crate::{MetricKind, Registry, RejectReason};
+
+/// Sender id owning the self-metrics series; ingest from it is rejected.
+pub const SELF_SENDER_ID: u64 = 0;
+
+const SERIES_TOTAL: &[u8] = b"metricsd.series_total";
+const LIVE_SPANS: &[u8] = b"metricsd.live_spans";
+const REJECTIONS_TOTAL: &[u8] = b"metricsd.rejections_total";
+
+/// `reason=` label of each `metricsd.rejections_total` series, in tally order.
+const REASON_LABELS: [&[u8]; 4] = [
+    b"reason=invalid_args\n",
+    b"reason=over_limit\n",
+    b"reason=rate_limited\n",
+    b"reason=not_found\n",
+];
+
+/// Rejected frames per reason since the registry was created.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct RejectTotals {
+    counts: [u64; 4],
+}
+
+impl RejectTotals {
+    pub(crate) fn note(&mut self, reason: RejectReason) {
+        let slot = match reason {
+            RejectReason::InvalidArgs => 0,
+            RejectReason::OverLimit(_) => 1,
+            RejectReason::RateLimited => 2,
+            RejectReason::NotFound => 3,
+        };
+        self.counts[slot] = self.counts[slot].saturating_add(1);
+    }
+}
+
+impl Registry {
+    /// Refreshes the `metricsd.*` self-metrics series from the current registry state.
+    pub fn emit_self_metrics(&mut self, now_ns: u64) {
+        let series_total = self.client_series_count();
+        let live_spans = self.live_spans.len();
+        self.set_self_gauge(SERIES_TOTAL, series_total, now_ns);
+        self.set_self_gauge(LIVE_SPANS, live_spans, now_ns);
+        for (labels, count) in REASON_LABELS.iter().zip(self.reject_totals.counts) {
+            let idx = self.self_series(MetricKind::Counter, REJECTIONS_TOTAL, labels);
+            self.series[idx].counter_value = count;
+        }
+    }
+
+    /// Series owned by clients, i.e. everything the cardinality caps apply to.
+    pub(crate) fn client_series_count(&self) -> usize {
+        self.series.iter().filter(|entry| entry.sender_service_id != SELF_SENDER_ID).count()
+    }
+
+    fn set_self_gauge(&mut self, name: &[u8], value: usize, now_ns: u64) {
+        let value = i64::try_from(value).unwrap_or(i64::MAX);
+        let idx = self.self_series(MetricKind::Gauge, name, b"");
+        self.series[idx].gauge_value = value;
+        self.record_gauge_history(idx, value, now_ns);
+    }
+
+    /// Index of a self series, created without the client checks and caps.
+    fn self_series(&mut self, kind: MetricKind, name: &[u8], labels: &[u8]) -> usize {
+        match self.find_series(SELF_SENDER_ID, kind, name, labels) {
+            Some(idx) => idx,
+            None => self.push_series(SELF_SENDER_ID, kind, name, labels),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LimitKind, RuntimeLimits};
+
+    fn export(reg: &Registry) -> String {
+        String::from_utf8(reg.export_metrics_text(0)).unwrap_or_default()
+    }
+
+    #[test]
+    fn self_metrics_reflect_ingest_spans_and_rejections() {
+        let mut reg = Registry::new();
+        assert_eq!(reg.counter_inc(3, b"net.rx", b"", 1), Ok(1));
+        assert_eq!(reg.gauge_set(4, b"mem.free", b"", 7), Ok(7));
+        let span = crate::SpanStartArgs {
+            sender_service_id: 3,
+            span_id: (3 << 32) | 1,
+            trace_id: 1,
+            parent_span_id: 0,
+            start_ns: 0,
+            name: b"op",
+            attrs: b"",
+        };
+        assert_eq!(reg.span_start(span), Ok(()));
+        reg.note_reject(3, 1, RejectReason::InvalidArgs);
+        reg.note_reject(4, 1, RejectReason::InvalidArgs);
+        reg.note_reject(4, 2, RejectReason::RateLimited);
+        // Draining the audit log leaves the totals alone.
+        assert_eq!(reg.take_rejections().len(), 3);
+
+        reg.emit_self_metrics(10);
+        let text = export(&reg);
+        let sender = "sender_service_id=\"0000000000000000\"";
+        for line in [
+            format!("metricsd_series_total{{{sender}}} 2"),
+            format!("metricsd_live_spans{{{sender}}} 1"),
+            format!("metricsd_rejections_total{{reason=\"invalid_args\",{sender}}} 2"),
+            format!("metricsd_rejections_total{{reason=\"over_limit\",{sender}}} 0"),
+            format!("metricsd_rejections_total{{reason=\"rate_limited\",{sender}}} 1"),
+            format!("metricsd_rejections_total{{reason=\"not_found\",{sender}}} 0"),
+        ] {
+            assert!(text.lines().any(|l| l == line), "missing {line} in\n{text}");
+        }
+
+        // A second emit updates the same six series.
+        assert!(reg.span_end(3, (3 << 32) | 1, 5, 0, b"").is_ok());
+        reg.note_reject(4, 1, RejectReason::OverLimit(LimitKind::SeriesTotal));
+        reg.emit_self_metrics(20);
+        let text = export(&reg);
+        assert!(text.contains(&format!("metricsd_live_spans{{{sender}}} 0\n")));
+        assert!(text
+            .contains(&format!("metricsd_rejections_total{{reason=\"over_limit\",{sender}}} 1\n")));
+        assert_eq!(reg.series.len(), 2 + 6);
+    }
+
+    #[test]
+    fn test_reject_self_series_neither_capped_nor_client_writable() {
+        let limits = RuntimeLimits {
+            max_series_total: 2,
+            max_series_per_metric: 1,
+            ..RuntimeLimits::default()
+        };
+        let mut reg = Registry::new_with_limits(limits);
+        reg.emit_self_metrics(0);
+        // Six self series, and clients still get their full budget.
+        assert_eq!(reg.counter_inc(3, b"a", b"", 1), Ok(1));
+        assert_eq!(reg.counter_inc(3, b"b", b"", 1), Ok(1));
+        assert_eq!(
+            reg.counter_inc(3, b"c", b"", 1),
+            Err(RejectReason::OverLimit(LimitKind::SeriesTotal))
+        );
+        assert_eq!(reg.apply_limits(limits), Ok(()));
+
+        assert_eq!(
+            reg.gauge_set(SELF_SENDER_ID, SERIES_TOTAL, b"", 99),
+            Err(RejectReason::InvalidArgs)
+        );
+        reg.emit_self_metrics(0);
+        assert!(export(&reg)
+            .contains("metricsd_series_total{sender_service_id=\"0000000000000000\"} 2\n"));
+
+        // Self series are derived: snapshots leave them out.
+        let mut restored = Registry::new_with_limits(limits);
+        assert_eq!(restored.restore(&reg.snapshot()), Ok(()));
+        assert_eq!(restored.series.len(), 2);
+    }
+}
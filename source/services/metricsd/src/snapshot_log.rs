@@ -0,0 +1,82 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+//
+//! CONTEXT: metricsd os-lite snapshot and span-end log lines exported to logd
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: QEMU marker proofs via selftest-client
+//! ADR: docs/rfcs/RFC-0024-observability-v2-metrics-tracing-contract-v1.md
+//!
+//! One `nexus_log` line per accepted update or ended span. Snapshot lines fold out of the
+//! collapsed boot overview (RFC-0068); span-end lines are always emitted.
+
+use nexus_metrics::{MetricScale, ScaledValue};
+
+use crate::records::{as_utf8_or_placeholder, escaped_attrs_or_placeholder};
+
+pub(crate) fn log_counter_snapshot(name: &[u8], value: u64) {
+    // RFC-0068: per-counter telemetry folds out of the collapsed boot overview
+    // (`NEXUS_LOG_EXPAND=metricsd` to recall it); proof boots (fold off) still print it.
+    if nexus_abi::service_trace() {
+        return;
+    }
+    nexus_log::info("metricsd", |line| {
+        line.text("metrics snapshot counter name=");
+        line.text(as_utf8_or_placeholder(name));
+        line.text(" value=");
+        line.fmt(format_args!("{}", ScaledValue::unsigned(value, MetricScale::of(name))));
+    });
+}
+
+pub(crate) fn log_gauge_snapshot(name: &[u8], value: i64) {
+    if nexus_abi::service_trace() {
+        return;
+    }
+    nexus_log::info("metricsd", |line| {
+        line.text("metrics snapshot gauge name=");
+        line.text(as_utf8_or_placeholder(name));
+        line.text(" value=");
+        line.fmt(format_args!("{}", ScaledValue::signed(value, MetricScale::of(name))));
+    });
+}
+
+pub(crate) fn log_hist_snapshot(name: &[u8], count: u64, sum: u64) {
+    if nexus_abi::service_trace() {
+        return;
+    }
+    nexus_log::info("metricsd", |line| {
+        line.text("metrics snapshot histogram name=");
+        line.text(as_utf8_or_placeholder(name));
+        line.text(" count=");
+        line.dec(count);
+        line.text(" sum=");
+        line.dec(sum);
+    });
+}
+
+pub(crate) fn log_span_end(
+    name: &[u8],
+    parent_span_id: u64,
+    duration_ns: u64,
+    status: u8,
+    start_attrs: &[u8],
+    end_attrs: &[u8],
+) {
+    let start_attrs_text = escaped_attrs_or_placeholder(start_attrs);
+    let end_attrs_text = escaped_attrs_or_placeholder(end_attrs);
+    nexus_log::info("metricsd", |line| {
+        line.text("tracing span end name=");
+        line.text(as_utf8_or_placeholder(name));
+        line.text(" parent_span_id=");
+        line.dec(parent_span_id);
+        line.text(" duration_ns=");
+        line.dec(duration_ns);
+        line.text(" status=");
+        line.dec(status as u64);
+        line.text(" start_attrs=");
+        line.text(start_attrs_text.as_str());
+        line.text(" end_attrs=");
+        line.text(end_attrs_text.as_str());
+    });
+}
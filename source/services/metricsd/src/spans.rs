@@ -0,0 +1,524 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+//
+//! CONTEXT: metricsd live span table — start/end pairing and stale-span expiry
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: Host unit tests in this module
+//! ADR: docs/rfcs/RFC-0024-observability-v2-metrics-tracing-contract-v1.md
+//!
+//! INVARIANTS:
+//! - Span IDs are bound to the kernel sender identity (upper 32 bits)
+//! - Expiry is driven only by the injected `now_ns` (no wall clock)
+//! - A non-zero parent must be a span the same sender started: live, or among its last
+//!   [`RECENT_ENDED_SPANS_PER_SENDER`] ended spans (children may outlive their parent)
+//! - With `span_sample_rate_denominator = N`, only spans whose local ID (low 32 bits) is a
+//!   multiple of N are admitted; the rest get `RateLimited` and never enter the table, so
+//!   their ends are `NotFound` and children naming them as parent are `InvalidArgs`
+//! - Links (`span_start_linked`) are stored as given, at most `MAX_SPAN_LINKS`; unlike the
+//!   parent they may name any sender's spans and are not checked against the table
+
+use alloc::vec::Vec;
+
+use nexus_metrics::MAX_SPAN_LINKS;
+
+use crate::{LimitKind, Registry, RejectReason, RuntimeLimits, RATE_MAX_SUBJECTS};
+
+/// `EndedSpan::status` for spans force-ended by [`Registry::expire_stale_spans`].
+pub const SPAN_STATUS_EXPIRED: u8 = 0xFE;
+/// Recently ended span IDs remembered per sender as valid parents.
+pub const RECENT_ENDED_SPANS_PER_SENDER: usize = 16;
+/// Senders with a tracked ended-span window.
+pub const MAX_ENDED_SPAN_SENDERS: usize = RATE_MAX_SUBJECTS;
+
+#[derive(Clone, Debug)]
+pub(crate) struct LiveSpan {
+    sender_service_id: u64,
+    span_id: u64,
+    trace_id: u64,
+    parent_span_id: u64,
+    name: Vec<u8>,
+    start_attrs: Vec<u8>,
+    start_ns: u64,
+    links: Vec<u64>,
+}
+
+impl LiveSpan {
+    /// Whether the stored name and start attrs fit the field lengths of `limits`.
+    pub(crate) fn fields_fit(&self, limits: &RuntimeLimits) -> bool {
+        self.name.len() <= limits.max_span_name_len
+            && self.start_attrs.len() <= limits.max_attrs_len
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EndedSpan {
+    pub sender_service_id: u64,
+    pub span_id: u64,
+    pub trace_id: u64,
+    pub parent_span_id: u64,
+    pub name: Vec<u8>,
+    pub start_attrs: Vec<u8>,
+    pub end_attrs: Vec<u8>,
+    pub duration_ns: u64,
+    pub status: u8,
+    /// Linked (non-parent) span IDs, in the order the start named them.
+    pub links: Vec<u64>,
+}
+
+/// Span start request payload for bounded registry insertion.
+pub struct SpanStartArgs<'a> {
+    pub sender_service_id: u64,
+    pub span_id: u64,
+    pub trace_id: u64,
+    pub parent_span_id: u64,
+    pub start_ns: u64,
+    pub name: &'a [u8],
+    pub attrs: &'a [u8],
+}
+
+#[derive(Clone, Debug)]
+struct SenderEndedSpans {
+    sender_service_id: u64,
+    /// Span IDs, oldest first.
+    recent: Vec<u64>,
+}
+
+/// Bounded per-sender LRU of ended span IDs; the oldest entry (and sender) goes first.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct RecentEndedSpans {
+    senders: Vec<SenderEndedSpans>,
+}
+
+impl RecentEndedSpans {
+    fn contains(&self, sender_service_id: u64, span_id: u64) -> bool {
+        self.senders
+            .iter()
+            .find(|s| s.sender_service_id == sender_service_id)
+            .is_some_and(|s| s.recent.contains(&span_id))
+    }
+
+    fn record(&mut self, sender_service_id: u64, span_id: u64) {
+        let pos = match self.senders.iter().position(|s| s.sender_service_id == sender_service_id) {
+            Some(pos) => pos,
+            None => {
+                if self.senders.len() >= MAX_ENDED_SPAN_SENDERS {
+                    self.senders.remove(0);
+                }
+                self.senders.push(SenderEndedSpans { sender_service_id, recent: Vec::new() });
+                self.senders.len() - 1
+            }
+        };
+        let recent = &mut self.senders[pos].recent;
+        if recent.len() >= RECENT_ENDED_SPANS_PER_SENDER {
+            recent.remove(0);
+        }
+        recent.push(span_id);
+    }
+}
+
+impl Registry {
+    /// Opens a span; `parent_span_id` is 0 for a root span.
+    ///
+    /// A non-zero parent that this sender never started (or that fell out of its
+    /// ended-span window) is rejected with `InvalidArgs`, so a trace cannot hang
+    /// off another service's span or a made-up ID.
+    pub fn span_start(&mut self, args: SpanStartArgs<'_>) -> Result<(), RejectReason> {
+        self.span_start_linked(args, &[])
+    }
+
+    /// [`Registry::span_start`] that also records up to `MAX_SPAN_LINKS` linked span IDs.
+    ///
+    /// More links than that are rejected with `InvalidArgs`.
+    pub fn span_start_linked(
+        &mut self,
+        args: SpanStartArgs<'_>,
+        links: &[u64],
+    ) -> Result<(), RejectReason> {
+        let SpanStartArgs {
+            sender_service_id,
+            span_id,
+            trace_id,
+            parent_span_id,
+            start_ns,
+            name,
+            attrs,
+        } = args;
+        self.check_sender(sender_service_id)?;
+        if !span_id_matches_sender(sender_service_id, span_id) {
+            return Err(RejectReason::InvalidArgs);
+        }
+        if name.is_empty() || links.len() > MAX_SPAN_LINKS {
+            return Err(RejectReason::InvalidArgs);
+        }
+        if name.len() > self.limits.max_span_name_len || attrs.len() > self.limits.max_attrs_len {
+            return Err(RejectReason::OverLimit(LimitKind::FieldLen));
+        }
+        if !sampled(span_id, self.limits.span_sample_rate_denominator) {
+            return Err(RejectReason::RateLimited);
+        }
+        if self
+            .live_spans
+            .iter()
+            .any(|span| span.sender_service_id == sender_service_id && span.span_id == span_id)
+        {
+            return Err(RejectReason::InvalidArgs);
+        }
+        if parent_span_id != 0 && !self.parent_known(sender_service_id, parent_span_id) {
+            return Err(RejectReason::InvalidArgs);
+        }
+        if self.live_spans.len() >= self.limits.max_live_spans {
+            return Err(RejectReason::OverLimit(LimitKind::LiveSpans));
+        }
+        self.live_spans.push(LiveSpan {
+            sender_service_id,
+            span_id,
+            trace_id,
+            parent_span_id,
+            name: name.to_vec(),
+            start_attrs: attrs.to_vec(),
+            start_ns,
+            links: links.to_vec(),
+        });
+        Ok(())
+    }
+
+    pub fn span_end(
+        &mut self,
+        sender_service_id: u64,
+        span_id: u64,
+        end_ns: u64,
+        status: u8,
+        attrs: &[u8],
+    ) -> Result<EndedSpan, RejectReason> {
+        if attrs.len() > self.limits.max_attrs_len {
+            return Err(RejectReason::OverLimit(LimitKind::FieldLen));
+        }
+        if let Some(pos) = self
+            .live_spans
+            .iter()
+            .position(|span| span.sender_service_id == sender_service_id && span.span_id == span_id)
+        {
+            let span = self.live_spans.swap_remove(pos);
+            self.ended_spans.record(sender_service_id, span_id);
+            let duration_ns = end_ns.saturating_sub(span.start_ns);
+            let ended = EndedSpan {
+                sender_service_id,
+                span_id,
+                trace_id: span.trace_id,
+                parent_span_id: span.parent_span_id,
+                name: span.name,
+                start_attrs: span.start_attrs,
+                end_attrs: attrs.to_vec(),
+                duration_ns,
+                status,
+                links: span.links,
+            };
+            self.ended_spans_ring.push(self.limits.max_ended_spans, &ended);
+            return Ok(ended);
+        }
+        Err(RejectReason::NotFound)
+    }
+
+    /// Force-ends live spans whose age (`now_ns - start_ns`) exceeds `max_age_ns`.
+    ///
+    /// A span whose end event was lost would otherwise hold a `max_live_spans`
+    /// slot forever. Expired spans are returned in table order with
+    /// [`SPAN_STATUS_EXPIRED`] and empty end attributes so callers can still
+    /// record them.
+    pub fn expire_stale_spans(&mut self, now_ns: u64, max_age_ns: u64) -> Vec<EndedSpan> {
+        let mut expired = Vec::new();
+        let ended_spans = &mut self.ended_spans;
+        self.live_spans.retain(|span| {
+            let age_ns = now_ns.saturating_sub(span.start_ns);
+            if age_ns <= max_age_ns {
+                return true;
+            }
+            ended_spans.record(span.sender_service_id, span.span_id);
+            expired.push(EndedSpan {
+                sender_service_id: span.sender_service_id,
+                span_id: span.span_id,
+                trace_id: span.trace_id,
+                parent_span_id: span.parent_span_id,
+                name: span.name.clone(),
+                start_attrs: span.start_attrs.clone(),
+                end_attrs: Vec::new(),
+                duration_ns: age_ns,
+                status: SPAN_STATUS_EXPIRED,
+                links: span.links.clone(),
+            });
+            false
+        });
+        for span in &expired {
+            self.ended_spans_ring.push(self.limits.max_ended_spans, span);
+        }
+        expired
+    }
+
+    fn parent_known(&self, sender_service_id: u64, parent_span_id: u64) -> bool {
+        self.live_spans.iter().any(|span| {
+            span.sender_service_id == sender_service_id && span.span_id == parent_span_id
+        }) || self.ended_spans.contains(sender_service_id, parent_span_id)
+    }
+}
+
+fn span_id_matches_sender(sender_service_id: u64, span_id: u64) -> bool {
+    (span_id >> 32) == (sender_service_id & 0xffff_ffff)
+}
+
+/// Deterministic 1-in-`denominator` pick on the sender-local part of the span ID.
+// `u64::is_multiple_of` is unstable on the pinned toolchain (nightly-2025-01-15).
+#[allow(unknown_lints, clippy::manual_is_multiple_of)]
+fn sampled(span_id: u64, denominator: u32) -> bool {
+    (span_id & 0xffff_ffff) % u64::from(denominator) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MAX_LIVE_SPANS;
+
+    #[test]
+    fn span_lifecycle_start_end_is_deterministic() {
+        let mut reg = Registry::new();
+        let sender = 0x1234u64;
+        let span_id = (sender << 32) | 1;
+        assert!(reg
+            .span_start(SpanStartArgs {
+                sender_service_id: sender,
+                span_id,
+                trace_id: 77,
+                parent_span_id: 0,
+                start_ns: 100,
+                name: b"exec.path",
+                attrs: b"phase=run\n",
+            })
+            .is_ok());
+        let ended = reg.span_end(sender, span_id, 180, 0, b"result=ok\n").unwrap_or(EndedSpan {
+            sender_service_id: 0,
+            span_id: 0,
+            trace_id: 0,
+            parent_span_id: 0,
+            name: Vec::new(),
+            start_attrs: Vec::new(),
+            end_attrs: Vec::new(),
+            duration_ns: 0,
+            status: 255,
+            links: Vec::new(),
+        });
+        assert_eq!(ended.duration_ns, 80);
+        assert_eq!(ended.status, 0);
+        assert_eq!(ended.parent_span_id, 0);
+        assert_eq!(ended.start_attrs.as_slice(), b"phase=run\n");
+        assert_eq!(ended.end_attrs.as_slice(), b"result=ok\n");
+    }
+
+    #[test]
+    fn test_reject_live_span_cap_exceeded() {
+        let mut reg = Registry::new();
+        let sender = 0x42u64;
+        for i in 0..MAX_LIVE_SPANS {
+            let span_id = ((sender & 0xffff_ffff) << 32) | (i as u64 + 1);
+            assert!(reg
+                .span_start(SpanStartArgs {
+                    sender_service_id: sender,
+                    span_id,
+                    trace_id: i as u64,
+                    parent_span_id: 0,
+                    start_ns: i as u64,
+                    name: b"s",
+                    attrs: b"",
+                })
+                .is_ok());
+        }
+        let over = ((sender & 0xffff_ffff) << 32) | 0xffff;
+        assert_eq!(
+            reg.span_start(SpanStartArgs {
+                sender_service_id: sender,
+                span_id: over,
+                trace_id: 999,
+                parent_span_id: 0,
+                start_ns: 999,
+                name: b"s",
+                attrs: b"",
+            }),
+            Err(RejectReason::OverLimit(LimitKind::LiveSpans))
+        );
+    }
+
+    #[test]
+    fn test_reject_payload_identity_spoof() {
+        let mut reg = Registry::new();
+        let spoofed_span = (0x9999u64 << 32) | 1;
+        assert_eq!(
+            reg.span_start(SpanStartArgs {
+                sender_service_id: 0x1111,
+                span_id: spoofed_span,
+                trace_id: 1,
+                parent_span_id: 0,
+                start_ns: 1,
+                name: b"spoof",
+                attrs: b"",
+            }),
+            Err(RejectReason::InvalidArgs)
+        );
+    }
+
+    fn start(
+        reg: &mut Registry,
+        sender: u64,
+        local: u64,
+        start_ns: u64,
+    ) -> Result<(), RejectReason> {
+        reg.span_start(SpanStartArgs {
+            sender_service_id: sender,
+            span_id: (sender << 32) | local,
+            trace_id: local,
+            parent_span_id: 0,
+            start_ns,
+            name: b"s",
+            attrs: b"k=v\n",
+        })
+    }
+
+    fn start_child(
+        reg: &mut Registry,
+        sender: u64,
+        local: u64,
+        parent: u64,
+    ) -> Result<(), RejectReason> {
+        reg.span_start(SpanStartArgs {
+            sender_service_id: sender,
+            span_id: (sender << 32) | local,
+            trace_id: 1,
+            parent_span_id: (sender << 32) | parent,
+            start_ns: 0,
+            name: b"child",
+            attrs: b"",
+        })
+    }
+
+    #[test]
+    fn child_of_live_parent_is_accepted() {
+        let mut reg = Registry::new();
+        assert!(start(&mut reg, 6, 1, 0).is_ok());
+        assert!(start_child(&mut reg, 6, 2, 1).is_ok());
+        assert!(start_child(&mut reg, 6, 3, 2).is_ok());
+        let ended = reg.span_end(6, (6 << 32) | 3, 10, 0, b"");
+        assert_eq!(ended.map(|span| span.parent_span_id), Ok((6 << 32) | 2));
+    }
+
+    #[test]
+    fn test_reject_unknown_or_foreign_parent() {
+        let mut reg = Registry::new();
+        assert_eq!(start_child(&mut reg, 6, 2, 1), Err(RejectReason::InvalidArgs));
+
+        // Another sender's live span is not a valid parent either.
+        assert!(start(&mut reg, 7, 1, 0).is_ok());
+        let foreign = reg.span_start(SpanStartArgs {
+            sender_service_id: 6,
+            span_id: (6 << 32) | 2,
+            trace_id: 1,
+            parent_span_id: (7 << 32) | 1,
+            start_ns: 0,
+            name: b"child",
+            attrs: b"",
+        });
+        assert_eq!(foreign, Err(RejectReason::InvalidArgs));
+        assert_eq!(reg.span_end(6, (6 << 32) | 2, 1, 0, b""), Err(RejectReason::NotFound));
+    }
+
+    #[test]
+    fn parent_ended_within_window_is_accepted() {
+        let mut reg = Registry::new();
+        assert!(start(&mut reg, 6, 1, 0).is_ok());
+        assert!(reg.span_end(6, (6 << 32) | 1, 5, 0, b"").is_ok());
+        assert!(start_child(&mut reg, 6, 2, 1).is_ok());
+
+        // An expired parent counts as ended too.
+        assert!(start(&mut reg, 6, 3, 0).is_ok());
+        assert_eq!(reg.expire_stale_spans(100, 10).len(), 2);
+        assert!(start_child(&mut reg, 6, 4, 3).is_ok());
+
+        // Once pushed out of the window, the parent is unknown.
+        for local in 0..RECENT_ENDED_SPANS_PER_SENDER as u64 {
+            assert!(start(&mut reg, 6, 0x100 + local, 0).is_ok());
+            assert!(reg.span_end(6, (6 << 32) | (0x100 + local), 1, 0, b"").is_ok());
+        }
+        assert_eq!(start_child(&mut reg, 6, 5, 1), Err(RejectReason::InvalidArgs));
+        assert!(
+            start_child(&mut reg, 6, 6, 0x100 + RECENT_ENDED_SPANS_PER_SENDER as u64 - 1).is_ok()
+        );
+    }
+
+    #[test]
+    fn sampler_admits_exactly_one_in_n_and_ends_match() {
+        let limits = RuntimeLimits { span_sample_rate_denominator: 4, ..RuntimeLimits::default() };
+        let mut reg = Registry::new_with_limits(limits);
+        let results: Vec<_> = (1..=40).map(|local| start(&mut reg, 6, local, 0)).collect();
+        for (local, result) in (1..=40u64).zip(&results) {
+            let expected = if local % 4 == 0 { Ok(()) } else { Err(RejectReason::RateLimited) };
+            assert_eq!(*result, expected, "span {local}");
+        }
+        assert_eq!(results.iter().filter(|r| r.is_ok()).count(), 10);
+
+        // Dropped spans never take a slot, so ends only find the admitted ones.
+        for local in 1..=40u64 {
+            let ended = reg.span_end(6, (6 << 32) | local, 5, 0, b"").map(|span| span.span_id);
+            let expected =
+                if local % 4 == 0 { Ok((6 << 32) | local) } else { Err(RejectReason::NotFound) };
+            assert_eq!(ended, expected, "span {local}");
+        }
+        // The decision is per ID, the same for every retry; a sampled child of a dropped
+        // span names an unknown parent.
+        assert_eq!(start(&mut reg, 6, 41, 0), Err(RejectReason::RateLimited));
+        assert_eq!(start(&mut reg, 6, 41, 0), Err(RejectReason::RateLimited));
+        assert_eq!(start_child(&mut reg, 6, 44, 41), Err(RejectReason::InvalidArgs));
+        assert_eq!(start_child(&mut reg, 6, 48, 40), Ok(()));
+    }
+
+    #[test]
+    fn expire_stale_spans_only_takes_over_age() {
+        let mut reg = Registry::new();
+        assert!(start(&mut reg, 3, 1, 100).is_ok());
+        assert!(start(&mut reg, 3, 2, 500).is_ok());
+        assert!(start(&mut reg, 4, 1, 900).is_ok());
+
+        // Exactly max_age old is still live.
+        assert!(reg.expire_stale_spans(1_400, 1_300).is_empty());
+        let expired = reg.expire_stale_spans(1_400, 1_299);
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].span_id, (3 << 32) | 1);
+        assert_eq!(expired[0].duration_ns, 1_300);
+        assert_eq!(expired[0].status, SPAN_STATUS_EXPIRED);
+        assert_eq!(expired[0].start_attrs.as_slice(), b"k=v\n");
+        assert!(expired[0].end_attrs.is_empty());
+
+        // The expired span is gone; the others still end normally.
+        assert_eq!(reg.span_end(3, (3 << 32) | 1, 1_500, 0, b""), Err(RejectReason::NotFound));
+        assert!(reg.span_end(3, (3 << 32) | 2, 1_500, 0, b"").is_ok());
+        assert_eq!(reg.expire_stale_spans(u64::MAX, 0).len(), 1);
+    }
+
+    #[test]
+    fn test_reject_full_table_until_stale_spans_expire() {
+        let mut reg = Registry::new();
+        for i in 0..MAX_LIVE_SPANS as u64 {
+            assert!(start(&mut reg, 5, i + 1, i).is_ok());
+        }
+        assert_eq!(
+            start(&mut reg, 5, 0xffff, 10_000),
+            Err(RejectReason::OverLimit(LimitKind::LiveSpans))
+        );
+        // A clock behind every start expires nothing (saturating age).
+        assert!(reg.expire_stale_spans(0, 0).is_empty());
+
+        let expired = reg.expire_stale_spans(10_000, 10_000 - 4);
+        assert_eq!(expired.len(), 4);
+        for local in 0..4u64 {
+            assert!(start(&mut reg, 5, 0xff00 + local, 10_000).is_ok());
+        }
+        assert!(start(&mut reg, 5, 0xffff, 10_000).is_err());
+    }
+}
@@ -0,0 +1,249 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+//
+//! CONTEXT: metricsd text scrape — every series in the Prometheus text exposition format
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: Host unit tests in this module
+//! ADR: docs/rfcs/RFC-0024-observability-v2-metrics-tracing-contract-v1.md
+//!
+//! [`Registry::export_metrics_text`] renders the registry for a pull-based collector. Each
+//! series keeps its own `key=value\n` labels as `key="value"` pairs followed by
+//! `sender_service_id="<hex16>"`, so series that differ only by labels or by sender stay
+//! distinct. A histogram becomes one cumulative `_bucket` line per bound with `le` first:
+//!
+//! `<name>_bucket{le="1000000",<labels>,sender_service_id="<hex16>"} <n>`
+//!
+//! then `le="+Inf"`, `_sum` and `_count`. Counters and gauges are one line each; windowed
+//! counters are evaluated at the caller's `now_ns` and exported as gauges. Metric names and
+//! label keys map every byte outside `[a-zA-Z0-9_:]` to `_` (`timed.latency` becomes
+//! `timed_latency`); label values escape `\` and `"`, and non-UTF-8 values read `<bin>`.
+//!
+//! INVARIANTS:
+//! - Deterministic: series are sorted by (name, kind, labels, sender); one `# TYPE` line per
+//!   family
+//! - Bounded by [`METRICS_TEXT_MAX_LEN`]; a series never straddles the cut, and a dump that does
+//!   not fit ends with `# truncated omitted=<n>`
+//! - Label pairs without `=` are dropped rather than guessed at
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::records::as_utf8_or_placeholder;
+use crate::{MetricKind, Registry, HIST_BUCKETS_NS};
+
+/// Largest dump [`Registry::export_metrics_text`] produces.
+pub const METRICS_TEXT_MAX_LEN: usize = 16 * 1024;
+/// Bytes kept free for the `# truncated omitted=<n>` line.
+const TRUNCATION_LINE_RESERVE: usize = 40;
+
+impl Registry {
+    /// Every series as Prometheus text, windowed counters evaluated at `now_ns`, at most
+    /// [`METRICS_TEXT_MAX_LEN`] bytes.
+    pub fn export_metrics_text(&self, now_ns: u64) -> Vec<u8> {
+        self.export_metrics(now_ns, METRICS_TEXT_MAX_LEN).0
+    }
+
+    /// Renders sorted series into at most `max_bytes`; `true` when some were left out.
+    fn export_metrics(&self, now_ns: u64, max_bytes: usize) -> (Vec<u8>, bool) {
+        let mut order: Vec<usize> = (0..self.series.len()).collect();
+        order.sort_by(|&a, &b| {
+            let (a, b) = (&self.series[a], &self.series[b]);
+            (&a.name, rank(a.kind), &a.labels, a.sender_service_id).cmp(&(
+                &b.name,
+                rank(b.kind),
+                &b.labels,
+                b.sender_service_id,
+            ))
+        });
+
+        let mut out = Vec::new();
+        let mut family: Option<(&[u8], MetricKind)> = None;
+        for (written, &idx) in order.iter().enumerate() {
+            let entry = &self.series[idx];
+            let name = metric_name(&entry.name);
+            let labels = render_labels(&entry.labels, entry.sender_service_id);
+            let mut block = String::new();
+            if family != Some((entry.name.as_slice(), entry.kind)) {
+                block.push_str(&format!("# TYPE {} {}\n", name, type_name(entry.kind)));
+            }
+            match entry.kind {
+                MetricKind::Counter => {
+                    block.push_str(&format!("{}{{{}}} {}\n", name, labels, entry.counter_value));
+                }
+                MetricKind::Gauge => {
+                    block.push_str(&format!("{}{{{}}} {}\n", name, labels, entry.gauge_value));
+                }
+                MetricKind::WindowedCounter => {
+                    let value = entry.windowed.value(now_ns);
+                    block.push_str(&format!("{}{{{}}} {}\n", name, labels, value));
+                }
+                MetricKind::Histogram => {
+                    let hist = &entry.histogram;
+                    let mut cumulative = 0u64;
+                    for (i, count) in hist.buckets.iter().enumerate() {
+                        cumulative = cumulative.saturating_add(*count);
+                        let le = match HIST_BUCKETS_NS.get(i) {
+                            Some(bound) => format!("{}", bound),
+                            None => String::from("+Inf"),
+                        };
+                        block.push_str(&format!(
+                            "{}_bucket{{le=\"{}\",{}}} {}\n",
+                            name, le, labels, cumulative
+                        ));
+                    }
+                    block.push_str(&format!("{}_sum{{{}}} {}\n", name, labels, hist.sum));
+                    block.push_str(&format!("{}_count{{{}}} {}\n", name, labels, hist.count));
+                }
+            }
+            let last = written + 1 == order.len();
+            let budget =
+                if last { max_bytes } else { max_bytes.saturating_sub(TRUNCATION_LINE_RESERVE) };
+            if out.len() + block.len() > budget {
+                let marker = format!("# truncated omitted={}\n", order.len() - written);
+                if out.len() + marker.len() <= max_bytes {
+                    out.extend_from_slice(marker.as_bytes());
+                }
+                return (out, true);
+            }
+            out.extend_from_slice(block.as_bytes());
+            family = Some((entry.name.as_slice(), entry.kind));
+        }
+        (out, false)
+    }
+}
+
+/// Sort rank of a kind; keeps each `(name, kind)` family contiguous.
+fn rank(kind: MetricKind) -> u8 {
+    match kind {
+        MetricKind::Counter => 0,
+        MetricKind::Gauge => 1,
+        MetricKind::Histogram => 2,
+        MetricKind::WindowedCounter => 3,
+    }
+}
+
+fn type_name(kind: MetricKind) -> &'static str {
+    match kind {
+        MetricKind::Counter => "counter",
+        MetricKind::Gauge | MetricKind::WindowedCounter => "gauge",
+        MetricKind::Histogram => "histogram",
+    }
+}
+
+/// `bytes` with every byte outside `[a-zA-Z0-9_:]` replaced by `_`.
+fn metric_name(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|&b| if b.is_ascii_alphanumeric() || b == b'_' || b == b':' { b as char } else { '_' })
+        .collect()
+}
+
+/// `key="value",...,sender_service_id="<hex16>"` from stored `key=value\n` labels.
+fn render_labels(labels: &[u8], sender_service_id: u64) -> String {
+    let mut out = String::new();
+    for pair in labels.split(|&b| b == b'\n') {
+        let Some(eq) = pair.iter().position(|&b| b == b'=') else {
+            continue;
+        };
+        let (key, value) = (&pair[..eq], &pair[eq + 1..]);
+        if key.is_empty() {
+            continue;
+        }
+        out.push_str(&metric_name(key));
+        out.push_str("=\"");
+        for ch in as_utf8_or_placeholder(value).chars() {
+            if ch == '\\' || ch == '"' {
+                out.push('\\');
+            }
+            out.push(ch);
+        }
+        out.push_str("\",");
+    }
+    out.push_str(&format!("sender_service_id=\"{:016x}\"", sender_service_id));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(reg: &Registry) -> String {
+        String::from_utf8(reg.export_metrics_text(0)).unwrap()
+    }
+
+    #[test]
+    fn labeled_histograms_export_independent_bucket_counts() {
+        let mut reg = Registry::new();
+        // Two label sets of one histogram, interleaved, are two series.
+        assert_eq!(reg.hist_observe(1, b"ipc.latency", b"svc=b\n", 3_000_000), Ok((1, 3_000_000)));
+        assert_eq!(reg.hist_observe(1, b"ipc.latency", b"svc=a\n", 500_000), Ok((1, 500_000)));
+        assert_eq!(
+            reg.hist_observe(1, b"ipc.latency", b"svc=b\n", 200_000_000),
+            Ok((2, 203_000_000))
+        );
+        reg.counter_inc(1, b"ipc.calls", b"svc=\"q\"\n", 4).unwrap();
+
+        let sender = "sender_service_id=\"0000000000000001\"";
+        let expected = [
+            String::from("# TYPE ipc_calls counter"),
+            format!("ipc_calls{{svc=\"\\\"q\\\"\",{}}} 4", sender),
+            String::from("# TYPE ipc_latency histogram"),
+            format!("ipc_latency_bucket{{le=\"1000000\",svc=\"a\",{}}} 1", sender),
+            format!("ipc_latency_bucket{{le=\"5000000\",svc=\"a\",{}}} 1", sender),
+            format!("ipc_latency_bucket{{le=\"20000000\",svc=\"a\",{}}} 1", sender),
+            format!("ipc_latency_bucket{{le=\"100000000\",svc=\"a\",{}}} 1", sender),
+            format!("ipc_latency_bucket{{le=\"+Inf\",svc=\"a\",{}}} 1", sender),
+            format!("ipc_latency_sum{{svc=\"a\",{}}} 500000", sender),
+            format!("ipc_latency_count{{svc=\"a\",{}}} 1", sender),
+            format!("ipc_latency_bucket{{le=\"1000000\",svc=\"b\",{}}} 0", sender),
+            format!("ipc_latency_bucket{{le=\"5000000\",svc=\"b\",{}}} 1", sender),
+            format!("ipc_latency_bucket{{le=\"20000000\",svc=\"b\",{}}} 1", sender),
+            format!("ipc_latency_bucket{{le=\"100000000\",svc=\"b\",{}}} 1", sender),
+            format!("ipc_latency_bucket{{le=\"+Inf\",svc=\"b\",{}}} 2", sender),
+            format!("ipc_latency_sum{{svc=\"b\",{}}} 203000000", sender),
+            format!("ipc_latency_count{{svc=\"b\",{}}} 2", sender),
+        ];
+        assert_eq!(text(&reg).lines().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn senders_and_kinds_stay_distinct_series() {
+        let mut reg = Registry::new();
+        reg.gauge_set(0x22, b"q.depth", b"", -2).unwrap();
+        reg.gauge_set(0x11, b"q.depth", b"", 5).unwrap();
+        reg.hist_observe(0x11, b"q.depth", b"bad-pair\n", 1).unwrap();
+
+        let dump = text(&reg);
+        let lines: Vec<&str> = dump.lines().collect();
+        assert_eq!(lines[0], "# TYPE q_depth gauge");
+        assert_eq!(lines[1], "q_depth{sender_service_id=\"0000000000000011\"} 5");
+        assert_eq!(lines[2], "q_depth{sender_service_id=\"0000000000000022\"} -2");
+        assert_eq!(lines[3], "# TYPE q_depth histogram");
+        assert_eq!(
+            lines[4],
+            "q_depth_bucket{le=\"1000000\",sender_service_id=\"0000000000000011\"} 1"
+        );
+        assert_eq!(lines.len(), 11);
+    }
+
+    #[test]
+    fn test_reject_oversized_dump_is_truncated_and_flagged() {
+        let mut reg = Registry::new();
+        for id in 1..5u64 {
+            reg.counter_inc(id, b"boot.events", b"", 1).unwrap();
+        }
+        let (full, truncated) = reg.export_metrics(0, usize::MAX);
+        assert!(!truncated);
+        let type_line = b"# TYPE boot_events counter\n".len();
+        let line_len = (full.len() - type_line) / 4;
+
+        let cap = type_line + 2 * line_len + TRUNCATION_LINE_RESERVE;
+        let (dump, truncated) = reg.export_metrics(0, cap);
+        assert!(truncated);
+        assert_eq!(&dump[..type_line + 2 * line_len], &full[..type_line + 2 * line_len]);
+        assert_eq!(&dump[type_line + 2 * line_len..], b"# truncated omitted=2\n");
+    }
+}
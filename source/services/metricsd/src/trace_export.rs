@@ -0,0 +1,246 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+//
+//! CONTEXT: metricsd trace export — bounded ring of ended spans and its text dump
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: Host unit tests in this module
+//! ADR: docs/rfcs/RFC-0024-observability-v2-metrics-tracing-contract-v1.md
+//!
+//! Every span that ends (by `span_end` or by expiry) is kept in a ring of the last
+//! `max_ended_spans` so a collector can assemble traces with `OP_SPANS_SCRAPE`. The dump
+//! has one line per span, sorted by `(trace_id, span_id)`:
+//!
+//! `span trace_id=<hex16> span_id=<hex16> parent_span_id=<hex16> name=<name> duration_ns=<n> status=<n>`
+//!
+//! A span started with links appends ` links=<hex16>,<hex16>...` in start order.
+//!
+//! INVARIANTS:
+//! - Bounded: the oldest ended span is dropped first; the ring is volatile (not persisted)
+//! - Attributes are not exported (they stay in the logd/retention records)
+//! - A dump that does not fit ends with `truncated omitted=<n>` instead of a cut line
+
+use alloc::collections::VecDeque;
+use alloc::format;
+use alloc::vec::Vec;
+
+use nexus_metrics::{
+    encode_spans_scrape_response, MAX_SPANS_SCRAPE_TEXT_LEN, SPANS_SCRAPE_FLAG_TRUNCATED,
+};
+
+use crate::records::escaped_attrs_or_placeholder;
+use crate::{EndedSpan, Registry};
+
+/// Bytes kept free for the `truncated omitted=<n>` line.
+const TRUNCATION_LINE_RESERVE: usize = 40;
+
+#[derive(Clone, Debug)]
+struct ExportedSpan {
+    span_id: u64,
+    trace_id: u64,
+    parent_span_id: u64,
+    name: Vec<u8>,
+    duration_ns: u64,
+    status: u8,
+    links: Vec<u64>,
+}
+
+/// Ring of the most recently ended spans, oldest first.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct EndedSpanRing {
+    spans: VecDeque<ExportedSpan>,
+}
+
+impl EndedSpanRing {
+    pub(crate) fn push(&mut self, capacity: usize, span: &EndedSpan) {
+        if capacity == 0 {
+            return;
+        }
+        while self.spans.len() >= capacity {
+            self.spans.pop_front();
+        }
+        self.spans.push_back(ExportedSpan {
+            span_id: span.span_id,
+            trace_id: span.trace_id,
+            parent_span_id: span.parent_span_id,
+            name: span.name.clone(),
+            duration_ns: span.duration_ns,
+            status: span.status,
+            links: span.links.clone(),
+        });
+    }
+}
+
+impl Registry {
+    /// Text dump of the ended-span ring, at most `MAX_SPANS_SCRAPE_TEXT_LEN` bytes.
+    pub fn export_spans_text(&self) -> Vec<u8> {
+        self.export_spans(MAX_SPANS_SCRAPE_TEXT_LEN).0
+    }
+
+    /// `OP_SPANS_SCRAPE` reply carrying [`Registry::export_spans_text`].
+    pub fn spans_scrape_response(&self, nonce: u32) -> Vec<u8> {
+        let (text, truncated) = self.export_spans(MAX_SPANS_SCRAPE_TEXT_LEN);
+        let flags = if truncated { SPANS_SCRAPE_FLAG_TRUNCATED } else { 0 };
+        encode_spans_scrape_response(nonce, flags, &text)
+    }
+
+    /// Renders sorted span lines into at most `max_bytes`; `true` when some were left out.
+    fn export_spans(&self, max_bytes: usize) -> (Vec<u8>, bool) {
+        let spans = &self.ended_spans_ring.spans;
+        let mut order: Vec<usize> = (0..spans.len()).collect();
+        // Stable: a reused ID keeps ring (end) order.
+        order.sort_by_key(|&idx| (spans[idx].trace_id, spans[idx].span_id));
+
+        let mut out = Vec::new();
+        for (written, &idx) in order.iter().enumerate() {
+            let span = &spans[idx];
+            let mut line = format!(
+                "span trace_id={:016x} span_id={:016x} parent_span_id={:016x} name={} \
+                 duration_ns={} status={}",
+                span.trace_id,
+                span.span_id,
+                span.parent_span_id,
+                escaped_attrs_or_placeholder(&span.name),
+                span.duration_ns,
+                span.status,
+            );
+            for (idx, link) in span.links.iter().enumerate() {
+                let sep = if idx == 0 { " links=" } else { "," };
+                line.push_str(&format!("{sep}{link:016x}"));
+            }
+            line.push('\n');
+            let last = written + 1 == order.len();
+            let budget =
+                if last { max_bytes } else { max_bytes.saturating_sub(TRUNCATION_LINE_RESERVE) };
+            if out.len() + line.len() > budget {
+                let marker = format!("truncated omitted={}\n", order.len() - written);
+                if out.len() + marker.len() <= max_bytes {
+                    out.extend_from_slice(marker.as_bytes());
+                }
+                return (out, true);
+            }
+            out.extend_from_slice(line.as_bytes());
+        }
+        (out, false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{RuntimeLimits, SpanStartArgs};
+
+    fn ended(reg: &mut Registry, sender: u64, local: u64, trace_id: u64, duration_ns: u64) {
+        let span_id = (sender << 32) | local;
+        let args = SpanStartArgs {
+            sender_service_id: sender,
+            span_id,
+            trace_id,
+            parent_span_id: 0,
+            start_ns: 0,
+            name: b"op",
+            attrs: b"secret=1\n",
+        };
+        assert!(reg.span_start(args).is_ok());
+        assert!(reg.span_end(sender, span_id, duration_ns, 0, b"").is_ok());
+    }
+
+    #[test]
+    fn export_is_sorted_by_trace_then_span() {
+        let mut reg = Registry::new();
+        ended(&mut reg, 2, 1, 9, 10);
+        ended(&mut reg, 1, 7, 3, 20);
+        ended(&mut reg, 1, 2, 9, 30);
+        assert!(reg
+            .span_start(SpanStartArgs {
+                sender_service_id: 1,
+                span_id: (1 << 32) | 5,
+                trace_id: 3,
+                parent_span_id: (1 << 32) | 7,
+                start_ns: 100,
+                name: b"stuck",
+                attrs: b"",
+            })
+            .is_ok());
+        assert_eq!(reg.expire_stale_spans(200, 50).len(), 1);
+
+        let text = reg.export_spans_text();
+        let text = core::str::from_utf8(&text).unwrap_or("");
+        let expected = "\
+span trace_id=0000000000000003 span_id=0000000100000005 parent_span_id=0000000100000007 name=stuck duration_ns=100 status=254
+span trace_id=0000000000000003 span_id=0000000100000007 parent_span_id=0000000000000000 name=op duration_ns=20 status=0
+span trace_id=0000000000000009 span_id=0000000100000002 parent_span_id=0000000000000000 name=op duration_ns=30 status=0
+span trace_id=0000000000000009 span_id=0000000200000001 parent_span_id=0000000000000000 name=op duration_ns=10 status=0
+";
+        assert_eq!(text, expected);
+        assert!(!text.contains("secret"));
+    }
+
+    #[test]
+    fn linked_span_exports_its_links_in_start_order() {
+        let mut reg = Registry::new();
+        let span_id = (3 << 32) | 1;
+        let args = |span_id| SpanStartArgs {
+            sender_service_id: 3,
+            span_id,
+            trace_id: 5,
+            parent_span_id: 0,
+            start_ns: 0,
+            name: b"batch",
+            attrs: b"",
+        };
+        assert!(reg.span_start_linked(args(span_id), &[(4 << 32) | 2, (2 << 32) | 9]).is_ok());
+        assert!(reg.span_end(3, span_id, 7, 0, b"").is_ok());
+
+        let text = reg.export_spans_text();
+        let text = core::str::from_utf8(&text).unwrap_or("");
+        assert_eq!(
+            text,
+            "span trace_id=0000000000000005 span_id=0000000300000001 \
+             parent_span_id=0000000000000000 name=batch duration_ns=7 status=0 \
+             links=0000000400000002,0000000200000009\n"
+        );
+
+        let links = [1u64; nexus_metrics::MAX_SPAN_LINKS + 1];
+        let over = reg.span_start_linked(args((3 << 32) | 2), &links);
+        assert_eq!(over, Err(crate::RejectReason::InvalidArgs));
+    }
+
+    #[test]
+    fn ring_drops_oldest_when_full() {
+        let limits = RuntimeLimits { max_ended_spans: 2, ..RuntimeLimits::default() };
+        let mut reg = Registry::new_with_limits(limits);
+        for local in 1..=3u64 {
+            ended(&mut reg, 4, local, 1, local);
+        }
+        let text = reg.export_spans_text();
+        let text = core::str::from_utf8(&text).unwrap_or("");
+        assert_eq!(text.lines().count(), 2);
+        assert!(!text.contains("span_id=0000000400000001"));
+        assert!(
+            text.contains("span_id=0000000400000002") && text.contains("span_id=0000000400000003")
+        );
+    }
+
+    #[test]
+    fn test_reject_oversized_dump_is_truncated_and_flagged() {
+        let mut reg = Registry::new();
+        for local in 1..=4u64 {
+            ended(&mut reg, 4, local, local, 1);
+        }
+        let (full, truncated) = reg.export_spans(usize::MAX);
+        assert!(!truncated);
+        let line_len = full.len() / 4;
+
+        let (text, truncated) = reg.export_spans(2 * line_len + TRUNCATION_LINE_RESERVE);
+        assert!(truncated);
+        assert_eq!(&text[..2 * line_len], &full[..2 * line_len]);
+        assert_eq!(&text[2 * line_len..], b"truncated omitted=2\n");
+
+        let rsp = reg.spans_scrape_response(11);
+        let decoded = nexus_metrics::decode_spans_scrape_response(&rsp, 11);
+        let body = decoded.ok().and_then(|(_, body)| body);
+        assert_eq!(body.map(|body| (body.flags, body.text.len())), Some((0, full.len())));
+    }
+}
@@ -0,0 +1,166 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+//
+//! CONTEXT: metricsd windowed counters ("active in the last N seconds", OP_WINDOWED_INC)
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: Host unit tests in this module
+//! ADR: docs/rfcs/RFC-0024-observability-v2-metrics-tracing-contract-v1.md
+//!
+//! A windowed counter sums its increments over a recent window instead of all time.
+//! The window is split into [`WINDOW_BUCKETS`] time buckets kept in a fixed ring keyed
+//! by bucket epoch (`now_ns / bucket_len`); an increment lands in the bucket of its epoch
+//! and a bucket is reused once its epoch has left the window. An increment therefore
+//! counts for at least `window - bucket_len` and at most `window`.
+//!
+//! INVARIANTS:
+//! - Fixed-size state per series; `now_ns` is always supplied by the caller
+//! - A series keeps the window of its first increment; another window id rejects
+//! - Windowed series are volatile: they are left out of registry snapshots
+
+use nexus_metrics::window_len_ns;
+
+use crate::{MetricKind, Registry, RejectReason};
+
+/// Time buckets per window.
+pub const WINDOW_BUCKETS: usize = 8;
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct WindowedState {
+    window_ns: u64,
+    /// `(epoch, sum)` per ring slot.
+    buckets: [(u64, u64); WINDOW_BUCKETS],
+}
+
+impl WindowedState {
+    fn bucket_ns(&self) -> u64 {
+        (self.window_ns / WINDOW_BUCKETS as u64).max(1)
+    }
+
+    fn add(&mut self, delta: u64, now_ns: u64) {
+        let epoch = now_ns / self.bucket_ns();
+        let slot = &mut self.buckets[(epoch % WINDOW_BUCKETS as u64) as usize];
+        if epoch > slot.0 {
+            *slot = (epoch, 0);
+        }
+        // An older epoch than the slot holds has already left the window.
+        if epoch == slot.0 {
+            slot.1 = slot.1.saturating_add(delta);
+        }
+    }
+
+    pub(crate) fn window_ns(&self) -> u64 {
+        self.window_ns
+    }
+
+    pub(crate) fn value(&self, now_ns: u64) -> u64 {
+        let epoch = now_ns / self.bucket_ns();
+        self.buckets
+            .iter()
+            .filter(|(start, _)| *start <= epoch && epoch - start < WINDOW_BUCKETS as u64)
+            .fold(0u64, |sum, (_, count)| sum.saturating_add(*count))
+    }
+}
+
+impl Registry {
+    /// Adds `delta` to a windowed counter and returns its value over the window at `now_ns`.
+    ///
+    /// `window_id` is one of the `nexus_metrics::WINDOW_*` ids; an unknown id, or one that
+    /// differs from the series' first increment, rejects with [`RejectReason::InvalidArgs`].
+    pub fn windowed_inc(
+        &mut self,
+        sender_service_id: u64,
+        window_id: u8,
+        name: &[u8],
+        labels: &[u8],
+        delta: u64,
+        now_ns: u64,
+    ) -> Result<u64, RejectReason> {
+        let window_ns = window_len_ns(window_id).ok_or(RejectReason::InvalidArgs)?;
+        let before = self.series.len();
+        let idx =
+            self.ensure_series(sender_service_id, MetricKind::WindowedCounter, name, labels)?;
+        let state = &mut self.series[idx].windowed;
+        if idx == before {
+            state.window_ns = window_ns;
+        } else if state.window_ns != window_ns {
+            return Err(RejectReason::InvalidArgs);
+        }
+        state.add(delta, now_ns);
+        Ok(state.value(now_ns))
+    }
+
+    /// Sum of a windowed counter's increments that are still inside its window at `now_ns`.
+    ///
+    /// Returns `None` for an unknown series (they are scoped to the sender and kind).
+    pub fn windowed_value(
+        &self,
+        sender_service_id: u64,
+        name: &[u8],
+        labels: &[u8],
+        now_ns: u64,
+    ) -> Option<u64> {
+        let series = self.series.iter().find(|entry| {
+            entry.sender_service_id == sender_service_id
+                && entry.kind == MetricKind::WindowedCounter
+                && entry.name.as_slice() == name
+                && entry.labels.as_slice() == labels
+        })?;
+        Some(series.windowed.value(now_ns))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nexus_metrics::{WINDOW_10S, WINDOW_1M};
+
+    const SEC: u64 = 1_000_000_000;
+
+    #[test]
+    fn contributions_decay_out_of_the_window() {
+        let mut reg = Registry::new();
+        // 10s window in 1.25s buckets.
+        assert_eq!(reg.windowed_inc(3, WINDOW_10S, b"net.active", b"", 5, 0), Ok(5));
+        assert_eq!(reg.windowed_inc(3, WINDOW_10S, b"net.active", b"", 3, 4 * SEC), Ok(8));
+        assert_eq!(reg.windowed_value(3, b"net.active", b"", 9 * SEC), Some(8));
+        // The t=0 bucket has left the window; the t=4s one has not.
+        assert_eq!(reg.windowed_value(3, b"net.active", b"", 10 * SEC), Some(3));
+        assert_eq!(reg.windowed_value(3, b"net.active", b"", 14 * SEC), Some(0));
+
+        // t=10s reuses the t=0 ring slot: the old 5 is dropped, the t=4s 3 still counts.
+        assert_eq!(reg.windowed_inc(3, WINDOW_10S, b"net.active", b"", 2, 10 * SEC), Ok(5));
+        assert_eq!(reg.windowed_value(3, b"net.active", b"", 14 * SEC), Some(2));
+        assert_eq!(reg.windowed_value(3, b"net.active", b"", 60 * SEC), Some(0));
+    }
+
+    #[test]
+    fn windowed_series_are_not_persisted() {
+        let mut reg = Registry::new();
+        reg.counter_inc(3, b"net.total", b"", 4).unwrap();
+        reg.windowed_inc(3, WINDOW_1M, b"net.active", b"", 4, SEC).unwrap();
+        let mut restored = Registry::new();
+        assert!(restored.restore(&reg.snapshot()).is_ok());
+        assert_eq!(restored.counter_inc(3, b"net.total", b"", 0), Ok(4));
+        assert_eq!(restored.windowed_value(3, b"net.active", b"", SEC), None);
+    }
+
+    #[test]
+    fn test_reject_unknown_or_changed_window() {
+        let mut reg = Registry::new();
+        assert_eq!(
+            reg.windowed_inc(3, 9, b"net.active", b"", 1, 0),
+            Err(RejectReason::InvalidArgs)
+        );
+        assert_eq!(reg.windowed_inc(3, WINDOW_10S, b"net.active", b"", 1, 0), Ok(1));
+        assert_eq!(
+            reg.windowed_inc(3, WINDOW_1M, b"net.active", b"", 1, 0),
+            Err(RejectReason::InvalidArgs)
+        );
+        // Scoped to the sender and kind.
+        assert_eq!(reg.windowed_value(4, b"net.active", b"", 0), None);
+        reg.counter_inc(3, b"net.total", b"", 1).unwrap();
+        assert_eq!(reg.windowed_value(3, b"net.total", b"", 0), None);
+    }
+}
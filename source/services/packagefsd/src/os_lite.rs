@@ -337,7 +337,7 @@ fn load_registry_from_bundlemgrd() -> Option<(BundleRegistry, MountMode)> {
         )
         .ok()?;
     let rsp = bundle.recv(Wait::Timeout(core::time::Duration::from_secs(1))).ok()?;
-    let (status, img) = nexus_abi::bundlemgrd::decode_fetch_image_rsp(&rsp)?;
+    let (status, img) = nexus_abi::bundlemgrd::decode_fetch_image_rsp_checked(&rsp)?;
     if status != nexus_abi::bundlemgrd::STATUS_OK {
         return None;
     }
@@ -0,0 +1,55 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: statefsd audit trail — access denials appended to logd (os-lite)
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: No tests (best-effort send; exercised by QEMU statefs markers)
+//! ADR: docs/adr/0023-statefs-persistence-architecture.md
+
+pub(crate) fn append_logd_audit(msg: &[u8]) {
+    const MAGIC0: u8 = b'L';
+    const MAGIC1: u8 = b'O';
+    const VERSION: u8 = 1;
+    const OP_APPEND: u8 = 1;
+    const LEVEL_INFO: u8 = 2;
+    const SCOPE: &[u8] = b"statefsd.audit";
+
+    if msg.len() > 256 || SCOPE.len() > 64 {
+        return;
+    }
+
+    // init-lite deterministic slots for statefsd:
+    // - logd send cap: 0x08
+    // - reply inbox: recv=0x05, send=0x06
+    let send_slot = 0x08;
+    let reply_send_slot = 0x06;
+    let _reply_recv_slot = 0x05;
+    let reply_send_clone = match nexus_abi::cap_clone(reply_send_slot) {
+        Ok(c) => c,
+        Err(_) => return,
+    };
+
+    let mut frame = [0u8; 512];
+    let mut len = 0usize;
+    frame[len..len + 4].copy_from_slice(&[MAGIC0, MAGIC1, VERSION, OP_APPEND]);
+    len += 4;
+    frame[len] = LEVEL_INFO;
+    len += 1;
+    frame[len] = SCOPE.len() as u8;
+    len += 1;
+    frame[len..len + 2].copy_from_slice(&(msg.len() as u16).to_le_bytes());
+    len += 2;
+    frame[len..len + 2].copy_from_slice(&0u16.to_le_bytes()); // fields_len
+    len += 2;
+    frame[len..len + SCOPE.len()].copy_from_slice(SCOPE);
+    len += SCOPE.len();
+    frame[len..len + msg.len()].copy_from_slice(msg);
+    len += msg.len();
+
+    let hdr =
+        nexus_abi::MsgHeader::new(reply_send_clone, 0, 0, nexus_abi::ipc_hdr::CAP_MOVE, len as u32);
+    let _ = nexus_abi::ipc_send_v1(send_slot, &hdr, &frame[..len], nexus_abi::IPC_SYS_NONBLOCK, 0);
+    let _ = _reply_recv_slot;
+}
@@ -0,0 +1,55 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: statefsd os-lite block backend (RAM bring-up device or virtio-blk)
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: No tests (thin dispatch; exercised by QEMU statefs markers)
+//! ADR: docs/adr/0023-statefs-persistence-architecture.md
+
+use storage::virtio_blk::VirtioBlkDevice;
+use storage::{BlockDevice, MemBlockDevice};
+
+/// Block device behind the journal: starts in RAM, upgraded to virtio-blk while pristine.
+pub(crate) enum Backend {
+    Virtio(VirtioBlkDevice),
+    Mem(MemBlockDevice),
+}
+
+impl BlockDevice for Backend {
+    fn block_size(&self) -> usize {
+        match self {
+            Backend::Virtio(dev) => dev.block_size(),
+            Backend::Mem(dev) => dev.block_size(),
+        }
+    }
+
+    fn block_count(&self) -> u64 {
+        match self {
+            Backend::Virtio(dev) => dev.block_count(),
+            Backend::Mem(dev) => dev.block_count(),
+        }
+    }
+
+    fn read_block(&self, block_idx: u64, buf: &mut [u8]) -> Result<(), storage::BlockError> {
+        match self {
+            Backend::Virtio(dev) => dev.read_block(block_idx, buf),
+            Backend::Mem(dev) => dev.read_block(block_idx, buf),
+        }
+    }
+
+    fn write_block(&mut self, block_idx: u64, buf: &[u8]) -> Result<(), storage::BlockError> {
+        match self {
+            Backend::Virtio(dev) => dev.write_block(block_idx, buf),
+            Backend::Mem(dev) => dev.write_block(block_idx, buf),
+        }
+    }
+
+    fn sync(&mut self) -> Result<(), storage::BlockError> {
+        match self {
+            Backend::Virtio(dev) => dev.sync(),
+            Backend::Mem(dev) => dev.sync(),
+        }
+    }
+}
@@ -17,6 +17,12 @@
 #[cfg(all(feature = "os-lite", nexus_env = "os"))]
 extern crate alloc;
 
+#[cfg(all(feature = "os-lite", nexus_env = "os"))]
+mod audit;
+#[cfg(all(feature = "os-lite", nexus_env = "os"))]
+mod backend;
+#[cfg(all(feature = "os-lite", nexus_env = "os"))]
+mod markers;
 #[cfg(all(feature = "os-lite", nexus_env = "os"))]
 mod os_lite;
 #[cfg(all(feature = "os-lite", nexus_env = "os"))]
@@ -0,0 +1,134 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: statefsd UART/service-line markers and their allocation-free formatting (os-lite)
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: No tests (exercised by QEMU statefs markers)
+//! ADR: docs/adr/0023-statefs-persistence-architecture.md
+
+use nexus_abi::debug_putc;
+use statefs::StatefsError;
+use storage::virtio_blk::VirtioBlkDevice;
+
+use crate::audit::append_logd_audit;
+
+pub(crate) fn emit_access_denied(path: &str, sender_service_id: u64) {
+    let mut buf = [0u8; 160];
+    let mut len = 0usize;
+    let _ = push_bytes(&mut buf, &mut len, b"statefsd: access denied path=");
+    let _ = push_bytes(&mut buf, &mut len, path.as_bytes());
+    let _ = push_bytes(&mut buf, &mut len, b" sender=0x");
+    write_hex_u64(&mut buf, &mut len, sender_service_id);
+    let msg = core::str::from_utf8(&buf[..len]).unwrap_or("statefsd: access denied");
+    emit_line(msg);
+    append_logd_audit(msg.as_bytes());
+}
+
+pub(crate) fn emit_line(message: &str) {
+    // RFC-0068: fold routine markers into recall (interactive); failures & proof print raw.
+    if nexus_abi::service_line(message.as_bytes()) {
+        return;
+    }
+    for byte in message.as_bytes().iter().copied().chain(core::iter::once(b'\n')) {
+        let _ = debug_putc(byte);
+    }
+}
+
+pub(crate) fn emit_statefs_error(err: StatefsError) {
+    let msg = match err {
+        StatefsError::NotFound => "statefsd: err not-found",
+        StatefsError::AccessDenied => "statefsd: err access-denied",
+        StatefsError::ValueTooLarge => "statefsd: err value-too-large",
+        StatefsError::KeyTooLong => "statefsd: err key-too-long",
+        StatefsError::IoError => "statefsd: err io",
+        StatefsError::Corrupted => "statefsd: err corrupted",
+        StatefsError::InvalidKey => "statefsd: err invalid-key",
+        StatefsError::ReplayLimitExceeded => "statefsd: err replay-limit",
+        StatefsError::SuperblockMismatch => "statefsd: err superblock-mismatch",
+        StatefsError::QuotaExceeded => "statefsd: err quota-exceeded",
+        StatefsError::Conflict => "statefsd: err conflict",
+    };
+    emit_line(msg);
+}
+
+pub(crate) fn emit_ipc_error(err: nexus_ipc::IpcError) {
+    let msg = match err {
+        nexus_ipc::IpcError::WouldBlock => "statefsd: ipc would-block",
+        nexus_ipc::IpcError::Timeout => "statefsd: ipc timeout",
+        nexus_ipc::IpcError::Disconnected => "statefsd: ipc disconnected",
+        nexus_ipc::IpcError::NoSpace => "statefsd: ipc no-space",
+        nexus_ipc::IpcError::Kernel(err) => match err {
+            nexus_abi::IpcError::NoSuchEndpoint => "statefsd: ipc no-such-endpoint",
+            nexus_abi::IpcError::QueueFull => "statefsd: ipc queue-full",
+            nexus_abi::IpcError::QueueEmpty => "statefsd: ipc queue-empty",
+            nexus_abi::IpcError::PermissionDenied => "statefsd: ipc permission-denied",
+            nexus_abi::IpcError::TimedOut => "statefsd: ipc timed-out",
+            nexus_abi::IpcError::NoSpace => "statefsd: ipc no-space",
+            nexus_abi::IpcError::Truncated => "statefsd: ipc truncated",
+            nexus_abi::IpcError::Unsupported => "statefsd: ipc unsupported",
+        },
+        nexus_ipc::IpcError::Unsupported => "statefsd: ipc unsupported",
+        _ => "statefsd: ipc other",
+    };
+    emit_line(msg);
+}
+
+pub(crate) fn emit_blk_marker(dev: &VirtioBlkDevice) {
+    let ss = dev.sector_size();
+    let nsec = dev.capacity_sectors();
+    emit_line("blk: virtio-blk up");
+    let mut buf = [0u8; 64];
+    let mut len = 0usize;
+    let _ = push_bytes(&mut buf, &mut len, b"blk: virtio-blk up (ss=");
+    push_u32(&mut buf, &mut len, ss);
+    let _ = push_bytes(&mut buf, &mut len, b" nsec=");
+    push_u64(&mut buf, &mut len, nsec);
+    let _ = push_bytes(&mut buf, &mut len, b")");
+    let msg = core::str::from_utf8(&buf[..len]).unwrap_or("blk: virtio-blk up");
+    emit_line(msg);
+}
+
+fn push_bytes(buf: &mut [u8], len: &mut usize, bytes: &[u8]) -> bool {
+    let available = buf.len().saturating_sub(*len);
+    if bytes.len() > available {
+        return false;
+    }
+    buf[*len..*len + bytes.len()].copy_from_slice(bytes);
+    *len += bytes.len();
+    true
+}
+
+fn write_hex_u64(buf: &mut [u8], len: &mut usize, value: u64) {
+    if buf.len().saturating_sub(*len) < 16 {
+        return;
+    }
+    for shift in (0..16).rev() {
+        let nibble = ((value >> (shift * 4)) & 0xF) as u8;
+        let ch = if nibble < 10 { b'0' + nibble } else { b'a' + (nibble - 10) };
+        buf[*len] = ch;
+        *len += 1;
+    }
+}
+
+fn push_u32(buf: &mut [u8], len: &mut usize, value: u32) {
+    push_u64(buf, len, value as u64);
+}
+
+fn push_u64(buf: &mut [u8], len: &mut usize, mut value: u64) {
+    let mut tmp = [0u8; 20];
+    let mut pos = 0usize;
+    if value == 0 {
+        tmp[0] = b'0';
+        pos = 1;
+    } else {
+        while value > 0 && pos < tmp.len() {
+            tmp[pos] = b'0' + (value % 10) as u8;
+            value /= 10;
+            pos += 1;
+        }
+        tmp[..pos].reverse();
+    }
+    let _ = push_bytes(buf, len, &tmp[..pos]);
+}
@@ -18,14 +18,17 @@ use alloc::vec::Vec;
 
 use core::fmt;
 
-use nexus_abi::{debug_putc, yield_};
+use nexus_abi::yield_;
 use nexus_ipc::{KernelServer, Server as _, Wait};
 
 use statefs::protocol::{self as proto, Request};
-use statefs::{JournalEngine, StatefsError};
-use storage::virtio_blk::VirtioBlkDevice;
-use storage::BlockDevice;
-use storage::MemBlockDevice;
+use statefs::{ChangeSet, JournalEngine, JournalStats, StatefsError};
+use storage::{virtio_blk::VirtioBlkDevice, MemBlockDevice};
+
+use crate::backend::Backend;
+use crate::markers::{
+    emit_access_denied, emit_blk_marker, emit_ipc_error, emit_line, emit_statefs_error,
+};
 
 /// Result alias surfaced by the lite statefsd backend.
 pub type LiteResult<T> = Result<T, ServerError>;
@@ -77,48 +80,6 @@ const CAP_WRITE: &str = "statefs.write";
 const CAP_KEYSTORE: &str = "statefs.keystore";
 const CAP_BOOT: &str = "statefs.boot";
 
-enum Backend {
-    Virtio(VirtioBlkDevice),
-    Mem(MemBlockDevice),
-}
-
-impl BlockDevice for Backend {
-    fn block_size(&self) -> usize {
-        match self {
-            Backend::Virtio(dev) => dev.block_size(),
-            Backend::Mem(dev) => dev.block_size(),
-        }
-    }
-
-    fn block_count(&self) -> u64 {
-        match self {
-            Backend::Virtio(dev) => dev.block_count(),
-            Backend::Mem(dev) => dev.block_count(),
-        }
-    }
-
-    fn read_block(&self, block_idx: u64, buf: &mut [u8]) -> Result<(), storage::BlockError> {
-        match self {
-            Backend::Virtio(dev) => dev.read_block(block_idx, buf),
-            Backend::Mem(dev) => dev.read_block(block_idx, buf),
-        }
-    }
-
-    fn write_block(&mut self, block_idx: u64, buf: &[u8]) -> Result<(), storage::BlockError> {
-        match self {
-            Backend::Virtio(dev) => dev.write_block(block_idx, buf),
-            Backend::Mem(dev) => dev.write_block(block_idx, buf),
-        }
-    }
-
-    fn sync(&mut self) -> Result<(), storage::BlockError> {
-        match self {
-            Backend::Virtio(dev) => dev.sync(),
-            Backend::Mem(dev) => dev.sync(),
-        }
-    }
-}
-
 /// Main statefsd bring-up service loop (os-lite).
 pub fn service_main_loop(notifier: ReadyNotifier) -> LiteResult<()> {
     emit_line("statefsd: entry");
@@ -190,6 +151,9 @@ pub fn service_main_loop(notifier: ReadyNotifier) -> LiteResult<()> {
                             emit_blk_marker(&blk);
                             match JournalEngine::open(Backend::Virtio(blk)) {
                                 Ok(new_engine) => {
+                                    if let Some(diag) = new_engine.superblock_diagnostic() {
+                                        emit_statefs_error(diag);
+                                    }
                                     engine = new_engine;
                                     virtio_upgraded = true;
                                     emit_line("statefsd: virtio upgrade ok");
@@ -215,7 +179,11 @@ pub fn service_main_loop(notifier: ReadyNotifier) -> LiteResult<()> {
                 if let Some(op) = frame.get(3).copied() {
                     if matches!(
                         op,
-                        proto::OP_PUT | proto::OP_DEL | proto::OP_SYNC | proto::OP_REOPEN
+                        proto::OP_PUT
+                            | proto::OP_DEL
+                            | proto::OP_SYNC
+                            | proto::OP_REOPEN
+                            | proto::OP_BARRIER
                     ) {
                         pristine = false;
                     }
@@ -272,32 +240,46 @@ fn handle_frame(
     };
 
     match request {
-        Request::Put { key, value } => {
-            if value.len() > MAX_INLINE_VALUE_BYTES {
-                return proto::encode_status_response_with_nonce(
-                    proto::OP_PUT,
-                    proto::STATUS_VALUE_TOO_LARGE,
-                    nonce,
-                );
-            }
-            if !policy_allows(sender_service_id, proto::OP_PUT, key) {
+        Request::Put { key, value, durable } => {
+            let status = if value.len() > MAX_INLINE_VALUE_BYTES {
+                proto::STATUS_VALUE_TOO_LARGE
+            } else if !policy_allows(sender_service_id, proto::OP_PUT, key) {
                 emit_access_denied(key, sender_service_id);
-                return proto::encode_status_response_with_nonce(
-                    proto::OP_PUT,
-                    proto::STATUS_ACCESS_DENIED,
-                    nonce,
-                );
-            }
-            match engine.put(key, value) {
-                Ok(()) => {
-                    proto::encode_status_response_with_nonce(proto::OP_PUT, proto::STATUS_OK, nonce)
+                proto::STATUS_ACCESS_DENIED
+            } else {
+                let put =
+                    if durable { engine.put_durable(key, value) } else { engine.put(key, value) };
+                put.map_or_else(proto::status_from_error, |()| proto::STATUS_OK)
+            };
+            proto::encode_status_response_with_nonce(proto::OP_PUT, status, nonce)
+        }
+        Request::PutVersioned { key, value, expected_gen } => {
+            // Gated like a put; a conflict reports STATUS_CONFLICT without a generation.
+            let (status, generation) = if value.len() > MAX_INLINE_VALUE_BYTES {
+                (proto::STATUS_VALUE_TOO_LARGE, 0)
+            } else if !policy_allows(sender_service_id, proto::OP_PUT, key) {
+                emit_access_denied(key, sender_service_id);
+                (proto::STATUS_ACCESS_DENIED, 0)
+            } else {
+                match engine.put_versioned(key, value, expected_gen) {
+                    Ok(generation) => (proto::STATUS_OK, generation),
+                    Err(err) => (proto::status_from_error(err), 0),
                 }
-                Err(err) => proto::encode_status_response_with_nonce(
-                    proto::OP_PUT,
-                    proto::status_from_error(err),
-                    nonce,
-                ),
-            }
+            };
+            proto::encode_put_versioned_response_with_nonce(status, generation, nonce)
+        }
+        Request::Validate { key, value } => {
+            // Dry-run put: same inline limit and policy gate, nothing is written.
+            let status = if value.len() > MAX_INLINE_VALUE_BYTES {
+                proto::STATUS_VALUE_TOO_LARGE
+            } else if !policy_allows(sender_service_id, proto::OP_PUT, key) {
+                emit_access_denied(key, sender_service_id);
+                proto::STATUS_ACCESS_DENIED
+            } else {
+                let validated = engine.validate_put(key, value);
+                validated.map_or_else(proto::status_from_error, |()| proto::STATUS_OK)
+            };
+            proto::encode_status_response_with_nonce(proto::OP_VALIDATE, status, nonce)
         }
         Request::Get { key } => {
             if !policy_allows(sender_service_id, proto::OP_GET, key) {
@@ -370,31 +352,36 @@ fn handle_frame(
                 ),
             }
         }
-        Request::Sync => {
-            // Sync is a durability boundary for all writers. Allow if the caller has either:
+        req @ (Request::Sync | Request::Barrier) => {
+            // Sync/barrier: a durability boundary for all writers. Allow if the caller has either:
             // - boot authority (`statefs.boot`) or
             // - generic state writer (`statefs.write`)
             let allowed = policyd_allows(sender_service_id, CAP_BOOT.as_bytes())
                 || policyd_allows(sender_service_id, CAP_WRITE.as_bytes());
+            let op =
+                if matches!(req, Request::Barrier) { proto::OP_BARRIER } else { proto::OP_SYNC };
             if !allowed {
                 emit_access_denied("/state", sender_service_id);
                 return proto::encode_status_response_with_nonce(
-                    proto::OP_SYNC,
+                    op,
                     proto::STATUS_ACCESS_DENIED,
                     nonce,
                 );
             }
+            if op == proto::OP_BARRIER {
+                let status =
+                    engine.barrier().map_or_else(proto::status_from_error, |()| proto::STATUS_OK);
+                return proto::encode_status_response_with_nonce(op, status, nonce);
+            }
             match engine.sync() {
-                Ok(()) => proto::encode_status_response_with_nonce(
-                    proto::OP_SYNC,
+                Ok(()) => proto::encode_sync_response_with_nonce(
                     proto::STATUS_OK,
+                    engine.last_sync_seq(),
                     nonce,
                 ),
-                Err(err) => proto::encode_status_response_with_nonce(
-                    proto::OP_SYNC,
-                    proto::status_from_error(err),
-                    nonce,
-                ),
+                Err(err) => {
+                    proto::encode_sync_response_with_nonce(proto::status_from_error(err), 0, nonce)
+                }
             }
         }
         Request::Reopen => {
@@ -421,6 +408,70 @@ fn handle_frame(
                 ),
             }
         }
+        Request::GetMany { keys } => {
+            // Per-key policy: a denied key reports ACCESS_DENIED in its own slot.
+            let mut results = engine.get_many(&keys);
+            for (key, slot) in keys.iter().zip(results.iter_mut()) {
+                if !policy_allows(sender_service_id, proto::OP_MGET, key) {
+                    emit_access_denied(key, sender_service_id);
+                    *slot = Err(StatefsError::AccessDenied);
+                }
+            }
+            let max = proto::MAX_MGET_RESPONSE_BYTES;
+            proto::encode_mget_response_with_nonce(proto::STATUS_OK, &results, max, nonce)
+        }
+        Request::Stats => {
+            // Aggregate counters only (no keys or values): gated like a `/state` read.
+            if !policy_allows(sender_service_id, proto::OP_STATS, "/state") {
+                emit_access_denied("/state", sender_service_id);
+                return proto::encode_stats_response_with_nonce(
+                    proto::STATUS_ACCESS_DENIED,
+                    &JournalStats::default(),
+                    nonce,
+                );
+            }
+            proto::encode_stats_response_with_nonce(proto::STATUS_OK, &engine.stats(), nonce)
+        }
+        Request::Changes { since } => {
+            // Names keys across `/state` without values: gated like a `/state` read.
+            if !policy_allows(sender_service_id, proto::OP_CHANGES, "/state") {
+                emit_access_denied("/state", sender_service_id);
+                return proto::encode_changes_response_with_nonce(
+                    proto::STATUS_ACCESS_DENIED,
+                    &ChangeSet::default(),
+                    0,
+                    nonce,
+                );
+            }
+            let max = proto::MAX_CHANGES_RESPONSE_BYTES;
+            proto::encode_changes_response_with_nonce(
+                proto::STATUS_OK,
+                &engine.changed_since(since),
+                max,
+                nonce,
+            )
+        }
+        Request::Verify => {
+            // Read-only walk reporting offsets and counts only: gated like `OP_STATS`.
+            if !policy_allows(sender_service_id, proto::OP_VERIFY, "/state") {
+                emit_access_denied("/state", sender_service_id);
+                return proto::encode_status_response_with_nonce(
+                    proto::OP_VERIFY,
+                    proto::STATUS_ACCESS_DENIED,
+                    nonce,
+                );
+            }
+            match engine.verify() {
+                Ok(report) => {
+                    proto::encode_verify_response_with_nonce(proto::STATUS_OK, &report, nonce)
+                }
+                Err(err) => proto::encode_status_response_with_nonce(
+                    proto::OP_VERIFY,
+                    proto::status_from_error(err),
+                    nonce,
+                ),
+            }
+        }
     }
 }
 
@@ -461,164 +512,3 @@ fn policyd_allows(subject_id: u64, cap: &[u8]) -> bool {
         nexus_ipc::policyd::CapDecision::Allow
     )
 }
-
-fn emit_access_denied(path: &str, sender_service_id: u64) {
-    let mut buf = [0u8; 160];
-    let mut len = 0usize;
-    let _ = push_bytes(&mut buf, &mut len, b"statefsd: access denied path=");
-    let _ = push_bytes(&mut buf, &mut len, path.as_bytes());
-    let _ = push_bytes(&mut buf, &mut len, b" sender=0x");
-    write_hex_u64(&mut buf, &mut len, sender_service_id);
-    let msg = core::str::from_utf8(&buf[..len]).unwrap_or("statefsd: access denied");
-    emit_line(msg);
-    append_logd_audit(msg.as_bytes());
-}
-
-fn emit_line(message: &str) {
-    // RFC-0068: fold routine markers into recall (interactive); failures & proof print raw.
-    if nexus_abi::service_line(message.as_bytes()) {
-        return;
-    }
-    for byte in message.as_bytes().iter().copied().chain(core::iter::once(b'\n')) {
-        let _ = debug_putc(byte);
-    }
-}
-
-fn emit_statefs_error(err: StatefsError) {
-    let msg = match err {
-        StatefsError::NotFound => "statefsd: err not-found",
-        StatefsError::AccessDenied => "statefsd: err access-denied",
-        StatefsError::ValueTooLarge => "statefsd: err value-too-large",
-        StatefsError::KeyTooLong => "statefsd: err key-too-long",
-        StatefsError::IoError => "statefsd: err io",
-        StatefsError::Corrupted => "statefsd: err corrupted",
-        StatefsError::InvalidKey => "statefsd: err invalid-key",
-        StatefsError::ReplayLimitExceeded => "statefsd: err replay-limit",
-    };
-    emit_line(msg);
-}
-
-fn emit_ipc_error(err: nexus_ipc::IpcError) {
-    let msg = match err {
-        nexus_ipc::IpcError::WouldBlock => "statefsd: ipc would-block",
-        nexus_ipc::IpcError::Timeout => "statefsd: ipc timeout",
-        nexus_ipc::IpcError::Disconnected => "statefsd: ipc disconnected",
-        nexus_ipc::IpcError::NoSpace => "statefsd: ipc no-space",
-        nexus_ipc::IpcError::Kernel(err) => match err {
-            nexus_abi::IpcError::NoSuchEndpoint => "statefsd: ipc no-such-endpoint",
-            nexus_abi::IpcError::QueueFull => "statefsd: ipc queue-full",
-            nexus_abi::IpcError::QueueEmpty => "statefsd: ipc queue-empty",
-            nexus_abi::IpcError::PermissionDenied => "statefsd: ipc permission-denied",
-            nexus_abi::IpcError::TimedOut => "statefsd: ipc timed-out",
-            nexus_abi::IpcError::NoSpace => "statefsd: ipc no-space",
-            nexus_abi::IpcError::Unsupported => "statefsd: ipc unsupported",
-        },
-        nexus_ipc::IpcError::Unsupported => "statefsd: ipc unsupported",
-        _ => "statefsd: ipc other",
-    };
-    emit_line(msg);
-}
-
-fn emit_blk_marker(dev: &VirtioBlkDevice) {
-    let ss = dev.sector_size();
-    let nsec = dev.capacity_sectors();
-    emit_line("blk: virtio-blk up");
-    let mut buf = [0u8; 64];
-    let mut len = 0usize;
-    let _ = push_bytes(&mut buf, &mut len, b"blk: virtio-blk up (ss=");
-    push_u32(&mut buf, &mut len, ss);
-    let _ = push_bytes(&mut buf, &mut len, b" nsec=");
-    push_u64(&mut buf, &mut len, nsec);
-    let _ = push_bytes(&mut buf, &mut len, b")");
-    let msg = core::str::from_utf8(&buf[..len]).unwrap_or("blk: virtio-blk up");
-    emit_line(msg);
-}
-
-fn push_bytes(buf: &mut [u8], len: &mut usize, bytes: &[u8]) -> bool {
-    let available = buf.len().saturating_sub(*len);
-    if bytes.len() > available {
-        return false;
-    }
-    buf[*len..*len + bytes.len()].copy_from_slice(bytes);
-    *len += bytes.len();
-    true
-}
-
-fn write_hex_u64(buf: &mut [u8], len: &mut usize, value: u64) {
-    if buf.len().saturating_sub(*len) < 16 {
-        return;
-    }
-    for shift in (0..16).rev() {
-        let nibble = ((value >> (shift * 4)) & 0xF) as u8;
-        let ch = if nibble < 10 { b'0' + nibble } else { b'a' + (nibble - 10) };
-        buf[*len] = ch;
-        *len += 1;
-    }
-}
-
-fn push_u32(buf: &mut [u8], len: &mut usize, value: u32) {
-    push_u64(buf, len, value as u64);
-}
-
-fn push_u64(buf: &mut [u8], len: &mut usize, mut value: u64) {
-    let mut tmp = [0u8; 20];
-    let mut pos = 0usize;
-    if value == 0 {
-        tmp[0] = b'0';
-        pos = 1;
-    } else {
-        while value > 0 && pos < tmp.len() {
-            tmp[pos] = b'0' + (value % 10) as u8;
-            value /= 10;
-            pos += 1;
-        }
-        tmp[..pos].reverse();
-    }
-    let _ = push_bytes(buf, len, &tmp[..pos]);
-}
-
-fn append_logd_audit(msg: &[u8]) {
-    const MAGIC0: u8 = b'L';
-    const MAGIC1: u8 = b'O';
-    const VERSION: u8 = 1;
-    const OP_APPEND: u8 = 1;
-    const LEVEL_INFO: u8 = 2;
-    const SCOPE: &[u8] = b"statefsd.audit";
-
-    if msg.len() > 256 || SCOPE.len() > 64 {
-        return;
-    }
-
-    // init-lite deterministic slots for statefsd:
-    // - logd send cap: 0x08
-    // - reply inbox: recv=0x05, send=0x06
-    let send_slot = 0x08;
-    let reply_send_slot = 0x06;
-    let _reply_recv_slot = 0x05;
-    let reply_send_clone = match nexus_abi::cap_clone(reply_send_slot) {
-        Ok(c) => c,
-        Err(_) => return,
-    };
-
-    let mut frame = [0u8; 512];
-    let mut len = 0usize;
-    frame[len..len + 4].copy_from_slice(&[MAGIC0, MAGIC1, VERSION, OP_APPEND]);
-    len += 4;
-    frame[len] = LEVEL_INFO;
-    len += 1;
-    frame[len] = SCOPE.len() as u8;
-    len += 1;
-    frame[len..len + 2].copy_from_slice(&(msg.len() as u16).to_le_bytes());
-    len += 2;
-    frame[len..len + 2].copy_from_slice(&0u16.to_le_bytes()); // fields_len
-    len += 2;
-    frame[len..len + SCOPE.len()].copy_from_slice(SCOPE);
-    len += SCOPE.len();
-    frame[len..len + msg.len()].copy_from_slice(msg);
-    len += msg.len();
-
-    let hdr =
-        nexus_abi::MsgHeader::new(reply_send_clone, 0, 0, nexus_abi::ipc_hdr::CAP_MOVE, len as u32);
-    let _ = nexus_abi::ipc_send_v1(send_slot, &hdr, &frame[..len], nexus_abi::IPC_SYS_NONBLOCK, 0);
-    let _ = _reply_recv_slot;
-}
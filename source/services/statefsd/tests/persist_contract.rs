@@ -13,7 +13,8 @@
 //!   - GET miss → STATUS_NOT_FOUND
 //!   - DEL → GET miss → STATUS_NOT_FOUND
 //!   - LIST returns matching keys
-//!   - SYNC/REOPEN round-trips
+//!   - SYNC/REOPEN round-trips (SYNC returns the incremented sync seq)
+//!   - MGET answers every key in request order (hit or NOT_FOUND)
 //!
 //! DEPENDENCIES:
 //!   - `statefs::protocol`: wire format constants + encode/decode functions
@@ -24,11 +25,12 @@ use statefs::protocol as proto;
 
 struct MemStore {
     data: std::collections::BTreeMap<String, Vec<u8>>,
+    syncs: u64,
 }
 
 impl MemStore {
     fn new() -> Self {
-        Self { data: std::collections::BTreeMap::new() }
+        Self { data: std::collections::BTreeMap::new(), syncs: 0 }
     }
 
     fn handle(&mut self, frame: &[u8]) -> Vec<u8> {
@@ -37,7 +39,7 @@ impl MemStore {
             Err(status) => return proto::encode_status_response(0, status),
         };
         match req {
-            proto::Request::Put { key, value } => {
+            proto::Request::Put { key, value, .. } => {
                 self.data.insert(key.to_string(), value.to_vec());
                 proto::encode_status_response(proto::OP_PUT, proto::STATUS_OK)
             }
@@ -60,10 +62,65 @@ impl MemStore {
                 matches.truncate(limit);
                 proto::encode_list_response(proto::STATUS_OK, &matches, 4096)
             }
-            proto::Request::Sync => proto::encode_status_response(proto::OP_SYNC, proto::STATUS_OK),
+            proto::Request::Sync => {
+                self.syncs += 1;
+                proto::encode_sync_response_with_nonce(proto::STATUS_OK, self.syncs, None)
+            }
             proto::Request::Reopen => {
                 proto::encode_status_response(proto::OP_REOPEN, proto::STATUS_OK)
             }
+            // No generations here: every versioned put succeeds with generation 1.
+            proto::Request::PutVersioned { key, value, .. } => {
+                self.data.insert(key.to_string(), value.to_vec());
+                proto::encode_put_versioned_response_with_nonce(proto::STATUS_OK, 1, None)
+            }
+            proto::Request::Validate { .. } => {
+                proto::encode_status_response(proto::OP_VALIDATE, proto::STATUS_OK)
+            }
+            proto::Request::Barrier => {
+                self.syncs += 1;
+                proto::encode_status_response(proto::OP_BARRIER, proto::STATUS_OK)
+            }
+            proto::Request::GetMany { keys } => {
+                let results: Vec<_> = keys
+                    .iter()
+                    .map(|key| self.data.get(*key).cloned().ok_or(statefs::StatefsError::NotFound))
+                    .collect();
+                proto::encode_mget_response_with_nonce(
+                    proto::STATUS_OK,
+                    &results,
+                    proto::MAX_MGET_RESPONSE_BYTES,
+                    None,
+                )
+            }
+            proto::Request::Stats => proto::encode_stats_response_with_nonce(
+                proto::STATUS_OK,
+                &statefs::JournalStats { live_keys: self.data.len() as u64, ..Default::default() },
+                None,
+            ),
+            proto::Request::Verify => proto::encode_verify_response_with_nonce(
+                proto::STATUS_OK,
+                &statefs::VerifyReport::default(),
+                None,
+            ),
+            // No per-key history here: every cursor gets the full key set (a full resync).
+            proto::Request::Changes { .. } => {
+                let changes = self
+                    .data
+                    .keys()
+                    .map(|key| statefs::Change {
+                        seq: self.syncs,
+                        key: key.clone(),
+                        kind: statefs::ChangeKind::Put,
+                    })
+                    .collect();
+                proto::encode_changes_response_with_nonce(
+                    proto::STATUS_OK,
+                    &statefs::ChangeSet { seq: self.syncs, full: true, changes },
+                    proto::MAX_CHANGES_RESPONSE_BYTES,
+                    None,
+                )
+            }
         }
     }
 }
@@ -162,6 +219,9 @@ fn sync_returns_ok() {
     let mut svc = MemStore::new();
     let rsp = svc.handle(&proto::encode_sync_request());
     assert_eq!(decode_rsp(&rsp).unwrap().1, proto::STATUS_OK);
+    assert_eq!(proto::decode_sync_response(&rsp), Ok(1));
+    let rsp = svc.handle(&proto::encode_sync_request());
+    assert_eq!(proto::decode_sync_response(&rsp), Ok(2));
 }
 
 #[test]
@@ -170,3 +230,40 @@ fn reopen_returns_ok() {
     let rsp = svc.handle(&proto::encode_reopen_request());
     assert_eq!(decode_rsp(&rsp).unwrap().1, proto::STATUS_OK);
 }
+
+#[test]
+fn put_durable_flag_round_trips() {
+    let frame =
+        proto::encode_put_request_with_flags("/state/k", b"v", proto::PUT_FLAG_DURABLE).unwrap();
+    assert_eq!(
+        proto::decode_request(&frame),
+        Ok(proto::Request::Put { key: "/state/k", value: b"v", durable: true })
+    );
+    // No flags keeps the legacy frame byte-for-byte.
+    let plain = proto::encode_put_request("/state/k", b"v").unwrap();
+    assert_eq!(proto::encode_put_request_with_flags("/state/k", b"v", 0).unwrap(), plain);
+    assert_eq!(
+        proto::decode_request(&plain),
+        Ok(proto::Request::Put { key: "/state/k", value: b"v", durable: false })
+    );
+
+    let mut svc = MemStore::new();
+    let rsp = svc.handle(&frame);
+    assert_eq!(decode_rsp(&rsp).unwrap().1, proto::STATUS_OK);
+}
+
+#[test]
+fn mget_returns_hits_and_misses_in_request_order() {
+    let mut svc = MemStore::new();
+    svc.handle(&proto::encode_put_request("/state/b", b"two").unwrap());
+    svc.handle(&proto::encode_put_request("/state/d", b"four").unwrap());
+
+    let keys = ["/state/d", "/state/a", "/state/b"];
+    let rsp = svc.handle(&proto::encode_mget_request(&keys).unwrap());
+    let decoded = proto::decode_mget_response(&rsp).unwrap();
+    assert!(!decoded.truncated);
+    assert_eq!(
+        decoded.values,
+        [Ok(b"four".to_vec()), Err(statefs::StatefsError::NotFound), Ok(b"two".to_vec())]
+    );
+}
@@ -15,6 +15,7 @@
 //!   - Value too large → STATUS_VALUE_TOO_LARGE
 //!   - Unsupported opcode → STATUS_UNSUPPORTED
 //!   - Protocol v2 with malformed nonce → STATUS_MALFORMED
+//!   - Unknown OP_PUT flag bits → STATUS_MALFORMED
 //!
 //! DEPENDENCIES:
 //!   - `statefs::protocol`: wire format constants + encode/decode functions
@@ -92,3 +93,13 @@ fn reject_unknown_protocol_version() {
     assert!(req.is_err());
     assert_eq!(req.unwrap_err(), proto::STATUS_MALFORMED);
 }
+
+#[test]
+fn reject_unknown_put_flags() {
+    let frame = proto::encode_put_request_with_flags("/state/k", b"v", 0x02).unwrap();
+    assert_eq!(proto::decode_request(&frame), Err(proto::STATUS_MALFORMED));
+    // More than one trailing byte is not a flag byte.
+    let mut frame = proto::encode_put_request_with_flags("/state/k", b"v", 0x01).unwrap();
+    frame.push(0);
+    assert_eq!(proto::decode_request(&frame), Err(proto::STATUS_MALFORMED));
+}
@@ -209,6 +209,11 @@ pub fn service_main_loop(notifier: ReadyNotifier) -> LiteResult<()> {
                     StatefsError::ReplayLimitExceeded => {
                         "updated: bootctl load err (ReplayLimitExceeded)"
                     }
+                    StatefsError::SuperblockMismatch => {
+                        "updated: bootctl load err (SuperblockMismatch)"
+                    }
+                    StatefsError::QuotaExceeded => "updated: bootctl load err (QuotaExceeded)",
+                    StatefsError::Conflict => "updated: bootctl load err (Conflict)",
                     StatefsError::NotFound => unreachable!("handled above"),
                 });
             }
@@ -311,6 +316,7 @@ fn ipc_error_label(err: nexus_abi::IpcError) -> &'static str {
         nexus_abi::IpcError::NoSpace => "NoSpace",
         nexus_abi::IpcError::NoSuchEndpoint => "NoSuchEndpoint",
         nexus_abi::IpcError::PermissionDenied => "PermissionDenied",
+        nexus_abi::IpcError::Truncated => "Truncated",
         nexus_abi::IpcError::Unsupported => "Unsupported",
     }
 }
@@ -668,6 +674,7 @@ fn bundlemgrd_set_active_slot(slot: Slot) -> Result<(), &'static str> {
                     nexus_abi::IpcError::PermissionDenied => "reply-denied",
                     nexus_abi::IpcError::QueueFull => "reply-full",
                     nexus_abi::IpcError::NoSpace => "reply-nospace",
+                    nexus_abi::IpcError::Truncated => "reply-truncated",
                     nexus_abi::IpcError::Unsupported => "reply-unsupported",
                     nexus_abi::IpcError::QueueEmpty => "reply-empty",
                 })
@@ -779,29 +786,17 @@ fn persist_bootctrl_state(
     // #region agent log (persist failure detail; rate-limited)
     static PERSIST_ERR_LOGGED: core::sync::atomic::AtomicBool =
         core::sync::atomic::AtomicBool::new(false);
-    let label = |e: StatefsError| -> &'static str {
-        match e {
-            StatefsError::NotFound => "NotFound",
-            StatefsError::AccessDenied => "AccessDenied",
-            StatefsError::ValueTooLarge => "ValueTooLarge",
-            StatefsError::KeyTooLong => "KeyTooLong",
-            StatefsError::IoError => "IoError",
-            StatefsError::Corrupted => "Corrupted",
-            StatefsError::InvalidKey => "InvalidKey",
-            StatefsError::ReplayLimitExceeded => "ReplayLimitExceeded",
-        }
-    };
     if let Err(e) = client.put(BOOTCTRL_STATE_KEY, &payload) {
         if !PERSIST_ERR_LOGGED.swap(true, core::sync::atomic::Ordering::Relaxed) {
             emit_bytes(b"updated: bootctl persist put err=");
-            emit_line(label(e));
+            emit_line(e.as_str());
         }
         return Err(e);
     }
     if let Err(e) = client.sync() {
         if !PERSIST_ERR_LOGGED.swap(true, core::sync::atomic::Ordering::Relaxed) {
             emit_bytes(b"updated: bootctl persist sync err=");
-            emit_line(label(e));
+            emit_line(e.as_str());
         }
         return Err(e);
     }
@@ -0,0 +1,252 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+//
+//! CONTEXT: BATCH wire — several counter/gauge/histogram updates in one frame
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: Host unit tests in this module
+//! ADR: docs/rfcs/RFC-0024-observability-v2-metrics-tracing-contract-v1.md
+//!
+//! Request: `MAGIC0 MAGIC1 VERSION OP_BATCH | nonce:u32 | count:u8 | count x entry`, each
+//! entry `op:u8 | name_len:u8 | labels_len:u16 | value:i64 | name | labels` with `op` one of
+//! `OP_COUNTER_INC` / `OP_GAUGE_SET` / `OP_HIST_OBSERVE`. Response: the plain status frame.
+//!
+//! The decoder checks every entry before it returns, so [`BatchEntries::iter`] only walks
+//! entries that are already known to be well formed. Entries carry no nonce of their own:
+//! unlike a nonced `COUNTER_INC`, a retried batch is applied again.
+
+use alloc::vec::Vec;
+
+use crate::{
+    read_u64_le, BoundedFields, DecodeError, EncodeError, MetricName, Request, MAGIC0, MAGIC1,
+    MAX_LABELS_LEN, MAX_METRIC_NAME_LEN, OP_BATCH, OP_COUNTER_INC, OP_GAUGE_SET, OP_HIST_OBSERVE,
+    VERSION,
+};
+
+/// Most entries one BATCH frame carries (keeps the frame under 4 KiB).
+pub const MAX_BATCH_ENTRIES: usize = 16;
+
+const ENTRY_HEADER_LEN: usize = 1 + 1 + 2 + 8;
+
+/// One update inside a BATCH frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BatchEntry<'a> {
+    /// Counter increment by `delta`.
+    Counter { name: &'a [u8], labels: &'a [u8], delta: u64 },
+    /// Gauge set to `value`.
+    Gauge { name: &'a [u8], labels: &'a [u8], value: i64 },
+    /// One histogram observation.
+    Hist { name: &'a [u8], labels: &'a [u8], value: u64 },
+}
+
+impl<'a> BatchEntry<'a> {
+    /// Builds a counter entry from validated fields.
+    pub fn counter(name: MetricName<'a>, labels: BoundedFields<'a>, delta: u64) -> Self {
+        Self::Counter { name: name.as_bytes(), labels: labels.as_bytes(), delta }
+    }
+
+    /// Builds a gauge entry from validated fields.
+    pub fn gauge(name: MetricName<'a>, labels: BoundedFields<'a>, value: i64) -> Self {
+        Self::Gauge { name: name.as_bytes(), labels: labels.as_bytes(), value }
+    }
+
+    /// Builds a histogram entry from validated fields.
+    pub fn hist(name: MetricName<'a>, labels: BoundedFields<'a>, value: u64) -> Self {
+        Self::Hist { name: name.as_bytes(), labels: labels.as_bytes(), value }
+    }
+
+    /// The single-op request this entry stands for, under `nonce`.
+    pub const fn request(self, nonce: u32) -> Request<'a> {
+        match self {
+            Self::Counter { name, labels, delta } => {
+                Request::CounterInc { nonce, name, labels, delta }
+            }
+            Self::Gauge { name, labels, value } => Request::GaugeSet { nonce, name, labels, value },
+            Self::Hist { name, labels, value } => {
+                Request::HistObserve { nonce, name, labels, value }
+            }
+        }
+    }
+
+    const fn parts(self) -> (u8, &'a [u8], &'a [u8], i64) {
+        match self {
+            Self::Counter { name, labels, delta } => (OP_COUNTER_INC, name, labels, delta as i64),
+            Self::Gauge { name, labels, value } => (OP_GAUGE_SET, name, labels, value),
+            Self::Hist { name, labels, value } => (OP_HIST_OBSERVE, name, labels, value as i64),
+        }
+    }
+}
+
+/// The decoded entries of a BATCH request.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BatchEntries<'a> {
+    count: u8,
+    bytes: &'a [u8],
+}
+
+impl<'a> BatchEntries<'a> {
+    /// Number of entries (`1..=MAX_BATCH_ENTRIES`).
+    pub const fn len(&self) -> usize {
+        self.count as usize
+    }
+
+    /// Whether there are no entries (never true for a decoded batch).
+    pub const fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Entries in frame order.
+    pub fn iter(&self) -> impl Iterator<Item = BatchEntry<'a>> {
+        let mut rest = self.bytes;
+        core::iter::from_fn(move || {
+            let (entry, tail) = split_entry(rest).ok()?;
+            rest = tail;
+            Some(entry)
+        })
+    }
+}
+
+/// Encodes a BATCH frame of `1..=MAX_BATCH_ENTRIES` entries.
+pub fn encode_batch(nonce: u32, entries: &[BatchEntry<'_>]) -> Result<Vec<u8>, EncodeError> {
+    if entries.is_empty() {
+        return Err(EncodeError::InvalidArgs);
+    }
+    if entries.len() > MAX_BATCH_ENTRIES {
+        return Err(EncodeError::OverLimit);
+    }
+    let body: usize = entries
+        .iter()
+        .map(|entry| {
+            let (_, name, labels, _) = entry.parts();
+            ENTRY_HEADER_LEN + name.len() + labels.len()
+        })
+        .sum();
+    let mut out = Vec::with_capacity(4 + 4 + 1 + body);
+    out.extend_from_slice(&[MAGIC0, MAGIC1, VERSION, OP_BATCH]);
+    out.extend_from_slice(&nonce.to_le_bytes());
+    out.push(entries.len() as u8);
+    for entry in entries {
+        let (op, name, labels, value) = entry.parts();
+        if name.is_empty() || name.len() > MAX_METRIC_NAME_LEN {
+            return Err(EncodeError::InvalidArgs);
+        }
+        if labels.len() > MAX_LABELS_LEN {
+            return Err(EncodeError::OverLimit);
+        }
+        out.push(op);
+        out.push(name.len() as u8);
+        out.extend_from_slice(&(labels.len() as u16).to_le_bytes());
+        out.extend_from_slice(&value.to_le_bytes());
+        out.extend_from_slice(name);
+        out.extend_from_slice(labels);
+    }
+    Ok(out)
+}
+
+pub(crate) fn decode_batch(nonce: u32, payload: &[u8]) -> Result<Request<'_>, DecodeError> {
+    let [count, bytes @ ..] = payload else {
+        return Err(DecodeError::Malformed);
+    };
+    if *count == 0 {
+        return Err(DecodeError::Malformed);
+    }
+    if usize::from(*count) > MAX_BATCH_ENTRIES {
+        return Err(DecodeError::OverLimit);
+    }
+    let mut rest = bytes;
+    for _ in 0..*count {
+        rest = split_entry(rest)?.1;
+    }
+    if !rest.is_empty() {
+        return Err(DecodeError::Malformed);
+    }
+    Ok(Request::Batch { nonce, entries: BatchEntries { count: *count, bytes } })
+}
+
+/// Splits the first entry off `bytes`, checking it like the single-op decoder would.
+fn split_entry(bytes: &[u8]) -> Result<(BatchEntry<'_>, &[u8]), DecodeError> {
+    if bytes.len() < ENTRY_HEADER_LEN {
+        return Err(DecodeError::Malformed);
+    }
+    let (op, name_len) = (bytes[0], usize::from(bytes[1]));
+    let labels_len = usize::from(u16::from_le_bytes([bytes[2], bytes[3]]));
+    let value = read_u64_le(bytes, 4) as i64;
+    if name_len == 0 || name_len > MAX_METRIC_NAME_LEN || labels_len > MAX_LABELS_LEN {
+        return Err(DecodeError::OverLimit);
+    }
+    let (fields, rest) = bytes[ENTRY_HEADER_LEN..]
+        .split_at_checked(name_len + labels_len)
+        .ok_or(DecodeError::Malformed)?;
+    let (name, labels) = fields.split_at(name_len);
+    let entry = match op {
+        OP_COUNTER_INC if value >= 0 => BatchEntry::Counter { name, labels, delta: value as u64 },
+        OP_GAUGE_SET => BatchEntry::Gauge { name, labels, value },
+        OP_HIST_OBSERVE if value >= 0 => BatchEntry::Hist { name, labels, value: value as u64 },
+        _ => return Err(DecodeError::Malformed),
+    };
+    Ok((entry, rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decode_request;
+
+    fn entries() -> [BatchEntry<'static>; 3] {
+        let labels = BoundedFields::labels(b"svc=netd\n").unwrap();
+        [
+            BatchEntry::counter(MetricName::new(b"net.rx").unwrap(), labels, 40),
+            BatchEntry::gauge(MetricName::new(b"net.queue").unwrap(), labels, -3),
+            BatchEntry::hist(MetricName::new(b"net.lat").unwrap(), labels, 900),
+        ]
+    }
+
+    fn decoded(frame: &[u8]) -> Result<Vec<BatchEntry<'_>>, DecodeError> {
+        match decode_request(frame)? {
+            Request::Batch { entries, .. } => Ok(entries.iter().collect()),
+            _ => Err(DecodeError::Unsupported),
+        }
+    }
+
+    #[test]
+    fn batch_round_trips_in_order() {
+        let frame = encode_batch(6, &entries()).unwrap();
+        assert_eq!(decoded(&frame), Ok(entries().to_vec()));
+        assert_eq!(decode_request(&frame).map(|req| req.op_nonce()), Ok((OP_BATCH, 6)));
+        assert_eq!(
+            entries()[0].request(0),
+            Request::CounterInc { nonce: 0, name: b"net.rx", labels: b"svc=netd\n", delta: 40 }
+        );
+    }
+
+    #[test]
+    fn test_reject_malformed_batches() {
+        assert_eq!(encode_batch(1, &[]), Err(EncodeError::InvalidArgs));
+        let many = [entries()[0]; MAX_BATCH_ENTRIES + 1];
+        assert_eq!(encode_batch(1, &many), Err(EncodeError::OverLimit));
+
+        let frame = encode_batch(1, &entries()).unwrap();
+        assert_eq!(decoded(&frame[..frame.len() - 1]), Err(DecodeError::Malformed));
+        let mut long = frame.clone();
+        long.push(0);
+        assert_eq!(decoded(&long), Err(DecodeError::Malformed));
+
+        let mut empty = frame.clone();
+        empty.truncate(9);
+        empty[8] = 0;
+        assert_eq!(decoded(&empty), Err(DecodeError::Malformed));
+        let mut over = frame.clone();
+        over[8] = MAX_BATCH_ENTRIES as u8 + 1;
+        assert_eq!(decoded(&over), Err(DecodeError::OverLimit));
+
+        // Only the three plain metric ops may appear inside a batch.
+        let mut nested = frame.clone();
+        nested[9] = OP_BATCH;
+        assert_eq!(decoded(&nested), Err(DecodeError::Malformed));
+        // A negative counter delta is malformed, as in COUNTER_INC.
+        let mut negative = frame;
+        negative[13..21].copy_from_slice(&(-1i64).to_le_bytes());
+        assert_eq!(decoded(&negative), Err(DecodeError::Malformed));
+    }
+}
@@ -0,0 +1,285 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+//
+//! CONTEXT: Client-side aggregation of counter/gauge/histogram updates into BATCH frames
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: Host unit tests in this module (against `RefCell<HostBackend>`)
+//! ADR: docs/rfcs/RFC-0024-observability-v2-metrics-tracing-contract-v1.md
+//!
+//! A service bumping a counter on every loop iteration costs metricsd one round trip per
+//! bump. [`BufferedMetricsClient`] keeps the updates locally instead and sends them as one
+//! `OP_BATCH` frame on [`BufferedMetricsClient::flush`], when the buffer holds
+//! [`MAX_BATCH_ENTRIES`] entries, or on drop. Per `(name, labels)` series, counter deltas
+//! are summed and the last gauge value wins; every histogram observation is its own entry.
+//! Readers see the values late by up to one flush interval.
+//!
+//! INVARIANTS:
+//! - At most `MAX_BATCH_ENTRIES` updates are buffered; the update that fills the buffer
+//!   flushes it before returning
+//! - Names and labels are validated when buffered, so a flush never fails on encoding
+//! - A failed flush drops its updates (best effort, like the `metrics_*!` macros): resending
+//!   could count them twice if metricsd applied the batch but the reply was lost
+//! - Spans and pings are not buffered; they go straight to the inner client
+
+use core::cell::RefCell;
+
+use alloc::vec::Vec;
+
+use crate::{
+    BatchEntry, BoundedFields, ClientError, MetricName, MetricsSink, SpanId, TraceId,
+    MAX_BATCH_ENTRIES, STATUS_OK,
+};
+
+/// A client that can send a BATCH frame.
+pub trait BatchSink: MetricsSink {
+    /// Sends `entries` (`1..=MAX_BATCH_ENTRIES`) as one BATCH frame.
+    fn send_batch(&self, entries: &[BatchEntry<'_>]) -> Result<u8, ClientError>;
+}
+
+impl<S: BatchSink + ?Sized> BatchSink for &S {
+    fn send_batch(&self, entries: &[BatchEntry<'_>]) -> Result<u8, ClientError> {
+        (**self).send_batch(entries)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Pending {
+    Counter(u64),
+    Gauge(i64),
+    Hist(u64),
+}
+
+struct Update {
+    name: Vec<u8>,
+    labels: Vec<u8>,
+    value: Pending,
+}
+
+impl Update {
+    fn entry(&self) -> BatchEntry<'_> {
+        let (name, labels) = (self.name.as_slice(), self.labels.as_slice());
+        match self.value {
+            Pending::Counter(delta) => BatchEntry::Counter { name, labels, delta },
+            Pending::Gauge(value) => BatchEntry::Gauge { name, labels, value },
+            Pending::Hist(value) => BatchEntry::Hist { name, labels, value },
+        }
+    }
+}
+
+/// Metrics client that aggregates updates locally and flushes them in batches.
+///
+/// Not `Sync`: keep one per thread of the instrumented service.
+pub struct BufferedMetricsClient<C: BatchSink> {
+    inner: C,
+    pending: RefCell<Vec<Update>>,
+}
+
+impl<C: BatchSink> BufferedMetricsClient<C> {
+    /// Wraps `inner`; nothing is buffered yet.
+    pub fn new(inner: C) -> Self {
+        Self { inner, pending: RefCell::new(Vec::new()) }
+    }
+
+    /// The wrapped client.
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    /// Number of buffered entries.
+    pub fn pending_len(&self) -> usize {
+        self.pending.borrow().len()
+    }
+
+    /// Adds `delta` to the buffered delta of the counter series.
+    pub fn counter_inc(&self, name: &str, labels: &[u8], delta: u64) -> Result<u8, ClientError> {
+        self.buffer(name, labels, Pending::Counter(delta))
+    }
+
+    /// Buffers a gauge value, replacing an earlier buffered value of the series.
+    pub fn gauge_set(&self, name: &str, labels: &[u8], value: i64) -> Result<u8, ClientError> {
+        self.buffer(name, labels, Pending::Gauge(value))
+    }
+
+    /// Buffers one histogram observation.
+    pub fn hist_observe(&self, name: &str, labels: &[u8], value: u64) -> Result<u8, ClientError> {
+        self.buffer(name, labels, Pending::Hist(value))
+    }
+
+    /// Sends every buffered update as one BATCH frame; `STATUS_OK` with no IPC when empty.
+    pub fn flush(&self) -> Result<u8, ClientError> {
+        let pending = core::mem::take(&mut *self.pending.borrow_mut());
+        if pending.is_empty() {
+            return Ok(STATUS_OK);
+        }
+        let entries: Vec<BatchEntry<'_>> = pending.iter().map(Update::entry).collect();
+        self.inner.send_batch(&entries)
+    }
+
+    fn buffer(&self, name: &str, labels: &[u8], value: Pending) -> Result<u8, ClientError> {
+        MetricName::new(name.as_bytes()).map_err(ClientError::Encode)?;
+        BoundedFields::labels(labels).map_err(ClientError::Encode)?;
+        let full = {
+            let mut pending = self.pending.borrow_mut();
+            let series = pending.iter_mut().find(|update| {
+                update.name == name.as_bytes()
+                    && update.labels == labels
+                    && matches!(
+                        (update.value, value),
+                        (Pending::Counter(_), Pending::Counter(_))
+                            | (Pending::Gauge(_), Pending::Gauge(_))
+                    )
+            });
+            match (series, value) {
+                (Some(update), Pending::Counter(delta)) => {
+                    if let Pending::Counter(sum) = &mut update.value {
+                        *sum = sum.saturating_add(delta);
+                    }
+                }
+                (Some(update), Pending::Gauge(_)) => update.value = value,
+                _ => pending.push(Update {
+                    name: name.as_bytes().to_vec(),
+                    labels: labels.to_vec(),
+                    value,
+                }),
+            }
+            pending.len() >= MAX_BATCH_ENTRIES
+        };
+        if full {
+            return self.flush();
+        }
+        Ok(STATUS_OK)
+    }
+}
+
+impl<C: BatchSink> Drop for BufferedMetricsClient<C> {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+impl<C: BatchSink> MetricsSink for BufferedMetricsClient<C> {
+    fn counter_inc(&self, name: &str, labels: &[u8], delta: u64) -> Result<u8, ClientError> {
+        BufferedMetricsClient::counter_inc(self, name, labels, delta)
+    }
+
+    fn gauge_set(&self, name: &str, labels: &[u8], value: i64) -> Result<u8, ClientError> {
+        BufferedMetricsClient::gauge_set(self, name, labels, value)
+    }
+
+    fn hist_observe(&self, name: &str, labels: &[u8], value: u64) -> Result<u8, ClientError> {
+        BufferedMetricsClient::hist_observe(self, name, labels, value)
+    }
+
+    fn span_start(
+        &self,
+        span_id: SpanId,
+        trace_id: TraceId,
+        parent_span_id: SpanId,
+        start_ns: u64,
+        name: &str,
+        attrs: &[u8],
+    ) -> Result<u8, ClientError> {
+        self.inner.span_start(span_id, trace_id, parent_span_id, start_ns, name, attrs)
+    }
+
+    fn span_end(
+        &self,
+        span_id: SpanId,
+        end_ns: u64,
+        status: u8,
+        attrs: &[u8],
+    ) -> Result<u8, ClientError> {
+        self.inner.span_end(span_id, end_ns, status, attrs)
+    }
+
+    fn ping(&self) -> Result<u8, ClientError> {
+        self.inner.ping()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::host::{Event, HostBackend};
+    use crate::EncodeError;
+
+    fn counter(name: &str, delta: u64) -> Event {
+        Event::Counter { name: name.as_bytes().to_vec(), labels: Vec::new(), delta }
+    }
+
+    #[test]
+    fn increments_between_flushes_become_one_summed_add() {
+        let client = BufferedMetricsClient::new(RefCell::new(HostBackend::new()));
+        for _ in 0..100 {
+            crate::metrics_counter_inc!(client, "loop.iter", 1);
+        }
+        assert!(client.inner().borrow().events().is_empty());
+        assert_eq!(client.flush(), Ok(STATUS_OK));
+        assert_eq!(client.inner().borrow().batches(), 1);
+        assert_eq!(client.inner().borrow().events(), &[counter("loop.iter", 100)]);
+
+        // A flush with nothing buffered sends nothing.
+        assert_eq!(client.flush(), Ok(STATUS_OK));
+        assert_eq!(client.inner().borrow().batches(), 1);
+    }
+
+    #[test]
+    fn gauges_keep_the_last_value_and_histograms_every_observation() {
+        let client = BufferedMetricsClient::new(RefCell::new(HostBackend::new()));
+        for value in [5, -2, 9] {
+            client.gauge_set("queue.depth", b"", value).unwrap();
+        }
+        client.hist_observe("lat", b"", 10).unwrap();
+        client.hist_observe("lat", b"", 20).unwrap();
+        client.counter_inc("hits", b"q=a\n", 2).unwrap();
+        client.counter_inc("hits", b"q=b\n", 3).unwrap();
+        assert_eq!(client.pending_len(), 5);
+        assert_eq!(client.flush(), Ok(STATUS_OK));
+
+        let hist = |value| Event::Hist { name: b"lat".to_vec(), labels: Vec::new(), value };
+        let hits = |labels: &[u8], delta| Event::Counter {
+            name: b"hits".to_vec(),
+            labels: labels.to_vec(),
+            delta,
+        };
+        let expected = [
+            Event::Gauge { name: b"queue.depth".to_vec(), labels: Vec::new(), value: 9 },
+            hist(10),
+            hist(20),
+            hits(b"q=a\n", 2),
+            hits(b"q=b\n", 3),
+        ];
+        assert_eq!(client.inner().borrow().events(), &expected);
+    }
+
+    #[test]
+    fn full_buffer_and_drop_flush() {
+        let backend = RefCell::new(HostBackend::new());
+        let client = BufferedMetricsClient::new(&backend);
+        for value in 0..MAX_BATCH_ENTRIES as u64 + 1 {
+            client.hist_observe("lat", b"", value).unwrap();
+        }
+        assert_eq!(backend.borrow().batches(), 1);
+        assert_eq!(client.pending_len(), 1);
+        drop(client);
+        assert_eq!(backend.borrow().batches(), 2);
+        assert_eq!(backend.borrow().events().len(), MAX_BATCH_ENTRIES + 1);
+    }
+
+    #[test]
+    fn test_reject_invalid_series_before_buffering() {
+        let client = BufferedMetricsClient::new(RefCell::new(HostBackend::new()));
+        let long = [b'k'; crate::MAX_LABELS_LEN + 1];
+        assert_eq!(
+            client.counter_inc("", b"", 1),
+            Err(ClientError::Encode(EncodeError::InvalidArgs))
+        );
+        assert_eq!(
+            client.counter_inc("ok", &long, 1),
+            Err(ClientError::Encode(EncodeError::OverLimit))
+        );
+        assert_eq!(client.pending_len(), 0);
+    }
+}
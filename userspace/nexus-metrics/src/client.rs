@@ -0,0 +1,336 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+//
+//! CONTEXT: OS-lite metricsd IPC client (routed KernelClient, 500ms bounded send/recv)
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: QEMU observability markers (no host tests; OS-only module)
+//! ADR: docs/rfcs/RFC-0024-observability-v2-metrics-tracing-contract-v1.md
+
+use super::*;
+use core::sync::atomic::{AtomicU32, Ordering};
+use core::time::Duration;
+use nexus_ipc::{Client as _, KernelClient, Wait};
+
+/// OS metrics/tracing client over kernel IPC.
+pub struct MetricsClient {
+    ipc: KernelClient,
+    next_nonce: AtomicU32,
+    ping_rtt: PingRttHistogram,
+}
+
+impl MetricsClient {
+    /// Creates a client routed to `metricsd`.
+    pub fn new() -> Result<Self, ClientError> {
+        Self::new_for("metricsd")
+    }
+
+    /// Creates a client for an explicit service name.
+    pub fn new_for(service_name: &str) -> Result<Self, ClientError> {
+        let ipc = KernelClient::new_for(service_name).map_err(|_| ClientError::Transport)?;
+        Ok(Self { ipc, next_nonce: AtomicU32::new(1), ping_rtt: PingRttHistogram::new() })
+    }
+
+    fn nonce(&self) -> u32 {
+        self.next_nonce.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Sends a counter increment.
+    pub fn counter_inc(&self, name: &str, labels: &[u8], delta: u64) -> Result<u8, ClientError> {
+        let nonce = self.nonce();
+        let frame = encode_counter_inc(
+            nonce,
+            MetricName::new(name.as_bytes()).map_err(ClientError::Encode)?,
+            BoundedFields::labels(labels).map_err(ClientError::Encode)?,
+            delta,
+        )
+        .map_err(ClientError::Encode)?;
+        self.send_and_parse(OP_COUNTER_INC, nonce, &frame)
+    }
+
+    /// Sends a gauge set.
+    pub fn gauge_set(&self, name: &str, labels: &[u8], value: i64) -> Result<u8, ClientError> {
+        let nonce = self.nonce();
+        let frame = encode_gauge_set(
+            nonce,
+            MetricName::new(name.as_bytes()).map_err(ClientError::Encode)?,
+            BoundedFields::labels(labels).map_err(ClientError::Encode)?,
+            value,
+        )
+        .map_err(ClientError::Encode)?;
+        self.send_and_parse(OP_GAUGE_SET, nonce, &frame)
+    }
+
+    /// Sends a gauge set of `value_milli` thousandths to a `*.milli` metric.
+    pub fn gauge_set_milli(
+        &self,
+        name: &str,
+        labels: &[u8],
+        value_milli: i64,
+    ) -> Result<u8, ClientError> {
+        let nonce = self.nonce();
+        let frame = encode_gauge_set_milli(
+            nonce,
+            MetricName::new(name.as_bytes()).map_err(ClientError::Encode)?,
+            BoundedFields::labels(labels).map_err(ClientError::Encode)?,
+            value_milli,
+        )
+        .map_err(ClientError::Encode)?;
+        self.send_and_parse(OP_GAUGE_SET, nonce, &frame)
+    }
+
+    /// Sends a histogram observation.
+    pub fn hist_observe(&self, name: &str, labels: &[u8], value: u64) -> Result<u8, ClientError> {
+        let nonce = self.nonce();
+        let frame = encode_hist_observe(
+            nonce,
+            MetricName::new(name.as_bytes()).map_err(ClientError::Encode)?,
+            BoundedFields::labels(labels).map_err(ClientError::Encode)?,
+            value,
+        )
+        .map_err(ClientError::Encode)?;
+        self.send_and_parse(OP_HIST_OBSERVE, nonce, &frame)
+    }
+
+    /// Sends `entries` as one BATCH frame (see [`BufferedMetricsClient`]).
+    pub fn send_batch(&self, entries: &[BatchEntry<'_>]) -> Result<u8, ClientError> {
+        let nonce = self.nonce();
+        let frame = encode_batch(nonce, entries).map_err(ClientError::Encode)?;
+        self.send_and_parse(OP_BATCH, nonce, &frame)
+    }
+
+    /// Sends a windowed counter increment (`window_id` is one of the `WINDOW_*` ids).
+    pub fn windowed_inc(
+        &self,
+        window_id: u8,
+        name: &str,
+        labels: &[u8],
+        delta: u64,
+    ) -> Result<u8, ClientError> {
+        let nonce = self.nonce();
+        let frame = encode_windowed_inc(
+            nonce,
+            window_id,
+            MetricName::new(name.as_bytes()).map_err(ClientError::Encode)?,
+            BoundedFields::labels(labels).map_err(ClientError::Encode)?,
+            delta,
+        )
+        .map_err(ClientError::Encode)?;
+        self.send_and_parse(OP_WINDOWED_INC, nonce, &frame)
+    }
+
+    /// Queries one percentile (`1..=100`) of this service's histogram series.
+    ///
+    /// Returns `(status, value)`; `value` is the upper bound of the bucket holding the
+    /// percentile and is present only with `STATUS_OK`.
+    pub fn hist_quantile(
+        &self,
+        name: &str,
+        labels: &[u8],
+        percentile: u8,
+    ) -> Result<(u8, Option<u64>), ClientError> {
+        let nonce = self.nonce();
+        let frame = encode_hist_quantile(
+            nonce,
+            MetricName::new(name.as_bytes()).map_err(ClientError::Encode)?,
+            BoundedFields::labels(labels).map_err(ClientError::Encode)?,
+            percentile,
+        )
+        .map_err(ClientError::Encode)?;
+        let rsp = self.send_and_recv(&frame)?;
+        decode_hist_quantile_response(&rsp, nonce).map_err(ClientError::Decode)
+    }
+
+    /// Raises the gauge to `value` if it is higher; returns `(status, resulting value)`.
+    pub fn gauge_max(
+        &self,
+        name: &str,
+        labels: &[u8],
+        value: i64,
+    ) -> Result<(u8, Option<i64>), ClientError> {
+        self.gauge_bound(GaugeBound::Max, name, labels, value)
+    }
+
+    /// Lowers the gauge to `value` if it is lower; returns `(status, resulting value)`.
+    pub fn gauge_min(
+        &self,
+        name: &str,
+        labels: &[u8],
+        value: i64,
+    ) -> Result<(u8, Option<i64>), ClientError> {
+        self.gauge_bound(GaugeBound::Min, name, labels, value)
+    }
+
+    fn gauge_bound(
+        &self,
+        bound: GaugeBound,
+        name: &str,
+        labels: &[u8],
+        value: i64,
+    ) -> Result<(u8, Option<i64>), ClientError> {
+        let nonce = self.nonce();
+        let name = MetricName::new(name.as_bytes()).map_err(ClientError::Encode)?;
+        let labels = BoundedFields::labels(labels).map_err(ClientError::Encode)?;
+        let frame = match bound {
+            GaugeBound::Max => encode_gauge_max(nonce, name, labels, value),
+            GaugeBound::Min => encode_gauge_min(nonce, name, labels, value),
+        }
+        .map_err(ClientError::Encode)?;
+        let rsp = self.send_and_recv(&frame)?;
+        decode_gauge_bound_response(&rsp, bound, nonce).map_err(ClientError::Decode)
+    }
+
+    /// Sends a span start event.
+    pub fn span_start(
+        &self,
+        span_id: SpanId,
+        trace_id: TraceId,
+        parent_span_id: SpanId,
+        start_ns: u64,
+        name: &str,
+        attrs: &[u8],
+    ) -> Result<u8, ClientError> {
+        let nonce = self.nonce();
+        let frame = encode_span_start(
+            nonce,
+            span_id,
+            trace_id,
+            parent_span_id,
+            start_ns,
+            SpanName::new(name.as_bytes()).map_err(ClientError::Encode)?,
+            BoundedFields::attrs(attrs).map_err(ClientError::Encode)?,
+        )
+        .map_err(ClientError::Encode)?;
+        self.send_and_parse(OP_SPAN_START, nonce, &frame)
+    }
+
+    /// Starts a span and returns an end-on-drop guard.
+    pub fn span_guard(
+        &self,
+        ids: &mut DeterministicIdSource,
+        parent_span_id: SpanId,
+        start_ns: u64,
+        name: &str,
+        attrs: &[u8],
+    ) -> Result<SpanGuard<'_, Self>, ClientError> {
+        let span_id = ids.next_span_id();
+        let trace_id = ids.next_trace_id();
+        let status = self.span_start(span_id, trace_id, parent_span_id, start_ns, name, attrs)?;
+        if status != STATUS_OK {
+            return Err(ClientError::Decode(DecodeError::Malformed));
+        }
+        Ok(SpanGuard::new(self, span_id, default_guard_end_ns))
+    }
+
+    /// Sends a span end event.
+    pub fn span_end(
+        &self,
+        span_id: SpanId,
+        end_ns: u64,
+        status: u8,
+        attrs: &[u8],
+    ) -> Result<u8, ClientError> {
+        let nonce = self.nonce();
+        let frame = encode_span_end(
+            nonce,
+            span_id,
+            end_ns,
+            status,
+            BoundedFields::attrs(attrs).map_err(ClientError::Encode)?,
+        )
+        .map_err(ClientError::Encode)?;
+        self.send_and_parse(OP_SPAN_END, nonce, &frame)
+    }
+
+    /// Sends a liveness ping.
+    pub fn ping(&self) -> Result<u8, ClientError> {
+        let nonce = self.nonce();
+        let frame = encode_ping(nonce);
+        self.send_and_parse(OP_PING, nonce, &frame)
+    }
+
+    /// Pings metricsd and returns the round-trip time in nanoseconds, as read from `now_ns`.
+    ///
+    /// Each measured RTT is also recorded in this client's histogram ([`Self::ping_rtt_stats`]).
+    pub fn ping_rtt(&self, now_ns: fn() -> u64) -> Result<u64, ClientError> {
+        measure_ping_rtt(self.nonce(), now_ns, &self.ping_rtt, |frame| self.send_and_recv(frame))
+    }
+
+    /// Ping RTTs measured by [`Self::ping_rtt`] so far.
+    pub fn ping_rtt_stats(&self) -> PingRttStats {
+        self.ping_rtt.stats()
+    }
+
+    fn send_and_parse(&self, op: u8, nonce: u32, frame: &[u8]) -> Result<u8, ClientError> {
+        let rsp = self.send_and_recv(frame)?;
+        decode_status_response(&rsp, op, nonce).map_err(ClientError::Decode)
+    }
+
+    fn send_and_recv(&self, frame: &[u8]) -> Result<Vec<u8>, ClientError> {
+        self.ipc
+            .send(frame, Wait::Timeout(Duration::from_millis(500)))
+            .map_err(|_| ClientError::Transport)?;
+        self.ipc.recv(Wait::Timeout(Duration::from_millis(500))).map_err(|_| ClientError::Transport)
+    }
+}
+
+impl SpanEndClient for MetricsClient {
+    type Error = ClientError;
+
+    fn end_span(
+        &self,
+        span_id: SpanId,
+        end_ns: u64,
+        status: u8,
+        attrs: &[u8],
+    ) -> Result<u8, Self::Error> {
+        MetricsClient::span_end(self, span_id, end_ns, status, attrs)
+    }
+}
+
+impl BatchSink for MetricsClient {
+    fn send_batch(&self, entries: &[BatchEntry<'_>]) -> Result<u8, ClientError> {
+        MetricsClient::send_batch(self, entries)
+    }
+}
+
+impl MetricsSink for MetricsClient {
+    fn counter_inc(&self, name: &str, labels: &[u8], delta: u64) -> Result<u8, ClientError> {
+        MetricsClient::counter_inc(self, name, labels, delta)
+    }
+
+    fn gauge_set(&self, name: &str, labels: &[u8], value: i64) -> Result<u8, ClientError> {
+        MetricsClient::gauge_set(self, name, labels, value)
+    }
+
+    fn hist_observe(&self, name: &str, labels: &[u8], value: u64) -> Result<u8, ClientError> {
+        MetricsClient::hist_observe(self, name, labels, value)
+    }
+
+    fn span_start(
+        &self,
+        span_id: SpanId,
+        trace_id: TraceId,
+        parent_span_id: SpanId,
+        start_ns: u64,
+        name: &str,
+        attrs: &[u8],
+    ) -> Result<u8, ClientError> {
+        MetricsClient::span_start(self, span_id, trace_id, parent_span_id, start_ns, name, attrs)
+    }
+
+    fn span_end(
+        &self,
+        span_id: SpanId,
+        end_ns: u64,
+        status: u8,
+        attrs: &[u8],
+    ) -> Result<u8, ClientError> {
+        MetricsClient::span_end(self, span_id, end_ns, status, attrs)
+    }
+
+    fn ping(&self) -> Result<u8, ClientError> {
+        MetricsClient::ping(self)
+    }
+}
@@ -0,0 +1,187 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+//
+//! CONTEXT: Fixed-point (milli-unit) gauges and counters over the integer wire
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: Host unit tests in this module
+//! ADR: docs/rfcs/RFC-0024-observability-v2-metrics-tracing-contract-v1.md
+//!
+//! The wire carries integers only. A fractional metric (CPU fraction, temperature) is
+//! sent in thousandths: its name ends in [`MILLI_SUFFIX`] (`cpu.busy.milli`) and its
+//! value is the real value times [`MILLI_SCALE`], in the existing `i64`/`u64` field.
+//! The scale is part of the name, so client, daemon and any export path agree without
+//! extra wire fields. [`ScaledValue`] renders a value with the decimal point for its
+//! name's scale (`0.250`, `-12.500`); all arithmetic stays integer.
+
+use core::fmt;
+
+use alloc::vec::Vec;
+
+use crate::{encode_counter_inc, encode_gauge_set, BoundedFields, EncodeError, MetricName};
+
+/// Name suffix marking a metric whose values are in thousandths.
+pub const MILLI_SUFFIX: &[u8] = b".milli";
+/// Wire units per whole unit for [`MetricScale::Milli`] metrics.
+pub const MILLI_SCALE: u64 = 1000;
+
+/// How a metric's integer wire value maps to its real value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MetricScale {
+    /// The wire value is the value.
+    Unit,
+    /// The wire value is the value times [`MILLI_SCALE`].
+    Milli,
+}
+
+impl MetricScale {
+    /// Scale implied by a metric name (see [`MILLI_SUFFIX`]).
+    pub fn of(name: &[u8]) -> Self {
+        if name.len() > MILLI_SUFFIX.len() && name.ends_with(MILLI_SUFFIX) {
+            Self::Milli
+        } else {
+            Self::Unit
+        }
+    }
+}
+
+impl MetricName<'_> {
+    /// Scale implied by this name.
+    pub fn scale(self) -> MetricScale {
+        MetricScale::of(self.as_bytes())
+    }
+}
+
+/// Encodes a GAUGE_SET of `value_milli` thousandths; `name` must end in [`MILLI_SUFFIX`].
+pub fn encode_gauge_set_milli(
+    nonce: u32,
+    name: MetricName<'_>,
+    labels: BoundedFields<'_>,
+    value_milli: i64,
+) -> Result<Vec<u8>, EncodeError> {
+    if name.scale() != MetricScale::Milli {
+        return Err(EncodeError::InvalidArgs);
+    }
+    encode_gauge_set(nonce, name, labels, value_milli)
+}
+
+/// Encodes a COUNTER_INC of `delta_milli` thousandths; `name` must end in [`MILLI_SUFFIX`].
+pub fn encode_counter_inc_milli(
+    nonce: u32,
+    name: MetricName<'_>,
+    labels: BoundedFields<'_>,
+    delta_milli: u64,
+) -> Result<Vec<u8>, EncodeError> {
+    if name.scale() != MetricScale::Milli {
+        return Err(EncodeError::InvalidArgs);
+    }
+    encode_counter_inc(nonce, name, labels, delta_milli)
+}
+
+/// A wire value paired with its scale, rendered with a decimal point when scaled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScaledValue {
+    negative: bool,
+    magnitude: u64,
+    scale: MetricScale,
+}
+
+impl ScaledValue {
+    /// A gauge value.
+    pub const fn signed(value: i64, scale: MetricScale) -> Self {
+        Self { negative: value < 0, magnitude: value.unsigned_abs(), scale }
+    }
+
+    /// A counter value.
+    pub const fn unsigned(value: u64, scale: MetricScale) -> Self {
+        Self { negative: false, magnitude: value, scale }
+    }
+
+    /// Whole units and thousandths (`(1, 250)` for `1.250`), without the sign.
+    pub const fn milli_parts(self) -> (u64, u64) {
+        (self.magnitude / MILLI_SCALE, self.magnitude % MILLI_SCALE)
+    }
+}
+
+impl fmt::Display for ScaledValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.negative { "-" } else { "" };
+        match self.scale {
+            MetricScale::Unit => write!(f, "{sign}{}", self.magnitude),
+            MetricScale::Milli => {
+                let (whole, frac) = self.milli_parts();
+                write!(f, "{sign}{whole}.{frac:03}")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+    use crate::{decode_request, Request};
+
+    fn name(bytes: &[u8]) -> MetricName<'_> {
+        MetricName::new(bytes).unwrap()
+    }
+
+    #[test]
+    fn fractional_gauge_round_trips_and_renders() {
+        let labels = BoundedFields::labels(b"zone=cpu0\n").unwrap();
+        // 41.5 degrees.
+        let frame = encode_gauge_set_milli(3, name(b"thermal.temp.milli"), labels, 41_500).unwrap();
+        assert_eq!(
+            decode_request(&frame),
+            Ok(Request::GaugeSet {
+                nonce: 3,
+                name: b"thermal.temp.milli",
+                labels: b"zone=cpu0\n",
+                value: 41_500
+            })
+        );
+        let scale = MetricScale::of(b"thermal.temp.milli");
+        assert_eq!(scale, MetricScale::Milli);
+        assert_eq!(ScaledValue::signed(41_500, scale).to_string(), "41.500");
+
+        let frame = encode_counter_inc_milli(4, name(b"cpu.busy.milli"), labels, 250).unwrap();
+        assert_eq!(
+            decode_request(&frame),
+            Ok(Request::CounterInc {
+                nonce: 4,
+                name: b"cpu.busy.milli",
+                labels: b"zone=cpu0\n",
+                delta: 250
+            })
+        );
+        assert_eq!(ScaledValue::unsigned(250, MetricScale::Milli).milli_parts(), (0, 250));
+    }
+
+    #[test]
+    fn text_rendering_places_the_decimal_point() {
+        let milli = MetricScale::Milli;
+        assert_eq!(ScaledValue::signed(-250, milli).to_string(), "-0.250");
+        assert_eq!(ScaledValue::signed(-12_005, milli).to_string(), "-12.005");
+        assert_eq!(ScaledValue::signed(0, milli).to_string(), "0.000");
+        assert_eq!(ScaledValue::signed(i64::MIN, milli).to_string(), "-9223372036854775.808");
+        assert_eq!(ScaledValue::unsigned(u64::MAX, milli).to_string(), "18446744073709551.615");
+        assert_eq!(ScaledValue::signed(-7, MetricScale::Unit).to_string(), "-7");
+        assert_eq!(ScaledValue::unsigned(7, MetricScale::Unit).to_string(), "7");
+    }
+
+    #[test]
+    fn test_reject_milli_encoder_on_unscaled_name() {
+        let labels = BoundedFields::labels(b"").unwrap();
+        assert_eq!(
+            encode_gauge_set_milli(1, name(b"cpu.busy"), labels, 1),
+            Err(EncodeError::InvalidArgs)
+        );
+        assert_eq!(
+            encode_counter_inc_milli(1, name(b".milli"), labels, 1),
+            Err(EncodeError::InvalidArgs)
+        );
+        assert_eq!(MetricScale::of(b"cpu.millis"), MetricScale::Unit);
+    }
+}
@@ -0,0 +1,184 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+//
+//! CONTEXT: GAUGE_MAX / GAUGE_MIN wire — server-side high- and low-water marks
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: Host unit tests in this module
+//! ADR: docs/rfcs/RFC-0024-observability-v2-metrics-tracing-contract-v1.md
+//!
+//! Tracking a peak with `GAUGE_SET` needs a read-then-write on the client, which races
+//! with other writers. `OP_GAUGE_MAX` / `OP_GAUGE_MIN` keep the `GAUGE_SET` request layout
+//! (`nonce:u32 | name_len:u8 | labels_len:u16 | value:i64 | name | labels`) and metricsd
+//! stores the value only when it is greater / less than the current one; the first update
+//! of a series stores it as is. Both update the same gauge series as `GAUGE_SET`.
+//!
+//! Response: the 9-byte status frame; with `STATUS_OK` it is followed by the resulting
+//! gauge value as `i64` LE.
+
+use alloc::vec::Vec;
+
+use crate::{
+    decode_status_response, encode_metric_value_frame, encode_status_response, BoundedFields,
+    DecodeError, EncodeError, MetricName, Request, OP_GAUGE_MAX, OP_GAUGE_MIN, STATUS_OK,
+};
+
+/// Status frame (9 bytes) plus the resulting `i64` value.
+const GAUGE_BOUND_RSP_LEN: usize = 17;
+
+/// Which water mark a GAUGE_MAX / GAUGE_MIN request moves.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GaugeBound {
+    /// High-water mark: keep the greater value.
+    Max,
+    /// Low-water mark: keep the lesser value.
+    Min,
+}
+
+impl GaugeBound {
+    /// Wire opcode of the bound.
+    pub const fn op(self) -> u8 {
+        match self {
+            Self::Max => OP_GAUGE_MAX,
+            Self::Min => OP_GAUGE_MIN,
+        }
+    }
+
+    /// The value a gauge holding `current` keeps after an update with `value`.
+    pub fn apply(self, current: i64, value: i64) -> i64 {
+        match self {
+            Self::Max => current.max(value),
+            Self::Min => current.min(value),
+        }
+    }
+}
+
+/// Encodes a GAUGE_MAX frame.
+pub fn encode_gauge_max(
+    nonce: u32,
+    name: MetricName<'_>,
+    labels: BoundedFields<'_>,
+    value: i64,
+) -> Result<Vec<u8>, EncodeError> {
+    encode_metric_value_frame(OP_GAUGE_MAX, nonce, name.as_bytes(), labels.as_bytes(), value)
+}
+
+/// Encodes a GAUGE_MIN frame.
+pub fn encode_gauge_min(
+    nonce: u32,
+    name: MetricName<'_>,
+    labels: BoundedFields<'_>,
+    value: i64,
+) -> Result<Vec<u8>, EncodeError> {
+    encode_metric_value_frame(OP_GAUGE_MIN, nonce, name.as_bytes(), labels.as_bytes(), value)
+}
+
+pub(crate) fn gauge_bound_request<'a>(
+    op: u8,
+    nonce: u32,
+    name: &'a [u8],
+    labels: &'a [u8],
+    value: i64,
+) -> Request<'a> {
+    let bound = if op == OP_GAUGE_MAX { GaugeBound::Max } else { GaugeBound::Min };
+    Request::GaugeBound { nonce, bound, name, labels, value }
+}
+
+/// Encodes a GAUGE_MAX / GAUGE_MIN response; `value` is only sent with [`STATUS_OK`].
+pub fn encode_gauge_bound_response(
+    bound: GaugeBound,
+    nonce: u32,
+    status: u8,
+    value: i64,
+) -> Vec<u8> {
+    let mut out = encode_status_response(bound.op(), nonce, status);
+    if status == STATUS_OK {
+        out.extend_from_slice(&value.to_le_bytes());
+    }
+    out
+}
+
+/// Decodes a GAUGE_MAX / GAUGE_MIN response into `(status, resulting value)`.
+///
+/// The value is `Some` exactly when `status` is [`STATUS_OK`].
+pub fn decode_gauge_bound_response(
+    frame: &[u8],
+    bound: GaugeBound,
+    expected_nonce: u32,
+) -> Result<(u8, Option<i64>), DecodeError> {
+    if frame.len() != GAUGE_BOUND_RSP_LEN {
+        return match decode_status_response(frame, bound.op(), expected_nonce)? {
+            STATUS_OK => Err(DecodeError::Malformed),
+            status => Ok((status, None)),
+        };
+    }
+    let (head, value) = frame.split_at(GAUGE_BOUND_RSP_LEN - 8);
+    let status = decode_status_response(head, bound.op(), expected_nonce)?;
+    if status != STATUS_OK {
+        return Err(DecodeError::Malformed);
+    }
+    let mut raw = [0u8; 8];
+    raw.copy_from_slice(value);
+    Ok((status, Some(i64::from_le_bytes(raw))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{decode_request, encode_status_response_ex, REASON_SERIES_TOTAL};
+    use crate::{STATUS_OVER_LIMIT, STATUS_RATE_LIMITED};
+
+    fn labels() -> BoundedFields<'static> {
+        BoundedFields::labels(b"pool=rx\n").unwrap()
+    }
+
+    #[test]
+    fn gauge_bound_frames_round_trip() {
+        let name = MetricName::new(b"net.queue.peak").unwrap();
+        let max = encode_gauge_max(5, name, labels(), -7).unwrap();
+        let min = encode_gauge_min(6, name, labels(), i64::MIN).unwrap();
+        let (name, labels) = (&b"net.queue.peak"[..], &b"pool=rx\n"[..]);
+        assert_eq!(
+            decode_request(&max),
+            Ok(Request::GaugeBound { nonce: 5, bound: GaugeBound::Max, name, labels, value: -7 })
+        );
+        assert_eq!(decode_request(&min).map(|req| req.op_nonce()), Ok((OP_GAUGE_MIN, 6)));
+        assert!(matches!(
+            decode_request(&min),
+            Ok(Request::GaugeBound { bound: GaugeBound::Min, value: i64::MIN, .. })
+        ));
+
+        let rsp = encode_gauge_bound_response(GaugeBound::Max, 5, STATUS_OK, -7);
+        assert_eq!(
+            decode_gauge_bound_response(&rsp, GaugeBound::Max, 5),
+            Ok((STATUS_OK, Some(-7)))
+        );
+        assert_eq!(GaugeBound::Max.apply(3, 9), 9);
+        assert_eq!(GaugeBound::Min.apply(3, 9), 3);
+    }
+
+    #[test]
+    fn test_reject_mismatched_or_truncated_gauge_bound_responses() {
+        let rsp = encode_gauge_bound_response(GaugeBound::Min, 2, STATUS_OK, 4);
+        assert_eq!(
+            decode_gauge_bound_response(&rsp, GaugeBound::Max, 2),
+            Err(DecodeError::Unsupported)
+        );
+        assert_eq!(
+            decode_gauge_bound_response(&rsp[..9], GaugeBound::Min, 2),
+            Err(DecodeError::Malformed)
+        );
+        let limited = encode_gauge_bound_response(GaugeBound::Min, 2, STATUS_RATE_LIMITED, 4);
+        assert_eq!(
+            decode_gauge_bound_response(&limited, GaugeBound::Min, 2),
+            Ok((STATUS_RATE_LIMITED, None))
+        );
+        let over =
+            encode_status_response_ex(OP_GAUGE_MAX, 3, STATUS_OVER_LIMIT, REASON_SERIES_TOTAL);
+        assert_eq!(
+            decode_gauge_bound_response(&over, GaugeBound::Max, 3),
+            Ok((STATUS_OVER_LIMIT, None))
+        );
+    }
+}
@@ -0,0 +1,163 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+//
+//! CONTEXT: Host-only deterministic metrics backend for tests
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: Exercised by host unit tests in this crate
+//! ADR: docs/rfcs/RFC-0024-observability-v2-metrics-tracing-contract-v1.md
+
+use super::*;
+use core::cell::RefCell;
+
+/// Minimal host event model.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event {
+    Counter { name: Vec<u8>, labels: Vec<u8>, delta: u64 },
+    Gauge { name: Vec<u8>, labels: Vec<u8>, value: i64 },
+    Hist { name: Vec<u8>, labels: Vec<u8>, value: u64 },
+    SpanStart { span_id: SpanId, trace_id: TraceId, name: Vec<u8> },
+    SpanEnd { span_id: SpanId, status: u8 },
+}
+
+/// In-memory host backend used by host tests.
+pub struct HostBackend {
+    events: Vec<Event>,
+    batches: usize,
+}
+
+impl HostBackend {
+    /// Creates an empty backend.
+    pub fn new() -> Self {
+        Self { events: Vec::new(), batches: 0 }
+    }
+
+    /// Records a counter event.
+    pub fn counter_inc(&mut self, name: &str, labels: &[u8], delta: u64) {
+        self.events.push(Event::Counter {
+            name: name.as_bytes().to_vec(),
+            labels: labels.to_vec(),
+            delta,
+        });
+    }
+
+    /// Records a gauge event.
+    pub fn gauge_set(&mut self, name: &str, labels: &[u8], value: i64) {
+        self.events.push(Event::Gauge {
+            name: name.as_bytes().to_vec(),
+            labels: labels.to_vec(),
+            value,
+        });
+    }
+
+    /// Records a histogram event.
+    pub fn hist_observe(&mut self, name: &str, labels: &[u8], value: u64) {
+        self.events.push(Event::Hist {
+            name: name.as_bytes().to_vec(),
+            labels: labels.to_vec(),
+            value,
+        });
+    }
+
+    /// Records a span start event.
+    pub fn span_start(&mut self, span_id: SpanId, trace_id: TraceId, name: &str) {
+        self.events.push(Event::SpanStart { span_id, trace_id, name: name.as_bytes().to_vec() });
+    }
+
+    /// Records a span end event.
+    pub fn span_end(&mut self, span_id: SpanId, status: u8) {
+        self.events.push(Event::SpanEnd { span_id, status });
+    }
+
+    /// Returns immutable events.
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    /// Number of BATCH frames received; their entries are recorded as plain events.
+    pub fn batches(&self) -> usize {
+        self.batches
+    }
+}
+
+impl Default for HostBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Lets a host test hand the backend to code that takes a [`MetricsSink`] and read the
+/// recorded events afterwards. Span parents, timestamps and attrs are not recorded.
+impl MetricsSink for RefCell<HostBackend> {
+    fn counter_inc(&self, name: &str, labels: &[u8], delta: u64) -> Result<u8, ClientError> {
+        self.borrow_mut().counter_inc(name, labels, delta);
+        Ok(STATUS_OK)
+    }
+
+    fn gauge_set(&self, name: &str, labels: &[u8], value: i64) -> Result<u8, ClientError> {
+        self.borrow_mut().gauge_set(name, labels, value);
+        Ok(STATUS_OK)
+    }
+
+    fn hist_observe(&self, name: &str, labels: &[u8], value: u64) -> Result<u8, ClientError> {
+        self.borrow_mut().hist_observe(name, labels, value);
+        Ok(STATUS_OK)
+    }
+
+    fn span_start(
+        &self,
+        span_id: SpanId,
+        trace_id: TraceId,
+        _parent_span_id: SpanId,
+        _start_ns: u64,
+        name: &str,
+        _attrs: &[u8],
+    ) -> Result<u8, ClientError> {
+        self.borrow_mut().span_start(span_id, trace_id, name);
+        Ok(STATUS_OK)
+    }
+
+    fn span_end(
+        &self,
+        span_id: SpanId,
+        _end_ns: u64,
+        status: u8,
+        _attrs: &[u8],
+    ) -> Result<u8, ClientError> {
+        self.borrow_mut().span_end(span_id, status);
+        Ok(STATUS_OK)
+    }
+
+    fn ping(&self) -> Result<u8, ClientError> {
+        Ok(STATUS_OK)
+    }
+}
+
+/// Round-trips the entries through the BATCH wire codec before recording them.
+impl BatchSink for RefCell<HostBackend> {
+    fn send_batch(&self, entries: &[BatchEntry<'_>]) -> Result<u8, ClientError> {
+        let frame = encode_batch(0, entries).map_err(ClientError::Encode)?;
+        let Request::Batch { entries, .. } = decode_request(&frame).map_err(ClientError::Decode)?
+        else {
+            return Err(ClientError::Decode(DecodeError::Malformed));
+        };
+        let mut backend = self.borrow_mut();
+        backend.batches += 1;
+        for entry in entries.iter() {
+            let event = match entry {
+                BatchEntry::Counter { name, labels, delta } => {
+                    Event::Counter { name: name.to_vec(), labels: labels.to_vec(), delta }
+                }
+                BatchEntry::Gauge { name, labels, value } => {
+                    Event::Gauge { name: name.to_vec(), labels: labels.to_vec(), value }
+                }
+                BatchEntry::Hist { name, labels, value } => {
+                    Event::Hist { name: name.to_vec(), labels: labels.to_vec(), value }
+                }
+            };
+            backend.events.push(event);
+        }
+        Ok(STATUS_OK)
+    }
+}
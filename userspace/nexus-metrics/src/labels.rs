@@ -0,0 +1,430 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+//
+//! CONTEXT: Bounded label/attribute fields, the typed `LabelSet` and `AttrSet` builders
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: 6 unit tests (encoding, separator injection, over-limit, typed attrs)
+//! ADR: docs/rfcs/RFC-0024-observability-v2-metrics-tracing-contract-v1.md
+//!
+//! Labels follow the RFC-0011 `key=value\n` convention. `BoundedFields::labels`
+//! only checks the length of caller-assembled bytes; `LabelSet` builds them pair
+//! by pair so a malformed series is rejected at the call site instead of stored.
+//!
+//! Span attrs may also carry typed values: `AttrSet` writes one TLV entry per attribute,
+//! `tag:u8 | key_len:u8 | key | value_len:u8 | value`, with tags 1 = string, 2 = u64,
+//! 3 = i64 (zigzag) and 4 = bool; integers are
+//! little-endian with high zero bytes dropped. [`decode_attrs`] walks a field entry by
+//! entry: a leading tag byte starts a TLV entry, anything else a legacy `key=value\n` line
+//! decoded as a string, so old and typed attrs may be mixed. Legacy keys must therefore not
+//! start with a byte in `0x01..=0x04`.
+
+use crate::{DecodeError, EncodeError, MAX_ATTRS_LEN, MAX_LABELS_LEN};
+
+/// Bounded labels/attributes wrapper.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BoundedFields<'a>(&'a [u8]);
+
+impl<'a> BoundedFields<'a> {
+    /// Validates and wraps bounded fields.
+    pub fn labels(data: &'a [u8]) -> Result<Self, EncodeError> {
+        if data.len() > MAX_LABELS_LEN {
+            return Err(EncodeError::OverLimit);
+        }
+        Ok(Self(data))
+    }
+
+    /// Validates and wraps bounded span attributes.
+    pub fn attrs(data: &'a [u8]) -> Result<Self, EncodeError> {
+        if data.len() > MAX_ATTRS_LEN {
+            return Err(EncodeError::OverLimit);
+        }
+        Ok(Self(data))
+    }
+
+    pub(crate) fn as_bytes(self) -> &'a [u8] {
+        self.0
+    }
+}
+
+/// Typed builder for `key=value\n` labels in a fixed `MAX_LABELS_LEN` buffer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LabelSet {
+    buf: [u8; MAX_LABELS_LEN],
+    len: usize,
+}
+
+impl Default for LabelSet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LabelSet {
+    /// Creates an empty label set.
+    pub const fn new() -> Self {
+        Self { buf: [0; MAX_LABELS_LEN], len: 0 }
+    }
+
+    /// Appends one `key=value\n` pair.
+    ///
+    /// Rejects an empty key and any `=`/`\n` inside key or value (`InvalidArgs`), and a
+    /// pair that would exceed `MAX_LABELS_LEN` (`OverLimit`). A rejected push leaves the
+    /// set unchanged.
+    pub fn push(&mut self, key: &[u8], value: &[u8]) -> Result<&mut Self, EncodeError> {
+        let separator = |b: &u8| *b == b'=' || *b == b'\n';
+        if key.is_empty() || key.iter().any(separator) || value.iter().any(separator) {
+            return Err(EncodeError::InvalidArgs);
+        }
+        let end = self.len + key.len() + value.len() + 2;
+        if end > MAX_LABELS_LEN {
+            return Err(EncodeError::OverLimit);
+        }
+        let mut pos = self.len;
+        for part in [key, b"=", value, b"\n"] {
+            self.buf[pos..pos + part.len()].copy_from_slice(part);
+            pos += part.len();
+        }
+        self.len = end;
+        Ok(self)
+    }
+
+    /// Encoded labels, ready for the wire.
+    pub fn fields(&self) -> BoundedFields<'_> {
+        BoundedFields(self.as_bytes())
+    }
+
+    /// Encoded `key=value\n` bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+
+    /// Whether no pair has been pushed.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+/// Typed attr tag: byte string.
+const ATTR_TAG_STR: u8 = 0x01;
+/// Typed attr tag: unsigned integer.
+const ATTR_TAG_U64: u8 = 0x02;
+/// Typed attr tag: signed integer (zigzag-encoded).
+const ATTR_TAG_I64: u8 = 0x03;
+/// Typed attr tag: boolean (one byte, 0 or 1).
+const ATTR_TAG_BOOL: u8 = 0x04;
+
+/// Value of one span attribute.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AttrValue<'a> {
+    /// Byte string (also every legacy `key=value` attr).
+    Str(&'a [u8]),
+    U64(u64),
+    I64(i64),
+    Bool(bool),
+}
+
+/// One decoded span attribute.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Attr<'a> {
+    pub key: &'a [u8],
+    pub value: AttrValue<'a>,
+}
+
+/// Typed builder for span attrs in a fixed `MAX_ATTRS_LEN` buffer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AttrSet {
+    buf: [u8; MAX_ATTRS_LEN],
+    len: usize,
+}
+
+impl Default for AttrSet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AttrSet {
+    /// Creates an empty attribute set.
+    pub const fn new() -> Self {
+        Self { buf: [0; MAX_ATTRS_LEN], len: 0 }
+    }
+
+    /// Appends a string attribute.
+    pub fn push_str(&mut self, key: &[u8], value: &[u8]) -> Result<&mut Self, EncodeError> {
+        self.push(ATTR_TAG_STR, key, value)
+    }
+
+    /// Appends an unsigned integer attribute.
+    pub fn push_u64(&mut self, key: &[u8], value: u64) -> Result<&mut Self, EncodeError> {
+        let bytes = value.to_le_bytes();
+        self.push(ATTR_TAG_U64, key, &bytes[..int_len(value)])
+    }
+
+    /// Appends a signed integer attribute.
+    pub fn push_i64(&mut self, key: &[u8], value: i64) -> Result<&mut Self, EncodeError> {
+        let zigzag = ((value << 1) ^ (value >> 63)) as u64;
+        let bytes = zigzag.to_le_bytes();
+        self.push(ATTR_TAG_I64, key, &bytes[..int_len(zigzag)])
+    }
+
+    /// Appends a boolean attribute.
+    pub fn push_bool(&mut self, key: &[u8], value: bool) -> Result<&mut Self, EncodeError> {
+        self.push(ATTR_TAG_BOOL, key, &[u8::from(value)])
+    }
+
+    /// Rejects an empty key, `=`/`\n` in the key, or a key/value over 255 bytes
+    /// (`InvalidArgs`), and an entry that would exceed `MAX_ATTRS_LEN` (`OverLimit`). A
+    /// rejected push leaves the set unchanged.
+    fn push(&mut self, tag: u8, key: &[u8], value: &[u8]) -> Result<&mut Self, EncodeError> {
+        if key.is_empty() || key.iter().any(|b| *b == b'=' || *b == b'\n') {
+            return Err(EncodeError::InvalidArgs);
+        }
+        let (Ok(key_len), Ok(value_len)) = (u8::try_from(key.len()), u8::try_from(value.len()))
+        else {
+            return Err(EncodeError::InvalidArgs);
+        };
+        let end = self.len + key.len() + value.len() + 3;
+        if end > MAX_ATTRS_LEN {
+            return Err(EncodeError::OverLimit);
+        }
+        let mut pos = self.len;
+        for part in [&[tag, key_len][..], key, &[value_len], value] {
+            self.buf[pos..pos + part.len()].copy_from_slice(part);
+            pos += part.len();
+        }
+        self.len = end;
+        Ok(self)
+    }
+
+    /// Encoded attrs, ready for the wire.
+    pub fn fields(&self) -> BoundedFields<'_> {
+        BoundedFields(self.as_bytes())
+    }
+
+    /// Encoded TLV bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+
+    /// Whether no attribute has been pushed.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+/// Bytes needed for `value` with high zero bytes dropped (0 for zero).
+fn int_len(value: u64) -> usize {
+    (u64::BITS - value.leading_zeros()).div_ceil(8) as usize
+}
+
+/// Decodes span attrs, typed or legacy (see the module docs); iteration ends after the
+/// first error.
+pub fn decode_attrs(bytes: &[u8]) -> impl Iterator<Item = Result<Attr<'_>, DecodeError>> + '_ {
+    AttrIter { rest: bytes }
+}
+
+/// The first typed `u64` attr named `key`, e.g. to index spans by it. Stops at a malformed
+/// entry.
+pub fn attr_u64(bytes: &[u8], key: &[u8]) -> Option<u64> {
+    decode_attrs(bytes).map_while(Result::ok).find_map(|attr| match attr.value {
+        AttrValue::U64(value) if attr.key == key => Some(value),
+        _ => None,
+    })
+}
+
+struct AttrIter<'a> {
+    rest: &'a [u8],
+}
+
+impl<'a> AttrIter<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], DecodeError> {
+        if n > self.rest.len() {
+            return Err(DecodeError::Malformed);
+        }
+        let (head, tail) = self.rest.split_at(n);
+        self.rest = tail;
+        Ok(head)
+    }
+
+    fn next_typed(&mut self, tag: u8) -> Result<Attr<'a>, DecodeError> {
+        let key_len = self.take(2)?[1] as usize;
+        let key = self.take(key_len)?;
+        let value_len = self.take(1)?[0] as usize;
+        let raw = self.take(value_len)?;
+        let int = || {
+            if raw.len() > 8 {
+                return Err(DecodeError::Malformed);
+            }
+            let mut le = [0u8; 8];
+            le[..raw.len()].copy_from_slice(raw);
+            Ok(u64::from_le_bytes(le))
+        };
+        let value = match tag {
+            ATTR_TAG_STR => AttrValue::Str(raw),
+            ATTR_TAG_U64 => AttrValue::U64(int()?),
+            ATTR_TAG_I64 => {
+                let zigzag = int()?;
+                AttrValue::I64((zigzag >> 1) as i64 ^ -((zigzag & 1) as i64))
+            }
+            _ => match raw {
+                [0] => AttrValue::Bool(false),
+                [1] => AttrValue::Bool(true),
+                _ => return Err(DecodeError::Malformed),
+            },
+        };
+        Ok(Attr { key, value })
+    }
+
+    fn next_legacy(&mut self) -> Result<Attr<'a>, DecodeError> {
+        let line_len = self.rest.iter().position(|b| *b == b'\n').unwrap_or(self.rest.len());
+        let line = self.take(line_len)?;
+        let _ = self.take(1); // the '\n', absent on an unterminated last line
+        let eq = line.iter().position(|b| *b == b'=').ok_or(DecodeError::Malformed)?;
+        if eq == 0 {
+            return Err(DecodeError::Malformed);
+        }
+        Ok(Attr { key: &line[..eq], value: AttrValue::Str(&line[eq + 1..]) })
+    }
+}
+
+impl<'a> Iterator for AttrIter<'a> {
+    type Item = Result<Attr<'a>, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let first = *self.rest.first()?;
+        let attr = match first {
+            ATTR_TAG_STR..=ATTR_TAG_BOOL => self.next_typed(first),
+            _ => self.next_legacy(),
+        };
+        if attr.is_err() {
+            self.rest = &[];
+        }
+        Some(attr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_key_value_lines() {
+        let mut labels = LabelSet::new();
+        assert!(labels.is_empty());
+        labels.push(b"svc", b"timed").unwrap().push(b"shard", b"").unwrap();
+        assert_eq!(labels.as_bytes(), b"svc=timed\nshard=\n");
+        assert_eq!(labels.fields(), BoundedFields::labels(b"svc=timed\nshard=\n").unwrap());
+    }
+
+    #[test]
+    fn test_reject_separator_injection() {
+        let mut labels = LabelSet::new();
+        labels.push(b"svc", b"timed").unwrap();
+        for (key, value) in [
+            (&b"svc\nx"[..], &b"v"[..]),
+            (b"a=b", b"v"),
+            (b"k", b"v\nevil=1"),
+            (b"k", b"="),
+            (b"", b"v"),
+        ] {
+            assert_eq!(labels.push(key, value), Err(EncodeError::InvalidArgs));
+        }
+        assert_eq!(labels.as_bytes(), b"svc=timed\n");
+    }
+
+    #[test]
+    fn test_reject_labels_over_limit() {
+        let mut labels = LabelSet::new();
+        let value = [b'v'; MAX_LABELS_LEN - 4];
+        labels.push(b"k", &value).unwrap();
+        assert_eq!(labels.as_bytes().len(), MAX_LABELS_LEN - 1);
+        assert_eq!(labels.push(b"k", b""), Err(EncodeError::OverLimit));
+        assert_eq!(labels.fields().as_bytes().len(), MAX_LABELS_LEN - 1);
+
+        let mut fresh = LabelSet::new();
+        assert_eq!(fresh.push(b"k", &[b'v'; MAX_LABELS_LEN]), Err(EncodeError::OverLimit));
+        assert!(fresh.is_empty());
+    }
+
+    fn decoded(bytes: &[u8]) -> alloc::vec::Vec<Attr<'_>> {
+        decode_attrs(bytes).collect::<Result<_, _>>().unwrap()
+    }
+
+    #[test]
+    fn typed_attrs_round_trip_every_value_type() {
+        let mut attrs = AttrSet::new();
+        attrs
+            .push_str(b"peer", b"samgrd")
+            .unwrap()
+            .push_u64(b"bytes", 0)
+            .unwrap()
+            .push_u64(b"max", u64::MAX)
+            .unwrap()
+            .push_i64(b"delta", -300)
+            .unwrap()
+            .push_i64(b"min", i64::MIN)
+            .unwrap()
+            .push_bool(b"ok", true)
+            .unwrap();
+        // Zero takes no value bytes; -300 zigzags to 599 (two bytes).
+        assert_eq!(&attrs.as_bytes()[13..21], &[ATTR_TAG_U64, 5, b'b', b'y', b't', b'e', b's', 0]);
+        let attr = |key: &'static [u8], value| Attr { key, value };
+        assert_eq!(
+            decoded(attrs.fields().as_bytes()),
+            [
+                attr(b"peer", AttrValue::Str(b"samgrd")),
+                attr(b"bytes", AttrValue::U64(0)),
+                attr(b"max", AttrValue::U64(u64::MAX)),
+                attr(b"delta", AttrValue::I64(-300)),
+                attr(b"min", AttrValue::I64(i64::MIN)),
+                attr(b"ok", AttrValue::Bool(true)),
+            ]
+        );
+        assert_eq!(attr_u64(attrs.as_bytes(), b"max"), Some(u64::MAX));
+        assert_eq!(attr_u64(attrs.as_bytes(), b"delta"), None);
+    }
+
+    #[test]
+    fn mixed_legacy_and_typed_attrs_decode() {
+        let mut typed = AttrSet::new();
+        typed.push_u64(b"len", 4096).unwrap().push_bool(b"hit", false).unwrap();
+        let mut bytes = alloc::vec::Vec::from(&b"svc=timed\nop=\n"[..]);
+        bytes.extend_from_slice(typed.as_bytes());
+        bytes.extend_from_slice(b"tail=x");
+
+        let attrs = decoded(&bytes);
+        assert_eq!(attrs[0], Attr { key: b"svc", value: AttrValue::Str(b"timed") });
+        assert_eq!(attrs[1], Attr { key: b"op", value: AttrValue::Str(b"") });
+        assert_eq!(attrs[2].value, AttrValue::U64(4096));
+        assert_eq!(attrs[3].value, AttrValue::Bool(false));
+        assert_eq!(attrs[4], Attr { key: b"tail", value: AttrValue::Str(b"x") });
+        assert_eq!(attr_u64(&bytes, b"len"), Some(4096));
+        // Legacy values are strings even when they look numeric.
+        assert_eq!(attr_u64(b"len=4096\n", b"len"), None);
+    }
+
+    #[test]
+    fn test_reject_malformed_or_oversized_attrs() {
+        let mut attrs = AttrSet::new();
+        assert_eq!(attrs.push_u64(b"a=b", 1), Err(EncodeError::InvalidArgs));
+        assert_eq!(attrs.push_str(b"", b"v"), Err(EncodeError::InvalidArgs));
+        assert_eq!(attrs.push_str(b"k", &[0; 256]), Err(EncodeError::InvalidArgs));
+        attrs.push_str(b"k", &[b'v'; MAX_ATTRS_LEN - 5]).unwrap();
+        assert_eq!(attrs.push_bool(b"b", true), Err(EncodeError::OverLimit));
+        assert_eq!(attrs.as_bytes().len(), MAX_ATTRS_LEN - 1);
+
+        // Truncated TLV, bad bool, overlong integer, and a legacy line without `=`.
+        for bad in [
+            &[ATTR_TAG_U64, 1, b'k', 4, 1][..],
+            &[ATTR_TAG_BOOL, 1, b'k', 1, 2],
+            &[ATTR_TAG_I64, 1, b'k', 9, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            b"novalue\n",
+        ] {
+            let mut iter = decode_attrs(bad);
+            assert_eq!(iter.next(), Some(Err(DecodeError::Malformed)));
+            assert_eq!(iter.next(), None);
+        }
+    }
+}
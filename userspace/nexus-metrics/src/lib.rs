@@ -43,6 +43,22 @@ pub const OP_SPAN_START: u8 = 4;
 pub const OP_SPAN_END: u8 = 5;
 /// Ping operation (liveness probe).
 pub const OP_PING: u8 = 6;
+/// Histogram percentile query (see [`encode_hist_quantile`]).
+pub const OP_HIST_QUANTILE: u8 = 7;
+/// Recently ended spans as text (see [`encode_spans_scrape`]).
+pub const OP_SPANS_SCRAPE: u8 = 8;
+/// Windowed (recent-activity) counter increment (see [`encode_windowed_inc`]).
+pub const OP_WINDOWED_INC: u8 = 9;
+/// Per-sender reject tallies for audit (see [`encode_rejections`]).
+pub const OP_REJECTIONS: u8 = 10;
+/// Gauge high-water mark: keep the greater value (see [`encode_gauge_max`]).
+pub const OP_GAUGE_MAX: u8 = 11;
+/// Gauge low-water mark: keep the lesser value (see [`encode_gauge_min`]).
+pub const OP_GAUGE_MIN: u8 = 12;
+/// Several counter/gauge/histogram updates in one frame (see [`encode_batch`]).
+pub const OP_BATCH: u8 = 13;
+/// Span start with linked (non-parent) spans (see [`encode_span_start_ex`]).
+pub const OP_SPAN_START_EX: u8 = 14;
 
 /// Response status: operation succeeded.
 pub const STATUS_OK: u8 = 0;
@@ -55,6 +71,22 @@ pub const STATUS_RATE_LIMITED: u8 = 3;
 /// Response status: requested entity was not found.
 pub const STATUS_NOT_FOUND: u8 = 4;
 
+/// Reject subcode: no further detail (also implied by a legacy 9-byte response).
+pub const REASON_NONE: u16 = 0;
+/// Reject subcode: a name/labels/attrs field exceeded its length cap.
+pub const REASON_FIELD_LEN: u16 = 1;
+/// Reject subcode: the registry-wide series cap was hit.
+pub const REASON_SERIES_TOTAL: u16 = 2;
+/// Reject subcode: the per-metric series cap was hit.
+pub const REASON_SERIES_PER_METRIC: u16 = 3;
+/// Reject subcode: the live span table is full.
+pub const REASON_LIVE_SPANS: u16 = 4;
+/// Reject subcode: the sender's rate window budget is spent.
+pub const REASON_SENDER_BUDGET: u16 = 5;
+
+const STATUS_RSP_LEN: usize = 9;
+const STATUS_RSP_EX_LEN: usize = 11;
+
 /// Maximum metric name size.
 pub const MAX_METRIC_NAME_LEN: usize = 48;
 /// Maximum labels payload size (RFC-0011 key=value\\n convention).
@@ -115,31 +147,48 @@ impl<'a> SpanName<'a> {
     }
 }
 
-/// Bounded labels/attributes wrapper.
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub struct BoundedFields<'a>(&'a [u8]);
-
-impl<'a> BoundedFields<'a> {
-    /// Validates and wraps bounded fields.
-    pub fn labels(data: &'a [u8]) -> Result<Self, EncodeError> {
-        if data.len() > MAX_LABELS_LEN {
-            return Err(EncodeError::OverLimit);
-        }
-        Ok(Self(data))
-    }
-
-    /// Validates and wraps bounded span attributes.
-    pub fn attrs(data: &'a [u8]) -> Result<Self, EncodeError> {
-        if data.len() > MAX_ATTRS_LEN {
-            return Err(EncodeError::OverLimit);
-        }
-        Ok(Self(data))
-    }
-
-    fn as_bytes(self) -> &'a [u8] {
-        self.0
-    }
-}
+mod batch;
+mod buffered;
+mod fixed_point;
+mod gauge_bound;
+mod labels;
+mod macros;
+mod ping_rtt;
+mod quantile;
+mod rejections;
+mod span_guard;
+mod span_wire;
+mod spans_scrape;
+mod windowed;
+pub use batch::{encode_batch, BatchEntries, BatchEntry, MAX_BATCH_ENTRIES};
+pub use buffered::{BatchSink, BufferedMetricsClient};
+pub use fixed_point::{
+    encode_counter_inc_milli, encode_gauge_set_milli, MetricScale, ScaledValue, MILLI_SCALE,
+    MILLI_SUFFIX,
+};
+pub use gauge_bound::{
+    decode_gauge_bound_response, encode_gauge_bound_response, encode_gauge_max, encode_gauge_min,
+    GaugeBound,
+};
+pub use labels::{attr_u64, decode_attrs, Attr, AttrSet, AttrValue, BoundedFields, LabelSet};
+pub use ping_rtt::{measure_ping_rtt, PingRttHistogram, PingRttStats, PING_RTT_BUCKETS_NS};
+pub use quantile::{
+    decode_hist_quantile_response, encode_hist_quantile, encode_hist_quantile_response,
+    is_valid_percentile,
+};
+pub use rejections::{
+    decode_rejections_response, encode_rejections, encode_rejections_response, RejectionEntry,
+    MAX_REJECTION_ENTRIES,
+};
+pub use span_guard::{SpanEndClient, SpanGuard};
+pub use span_wire::{
+    encode_span_end, encode_span_start, encode_span_start_ex, SpanLinks, MAX_SPAN_LINKS,
+};
+pub use spans_scrape::{
+    decode_spans_scrape_response, encode_spans_scrape, encode_spans_scrape_response, SpansScrape,
+    MAX_SPANS_SCRAPE_TEXT_LEN, SPANS_SCRAPE_FLAG_TRUNCATED,
+};
+pub use windowed::{encode_windowed_inc, window_len_ns, WINDOW_10S, WINDOW_1M, WINDOW_5M};
 
 /// Deterministic ID source derived from sender identity and a local monotonic counter.
 pub struct DeterministicIdSource {
@@ -173,56 +222,6 @@ fn compose_deterministic_id(sender_service_id: u64, local: u64) -> u64 {
     ((sender_service_id & 0xffff_ffff) << 32) | (local & 0xffff_ffff)
 }
 
-/// Minimal span-end client contract used by the span guard.
-pub trait SpanEndClient {
-    type Error;
-
-    fn end_span(
-        &self,
-        span_id: SpanId,
-        end_ns: u64,
-        status: u8,
-        attrs: &[u8],
-    ) -> Result<u8, Self::Error>;
-}
-
-/// RAII guard that emits `span_end` on drop unless ended explicitly.
-pub struct SpanGuard<'a, C: SpanEndClient> {
-    client: &'a C,
-    span_id: SpanId,
-    end_now: fn() -> u64,
-    closed: bool,
-}
-
-impl<'a, C: SpanEndClient> SpanGuard<'a, C> {
-    /// Creates a span guard with a deterministic end-time provider.
-    pub fn new(client: &'a C, span_id: SpanId, end_now: fn() -> u64) -> Self {
-        Self { client, span_id, end_now, closed: false }
-    }
-
-    /// Returns the guarded span id.
-    pub const fn span_id(&self) -> SpanId {
-        self.span_id
-    }
-
-    /// Ends the span explicitly and consumes the guard.
-    pub fn end(mut self, end_ns: u64, status: u8, attrs: &[u8]) -> Result<u8, C::Error> {
-        let rsp = self.client.end_span(self.span_id, end_ns, status, attrs)?;
-        self.closed = true;
-        Ok(rsp)
-    }
-}
-
-impl<C: SpanEndClient> Drop for SpanGuard<'_, C> {
-    fn drop(&mut self) {
-        if self.closed {
-            return;
-        }
-        let _ = self.client.end_span(self.span_id, (self.end_now)(), STATUS_OK, b"");
-        self.closed = true;
-    }
-}
-
 #[cfg(all(feature = "os-lite", nexus_env = "os"))]
 fn default_guard_end_ns() -> u64 {
     0
@@ -280,6 +279,17 @@ pub enum Request<'a> {
         name: &'a [u8],
         attrs: &'a [u8],
     },
+    /// SPAN_START plus causally related spans that are not the parent.
+    SpanStartEx {
+        nonce: u32,
+        span_id: SpanId,
+        trace_id: TraceId,
+        parent_span_id: SpanId,
+        start_ns: u64,
+        name: &'a [u8],
+        attrs: &'a [u8],
+        links: SpanLinks<'a>,
+    },
     SpanEnd {
         nonce: u32,
         span_id: SpanId,
@@ -290,6 +300,63 @@ pub enum Request<'a> {
     Ping {
         nonce: u32,
     },
+    /// Read-only: the caller's own histogram series, `percentile` in `1..=100`.
+    HistQuantile {
+        nonce: u32,
+        name: &'a [u8],
+        labels: &'a [u8],
+        percentile: u8,
+    },
+    /// Read-only: the daemon's recently ended spans.
+    SpansScrape {
+        nonce: u32,
+    },
+    /// Counter increment that ages out after the window named by `window_id`.
+    WindowedInc {
+        nonce: u32,
+        window_id: u8,
+        name: &'a [u8],
+        labels: &'a [u8],
+        delta: u64,
+    },
+    /// Read-only: metricsd's aggregated reject tallies.
+    Rejections {
+        nonce: u32,
+    },
+    /// GAUGE_MAX / GAUGE_MIN: move the gauge only past its current value.
+    GaugeBound {
+        nonce: u32,
+        bound: GaugeBound,
+        name: &'a [u8],
+        labels: &'a [u8],
+        value: i64,
+    },
+    /// Counter/gauge/histogram updates applied in order, as if sent one by one.
+    Batch {
+        nonce: u32,
+        entries: BatchEntries<'a>,
+    },
+}
+
+impl Request<'_> {
+    /// Opcode and nonce of the request, for replying to any variant.
+    pub const fn op_nonce(&self) -> (u8, u32) {
+        match *self {
+            Self::CounterInc { nonce, .. } => (OP_COUNTER_INC, nonce),
+            Self::GaugeSet { nonce, .. } => (OP_GAUGE_SET, nonce),
+            Self::HistObserve { nonce, .. } => (OP_HIST_OBSERVE, nonce),
+            Self::SpanStart { nonce, .. } => (OP_SPAN_START, nonce),
+            Self::SpanStartEx { nonce, .. } => (OP_SPAN_START_EX, nonce),
+            Self::SpanEnd { nonce, .. } => (OP_SPAN_END, nonce),
+            Self::Ping { nonce } => (OP_PING, nonce),
+            Self::HistQuantile { nonce, .. } => (OP_HIST_QUANTILE, nonce),
+            Self::SpansScrape { nonce } => (OP_SPANS_SCRAPE, nonce),
+            Self::WindowedInc { nonce, .. } => (OP_WINDOWED_INC, nonce),
+            Self::Rejections { nonce } => (OP_REJECTIONS, nonce),
+            Self::GaugeBound { nonce, bound, .. } => (bound.op(), nonce),
+            Self::Batch { nonce, .. } => (OP_BATCH, nonce),
+        }
+    }
 }
 
 /// Encodes a COUNTER_INC frame.
@@ -334,7 +401,7 @@ pub fn encode_hist_observe(
     )
 }
 
-fn encode_metric_value_frame(
+pub(crate) fn encode_metric_value_frame(
     op: u8,
     nonce: u32,
     name: &[u8],
@@ -358,58 +425,6 @@ fn encode_metric_value_frame(
     Ok(out)
 }
 
-/// Encodes a SPAN_START frame.
-pub fn encode_span_start(
-    nonce: u32,
-    span_id: SpanId,
-    trace_id: TraceId,
-    parent_span_id: SpanId,
-    start_ns: u64,
-    name: SpanName<'_>,
-    attrs: BoundedFields<'_>,
-) -> Result<Vec<u8>, EncodeError> {
-    let name = name.as_bytes();
-    let attrs = attrs.as_bytes();
-    if attrs.len() > MAX_ATTRS_LEN {
-        return Err(EncodeError::OverLimit);
-    }
-    let mut out = Vec::with_capacity(4 + 4 + 8 + 8 + 8 + 8 + 1 + 2 + name.len() + attrs.len());
-    out.extend_from_slice(&[MAGIC0, MAGIC1, VERSION, OP_SPAN_START]);
-    out.extend_from_slice(&nonce.to_le_bytes());
-    out.extend_from_slice(&span_id.0.to_le_bytes());
-    out.extend_from_slice(&trace_id.0.to_le_bytes());
-    out.extend_from_slice(&parent_span_id.0.to_le_bytes());
-    out.extend_from_slice(&start_ns.to_le_bytes());
-    out.push(name.len() as u8);
-    out.extend_from_slice(&(attrs.len() as u16).to_le_bytes());
-    out.extend_from_slice(name);
-    out.extend_from_slice(attrs);
-    Ok(out)
-}
-
-/// Encodes a SPAN_END frame.
-pub fn encode_span_end(
-    nonce: u32,
-    span_id: SpanId,
-    end_ns: u64,
-    status: u8,
-    attrs: BoundedFields<'_>,
-) -> Result<Vec<u8>, EncodeError> {
-    let attrs = attrs.as_bytes();
-    if attrs.len() > MAX_ATTRS_LEN {
-        return Err(EncodeError::OverLimit);
-    }
-    let mut out = Vec::with_capacity(4 + 4 + 8 + 8 + 1 + 2 + attrs.len());
-    out.extend_from_slice(&[MAGIC0, MAGIC1, VERSION, OP_SPAN_END]);
-    out.extend_from_slice(&nonce.to_le_bytes());
-    out.extend_from_slice(&span_id.0.to_le_bytes());
-    out.extend_from_slice(&end_ns.to_le_bytes());
-    out.push(status);
-    out.extend_from_slice(&(attrs.len() as u16).to_le_bytes());
-    out.extend_from_slice(attrs);
-    Ok(out)
-}
-
 /// Encodes a PING frame.
 pub fn encode_ping(nonce: u32) -> Vec<u8> {
     let mut out = Vec::with_capacity(8);
@@ -429,18 +444,21 @@ pub fn decode_request(frame: &[u8]) -> Result<Request<'_>, DecodeError> {
     let op = frame[3];
     let nonce = u32::from_le_bytes([frame[4], frame[5], frame[6], frame[7]]);
     match op {
-        OP_COUNTER_INC | OP_GAUGE_SET | OP_HIST_OBSERVE => {
+        OP_COUNTER_INC | OP_GAUGE_SET | OP_HIST_OBSERVE | OP_GAUGE_MAX | OP_GAUGE_MIN => {
             decode_metric_value(op, nonce, &frame[8..])
         }
-        OP_SPAN_START => decode_span_start(nonce, &frame[8..]),
-        OP_SPAN_END => decode_span_end(nonce, &frame[8..]),
-        OP_PING => {
-            if frame.len() != 8 {
-                Err(DecodeError::Malformed)
-            } else {
-                Ok(Request::Ping { nonce })
-            }
-        }
+        OP_SPAN_START => span_wire::decode_span_start(nonce, &frame[8..]),
+        OP_SPAN_START_EX => span_wire::decode_span_start_ex(nonce, &frame[8..]),
+        OP_SPAN_END => span_wire::decode_span_end(nonce, &frame[8..]),
+        OP_HIST_QUANTILE => quantile::decode_hist_quantile(nonce, &frame[8..]),
+        OP_WINDOWED_INC => windowed::decode_windowed_inc(nonce, &frame[8..]),
+        OP_BATCH => batch::decode_batch(nonce, &frame[8..]),
+        OP_PING | OP_SPANS_SCRAPE | OP_REJECTIONS if frame.len() != 8 => {
+            Err(DecodeError::Malformed)
+        }
+        OP_PING => Ok(Request::Ping { nonce }),
+        OP_SPANS_SCRAPE => Ok(Request::SpansScrape { nonce }),
+        OP_REJECTIONS => Ok(Request::Rejections { nonce }),
         _ => Err(DecodeError::Unsupported),
     }
 }
@@ -451,16 +469,7 @@ fn decode_metric_value(op: u8, nonce: u32, payload: &[u8]) -> Result<Request<'_>
     }
     let name_len = payload[0] as usize;
     let labels_len = u16::from_le_bytes([payload[1], payload[2]]) as usize;
-    let value = i64::from_le_bytes([
-        payload[3],
-        payload[4],
-        payload[5],
-        payload[6],
-        payload[7],
-        payload[8],
-        payload[9],
-        payload[10],
-    ]);
+    let value = read_u64_le(payload, 3) as i64;
     if name_len == 0 || name_len > MAX_METRIC_NAME_LEN || labels_len > MAX_LABELS_LEN {
         return Err(DecodeError::OverLimit);
     }
@@ -470,119 +479,67 @@ fn decode_metric_value(op: u8, nonce: u32, payload: &[u8]) -> Result<Request<'_>
     let name = &payload[11..11 + name_len];
     let labels = &payload[11 + name_len..];
     match op {
-        OP_COUNTER_INC => {
-            if value < 0 {
-                return Err(DecodeError::Malformed);
-            }
+        OP_COUNTER_INC if value >= 0 => {
             Ok(Request::CounterInc { nonce, name, labels, delta: value as u64 })
         }
         OP_GAUGE_SET => Ok(Request::GaugeSet { nonce, name, labels, value }),
-        OP_HIST_OBSERVE => {
-            if value < 0 {
-                return Err(DecodeError::Malformed);
-            }
+        OP_HIST_OBSERVE if value >= 0 => {
             Ok(Request::HistObserve { nonce, name, labels, value: value as u64 })
         }
+        OP_GAUGE_MAX | OP_GAUGE_MIN => {
+            Ok(gauge_bound::gauge_bound_request(op, nonce, name, labels, value))
+        }
+        OP_COUNTER_INC | OP_HIST_OBSERVE => Err(DecodeError::Malformed),
         _ => Err(DecodeError::Unsupported),
     }
 }
 
-fn decode_span_start(nonce: u32, payload: &[u8]) -> Result<Request<'_>, DecodeError> {
-    if payload.len() < 8 + 8 + 8 + 8 + 1 + 2 {
-        return Err(DecodeError::Malformed);
-    }
-    let span_id = SpanId(u64::from_le_bytes([
-        payload[0], payload[1], payload[2], payload[3], payload[4], payload[5], payload[6],
-        payload[7],
-    ]));
-    let trace_id = TraceId(u64::from_le_bytes([
-        payload[8],
-        payload[9],
-        payload[10],
-        payload[11],
-        payload[12],
-        payload[13],
-        payload[14],
-        payload[15],
-    ]));
-    let parent_span_id = SpanId(u64::from_le_bytes([
-        payload[16],
-        payload[17],
-        payload[18],
-        payload[19],
-        payload[20],
-        payload[21],
-        payload[22],
-        payload[23],
-    ]));
-    let start_ns = u64::from_le_bytes([
-        payload[24],
-        payload[25],
-        payload[26],
-        payload[27],
-        payload[28],
-        payload[29],
-        payload[30],
-        payload[31],
-    ]);
-    let name_len = payload[32] as usize;
-    let attrs_len = u16::from_le_bytes([payload[33], payload[34]]) as usize;
-    if name_len == 0 || name_len > MAX_SPAN_NAME_LEN || attrs_len > MAX_ATTRS_LEN {
-        return Err(DecodeError::OverLimit);
-    }
-    if payload.len() != 35 + name_len + attrs_len {
-        return Err(DecodeError::Malformed);
-    }
-    let name = &payload[35..35 + name_len];
-    let attrs = &payload[35 + name_len..];
-    Ok(Request::SpanStart { nonce, span_id, trace_id, parent_span_id, start_ns, name, attrs })
-}
-
-fn decode_span_end(nonce: u32, payload: &[u8]) -> Result<Request<'_>, DecodeError> {
-    if payload.len() < 8 + 8 + 1 + 2 {
-        return Err(DecodeError::Malformed);
-    }
-    let span_id = SpanId(u64::from_le_bytes([
-        payload[0], payload[1], payload[2], payload[3], payload[4], payload[5], payload[6],
-        payload[7],
-    ]));
-    let end_ns = u64::from_le_bytes([
-        payload[8],
-        payload[9],
-        payload[10],
-        payload[11],
-        payload[12],
-        payload[13],
-        payload[14],
-        payload[15],
-    ]);
-    let status = payload[16];
-    let attrs_len = u16::from_le_bytes([payload[17], payload[18]]) as usize;
-    if attrs_len > MAX_ATTRS_LEN {
-        return Err(DecodeError::OverLimit);
-    }
-    if payload.len() != 19 + attrs_len {
-        return Err(DecodeError::Malformed);
-    }
-    let attrs = &payload[19..];
-    Ok(Request::SpanEnd { nonce, span_id, end_ns, status, attrs })
+/// Reads a little-endian `u64` at `at`; callers have already checked the length.
+pub(crate) fn read_u64_le(payload: &[u8], at: usize) -> u64 {
+    let mut raw = [0u8; 8];
+    raw.copy_from_slice(&payload[at..at + 8]);
+    u64::from_le_bytes(raw)
 }
 
 /// Encodes a status-only response frame.
 pub fn encode_status_response(op: u8, nonce: u32, status: u8) -> Vec<u8> {
-    let mut out = Vec::with_capacity(9);
+    let mut out = Vec::with_capacity(STATUS_RSP_EX_LEN);
     out.extend_from_slice(&[MAGIC0, MAGIC1, VERSION, op | 0x80, status]);
     out.extend_from_slice(&nonce.to_le_bytes());
     out
 }
 
+/// Encodes a status response carrying a `REASON_*` subcode after the nonce.
+pub fn encode_status_response_ex(op: u8, nonce: u32, status: u8, subcode: u16) -> Vec<u8> {
+    let mut out = encode_status_response(op, nonce, status);
+    out.extend_from_slice(&subcode.to_le_bytes());
+    out
+}
+
 /// Decodes a status-only response and validates nonce/opcode.
+///
+/// Accepts both the 9-byte frame and the extended frame; the subcode is dropped.
 pub fn decode_status_response(
     frame: &[u8],
     expected_op: u8,
     expected_nonce: u32,
 ) -> Result<u8, DecodeError> {
-    if frame.len() != 9 || frame[0] != MAGIC0 || frame[1] != MAGIC1 || frame[2] != VERSION {
+    decode_status_response_ex(frame, expected_op, expected_nonce).map(|(status, _)| status)
+}
+
+/// Decodes a status response into `(status, subcode)`.
+///
+/// A legacy 9-byte frame yields [`REASON_NONE`].
+pub fn decode_status_response_ex(
+    frame: &[u8],
+    expected_op: u8,
+    expected_nonce: u32,
+) -> Result<(u8, u16), DecodeError> {
+    if (frame.len() != STATUS_RSP_LEN && frame.len() != STATUS_RSP_EX_LEN)
+        || frame[0] != MAGIC0
+        || frame[1] != MAGIC1
+        || frame[2] != VERSION
+    {
         return Err(DecodeError::Malformed);
     }
     if frame[3] != (expected_op | 0x80) {
@@ -592,7 +549,11 @@ pub fn decode_status_response(
     if nonce != expected_nonce {
         return Err(DecodeError::Malformed);
     }
-    Ok(frame[4])
+    let subcode = match frame.get(9..STATUS_RSP_EX_LEN) {
+        Some(&[lo, hi]) => u16::from_le_bytes([lo, hi]),
+        _ => REASON_NONE,
+    };
+    Ok((frame[4], subcode))
 }
 
 /// Client-side metrics IPC errors.
@@ -608,308 +569,18 @@ pub enum ClientError {
 }
 
 #[cfg(all(feature = "os-lite", nexus_env = "os"))]
-pub mod client {
-    use super::*;
-    use core::sync::atomic::{AtomicU32, Ordering};
-    use core::time::Duration;
-    use nexus_ipc::{Client as _, KernelClient, Wait};
-
-    /// OS metrics/tracing client over kernel IPC.
-    pub struct MetricsClient {
-        ipc: KernelClient,
-        next_nonce: AtomicU32,
-    }
-
-    impl MetricsClient {
-        /// Creates a client routed to `metricsd`.
-        pub fn new() -> Result<Self, ClientError> {
-            Self::new_for("metricsd")
-        }
-
-        /// Creates a client for an explicit service name.
-        pub fn new_for(service_name: &str) -> Result<Self, ClientError> {
-            let ipc = KernelClient::new_for(service_name).map_err(|_| ClientError::Transport)?;
-            Ok(Self { ipc, next_nonce: AtomicU32::new(1) })
-        }
-
-        fn nonce(&self) -> u32 {
-            self.next_nonce.fetch_add(1, Ordering::Relaxed)
-        }
-
-        /// Sends a counter increment.
-        pub fn counter_inc(
-            &self,
-            name: &str,
-            labels: &[u8],
-            delta: u64,
-        ) -> Result<u8, ClientError> {
-            let nonce = self.nonce();
-            let frame = encode_counter_inc(
-                nonce,
-                MetricName::new(name.as_bytes()).map_err(ClientError::Encode)?,
-                BoundedFields::labels(labels).map_err(ClientError::Encode)?,
-                delta,
-            )
-            .map_err(ClientError::Encode)?;
-            self.send_and_parse(OP_COUNTER_INC, nonce, &frame)
-        }
-
-        /// Sends a gauge set.
-        pub fn gauge_set(&self, name: &str, labels: &[u8], value: i64) -> Result<u8, ClientError> {
-            let nonce = self.nonce();
-            let frame = encode_gauge_set(
-                nonce,
-                MetricName::new(name.as_bytes()).map_err(ClientError::Encode)?,
-                BoundedFields::labels(labels).map_err(ClientError::Encode)?,
-                value,
-            )
-            .map_err(ClientError::Encode)?;
-            self.send_and_parse(OP_GAUGE_SET, nonce, &frame)
-        }
-
-        /// Sends a histogram observation.
-        pub fn hist_observe(
-            &self,
-            name: &str,
-            labels: &[u8],
-            value: u64,
-        ) -> Result<u8, ClientError> {
-            let nonce = self.nonce();
-            let frame = encode_hist_observe(
-                nonce,
-                MetricName::new(name.as_bytes()).map_err(ClientError::Encode)?,
-                BoundedFields::labels(labels).map_err(ClientError::Encode)?,
-                value,
-            )
-            .map_err(ClientError::Encode)?;
-            self.send_and_parse(OP_HIST_OBSERVE, nonce, &frame)
-        }
-
-        /// Sends a span start event.
-        pub fn span_start(
-            &self,
-            span_id: SpanId,
-            trace_id: TraceId,
-            parent_span_id: SpanId,
-            start_ns: u64,
-            name: &str,
-            attrs: &[u8],
-        ) -> Result<u8, ClientError> {
-            let nonce = self.nonce();
-            let frame = encode_span_start(
-                nonce,
-                span_id,
-                trace_id,
-                parent_span_id,
-                start_ns,
-                SpanName::new(name.as_bytes()).map_err(ClientError::Encode)?,
-                BoundedFields::attrs(attrs).map_err(ClientError::Encode)?,
-            )
-            .map_err(ClientError::Encode)?;
-            self.send_and_parse(OP_SPAN_START, nonce, &frame)
-        }
-
-        /// Starts a span and returns an end-on-drop guard.
-        pub fn span_guard(
-            &self,
-            ids: &mut DeterministicIdSource,
-            parent_span_id: SpanId,
-            start_ns: u64,
-            name: &str,
-            attrs: &[u8],
-        ) -> Result<SpanGuard<'_, Self>, ClientError> {
-            let span_id = ids.next_span_id();
-            let trace_id = ids.next_trace_id();
-            let status =
-                self.span_start(span_id, trace_id, parent_span_id, start_ns, name, attrs)?;
-            if status != STATUS_OK {
-                return Err(ClientError::Decode(DecodeError::Malformed));
-            }
-            Ok(SpanGuard::new(self, span_id, default_guard_end_ns))
-        }
-
-        /// Sends a span end event.
-        pub fn span_end(
-            &self,
-            span_id: SpanId,
-            end_ns: u64,
-            status: u8,
-            attrs: &[u8],
-        ) -> Result<u8, ClientError> {
-            let nonce = self.nonce();
-            let frame = encode_span_end(
-                nonce,
-                span_id,
-                end_ns,
-                status,
-                BoundedFields::attrs(attrs).map_err(ClientError::Encode)?,
-            )
-            .map_err(ClientError::Encode)?;
-            self.send_and_parse(OP_SPAN_END, nonce, &frame)
-        }
-
-        /// Sends a liveness ping.
-        pub fn ping(&self) -> Result<u8, ClientError> {
-            let nonce = self.nonce();
-            let frame = encode_ping(nonce);
-            self.send_and_parse(OP_PING, nonce, &frame)
-        }
-
-        fn send_and_parse(&self, op: u8, nonce: u32, frame: &[u8]) -> Result<u8, ClientError> {
-            self.ipc
-                .send(frame, Wait::Timeout(Duration::from_millis(500)))
-                .map_err(|_| ClientError::Transport)?;
-            let rsp = self
-                .ipc
-                .recv(Wait::Timeout(Duration::from_millis(500)))
-                .map_err(|_| ClientError::Transport)?;
-            decode_status_response(&rsp, op, nonce).map_err(ClientError::Decode)
-        }
-    }
-
-    impl SpanEndClient for MetricsClient {
-        type Error = ClientError;
-
-        fn end_span(
-            &self,
-            span_id: SpanId,
-            end_ns: u64,
-            status: u8,
-            attrs: &[u8],
-        ) -> Result<u8, Self::Error> {
-            MetricsClient::span_end(self, span_id, end_ns, status, attrs)
-        }
-    }
-}
+pub mod client;
 
 /// Host-only deterministic backend for tests.
 #[cfg(not(all(feature = "os-lite", nexus_env = "os")))]
-pub mod host {
-    use super::*;
-
-    /// Minimal host event model.
-    #[derive(Clone, Debug, PartialEq, Eq)]
-    pub enum Event {
-        Counter { name: Vec<u8>, labels: Vec<u8>, delta: u64 },
-        Gauge { name: Vec<u8>, labels: Vec<u8>, value: i64 },
-        Hist { name: Vec<u8>, labels: Vec<u8>, value: u64 },
-        SpanStart { span_id: SpanId, trace_id: TraceId, name: Vec<u8> },
-        SpanEnd { span_id: SpanId, status: u8 },
-    }
-
-    /// In-memory host backend used by host tests.
-    pub struct HostBackend {
-        events: Vec<Event>,
-    }
-
-    impl HostBackend {
-        /// Creates an empty backend.
-        pub fn new() -> Self {
-            Self { events: Vec::new() }
-        }
-
-        /// Records a counter event.
-        pub fn counter_inc(&mut self, name: &str, labels: &[u8], delta: u64) {
-            self.events.push(Event::Counter {
-                name: name.as_bytes().to_vec(),
-                labels: labels.to_vec(),
-                delta,
-            });
-        }
-
-        /// Records a gauge event.
-        pub fn gauge_set(&mut self, name: &str, labels: &[u8], value: i64) {
-            self.events.push(Event::Gauge {
-                name: name.as_bytes().to_vec(),
-                labels: labels.to_vec(),
-                value,
-            });
-        }
-
-        /// Records a histogram event.
-        pub fn hist_observe(&mut self, name: &str, labels: &[u8], value: u64) {
-            self.events.push(Event::Hist {
-                name: name.as_bytes().to_vec(),
-                labels: labels.to_vec(),
-                value,
-            });
-        }
-
-        /// Records a span start event.
-        pub fn span_start(&mut self, span_id: SpanId, trace_id: TraceId, name: &str) {
-            self.events.push(Event::SpanStart {
-                span_id,
-                trace_id,
-                name: name.as_bytes().to_vec(),
-            });
-        }
-
-        /// Records a span end event.
-        pub fn span_end(&mut self, span_id: SpanId, status: u8) {
-            self.events.push(Event::SpanEnd { span_id, status });
-        }
-
-        /// Returns immutable events.
-        pub fn events(&self) -> &[Event] {
-            &self.events
-        }
-    }
+pub mod host;
 
-    impl Default for HostBackend {
-        fn default() -> Self {
-            Self::new()
-        }
-    }
-}
-
-/// Best-effort counter macro.
-#[macro_export]
-macro_rules! metrics_counter_inc {
-    ($client:expr, $name:expr, $delta:expr) => {{
-        let _ = $client.counter_inc($name, b"", $delta);
-    }};
-    ($client:expr, $name:expr, $labels:expr, $delta:expr) => {{
-        let _ = $client.counter_inc($name, $labels, $delta);
-    }};
-}
-
-/// Best-effort gauge macro.
-#[macro_export]
-macro_rules! metrics_gauge_set {
-    ($client:expr, $name:expr, $value:expr) => {{
-        let _ = $client.gauge_set($name, b"", $value);
-    }};
-    ($client:expr, $name:expr, $labels:expr, $value:expr) => {{
-        let _ = $client.gauge_set($name, $labels, $value);
-    }};
-}
-
-/// Best-effort histogram macro.
-#[macro_export]
-macro_rules! metrics_hist_observe {
-    ($client:expr, $name:expr, $value:expr) => {{
-        let _ = $client.hist_observe($name, b"", $value);
-    }};
-    ($client:expr, $name:expr, $labels:expr, $value:expr) => {{
-        let _ = $client.hist_observe($name, $labels, $value);
-    }};
-}
-
-/// Starts an end-on-drop span guard on OS metrics clients.
-#[macro_export]
-macro_rules! metrics_span_guard_start {
-    ($client:expr, $ids:expr, $start_ns:expr, $name:expr) => {{
-        $client.span_guard($ids, $crate::SpanId(0), $start_ns, $name, b"")
-    }};
-    ($client:expr, $ids:expr, $parent_span_id:expr, $start_ns:expr, $name:expr, $attrs:expr) => {{
-        $client.span_guard($ids, $parent_span_id, $start_ns, $name, $attrs)
-    }};
-}
+mod null;
+pub use null::{MetricsSink, NullClient};
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use core::sync::atomic::{AtomicU64, AtomicU8, AtomicUsize, Ordering};
 
     fn assert_send_sync<T: Send + Sync>() {}
 
@@ -955,87 +626,45 @@ mod tests {
             3,
         )
         .unwrap();
-        let req = decode_request(&frame).unwrap();
-        match req {
-            Request::CounterInc { nonce, name, labels, delta } => {
-                assert_eq!(nonce, 7);
-                assert_eq!(name, b"sched.wakeups");
-                assert_eq!(labels, b"svc=timed\n");
-                assert_eq!(delta, 3);
-            }
-            _ => panic!("wrong request variant"),
-        }
+        let (name, labels) = (&b"sched.wakeups"[..], &b"svc=timed\n"[..]);
+        assert_eq!(
+            decode_request(&frame),
+            Ok(Request::CounterInc { nonce: 7, name, labels, delta: 3 })
+        );
     }
 
     #[test]
-    fn test_best_effort_macros_record_events_on_host_backend() {
-        let mut backend = host::HostBackend::new();
-        metrics_counter_inc!(backend, "boot.events", 1);
-        metrics_gauge_set!(backend, "sched.depth", -2);
-        metrics_hist_observe!(backend, "timed.latency", 77);
-        assert_eq!(backend.events().len(), 3);
-    }
-
-    struct FakeSpanClient {
-        calls: AtomicUsize,
-        span: AtomicU64,
-        end_ns: AtomicU64,
-        status: AtomicU8,
-    }
-
-    impl FakeSpanClient {
-        const fn new() -> Self {
-            Self {
-                calls: AtomicUsize::new(0),
-                span: AtomicU64::new(0),
-                end_ns: AtomicU64::new(0),
-                status: AtomicU8::new(0),
-            }
-        }
-    }
-
-    impl SpanEndClient for FakeSpanClient {
-        type Error = ();
-
-        fn end_span(
-            &self,
-            span_id: SpanId,
-            end_ns: u64,
-            status: u8,
-            _attrs: &[u8],
-        ) -> Result<u8, Self::Error> {
-            self.calls.fetch_add(1, Ordering::Relaxed);
-            self.span.store(span_id.0, Ordering::Relaxed);
-            self.end_ns.store(end_ns, Ordering::Relaxed);
-            self.status.store(status, Ordering::Relaxed);
-            Ok(STATUS_OK)
-        }
-    }
-
-    fn fixed_end_now() -> u64 {
-        1234
+    fn test_status_response_subcode_roundtrip() {
+        let frame = encode_status_response_ex(OP_SPAN_START, 9, STATUS_OVER_LIMIT, 0xbeef);
+        assert_eq!(
+            decode_status_response_ex(&frame, OP_SPAN_START, 9),
+            Ok((STATUS_OVER_LIMIT, 0xbeef))
+        );
+        // Legacy decoder still understands the extended frame, and vice versa.
+        assert_eq!(decode_status_response(&frame, OP_SPAN_START, 9), Ok(STATUS_OVER_LIMIT));
+        let legacy = encode_status_response(OP_PING, 3, STATUS_OK);
+        assert_eq!(decode_status_response_ex(&legacy, OP_PING, 3), Ok((STATUS_OK, REASON_NONE)));
     }
 
     #[test]
-    fn test_span_guard_drop_sends_end_once() {
-        let fake = FakeSpanClient::new();
-        {
-            let _guard = SpanGuard::new(&fake, SpanId(77), fixed_end_now);
-        }
-        assert_eq!(fake.calls.load(Ordering::Relaxed), 1);
-        assert_eq!(fake.span.load(Ordering::Relaxed), 77);
-        assert_eq!(fake.end_ns.load(Ordering::Relaxed), 1234);
-        assert_eq!(fake.status.load(Ordering::Relaxed), STATUS_OK);
+    fn test_reject_truncated_status_subcode() {
+        let frame = encode_status_response_ex(OP_PING, 1, STATUS_OK, REASON_NONE);
+        assert_eq!(
+            decode_status_response_ex(&frame[..10], OP_PING, 1),
+            Err(DecodeError::Malformed)
+        );
+        let mut long = frame.clone();
+        long.push(0);
+        assert_eq!(decode_status_response(&long, OP_PING, 1), Err(DecodeError::Malformed));
+        assert_eq!(decode_status_response_ex(&frame, OP_PING, 2), Err(DecodeError::Malformed));
     }
 
     #[test]
-    fn test_span_guard_manual_end_disarms_drop() {
-        let fake = FakeSpanClient::new();
-        let guard = SpanGuard::new(&fake, SpanId(99), fixed_end_now);
-        let _ = guard.end(777, 3, b"result=ok\n");
-        assert_eq!(fake.calls.load(Ordering::Relaxed), 1);
-        assert_eq!(fake.span.load(Ordering::Relaxed), 99);
-        assert_eq!(fake.end_ns.load(Ordering::Relaxed), 777);
-        assert_eq!(fake.status.load(Ordering::Relaxed), 3);
+    fn test_best_effort_macros_record_events_on_host_backend() {
+        let mut backend = host::HostBackend::new();
+        metrics_counter_inc!(backend, "boot.events", 1);
+        metrics_gauge_set!(backend, "sched.depth", -2);
+        metrics_hist_observe!(backend, "timed.latency", 77);
+        assert_eq!(backend.events().len(), 3);
     }
 }
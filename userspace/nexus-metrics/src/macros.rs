@@ -0,0 +1,53 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+//
+//! CONTEXT: Best-effort metrics macros (results are dropped on purpose)
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: Host unit test in the crate root (macros against `HostBackend`)
+//! ADR: docs/rfcs/RFC-0024-observability-v2-metrics-tracing-contract-v1.md
+
+/// Best-effort counter macro.
+#[macro_export]
+macro_rules! metrics_counter_inc {
+    ($client:expr, $name:expr, $delta:expr) => {{
+        let _ = $client.counter_inc($name, b"", $delta);
+    }};
+    ($client:expr, $name:expr, $labels:expr, $delta:expr) => {{
+        let _ = $client.counter_inc($name, $labels, $delta);
+    }};
+}
+
+/// Best-effort gauge macro.
+#[macro_export]
+macro_rules! metrics_gauge_set {
+    ($client:expr, $name:expr, $value:expr) => {{
+        let _ = $client.gauge_set($name, b"", $value);
+    }};
+    ($client:expr, $name:expr, $labels:expr, $value:expr) => {{
+        let _ = $client.gauge_set($name, $labels, $value);
+    }};
+}
+
+/// Best-effort histogram macro.
+#[macro_export]
+macro_rules! metrics_hist_observe {
+    ($client:expr, $name:expr, $value:expr) => {{
+        let _ = $client.hist_observe($name, b"", $value);
+    }};
+    ($client:expr, $name:expr, $labels:expr, $value:expr) => {{
+        let _ = $client.hist_observe($name, $labels, $value);
+    }};
+}
+
+/// Starts an end-on-drop span guard on OS metrics clients (or [`NullClient`]).
+#[macro_export]
+macro_rules! metrics_span_guard_start {
+    ($client:expr, $ids:expr, $start_ns:expr, $name:expr) => {{
+        $client.span_guard($ids, $crate::SpanId(0), $start_ns, $name, b"")
+    }};
+    ($client:expr, $ids:expr, $parent_span_id:expr, $start_ns:expr, $name:expr, $attrs:expr) => {{
+        $client.span_guard($ids, $parent_span_id, $start_ns, $name, $attrs)
+    }};
+}
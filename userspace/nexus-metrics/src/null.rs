@@ -0,0 +1,268 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+//
+//! CONTEXT: Client-agnostic metrics surface and the no-op `NullClient`
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: Host unit tests in this module
+//! ADR: docs/rfcs/RFC-0024-observability-v2-metrics-tracing-contract-v1.md
+//!
+//! A service that may run without metricsd keeps its instrumentation in place and
+//! picks the client at runtime: the IPC [`client::MetricsClient`] when metricsd is
+//! enabled, [`NullClient`] otherwise. Both implement [`MetricsSink`], so a
+//! `&dyn MetricsSink` works with the best-effort `metrics_*!` macros.
+//!
+//! INVARIANTS:
+//! - `NullClient` holds no state, performs no IPC and never allocates
+//! - Every `NullClient` call answers `Ok(STATUS_OK)`, without validating its input
+//!
+//! [`client::MetricsClient`]: crate::client
+
+use super::*;
+
+/// The fire-and-forget metrics/tracing calls shared by every client.
+pub trait MetricsSink {
+    /// Increments a counter.
+    fn counter_inc(&self, name: &str, labels: &[u8], delta: u64) -> Result<u8, ClientError>;
+
+    /// Sets a gauge.
+    fn gauge_set(&self, name: &str, labels: &[u8], value: i64) -> Result<u8, ClientError>;
+
+    /// Records a histogram observation.
+    fn hist_observe(&self, name: &str, labels: &[u8], value: u64) -> Result<u8, ClientError>;
+
+    /// Emits a span start event.
+    fn span_start(
+        &self,
+        span_id: SpanId,
+        trace_id: TraceId,
+        parent_span_id: SpanId,
+        start_ns: u64,
+        name: &str,
+        attrs: &[u8],
+    ) -> Result<u8, ClientError>;
+
+    /// Emits a span end event.
+    fn span_end(
+        &self,
+        span_id: SpanId,
+        end_ns: u64,
+        status: u8,
+        attrs: &[u8],
+    ) -> Result<u8, ClientError>;
+
+    /// Liveness probe.
+    fn ping(&self) -> Result<u8, ClientError>;
+}
+
+/// A borrowed client is a sink too, so wrappers such as
+/// [`BufferedMetricsClient`](crate::BufferedMetricsClient) can share one.
+impl<S: MetricsSink + ?Sized> MetricsSink for &S {
+    fn counter_inc(&self, name: &str, labels: &[u8], delta: u64) -> Result<u8, ClientError> {
+        (**self).counter_inc(name, labels, delta)
+    }
+
+    fn gauge_set(&self, name: &str, labels: &[u8], value: i64) -> Result<u8, ClientError> {
+        (**self).gauge_set(name, labels, value)
+    }
+
+    fn hist_observe(&self, name: &str, labels: &[u8], value: u64) -> Result<u8, ClientError> {
+        (**self).hist_observe(name, labels, value)
+    }
+
+    fn span_start(
+        &self,
+        span_id: SpanId,
+        trace_id: TraceId,
+        parent_span_id: SpanId,
+        start_ns: u64,
+        name: &str,
+        attrs: &[u8],
+    ) -> Result<u8, ClientError> {
+        (**self).span_start(span_id, trace_id, parent_span_id, start_ns, name, attrs)
+    }
+
+    fn span_end(
+        &self,
+        span_id: SpanId,
+        end_ns: u64,
+        status: u8,
+        attrs: &[u8],
+    ) -> Result<u8, ClientError> {
+        (**self).span_end(span_id, end_ns, status, attrs)
+    }
+
+    fn ping(&self) -> Result<u8, ClientError> {
+        (**self).ping()
+    }
+}
+
+/// Metrics client that discards everything (instrumentation compiled in, metricsd off).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NullClient;
+
+impl NullClient {
+    /// Discards a counter increment.
+    pub fn counter_inc(&self, _name: &str, _labels: &[u8], _delta: u64) -> Result<u8, ClientError> {
+        Ok(STATUS_OK)
+    }
+
+    /// Discards a gauge set.
+    pub fn gauge_set(&self, _name: &str, _labels: &[u8], _value: i64) -> Result<u8, ClientError> {
+        Ok(STATUS_OK)
+    }
+
+    /// Discards a histogram observation.
+    pub fn hist_observe(
+        &self,
+        _name: &str,
+        _labels: &[u8],
+        _value: u64,
+    ) -> Result<u8, ClientError> {
+        Ok(STATUS_OK)
+    }
+
+    /// Discards a span start event.
+    pub fn span_start(
+        &self,
+        _span_id: SpanId,
+        _trace_id: TraceId,
+        _parent_span_id: SpanId,
+        _start_ns: u64,
+        _name: &str,
+        _attrs: &[u8],
+    ) -> Result<u8, ClientError> {
+        Ok(STATUS_OK)
+    }
+
+    /// Returns a span guard whose end is discarded too.
+    ///
+    /// IDs are still drawn from `ids`, so the sequence matches a real client's.
+    pub fn span_guard(
+        &self,
+        ids: &mut DeterministicIdSource,
+        _parent_span_id: SpanId,
+        _start_ns: u64,
+        _name: &str,
+        _attrs: &[u8],
+    ) -> Result<SpanGuard<'_, Self>, ClientError> {
+        let span_id = ids.next_span_id();
+        let _ = ids.next_trace_id();
+        Ok(SpanGuard::new(self, span_id, || 0))
+    }
+
+    /// Discards a span end event.
+    pub fn span_end(
+        &self,
+        _span_id: SpanId,
+        _end_ns: u64,
+        _status: u8,
+        _attrs: &[u8],
+    ) -> Result<u8, ClientError> {
+        Ok(STATUS_OK)
+    }
+
+    /// Answers a ping without a round trip.
+    pub fn ping(&self) -> Result<u8, ClientError> {
+        Ok(STATUS_OK)
+    }
+}
+
+impl MetricsSink for NullClient {
+    fn counter_inc(&self, name: &str, labels: &[u8], delta: u64) -> Result<u8, ClientError> {
+        NullClient::counter_inc(self, name, labels, delta)
+    }
+
+    fn gauge_set(&self, name: &str, labels: &[u8], value: i64) -> Result<u8, ClientError> {
+        NullClient::gauge_set(self, name, labels, value)
+    }
+
+    fn hist_observe(&self, name: &str, labels: &[u8], value: u64) -> Result<u8, ClientError> {
+        NullClient::hist_observe(self, name, labels, value)
+    }
+
+    fn span_start(
+        &self,
+        span_id: SpanId,
+        trace_id: TraceId,
+        parent_span_id: SpanId,
+        start_ns: u64,
+        name: &str,
+        attrs: &[u8],
+    ) -> Result<u8, ClientError> {
+        NullClient::span_start(self, span_id, trace_id, parent_span_id, start_ns, name, attrs)
+    }
+
+    fn span_end(
+        &self,
+        span_id: SpanId,
+        end_ns: u64,
+        status: u8,
+        attrs: &[u8],
+    ) -> Result<u8, ClientError> {
+        NullClient::span_end(self, span_id, end_ns, status, attrs)
+    }
+
+    fn ping(&self) -> Result<u8, ClientError> {
+        NullClient::ping(self)
+    }
+}
+
+impl BatchSink for NullClient {
+    fn send_batch(&self, _entries: &[BatchEntry<'_>]) -> Result<u8, ClientError> {
+        Ok(STATUS_OK)
+    }
+}
+
+impl SpanEndClient for NullClient {
+    type Error = ClientError;
+
+    fn end_span(
+        &self,
+        span_id: SpanId,
+        end_ns: u64,
+        status: u8,
+        attrs: &[u8],
+    ) -> Result<u8, Self::Error> {
+        NullClient::span_end(self, span_id, end_ns, status, attrs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn null_client_records_nothing_and_answers_ok() {
+        assert_eq!(core::mem::size_of::<NullClient>(), 0);
+        let null = NullClient;
+        crate::metrics_counter_inc!(null, "boot.events", 1);
+        crate::metrics_gauge_set!(null, "mem.free", b"zone=dma\n", -4);
+        crate::metrics_hist_observe!(null, "ipc.latency_ns", 1_500);
+        assert_eq!(null.ping(), Ok(STATUS_OK));
+
+        let mut ids = DeterministicIdSource::new(7);
+        let guard = crate::metrics_span_guard_start!(null, &mut ids, 10, "boot").unwrap();
+        assert_eq!(guard.span_id(), DeterministicIdSource::new(7).next_span_id());
+        assert_eq!(guard.end(20, STATUS_OK, b""), Ok(STATUS_OK));
+    }
+
+    #[test]
+    fn null_client_never_errors_on_over_limit_input() {
+        // Chosen at runtime: the macros expand the same against a trait object.
+        let sink: &dyn MetricsSink = &NullClient;
+        let name = core::str::from_utf8(&[b'n'; MAX_METRIC_NAME_LEN + 1]).unwrap_or("");
+        let labels = [b'l'; MAX_LABELS_LEN + 1];
+        assert_eq!(sink.counter_inc(name, &labels, u64::MAX), Ok(STATUS_OK));
+        assert_eq!(sink.gauge_set(name, &labels, i64::MIN), Ok(STATUS_OK));
+        assert_eq!(sink.hist_observe(name, &labels, 0), Ok(STATUS_OK));
+        assert_eq!(
+            sink.span_start(SpanId(1), TraceId(1), SpanId(0), 0, name, &labels),
+            Ok(STATUS_OK)
+        );
+        assert_eq!(sink.span_end(SpanId(1), 0, STATUS_INVALID_ARGS, &labels), Ok(STATUS_OK));
+        assert_eq!(sink.ping(), Ok(STATUS_OK));
+        crate::metrics_counter_inc!(sink, name, 1);
+    }
+}
@@ -0,0 +1,159 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+//
+//! CONTEXT: Ping round-trip-time measurement for metricsd liveness SLOs
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: Host unit tests in this module (fake transport + mock clock)
+//! ADR: docs/rfcs/RFC-0024-observability-v2-metrics-tracing-contract-v1.md
+//!
+//! `MetricsClient::ping_rtt` timestamps an `OP_PING` right before the send and right after
+//! the reply, and folds the difference into a client-local [`PingRttHistogram`] so a
+//! service can alert on metricsd reply latency without extra IPC. The clock is a plain
+//! `fn() -> u64` so host tests can inject a mock; the server side is a regular ping.
+//!
+//! INVARIANTS:
+//! - Only replies that decode as `STATUS_OK` for the sent nonce are measured and recorded
+//! - The histogram is lock-free (atomics) and fixed-size
+
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use super::*;
+
+/// Upper bounds (inclusive) of the ping RTT buckets; a final bucket takes the rest.
+pub const PING_RTT_BUCKETS_NS: [u64; 4] = [100_000, 1_000_000, 10_000_000, 100_000_000];
+
+/// Point-in-time copy of a [`PingRttHistogram`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PingRttStats {
+    /// Per-bucket counts; index `i < 4` is `<= PING_RTT_BUCKETS_NS[i]`, index 4 is above.
+    pub buckets: [u64; PING_RTT_BUCKETS_NS.len() + 1],
+    /// Measured pings.
+    pub count: u64,
+    /// Sum of all measured RTTs in nanoseconds (saturating).
+    pub sum_ns: u64,
+}
+
+/// Client-local histogram of ping round-trip times.
+#[derive(Debug, Default)]
+pub struct PingRttHistogram {
+    buckets: [AtomicU64; PING_RTT_BUCKETS_NS.len() + 1],
+    count: AtomicU64,
+    sum_ns: AtomicU64,
+}
+
+impl PingRttHistogram {
+    /// Creates an empty histogram.
+    pub const fn new() -> Self {
+        Self {
+            buckets: [
+                AtomicU64::new(0),
+                AtomicU64::new(0),
+                AtomicU64::new(0),
+                AtomicU64::new(0),
+                AtomicU64::new(0),
+            ],
+            count: AtomicU64::new(0),
+            sum_ns: AtomicU64::new(0),
+        }
+    }
+
+    /// Records one RTT sample.
+    pub fn observe(&self, rtt_ns: u64) {
+        let idx = PING_RTT_BUCKETS_NS.partition_point(|&bound| bound < rtt_ns);
+        self.buckets[idx].fetch_add(1, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+        let _ = self.sum_ns.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |sum| {
+            Some(sum.saturating_add(rtt_ns))
+        });
+    }
+
+    /// Copies the current counts.
+    pub fn stats(&self) -> PingRttStats {
+        PingRttStats {
+            buckets: core::array::from_fn(|idx| self.buckets[idx].load(Ordering::Relaxed)),
+            count: self.count.load(Ordering::Relaxed),
+            sum_ns: self.sum_ns.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Sends a ping through `roundtrip` and returns its RTT, recording it in `hist`.
+///
+/// `now_ns` is read immediately before `roundtrip` and immediately after it returns. A
+/// transport error, a reply for another nonce or a non-OK status is returned as an error
+/// and not recorded.
+pub fn measure_ping_rtt<F>(
+    nonce: u32,
+    now_ns: fn() -> u64,
+    hist: &PingRttHistogram,
+    roundtrip: F,
+) -> Result<u64, ClientError>
+where
+    F: FnOnce(&[u8]) -> Result<Vec<u8>, ClientError>,
+{
+    let frame = encode_ping(nonce);
+    let sent_ns = now_ns();
+    let rsp = roundtrip(&frame)?;
+    let rtt_ns = now_ns().saturating_sub(sent_ns);
+    if decode_status_response(&rsp, OP_PING, nonce).map_err(ClientError::Decode)? != STATUS_OK {
+        return Err(ClientError::Decode(DecodeError::Malformed));
+    }
+    hist.observe(rtt_ns);
+    Ok(rtt_ns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static CLOCK_NS: AtomicU64 = AtomicU64::new(0);
+    const SIMULATED_DELAY_NS: u64 = 2_500_000;
+
+    fn mock_now() -> u64 {
+        CLOCK_NS.load(Ordering::SeqCst)
+    }
+
+    /// Fake metricsd: answers the ping after advancing the mock clock by the delay.
+    fn fake_transport(frame: &[u8]) -> Result<Vec<u8>, ClientError> {
+        let Ok(Request::Ping { nonce }) = decode_request(frame) else {
+            return Err(ClientError::Transport);
+        };
+        CLOCK_NS.fetch_add(SIMULATED_DELAY_NS, Ordering::SeqCst);
+        Ok(encode_status_response(OP_PING, nonce, STATUS_OK))
+    }
+
+    #[test]
+    fn ping_rtt_equals_simulated_delay() {
+        let hist = PingRttHistogram::new();
+        CLOCK_NS.store(1_000, Ordering::SeqCst);
+        assert_eq!(measure_ping_rtt(7, mock_now, &hist, fake_transport), Ok(SIMULATED_DELAY_NS));
+        assert_eq!(measure_ping_rtt(8, mock_now, &hist, fake_transport), Ok(SIMULATED_DELAY_NS));
+        hist.observe(50_000);
+        hist.observe(u64::MAX);
+
+        let stats = hist.stats();
+        assert_eq!(stats.count, 4);
+        assert_eq!(stats.buckets, [1, 0, 2, 0, 1]);
+        assert_eq!(stats.sum_ns, u64::MAX);
+    }
+
+    #[test]
+    fn test_reject_mismatched_or_failed_ping_reply() {
+        let hist = PingRttHistogram::new();
+        let stale = |_: &[u8]| Ok(encode_status_response(OP_PING, 1, STATUS_OK));
+        assert_eq!(
+            measure_ping_rtt(2, mock_now, &hist, stale),
+            Err(ClientError::Decode(DecodeError::Malformed))
+        );
+        let limited = |_: &[u8]| Ok(encode_status_response(OP_PING, 3, STATUS_RATE_LIMITED));
+        assert_eq!(
+            measure_ping_rtt(3, mock_now, &hist, limited),
+            Err(ClientError::Decode(DecodeError::Malformed))
+        );
+        let down = |_: &[u8]| Err(ClientError::Transport);
+        assert_eq!(measure_ping_rtt(4, mock_now, &hist, down), Err(ClientError::Transport));
+        assert_eq!(hist.stats(), PingRttStats::default());
+    }
+}
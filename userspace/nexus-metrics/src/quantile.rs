@@ -0,0 +1,171 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+//
+//! CONTEXT: HIST_QUANTILE wire — one percentile of one histogram series, no registry export
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: Host unit tests in this module
+//! ADR: docs/rfcs/RFC-0024-observability-v2-metrics-tracing-contract-v1.md
+//!
+//! Request: `MAGIC0 MAGIC1 VERSION OP_HIST_QUANTILE | nonce:u32 | percentile:u8 |
+//! name_len:u8 | labels_len:u16 | name | labels`. The series is looked up under the
+//! caller's own kernel sender identity.
+//!
+//! Response: the 9-byte status frame; with `STATUS_OK` it is followed by the value as
+//! `u64` LE. Buckets are coarse, so the value is the upper bound of the bucket that
+//! holds the percentile (`u64::MAX` for the overflow bucket), not an interpolation.
+
+use alloc::vec::Vec;
+
+use crate::{
+    decode_status_response, encode_status_response, BoundedFields, DecodeError, EncodeError,
+    MetricName, Request, MAGIC0, MAGIC1, MAX_LABELS_LEN, MAX_METRIC_NAME_LEN, OP_HIST_QUANTILE,
+    STATUS_OK, VERSION,
+};
+
+/// Status frame (9 bytes) plus the `u64` value.
+const QUANTILE_RSP_LEN: usize = 17;
+
+/// Whether `percentile` is a valid query (`1..=100`).
+pub const fn is_valid_percentile(percentile: u8) -> bool {
+    matches!(percentile, 1..=100)
+}
+
+/// Encodes a HIST_QUANTILE query frame; `percentile` must be in `1..=100`.
+pub fn encode_hist_quantile(
+    nonce: u32,
+    name: MetricName<'_>,
+    labels: BoundedFields<'_>,
+    percentile: u8,
+) -> Result<Vec<u8>, EncodeError> {
+    if !is_valid_percentile(percentile) {
+        return Err(EncodeError::InvalidArgs);
+    }
+    let (name, labels) = (name.as_bytes(), labels.as_bytes());
+    if labels.len() > MAX_LABELS_LEN {
+        return Err(EncodeError::OverLimit);
+    }
+    let mut out = Vec::with_capacity(4 + 4 + 1 + 1 + 2 + name.len() + labels.len());
+    out.extend_from_slice(&[MAGIC0, MAGIC1, VERSION, OP_HIST_QUANTILE]);
+    out.extend_from_slice(&nonce.to_le_bytes());
+    out.push(percentile);
+    out.push(name.len() as u8);
+    out.extend_from_slice(&(labels.len() as u16).to_le_bytes());
+    out.extend_from_slice(name);
+    out.extend_from_slice(labels);
+    Ok(out)
+}
+
+pub(crate) fn decode_hist_quantile(nonce: u32, payload: &[u8]) -> Result<Request<'_>, DecodeError> {
+    let [percentile, name_len, l0, l1, rest @ ..] = payload else {
+        return Err(DecodeError::Malformed);
+    };
+    let (name_len, labels_len) = (*name_len as usize, u16::from_le_bytes([*l0, *l1]) as usize);
+    if !is_valid_percentile(*percentile) {
+        return Err(DecodeError::Malformed);
+    }
+    if name_len == 0 || name_len > MAX_METRIC_NAME_LEN || labels_len > MAX_LABELS_LEN {
+        return Err(DecodeError::OverLimit);
+    }
+    if rest.len() != name_len + labels_len {
+        return Err(DecodeError::Malformed);
+    }
+    let (name, labels) = rest.split_at(name_len);
+    Ok(Request::HistQuantile { nonce, name, labels, percentile: *percentile })
+}
+
+/// Encodes a HIST_QUANTILE response; `value` is only sent with [`STATUS_OK`].
+pub fn encode_hist_quantile_response(nonce: u32, status: u8, value: u64) -> Vec<u8> {
+    let mut out = encode_status_response(OP_HIST_QUANTILE, nonce, status);
+    if status == STATUS_OK {
+        out.extend_from_slice(&value.to_le_bytes());
+    }
+    out
+}
+
+/// Decodes a HIST_QUANTILE response into `(status, value)`.
+///
+/// `value` is `Some` exactly when `status` is [`STATUS_OK`]; reject frames (with or
+/// without a reason subcode) decode to `(status, None)`.
+pub fn decode_hist_quantile_response(
+    frame: &[u8],
+    expected_nonce: u32,
+) -> Result<(u8, Option<u64>), DecodeError> {
+    if frame.len() != QUANTILE_RSP_LEN {
+        return match decode_status_response(frame, OP_HIST_QUANTILE, expected_nonce)? {
+            STATUS_OK => Err(DecodeError::Malformed),
+            status => Ok((status, None)),
+        };
+    }
+    let (head, value) = frame.split_at(QUANTILE_RSP_LEN - 8);
+    let status = decode_status_response(head, OP_HIST_QUANTILE, expected_nonce)?;
+    if status != STATUS_OK {
+        return Err(DecodeError::Malformed);
+    }
+    let mut raw = [0u8; 8];
+    raw.copy_from_slice(value);
+    Ok((status, Some(u64::from_le_bytes(raw))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{decode_request, encode_status_response_ex, REASON_SENDER_BUDGET};
+    use crate::{STATUS_NOT_FOUND, STATUS_RATE_LIMITED};
+
+    fn query(percentile: u8) -> Result<Vec<u8>, EncodeError> {
+        let name = MetricName::new(b"ipc.latency").unwrap();
+        encode_hist_quantile(9, name, BoundedFields::labels(b"svc=a\n").unwrap(), percentile)
+    }
+
+    #[test]
+    fn quantile_query_round_trips() {
+        let frame = query(90).unwrap();
+        assert_eq!(
+            decode_request(&frame),
+            Ok(Request::HistQuantile {
+                nonce: 9,
+                name: b"ipc.latency",
+                labels: b"svc=a\n",
+                percentile: 90
+            })
+        );
+        assert_eq!(decode_request(&frame).map(|req| req.op_nonce()), Ok((OP_HIST_QUANTILE, 9)));
+
+        let rsp = encode_hist_quantile_response(9, STATUS_OK, 5_000_000);
+        assert_eq!(decode_hist_quantile_response(&rsp, 9), Ok((STATUS_OK, Some(5_000_000))));
+        let rsp = encode_hist_quantile_response(9, STATUS_NOT_FOUND, 0);
+        assert_eq!(decode_hist_quantile_response(&rsp, 9), Ok((STATUS_NOT_FOUND, None)));
+        let limited = encode_status_response_ex(
+            OP_HIST_QUANTILE,
+            9,
+            STATUS_RATE_LIMITED,
+            REASON_SENDER_BUDGET,
+        );
+        assert_eq!(decode_hist_quantile_response(&limited, 9), Ok((STATUS_RATE_LIMITED, None)));
+    }
+
+    #[test]
+    fn test_reject_out_of_range_percentile() {
+        assert_eq!(query(0), Err(EncodeError::InvalidArgs));
+        assert_eq!(query(101), Err(EncodeError::InvalidArgs));
+        assert!(query(1).is_ok() && query(100).is_ok());
+
+        let mut frame = query(50).unwrap();
+        frame[8] = 0;
+        assert_eq!(decode_request(&frame), Err(DecodeError::Malformed));
+        frame[8] = 101;
+        assert_eq!(decode_request(&frame), Err(DecodeError::Malformed));
+        frame[8] = 50;
+        frame.push(0);
+        assert_eq!(decode_request(&frame), Err(DecodeError::Malformed));
+
+        // An OK status without the value, or a value on a reject, is malformed.
+        let bare_ok = encode_status_response(OP_HIST_QUANTILE, 9, STATUS_OK);
+        assert_eq!(decode_hist_quantile_response(&bare_ok, 9), Err(DecodeError::Malformed));
+        let mut bad = encode_status_response(OP_HIST_QUANTILE, 9, STATUS_NOT_FOUND);
+        bad.extend_from_slice(&7u64.to_le_bytes());
+        assert_eq!(decode_hist_quantile_response(&bad, 9), Err(DecodeError::Malformed));
+    }
+}
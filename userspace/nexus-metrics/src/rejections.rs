@@ -0,0 +1,166 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+//
+//! CONTEXT: REJECTIONS wire — metricsd's per-sender reject tallies for audit
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: Host unit tests in this module
+//! ADR: docs/rfcs/RFC-0024-observability-v2-metrics-tracing-contract-v1.md
+//!
+//! Request: `MAGIC0 MAGIC1 VERSION OP_REJECTIONS | nonce:u32` (no payload).
+//!
+//! Response: the 9-byte status frame; with `STATUS_OK` it is followed by `count:u8` and
+//! `count` entries of `sender_service_id:u64 | op:u8 | status:u8 | subcode:u16 | count:u32`
+//! (little-endian, 16 bytes each), at most [`MAX_REJECTION_ENTRIES`]. The reject reason is
+//! carried as the same `STATUS_*` / `REASON_*` pair a rejected sender was answered with.
+
+use alloc::vec::Vec;
+
+use crate::{
+    decode_status_response, encode_status_response, DecodeError, OP_REJECTIONS, STATUS_OK,
+};
+
+/// Most entries one REJECTIONS response carries (the size of metricsd's log).
+pub const MAX_REJECTION_ENTRIES: usize = 16;
+
+const ENTRY_LEN: usize = 16;
+/// Status frame (9 bytes) plus `count:u8`.
+const HEAD_LEN: usize = 10;
+
+/// One aggregated reject tally: `count` rejects of one sender for one reason.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RejectionEntry {
+    /// Kernel-reported identity of the rejected sender.
+    pub sender_service_id: u64,
+    /// Opcode of the most recent rejected frame.
+    pub op: u8,
+    /// Reject status (`STATUS_*`).
+    pub status: u8,
+    /// Reject subcode (`REASON_*`).
+    pub subcode: u16,
+    /// Rejects folded into this entry (saturating).
+    pub count: u32,
+}
+
+/// Encodes a REJECTIONS request frame.
+pub fn encode_rejections(nonce: u32) -> Vec<u8> {
+    let mut out = Vec::with_capacity(8);
+    out.extend_from_slice(&[crate::MAGIC0, crate::MAGIC1, crate::VERSION, OP_REJECTIONS]);
+    out.extend_from_slice(&nonce.to_le_bytes());
+    out
+}
+
+/// Encodes a successful REJECTIONS response; entries past the cap are left out.
+pub fn encode_rejections_response(nonce: u32, entries: &[RejectionEntry]) -> Vec<u8> {
+    let entries = &entries[..entries.len().min(MAX_REJECTION_ENTRIES)];
+    let mut out = encode_status_response(OP_REJECTIONS, nonce, STATUS_OK);
+    out.push(entries.len() as u8);
+    for entry in entries {
+        out.extend_from_slice(&entry.sender_service_id.to_le_bytes());
+        out.extend_from_slice(&[entry.op, entry.status]);
+        out.extend_from_slice(&entry.subcode.to_le_bytes());
+        out.extend_from_slice(&entry.count.to_le_bytes());
+    }
+    out
+}
+
+/// Decodes a REJECTIONS response into `(status, entries)`.
+///
+/// Reject frames decode to `(status, [])`; an OK frame must carry the entry list.
+pub fn decode_rejections_response(
+    frame: &[u8],
+    expected_nonce: u32,
+) -> Result<(u8, Vec<RejectionEntry>), DecodeError> {
+    // An OK body is `HEAD_LEN + 16 * n` bytes, so 9 and 11 bytes are plain status frames.
+    if frame.len() == crate::STATUS_RSP_LEN || frame.len() == crate::STATUS_RSP_EX_LEN {
+        return match decode_status_response(frame, OP_REJECTIONS, expected_nonce)? {
+            STATUS_OK => Err(DecodeError::Malformed),
+            status => Ok((status, Vec::new())),
+        };
+    }
+    if frame.len() < HEAD_LEN {
+        return Err(DecodeError::Malformed);
+    }
+    let (head, rest) = frame.split_at(HEAD_LEN - 1);
+    let status = decode_status_response(head, OP_REJECTIONS, expected_nonce)?;
+    if status != STATUS_OK {
+        return Err(DecodeError::Malformed);
+    }
+    let [count, body @ ..] = rest else {
+        return Err(DecodeError::Malformed);
+    };
+    let count = *count as usize;
+    if count > MAX_REJECTION_ENTRIES || body.len() != count * ENTRY_LEN {
+        return Err(DecodeError::Malformed);
+    }
+    let entries = body
+        .chunks_exact(ENTRY_LEN)
+        .map(|raw| RejectionEntry {
+            sender_service_id: crate::read_u64_le(raw, 0),
+            op: raw[8],
+            status: raw[9],
+            subcode: u16::from_le_bytes([raw[10], raw[11]]),
+            count: u32::from_le_bytes([raw[12], raw[13], raw[14], raw[15]]),
+        })
+        .collect();
+    Ok((status, entries))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        decode_request, encode_status_response_ex, Request, OP_COUNTER_INC, REASON_SENDER_BUDGET,
+        STATUS_RATE_LIMITED,
+    };
+
+    fn entry(sender_service_id: u64, count: u32) -> RejectionEntry {
+        RejectionEntry {
+            sender_service_id,
+            op: OP_COUNTER_INC,
+            status: STATUS_RATE_LIMITED,
+            subcode: REASON_SENDER_BUDGET,
+            count,
+        }
+    }
+
+    #[test]
+    fn rejections_round_trip() {
+        let frame = encode_rejections(7);
+        assert_eq!(decode_request(&frame), Ok(Request::Rejections { nonce: 7 }));
+        assert_eq!(decode_request(&frame).map(|req| req.op_nonce()), Ok((OP_REJECTIONS, 7)));
+
+        let entries = [entry(0x51, 3), entry(u64::MAX, u32::MAX)];
+        let rsp = encode_rejections_response(7, &entries);
+        assert_eq!(rsp.len(), HEAD_LEN + 2 * ENTRY_LEN);
+        assert_eq!(decode_rejections_response(&rsp, 7), Ok((STATUS_OK, entries.to_vec())));
+        let empty = encode_rejections_response(7, &[]);
+        assert_eq!(decode_rejections_response(&empty, 7), Ok((STATUS_OK, Vec::new())));
+        let limited =
+            encode_status_response_ex(OP_REJECTIONS, 7, STATUS_RATE_LIMITED, REASON_SENDER_BUDGET);
+        assert_eq!(decode_rejections_response(&limited, 7), Ok((STATUS_RATE_LIMITED, Vec::new())));
+    }
+
+    #[test]
+    fn test_reject_oversized_or_inconsistent_rejections() {
+        let many: Vec<_> = (0..MAX_REJECTION_ENTRIES as u64 + 4).map(|id| entry(id, 1)).collect();
+        let rsp = encode_rejections_response(1, &many);
+        let (_, entries) = decode_rejections_response(&rsp, 1).unwrap();
+        assert_eq!(entries, many[..MAX_REJECTION_ENTRIES]);
+
+        let mut frame = encode_rejections(1);
+        frame.push(0);
+        assert_eq!(decode_request(&frame), Err(DecodeError::Malformed));
+
+        let mut short = encode_rejections_response(1, &[entry(1, 1)]);
+        short.pop();
+        assert_eq!(decode_rejections_response(&short, 1), Err(DecodeError::Malformed));
+        let mut over = encode_rejections_response(1, &[]);
+        over[HEAD_LEN - 1] = MAX_REJECTION_ENTRIES as u8 + 1;
+        assert_eq!(decode_rejections_response(&over, 1), Err(DecodeError::Malformed));
+        let bare_ok = encode_status_response(OP_REJECTIONS, 1, STATUS_OK);
+        assert_eq!(decode_rejections_response(&bare_ok, 1), Err(DecodeError::Malformed));
+        assert_eq!(decode_rejections_response(&rsp, 2), Err(DecodeError::Malformed));
+    }
+}
@@ -0,0 +1,133 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+//
+//! CONTEXT: End-on-drop span guard (`SpanGuard`) over the `SpanEndClient` contract
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: Host unit tests in this module
+//! ADR: docs/rfcs/RFC-0024-observability-v2-metrics-tracing-contract-v1.md
+//!
+//! A guard sends `span_end` exactly once: explicitly through [`SpanGuard::end`], or on drop
+//! with status `STATUS_OK`, no attributes and the end time from its `end_now` provider.
+
+use crate::{SpanId, STATUS_OK};
+
+/// Minimal span-end client contract used by the span guard.
+pub trait SpanEndClient {
+    type Error;
+
+    fn end_span(
+        &self,
+        span_id: SpanId,
+        end_ns: u64,
+        status: u8,
+        attrs: &[u8],
+    ) -> Result<u8, Self::Error>;
+}
+
+/// RAII guard that emits `span_end` on drop unless ended explicitly.
+pub struct SpanGuard<'a, C: SpanEndClient> {
+    client: &'a C,
+    span_id: SpanId,
+    end_now: fn() -> u64,
+    closed: bool,
+}
+
+impl<'a, C: SpanEndClient> SpanGuard<'a, C> {
+    /// Creates a span guard with a deterministic end-time provider.
+    pub fn new(client: &'a C, span_id: SpanId, end_now: fn() -> u64) -> Self {
+        Self { client, span_id, end_now, closed: false }
+    }
+
+    /// Returns the guarded span id.
+    pub const fn span_id(&self) -> SpanId {
+        self.span_id
+    }
+
+    /// Ends the span explicitly and consumes the guard.
+    pub fn end(mut self, end_ns: u64, status: u8, attrs: &[u8]) -> Result<u8, C::Error> {
+        let rsp = self.client.end_span(self.span_id, end_ns, status, attrs)?;
+        self.closed = true;
+        Ok(rsp)
+    }
+}
+
+impl<C: SpanEndClient> Drop for SpanGuard<'_, C> {
+    fn drop(&mut self) {
+        if self.closed {
+            return;
+        }
+        let _ = self.client.end_span(self.span_id, (self.end_now)(), STATUS_OK, b"");
+        self.closed = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::sync::atomic::{AtomicU64, AtomicU8, AtomicUsize, Ordering};
+
+    struct FakeSpanClient {
+        calls: AtomicUsize,
+        span: AtomicU64,
+        end_ns: AtomicU64,
+        status: AtomicU8,
+    }
+
+    impl FakeSpanClient {
+        const fn new() -> Self {
+            Self {
+                calls: AtomicUsize::new(0),
+                span: AtomicU64::new(0),
+                end_ns: AtomicU64::new(0),
+                status: AtomicU8::new(0),
+            }
+        }
+    }
+
+    impl SpanEndClient for FakeSpanClient {
+        type Error = ();
+
+        fn end_span(
+            &self,
+            span_id: SpanId,
+            end_ns: u64,
+            status: u8,
+            _attrs: &[u8],
+        ) -> Result<u8, Self::Error> {
+            self.calls.fetch_add(1, Ordering::Relaxed);
+            self.span.store(span_id.0, Ordering::Relaxed);
+            self.end_ns.store(end_ns, Ordering::Relaxed);
+            self.status.store(status, Ordering::Relaxed);
+            Ok(STATUS_OK)
+        }
+    }
+
+    fn fixed_end_now() -> u64 {
+        1234
+    }
+
+    #[test]
+    fn test_span_guard_drop_sends_end_once() {
+        let fake = FakeSpanClient::new();
+        {
+            let _guard = SpanGuard::new(&fake, SpanId(77), fixed_end_now);
+        }
+        assert_eq!(fake.calls.load(Ordering::Relaxed), 1);
+        assert_eq!(fake.span.load(Ordering::Relaxed), 77);
+        assert_eq!(fake.end_ns.load(Ordering::Relaxed), 1234);
+        assert_eq!(fake.status.load(Ordering::Relaxed), STATUS_OK);
+    }
+
+    #[test]
+    fn test_span_guard_manual_end_disarms_drop() {
+        let fake = FakeSpanClient::new();
+        let guard = SpanGuard::new(&fake, SpanId(99), fixed_end_now);
+        let _ = guard.end(777, 3, b"result=ok\n");
+        assert_eq!(fake.calls.load(Ordering::Relaxed), 1);
+        assert_eq!(fake.span.load(Ordering::Relaxed), 99);
+        assert_eq!(fake.end_ns.load(Ordering::Relaxed), 777);
+        assert_eq!(fake.status.load(Ordering::Relaxed), 3);
+    }
+}
@@ -0,0 +1,284 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+//
+//! CONTEXT: SPAN_START / SPAN_END wire codec
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: Host unit tests in the crate root (attrs bounds) and in this module (links)
+//! ADR: docs/rfcs/RFC-0024-observability-v2-metrics-tracing-contract-v1.md
+//!
+//! SPAN_START: `nonce:u32 | span_id:u64 | trace_id:u64 | parent_span_id:u64 | start_ns:u64 |
+//! name_len:u8 | attrs_len:u16 | name | attrs`. SPAN_END: `nonce:u32 | span_id:u64 |
+//! end_ns:u64 | status:u8 | attrs_len:u16 | attrs`. Both answer with the plain status frame.
+//!
+//! SPAN_START_EX is SPAN_START followed by `link_count:u8 | link_span_id:u64 * link_count`:
+//! up to [`MAX_SPAN_LINKS`] spans the new one is causally related to without being their child
+//! (a batch job linking the spans that produced its inputs). Links may name any sender's spans.
+
+use alloc::vec::Vec;
+
+use crate::{
+    read_u64_le, BoundedFields, DecodeError, EncodeError, Request, SpanId, SpanName, TraceId,
+    MAGIC0, MAGIC1, MAX_ATTRS_LEN, MAX_SPAN_NAME_LEN, OP_SPAN_END, OP_SPAN_START, OP_SPAN_START_EX,
+    VERSION,
+};
+
+/// Most linked spans one SPAN_START_EX carries.
+pub const MAX_SPAN_LINKS: usize = 8;
+
+/// The linked span ids of a SPAN_START_EX request.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SpanLinks<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> SpanLinks<'a> {
+    /// Number of links (`0..=MAX_SPAN_LINKS`).
+    pub const fn len(&self) -> usize {
+        self.bytes.len() / 8
+    }
+
+    /// Whether the span has no links.
+    pub const fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Linked span ids in frame order.
+    pub fn iter(&self) -> impl Iterator<Item = SpanId> + 'a {
+        self.bytes.chunks_exact(8).map(|raw| SpanId(read_u64_le(raw, 0)))
+    }
+}
+
+/// Encodes a SPAN_START frame.
+pub fn encode_span_start(
+    nonce: u32,
+    span_id: SpanId,
+    trace_id: TraceId,
+    parent_span_id: SpanId,
+    start_ns: u64,
+    name: SpanName<'_>,
+    attrs: BoundedFields<'_>,
+) -> Result<Vec<u8>, EncodeError> {
+    let name = name.as_bytes();
+    let attrs = attrs.as_bytes();
+    if attrs.len() > MAX_ATTRS_LEN {
+        return Err(EncodeError::OverLimit);
+    }
+    let mut out = Vec::with_capacity(4 + 4 + 8 + 8 + 8 + 8 + 1 + 2 + name.len() + attrs.len());
+    out.extend_from_slice(&[MAGIC0, MAGIC1, VERSION, OP_SPAN_START]);
+    out.extend_from_slice(&nonce.to_le_bytes());
+    out.extend_from_slice(&span_id.0.to_le_bytes());
+    out.extend_from_slice(&trace_id.0.to_le_bytes());
+    out.extend_from_slice(&parent_span_id.0.to_le_bytes());
+    out.extend_from_slice(&start_ns.to_le_bytes());
+    out.push(name.len() as u8);
+    out.extend_from_slice(&(attrs.len() as u16).to_le_bytes());
+    out.extend_from_slice(name);
+    out.extend_from_slice(attrs);
+    Ok(out)
+}
+
+/// Encodes a SPAN_START_EX frame: [`encode_span_start`] plus up to [`MAX_SPAN_LINKS`] links.
+#[allow(clippy::too_many_arguments)]
+pub fn encode_span_start_ex(
+    nonce: u32,
+    span_id: SpanId,
+    trace_id: TraceId,
+    parent_span_id: SpanId,
+    start_ns: u64,
+    name: SpanName<'_>,
+    attrs: BoundedFields<'_>,
+    links: &[SpanId],
+) -> Result<Vec<u8>, EncodeError> {
+    if links.len() > MAX_SPAN_LINKS {
+        return Err(EncodeError::OverLimit);
+    }
+    let mut out =
+        encode_span_start(nonce, span_id, trace_id, parent_span_id, start_ns, name, attrs)?;
+    out[3] = OP_SPAN_START_EX;
+    out.reserve(1 + 8 * links.len());
+    out.push(links.len() as u8);
+    for link in links {
+        out.extend_from_slice(&link.0.to_le_bytes());
+    }
+    Ok(out)
+}
+
+/// Encodes a SPAN_END frame.
+pub fn encode_span_end(
+    nonce: u32,
+    span_id: SpanId,
+    end_ns: u64,
+    status: u8,
+    attrs: BoundedFields<'_>,
+) -> Result<Vec<u8>, EncodeError> {
+    let attrs = attrs.as_bytes();
+    if attrs.len() > MAX_ATTRS_LEN {
+        return Err(EncodeError::OverLimit);
+    }
+    let mut out = Vec::with_capacity(4 + 4 + 8 + 8 + 1 + 2 + attrs.len());
+    out.extend_from_slice(&[MAGIC0, MAGIC1, VERSION, OP_SPAN_END]);
+    out.extend_from_slice(&nonce.to_le_bytes());
+    out.extend_from_slice(&span_id.0.to_le_bytes());
+    out.extend_from_slice(&end_ns.to_le_bytes());
+    out.push(status);
+    out.extend_from_slice(&(attrs.len() as u16).to_le_bytes());
+    out.extend_from_slice(attrs);
+    Ok(out)
+}
+
+pub(crate) fn decode_span_start(nonce: u32, payload: &[u8]) -> Result<Request<'_>, DecodeError> {
+    let (start, rest) = split_span_start(payload)?;
+    if !rest.is_empty() {
+        return Err(DecodeError::Malformed);
+    }
+    let SpanStartFields { span_id, trace_id, parent_span_id, start_ns, name, attrs } = start;
+    Ok(Request::SpanStart { nonce, span_id, trace_id, parent_span_id, start_ns, name, attrs })
+}
+
+pub(crate) fn decode_span_start_ex(nonce: u32, payload: &[u8]) -> Result<Request<'_>, DecodeError> {
+    let (start, rest) = split_span_start(payload)?;
+    let (&count, bytes) = rest.split_first().ok_or(DecodeError::Malformed)?;
+    if count as usize > MAX_SPAN_LINKS {
+        return Err(DecodeError::OverLimit);
+    }
+    if bytes.len() != 8 * count as usize {
+        return Err(DecodeError::Malformed);
+    }
+    let SpanStartFields { span_id, trace_id, parent_span_id, start_ns, name, attrs } = start;
+    let links = SpanLinks { bytes };
+    Ok(Request::SpanStartEx {
+        nonce,
+        span_id,
+        trace_id,
+        parent_span_id,
+        start_ns,
+        name,
+        attrs,
+        links,
+    })
+}
+
+/// The SPAN_START fields shared by SPAN_START and SPAN_START_EX.
+struct SpanStartFields<'a> {
+    span_id: SpanId,
+    trace_id: TraceId,
+    parent_span_id: SpanId,
+    start_ns: u64,
+    name: &'a [u8],
+    attrs: &'a [u8],
+}
+
+/// Reads the SPAN_START fields off the front of `payload`; returns them and what follows.
+fn split_span_start(payload: &[u8]) -> Result<(SpanStartFields<'_>, &[u8]), DecodeError> {
+    if payload.len() < 8 + 8 + 8 + 8 + 1 + 2 {
+        return Err(DecodeError::Malformed);
+    }
+    let span_id = SpanId(read_u64_le(payload, 0));
+    let trace_id = TraceId(read_u64_le(payload, 8));
+    let parent_span_id = SpanId(read_u64_le(payload, 16));
+    let start_ns = read_u64_le(payload, 24);
+    let name_len = payload[32] as usize;
+    let attrs_len = u16::from_le_bytes([payload[33], payload[34]]) as usize;
+    if name_len == 0 || name_len > MAX_SPAN_NAME_LEN || attrs_len > MAX_ATTRS_LEN {
+        return Err(DecodeError::OverLimit);
+    }
+    let end = 35 + name_len + attrs_len;
+    if payload.len() < end {
+        return Err(DecodeError::Malformed);
+    }
+    let name = &payload[35..35 + name_len];
+    let attrs = &payload[35 + name_len..end];
+    let fields = SpanStartFields { span_id, trace_id, parent_span_id, start_ns, name, attrs };
+    Ok((fields, &payload[end..]))
+}
+
+pub(crate) fn decode_span_end(nonce: u32, payload: &[u8]) -> Result<Request<'_>, DecodeError> {
+    if payload.len() < 8 + 8 + 1 + 2 {
+        return Err(DecodeError::Malformed);
+    }
+    let span_id = SpanId(read_u64_le(payload, 0));
+    let end_ns = read_u64_le(payload, 8);
+    let status = payload[16];
+    let attrs_len = u16::from_le_bytes([payload[17], payload[18]]) as usize;
+    if attrs_len > MAX_ATTRS_LEN {
+        return Err(DecodeError::OverLimit);
+    }
+    if payload.len() != 19 + attrs_len {
+        return Err(DecodeError::Malformed);
+    }
+    let attrs = &payload[19..];
+    Ok(Request::SpanEnd { nonce, span_id, end_ns, status, attrs })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decode_request;
+
+    fn start_ex(links: &[SpanId]) -> Result<Vec<u8>, EncodeError> {
+        encode_span_start_ex(
+            5,
+            SpanId(0x11),
+            TraceId(0x22),
+            SpanId(0x33),
+            1_000,
+            SpanName::new(b"batch.run").unwrap(),
+            BoundedFields::attrs(b"job=7\n").unwrap(),
+            links,
+        )
+    }
+
+    #[test]
+    fn span_start_ex_roundtrip_with_several_links() {
+        let links = [SpanId(0x1_0000_0001), SpanId(0x2_0000_0004), SpanId(0x3_0000_0009)];
+        let frame = start_ex(&links).unwrap();
+        let Ok(Request::SpanStartEx {
+            nonce,
+            span_id,
+            parent_span_id,
+            name,
+            attrs,
+            links: got,
+            ..
+        }) = decode_request(&frame)
+        else {
+            panic!("not a SPAN_START_EX");
+        };
+        assert_eq!((nonce, span_id, parent_span_id), (5, SpanId(0x11), SpanId(0x33)));
+        assert_eq!((name, attrs), (&b"batch.run"[..], &b"job=7\n"[..]));
+        assert_eq!(got.len(), 3);
+        assert!(got.iter().eq(links));
+
+        // No links is valid, and the plain SPAN_START frame is the same minus the link list.
+        let frame = start_ex(&[]).unwrap();
+        let Ok(Request::SpanStartEx { links, .. }) = decode_request(&frame) else {
+            panic!("not a SPAN_START_EX");
+        };
+        assert!(links.is_empty());
+        let mut plain = frame[..frame.len() - 1].to_vec();
+        plain[3] = OP_SPAN_START;
+        assert!(matches!(decode_request(&plain), Ok(Request::SpanStart { .. })));
+    }
+
+    #[test]
+    fn test_reject_span_links_over_limit() {
+        let links = [SpanId(1); MAX_SPAN_LINKS + 1];
+        assert_eq!(start_ex(&links), Err(EncodeError::OverLimit));
+
+        let mut frame = start_ex(&links[..MAX_SPAN_LINKS]).unwrap();
+        assert!(decode_request(&frame).is_ok());
+        let count_at = frame.len() - 1 - 8 * MAX_SPAN_LINKS;
+        frame[count_at] = MAX_SPAN_LINKS as u8 + 1;
+        frame.extend_from_slice(&[0; 8]);
+        assert_eq!(decode_request(&frame), Err(DecodeError::OverLimit));
+
+        // A count that disagrees with the bytes that follow is malformed.
+        let frame = start_ex(&links[..2]).unwrap();
+        assert_eq!(decode_request(&frame[..frame.len() - 1]), Err(DecodeError::Malformed));
+        let mut long = frame.clone();
+        long.push(0);
+        assert_eq!(decode_request(&long), Err(DecodeError::Malformed));
+    }
+}
@@ -0,0 +1,144 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+//
+//! CONTEXT: SPANS_SCRAPE wire — text dump of recently ended spans for a trace collector
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: Host unit tests in this module
+//! ADR: docs/rfcs/RFC-0024-observability-v2-metrics-tracing-contract-v1.md
+//!
+//! Request: `MAGIC0 MAGIC1 VERSION OP_SPANS_SCRAPE | nonce:u32` (no payload).
+//!
+//! Response: the 9-byte status frame; with `STATUS_OK` it is followed by
+//! `flags:u8 | text_len:u16 | text`. `text` is metricsd's line-oriented span dump, at most
+//! [`MAX_SPANS_SCRAPE_TEXT_LEN`] bytes; [`SPANS_SCRAPE_FLAG_TRUNCATED`] says lines were cut.
+
+use alloc::vec::Vec;
+
+use crate::{
+    decode_status_response, encode_status_response, DecodeError, OP_SPANS_SCRAPE, STATUS_OK,
+};
+
+/// Response flag: the dump did not fit and trailing spans were left out.
+pub const SPANS_SCRAPE_FLAG_TRUNCATED: u8 = 1 << 0;
+/// Largest span dump one SPANS_SCRAPE response carries.
+pub const MAX_SPANS_SCRAPE_TEXT_LEN: usize = 4096 - SCRAPE_HEAD_LEN;
+
+/// Status frame (9 bytes) plus `flags:u8` and `text_len:u16`.
+const SCRAPE_HEAD_LEN: usize = 12;
+
+/// Decoded SPANS_SCRAPE response body.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SpansScrape<'a> {
+    /// [`SPANS_SCRAPE_FLAG_TRUNCATED`] or 0.
+    pub flags: u8,
+    /// The span dump, one span per line.
+    pub text: &'a [u8],
+}
+
+/// Encodes a SPANS_SCRAPE request frame.
+pub fn encode_spans_scrape(nonce: u32) -> Vec<u8> {
+    let mut out = Vec::with_capacity(8);
+    out.extend_from_slice(&[crate::MAGIC0, crate::MAGIC1, crate::VERSION, OP_SPANS_SCRAPE]);
+    out.extend_from_slice(&nonce.to_le_bytes());
+    out
+}
+
+/// Encodes a successful SPANS_SCRAPE response; `text` past the cap is cut and flagged.
+pub fn encode_spans_scrape_response(nonce: u32, mut flags: u8, text: &[u8]) -> Vec<u8> {
+    let text = if text.len() > MAX_SPANS_SCRAPE_TEXT_LEN {
+        flags |= SPANS_SCRAPE_FLAG_TRUNCATED;
+        &text[..MAX_SPANS_SCRAPE_TEXT_LEN]
+    } else {
+        text
+    };
+    let mut out = encode_status_response(OP_SPANS_SCRAPE, nonce, STATUS_OK);
+    out.push(flags);
+    out.extend_from_slice(&(text.len() as u16).to_le_bytes());
+    out.extend_from_slice(text);
+    out
+}
+
+/// Decodes a SPANS_SCRAPE response into `(status, body)`.
+///
+/// `body` is `Some` exactly when `status` is [`STATUS_OK`]; reject frames decode to
+/// `(status, None)`.
+pub fn decode_spans_scrape_response(
+    frame: &[u8],
+    expected_nonce: u32,
+) -> Result<(u8, Option<SpansScrape<'_>>), DecodeError> {
+    if frame.len() < SCRAPE_HEAD_LEN {
+        return match decode_status_response(frame, OP_SPANS_SCRAPE, expected_nonce)? {
+            STATUS_OK => Err(DecodeError::Malformed),
+            status => Ok((status, None)),
+        };
+    }
+    let (head, rest) = frame.split_at(SCRAPE_HEAD_LEN - 3);
+    let status = decode_status_response(head, OP_SPANS_SCRAPE, expected_nonce)?;
+    if status != STATUS_OK {
+        return Err(DecodeError::Malformed);
+    }
+    let [flags, l0, l1, text @ ..] = rest else {
+        return Err(DecodeError::Malformed);
+    };
+    let text_len = u16::from_le_bytes([*l0, *l1]) as usize;
+    if text_len > MAX_SPANS_SCRAPE_TEXT_LEN || text.len() != text_len {
+        return Err(DecodeError::Malformed);
+    }
+    Ok((status, Some(SpansScrape { flags: *flags, text })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::STATUS_RATE_LIMITED;
+    use crate::{decode_request, encode_status_response_ex, Request, REASON_SENDER_BUDGET};
+
+    #[test]
+    fn spans_scrape_round_trips() {
+        let frame = encode_spans_scrape(5);
+        assert_eq!(decode_request(&frame), Ok(Request::SpansScrape { nonce: 5 }));
+        assert_eq!(decode_request(&frame).map(|req| req.op_nonce()), Ok((OP_SPANS_SCRAPE, 5)));
+
+        let rsp = encode_spans_scrape_response(5, 0, b"span a\nspan b\n");
+        let body = SpansScrape { flags: 0, text: b"span a\nspan b\n" };
+        assert_eq!(decode_spans_scrape_response(&rsp, 5), Ok((STATUS_OK, Some(body))));
+        let empty = encode_spans_scrape_response(5, 0, b"");
+        assert_eq!(
+            decode_spans_scrape_response(&empty, 5),
+            Ok((STATUS_OK, Some(SpansScrape { flags: 0, text: b"" })))
+        );
+        let limited = encode_status_response_ex(
+            OP_SPANS_SCRAPE,
+            5,
+            STATUS_RATE_LIMITED,
+            REASON_SENDER_BUDGET,
+        );
+        assert_eq!(decode_spans_scrape_response(&limited, 5), Ok((STATUS_RATE_LIMITED, None)));
+    }
+
+    #[test]
+    fn test_reject_oversized_or_inconsistent_scrape() {
+        let big = alloc::vec![b'x'; MAX_SPANS_SCRAPE_TEXT_LEN + 10];
+        let rsp = encode_spans_scrape_response(1, 0, &big);
+        assert_eq!(rsp.len(), 4096);
+        let (_, body) = decode_spans_scrape_response(&rsp, 1).unwrap();
+        let body = body.unwrap();
+        assert_eq!(body.flags, SPANS_SCRAPE_FLAG_TRUNCATED);
+        assert_eq!(body.text.len(), MAX_SPANS_SCRAPE_TEXT_LEN);
+
+        // Request frames carry no payload.
+        let mut frame = encode_spans_scrape(1);
+        frame.push(0);
+        assert_eq!(decode_request(&frame), Err(DecodeError::Malformed));
+
+        // Length prefix must match, and an OK needs the body header.
+        let mut bad = encode_spans_scrape_response(1, 0, b"abc");
+        bad.pop();
+        assert_eq!(decode_spans_scrape_response(&bad, 1), Err(DecodeError::Malformed));
+        let bare_ok = encode_status_response(OP_SPANS_SCRAPE, 1, STATUS_OK);
+        assert_eq!(decode_spans_scrape_response(&bare_ok, 1), Err(DecodeError::Malformed));
+        assert_eq!(decode_spans_scrape_response(&rsp, 2), Err(DecodeError::Malformed));
+    }
+}
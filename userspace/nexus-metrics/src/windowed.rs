@@ -0,0 +1,136 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+//
+//! CONTEXT: WINDOWED_INC wire — counter increments that age out of a recent time window
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! API_STABILITY: Unstable
+//! TEST_COVERAGE: Host unit tests in this module
+//! ADR: docs/rfcs/RFC-0024-observability-v2-metrics-tracing-contract-v1.md
+//!
+//! Request: `MAGIC0 MAGIC1 VERSION OP_WINDOWED_INC | nonce:u32 | window_id:u8 |
+//! name_len:u8 | labels_len:u16 | delta:u64 | name | labels`. Response: the plain
+//! status frame. The window length is chosen from a fixed table by `window_id`
+//! ([`window_len_ns`]); a series keeps the window of its first increment.
+
+use alloc::vec::Vec;
+
+use crate::{
+    BoundedFields, DecodeError, EncodeError, MetricName, Request, MAGIC0, MAGIC1, MAX_LABELS_LEN,
+    MAX_METRIC_NAME_LEN, OP_WINDOWED_INC, VERSION,
+};
+
+/// "Active in the last 10 seconds".
+pub const WINDOW_10S: u8 = 0;
+/// "Active in the last minute".
+pub const WINDOW_1M: u8 = 1;
+/// "Active in the last 5 minutes".
+pub const WINDOW_5M: u8 = 2;
+
+const WINDOW_LENS_NS: [u64; 3] = [10_000_000_000, 60_000_000_000, 300_000_000_000];
+
+/// Window length for `window_id`, `None` for an unknown id.
+pub const fn window_len_ns(window_id: u8) -> Option<u64> {
+    match window_id {
+        WINDOW_10S | WINDOW_1M | WINDOW_5M => Some(WINDOW_LENS_NS[window_id as usize]),
+        _ => None,
+    }
+}
+
+/// Encodes a WINDOWED_INC frame; `window_id` must name a known window.
+pub fn encode_windowed_inc(
+    nonce: u32,
+    window_id: u8,
+    name: MetricName<'_>,
+    labels: BoundedFields<'_>,
+    delta: u64,
+) -> Result<Vec<u8>, EncodeError> {
+    if window_len_ns(window_id).is_none() {
+        return Err(EncodeError::InvalidArgs);
+    }
+    let (name, labels) = (name.as_bytes(), labels.as_bytes());
+    if labels.len() > MAX_LABELS_LEN {
+        return Err(EncodeError::OverLimit);
+    }
+    let mut out = Vec::with_capacity(4 + 4 + 1 + 1 + 2 + 8 + name.len() + labels.len());
+    out.extend_from_slice(&[MAGIC0, MAGIC1, VERSION, OP_WINDOWED_INC]);
+    out.extend_from_slice(&nonce.to_le_bytes());
+    out.push(window_id);
+    out.push(name.len() as u8);
+    out.extend_from_slice(&(labels.len() as u16).to_le_bytes());
+    out.extend_from_slice(&delta.to_le_bytes());
+    out.extend_from_slice(name);
+    out.extend_from_slice(labels);
+    Ok(out)
+}
+
+pub(crate) fn decode_windowed_inc(nonce: u32, payload: &[u8]) -> Result<Request<'_>, DecodeError> {
+    let [window_id, name_len, l0, l1, rest @ ..] = payload else {
+        return Err(DecodeError::Malformed);
+    };
+    let (name_len, labels_len) = (*name_len as usize, u16::from_le_bytes([*l0, *l1]) as usize);
+    if window_len_ns(*window_id).is_none() {
+        return Err(DecodeError::Malformed);
+    }
+    if name_len == 0 || name_len > MAX_METRIC_NAME_LEN || labels_len > MAX_LABELS_LEN {
+        return Err(DecodeError::OverLimit);
+    }
+    if rest.len() != 8 + name_len + labels_len {
+        return Err(DecodeError::Malformed);
+    }
+    let (delta, rest) = rest.split_at(8);
+    let mut raw = [0u8; 8];
+    raw.copy_from_slice(delta);
+    let (name, labels) = rest.split_at(name_len);
+    Ok(Request::WindowedInc {
+        nonce,
+        window_id: *window_id,
+        name,
+        labels,
+        delta: u64::from_le_bytes(raw),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decode_request;
+
+    fn frame(window_id: u8) -> Result<Vec<u8>, EncodeError> {
+        let name = MetricName::new(b"net.active").unwrap();
+        encode_windowed_inc(4, window_id, name, BoundedFields::labels(b"if=eth0\n").unwrap(), 3)
+    }
+
+    #[test]
+    fn windowed_inc_round_trips() {
+        let frame = frame(WINDOW_1M).unwrap();
+        assert_eq!(
+            decode_request(&frame),
+            Ok(Request::WindowedInc {
+                nonce: 4,
+                window_id: WINDOW_1M,
+                name: b"net.active",
+                labels: b"if=eth0\n",
+                delta: 3
+            })
+        );
+        assert_eq!(decode_request(&frame).map(|req| req.op_nonce()), Ok((OP_WINDOWED_INC, 4)));
+        assert_eq!(window_len_ns(WINDOW_10S), Some(10_000_000_000));
+        assert_eq!(window_len_ns(WINDOW_5M), Some(300_000_000_000));
+    }
+
+    #[test]
+    fn test_reject_unknown_window_or_bad_length() {
+        assert_eq!(frame(3), Err(EncodeError::InvalidArgs));
+        assert_eq!(window_len_ns(3), None);
+
+        let mut bad = frame(WINDOW_10S).unwrap();
+        bad[8] = 3;
+        assert_eq!(decode_request(&bad), Err(DecodeError::Malformed));
+        bad[8] = WINDOW_10S;
+        bad.push(0);
+        assert_eq!(decode_request(&bad), Err(DecodeError::Malformed));
+        bad.truncate(bad.len() - 2);
+        assert_eq!(decode_request(&bad), Err(DecodeError::Malformed));
+    }
+}
@@ -0,0 +1,197 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: Per-requester access control for engine operations (`AccessPolicy`)
+//! OWNERS: @runtime
+//! STATUS: Functional (host-first)
+//! TEST_COVERAGE: 5 unit tests (allow-all default, denied prefix, list filtering,
+//! prefix delete over a denied key, bulk reads/appends/changes)
+//!
+//! [`JournalEngine::open_with_policy`] injects an [`AccessPolicy`]; engines opened any other
+//! way use [`AllowAll`]. The policy is asked before every `put`/`get`/`delete`/`list`:
+//! the `*_as` methods pass the caller's requester id (statefsd: the kernel-derived
+//! `sender_service_id` from `ipc_recv_v2`), the plain methods pass [`LOCAL_REQUESTER`].
+//! A denied `put`/`get`/`delete` fails with `AccessDenied` before touching the journal;
+//! a denied `list` prefix fails the same way, and keys the policy hides under an allowed
+//! prefix are left out of the listing.
+//!
+//! The other entry points map onto the same four ops: `append` is a `Put`, `read_entries`
+//! and each `get_many` key a `Get`, `delete_prefix` a `Delete` on the prefix and on every
+//! key under it, and `changed_since` leaves out keys a `List` would hide.
+//!
+//! INVARIANTS:
+//! - Keys are validated before the policy sees them (`InvalidKey`/`KeyTooLong` win)
+//! - The policy is configuration, not journaled state: it stays set across `reopen`
+//! - No engine operation reaches a key without asking the policy
+
+use alloc::boxed::Box;
+
+use storage::BlockDevice;
+
+use crate::{JournalEngine, StatefsError};
+
+/// Requester id the plain `put`/`get`/`delete`/`list` methods pass to the policy.
+pub const LOCAL_REQUESTER: u64 = 0;
+
+/// Engine operation an [`AccessPolicy`] is asked about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Put,
+    Get,
+    Delete,
+    /// Asked once for the prefix, then once per listed key.
+    List,
+}
+
+/// Decides whether `requester_id` may perform `op` on `key`.
+pub trait AccessPolicy {
+    /// `true` to let the operation proceed.
+    fn allow(&self, requester_id: u64, op: Op, key: &str) -> bool;
+}
+
+/// Policy allowing every operation (the default).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AllowAll;
+
+impl AccessPolicy for AllowAll {
+    fn allow(&self, _requester_id: u64, _op: Op, _key: &str) -> bool {
+        true
+    }
+}
+
+impl<B: BlockDevice> JournalEngine<B> {
+    /// Like [`JournalEngine::open`], consulting `policy` on every operation (see module docs).
+    pub fn open_with_policy(
+        device: B,
+        policy: Box<dyn AccessPolicy>,
+    ) -> Result<Self, StatefsError> {
+        let mut engine = Self::open(device)?;
+        engine.access = policy;
+        Ok(engine)
+    }
+
+    /// `AccessDenied` unless the policy allows `requester_id` to perform `op` on `key`.
+    pub(crate) fn check_access(
+        &self,
+        requester_id: u64,
+        op: Op,
+        key: &str,
+    ) -> Result<(), StatefsError> {
+        if self.access.allow(requester_id, op, key) {
+            Ok(())
+        } else {
+            Err(StatefsError::AccessDenied)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+    use storage::MemBlockDevice;
+
+    const APP: u64 = 0x51;
+
+    /// Denies `APP` everything under `/state/secret/`.
+    struct DenyPrefix;
+
+    impl AccessPolicy for DenyPrefix {
+        fn allow(&self, requester_id: u64, _op: Op, key: &str) -> bool {
+            requester_id != APP || !key.starts_with("/state/secret/")
+        }
+    }
+
+    fn engine(policy: Box<dyn AccessPolicy>) -> JournalEngine<MemBlockDevice> {
+        JournalEngine::open_with_policy(MemBlockDevice::new(512, 64), policy).unwrap()
+    }
+
+    #[test]
+    fn default_policy_allows_every_requester() {
+        let mut engine = JournalEngine::open(MemBlockDevice::new(512, 64)).unwrap();
+        engine.put_as(APP, "/state/secret/k", b"v").unwrap();
+        assert_eq!(engine.get_as(APP, "/state/secret/k").unwrap(), b"v");
+        assert_eq!(engine.list_as(APP, "/state/", 10).unwrap(), ["/state/secret/k"]);
+        engine.delete_as(APP, "/state/secret/k").unwrap();
+        assert!(engine.is_empty());
+    }
+
+    #[test]
+    fn test_reject_denied_prefix_for_one_requester() {
+        let mut engine = engine(Box::new(DenyPrefix));
+        engine.put("/state/secret/k", b"v").unwrap();
+
+        assert_eq!(engine.put_as(APP, "/state/secret/k", b"w"), Err(StatefsError::AccessDenied));
+        assert_eq!(engine.get_as(APP, "/state/secret/k"), Err(StatefsError::AccessDenied));
+        assert_eq!(engine.delete_as(APP, "/state/secret/k"), Err(StatefsError::AccessDenied));
+        assert_eq!(engine.list_as(APP, "/state/secret/", 10), Err(StatefsError::AccessDenied));
+        // Invalid keys are reported as such, not as denials.
+        assert_eq!(engine.get_as(APP, "/other/k"), Err(StatefsError::InvalidKey));
+
+        // Nothing changed, and the policy survives a reopen.
+        engine.reopen().unwrap();
+        assert_eq!(engine.get("/state/secret/k").unwrap(), b"v");
+        assert_eq!(engine.get_as(APP, "/state/secret/k"), Err(StatefsError::AccessDenied));
+        engine.put_as(APP, "/state/app/k", b"x").unwrap();
+    }
+
+    #[test]
+    fn list_filters_denied_keys() {
+        let mut engine = engine(Box::new(DenyPrefix));
+        for key in ["/state/a", "/state/secret/b", "/state/secret/c", "/state/d"] {
+            engine.put(key, b"v").unwrap();
+        }
+        // The limit counts visible keys only.
+        assert_eq!(engine.list_as(APP, "/state/", 2).unwrap(), ["/state/a", "/state/d"]);
+        let all: Vec<String> = engine.list("/state/", 10).unwrap();
+        assert_eq!(all.len(), 4);
+    }
+
+    #[test]
+    fn test_reject_delete_prefix_over_a_denied_key() {
+        let mut engine = engine(Box::new(DenyPrefix));
+        engine.put("/state/app/k", b"a").unwrap();
+        engine.put("/state/secret/x", b"s").unwrap();
+
+        assert_eq!(engine.delete_prefix_as(APP, "/state/"), Err(StatefsError::AccessDenied));
+        assert_eq!(engine.delete_prefix_as(APP, "/state/secret/"), Err(StatefsError::AccessDenied));
+        // All or nothing: the allowed key survives too, and across a replay.
+        engine.reopen().unwrap();
+        assert_eq!(engine.get("/state/secret/x").unwrap(), b"s");
+        assert_eq!(engine.get("/state/app/k").unwrap(), b"a");
+
+        assert_eq!(engine.delete_prefix_as(APP, "/state/app/"), Ok(1));
+        assert_eq!(engine.delete_prefix("/state/"), Ok(1));
+    }
+
+    #[test]
+    fn test_reject_bulk_reads_and_appends_under_a_denied_prefix() {
+        let mut engine = engine(Box::new(DenyPrefix));
+        engine.put("/state/app/k", b"a").unwrap();
+        engine.put("/state/secret/x", b"s").unwrap();
+        engine.append("/state/secret/log", b"e").unwrap();
+
+        let got = engine.get_many_as(APP, &["/state/app/k", "/state/secret/x"]);
+        assert_eq!(got, [Ok(b"a".to_vec()), Err(StatefsError::AccessDenied)]);
+        assert_eq!(
+            engine.read_entries_as(APP, "/state/secret/log", 0, 10),
+            Err(StatefsError::AccessDenied)
+        );
+        assert_eq!(
+            engine.append_as(APP, "/state/secret/log", b"f"),
+            Err(StatefsError::AccessDenied)
+        );
+        assert_eq!(
+            engine.append_as(APP, "/state/secret/new", b"f"),
+            Err(StatefsError::AccessDenied)
+        );
+        assert_eq!(engine.read_entries("/state/secret/log", 0, 10).len(), 1);
+
+        let visible: Vec<String> =
+            engine.changed_since_as(APP, 0).changes.into_iter().map(|c| c.key).collect();
+        assert_eq!(visible, ["/state/app/k"]);
+        assert_eq!(engine.changed_since_as(APP, 0).seq, engine.changed_since(0).seq);
+        assert_eq!(engine.changed_since(0).changes.len(), 3);
+    }
+}
@@ -0,0 +1,457 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: Change sequence for pull-based client sync (`JournalEngine::changed_since`,
+//! `OP_CHANGES`)
+//! OWNERS: @runtime
+//! STATUS: Functional (host-first)
+//! TEST_COVERAGE: 4 unit tests (mutations after a cursor, full resync, frame round trip,
+//! malformed frames)
+//!
+//! Every mutation (put, large put, append, delete, prefix delete) takes the next value of a
+//! per-engine change sequence and stamps the key with it. A client mirroring statefs keeps
+//! the `seq` of its last sync as a cursor and asks for the keys stamped after it, instead of
+//! re-listing everything. Deletes are remembered as bounded tombstones.
+//!
+//! The sequence lives in memory only. Open and `reopen` restamp every live key, and so does
+//! dropping tombstones once [`MAX_CHANGE_TOMBSTONES`] is reached. Either way, the
+//! *horizon* moves up to the sequence value from before the restamp. A cursor below the
+//! horizon, or one from the future (another engine instance), cannot be served
+//! incrementally: it gets a *full* change set, which lists every live key and tells the
+//! client to drop its mirror first.
+//!
+//! Request: `[S, F, ver, OP_CHANGES, since:u64]`. Response: `[S, F, ver, OP_CHANGES|0x80,
+//! status, (nonce:u64 if v2), seq:u64, flags:u8, count:u16, (kind:u8, key_len:u16, key)*count]`.
+//! Changes are sent in sequence order until the frame budget is used up. A cut-short frame
+//! sets [`CHANGES_FLAG_TRUNCATED`], and its `seq` is just below the first change it left out,
+//! so the next request picks up where the frame stopped.
+//!
+//! INVARIANTS:
+//! - Stamps are unique, and every live key and tombstone is stamped above the horizon
+//! - `changed_since_as` names only keys the engine's `AccessPolicy` lets the requester list
+
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::str;
+
+use storage::BlockDevice;
+
+use crate::access::{Op, LOCAL_REQUESTER};
+use crate::protocol::{
+    encode_status_response_with_nonce, error_from_status, Request, MAGIC0, MAGIC1, OP_CHANGES,
+    STATUS_MALFORMED, STATUS_OK, VERSION, VERSION_V2,
+};
+use crate::{JournalEngine, StatefsError, MAX_KEY_LEN};
+
+/// Deleted keys remembered for incremental sync before the horizon moves up.
+pub const MAX_CHANGE_TOMBSTONES: usize = 256;
+/// Response budget statefsd applies to `OP_CHANGES` (the client's receive buffer).
+pub const MAX_CHANGES_RESPONSE_BYTES: usize = 4096;
+/// Response flag: the cursor was too old to be served incrementally; drop the mirror first.
+pub const CHANGES_FLAG_FULL: u8 = 1 << 0;
+/// Response flag: more changes follow; ask again from the returned `seq`.
+pub const CHANGES_FLAG_TRUNCATED: u8 = 1 << 1;
+
+/// Body bytes before the entries: `seq:u64, flags:u8, count:u16`.
+const CHANGES_BODY_HEADER_LEN: usize = 11;
+/// Per-entry header: `kind:u8, key_len:u16`.
+const ENTRY_HEADER_LEN: usize = 3;
+
+/// What happened to a key after the cursor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ChangeKind {
+    /// The key holds a new value (or list entry); fetch it.
+    Put = 1,
+    /// The key no longer exists.
+    Delete = 2,
+}
+
+impl ChangeKind {
+    fn from_u8(v: u8) -> Option<Self> {
+        match v {
+            1 => Some(Self::Put),
+            2 => Some(Self::Delete),
+            _ => None,
+        }
+    }
+}
+
+/// One changed key and the sequence value it was stamped with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    pub seq: u64,
+    pub key: String,
+    pub kind: ChangeKind,
+}
+
+/// Outcome of [`JournalEngine::changed_since`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ChangeSet {
+    /// Current sequence: the cursor for the next sync.
+    pub seq: u64,
+    /// The cursor predates the horizon: `changes` names every live key, and the client
+    /// must drop keys it does not name.
+    pub full: bool,
+    /// Changes in sequence order.
+    pub changes: Vec<Change>,
+}
+
+/// Decoded `OP_CHANGES` response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangesResponse {
+    /// Cursor for the next request.
+    pub seq: u64,
+    /// See [`CHANGES_FLAG_FULL`].
+    pub full: bool,
+    /// See [`CHANGES_FLAG_TRUNCATED`].
+    pub truncated: bool,
+    /// Changed keys in sequence order.
+    pub changes: Vec<(String, ChangeKind)>,
+}
+
+/// Per-key stamps and bounded tombstones behind [`JournalEngine::changed_since`].
+#[derive(Debug, Default)]
+pub(crate) struct ChangeLog {
+    seq: u64,
+    horizon: u64,
+    stamps: BTreeMap<String, u64>,
+    tombstones: VecDeque<(u64, String)>,
+}
+
+impl ChangeLog {
+    fn next(&mut self) -> u64 {
+        self.seq = self.seq.saturating_add(1);
+        self.seq
+    }
+
+    fn note_put(&mut self, key: &str) {
+        let seq = self.next();
+        self.tombstones.retain(|(_, k)| k != key);
+        self.stamps.insert(key.into(), seq);
+    }
+
+    /// Returns `true` when the tombstones are full and the caller must restamp.
+    fn note_delete(&mut self, key: &str) -> bool {
+        let seq = self.next();
+        self.stamps.remove(key);
+        self.tombstones.retain(|(_, k)| k != key);
+        self.tombstones.push_back((seq, key.into()));
+        self.tombstones.len() > MAX_CHANGE_TOMBSTONES
+    }
+
+    /// The key's current stamp, if it is live.
+    pub(crate) fn stamp(&self, key: &str) -> Option<u64> {
+        self.stamps.get(key).copied()
+    }
+
+    fn restamp<'a>(&mut self, live: impl Iterator<Item = &'a String>) {
+        self.horizon = self.seq;
+        self.tombstones.clear();
+        self.stamps.clear();
+        for key in live {
+            let seq = self.next();
+            self.stamps.insert(key.clone(), seq);
+        }
+    }
+}
+
+impl<B: BlockDevice> JournalEngine<B> {
+    /// Keys changed after the cursor `seq`, in sequence order, plus the current sequence.
+    ///
+    /// Pass 0 on first sync. Cursors below the horizon (or above the current sequence)
+    /// get a full change set; see the module docs.
+    pub fn changed_since(&self, seq: u64) -> ChangeSet {
+        self.changed_since_as(LOCAL_REQUESTER, seq)
+    }
+
+    /// [`JournalEngine::changed_since`] on behalf of `requester_id`, leaving out keys the
+    /// [`AccessPolicy`](crate::AccessPolicy) hides from it (asked as [`Op::List`], like
+    /// [`JournalEngine::list_as`]). The returned `seq` is unchanged by the filtering.
+    pub fn changed_since_as(&self, requester_id: u64, seq: u64) -> ChangeSet {
+        let log = &self.changes;
+        let full = seq < log.horizon || seq > log.seq;
+        let since = if full { log.horizon } else { seq };
+        let puts = log.stamps.iter().map(|(key, &s)| (s, key, ChangeKind::Put));
+        let deletes = log.tombstones.iter().map(|(s, key)| (*s, key, ChangeKind::Delete));
+        let mut changes: Vec<Change> = puts
+            .chain(deletes)
+            .filter(|&(s, key, _)| s > since && self.access.allow(requester_id, Op::List, key))
+            .map(|(seq, key, kind)| Change { seq, key: key.clone(), kind })
+            .collect();
+        changes.sort_unstable_by_key(|change| change.seq);
+        ChangeSet { seq: log.seq, full, changes }
+    }
+
+    /// Current change sequence (the `seq` a sync right now would return).
+    pub fn change_seq(&self) -> u64 {
+        self.changes.seq
+    }
+
+    pub(crate) fn note_change_put(&mut self, key: &str) {
+        self.changes.note_put(key);
+    }
+
+    pub(crate) fn note_change_delete(&mut self, key: &str) {
+        if self.changes.note_delete(key) {
+            self.restamp_changes();
+        }
+    }
+
+    /// Restamp every live key above a new horizon (after replay, or when tombstones overflow).
+    pub(crate) fn restamp_changes(&mut self) {
+        let mut live: Vec<&String> = self.kv.keys().chain(self.lists.keys()).collect();
+        live.sort_unstable();
+        self.changes.restamp(live.into_iter());
+    }
+}
+
+/// `OP_CHANGES` request frame.
+pub fn encode_changes_request(since: u64) -> Vec<u8> {
+    let mut out = Vec::with_capacity(12);
+    out.extend_from_slice(&[MAGIC0, MAGIC1, VERSION, OP_CHANGES]);
+    out.extend_from_slice(&since.to_le_bytes());
+    out
+}
+
+pub(crate) fn decode_changes_payload(payload: &[u8]) -> Result<Request<'_>, u8> {
+    let since: [u8; 8] = payload.try_into().map_err(|_| STATUS_MALFORMED)?;
+    Ok(Request::Changes { since: u64::from_le_bytes(since) })
+}
+
+/// `OP_CHANGES` response, emitting changes until the next one would pass `max_bytes`.
+///
+/// The body is appended only when `status` is `STATUS_OK`.
+pub fn encode_changes_response_with_nonce(
+    status: u8,
+    set: &ChangeSet,
+    max_bytes: usize,
+    nonce: Option<u64>,
+) -> Vec<u8> {
+    let mut out = encode_status_response_with_nonce(OP_CHANGES, status, nonce);
+    if status != STATUS_OK {
+        return out;
+    }
+    let header = out.len();
+    out.extend_from_slice(&[0; CHANGES_BODY_HEADER_LEN]);
+    let mut flags = if set.full { CHANGES_FLAG_FULL } else { 0 };
+    let mut seq = set.seq;
+    let mut count: u16 = 0;
+    for change in &set.changes {
+        let entry_len = ENTRY_HEADER_LEN + change.key.len();
+        if count == u16::MAX || out.len().saturating_add(entry_len) > max_bytes {
+            // Stamps are unique, so everything from this change on is above `seq`.
+            flags |= CHANGES_FLAG_TRUNCATED;
+            seq = change.seq - 1;
+            break;
+        }
+        out.push(change.kind as u8);
+        out.extend_from_slice(&(change.key.len() as u16).to_le_bytes());
+        out.extend_from_slice(change.key.as_bytes());
+        count += 1;
+    }
+    out[header..header + 8].copy_from_slice(&seq.to_le_bytes());
+    out[header + 8] = flags;
+    out[header + 9..header + 11].copy_from_slice(&count.to_le_bytes());
+    out
+}
+
+/// Decode an `OP_CHANGES` response.
+pub fn decode_changes_response(frame: &[u8]) -> Result<ChangesResponse, StatefsError> {
+    if frame.len() < 5 || frame[0] != MAGIC0 || frame[1] != MAGIC1 {
+        return Err(StatefsError::Corrupted);
+    }
+    if frame[3] != (OP_CHANGES | 0x80) {
+        return Err(StatefsError::Corrupted);
+    }
+    let body = match frame[2] {
+        VERSION => &frame[5..],
+        VERSION_V2 if frame.len() >= 13 => &frame[13..],
+        _ => return Err(StatefsError::Corrupted),
+    };
+    if frame[4] != STATUS_OK {
+        return Err(error_from_status(frame[4]));
+    }
+    let (header, mut rest) =
+        body.split_first_chunk::<CHANGES_BODY_HEADER_LEN>().ok_or(StatefsError::Corrupted)?;
+    let [s0, s1, s2, s3, s4, s5, s6, s7, flags, c0, c1] = *header;
+    if flags & !(CHANGES_FLAG_FULL | CHANGES_FLAG_TRUNCATED) != 0 {
+        return Err(StatefsError::Corrupted);
+    }
+    let count = u16::from_le_bytes([c0, c1]);
+    let mut changes = Vec::new();
+    for _ in 0..count {
+        let ([kind, l0, l1], tail) =
+            rest.split_first_chunk::<ENTRY_HEADER_LEN>().ok_or(StatefsError::Corrupted)?;
+        let kind = ChangeKind::from_u8(*kind).ok_or(StatefsError::Corrupted)?;
+        let key_len = usize::from(u16::from_le_bytes([*l0, *l1]));
+        if key_len == 0 || key_len > MAX_KEY_LEN {
+            return Err(StatefsError::Corrupted);
+        }
+        let key = tail.get(..key_len).ok_or(StatefsError::Corrupted)?;
+        let key = str::from_utf8(key).map_err(|_| StatefsError::Corrupted)?;
+        changes.push((String::from(key), kind));
+        rest = &tail[key_len..];
+    }
+    if !rest.is_empty() {
+        return Err(StatefsError::Corrupted);
+    }
+    Ok(ChangesResponse {
+        seq: u64::from_le_bytes([s0, s1, s2, s3, s4, s5, s6, s7]),
+        full: flags & CHANGES_FLAG_FULL != 0,
+        truncated: flags & CHANGES_FLAG_TRUNCATED != 0,
+        changes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::decode_request_with_nonce;
+    use alloc::vec;
+    use storage::MemBlockDevice;
+
+    fn engine() -> JournalEngine<MemBlockDevice> {
+        JournalEngine::open(MemBlockDevice::new(512, 64)).unwrap()
+    }
+
+    fn keys(set: &ChangeSet) -> Vec<(&str, ChangeKind)> {
+        set.changes.iter().map(|c| (c.key.as_str(), c.kind)).collect()
+    }
+
+    #[test]
+    fn changed_since_returns_only_mutations_after_the_cursor() {
+        let mut engine = engine();
+        engine.put("/state/a", b"1").unwrap();
+        engine.put("/state/b", b"2").unwrap();
+        engine.put("/state/c", b"3").unwrap();
+        let first = engine.changed_since(0);
+        assert!(!first.full);
+        assert_eq!(first.changes.len(), 3);
+        let cursor = first.seq;
+
+        engine.put("/state/b", b"22").unwrap();
+        engine.append("/state/log", b"x").unwrap();
+        engine.delete("/state/c").unwrap();
+        engine.put("/state/d", b"4").unwrap();
+        engine.delete("/state/d").unwrap();
+        let set = engine.changed_since(cursor);
+        assert!(!set.full);
+        assert_eq!(set.seq, engine.change_seq());
+        // `/state/a` is unchanged; `/state/d` only shows its latest state.
+        assert_eq!(
+            keys(&set),
+            [
+                ("/state/b", ChangeKind::Put),
+                ("/state/log", ChangeKind::Put),
+                ("/state/c", ChangeKind::Delete),
+                ("/state/d", ChangeKind::Delete),
+            ]
+        );
+        assert!(engine.changed_since(set.seq).changes.is_empty());
+
+        engine.put("/state/x/1", b"1").unwrap();
+        engine.put("/state/x/2", b"2").unwrap();
+        let before = engine.change_seq();
+        assert_eq!(engine.delete_prefix("/state/x/").unwrap(), 2);
+        let set = engine.changed_since(before);
+        assert_eq!(
+            keys(&set),
+            [("/state/x/1", ChangeKind::Delete), ("/state/x/2", ChangeKind::Delete)]
+        );
+    }
+
+    #[test]
+    fn stale_cursors_get_a_full_change_set() {
+        let mut engine = engine();
+        engine.put("/state/a", b"1").unwrap();
+        let stale = engine.change_seq();
+        engine.put("/state/b", b"2").unwrap();
+        let current = engine.change_seq();
+        engine.sync().unwrap();
+        engine.reopen().unwrap();
+
+        // Reopen restamps every key: an up-to-date cursor sees them again as puts, an
+        // older one may have missed a delete and must resync in full.
+        let set = engine.changed_since(stale);
+        assert!(set.full && set.seq > current);
+        assert_eq!(keys(&set), [("/state/a", ChangeKind::Put), ("/state/b", ChangeKind::Put)]);
+        assert_eq!(engine.changed_since(current), ChangeSet { full: false, ..set.clone() });
+        assert!(engine.changed_since(u64::MAX).full);
+        assert!(engine.changed_since(set.seq).changes.is_empty());
+
+        // Overflowing the tombstones moves the horizon past older cursors.
+        let cursor = engine.change_seq();
+        for i in 0..=MAX_CHANGE_TOMBSTONES {
+            let key = alloc::format!("/state/t/{i}");
+            engine.put(&key, b"t").unwrap();
+            engine.delete(&key).unwrap();
+        }
+        let set = engine.changed_since(cursor);
+        assert!(set.full);
+        assert_eq!(keys(&set), [("/state/a", ChangeKind::Put), ("/state/b", ChangeKind::Put)]);
+    }
+
+    #[test]
+    fn changes_frame_round_trips_and_resumes_after_truncation() {
+        let mut engine = engine();
+        for i in 0..4 {
+            engine.put(&alloc::format!("/state/k{i}"), b"v").unwrap();
+        }
+        engine.delete("/state/k1").unwrap();
+        let req = encode_changes_request(0);
+        assert_eq!(decode_request_with_nonce(&req), Ok((Request::Changes { since: 0 }, None)));
+
+        let set = engine.changed_since(0);
+        let frame = encode_changes_response_with_nonce(STATUS_OK, &set, 4096, Some(9));
+        let rsp = decode_changes_response(&frame).unwrap();
+        assert_eq!((rsp.seq, rsp.full, rsp.truncated), (set.seq, false, false));
+        assert_eq!(
+            rsp.changes,
+            [
+                ("/state/k0".into(), ChangeKind::Put),
+                ("/state/k2".into(), ChangeKind::Put),
+                ("/state/k3".into(), ChangeKind::Put),
+                ("/state/k1".into(), ChangeKind::Delete),
+            ]
+        );
+
+        // Room for two entries: the next request resumes right after them.
+        let budget = 5 + CHANGES_BODY_HEADER_LEN + 2 * (ENTRY_HEADER_LEN + 9);
+        let frame = encode_changes_response_with_nonce(STATUS_OK, &set, budget, None);
+        let head = decode_changes_response(&frame).unwrap();
+        assert!(head.truncated);
+        assert_eq!(head.changes, rsp.changes[..2]);
+        let tail = engine.changed_since(head.seq);
+        let tail: Vec<_> = tail.changes.into_iter().map(|c| (c.key, c.kind)).collect();
+        assert_eq!(tail, rsp.changes[2..]);
+    }
+
+    #[test]
+    fn test_reject_malformed_changes_frames() {
+        let mut req = encode_changes_request(7);
+        req.push(0);
+        assert_eq!(decode_request_with_nonce(&req), Err(STATUS_MALFORMED));
+        assert_eq!(decode_request_with_nonce(&req[..8]), Err(STATUS_MALFORMED));
+
+        let set = ChangeSet {
+            seq: 3,
+            full: false,
+            changes: vec![Change { seq: 3, key: "/state/a".into(), kind: ChangeKind::Put }],
+        };
+        let frame = encode_changes_response_with_nonce(STATUS_OK, &set, 4096, None);
+        assert_eq!(
+            decode_changes_response(&frame[..frame.len() - 1]),
+            Err(StatefsError::Corrupted)
+        );
+        let mut bad_kind = frame.clone();
+        bad_kind[5 + CHANGES_BODY_HEADER_LEN] = 9;
+        assert_eq!(decode_changes_response(&bad_kind), Err(StatefsError::Corrupted));
+        let mut bad_flags = frame.clone();
+        bad_flags[5 + 8] = 0x80;
+        assert_eq!(decode_changes_response(&bad_flags), Err(StatefsError::Corrupted));
+        let denied = encode_changes_response_with_nonce(2, &set, 4096, None);
+        assert_eq!(decode_changes_response(&denied), Err(StatefsError::AccessDenied));
+    }
+}
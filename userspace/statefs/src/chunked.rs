@@ -0,0 +1,352 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: Values over 64 KiB stored as chunked multi-record runs (`JournalEngine::put_large`)
+//! OWNERS: @runtime
+//! STATUS: Functional (host-first)
+//! TEST_COVERAGE: 3 unit tests (multi-chunk round trip, partial runs discarded, size ceiling)
+//!
+//! A journal record carries at most `MAX_VALUE_SIZE` bytes, which is too small for
+//! certificate bundles or fonts. `put_large` stores a value of up to
+//! [`MAX_LARGE_VALUE_SIZE`] bytes as a run of `PutChunk` records written back to back, each
+//! value being `index:u16 | count:u16 | data` (little-endian) with `CHUNK_DATA_LEN` data bytes
+//! in every chunk but the last. Values that fit one record take the plain `Put` path, so a
+//! value's length alone tells which layout holds it.
+//!
+//! Replay collects a run and applies it only when its last chunk is read. A run that is
+//! interrupted by any other record, restarts, or is cut off at the journal tail was never
+//! committed: it is dropped, the previous value of the key stays, and its bytes count as
+//! dead. A chunk with an impossible layout is corruption and stops replay. Compaction copies
+//! a large value as a fresh run, and an evicted one is reloaded chunk by chunk.
+//!
+//! statefsd frames keep the `MAX_VALUE_SIZE` cap; large values are an engine API for now.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use storage::BlockDevice;
+
+use crate::access::LOCAL_REQUESTER;
+use crate::checksum::RecordChecksum;
+use crate::journal::{serialize_record, TAIL_GUARD_LEN};
+use crate::stats::record_len;
+use crate::value_cache::ValueSlot;
+use crate::{JournalEngine, JournalOpCode, StatefsError, MAX_VALUE_SIZE};
+
+/// Maximum size of a value stored with [`JournalEngine::put_large`] (1 MiB).
+pub const MAX_LARGE_VALUE_SIZE: usize = 1024 * 1024;
+
+/// `index:u16 | count:u16` prefix of a `PutChunk` record value.
+const CHUNK_HEADER_LEN: usize = 4;
+/// Value bytes per chunk (every chunk but the last is full).
+const CHUNK_DATA_LEN: usize = MAX_VALUE_SIZE - CHUNK_HEADER_LEN;
+/// Offset of the key in a serialized record (magic, opcode, key and value lengths).
+const KEY_OFFSET: usize = 11;
+
+/// A `PutChunk` run being collected by replay.
+pub(crate) struct PendingChunks {
+    key: String,
+    count: u16,
+    value: Vec<u8>,
+    /// Journal offset of the first chunk.
+    offset: usize,
+    /// Journal bytes of the chunks collected so far.
+    journal_bytes: usize,
+    next: u16,
+}
+
+fn chunk_count(len: usize) -> usize {
+    len.div_ceil(CHUNK_DATA_LEN)
+}
+
+/// `PutChunk` record values for `value`, in journal order.
+fn chunk_payloads(value: &[u8]) -> impl Iterator<Item = Vec<u8>> + '_ {
+    let count = chunk_count(value.len()) as u16;
+    value.chunks(CHUNK_DATA_LEN).enumerate().map(move |(index, data)| {
+        let mut payload = Vec::with_capacity(CHUNK_HEADER_LEN + data.len());
+        payload.extend_from_slice(&(index as u16).to_le_bytes());
+        payload.extend_from_slice(&count.to_le_bytes());
+        payload.extend_from_slice(data);
+        payload
+    })
+}
+
+/// Splits a `PutChunk` value into `(index, count, data)`; `None` for an impossible header.
+fn split_chunk(payload: &[u8]) -> Option<(u16, u16, &[u8])> {
+    let (head, data) = payload.split_first_chunk::<CHUNK_HEADER_LEN>()?;
+    let index = u16::from_le_bytes([head[0], head[1]]);
+    let count = u16::from_le_bytes([head[2], head[3]]);
+    let full = usize::from(index) + 1 < usize::from(count);
+    let data_ok = if full { data.len() == CHUNK_DATA_LEN } else { !data.is_empty() };
+    (count >= 2 && index < count && data_ok).then_some((index, count, data))
+}
+
+/// Serialized `PutChunk` records that store `value` under `key`.
+pub(crate) fn chunk_records(checksum: RecordChecksum, key: &str, value: &[u8]) -> Vec<Vec<u8>> {
+    chunk_payloads(value)
+        .map(|payload| serialize_record(checksum, JournalOpCode::PutChunk, key, &payload))
+        .collect()
+}
+
+/// Journal bytes taken by a `len`-byte value of `key` (one `Put` or a chunk run).
+pub(crate) fn value_record_bytes(key: &str, len: usize) -> usize {
+    if len <= MAX_VALUE_SIZE {
+        return record_len(key, len);
+    }
+    chunk_count(len) * record_len(key, CHUNK_HEADER_LEN) + len
+}
+
+/// Whether a serialized record starts a value: a `Put` or the first chunk of a run.
+pub(crate) fn is_value_head(record: &[u8]) -> bool {
+    match record.get(4).copied() {
+        Some(op) if op == JournalOpCode::Put as u8 => true,
+        Some(op) if op == JournalOpCode::PutChunk as u8 => {
+            let Some(&[lo, hi]) = record.get(5..7) else {
+                return false;
+            };
+            let index_at = KEY_OFFSET + usize::from(u16::from_le_bytes([lo, hi]));
+            record.get(index_at..index_at + 2) == Some(&[0, 0])
+        }
+        _ => false,
+    }
+}
+
+impl<B: BlockDevice> JournalEngine<B> {
+    /// Put a value of up to [`MAX_LARGE_VALUE_SIZE`] bytes.
+    ///
+    /// Values of at most `MAX_VALUE_SIZE` bytes are stored exactly like [`JournalEngine::put`];
+    /// larger ones as a run of chunk records that replay applies only when complete.
+    pub fn put_large(&mut self, key: &str, value: &[u8]) -> Result<(), StatefsError> {
+        self.put_large_as(LOCAL_REQUESTER, key, value)
+    }
+
+    /// [`JournalEngine::put_large`] on behalf of `requester_id` (see `AccessPolicy`).
+    pub fn put_large_as(
+        &mut self,
+        requester_id: u64,
+        key: &str,
+        value: &[u8],
+    ) -> Result<(), StatefsError> {
+        if value.len() <= MAX_VALUE_SIZE {
+            return self.put_as(requester_id, key, value);
+        }
+        self.check_put_len(requester_id, key, value.len(), MAX_LARGE_VALUE_SIZE)?;
+        let needed = value_record_bytes(key, value.len()) + TAIL_GUARD_LEN;
+        if self.write_pos.saturating_add(needed) > self.capacity() {
+            return Err(StatefsError::IoError);
+        }
+
+        let offset = self.write_pos;
+        for payload in chunk_payloads(value) {
+            if let Err(err) = self.append_record(JournalOpCode::PutChunk, key, &payload) {
+                // The chunks written so far belong to no value; replay drops them as well.
+                self.dead_bytes += self.write_pos - offset;
+                return Err(err);
+            }
+        }
+        self.commit_put(key, value, offset, self.durability)
+    }
+
+    /// Re-reads the chunk run of an evicted large value.
+    pub(crate) fn read_chunked(
+        &self,
+        key: &str,
+        slot: &ValueSlot,
+    ) -> Result<Vec<u8>, StatefsError> {
+        let count = chunk_count(slot.len);
+        let mut value = Vec::with_capacity(slot.len);
+        let mut pos = slot.offset;
+        for expected in 0..count {
+            let Some((record, consumed)) = self.record_at(pos)? else {
+                return Err(StatefsError::Corrupted);
+            };
+            match split_chunk(&record.value) {
+                Some((index, n, data))
+                    if record.op == JournalOpCode::PutChunk
+                        && record.key == key
+                        && usize::from(index) == expected
+                        && usize::from(n) == count =>
+                {
+                    value.extend_from_slice(data);
+                }
+                _ => return Err(StatefsError::Corrupted),
+            }
+            pos += consumed;
+        }
+        if value.len() != slot.len {
+            return Err(StatefsError::Corrupted);
+        }
+        Ok(value)
+    }
+
+    /// Applies a replayed `PutChunk` record at journal offset `offset`.
+    ///
+    /// The value is installed when the last chunk of a run arrives; a chunk that does not
+    /// continue `pending` ends that run. Returns `Corrupted` for an impossible chunk.
+    pub(crate) fn replay_chunk(
+        &mut self,
+        pending: &mut Option<PendingChunks>,
+        key: String,
+        payload: &[u8],
+        offset: usize,
+        consumed: usize,
+    ) -> Result<(), StatefsError> {
+        let (index, count, data) = split_chunk(payload).ok_or(StatefsError::Corrupted)?;
+        if index == 0 {
+            self.discard_chunks(pending);
+            *pending = Some(PendingChunks {
+                key,
+                count,
+                value: Vec::with_capacity(usize::from(count) * CHUNK_DATA_LEN),
+                offset,
+                journal_bytes: 0,
+                next: 0,
+            });
+        } else if !pending
+            .as_ref()
+            .is_some_and(|run| run.key == key && run.count == count && run.next == index)
+        {
+            // A chunk without its predecessors belongs to no value.
+            self.discard_chunks(pending);
+            self.dead_bytes += consumed;
+            return Ok(());
+        }
+
+        let Some(run) = pending.as_mut() else {
+            return Ok(());
+        };
+        run.value.extend_from_slice(data);
+        run.journal_bytes += consumed;
+        run.next += 1;
+        if run.next < run.count {
+            return Ok(());
+        }
+        let Some(run) = pending.take() else {
+            return Ok(());
+        };
+        if run.value.len() <= MAX_VALUE_SIZE || run.value.len() > MAX_LARGE_VALUE_SIZE {
+            return Err(StatefsError::Corrupted);
+        }
+        self.note_superseded(&run.key);
+        self.kv.insert(run.key, ValueSlot::new(run.value, run.offset, 0));
+        Ok(())
+    }
+
+    /// Drops an incomplete chunk run, counting its journal bytes as dead.
+    pub(crate) fn discard_chunks(&mut self, pending: &mut Option<PendingChunks>) {
+        if let Some(run) = pending.take() {
+            self.dead_bytes += run.journal_bytes;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ValueCache;
+    use alloc::vec;
+    use storage::MemBlockDevice;
+
+    const BUNDLE: &str = "/state/certs/bundle";
+
+    fn engine() -> JournalEngine<MemBlockDevice> {
+        JournalEngine::open(MemBlockDevice::new(4096, 320)).unwrap()
+    }
+
+    fn pattern(len: usize, seed: u8) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8 ^ seed).collect()
+    }
+
+    #[test]
+    fn large_value_spans_chunks_across_reopen_and_compaction() {
+        let mut engine = engine();
+        let bundle = pattern(3 * CHUNK_DATA_LEN + 100, 0);
+        engine.put("/state/certs/small", b"pem").unwrap();
+        let records = engine.stats().record_count;
+        engine.put_large(BUNDLE, &bundle).unwrap();
+        assert_eq!(engine.stats().record_count, records + 4);
+        assert_eq!(engine.get(BUNDLE).unwrap(), bundle);
+        // Values that fit one record keep the single-record path.
+        engine.put_large("/state/certs/small", b"pem2").unwrap();
+        assert_eq!(engine.stats().record_count, records + 5);
+
+        engine.sync().unwrap();
+        engine.reopen().unwrap();
+        assert_eq!(engine.get(BUNDLE).unwrap(), bundle);
+        let superseded = record_len("/state/certs/small", 3);
+        assert_eq!(engine.stats().dead_bytes_estimate, superseded as u64);
+        assert_eq!(engine.write_pos - engine.base, engine.live_bytes() + superseded);
+
+        // Overwrites, compaction and reloads of evicted values all walk the chunk run.
+        let updated = pattern(2 * CHUNK_DATA_LEN + 1, 0x5A);
+        engine.put_large(BUNDLE, &updated).unwrap();
+        assert!(engine.compact().unwrap() > 0);
+        assert_eq!(engine.stats().dead_bytes_estimate, 0);
+        engine.set_value_cache(Some(ValueCache { budget_bytes: 0, evict_above: 0 }));
+        assert_eq!(engine.get(BUNDLE).unwrap(), updated);
+        engine.reopen().unwrap();
+        assert_eq!(engine.get(BUNDLE).unwrap(), updated);
+        assert_eq!(engine.get("/state/certs/small").unwrap(), b"pem2");
+    }
+
+    #[test]
+    fn test_reject_partially_written_large_value_on_replay() {
+        let mut engine = engine();
+        let old = pattern(CHUNK_DATA_LEN + 10, 1);
+        engine.put_large(BUNDLE, &old).unwrap();
+        let start = engine.write_pos;
+
+        // Two of three chunks, then an unrelated put, then a run cut off at the tail.
+        let new = pattern(3 * CHUNK_DATA_LEN, 2);
+        for payload in chunk_payloads(&new).take(2) {
+            engine.append_record(JournalOpCode::PutChunk, BUNDLE, &payload).unwrap();
+        }
+        engine.put("/state/certs/other", b"x").unwrap();
+        for payload in chunk_payloads(&new).take(1) {
+            engine.append_record(JournalOpCode::PutChunk, BUNDLE, &payload).unwrap();
+        }
+        engine.sync().unwrap();
+        engine.reopen().unwrap();
+        assert_eq!(engine.get(BUNDLE).unwrap(), old);
+        assert_eq!(engine.get("/state/certs/other").unwrap(), b"x");
+        let orphaned = engine.write_pos - start - record_len("/state/certs/other", 1);
+        assert_eq!(engine.stats().dead_bytes_estimate, orphaned as u64);
+
+        // A complete run whose last chunk is damaged is not applied either.
+        engine.put_large(BUNDLE, &new).unwrap();
+        assert_eq!(engine.get(BUNDLE).unwrap(), new);
+        let byte = engine.write_pos - 10;
+        let mut device = engine.device;
+        device.raw_storage_mut()[byte / 4096][byte % 4096] ^= 0xFF;
+        let engine = JournalEngine::open(device).unwrap();
+        assert_eq!(engine.get(BUNDLE).unwrap(), old);
+    }
+
+    #[test]
+    fn test_reject_large_value_over_ceiling() {
+        let mut engine = engine();
+        let max = vec![0x5A; MAX_LARGE_VALUE_SIZE];
+        let over = vec![0x5A; MAX_LARGE_VALUE_SIZE + 1];
+        assert_eq!(engine.put_large(BUNDLE, &over), Err(StatefsError::ValueTooLarge));
+        // The plain path keeps its single-record cap.
+        assert_eq!(
+            engine.put(BUNDLE, &max[..MAX_VALUE_SIZE + 1]),
+            Err(StatefsError::ValueTooLarge)
+        );
+        engine.put_large(BUNDLE, &max).unwrap();
+        assert_eq!(engine.get(BUNDLE).unwrap().len(), MAX_LARGE_VALUE_SIZE);
+
+        // Keys, quotas and the value/list split are checked as for `put`.
+        let big = &max[..2 * CHUNK_DATA_LEN];
+        engine.set_quota("/state/fonts/", CHUNK_DATA_LEN * 3 / 2).unwrap();
+        assert_eq!(engine.put_large("/state/fonts/a", big), Err(StatefsError::QuotaExceeded));
+        engine.append("/state/log", b"e").unwrap();
+        assert_eq!(engine.put_large("/state/log", big), Err(StatefsError::InvalidKey));
+        assert_eq!(engine.put_large("/other/x", big), Err(StatefsError::InvalidKey));
+
+        // A value the device cannot hold fails before anything is written.
+        let write_pos = engine.write_pos;
+        assert_eq!(engine.put_large("/state/certs/copy", &max), Err(StatefsError::IoError));
+        assert_eq!(engine.write_pos, write_pos);
+    }
+}
@@ -0,0 +1,226 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: Write-through vs write-back durability for `JournalEngine::put`
+//! OWNERS: @runtime
+//! STATUS: Functional (host-first)
+//! TEST_COVERAGE: 4 unit tests (sync-counting device, sync seq, `OP_SYNC` response)
+//!
+//! A `put` always writes its journal blocks before returning; whether the device
+//! is also flushed is the durability mode. Under `WriteBack` (the default) data
+//! becomes durable at the next explicit `sync`. `WriteThrough` — engine-wide via
+//! `set_durability`, or for one call via `put_durable` — runs the full `sync`
+//! (device flush + superblock tail) before the put returns.
+//!
+//! [`JournalEngine::pending_sync`] says whether anything was written since the last
+//! successful `sync`, and [`JournalEngine::last_sync_seq`] counts those syncs. statefsd
+//! returns the new count in the `OP_SYNC` response:
+//! `[S, F, ver, OP_SYNC|0x80, status, (nonce:u64 if v2), sync_seq:u64 if OK]`.
+
+use alloc::vec::Vec;
+
+use storage::BlockDevice;
+
+use crate::access::LOCAL_REQUESTER;
+use crate::protocol::{
+    encode_status_response_with_nonce, error_from_status, MAGIC0, MAGIC1, OP_SYNC, STATUS_OK,
+    VERSION, VERSION_V2,
+};
+use crate::{JournalEngine, StatefsError};
+
+/// When a `put` reaches durable storage.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DurabilityMode {
+    /// Blocks are written; durability waits for the next `sync`.
+    #[default]
+    WriteBack,
+    /// Every `put` is synced before it returns.
+    WriteThrough,
+}
+
+impl<B: BlockDevice> JournalEngine<B> {
+    /// Set the durability mode applied to every subsequent `put`.
+    pub fn set_durability(&mut self, mode: DurabilityMode) {
+        self.durability = mode;
+    }
+
+    /// Current durability mode (see [`JournalEngine::set_durability`]).
+    pub fn durability(&self) -> DurabilityMode {
+        self.durability
+    }
+
+    /// Put a key-value pair and sync before returning, whatever the engine's mode.
+    ///
+    /// If the sync fails the value is already visible to `get` but may not survive
+    /// power loss; the caller sees `IoError`.
+    pub fn put_durable(&mut self, key: &str, value: &[u8]) -> Result<(), StatefsError> {
+        self.put_with(LOCAL_REQUESTER, key, value, DurabilityMode::WriteThrough)
+    }
+
+    /// [`JournalEngine::put_durable`] on behalf of `requester_id` (see [`crate::AccessPolicy`]).
+    pub fn put_durable_as(
+        &mut self,
+        requester_id: u64,
+        key: &str,
+        value: &[u8],
+    ) -> Result<(), StatefsError> {
+        self.put_with(requester_id, key, value, DurabilityMode::WriteThrough)
+    }
+
+    /// Whether journal blocks (a mutation, or compaction) were written since the
+    /// last successful `sync`; `false` means everything written is durable.
+    pub fn pending_sync(&self) -> bool {
+        self.dirty
+    }
+
+    /// Successful `sync` calls since the engine was opened (monotonic, volatile).
+    pub fn last_sync_seq(&self) -> u64 {
+        self.sync_seq
+    }
+}
+
+/// `OP_SYNC` response; `sync_seq` is appended only when `status` is `STATUS_OK`.
+pub fn encode_sync_response_with_nonce(status: u8, sync_seq: u64, nonce: Option<u64>) -> Vec<u8> {
+    let mut out = encode_status_response_with_nonce(OP_SYNC, status, nonce);
+    if status == STATUS_OK {
+        out.extend_from_slice(&sync_seq.to_le_bytes());
+    }
+    out
+}
+
+/// Decode an `OP_SYNC` response into the daemon's sync seq.
+pub fn decode_sync_response(frame: &[u8]) -> Result<u64, StatefsError> {
+    if frame.len() < 5 || frame[0] != MAGIC0 || frame[1] != MAGIC1 {
+        return Err(StatefsError::Corrupted);
+    }
+    if frame[3] != (OP_SYNC | 0x80) {
+        return Err(StatefsError::Corrupted);
+    }
+    let body = match frame[2] {
+        VERSION => &frame[5..],
+        VERSION_V2 if frame.len() >= 13 => &frame[13..],
+        _ => return Err(StatefsError::Corrupted),
+    };
+    if frame[4] != STATUS_OK {
+        return Err(error_from_status(frame[4]));
+    }
+    let seq: [u8; 8] = body.try_into().map_err(|_| StatefsError::Corrupted)?;
+    Ok(u64::from_le_bytes(seq))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use storage::{BlockError, MemBlockDevice};
+
+    /// Counts `sync` calls on the wrapped device.
+    struct SyncCounting {
+        inner: MemBlockDevice,
+        syncs: usize,
+    }
+
+    impl BlockDevice for SyncCounting {
+        fn block_size(&self) -> usize {
+            self.inner.block_size()
+        }
+        fn block_count(&self) -> u64 {
+            self.inner.block_count()
+        }
+        fn read_block(&self, block_idx: u64, buf: &mut [u8]) -> Result<(), BlockError> {
+            self.inner.read_block(block_idx, buf)
+        }
+        fn write_block(&mut self, block_idx: u64, buf: &[u8]) -> Result<(), BlockError> {
+            self.inner.write_block(block_idx, buf)
+        }
+        fn sync(&mut self) -> Result<(), BlockError> {
+            self.syncs += 1;
+            self.inner.sync()
+        }
+    }
+
+    fn engine() -> JournalEngine<SyncCounting> {
+        let device = SyncCounting { inner: MemBlockDevice::new(512, 64), syncs: 0 };
+        JournalEngine::open(device).unwrap()
+    }
+
+    #[test]
+    fn write_back_put_does_not_sync() {
+        let mut engine = engine();
+        assert_eq!(engine.durability(), DurabilityMode::WriteBack);
+        engine.put("/state/k", b"v").unwrap();
+        assert_eq!(engine.device.syncs, 0);
+
+        engine.put_durable("/state/k", b"v2").unwrap();
+        let after_durable = engine.device.syncs;
+        assert!(after_durable > 0);
+        engine.put("/state/k", b"v3").unwrap();
+        assert_eq!(engine.device.syncs, after_durable);
+    }
+
+    #[test]
+    fn write_through_put_syncs_every_call() {
+        let mut engine = engine();
+        engine.set_durability(DurabilityMode::WriteThrough);
+        engine.put("/state/a", b"1").unwrap();
+        let per_put = engine.device.syncs;
+        assert!(per_put > 0);
+        engine.put("/state/b", b"2").unwrap();
+        assert_eq!(engine.device.syncs, 2 * per_put);
+
+        // A rejected put writes nothing and so does not sync.
+        assert_eq!(engine.put("/other/c", b"3"), Err(StatefsError::InvalidKey));
+        assert_eq!(engine.device.syncs, 2 * per_put);
+        assert_eq!(engine.get("/state/b").unwrap(), b"2");
+    }
+
+    #[test]
+    fn mutations_mark_pending_until_sync_bumps_seq() {
+        let mut engine = engine();
+        assert!(!engine.pending_sync());
+        assert_eq!(engine.last_sync_seq(), 0);
+
+        engine.put("/state/a", b"1").unwrap();
+        assert!(engine.pending_sync());
+        engine.sync().unwrap();
+        assert!(!engine.pending_sync());
+        assert_eq!(engine.last_sync_seq(), 1);
+
+        // A rejected mutation writes nothing.
+        assert_eq!(engine.delete("/state/missing"), Err(StatefsError::NotFound));
+        assert!(!engine.pending_sync());
+
+        for (key, mutate) in [("/state/a", true), ("/state/l", false)] {
+            if mutate {
+                engine.delete(key).unwrap();
+            } else {
+                engine.append(key, b"entry").unwrap();
+            }
+            assert!(engine.pending_sync());
+            engine.sync().unwrap();
+            assert!(!engine.pending_sync());
+        }
+        assert_eq!(engine.last_sync_seq(), 3);
+
+        // A clean sync still counts; write-through leaves nothing pending.
+        engine.sync().unwrap();
+        assert_eq!(engine.last_sync_seq(), 4);
+        engine.put_durable("/state/b", b"2").unwrap();
+        assert!(!engine.pending_sync());
+        assert_eq!(engine.last_sync_seq(), 5);
+    }
+
+    #[test]
+    fn sync_response_carries_seq() {
+        let v1 = encode_sync_response_with_nonce(STATUS_OK, 7, None);
+        assert_eq!(decode_sync_response(&v1), Ok(7));
+        let v2 = encode_sync_response_with_nonce(STATUS_OK, u64::MAX, Some(9));
+        assert_eq!(decode_sync_response(&v2), Ok(u64::MAX));
+
+        let failed = encode_sync_response_with_nonce(crate::protocol::STATUS_IO_ERROR, 7, None);
+        assert_eq!(failed.len(), 5);
+        assert_eq!(decode_sync_response(&failed), Err(StatefsError::IoError));
+        // An OK without the seq (or with a short one) is malformed.
+        assert_eq!(decode_sync_response(&v1[..5]), Err(StatefsError::Corrupted));
+        assert_eq!(decode_sync_response(&v1[..v1.len() - 1]), Err(StatefsError::Corrupted));
+    }
+}
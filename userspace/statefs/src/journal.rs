@@ -0,0 +1,574 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: Journal record format and the replaying `JournalEngine`
+//! OWNERS: @runtime
+//! STATUS: Functional (host-first)
+//! TEST_COVERAGE: Host unit tests in the crate root
+
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cell::Cell;
+
+use storage::BlockDevice;
+
+use crate::access::{AccessPolicy, AllowAll, Op, LOCAL_REQUESTER};
+use crate::append::AppendList;
+use crate::changes::ChangeLog;
+use crate::checksum::{Checksum, RecordChecksum};
+use crate::compact::{AutoCompact, Compaction};
+use crate::durability::DurabilityMode;
+use crate::quota::Quota;
+use crate::replay::ReplayProgress;
+use crate::stats::record_len;
+use crate::value_cache::{ValueCache, ValueSlot};
+use crate::{
+    StatefsError, DEFAULT_ROOT, JOURNAL_MAGIC, MAX_KEY_LEN, MAX_VALUE_SIZE, RECORD_HEADER_SIZE,
+};
+
+/// Zeroed bytes written after every record so replay stops at the new tail even
+/// when the region past it still holds records from before a relocation.
+pub(crate) const TAIL_GUARD_LEN: usize = 4;
+
+// ============================================================================
+// Journal Record Format
+// ============================================================================
+
+/// Journal operation codes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum JournalOpCode {
+    Put = 0x01,
+    Delete = 0x02,
+    Checkpoint = 0x03,
+    Append = 0x04,
+    DeletePrefix = 0x05,
+    Barrier = 0x06,
+    PutChunk = 0x07,
+}
+
+impl JournalOpCode {
+    fn from_u8(v: u8) -> Option<Self> {
+        match v {
+            0x01 => Some(Self::Put),
+            0x02 => Some(Self::Delete),
+            0x03 => Some(Self::Checkpoint),
+            0x04 => Some(Self::Append),
+            0x05 => Some(Self::DeletePrefix),
+            0x06 => Some(Self::Barrier),
+            0x07 => Some(Self::PutChunk),
+            _ => None,
+        }
+    }
+}
+
+/// A parsed journal record
+#[derive(Debug, Clone)]
+pub(crate) struct JournalRecord {
+    pub(crate) op: JournalOpCode,
+    pub(crate) key: String,
+    pub(crate) value: Vec<u8>,
+}
+
+/// Serialize a journal record to bytes (including CRC32).
+pub(crate) fn serialize_record(
+    checksum: RecordChecksum,
+    op: JournalOpCode,
+    key: &str,
+    value: &[u8],
+) -> Vec<u8> {
+    let key_bytes = key.as_bytes();
+    let key_len = key_bytes.len() as u16;
+    let value_len = value.len() as u32;
+
+    // Calculate total size: header + key + value + crc
+    let total_len = RECORD_HEADER_SIZE + key_bytes.len() + value.len();
+    let mut buf = vec![0u8; total_len];
+
+    // Magic (4 bytes, little-endian)
+    buf[0..4].copy_from_slice(&JOURNAL_MAGIC.to_le_bytes());
+
+    // OpCode (1 byte)
+    buf[4] = op as u8;
+
+    // KeyLen (2 bytes, little-endian)
+    buf[5..7].copy_from_slice(&key_len.to_le_bytes());
+
+    // ValueLen (4 bytes, little-endian)
+    buf[7..11].copy_from_slice(&value_len.to_le_bytes());
+
+    // Key
+    let key_start = 11;
+    let key_end = key_start + key_bytes.len();
+    buf[key_start..key_end].copy_from_slice(key_bytes);
+
+    // Value
+    let value_start = key_end;
+    let value_end = value_start + value.len();
+    buf[value_start..value_end].copy_from_slice(value);
+
+    // CRC32 over [magic..value] (everything except the CRC itself)
+    let crc = checksum.sum(&buf[..value_end]);
+    buf[value_end..value_end + 4].copy_from_slice(&crc.to_le_bytes());
+
+    buf
+}
+
+/// Try to parse a journal record from a byte slice.
+/// Returns (record, bytes_consumed) on success.
+pub(crate) fn parse_record(
+    checksum: RecordChecksum,
+    data: &[u8],
+) -> Result<Option<(JournalRecord, usize)>, StatefsError> {
+    // Need at least header size
+    if data.len() < RECORD_HEADER_SIZE {
+        return Ok(None);
+    }
+
+    // Check magic
+    let magic = u32::from_le_bytes([data[0], data[1], data[2], data[3]]);
+    if magic != JOURNAL_MAGIC {
+        // Not a valid record start; might be end of journal
+        return Ok(None);
+    }
+
+    // Parse header
+    let op_byte = data[4];
+    let op = JournalOpCode::from_u8(op_byte).ok_or(StatefsError::Corrupted)?;
+
+    let key_len = u16::from_le_bytes([data[5], data[6]]) as usize;
+    let value_len = u32::from_le_bytes([data[7], data[8], data[9], data[10]]) as usize;
+
+    // Validate lengths
+    if key_len > MAX_KEY_LEN || value_len > MAX_VALUE_SIZE {
+        return Err(StatefsError::Corrupted);
+    }
+
+    let total_len = RECORD_HEADER_SIZE + key_len + value_len;
+    if data.len() < total_len {
+        // Truncated record
+        return Ok(None);
+    }
+
+    // Extract key and value
+    let key_start = 11;
+    let key_end = key_start + key_len;
+    let key_bytes = &data[key_start..key_end];
+
+    let value_start = key_end;
+    let value_end = value_start + value_len;
+    let value = &data[value_start..value_end];
+
+    // Verify CRC
+    let crc_start = value_end;
+    if data.len() < crc_start + 4 {
+        return Ok(None);
+    }
+    let stored_crc = u32::from_le_bytes([
+        data[crc_start],
+        data[crc_start + 1],
+        data[crc_start + 2],
+        data[crc_start + 3],
+    ]);
+    let computed_crc = checksum.sum(&data[..value_end]);
+    if stored_crc != computed_crc {
+        return Err(StatefsError::Corrupted);
+    }
+
+    // Parse key as UTF-8
+    let key = core::str::from_utf8(key_bytes).map_err(|_| StatefsError::Corrupted)?.into();
+
+    Ok(Some((JournalRecord { op, key, value: value.to_vec() }, total_len)))
+}
+
+// ============================================================================
+// JournalEngine
+// ============================================================================
+
+/// Journaled key-value store engine.
+pub struct JournalEngine<B: BlockDevice> {
+    pub(crate) device: B,
+    /// In-memory key-value map (populated from journal replay; see `value_cache`)
+    pub(crate) kv: BTreeMap<String, ValueSlot>,
+    /// Append lists (see `JournalEngine::append`), disjoint from `kv`
+    pub(crate) lists: BTreeMap<String, AppendList>,
+    /// Current write position in the journal (byte offset)
+    pub(crate) write_pos: usize,
+    /// Number of records replayed (for bounded replay check)
+    pub(crate) record_count: usize,
+    /// Start of the live journal region (the origin until the first compaction relocates it)
+    pub(crate) base: usize,
+    /// Pre-superblock image: the journal starts at block 0 (see `superblock`)
+    pub(crate) legacy_layout: bool,
+    /// In-flight incremental compaction, if any
+    pub(crate) compaction: Option<Compaction>,
+    /// Fill-watermark trigger for incremental compaction
+    pub(crate) auto_compact: Option<AutoCompact>,
+    /// Superblock cross-check outcome from the last open/reopen
+    pub(crate) superblock_diag: Option<StatefsError>,
+    /// Journal bytes superseded since the live region began (see `JournalStats`)
+    pub(crate) dead_bytes: usize,
+    /// Namespace root every key must live under, with a trailing `/`
+    root: String,
+    /// Whether `put` syncs before returning (see `JournalEngine::set_durability`)
+    pub(crate) durability: DurabilityMode,
+    /// Record checksum algorithm (see `JournalEngine::open_with_checksum`)
+    pub(crate) checksum: RecordChecksum,
+    /// Journal blocks written since the last successful `sync`
+    pub(crate) dirty: bool,
+    /// Successful `sync` calls since open (see `JournalEngine::last_sync_seq`)
+    pub(crate) sync_seq: u64,
+    /// Per-prefix byte quotas (see `JournalEngine::set_quota`); kept across `reopen`
+    pub(crate) quotas: Vec<Quota>,
+    /// Access policy (see `JournalEngine::open_with_policy`); kept across `reopen`
+    pub(crate) access: Box<dyn AccessPolicy>,
+    /// Value memory budget (see `JournalEngine::set_value_cache`); kept across `reopen`
+    pub(crate) value_cache: Option<ValueCache>,
+    /// LRU clock for cached values
+    pub(crate) lru_clock: Cell<u64>,
+    /// Change sequence and per-key stamps (see `JournalEngine::changed_since`)
+    pub(crate) changes: ChangeLog,
+    /// Metrics sink injected by `JournalEngine::open_with_metrics`
+    #[cfg(feature = "metrics")]
+    pub(crate) metrics: Option<alloc::rc::Rc<dyn nexus_metrics::MetricsSink>>,
+}
+
+impl<B: BlockDevice> JournalEngine<B> {
+    /// Create a new journal engine and replay existing journal from device.
+    ///
+    /// The replayed tail is cross-checked against the superblock afterwards;
+    /// see [`JournalEngine::superblock_diagnostic`].
+    pub fn open(device: B) -> Result<Self, StatefsError> {
+        Self::open_with_root(device, DEFAULT_ROOT)
+    }
+
+    /// Like [`JournalEngine::open`], but keys are rooted at `root` instead of `/state/`.
+    ///
+    /// `root` must be an absolute path other than `/`, without `.`/`..` segments; a
+    /// missing trailing `/` is added. Otherwise returns `InvalidKey`.
+    pub fn open_with_root(device: B, root: &str) -> Result<Self, StatefsError> {
+        Self::open_inner(device, root, RecordChecksum::CRC32C, &mut |_| {})
+    }
+
+    /// Like [`JournalEngine::open`], but records are checksummed with `C` instead of CRC32-C.
+    ///
+    /// A device synced under another algorithm fails with `Corrupted`.
+    pub fn open_with_checksum<C: Checksum>(device: B, _checksum: C) -> Result<Self, StatefsError> {
+        Self::open_inner(device, DEFAULT_ROOT, RecordChecksum::of::<C>(), &mut |_| {})
+    }
+
+    pub(crate) fn open_inner(
+        device: B,
+        root: &str,
+        checksum: RecordChecksum,
+        progress: &mut dyn FnMut(ReplayProgress),
+    ) -> Result<Self, StatefsError> {
+        let root = Self::canonical_root(root)?;
+        let legacy_layout = Self::detect_legacy_layout(&device)?;
+        let start = if legacy_layout { 0 } else { device.block_size() };
+        let mut engine = Self {
+            device,
+            kv: BTreeMap::new(),
+            lists: BTreeMap::new(),
+            write_pos: start,
+            record_count: 0,
+            base: start,
+            legacy_layout,
+            compaction: None,
+            auto_compact: None,
+            superblock_diag: None,
+            dead_bytes: 0,
+            root,
+            durability: DurabilityMode::default(),
+            checksum,
+            dirty: false,
+            sync_seq: 0,
+            quotas: Vec::new(),
+            access: Box::new(AllowAll),
+            value_cache: None,
+            lru_clock: Cell::new(0),
+            changes: ChangeLog::default(),
+            #[cfg(feature = "metrics")]
+            metrics: None,
+        };
+        engine.replay(progress)?;
+        engine.restamp_changes();
+        engine.check_superblock()?;
+        Ok(engine)
+    }
+
+    /// Byte offset of the journal origin (block 1; block 0 holds the superblock, except on a
+    /// legacy image where the journal starts at 0).
+    pub(crate) fn journal_start(&self) -> usize {
+        if self.legacy_layout {
+            0
+        } else {
+            self.device.block_size()
+        }
+    }
+
+    /// Namespace root keys are validated against (e.g. `/state/`).
+    pub fn root(&self) -> &str {
+        &self.root
+    }
+
+    fn canonical_root(root: &str) -> Result<String, StatefsError> {
+        let mut root = String::from(root);
+        if !root.ends_with('/') {
+            root.push('/');
+        }
+        if root.len() < 3 || root.len() >= MAX_KEY_LEN || !root.starts_with('/') {
+            return Err(StatefsError::InvalidKey);
+        }
+        Self::reject_traversal(&root)?;
+        Ok(root)
+    }
+
+    /// Validate a key path.
+    pub(crate) fn validate_key(&self, key: &str) -> Result<(), StatefsError> {
+        if key.len() > MAX_KEY_LEN {
+            return Err(StatefsError::KeyTooLong);
+        }
+        if !key.starts_with(self.root.as_str()) {
+            return Err(StatefsError::InvalidKey);
+        }
+        Self::reject_traversal(key)
+    }
+
+    fn reject_traversal(path: &str) -> Result<(), StatefsError> {
+        if path.contains("/../")
+            || path.contains("/./")
+            || path.ends_with("/..")
+            || path.ends_with("/.")
+        {
+            return Err(StatefsError::InvalidKey);
+        }
+        Ok(())
+    }
+
+    /// Total journal capacity of the device in bytes.
+    pub(crate) fn capacity(&self) -> usize {
+        self.device.block_size().saturating_mul(self.device.block_count() as usize)
+    }
+
+    /// Write `bytes` at byte offset `pos` (read-modify-write), followed by a
+    /// [`TAIL_GUARD_LEN`] zero guard clamped to the device end.
+    ///
+    /// Returns the offset just past `bytes`.
+    pub(crate) fn write_at(&mut self, pos: usize, bytes: &[u8]) -> Result<usize, StatefsError> {
+        let block_size = self.device.block_size();
+        let capacity = self.capacity();
+        let end = pos
+            .checked_add(bytes.len())
+            .filter(|&end| end <= capacity)
+            .ok_or(StatefsError::IoError)?;
+        let guard_end = end.saturating_add(TAIL_GUARD_LEN).min(capacity);
+
+        // Read-modify-write for blocks that span the write
+        let mut buf = vec![0u8; block_size];
+        for block_idx in pos / block_size..guard_end.div_ceil(block_size) {
+            self.device
+                .read_block(block_idx as u64, &mut buf)
+                .map_err(|_| StatefsError::IoError)?;
+
+            let block_start = block_idx * block_size;
+            let lo = pos.max(block_start);
+            let hi = guard_end.min(block_start + block_size);
+            let data_hi = end.clamp(lo, hi);
+            if lo < data_hi {
+                buf[lo - block_start..data_hi - block_start]
+                    .copy_from_slice(&bytes[lo - pos..data_hi - pos]);
+            }
+            buf[data_hi - block_start..hi - block_start].fill(0);
+
+            self.dirty = true;
+            self.device.write_block(block_idx as u64, &buf).map_err(|_| StatefsError::IoError)?;
+        }
+        Ok(end)
+    }
+
+    /// Write a record to the journal (and to an in-flight compaction shadow).
+    pub(crate) fn append_record(
+        &mut self,
+        op: JournalOpCode,
+        key: &str,
+        value: &[u8],
+    ) -> Result<(), StatefsError> {
+        let record_bytes = serialize_record(self.checksum, op, key, value);
+        self.yield_to_live_append(self.write_pos.saturating_add(record_bytes.len()));
+        self.write_pos = self.write_at(self.write_pos, &record_bytes)?;
+        self.record_count += 1;
+        self.mirror_to_shadow(key, &record_bytes);
+        Ok(())
+    }
+
+    /// Put a key-value pair, synced first under [`DurabilityMode::WriteThrough`].
+    pub fn put(&mut self, key: &str, value: &[u8]) -> Result<(), StatefsError> {
+        self.put_as(LOCAL_REQUESTER, key, value)
+    }
+
+    /// [`JournalEngine::put`] on behalf of `requester_id` (see [`AccessPolicy`]).
+    pub fn put_as(
+        &mut self,
+        requester_id: u64,
+        key: &str,
+        value: &[u8],
+    ) -> Result<(), StatefsError> {
+        self.put_with(requester_id, key, value, self.durability)
+    }
+
+    pub(crate) fn put_with(
+        &mut self,
+        requester_id: u64,
+        key: &str,
+        value: &[u8],
+        mode: DurabilityMode,
+    ) -> Result<(), StatefsError> {
+        self.check_put(requester_id, key, value)?;
+
+        // Append to journal
+        let offset = self.write_pos;
+        self.append_record(JournalOpCode::Put, key, value)?;
+        self.commit_put(key, value, offset, mode)
+    }
+
+    /// Installs a journaled value whose first record starts at `offset`.
+    pub(crate) fn commit_put(
+        &mut self,
+        key: &str,
+        value: &[u8],
+        offset: usize,
+        mode: DurabilityMode,
+    ) -> Result<(), StatefsError> {
+        let before = self.stored_bytes(key);
+        self.note_superseded(key);
+        self.kv.insert(key.into(), ValueSlot::new(value.to_vec(), offset, self.lru_tick()));
+        self.note_change_put(key);
+        self.enforce_value_cache();
+        self.charge_quota(key, before);
+        self.auto_compact_step();
+        self.note_put();
+        match mode {
+            DurabilityMode::WriteBack => Ok(()),
+            DurabilityMode::WriteThrough => self.sync(),
+        }
+    }
+
+    /// Get a value by key.
+    pub fn get(&self, key: &str) -> Result<Vec<u8>, StatefsError> {
+        self.get_as(LOCAL_REQUESTER, key)
+    }
+
+    /// [`JournalEngine::get`] on behalf of `requester_id` (see [`AccessPolicy`]).
+    pub fn get_as(&self, requester_id: u64, key: &str) -> Result<Vec<u8>, StatefsError> {
+        self.validate_key(key)?;
+        self.check_access(requester_id, Op::Get, key)?;
+        let Some(slot) = self.kv.get(key) else {
+            self.note_get_miss();
+            return Err(StatefsError::NotFound);
+        };
+        self.slot_value(key, slot)
+    }
+
+    /// Delete a key (a plain value or an append list).
+    pub fn delete(&mut self, key: &str) -> Result<(), StatefsError> {
+        self.delete_as(LOCAL_REQUESTER, key)
+    }
+
+    /// [`JournalEngine::delete`] on behalf of `requester_id` (see [`AccessPolicy`]).
+    pub fn delete_as(&mut self, requester_id: u64, key: &str) -> Result<(), StatefsError> {
+        self.validate_key(key)?;
+        self.check_access(requester_id, Op::Delete, key)?;
+        if !self.kv.contains_key(key) && !self.lists.contains_key(key) {
+            return Err(StatefsError::NotFound);
+        }
+
+        // Append to journal
+        self.append_record(JournalOpCode::Delete, key, &[])?;
+
+        // Update in-memory state
+        let before = self.stored_bytes(key);
+        self.note_superseded(key);
+        self.dead_bytes += record_len(key, 0);
+        self.kv.remove(key);
+        self.lists.remove(key);
+        self.charge_quota(key, before);
+        self.note_change_delete(key);
+        self.auto_compact_step();
+        self.note_delete();
+        Ok(())
+    }
+
+    /// List keys (plain values and append lists) matching a prefix, in key order.
+    pub fn list(&self, prefix: &str, limit: usize) -> Result<Vec<String>, StatefsError> {
+        self.list_as(LOCAL_REQUESTER, prefix, limit)
+    }
+
+    /// [`JournalEngine::list`] on behalf of `requester_id`, leaving out keys the
+    /// [`AccessPolicy`] hides from it.
+    pub fn list_as(
+        &self,
+        requester_id: u64,
+        prefix: &str,
+        limit: usize,
+    ) -> Result<Vec<String>, StatefsError> {
+        if !prefix.starts_with(self.root.as_str()) && prefix != self.root.trim_end_matches('/') {
+            return Err(StatefsError::InvalidKey);
+        }
+        self.check_access(requester_id, Op::List, prefix)?;
+
+        let visible =
+            |k: &&String| k.starts_with(prefix) && self.access.allow(requester_id, Op::List, k);
+        let mut keys: Vec<String> = self
+            .kv
+            .keys()
+            .filter(visible)
+            .take(limit)
+            .chain(self.lists.keys().filter(visible).take(limit))
+            .cloned()
+            .collect();
+        keys.sort_unstable();
+        keys.truncate(limit);
+
+        Ok(keys)
+    }
+
+    /// Sync all pending writes to durable storage, then record the tail in the superblock.
+    pub fn sync(&mut self) -> Result<(), StatefsError> {
+        self.device.sync().map_err(|_| StatefsError::IoError)?;
+        self.write_superblock()?;
+        self.device.sync().map_err(|_| StatefsError::IoError)?;
+        self.dirty = false;
+        self.sync_seq = self.sync_seq.saturating_add(1);
+        Ok(())
+    }
+
+    /// Reopen the journal by replaying from the current device.
+    pub fn reopen(&mut self) -> Result<(), StatefsError> {
+        self.kv.clear();
+        self.lists.clear();
+        self.write_pos = self.journal_start();
+        self.record_count = 0;
+        self.dead_bytes = 0;
+        self.base = self.journal_start();
+        self.compaction = None;
+        self.replay(&mut |_| {})?;
+        self.restamp_changes();
+        self.enforce_value_cache();
+        self.note_replayed();
+        self.check_superblock()
+    }
+
+    /// Get the number of keys in the store (append lists included).
+    pub fn len(&self) -> usize {
+        self.kv.len() + self.lists.len()
+    }
+
+    /// Check if the store is empty.
+    pub fn is_empty(&self) -> bool {
+        self.kv.is_empty() && self.lists.is_empty()
+    }
+}
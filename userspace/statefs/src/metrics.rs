@@ -0,0 +1,158 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: Optional engine instrumentation reported through a `nexus_metrics` sink
+//! OWNERS: @runtime
+//! STATUS: Experimental
+//! TEST_COVERAGE: 2 unit tests with feature `metrics` (put/get/delete/replay events, failed ops)
+//!
+//! With feature `metrics`, [`JournalEngine::open_with_metrics`] injects a
+//! [`MetricsSink`] — the IPC client to metricsd on the OS, a `RefCell<HostBackend>` in
+//! host tests. The engine then reports:
+//! - `statefs.put`, `statefs.delete`: one increment per successful mutation
+//! - `statefs.get.miss`: one increment per `get` answered `NotFound`
+//! - `statefs.replay.records`: records replayed by the open or a `reopen`
+//! - `statefs.fill_ratio_milli`: `write_pos` per mille of the device, after each of the above
+//!
+//! INVARIANTS:
+//! - Best effort: sink errors are ignored and never change an operation's result
+//! - Without the feature, or opened without a sink, the engine behaves and stores the same
+//! - No keys or values are reported, only counts and the fill ratio
+
+#[cfg(feature = "metrics")]
+use alloc::rc::Rc;
+
+#[cfg(feature = "metrics")]
+use nexus_metrics::MetricsSink;
+use storage::BlockDevice;
+
+use crate::JournalEngine;
+#[cfg(feature = "metrics")]
+use crate::StatefsError;
+
+/// Counter: successful puts.
+pub const METRIC_PUT: &str = "statefs.put";
+/// Counter: gets answered `NotFound`.
+pub const METRIC_GET_MISS: &str = "statefs.get.miss";
+/// Counter: successful deletes.
+pub const METRIC_DELETE: &str = "statefs.delete";
+/// Counter: records replayed on open/reopen.
+pub const METRIC_REPLAY_RECORDS: &str = "statefs.replay.records";
+/// Gauge: journal fill in thousandths of the device capacity.
+pub const METRIC_FILL_RATIO_MILLI: &str = "statefs.fill_ratio_milli";
+
+impl<B: BlockDevice> JournalEngine<B> {
+    /// Like [`JournalEngine::open`], reporting engine activity to `sink`.
+    ///
+    /// The records replayed by this open are reported once it has succeeded.
+    #[cfg(feature = "metrics")]
+    pub fn open_with_metrics(device: B, sink: Rc<dyn MetricsSink>) -> Result<Self, StatefsError> {
+        let mut engine = Self::open(device)?;
+        engine.metrics = Some(sink);
+        engine.note_replayed();
+        Ok(engine)
+    }
+
+    pub(crate) fn note_put(&self) {
+        self.emit(METRIC_PUT, 1);
+    }
+
+    pub(crate) fn note_get_miss(&self) {
+        self.emit(METRIC_GET_MISS, 1);
+    }
+
+    pub(crate) fn note_delete(&self) {
+        self.emit(METRIC_DELETE, 1);
+    }
+
+    pub(crate) fn note_replayed(&self) {
+        self.emit(METRIC_REPLAY_RECORDS, self.record_count as u64);
+    }
+
+    /// Adds `delta` (when non-zero) to `counter`, then refreshes the fill gauge.
+    fn emit(&self, counter: &str, delta: u64) {
+        #[cfg(feature = "metrics")]
+        if let Some(sink) = &self.metrics {
+            if delta > 0 {
+                let _ = sink.counter_inc(counter, b"", delta);
+            }
+            let fill =
+                (self.write_pos as u64).saturating_mul(1000) / (self.capacity() as u64).max(1);
+            let _ = sink.gauge_set(METRIC_FILL_RATIO_MILLI, b"", fill as i64);
+        }
+        #[cfg(not(feature = "metrics"))]
+        let _ = (counter, delta);
+    }
+}
+
+#[cfg(all(test, feature = "metrics"))]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+    use core::cell::RefCell;
+    use nexus_metrics::host::{Event, HostBackend};
+    use storage::MemBlockDevice;
+
+    fn counter(name: &str, delta: u64) -> Event {
+        Event::Counter { name: name.as_bytes().to_vec(), labels: Vec::new(), delta }
+    }
+
+    fn fill(engine: &JournalEngine<MemBlockDevice>) -> Event {
+        let value = (engine.write_pos * 1000 / engine.capacity()) as i64;
+        Event::Gauge {
+            name: METRIC_FILL_RATIO_MILLI.as_bytes().to_vec(),
+            labels: Vec::new(),
+            value,
+        }
+    }
+
+    #[test]
+    fn put_get_delete_and_replay_land_in_the_backend() {
+        let backend = Rc::new(RefCell::new(HostBackend::new()));
+        let mut engine =
+            JournalEngine::open_with_metrics(MemBlockDevice::new(128, 64), backend.clone())
+                .unwrap();
+        // An empty journal replays nothing: only the fill gauge is reported.
+        assert_eq!(backend.borrow().events(), [fill(&engine)]);
+
+        engine.put("/state/a", b"one").unwrap();
+        let after_put = fill(&engine);
+        assert_eq!(engine.get("/state/a").unwrap(), b"one");
+        assert_eq!(engine.get("/state/b"), Err(StatefsError::NotFound));
+        engine.delete("/state/a").unwrap();
+        assert_eq!(
+            backend.borrow().events()[1..],
+            [
+                counter(METRIC_PUT, 1),
+                after_put.clone(),
+                counter(METRIC_GET_MISS, 1),
+                after_put,
+                counter(METRIC_DELETE, 1),
+                fill(&engine),
+            ]
+        );
+
+        let replay = Rc::new(RefCell::new(HostBackend::new()));
+        let reopened = JournalEngine::open_with_metrics(engine.device, replay.clone()).unwrap();
+        assert_eq!(replay.borrow().events(), [counter(METRIC_REPLAY_RECORDS, 2), fill(&reopened)]);
+    }
+
+    #[test]
+    fn test_reject_failed_operations_emit_nothing() {
+        let backend = Rc::new(RefCell::new(HostBackend::new()));
+        let mut engine =
+            JournalEngine::open_with_metrics(MemBlockDevice::new(128, 64), backend.clone())
+                .unwrap();
+        let baseline = backend.borrow().events().len();
+
+        assert_eq!(engine.put("/other/a", b"x"), Err(StatefsError::InvalidKey));
+        assert_eq!(engine.delete("/state/missing"), Err(StatefsError::NotFound));
+        assert_eq!(engine.get("/state/../etc"), Err(StatefsError::InvalidKey));
+        assert_eq!(backend.borrow().events().len(), baseline);
+
+        // A plain open carries no sink and still serves the same data.
+        engine.put("/state/a", b"x").unwrap();
+        let plain = JournalEngine::open(engine.device).unwrap();
+        assert_eq!(plain.get("/state/a").unwrap(), b"x");
+    }
+}
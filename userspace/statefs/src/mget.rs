@@ -0,0 +1,265 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: Bulk get — several keys in one statefsd round trip (`OP_MGET`)
+//! OWNERS: @runtime
+//! STATUS: Functional (host-first)
+//! TEST_COVERAGE: 3 unit tests (mixed hit/miss order, truncation, malformed frames)
+//!
+//! Request: `[S, F, ver, OP_MGET, count:u8, (key_len:u16, key)*count]`, with
+//! `1..=MAX_MGET_KEYS` keys. Response: `[S, F, ver, OP_MGET|0x80, status, (nonce:u64 if v2),
+//! flags:u8, count:u8, (status:u8, val_len:u32, value)*count]`.
+//!
+//! Every key gets its own slot in request order, so a miss is `NOT_FOUND` in that slot
+//! rather than a failed batch. Slots are emitted while the frame stays within the
+//! caller's byte budget; the first slot that does not fit ends the frame and sets
+//! [`MGET_FLAG_TRUNCATED`], leaving the remaining keys to be fetched again.
+
+use alloc::vec::Vec;
+use core::str;
+
+use storage::BlockDevice;
+
+use crate::access::LOCAL_REQUESTER;
+use crate::protocol::{
+    error_from_status, status_from_error, Request, MAGIC0, MAGIC1, OP_MGET, STATUS_KEY_TOO_LONG,
+    STATUS_MALFORMED, STATUS_OK, VERSION, VERSION_V2,
+};
+use crate::{JournalEngine, StatefsError, MAX_KEY_LEN, MAX_VALUE_SIZE};
+
+/// Most keys one `OP_MGET` request may carry.
+pub const MAX_MGET_KEYS: usize = 16;
+/// Response budget statefsd applies to `OP_MGET` (the client's receive buffer).
+pub const MAX_MGET_RESPONSE_BYTES: usize = 4096;
+/// Response flag: slots after the last one returned did not fit.
+pub const MGET_FLAG_TRUNCATED: u8 = 1 << 0;
+
+/// Per-slot header: `status:u8, val_len:u32`.
+const SLOT_HEADER_LEN: usize = 5;
+
+/// Decoded `OP_MGET` response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MgetResponse {
+    /// One result per returned key, in request order.
+    pub values: Vec<Result<Vec<u8>, StatefsError>>,
+    /// `values` stops short of the requested keys (see [`MGET_FLAG_TRUNCATED`]).
+    pub truncated: bool,
+}
+
+impl<B: BlockDevice> JournalEngine<B> {
+    /// Get several keys at once; one result per key, in input order.
+    ///
+    /// A missing or invalid key fails only its own slot.
+    pub fn get_many(&self, keys: &[&str]) -> Vec<Result<Vec<u8>, StatefsError>> {
+        self.get_many_as(LOCAL_REQUESTER, keys)
+    }
+
+    /// [`JournalEngine::get_many`] on behalf of `requester_id`: each key goes through
+    /// [`JournalEngine::get_as`], so a denied key fails its own slot with `AccessDenied`.
+    pub fn get_many_as(
+        &self,
+        requester_id: u64,
+        keys: &[&str],
+    ) -> Vec<Result<Vec<u8>, StatefsError>> {
+        keys.iter().map(|key| self.get_as(requester_id, key)).collect()
+    }
+}
+
+pub fn encode_mget_request(keys: &[&str]) -> Result<Vec<u8>, StatefsError> {
+    if keys.is_empty() || keys.len() > MAX_MGET_KEYS {
+        return Err(StatefsError::InvalidKey);
+    }
+    let mut out = Vec::with_capacity(5 + keys.iter().map(|k| 2 + k.len()).sum::<usize>());
+    out.extend_from_slice(&[MAGIC0, MAGIC1, VERSION, OP_MGET, keys.len() as u8]);
+    for key in keys {
+        if key.len() > MAX_KEY_LEN {
+            return Err(StatefsError::KeyTooLong);
+        }
+        out.extend_from_slice(&(key.len() as u16).to_le_bytes());
+        out.extend_from_slice(key.as_bytes());
+    }
+    Ok(out)
+}
+
+pub(crate) fn decode_mget_payload(payload: &[u8]) -> Result<Request<'_>, u8> {
+    let (&count, mut rest) = payload.split_first().ok_or(STATUS_MALFORMED)?;
+    let count = usize::from(count);
+    if count == 0 || count > MAX_MGET_KEYS {
+        return Err(STATUS_MALFORMED);
+    }
+    let mut keys = Vec::with_capacity(count);
+    for _ in 0..count {
+        let (len, tail) = rest.split_first_chunk::<2>().ok_or(STATUS_MALFORMED)?;
+        let key_len = usize::from(u16::from_le_bytes(*len));
+        if key_len == 0 {
+            return Err(STATUS_MALFORMED);
+        }
+        if key_len > MAX_KEY_LEN {
+            return Err(STATUS_KEY_TOO_LONG);
+        }
+        let key = tail.get(..key_len).ok_or(STATUS_MALFORMED)?;
+        keys.push(str::from_utf8(key).map_err(|_| STATUS_MALFORMED)?);
+        rest = &tail[key_len..];
+    }
+    if !rest.is_empty() {
+        return Err(STATUS_MALFORMED);
+    }
+    Ok(Request::GetMany { keys })
+}
+
+/// Encode the per-key results of an `OP_MGET`, stopping before the first slot that would
+/// take the frame past `max_bytes`.
+pub fn encode_mget_response_with_nonce(
+    status: u8,
+    results: &[Result<Vec<u8>, StatefsError>],
+    max_bytes: usize,
+    nonce: Option<u64>,
+) -> Vec<u8> {
+    let version = if nonce.is_some() { VERSION_V2 } else { VERSION };
+    let mut out = Vec::with_capacity(15);
+    out.extend_from_slice(&[MAGIC0, MAGIC1, version, OP_MGET | 0x80, status]);
+    if let Some(n) = nonce {
+        out.extend_from_slice(&n.to_le_bytes());
+    }
+    let flags_pos = out.len();
+    out.extend_from_slice(&[0, 0]);
+    let mut count: u8 = 0;
+    for result in results.iter().take(MAX_MGET_KEYS) {
+        let (slot_status, value) = match result {
+            Ok(value) => (STATUS_OK, value.as_slice()),
+            Err(err) => (status_from_error(*err), &[][..]),
+        };
+        if out.len().saturating_add(SLOT_HEADER_LEN + value.len()) > max_bytes {
+            out[flags_pos] = MGET_FLAG_TRUNCATED;
+            break;
+        }
+        out.push(slot_status);
+        out.extend_from_slice(&(value.len() as u32).to_le_bytes());
+        out.extend_from_slice(value);
+        count += 1;
+    }
+    out[flags_pos + 1] = count;
+    out
+}
+
+pub fn decode_mget_response(frame: &[u8]) -> Result<MgetResponse, StatefsError> {
+    if frame.len() < 5 || frame[0] != MAGIC0 || frame[1] != MAGIC1 {
+        return Err(StatefsError::Corrupted);
+    }
+    if frame[3] != (OP_MGET | 0x80) {
+        return Err(StatefsError::Corrupted);
+    }
+    let body = match frame[2] {
+        VERSION => &frame[5..],
+        VERSION_V2 if frame.len() >= 13 => &frame[13..],
+        _ => return Err(StatefsError::Corrupted),
+    };
+    if frame[4] != STATUS_OK {
+        return Err(error_from_status(frame[4]));
+    }
+    let [flags, count, tail @ ..] = body else {
+        return Err(StatefsError::Corrupted);
+    };
+    let mut rest = tail;
+    if flags & !MGET_FLAG_TRUNCATED != 0 || usize::from(*count) > MAX_MGET_KEYS {
+        return Err(StatefsError::Corrupted);
+    }
+    let mut values = Vec::with_capacity(usize::from(*count));
+    for _ in 0..*count {
+        let (head, tail) =
+            rest.split_first_chunk::<SLOT_HEADER_LEN>().ok_or(StatefsError::Corrupted)?;
+        let val_len = u32::from_le_bytes([head[1], head[2], head[3], head[4]]) as usize;
+        if val_len > MAX_VALUE_SIZE || (head[0] != STATUS_OK && val_len != 0) {
+            return Err(StatefsError::Corrupted);
+        }
+        let value = tail.get(..val_len).ok_or(StatefsError::Corrupted)?;
+        values.push(if head[0] == STATUS_OK {
+            Ok(value.to_vec())
+        } else {
+            Err(error_from_status(head[0]))
+        });
+        rest = &tail[val_len..];
+    }
+    if !rest.is_empty() {
+        return Err(StatefsError::Corrupted);
+    }
+    Ok(MgetResponse { values, truncated: flags & MGET_FLAG_TRUNCATED != 0 })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::decode_request_with_nonce;
+    use alloc::vec;
+    use storage::MemBlockDevice;
+
+    #[test]
+    fn get_many_keeps_order_across_hits_and_misses() {
+        let mut engine = JournalEngine::open(MemBlockDevice::new(512, 64)).unwrap();
+        engine.put("/state/a", b"1").unwrap();
+        engine.put("/state/c", b"333").unwrap();
+        let keys = ["/state/c", "/state/b", "/state/a", "bad"];
+        let results = engine.get_many(&keys);
+        assert_eq!(
+            results,
+            [
+                Ok(b"333".to_vec()),
+                Err(StatefsError::NotFound),
+                Ok(b"1".to_vec()),
+                Err(StatefsError::InvalidKey),
+            ]
+        );
+
+        let frame = encode_mget_request(&keys).unwrap();
+        assert_eq!(
+            decode_request_with_nonce(&frame),
+            Ok((Request::GetMany { keys: keys.to_vec() }, None))
+        );
+        let rsp = encode_mget_response_with_nonce(STATUS_OK, &results, 4096, Some(9));
+        let decoded = decode_mget_response(&rsp).unwrap();
+        assert_eq!(decoded, MgetResponse { values: results, truncated: false });
+    }
+
+    #[test]
+    fn response_truncates_at_the_byte_budget() {
+        let results = [Ok(vec![1u8; 40]), Err(StatefsError::NotFound), Ok(vec![2u8; 40])];
+        // Header (5) + flags/count (2) + first slot (45) + miss slot (5) = 57 bytes.
+        let rsp = encode_mget_response_with_nonce(STATUS_OK, &results, 60, None);
+        assert_eq!(rsp.len(), 57);
+        let decoded = decode_mget_response(&rsp).unwrap();
+        assert!(decoded.truncated);
+        assert_eq!(decoded.values, results[..2]);
+
+        let whole = encode_mget_response_with_nonce(STATUS_OK, &results, 4096, None);
+        assert!(!decode_mget_response(&whole).unwrap().truncated);
+    }
+
+    #[test]
+    fn test_reject_malformed_mget_frames() {
+        assert_eq!(encode_mget_request(&[]), Err(StatefsError::InvalidKey));
+        assert_eq!(
+            encode_mget_request(&["/state/k"; MAX_MGET_KEYS + 1]),
+            Err(StatefsError::InvalidKey)
+        );
+
+        let frame = encode_mget_request(&["/state/a", "/state/b"]).unwrap();
+        for cut in 4..frame.len() {
+            assert_eq!(decode_request_with_nonce(&frame[..cut]), Err(STATUS_MALFORMED));
+        }
+        let mut trailing = frame.clone();
+        trailing.push(0);
+        assert_eq!(decode_request_with_nonce(&trailing), Err(STATUS_MALFORMED));
+        let mut too_many = frame.clone();
+        too_many[4] = MAX_MGET_KEYS as u8 + 1;
+        assert_eq!(decode_request_with_nonce(&too_many), Err(STATUS_MALFORMED));
+
+        let rsp = encode_mget_response_with_nonce(STATUS_OK, &[Ok(b"v".to_vec())], 4096, None);
+        assert!(decode_mget_response(&rsp[..rsp.len() - 1]).is_err());
+        let mut bad_flags = rsp.clone();
+        bad_flags[5] = 0x80;
+        assert_eq!(decode_mget_response(&bad_flags), Err(StatefsError::Corrupted));
+        let denied =
+            encode_mget_response_with_nonce(crate::protocol::STATUS_ACCESS_DENIED, &[], 4096, None);
+        assert_eq!(decode_mget_response(&denied), Err(StatefsError::AccessDenied));
+    }
+}
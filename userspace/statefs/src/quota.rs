@@ -0,0 +1,186 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: Per-namespace byte quotas (`JournalEngine::set_quota`)
+//! OWNERS: @runtime
+//! STATUS: Functional (host-first)
+//! TEST_COVERAGE: 4 unit tests (fits, exceeds, delete frees, survives reopen)
+//!
+//! A quota caps the summed value bytes (plain values plus append-list entries) of every
+//! key under a prefix such as `/state/app/<id>/`, so one app cannot fill `/state` and
+//! starve system services. `put` and `append` reject with `QuotaExceeded` when the write
+//! would grow a namespace past its limit; writes that shrink or keep a namespace's size
+//! always pass, even while it is over a limit set below its current usage. Overlapping
+//! prefixes are each enforced.
+//!
+//! Quotas are configuration, not journaled state: they stay set across `reopen`, and
+//! their usage is recomputed from the replayed keys after every replay. Journal
+//! overhead (record headers, superseded records) is not charged.
+
+use alloc::string::String;
+use core::ops::Bound;
+
+use storage::BlockDevice;
+
+use crate::{JournalEngine, StatefsError};
+
+/// One quota'd prefix and its tracked usage.
+#[derive(Debug)]
+pub(crate) struct Quota {
+    prefix: String,
+    max_bytes: usize,
+    used: usize,
+}
+
+impl<B: BlockDevice> JournalEngine<B> {
+    /// Limit the value bytes stored under `prefix` to `max_bytes` (replacing any previous
+    /// limit for the same prefix).
+    ///
+    /// `prefix` is validated like a key. Existing data is never removed; a namespace
+    /// already over the new limit only rejects further growth.
+    pub fn set_quota(&mut self, prefix: &str, max_bytes: usize) -> Result<(), StatefsError> {
+        self.validate_key(prefix)?;
+        let used = self.bytes_under(prefix);
+        match self.quotas.iter_mut().find(|quota| quota.prefix == prefix) {
+            Some(quota) => {
+                quota.max_bytes = max_bytes;
+                quota.used = used;
+            }
+            None => self.quotas.push(Quota { prefix: prefix.into(), max_bytes, used }),
+        }
+        Ok(())
+    }
+
+    /// `(used, max_bytes)` of the quota on exactly `prefix`, if one is set.
+    pub fn quota_usage(&self, prefix: &str) -> Option<(usize, usize)> {
+        self.quotas
+            .iter()
+            .find(|quota| quota.prefix == prefix)
+            .map(|quota| (quota.used, quota.max_bytes))
+    }
+
+    /// Value bytes currently stored at `key` (a plain value or an append list).
+    pub(crate) fn stored_bytes(&self, key: &str) -> usize {
+        self.kv.get(key).map_or(0, |slot| slot.len)
+            + self.lists.get(key).map_or(0, |list| list.bytes)
+    }
+
+    /// Reject a write that would make `key` hold `new_bytes` beyond a covering quota.
+    pub(crate) fn check_quota(&self, key: &str, new_bytes: usize) -> Result<(), StatefsError> {
+        let old_bytes = self.stored_bytes(key);
+        if new_bytes <= old_bytes {
+            return Ok(());
+        }
+        let grows_past = |quota: &Quota| {
+            key.starts_with(quota.prefix.as_str())
+                && quota.used.saturating_sub(old_bytes) + new_bytes > quota.max_bytes
+        };
+        if self.quotas.iter().any(grows_past) {
+            return Err(StatefsError::QuotaExceeded);
+        }
+        Ok(())
+    }
+
+    /// Move the usage of covering quotas from `before` to what `key` holds now.
+    pub(crate) fn charge_quota(&mut self, key: &str, before: usize) {
+        let after = self.stored_bytes(key);
+        for quota in self.quotas.iter_mut().filter(|quota| key.starts_with(quota.prefix.as_str())) {
+            quota.used = quota.used.saturating_sub(before) + after;
+        }
+    }
+
+    /// Rebuild every quota's usage from the live keys (after replay).
+    pub(crate) fn recompute_quotas(&mut self) {
+        let mut quotas = core::mem::take(&mut self.quotas);
+        for quota in quotas.iter_mut() {
+            quota.used = self.bytes_under(&quota.prefix);
+        }
+        self.quotas = quotas;
+    }
+
+    fn bytes_under(&self, prefix: &str) -> usize {
+        let from = (Bound::Included(prefix), Bound::Unbounded);
+        let values = self.kv.range::<str, _>(from).take_while(|(key, _)| key.starts_with(prefix));
+        let lists = self.lists.range::<str, _>(from).take_while(|(key, _)| key.starts_with(prefix));
+        values.map(|(_, slot)| slot.len).sum::<usize>()
+            + lists.map(|(_, list)| list.bytes).sum::<usize>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use storage::MemBlockDevice;
+
+    const APP: &str = "/state/app/1/";
+
+    fn engine() -> JournalEngine<MemBlockDevice> {
+        let mut engine = JournalEngine::open(MemBlockDevice::new(512, 64)).unwrap();
+        engine.put("/state/app/1/config", &[1; 40]).unwrap();
+        engine.set_quota(APP, 100).unwrap();
+        engine
+    }
+
+    #[test]
+    fn writes_that_fit_are_charged() {
+        let mut engine = engine();
+        assert_eq!(engine.quota_usage(APP), Some((40, 100)));
+        engine.put("/state/app/1/cache", &[2; 50]).unwrap();
+        engine.append("/state/app/1/log", &[3; 10]).unwrap();
+        assert_eq!(engine.quota_usage(APP), Some((100, 100)));
+        // Replacing a value charges only the difference; other namespaces are not limited.
+        engine.put("/state/app/1/config", &[4; 30]).unwrap();
+        engine.put("/state/app/2/config", &[5; 500]).unwrap();
+        assert_eq!(engine.quota_usage(APP), Some((90, 100)));
+    }
+
+    #[test]
+    fn test_reject_write_over_quota() {
+        let mut engine = engine();
+        let pos = engine.write_pos;
+        assert_eq!(engine.put("/state/app/1/cache", &[2; 61]), Err(StatefsError::QuotaExceeded));
+        assert_eq!(engine.put("/state/app/1/config", &[2; 101]), Err(StatefsError::QuotaExceeded));
+        assert_eq!(engine.write_pos, pos);
+        engine.put("/state/app/1/log-a", &[2; 60]).unwrap();
+        assert_eq!(engine.append("/state/app/1/log", b"x"), Err(StatefsError::QuotaExceeded));
+        // Nothing was applied for the rejected writes.
+        assert_eq!(engine.get("/state/app/1/cache"), Err(StatefsError::NotFound));
+        assert!(engine.read_entries("/state/app/1/log", 0, 1).is_empty());
+        assert_eq!(engine.quota_usage(APP), Some((100, 100)));
+
+        // Lowering the limit keeps data; shrinking writes still pass.
+        engine.set_quota(APP, 10).unwrap();
+        engine.put("/state/app/1/config", &[1; 20]).unwrap();
+        assert_eq!(engine.quota_usage(APP), Some((80, 10)));
+        assert_eq!(engine.set_quota("/other/", 1), Err(StatefsError::InvalidKey));
+    }
+
+    #[test]
+    fn delete_frees_quota() {
+        let mut engine = engine();
+        engine.put("/state/app/1/cache", &[2; 60]).unwrap();
+        engine.delete("/state/app/1/cache").unwrap();
+        assert_eq!(engine.quota_usage(APP), Some((40, 100)));
+        engine.put("/state/app/1/cache", &[2; 60]).unwrap();
+
+        engine.append("/state/app/1/sub/log", b"").unwrap();
+        assert_eq!(engine.delete_prefix(APP), Ok(3));
+        assert_eq!(engine.quota_usage(APP), Some((0, 100)));
+        engine.put("/state/app/1/cache", &[2; 100]).unwrap();
+    }
+
+    #[test]
+    fn quotas_survive_reopen() {
+        let mut engine = engine();
+        engine.append("/state/app/1/log", &[3; 25]).unwrap();
+        engine.sync().unwrap();
+        engine.reopen().unwrap();
+        assert_eq!(engine.quota_usage(APP), Some((65, 100)));
+        assert_eq!(engine.put("/state/app/1/cache", &[2; 36]), Err(StatefsError::QuotaExceeded));
+
+        engine.compact().unwrap();
+        engine.reopen().unwrap();
+        assert_eq!(engine.quota_usage(APP), Some((65, 100)));
+        engine.put("/state/app/1/cache", &[2; 35]).unwrap();
+    }
+}
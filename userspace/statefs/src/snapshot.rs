@@ -0,0 +1,247 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: Snapshot overlay (`JournalEngine::merge_snapshot`, `encode_snapshot`)
+//! OWNERS: @runtime
+//! STATUS: Functional (host-first)
+//! TEST_COVERAGE: 3 unit tests (overlay keeps existing values, overlay replaces them, bad blobs)
+//!
+//! Provisioning ships default keys as a snapshot blob and overlays it onto a store that may
+//! already hold user data. [`JournalEngine::merge_snapshot`] applies every key of the blob
+//! without clearing anything: a key the store lacks is added, an existing key is replaced
+//! only when `overwrite` is set and skipped otherwise. The [`MergeReport`] counts each case.
+//!
+//! The whole blob is decoded and every entry validated as `put` validates it (key, access
+//! policy, value size, append-list collision, quota) before anything is written, so a bad
+//! blob or a bad entry changes nothing. Entries are then written as ordinary puts in key
+//! order; quota consumed by earlier entries, or a device error, can still stop a merge part
+//! way. A write-through engine syncs once at the end.
+//!
+//! Blob: `"NXSS" | version:u8 | count:u32 | count × (key_len:u16, val_len:u32, key, value) |
+//! crc32c:u32`, integers LE, keys strictly ascending, the CRC over everything before it.
+//! Values are plain values up to `MAX_VALUE_SIZE`; append lists are not part of a snapshot.
+
+use alloc::vec::Vec;
+
+use storage::BlockDevice;
+
+use crate::checksum::crc32c;
+use crate::{
+    DurabilityMode, JournalEngine, StatefsError, LOCAL_REQUESTER, MAX_KEY_LEN, MAX_VALUE_SIZE,
+};
+
+const SNAPSHOT_MAGIC: &[u8; 4] = b"NXSS";
+const SNAPSHOT_VERSION: u8 = 1;
+/// Magic, version and entry count.
+const SNAPSHOT_HEADER_LEN: usize = 9;
+/// `key_len:u16 | val_len:u32` ahead of each entry.
+const ENTRY_HEADER_LEN: usize = 6;
+const CRC_LEN: usize = 4;
+
+/// What [`JournalEngine::merge_snapshot`] did with the snapshot's keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MergeReport {
+    /// Keys the store did not hold.
+    pub added: usize,
+    /// Existing keys whose value was replaced (`overwrite == true`).
+    pub replaced: usize,
+    /// Existing keys left as they were (`overwrite == false`).
+    pub skipped: usize,
+}
+
+impl<B: BlockDevice> JournalEngine<B> {
+    /// Overlay the snapshot `blob` onto the store; existing keys are replaced only if
+    /// `overwrite` is set.
+    pub fn merge_snapshot(
+        &mut self,
+        blob: &[u8],
+        overwrite: bool,
+    ) -> Result<MergeReport, StatefsError> {
+        self.merge_snapshot_as(LOCAL_REQUESTER, blob, overwrite)
+    }
+
+    /// [`JournalEngine::merge_snapshot`] on behalf of `requester_id` (see `AccessPolicy`).
+    pub fn merge_snapshot_as(
+        &mut self,
+        requester_id: u64,
+        blob: &[u8],
+        overwrite: bool,
+    ) -> Result<MergeReport, StatefsError> {
+        let entries = decode_snapshot(blob)?;
+        for &(key, value) in &entries {
+            self.check_put(requester_id, key, value)?;
+        }
+        let mut report = MergeReport::default();
+        for (key, value) in entries {
+            let exists = self.kv.contains_key(key);
+            if exists && !overwrite {
+                report.skipped += 1;
+                continue;
+            }
+            self.put_with(requester_id, key, value, DurabilityMode::WriteBack)?;
+            if exists {
+                report.replaced += 1;
+            } else {
+                report.added += 1;
+            }
+        }
+        let written = report.added + report.replaced;
+        if written > 0 && self.durability == DurabilityMode::WriteThrough {
+            self.sync()?;
+        }
+        Ok(report)
+    }
+}
+
+/// Snapshot blob holding `entries`, in key order whatever order they are given in.
+///
+/// Rejects an over-long key (`KeyTooLong`), an over-size value (`ValueTooLarge`) and a
+/// key given twice (`InvalidKey`). Keys are checked against the store's rules only when
+/// the blob is merged.
+pub fn encode_snapshot(entries: &[(&str, &[u8])]) -> Result<Vec<u8>, StatefsError> {
+    let mut sorted: Vec<(&str, &[u8])> = entries.to_vec();
+    sorted.sort_unstable_by(|a, b| a.0.cmp(b.0));
+    if sorted.windows(2).any(|pair| pair[0].0 == pair[1].0) {
+        return Err(StatefsError::InvalidKey);
+    }
+    let count = u32::try_from(sorted.len()).map_err(|_| StatefsError::ValueTooLarge)?;
+    let mut out = Vec::with_capacity(SNAPSHOT_HEADER_LEN + CRC_LEN);
+    out.extend_from_slice(SNAPSHOT_MAGIC);
+    out.push(SNAPSHOT_VERSION);
+    out.extend_from_slice(&count.to_le_bytes());
+    for (key, value) in sorted {
+        if key.len() > MAX_KEY_LEN {
+            return Err(StatefsError::KeyTooLong);
+        }
+        if value.len() > MAX_VALUE_SIZE {
+            return Err(StatefsError::ValueTooLarge);
+        }
+        out.extend_from_slice(&(key.len() as u16).to_le_bytes());
+        out.extend_from_slice(&(value.len() as u32).to_le_bytes());
+        out.extend_from_slice(key.as_bytes());
+        out.extend_from_slice(value);
+    }
+    let crc = crc32c(&out);
+    out.extend_from_slice(&crc.to_le_bytes());
+    Ok(out)
+}
+
+/// The entries of a snapshot blob; `Corrupted` unless the whole blob is well formed.
+fn decode_snapshot(blob: &[u8]) -> Result<Vec<(&str, &[u8])>, StatefsError> {
+    let (body, crc) = blob.split_last_chunk::<CRC_LEN>().ok_or(StatefsError::Corrupted)?;
+    if crc32c(body) != u32::from_le_bytes(*crc) {
+        return Err(StatefsError::Corrupted);
+    }
+    let (header, mut rest) =
+        body.split_first_chunk::<SNAPSHOT_HEADER_LEN>().ok_or(StatefsError::Corrupted)?;
+    if &header[..4] != SNAPSHOT_MAGIC || header[4] != SNAPSHOT_VERSION {
+        return Err(StatefsError::Corrupted);
+    }
+    let count = u32::from_le_bytes([header[5], header[6], header[7], header[8]]) as usize;
+    // Every entry takes at least its header, so `count` cannot outgrow the blob.
+    if count > rest.len() / ENTRY_HEADER_LEN {
+        return Err(StatefsError::Corrupted);
+    }
+    let mut entries: Vec<(&str, &[u8])> = Vec::with_capacity(count);
+    let mut previous: Option<&str> = None;
+    for _ in 0..count {
+        let (lens, tail) =
+            rest.split_first_chunk::<ENTRY_HEADER_LEN>().ok_or(StatefsError::Corrupted)?;
+        let key_len = u16::from_le_bytes([lens[0], lens[1]]) as usize;
+        let value_len = u32::from_le_bytes([lens[2], lens[3], lens[4], lens[5]]) as usize;
+        if tail.len() < key_len || tail.len() - key_len < value_len {
+            return Err(StatefsError::Corrupted);
+        }
+        let (key, tail) = tail.split_at(key_len);
+        let (value, tail) = tail.split_at(value_len);
+        let key = core::str::from_utf8(key).map_err(|_| StatefsError::Corrupted)?;
+        if previous.is_some_and(|prev| prev >= key) {
+            return Err(StatefsError::Corrupted);
+        }
+        previous = Some(key);
+        entries.push((key, value));
+        rest = tail;
+    }
+    if !rest.is_empty() {
+        return Err(StatefsError::Corrupted);
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use storage::MemBlockDevice;
+
+    fn engine() -> JournalEngine<MemBlockDevice> {
+        JournalEngine::open(MemBlockDevice::new(512, 64)).unwrap()
+    }
+
+    fn defaults() -> Vec<u8> {
+        encode_snapshot(&[
+            ("/state/net/mtu", b"1500"),
+            ("/state/ui/theme", b"light"),
+            ("/state/ui/locale", b"en"),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn overlay_without_overwrite_keeps_existing_values() {
+        let mut engine = engine();
+        engine.put("/state/ui/theme", b"dark").unwrap();
+        engine.put("/state/user/name", b"ada").unwrap();
+
+        let report = engine.merge_snapshot(&defaults(), false).unwrap();
+        assert_eq!(report, MergeReport { added: 2, replaced: 0, skipped: 1 });
+        assert_eq!(engine.get("/state/ui/theme").unwrap(), b"dark");
+        assert_eq!(engine.get("/state/ui/locale").unwrap(), b"en");
+        assert_eq!(engine.get("/state/net/mtu").unwrap(), b"1500");
+        assert_eq!(engine.get("/state/user/name").unwrap(), b"ada");
+
+        // Merging again adds nothing, and the overlay survives a reopen.
+        let again = engine.merge_snapshot(&defaults(), false).unwrap();
+        assert_eq!(again, MergeReport { added: 0, replaced: 0, skipped: 3 });
+        engine.sync().unwrap();
+        engine.reopen().unwrap();
+        assert_eq!(engine.get("/state/ui/locale").unwrap(), b"en");
+        assert_eq!(engine.len(), 4);
+    }
+
+    #[test]
+    fn overlay_with_overwrite_replaces_existing_values() {
+        let mut engine = engine();
+        engine.put("/state/ui/theme", b"dark").unwrap();
+        engine.put("/state/user/name", b"ada").unwrap();
+
+        let report = engine.merge_snapshot(&defaults(), true).unwrap();
+        assert_eq!(report, MergeReport { added: 2, replaced: 1, skipped: 0 });
+        assert_eq!(engine.get("/state/ui/theme").unwrap(), b"light");
+        // Keys outside the snapshot are left alone.
+        assert_eq!(engine.get("/state/user/name").unwrap(), b"ada");
+    }
+
+    #[test]
+    fn test_reject_bad_snapshot_applies_nothing() {
+        let mut engine = engine();
+        let blob = defaults();
+        let mut flipped = blob.clone();
+        flipped[12] ^= 1;
+        assert_eq!(engine.merge_snapshot(&flipped, true), Err(StatefsError::Corrupted));
+        assert_eq!(
+            engine.merge_snapshot(&blob[..blob.len() - 1], true),
+            Err(StatefsError::Corrupted)
+        );
+        assert_eq!(engine.merge_snapshot(&[], true), Err(StatefsError::Corrupted));
+
+        // One entry outside the namespace rejects the whole snapshot before any write.
+        let outside = encode_snapshot(&[("/state/a", b"1"), ("/etc/b", b"2")]).unwrap();
+        assert_eq!(engine.merge_snapshot(&outside, true), Err(StatefsError::InvalidKey));
+        assert_eq!(engine.len(), 0);
+
+        assert_eq!(
+            encode_snapshot(&[("/state/a", b"1"), ("/state/a", b"2")]),
+            Err(StatefsError::InvalidKey)
+        );
+    }
+}
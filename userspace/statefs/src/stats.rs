@@ -0,0 +1,232 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: Journal health statistics (live keys, fill, dead bytes) and the `OP_STATS` codec
+//! OWNERS: @runtime
+//! STATUS: Functional (host-first)
+//! TEST_COVERAGE: 3 unit tests (dead-byte accounting, compaction reset, stats wire)
+//!
+//! Dead bytes are journal bytes in the live region that compaction would not copy:
+//! records superseded by a later put/delete of the same key (for an append list, all of
+//! its entries), delete tombstones, and plain checkpoints. The count is kept
+//! incrementally by mutations and replay, and recomputed exactly when a compaction
+//! installs its shadow (mirrored mutations can leave superseded copies there).
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use storage::BlockDevice;
+
+use crate::chunked::value_record_bytes;
+use crate::protocol::{
+    error_from_status, MAGIC0, MAGIC1, OP_STATS, STATUS_OK, VERSION, VERSION_V2,
+};
+use crate::{JournalEngine, StatefsError, RECORD_HEADER_SIZE};
+
+/// `OP_STATS` response body: the five `JournalStats` counters as u64 LE.
+const STATS_BODY_LEN: usize = 40;
+
+/// Point-in-time journal health, as served by statefsd `OP_STATS`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct JournalStats {
+    /// Keys currently present.
+    pub live_keys: u64,
+    /// Journal append offset in bytes (includes the superblock block).
+    pub write_pos: u64,
+    /// Device size in bytes.
+    pub device_capacity: u64,
+    /// Bytes held by superseded records, tombstones and checkpoints.
+    pub dead_bytes_estimate: u64,
+    /// Records in the live journal region (replayed plus appended).
+    pub record_count: u64,
+}
+
+/// Journal bytes taken by a record for `key` with a `value_len`-byte value.
+pub(crate) fn record_len(key: &str, value_len: usize) -> usize {
+    RECORD_HEADER_SIZE + key.len() + value_len
+}
+
+impl<B: BlockDevice> JournalEngine<B> {
+    /// Snapshot of the journal counters (cheap; no device I/O).
+    pub fn stats(&self) -> JournalStats {
+        JournalStats {
+            live_keys: self.len() as u64,
+            write_pos: self.write_pos as u64,
+            device_capacity: self.capacity() as u64,
+            dead_bytes_estimate: self.dead_bytes as u64,
+            record_count: self.record_count as u64,
+        }
+    }
+
+    /// Journal bytes the current live set takes once compacted.
+    pub(crate) fn live_bytes(&self) -> usize {
+        let values: usize =
+            self.kv.iter().map(|(key, slot)| value_record_bytes(key, slot.len)).sum();
+        values + self.lists.iter().map(|(key, list)| list.record_bytes(key)).sum::<usize>()
+    }
+
+    /// Counts the record for `key` as dead; call before a put/delete replaces it.
+    pub(crate) fn note_superseded(&mut self, key: &str) {
+        if let Some(old) = self.kv.get(key) {
+            self.dead_bytes += value_record_bytes(key, old.len);
+        }
+        if let Some(list) = self.lists.get(key) {
+            self.dead_bytes += list.record_bytes(key);
+        }
+    }
+}
+
+/// `[S, F, ver, OP_STATS|0x80, status, (nonce:u64 if v2), 5 x u64 counters]`.
+pub fn encode_stats_response_with_nonce(
+    status: u8,
+    stats: &JournalStats,
+    nonce: Option<u64>,
+) -> Vec<u8> {
+    let version = if nonce.is_some() { VERSION_V2 } else { VERSION };
+    let mut out = Vec::with_capacity(13 + STATS_BODY_LEN);
+    out.extend_from_slice(&[MAGIC0, MAGIC1, version, OP_STATS | 0x80, status]);
+    if let Some(n) = nonce {
+        out.extend_from_slice(&n.to_le_bytes());
+    }
+    for counter in [
+        stats.live_keys,
+        stats.write_pos,
+        stats.device_capacity,
+        stats.dead_bytes_estimate,
+        stats.record_count,
+    ] {
+        out.extend_from_slice(&counter.to_le_bytes());
+    }
+    out
+}
+
+pub fn decode_stats_response(frame: &[u8]) -> Result<JournalStats, StatefsError> {
+    if frame.len() < 5 || frame[0] != MAGIC0 || frame[1] != MAGIC1 {
+        return Err(StatefsError::Corrupted);
+    }
+    if frame[3] != (OP_STATS | 0x80) {
+        return Err(StatefsError::Corrupted);
+    }
+    let body = match frame[2] {
+        VERSION => &frame[5..],
+        VERSION_V2 if frame.len() >= 13 => &frame[13..],
+        _ => return Err(StatefsError::Corrupted),
+    };
+    if frame[4] != STATUS_OK {
+        return Err(error_from_status(frame[4]));
+    }
+    if body.len() != STATS_BODY_LEN {
+        return Err(StatefsError::Corrupted);
+    }
+    let counter = |idx: usize| {
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&body[idx * 8..idx * 8 + 8]);
+        u64::from_le_bytes(bytes)
+    };
+    Ok(JournalStats {
+        live_keys: counter(0),
+        write_pos: counter(1),
+        device_capacity: counter(2),
+        dead_bytes_estimate: counter(3),
+        record_count: counter(4),
+    })
+}
+
+pub fn encode_stats_request() -> Vec<u8> {
+    vec![MAGIC0, MAGIC1, VERSION, OP_STATS]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol;
+    use crate::StatefsError;
+    use storage::MemBlockDevice;
+
+    fn engine() -> JournalEngine<MemBlockDevice> {
+        JournalEngine::open(MemBlockDevice::new(128, 64)).unwrap()
+    }
+
+    /// Dead bytes as derived from the region itself (valid while no relocation head is inside).
+    fn exact_dead(engine: &JournalEngine<MemBlockDevice>) -> u64 {
+        (engine.write_pos - engine.base - engine.live_bytes()) as u64
+    }
+
+    #[test]
+    fn dead_bytes_track_overwrites_and_deletes() {
+        let mut engine = engine();
+        assert_eq!(engine.stats().dead_bytes_estimate, 0);
+        engine.put("/state/s/a", b"one").unwrap();
+        engine.put("/state/s/a", b"three").unwrap();
+        engine.put("/state/s/b", b"two").unwrap();
+        engine.delete("/state/s/b").unwrap();
+
+        let expected =
+            record_len("/state/s/a", 3) + record_len("/state/s/b", 3) + record_len("/state/s/b", 0);
+        let stats = engine.stats();
+        assert_eq!(stats.dead_bytes_estimate, expected as u64);
+        assert_eq!(stats.dead_bytes_estimate, exact_dead(&engine));
+        assert_eq!((stats.live_keys, stats.record_count), (1, 4));
+        assert_eq!(stats.device_capacity, 128 * 64);
+
+        // Replay rebuilds the same accounting.
+        engine.sync().unwrap();
+        engine.reopen().unwrap();
+        assert_eq!(engine.stats(), stats);
+    }
+
+    #[test]
+    fn compaction_zeroes_dead_bytes() {
+        let mut engine = engine();
+        for round in 0..3u8 {
+            for i in 0..6u8 {
+                engine.put(&alloc::format!("/state/s/{i}"), &[round; 8]).unwrap();
+            }
+        }
+        assert!(engine.stats().dead_bytes_estimate > 0);
+        engine.compact().unwrap();
+        assert_eq!(engine.stats().dead_bytes_estimate, 0);
+        engine.reopen().unwrap();
+        assert_eq!(engine.stats().dead_bytes_estimate, 0);
+
+        // Overwrites mirrored into an in-flight shadow stay dead after the install.
+        engine.put("/state/s/0", b"x").unwrap();
+        assert!(matches!(engine.compact_step(2), Ok(crate::CompactProgress::InProgress { .. })));
+        engine.put("/state/s/0", b"y").unwrap();
+        engine.compact().unwrap();
+        assert_eq!(engine.stats().dead_bytes_estimate, record_len("/state/s/0", 1) as u64);
+        assert_eq!(engine.stats().dead_bytes_estimate, exact_dead(&engine));
+    }
+
+    #[test]
+    fn test_reject_malformed_stats_response() {
+        let stats = JournalStats {
+            live_keys: 1,
+            write_pos: 2,
+            device_capacity: 3,
+            dead_bytes_estimate: 4,
+            record_count: 5,
+        };
+        for nonce in [None, Some(0xA5)] {
+            let frame =
+                protocol::encode_stats_response_with_nonce(protocol::STATUS_OK, &stats, nonce);
+            assert_eq!(protocol::decode_stats_response(&frame), Ok(stats));
+            assert_eq!(
+                protocol::decode_stats_response(&frame[..frame.len() - 1]),
+                Err(StatefsError::Corrupted)
+            );
+        }
+        let denied = protocol::encode_stats_response_with_nonce(
+            protocol::STATUS_ACCESS_DENIED,
+            &JournalStats::default(),
+            Some(1),
+        );
+        assert_eq!(protocol::decode_stats_response(&denied), Err(StatefsError::AccessDenied));
+        let request = protocol::encode_stats_request();
+        assert_eq!(protocol::decode_request(&request), Ok(protocol::Request::Stats));
+        assert_eq!(
+            protocol::decode_request(&[request.as_slice(), &[0]].concat()),
+            Err(protocol::STATUS_MALFORMED)
+        );
+    }
+}
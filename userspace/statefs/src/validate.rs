@@ -0,0 +1,142 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: Dry-run put (`JournalEngine::validate_put`, `OP_VALIDATE`)
+//! OWNERS: @runtime
+//! STATUS: Functional (host-first)
+//! TEST_COVERAGE: 2 unit tests (same errors as `put`, valid path leaves the store unchanged)
+//!
+//! Settings and other configuration tools preview a write before committing it.
+//! `validate_put` runs the checks `put` runs before it touches the journal, through the
+//! same code path: key validation, the access policy, the value size, the append-list
+//! collision and the namespace quota. It takes `&self`, so nothing is journaled and no
+//! in-memory state changes. A validated put can still fail on the device (`IoError`) or
+//! because another put consumed the quota in between.
+//!
+//! `OP_VALIDATE` carries the `OP_PUT` payload without the flag byte; the daemon answers
+//! with a plain status frame and gates it like a put of the same key.
+
+use alloc::vec::Vec;
+
+use storage::BlockDevice;
+
+use crate::protocol::{self, Request, OP_VALIDATE, STATUS_MALFORMED};
+use crate::{JournalEngine, Op, StatefsError, LOCAL_REQUESTER, MAX_VALUE_SIZE};
+
+impl<B: BlockDevice> JournalEngine<B> {
+    /// Check that `put(key, value)` would be accepted, without writing anything.
+    pub fn validate_put(&self, key: &str, value: &[u8]) -> Result<(), StatefsError> {
+        self.validate_put_as(LOCAL_REQUESTER, key, value)
+    }
+
+    /// [`JournalEngine::validate_put`] on behalf of `requester_id` (see `AccessPolicy`).
+    pub fn validate_put_as(
+        &self,
+        requester_id: u64,
+        key: &str,
+        value: &[u8],
+    ) -> Result<(), StatefsError> {
+        self.check_put(requester_id, key, value)
+    }
+
+    /// The checks `put` runs before appending, in the order it runs them.
+    pub(crate) fn check_put(
+        &self,
+        requester_id: u64,
+        key: &str,
+        value: &[u8],
+    ) -> Result<(), StatefsError> {
+        self.check_put_len(requester_id, key, value.len(), MAX_VALUE_SIZE)
+    }
+
+    /// [`JournalEngine::check_put`] for a `value_len`-byte value capped at `max_len`.
+    pub(crate) fn check_put_len(
+        &self,
+        requester_id: u64,
+        key: &str,
+        value_len: usize,
+        max_len: usize,
+    ) -> Result<(), StatefsError> {
+        self.validate_key(key)?;
+        self.check_access(requester_id, Op::Put, key)?;
+        if value_len > max_len {
+            return Err(StatefsError::ValueTooLarge);
+        }
+        if self.lists.contains_key(key) {
+            return Err(StatefsError::InvalidKey);
+        }
+        self.check_quota(key, value_len)
+    }
+}
+
+/// `OP_VALIDATE` request: the `OP_PUT` frame with the opcode swapped.
+pub fn encode_validate_request(key: &str, value: &[u8]) -> Result<Vec<u8>, StatefsError> {
+    let mut frame = protocol::encode_put_request(key, value)?;
+    frame[3] = OP_VALIDATE;
+    Ok(frame)
+}
+
+pub(crate) fn decode_validate_payload(payload: &[u8]) -> Result<Request<'_>, u8> {
+    match protocol::decode_put_payload(payload)? {
+        Request::Put { key, value, durable: false } => Ok(Request::Validate { key, value }),
+        _ => Err(STATUS_MALFORMED),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{format, vec};
+    use storage::MemBlockDevice;
+
+    fn engine() -> JournalEngine<MemBlockDevice> {
+        JournalEngine::open(MemBlockDevice::new(512, 64)).unwrap()
+    }
+
+    #[test]
+    fn test_reject_same_errors_as_put() {
+        let mut engine = engine();
+        engine.set_quota("/state/app/", 8).unwrap();
+        engine.append("/state/log", b"entry").unwrap();
+        let long_key = format!("/state/{}", "k".repeat(crate::MAX_KEY_LEN));
+        let oversized = vec![0u8; MAX_VALUE_SIZE + 1];
+        let cases: [(&str, &[u8]); 5] = [
+            ("/other/k", b"v"),
+            (&long_key, b"v"),
+            ("/state/big", &oversized),
+            ("/state/app/k", b"123456789"),
+            ("/state/log", b"v"),
+        ];
+        for (key, value) in cases {
+            let expected = engine.validate_put(key, value);
+            assert!(expected.is_err(), "{key} should be rejected");
+            assert_eq!(engine.put(key, value), expected, "{key}");
+        }
+    }
+
+    #[test]
+    fn valid_put_is_previewed_without_writing() {
+        let mut engine = engine();
+        engine.set_quota("/state/app/", 8).unwrap();
+        engine.put("/state/app/k", b"1234").unwrap();
+        let (stats, pending) = (engine.stats(), engine.pending_sync());
+
+        // Replacing a value only counts the growth against the quota.
+        assert_eq!(engine.validate_put("/state/app/k", b"12345678"), Ok(()));
+        assert_eq!(engine.validate_put("/state/app/new", b"1234"), Ok(()));
+        assert_eq!(engine.stats(), stats);
+        assert_eq!(engine.pending_sync(), pending);
+        assert_eq!(engine.get("/state/app/k").unwrap(), b"1234");
+        assert_eq!(engine.get("/state/app/new"), Err(StatefsError::NotFound));
+        assert_eq!(engine.quota_usage("/state/app/"), Some((4, 8)));
+
+        let frame = encode_validate_request("/state/app/new", b"1234").unwrap();
+        assert_eq!(
+            protocol::decode_request(&frame),
+            Ok(Request::Validate { key: "/state/app/new", value: b"1234" })
+        );
+        let mut durable = frame.clone();
+        durable.push(protocol::PUT_FLAG_DURABLE);
+        assert_eq!(protocol::decode_request(&durable), Err(STATUS_MALFORMED));
+    }
+}
@@ -0,0 +1,279 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: Opt-in value cache budget with LRU eviction (`JournalEngine::set_value_cache`)
+//! OWNERS: @runtime
+//! STATUS: Functional (host-first)
+//! TEST_COVERAGE: 5 unit tests (eviction under the budget, reload on get, offsets after compaction,
+//! key offsets point at parseable records and follow overwrites)
+//!
+//! By default every live value stays in memory, so a large `/state` costs RAM in
+//! proportion to its size on disk. With a [`ValueCache`] set, values longer than
+//! `evict_above` count against `budget_bytes`; after a put (and after `reopen`) the least
+//! recently used ones are dropped from memory until the rest fit. Keys, lengths and journal
+//! offsets stay resident, so `get` of an evicted value re-reads its `Put` record from the
+//! device. A reloaded value is returned without being cached again: reads never grow memory,
+//! and the next put of the key caches it anew. `key_offsets` exposes the same offsets to
+//! indexers outside the engine.
+//!
+//! INVARIANTS:
+//! - Every live value's slot holds the offset of its current `Put` record (the first
+//!   `PutChunk` of a large value), also after compaction moves records into the shadow region
+//! - A reload checks the record's opcode, key and length; a mismatch is `Corrupted`
+//! - The cache setting is configuration, not journaled state: it stays set across `reopen`
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cell::Cell;
+
+use storage::BlockDevice;
+
+use crate::chunked::value_record_bytes;
+use crate::{JournalEngine, JournalOpCode, StatefsError, MAX_VALUE_SIZE};
+
+/// Memory budget for large values (see [`JournalEngine::set_value_cache`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ValueCache {
+    /// Bytes of evictable values kept in memory at most.
+    pub budget_bytes: usize,
+    /// Values up to this length are never evicted and do not count against the budget.
+    pub evict_above: usize,
+}
+
+/// A live value: its length and journal offset, and its bytes while cached.
+#[derive(Debug, Clone)]
+pub(crate) struct ValueSlot {
+    bytes: Option<Vec<u8>>,
+    pub(crate) len: usize,
+    /// Byte offset of the `Put` record holding the value.
+    pub(crate) offset: usize,
+    /// LRU clock reading of the last put or cached get.
+    last_used: Cell<u64>,
+}
+
+impl ValueSlot {
+    pub(crate) fn new(bytes: Vec<u8>, offset: usize, now: u64) -> Self {
+        Self { len: bytes.len(), bytes: Some(bytes), offset, last_used: Cell::new(now) }
+    }
+
+    pub(crate) fn cached(&self) -> Option<&[u8]> {
+        self.bytes.as_deref()
+    }
+}
+
+impl<B: BlockDevice> JournalEngine<B> {
+    /// Bound memory for large values (`None`, the default, keeps every value resident).
+    ///
+    /// Applies the budget right away; an evicted value is read back from the journal on `get`.
+    pub fn set_value_cache(&mut self, cache: Option<ValueCache>) {
+        self.value_cache = cache;
+        self.enforce_value_cache();
+    }
+
+    /// Bytes of evictable values currently held in memory.
+    pub fn cached_value_bytes(&self) -> usize {
+        let Some(cache) = self.value_cache else {
+            return 0;
+        };
+        self.kv
+            .values()
+            .filter_map(ValueSlot::cached)
+            .filter(|bytes| bytes.len() > cache.evict_above)
+            .map(<[u8]>::len)
+            .sum()
+    }
+
+    /// Next LRU clock reading.
+    pub(crate) fn lru_tick(&self) -> u64 {
+        let now = self.lru_clock.get().wrapping_add(1);
+        self.lru_clock.set(now);
+        now
+    }
+
+    /// Where each key's current value lives on the device, as `(key, record_offset, record_len)`.
+    ///
+    /// `record_offset` is the absolute byte offset of the key's newest `Put` record, or of the
+    /// first `PutChunk` of a large value, whose `record_len` then spans the whole run. Meant for
+    /// external indexers that read values lazily; an offset is stale after the next mutation of
+    /// the key or a compaction. Append lists are left out, since their entries are many records.
+    pub fn key_offsets(&self) -> Vec<(String, usize, usize)> {
+        self.kv
+            .iter()
+            .map(|(key, slot)| (key.clone(), slot.offset, value_record_bytes(key, slot.len)))
+            .collect()
+    }
+
+    /// The value of `slot`, from memory or re-read from its journal record.
+    pub(crate) fn slot_value(&self, key: &str, slot: &ValueSlot) -> Result<Vec<u8>, StatefsError> {
+        if let Some(bytes) = slot.cached() {
+            slot.last_used.set(self.lru_tick());
+            return Ok(bytes.to_vec());
+        }
+        if slot.len > MAX_VALUE_SIZE {
+            return self.read_chunked(key, slot);
+        }
+        match self.record_at(slot.offset)? {
+            Some((record, _))
+                if record.op == JournalOpCode::Put
+                    && record.key == key
+                    && record.value.len() == slot.len =>
+            {
+                Ok(record.value)
+            }
+            _ => Err(StatefsError::Corrupted),
+        }
+    }
+
+    /// Drops least recently used large values until the cached ones fit the budget.
+    pub(crate) fn enforce_value_cache(&mut self) {
+        let Some(cache) = self.value_cache else {
+            return;
+        };
+        let mut cached = self.cached_value_bytes();
+        if cached <= cache.budget_bytes {
+            return;
+        }
+        let mut lru: Vec<(u64, String)> = self
+            .kv
+            .iter()
+            .filter(|(_, slot)| slot.cached().is_some_and(|bytes| bytes.len() > cache.evict_above))
+            .map(|(key, slot)| (slot.last_used.get(), key.clone()))
+            .collect();
+        lru.sort_unstable();
+        for (_, key) in lru {
+            if cached <= cache.budget_bytes {
+                break;
+            }
+            if let Some(slot) = self.kv.get_mut(&key) {
+                slot.bytes = None;
+                cached -= slot.len;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use storage::MemBlockDevice;
+
+    fn engine() -> JournalEngine<MemBlockDevice> {
+        let mut engine = JournalEngine::open(MemBlockDevice::new(512, 64)).unwrap();
+        engine.set_value_cache(Some(ValueCache { budget_bytes: 250, evict_above: 16 }));
+        engine
+    }
+
+    fn resident(engine: &JournalEngine<MemBlockDevice>, key: &str) -> bool {
+        engine.kv.get(key).is_some_and(|slot| slot.cached().is_some())
+    }
+
+    #[test]
+    fn least_recently_used_values_are_evicted_over_budget() {
+        let mut engine = engine();
+        engine.set_quota("/state/", 1000).unwrap();
+        engine.put("/state/a", &[1; 100]).unwrap();
+        engine.put("/state/b", &[2; 100]).unwrap();
+        engine.put("/state/small", &[3; 16]).unwrap();
+        assert_eq!(engine.cached_value_bytes(), 200);
+        // Reading `a` makes `b` the least recently used value.
+        engine.get("/state/a").unwrap();
+        engine.put("/state/c", &[4; 100]).unwrap();
+
+        assert_eq!(engine.cached_value_bytes(), 200);
+        assert!(resident(&engine, "/state/a") && resident(&engine, "/state/c"));
+        assert!(!resident(&engine, "/state/b"));
+        assert!(resident(&engine, "/state/small"));
+        // Lengths stay known without the bytes.
+        assert_eq!(engine.quota_usage("/state/"), Some((316, 1000)));
+        assert_eq!(engine.stats().live_keys, 4);
+    }
+
+    #[test]
+    fn get_reloads_evicted_values_from_the_journal() {
+        let mut engine = engine();
+        for (i, key) in ["/state/a", "/state/b", "/state/c"].into_iter().enumerate() {
+            engine.put(key, &[i as u8; 120]).unwrap();
+        }
+        engine.put("/state/a", &[9; 120]).unwrap();
+        assert!(!resident(&engine, "/state/b"));
+        assert_eq!(engine.get("/state/b").unwrap(), vec![1; 120]);
+        assert_eq!(engine.get_many(&["/state/a", "/state/c"])[0], Ok(vec![9; 120]));
+        // The reload is not cached again.
+        assert!(!resident(&engine, "/state/b"));
+
+        // Replay restores offsets; the budget applies again after `reopen`.
+        engine.reopen().unwrap();
+        assert!(engine.cached_value_bytes() <= 250);
+        for (key, byte) in [("/state/a", 9), ("/state/b", 1), ("/state/c", 2)] {
+            assert_eq!(engine.get(key).unwrap(), vec![byte; 120]);
+        }
+
+        engine.set_value_cache(Some(ValueCache { budget_bytes: 0, evict_above: 0 }));
+        assert_eq!(engine.cached_value_bytes(), 0);
+        assert_eq!(engine.get("/state/c").unwrap(), vec![2; 120]);
+    }
+
+    #[test]
+    fn compaction_moves_offsets_of_evicted_values() {
+        let mut engine = engine();
+        for round in 0..3u8 {
+            for key in ["/state/a", "/state/b", "/state/c"] {
+                engine.put(key, &[round; 120]).unwrap();
+            }
+        }
+        engine.delete("/state/a").unwrap();
+        assert!(engine.compact().unwrap() > 0);
+        assert_eq!(engine.get("/state/b").unwrap(), vec![2; 120]);
+        assert_eq!(engine.get("/state/c").unwrap(), vec![2; 120]);
+        assert_eq!(engine.get("/state/a"), Err(StatefsError::NotFound));
+    }
+
+    /// The record at each reported offset, checked against the reported key and length.
+    fn offset_records(engine: &JournalEngine<MemBlockDevice>) -> Vec<(String, usize, Vec<u8>)> {
+        engine
+            .key_offsets()
+            .into_iter()
+            .map(|(key, offset, len)| {
+                let (record, consumed) = engine.record_at(offset).unwrap().unwrap();
+                assert_eq!((record.key.as_str(), consumed), (key.as_str(), len));
+                (key, offset, record.value)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn key_offsets_point_at_parseable_records() {
+        let mut engine = engine();
+        engine.put("/state/a", &[1; 40]).unwrap();
+        engine.put("/state/b", &[2; 120]).unwrap();
+        engine.append("/state/log", b"entry").unwrap();
+        let records = offset_records(&engine);
+        let keys: Vec<&str> = records.iter().map(|(key, ..)| key.as_str()).collect();
+        assert_eq!(keys, ["/state/a", "/state/b"]);
+        assert_eq!(records[0].1, engine.journal_start());
+        assert_eq!(records[1].2, vec![2; 120]);
+
+        // Replay rebuilds the same offsets.
+        let before = engine.key_offsets();
+        engine.reopen().unwrap();
+        assert_eq!(engine.key_offsets(), before);
+    }
+
+    #[test]
+    fn key_offsets_follow_overwrites_and_compaction() {
+        let mut engine = engine();
+        engine.put("/state/a", &[1; 40]).unwrap();
+        engine.put("/state/b", &[2; 40]).unwrap();
+        let old = engine.key_offsets()[0].1;
+        engine.put("/state/a", &[3; 60]).unwrap();
+        let records = offset_records(&engine);
+        assert!(records[0].1 > old);
+        assert_eq!(records[0].2, vec![3; 60]);
+
+        engine.delete("/state/b").unwrap();
+        assert_eq!(engine.key_offsets().len(), 1);
+        engine.compact().unwrap();
+        assert_eq!(offset_records(&engine)[0].2, vec![3; 60]);
+    }
+}
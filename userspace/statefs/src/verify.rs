@@ -0,0 +1,258 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: Read-only journal integrity walk (`JournalEngine::verify`) and its `OP_VERIFY` frame
+//! OWNERS: @runtime
+//! STATUS: Functional (host-first)
+//! TEST_COVERAGE: 3 unit tests (clean journal, corrupt record mid-stream, offset == replay stop)
+//!
+//! `verify` re-reads the device and re-parses every record of the live journal, checksums
+//! included, from the journal origin up to the engine's `write_pos`. It follows a relocation
+//! checkpoint the same way replay does. In-memory state is never touched, so operators can
+//! check a `/state` image without reopening the engine. The first record that is not intact
+//! (bad magic, bad lengths, checksum mismatch, or running past `write_pos`) ends the walk, and
+//! its absolute byte offset is reported. A replay of the same device stops at that offset too.
+//!
+//! statefsd answers `OP_VERIFY` with:
+//! `[S, F, ver, OP_VERIFY|0x80, status, (nonce:u64 if v2), records_ok:u64, bytes_scanned:u64,
+//! first_bad_offset:u64]`. The three counters are present only when the status is OK, and
+//! `u64::MAX` encodes "no bad record".
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use storage::BlockDevice;
+
+use crate::compact::relocation_target;
+use crate::journal::{parse_record, JournalRecord};
+use crate::protocol::{
+    encode_status_response_with_nonce, error_from_status, MAGIC0, MAGIC1, OP_VERIFY, STATUS_OK,
+    VERSION, VERSION_V2,
+};
+use crate::{
+    JournalEngine, JournalOpCode, StatefsError, MAX_KEY_LEN, MAX_VALUE_SIZE, RECORD_HEADER_SIZE,
+};
+
+/// Header bytes through the value length (magic, opcode, key and value lengths).
+const LENGTHS_END: usize = 11;
+/// `OP_VERIFY` response body: three u64 LE counters.
+const VERIFY_BODY_LEN: usize = 24;
+
+/// Outcome of [`JournalEngine::verify`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct VerifyReport {
+    /// Records that parsed with a matching checksum, in walk order.
+    pub records_ok: usize,
+    /// Absolute device byte offset of the first damaged record, if any.
+    pub first_bad_offset: Option<usize>,
+    /// Journal bytes taken by the intact records.
+    pub bytes_scanned: usize,
+}
+
+impl<B: BlockDevice> JournalEngine<B> {
+    /// Re-read and check every record of the live journal without changing engine state.
+    ///
+    /// Device read failures return `IoError`; damaged records are reported, not returned
+    /// as errors.
+    pub fn verify(&self) -> Result<VerifyReport, StatefsError> {
+        let block_size = self.device.block_size();
+        let capacity = self.capacity();
+        let mut report = VerifyReport::default();
+        let mut pos = self.journal_start();
+        while pos < self.write_pos {
+            let Some((record, consumed)) = self.record_at(pos)? else {
+                report.first_bad_offset = Some(pos);
+                break;
+            };
+            if pos + consumed > self.write_pos {
+                report.first_bad_offset = Some(pos);
+                break;
+            }
+            report.records_ok += 1;
+            report.bytes_scanned += consumed;
+            let relocation = match record.op {
+                JournalOpCode::Checkpoint => relocation_target(&record.value),
+                _ => None,
+            };
+            pos = match relocation {
+                None => pos + consumed,
+                // Only forward, block-aligned, in-bounds jumps are honoured, as in replay.
+                Some(target) if target % block_size == 0 && target > pos && target < capacity => {
+                    target
+                }
+                Some(_) => {
+                    report.first_bad_offset = Some(pos);
+                    break;
+                }
+            };
+        }
+        Ok(report)
+    }
+
+    /// Parse the record at byte offset `pos`; `None` when it is not an intact record.
+    pub(crate) fn record_at(
+        &self,
+        pos: usize,
+    ) -> Result<Option<(JournalRecord, usize)>, StatefsError> {
+        let Some(header) = self.read_span(pos, LENGTHS_END)? else {
+            return Ok(None);
+        };
+        let key_len = u16::from_le_bytes([header[5], header[6]]) as usize;
+        let value_len = u32::from_le_bytes([header[7], header[8], header[9], header[10]]) as usize;
+        if key_len > MAX_KEY_LEN || value_len > MAX_VALUE_SIZE {
+            return Ok(None);
+        }
+        let Some(bytes) = self.read_span(pos, RECORD_HEADER_SIZE + key_len + value_len)? else {
+            return Ok(None);
+        };
+        Ok(parse_record(self.checksum, &bytes).ok().flatten())
+    }
+
+    /// Read `len` bytes at byte offset `pos`; `None` when the span runs off the device.
+    fn read_span(&self, pos: usize, len: usize) -> Result<Option<Vec<u8>>, StatefsError> {
+        let block_size = self.device.block_size();
+        let Some(end) = pos.checked_add(len).filter(|&end| end <= self.capacity()) else {
+            return Ok(None);
+        };
+        let first = pos / block_size;
+        let mut bytes = vec![0u8; (end.div_ceil(block_size) - first) * block_size];
+        for (idx, block) in bytes.chunks_mut(block_size).enumerate() {
+            self.device
+                .read_block((first + idx) as u64, block)
+                .map_err(|_| StatefsError::IoError)?;
+        }
+        let skip = pos - first * block_size;
+        bytes.drain(..skip);
+        bytes.truncate(len);
+        Ok(Some(bytes))
+    }
+}
+
+/// `OP_VERIFY` request frame.
+pub fn encode_verify_request() -> Vec<u8> {
+    vec![MAGIC0, MAGIC1, VERSION, OP_VERIFY]
+}
+
+/// `OP_VERIFY` response; the report is appended only when `status` is `STATUS_OK`.
+pub fn encode_verify_response_with_nonce(
+    status: u8,
+    report: &VerifyReport,
+    nonce: Option<u64>,
+) -> Vec<u8> {
+    let mut out = encode_status_response_with_nonce(OP_VERIFY, status, nonce);
+    if status == STATUS_OK {
+        let bad = report.first_bad_offset.map_or(u64::MAX, |offset| offset as u64);
+        for counter in [report.records_ok as u64, report.bytes_scanned as u64, bad] {
+            out.extend_from_slice(&counter.to_le_bytes());
+        }
+    }
+    out
+}
+
+/// Decode an `OP_VERIFY` response into the daemon's report.
+pub fn decode_verify_response(frame: &[u8]) -> Result<VerifyReport, StatefsError> {
+    if frame.len() < 5 || frame[0] != MAGIC0 || frame[1] != MAGIC1 {
+        return Err(StatefsError::Corrupted);
+    }
+    if frame[3] != (OP_VERIFY | 0x80) {
+        return Err(StatefsError::Corrupted);
+    }
+    let body = match frame[2] {
+        VERSION => &frame[5..],
+        VERSION_V2 if frame.len() >= 13 => &frame[13..],
+        _ => return Err(StatefsError::Corrupted),
+    };
+    if frame[4] != STATUS_OK {
+        return Err(error_from_status(frame[4]));
+    }
+    if body.len() != VERIFY_BODY_LEN {
+        return Err(StatefsError::Corrupted);
+    }
+    let counter = |idx: usize| {
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&body[idx * 8..idx * 8 + 8]);
+        usize::try_from(u64::from_le_bytes(bytes)).ok()
+    };
+    Ok(VerifyReport {
+        records_ok: counter(0).ok_or(StatefsError::Corrupted)?,
+        bytes_scanned: counter(1).ok_or(StatefsError::Corrupted)?,
+        first_bad_offset: counter(2).filter(|&offset| offset as u64 != u64::MAX),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats::record_len;
+    use alloc::format;
+    use storage::MemBlockDevice;
+
+    const BLOCK: usize = 128;
+
+    /// Ten equal-sized puts (`/state/v/0` .. `/state/v/9`).
+    fn populated() -> (JournalEngine<MemBlockDevice>, usize) {
+        let mut engine = JournalEngine::open(MemBlockDevice::new(BLOCK, 64)).unwrap();
+        for i in 0..10 {
+            engine.put(&format!("/state/v/{i}"), &[i as u8; 20]).unwrap();
+        }
+        (engine, record_len("/state/v/0", 20))
+    }
+
+    #[test]
+    fn clean_journal_verifies_every_record() {
+        let (mut engine, len) = populated();
+        let report = engine.verify().unwrap();
+        assert_eq!(
+            report,
+            VerifyReport { records_ok: 10, first_bad_offset: None, bytes_scanned: 10 * len }
+        );
+        assert_eq!(report.bytes_scanned, engine.write_pos - BLOCK);
+
+        // After a relocating compaction the walk follows the relocation record.
+        engine.delete("/state/v/0").unwrap();
+        engine.compact().unwrap();
+        let (pos, keys) = (engine.write_pos, engine.len());
+        let report = engine.verify().unwrap();
+        assert_eq!((report.records_ok, report.first_bad_offset), (1 + 9, None));
+        assert_eq!((engine.write_pos, engine.len()), (pos, keys));
+
+        let frame = encode_verify_response_with_nonce(STATUS_OK, &report, Some(3));
+        assert_eq!(decode_verify_response(&frame), Ok(report));
+    }
+
+    #[test]
+    fn test_reject_corrupt_record_mid_stream() {
+        let (mut engine, len) = populated();
+        let bad = BLOCK + 4 * len;
+        let byte = bad + RECORD_HEADER_SIZE;
+        engine.device.raw_storage_mut()[byte / BLOCK][byte % BLOCK] ^= 0xFF;
+
+        let report = engine.verify().unwrap();
+        assert_eq!(
+            report,
+            VerifyReport { records_ok: 4, first_bad_offset: Some(bad), bytes_scanned: 4 * len }
+        );
+        // Nothing in memory changed: the damaged value is still served from the map.
+        assert_eq!(engine.get("/state/v/4").unwrap(), [4u8; 20]);
+        assert_eq!(engine.len(), 10);
+
+        let denied =
+            encode_verify_response_with_nonce(crate::protocol::STATUS_ACCESS_DENIED, &report, None);
+        assert_eq!(decode_verify_response(&denied), Err(StatefsError::AccessDenied));
+    }
+
+    #[test]
+    fn reported_offset_is_where_replay_stops() {
+        let (mut engine, len) = populated();
+        // Break the magic of record 7: replay ends the journal there.
+        let bad = BLOCK + 7 * len;
+        engine.device.raw_storage_mut()[bad / BLOCK][bad % BLOCK] ^= 0x01;
+        let report = engine.verify().unwrap();
+        assert_eq!(report.first_bad_offset, Some(bad));
+        assert_eq!(report.records_ok, 7);
+
+        let replayed = JournalEngine::open(engine.device).unwrap();
+        assert_eq!(replayed.write_pos, bad);
+        assert_eq!(replayed.len(), 7);
+    }
+}
@@ -0,0 +1,219 @@
+// Copyright 2026 Open Nexus OS Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! CONTEXT: Optimistic per-key concurrency (`JournalEngine::get_versioned` /
+//! `put_versioned`, `OP_PUT_VERSIONED`)
+//! OWNERS: @runtime
+//! STATUS: Functional (host-first)
+//! TEST_COVERAGE: 3 unit tests (versioned write, conflicting write, generation per write)
+//!
+//! Two writers that read a key, change it and write it back can lose each other's update.
+//! `get_versioned` returns the value together with the key's *generation*, and
+//! `put_versioned` only writes if the generation is still the one the caller read,
+//! failing with [`StatefsError::Conflict`] otherwise. The caller re-reads and retries.
+//!
+//! The generation is the key's stamp in the change sequence (see `changes`): every write to
+//! the key stamps it higher, and a deleted key that is written again never gets an old
+//! stamp back. An absent key has generation 0, so `put_versioned(key, value, 0)` creates
+//! the key only if nobody else did. Like the stamps, generations live in memory: `reopen`
+//! and a tombstone overflow renumber them, and writers holding an older generation get
+//! `Conflict` and re-read.
+//!
+//! Request: `[S, F, ver, OP_PUT_VERSIONED, expected_gen:u64, key_len:u16, val_len:u32, key,
+//! value]` (the `OP_PUT` payload without the flag byte, after `expected_gen`). Response: the
+//! status frame, then `generation:u64` (the key's new generation) when the status is
+//! `STATUS_OK`.
+
+use alloc::vec::Vec;
+
+use storage::BlockDevice;
+
+use crate::protocol::{
+    self, encode_status_response_with_nonce, error_from_status, Request, MAGIC0, MAGIC1,
+    OP_PUT_VERSIONED, STATUS_MALFORMED, STATUS_OK, VERSION, VERSION_V2,
+};
+use crate::{JournalEngine, StatefsError, LOCAL_REQUESTER};
+
+impl<B: BlockDevice> JournalEngine<B> {
+    /// The value under `key` and its generation; `None` if the key cannot be read.
+    pub fn get_versioned(&self, key: &str) -> Option<(Vec<u8>, u64)> {
+        self.get_versioned_as(LOCAL_REQUESTER, key)
+    }
+
+    /// [`JournalEngine::get_versioned`] on behalf of `requester_id` (see `AccessPolicy`).
+    pub fn get_versioned_as(&self, requester_id: u64, key: &str) -> Option<(Vec<u8>, u64)> {
+        let value = self.get_as(requester_id, key).ok()?;
+        Some((value, self.generation(key)))
+    }
+
+    /// Put `value` under `key` if its generation is still `expected_gen` (0: key absent).
+    ///
+    /// Returns the key's new generation. The usual put checks run first, so a caller
+    /// without access learns nothing about the generation.
+    pub fn put_versioned(
+        &mut self,
+        key: &str,
+        value: &[u8],
+        expected_gen: u64,
+    ) -> Result<u64, StatefsError> {
+        self.put_versioned_as(LOCAL_REQUESTER, key, value, expected_gen)
+    }
+
+    /// [`JournalEngine::put_versioned`] on behalf of `requester_id` (see `AccessPolicy`).
+    pub fn put_versioned_as(
+        &mut self,
+        requester_id: u64,
+        key: &str,
+        value: &[u8],
+        expected_gen: u64,
+    ) -> Result<u64, StatefsError> {
+        self.check_put(requester_id, key, value)?;
+        if self.generation(key) != expected_gen {
+            return Err(StatefsError::Conflict);
+        }
+        self.put_with(requester_id, key, value, self.durability)?;
+        Ok(self.generation(key))
+    }
+
+    /// The key's current generation; 0 for a key that holds no value.
+    fn generation(&self, key: &str) -> u64 {
+        if !self.kv.contains_key(key) {
+            return 0;
+        }
+        self.changes.stamp(key).unwrap_or(0)
+    }
+}
+
+/// `OP_PUT_VERSIONED` request frame.
+pub fn encode_put_versioned_request(
+    key: &str,
+    value: &[u8],
+    expected_gen: u64,
+) -> Result<Vec<u8>, StatefsError> {
+    let mut frame = protocol::encode_put_request(key, value)?;
+    frame[3] = OP_PUT_VERSIONED;
+    frame.splice(4..4, expected_gen.to_le_bytes());
+    Ok(frame)
+}
+
+pub(crate) fn decode_put_versioned_payload(payload: &[u8]) -> Result<Request<'_>, u8> {
+    let (expected_gen, put) = payload.split_first_chunk::<8>().ok_or(STATUS_MALFORMED)?;
+    match protocol::decode_put_payload(put)? {
+        Request::Put { key, value, durable: false } => Ok(Request::PutVersioned {
+            key,
+            value,
+            expected_gen: u64::from_le_bytes(*expected_gen),
+        }),
+        _ => Err(STATUS_MALFORMED),
+    }
+}
+
+/// `OP_PUT_VERSIONED` response; `generation` is appended only when `status` is `STATUS_OK`.
+pub fn encode_put_versioned_response_with_nonce(
+    status: u8,
+    generation: u64,
+    nonce: Option<u64>,
+) -> Vec<u8> {
+    let mut out = encode_status_response_with_nonce(OP_PUT_VERSIONED, status, nonce);
+    if status == STATUS_OK {
+        out.extend_from_slice(&generation.to_le_bytes());
+    }
+    out
+}
+
+/// Decode an `OP_PUT_VERSIONED` response into the key's new generation.
+pub fn decode_put_versioned_response(frame: &[u8]) -> Result<u64, StatefsError> {
+    if frame.len() < 5 || frame[0] != MAGIC0 || frame[1] != MAGIC1 {
+        return Err(StatefsError::Corrupted);
+    }
+    if frame[3] != (OP_PUT_VERSIONED | 0x80) {
+        return Err(StatefsError::Corrupted);
+    }
+    let body = match frame[2] {
+        VERSION => &frame[5..],
+        VERSION_V2 if frame.len() >= 13 => &frame[13..],
+        _ => return Err(StatefsError::Corrupted),
+    };
+    if frame[4] != STATUS_OK {
+        return Err(error_from_status(frame[4]));
+    }
+    let generation: [u8; 8] = body.try_into().map_err(|_| StatefsError::Corrupted)?;
+    Ok(u64::from_le_bytes(generation))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use storage::MemBlockDevice;
+
+    fn engine() -> JournalEngine<MemBlockDevice> {
+        JournalEngine::open(MemBlockDevice::new(512, 64)).unwrap()
+    }
+
+    #[test]
+    fn versioned_write_succeeds_on_the_read_generation() {
+        let mut engine = engine();
+        assert_eq!(engine.get_versioned("/state/app/cfg"), None);
+        let created = engine.put_versioned("/state/app/cfg", b"v1", 0).unwrap();
+        assert_eq!(engine.get_versioned("/state/app/cfg"), Some((b"v1".to_vec(), created)));
+
+        let updated = engine.put_versioned("/state/app/cfg", b"v2", created).unwrap();
+        assert_eq!(engine.get("/state/app/cfg").unwrap(), b"v2");
+        assert_eq!(engine.get_versioned("/state/app/cfg"), Some((b"v2".to_vec(), updated)));
+
+        let frame = encode_put_versioned_request("/state/app/cfg", b"v3", updated).unwrap();
+        assert_eq!(
+            protocol::decode_request(&frame),
+            Ok(Request::PutVersioned {
+                key: "/state/app/cfg",
+                value: b"v3",
+                expected_gen: updated
+            })
+        );
+        let rsp = encode_put_versioned_response_with_nonce(STATUS_OK, updated, Some(7));
+        assert_eq!(decode_put_versioned_response(&rsp), Ok(updated));
+    }
+
+    #[test]
+    fn test_reject_conflicting_versioned_write() {
+        let mut engine = engine();
+        let first = engine.put_versioned("/state/app/cfg", b"a", 0).unwrap();
+        // A second writer read the same generation and got in first.
+        engine.put_versioned("/state/app/cfg", b"b", first).unwrap();
+        assert_eq!(
+            engine.put_versioned("/state/app/cfg", b"c", first),
+            Err(StatefsError::Conflict)
+        );
+        assert_eq!(engine.put_versioned("/state/app/cfg", b"c", 0), Err(StatefsError::Conflict));
+        assert_eq!(engine.get("/state/app/cfg").unwrap(), b"b");
+
+        // A plain put moves the generation too.
+        let (_, seen) = engine.get_versioned("/state/app/cfg").unwrap();
+        engine.put("/state/app/cfg", b"d").unwrap();
+        assert_eq!(engine.put_versioned("/state/app/cfg", b"e", seen), Err(StatefsError::Conflict));
+
+        let rsp = encode_put_versioned_response_with_nonce(protocol::STATUS_CONFLICT, 0, None);
+        assert_eq!(decode_put_versioned_response(&rsp), Err(StatefsError::Conflict));
+        let frame = encode_put_versioned_request("/state/app/cfg", b"x", 1).unwrap();
+        assert_eq!(protocol::decode_request(&frame[..11]), Err(STATUS_MALFORMED));
+        let mut durable = frame.clone();
+        durable.push(protocol::PUT_FLAG_DURABLE);
+        assert_eq!(protocol::decode_request(&durable), Err(STATUS_MALFORMED));
+    }
+
+    #[test]
+    fn generation_increments_on_each_write() {
+        let mut engine = engine();
+        let mut last = 0;
+        for value in [&b"1"[..], b"2", b"3"] {
+            let generation = engine.put_versioned("/state/app/n", value, last).unwrap();
+            assert!(generation > last);
+            last = generation;
+        }
+        // Deleting and recreating never hands out an old generation again.
+        engine.delete("/state/app/n").unwrap();
+        assert_eq!(engine.get_versioned("/state/app/n"), None);
+        assert_eq!(engine.put_versioned("/state/app/n", b"4", last), Err(StatefsError::Conflict));
+        assert!(engine.put_versioned("/state/app/n", b"4", 0).unwrap() > last);
+    }
+}